@@ -0,0 +1,75 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::error::IntoAnyError;
+
+/// Identifier of a compression algorithm applied to application messages.
+#[derive(
+    Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, MlsSize, MlsEncode, MlsDecode,
+)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+pub struct CompressionAlgorithm(u16);
+
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen)]
+impl CompressionAlgorithm {
+    /// Raw DEFLATE stream as defined by
+    /// [RFC 1951](https://www.rfc-editor.org/rfc/rfc1951.html).
+    pub const DEFLATE: CompressionAlgorithm = CompressionAlgorithm(1);
+    /// Zstandard frame as defined by
+    /// [RFC 8878](https://www.rfc-editor.org/rfc/rfc8878.html).
+    pub const ZSTD: CompressionAlgorithm = CompressionAlgorithm(2);
+
+    /// Compression algorithm from a raw value.
+    pub const fn new(raw_value: u16) -> Self {
+        CompressionAlgorithm(raw_value)
+    }
+
+    /// Raw numerical wrapped value.
+    pub const fn raw_value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for CompressionAlgorithm {
+    fn from(value: u16) -> Self {
+        CompressionAlgorithm(value)
+    }
+}
+
+/// Compression of application messages before they are encrypted.
+///
+/// Compression is used in groups whose context contains the application
+/// compression extension with the [`algorithm`](MessageCompressor::algorithm)
+/// of the compressor. Every member of such a group must be configured with a
+/// compressor for the same algorithm.
+pub trait MessageCompressor: Send + Sync {
+    /// Error type that the compressor returns on internal failure.
+    type Error: IntoAnyError;
+
+    /// Algorithm implemented by this compressor, or `None` if application
+    /// messages are not compressed.
+    fn algorithm(&self) -> Option<CompressionAlgorithm>;
+
+    /// Compress `data`.
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+    /// Decompress `data`, which is expected to decompress to exactly
+    /// `decompressed_len` bytes.
+    ///
+    /// Implementations must not produce more than `decompressed_len` bytes,
+    /// in order to protect receivers against decompression bombs.
+    fn decompress(&self, data: &[u8], decompressed_len: usize) -> Result<Vec<u8>, Self::Error>;
+
+    /// Maximum size of a decompressed application message. Messages
+    /// announcing a larger size are rejected before decompression.
+    fn max_decompressed_size(&self) -> usize {
+        16 * 1024 * 1024
+    }
+}
@@ -2,7 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::error::IntoAnyError;
+use crate::{error::IntoAnyError, secret::SecretBytes};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::{
@@ -13,7 +13,11 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 mod cipher_suite;
+mod epoch_secrets_vault;
+mod hardware_keystore;
 pub use self::cipher_suite::*;
+pub use self::epoch_secrets_vault::*;
+pub use self::hardware_keystore::*;
 
 #[cfg(feature = "test_suite")]
 pub mod test_suite;
@@ -97,11 +101,7 @@ impl AsRef<[u8]> for HpkePublicKey {
     safer_ffi_gen::ffi_type(clone, opaque)
 )]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct HpkeSecretKey(
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "crate::vec_serde"))]
-    Vec<u8>,
-);
+pub struct HpkeSecretKey(SecretBytes);
 
 impl Debug for HpkeSecretKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -113,7 +113,7 @@ impl Debug for HpkeSecretKey {
 
 impl From<Vec<u8>> for HpkeSecretKey {
     fn from(data: Vec<u8>) -> Self {
-        Self(data)
+        Self(data.into())
     }
 }
 
@@ -243,9 +243,7 @@ impl From<SignaturePublicKey> for Vec<u8> {
 #[derive(Clone, PartialEq, Eq, ZeroizeOnDrop, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SignatureSecretKey {
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "crate::vec_serde"))]
-    bytes: Vec<u8>,
+    bytes: SecretBytes,
 }
 
 impl Debug for SignatureSecretKey {
@@ -264,7 +262,7 @@ impl SignatureSecretKey {
 
     pub fn new_slice(data: &[u8]) -> Self {
         Self {
-            bytes: data.to_vec(),
+            bytes: data.to_vec().into(),
         }
     }
 
@@ -275,7 +273,9 @@ impl SignatureSecretKey {
 
 impl From<Vec<u8>> for SignatureSecretKey {
     fn from(bytes: Vec<u8>) -> Self {
-        Self { bytes }
+        Self {
+            bytes: bytes.into(),
+        }
     }
 }
 
@@ -0,0 +1,181 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::fmt::{self, Debug};
+
+use crate::{
+    error::{AnyError, IntoAnyError},
+    secret::SecretBytes,
+};
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use zeroize::Zeroizing;
+
+use super::CipherSuiteProvider;
+
+/// Opaque reference to a secret held by an [`EpochSecretsVault`].
+///
+/// Handles are stored with the group state in place of the secrets they
+/// refer to. The content of a handle is defined by the vault that created
+/// it.
+#[derive(Clone, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecretHandle(SecretBytes);
+
+impl Debug for SecretHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::debug::pretty_bytes(&self.0)
+            .named("SecretHandle")
+            .fmt(f)
+    }
+}
+
+impl SecretHandle {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes.into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SecretHandle {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<Zeroizing<Vec<u8>>> for SecretHandle {
+    fn from(bytes: Zeroizing<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+}
+
+/// Holder of the secrets of the key schedule of a group.
+///
+/// The key schedule derives the secrets of each epoch through the vault,
+/// referring to them with [`SecretHandle`]s. This allows deployments that
+/// require epoch secrets to reside in an HSM to keep the init, exporter,
+/// external and membership secrets of every epoch out of the group state.
+/// Secrets that are used directly by the protocol, such as the encryption
+/// secret and the confirmation key, are exported from the vault when they
+/// are derived.
+///
+/// `extract` and `expand` are the `KDF.Extract` and `KDF.Expand` functions
+/// of the cipher suite of `cipher_suite_provider`, which may be ignored by
+/// vaults performing the derivation themselves.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait EpochSecretsVault: Send + Sync {
+    /// Error type that the vault returns on internal failure.
+    type Error: IntoAnyError;
+
+    /// Move `secret` into the vault.
+    async fn import<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        secret: &[u8],
+    ) -> Result<SecretHandle, Self::Error>;
+
+    /// Generate a random secret of `len` bytes inside the vault.
+    async fn generate<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        len: usize,
+    ) -> Result<SecretHandle, Self::Error>;
+
+    /// Extract a secret from the secret referred to by `salt` and `ikm`.
+    async fn extract<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        salt: &SecretHandle,
+        ikm: &[u8],
+    ) -> Result<SecretHandle, Self::Error>;
+
+    /// Expand the secret referred to by `prk` into a secret of `len` bytes.
+    async fn expand<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        prk: &SecretHandle,
+        info: &[u8],
+        len: usize,
+    ) -> Result<SecretHandle, Self::Error>;
+
+    /// Raw value of the secret referred to by `handle`.
+    async fn export(&self, handle: &SecretHandle) -> Result<Zeroizing<Vec<u8>>, Self::Error>;
+
+    /// Release a secret that is no longer needed. Secrets of an epoch are
+    /// released once the state of a later epoch is written to storage, or
+    /// when the group is discarded. Prior epochs don't refer to any handle.
+    async fn release(&self, _handle: SecretHandle) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// [`EpochSecretsVault`] keeping secrets in memory, where a handle is the
+/// secret itself.
+///
+/// This is the vault used by default, with which group states are stored
+/// in the same format as without a vault.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultEpochSecretsVault;
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl EpochSecretsVault for DefaultEpochSecretsVault {
+    type Error = AnyError;
+
+    async fn import<P: CipherSuiteProvider>(
+        &self,
+        _cipher_suite_provider: &P,
+        secret: &[u8],
+    ) -> Result<SecretHandle, Self::Error> {
+        Ok(SecretHandle::new(secret.to_vec()))
+    }
+
+    async fn generate<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        len: usize,
+    ) -> Result<SecretHandle, Self::Error> {
+        cipher_suite_provider
+            .random_bytes_vec(len)
+            .map(SecretHandle::new)
+            .map_err(|e| e.into_any_error())
+    }
+
+    async fn extract<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        salt: &SecretHandle,
+        ikm: &[u8],
+    ) -> Result<SecretHandle, Self::Error> {
+        cipher_suite_provider
+            .kdf_extract(salt.as_bytes(), ikm)
+            .await
+            .map(SecretHandle::from)
+            .map_err(|e| e.into_any_error())
+    }
+
+    async fn expand<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        prk: &SecretHandle,
+        info: &[u8],
+        len: usize,
+    ) -> Result<SecretHandle, Self::Error> {
+        cipher_suite_provider
+            .kdf_expand(prk.as_bytes(), info, len)
+            .await
+            .map(SecretHandle::from)
+            .map_err(|e| e.into_any_error())
+    }
+
+    async fn export(&self, handle: &SecretHandle) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        Ok(Zeroizing::new(handle.as_bytes().to_vec()))
+    }
+}
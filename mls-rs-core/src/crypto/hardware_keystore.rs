@@ -0,0 +1,428 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+use crate::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkePublicKey,
+        HpkeSecretKey, SignaturePublicKey, SignatureSecretKey,
+    },
+    error::{AnyError, IntoAnyError},
+    identity::{KeychainStorage, SigningIdentity},
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+/// Callbacks to a platform keystore holding signature private keys, such as
+/// the Android Keystore or the iOS Secure Enclave.
+///
+/// Keys are referred to by handles, which are the bytes of the
+/// [`SignatureSecretKey`]s used by the client. The content of a handle is
+/// defined by the keystore, for example the alias of a key in the Android
+/// Keystore. Signatures are computed by the keystore, so private keys never
+/// enter the memory of the process.
+///
+/// A keystore is used through two adapters:
+///
+/// * [`HardwareKeystoreCryptoProvider`] wraps a [`CryptoProvider`] so that
+///   signature key generation and signing go through the keystore.
+/// * [`HardwareKeychainStorage`] wraps a [`KeychainStorage`] storing handles
+///   in place of private keys, and deletes replaced keys from the keystore.
+///
+/// Both adapters treat every signature secret key as a handle, so a client
+/// using them can't use software signature keys.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait HardwareKeystore: Send + Sync {
+    /// Error type that the keystore returns on internal failure.
+    type Error: IntoAnyError;
+
+    /// Generate a key pair for the signature scheme of `cipher_suite` inside
+    /// the keystore, returning the handle of the private key and the public
+    /// key.
+    async fn generate_key(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<(Vec<u8>, SignaturePublicKey), Self::Error>;
+
+    /// Public key of the key pair referred to by `handle`.
+    async fn public_key(&self, handle: &[u8]) -> Result<SignaturePublicKey, Self::Error>;
+
+    /// Sign `data` with the private key referred to by `handle`, using the
+    /// signature scheme of `cipher_suite`. `data` is not hashed beforehand.
+    async fn sign(
+        &self,
+        cipher_suite: CipherSuite,
+        handle: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error>;
+
+    /// Delete the key pair referred to by `handle` from the keystore.
+    async fn delete_key(&self, handle: &[u8]) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum HardwareKeystoreError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    KeystoreError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    CryptoProviderError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    KeychainStorageError(AnyError),
+    #[cfg_attr(
+        feature = "std",
+        error("signing identity does not match the public key of the keystore handle")
+    )]
+    PublicKeyMismatch,
+}
+
+impl IntoAnyError for HardwareKeystoreError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+fn keystore_error<E: IntoAnyError>(e: E) -> HardwareKeystoreError {
+    HardwareKeystoreError::KeystoreError(e.into_any_error())
+}
+
+fn crypto_error<E: IntoAnyError>(e: E) -> HardwareKeystoreError {
+    HardwareKeystoreError::CryptoProviderError(e.into_any_error())
+}
+
+/// [`CryptoProvider`] signing with the keys of a [`HardwareKeystore`] and
+/// delegating every other operation to the wrapped provider.
+#[derive(Clone, Debug)]
+pub struct HardwareKeystoreCryptoProvider<C, K> {
+    crypto_provider: C,
+    keystore: K,
+}
+
+impl<C, K> HardwareKeystoreCryptoProvider<C, K> {
+    pub fn new(crypto_provider: C, keystore: K) -> Self {
+        Self {
+            crypto_provider,
+            keystore,
+        }
+    }
+}
+
+impl<C, K> CryptoProvider for HardwareKeystoreCryptoProvider<C, K>
+where
+    C: CryptoProvider,
+    K: HardwareKeystore + Clone,
+{
+    type CipherSuiteProvider = HardwareKeystoreCipherSuiteProvider<C::CipherSuiteProvider, K>;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        self.crypto_provider.supported_cipher_suites()
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        self.crypto_provider
+            .cipher_suite_provider(cipher_suite)
+            .map(|provider| HardwareKeystoreCipherSuiteProvider {
+                provider,
+                keystore: self.keystore.clone(),
+            })
+    }
+}
+
+/// [`CipherSuiteProvider`] of a [`HardwareKeystoreCryptoProvider`].
+///
+/// Signature secret keys generated by this provider are keystore handles,
+/// and the secret keys passed to it are expected to be handles.
+#[derive(Clone, Debug)]
+pub struct HardwareKeystoreCipherSuiteProvider<P, K> {
+    provider: P,
+    keystore: K,
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<P, K> CipherSuiteProvider for HardwareKeystoreCipherSuiteProvider<P, K>
+where
+    P: CipherSuiteProvider,
+    K: HardwareKeystore,
+{
+    type Error = HardwareKeystoreError;
+    type HpkeContextS = P::HpkeContextS;
+    type HpkeContextR = P::HpkeContextR;
+
+    fn cipher_suite(&self) -> CipherSuite {
+        self.provider.cipher_suite()
+    }
+
+    async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.provider.hash(data).await.map_err(crypto_error)
+    }
+
+    async fn mac(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.provider.mac(key, data).await.map_err(crypto_error)
+    }
+
+    async fn aead_seal(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.provider
+            .aead_seal(key, data, aad, nonce)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn aead_open(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.provider
+            .aead_open(key, ciphertext, aad, nonce)
+            .await
+            .map_err(crypto_error)
+    }
+
+    fn aead_key_size(&self) -> usize {
+        self.provider.aead_key_size()
+    }
+
+    fn aead_nonce_size(&self) -> usize {
+        self.provider.aead_nonce_size()
+    }
+
+    async fn kdf_extract(
+        &self,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.provider
+            .kdf_extract(salt, ikm)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn kdf_expand(
+        &self,
+        prk: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.provider
+            .kdf_expand(prk, info, len)
+            .await
+            .map_err(crypto_error)
+    }
+
+    fn kdf_extract_size(&self) -> usize {
+        self.provider.kdf_extract_size()
+    }
+
+    async fn hpke_seal(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+        pt: &[u8],
+    ) -> Result<HpkeCiphertext, Self::Error> {
+        self.provider
+            .hpke_seal(remote_key, info, aad, pt)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn hpke_open(
+        &self,
+        ciphertext: &HpkeCiphertext,
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.provider
+            .hpke_open(ciphertext, local_secret, local_public, info, aad)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn hpke_setup_s(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<(Vec<u8>, Self::HpkeContextS), Self::Error> {
+        self.provider
+            .hpke_setup_s(remote_key, info)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn hpke_setup_r(
+        &self,
+        kem_output: &[u8],
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<Self::HpkeContextR, Self::Error> {
+        self.provider
+            .hpke_setup_r(kem_output, local_secret, local_public, info)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn kem_derive(&self, ikm: &[u8]) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        self.provider.kem_derive(ikm).await.map_err(crypto_error)
+    }
+
+    async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        self.provider.kem_generate().await.map_err(crypto_error)
+    }
+
+    fn kem_public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+        self.provider
+            .kem_public_key_validate(key)
+            .map_err(crypto_error)
+    }
+
+    fn random_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.provider.random_bytes(out).map_err(crypto_error)
+    }
+
+    fn random_bytes_vec(&self, count: usize) -> Result<Vec<u8>, Self::Error> {
+        self.provider.random_bytes_vec(count).map_err(crypto_error)
+    }
+
+    async fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), Self::Error> {
+        let (handle, public_key) = self
+            .keystore
+            .generate_key(self.cipher_suite())
+            .await
+            .map_err(keystore_error)?;
+
+        Ok((SignatureSecretKey::new(handle), public_key))
+    }
+
+    async fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, Self::Error> {
+        self.keystore
+            .public_key(secret_key.as_bytes())
+            .await
+            .map_err(keystore_error)
+    }
+
+    async fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.keystore
+            .sign(self.cipher_suite(), secret_key.as_bytes(), data)
+            .await
+            .map_err(keystore_error)
+    }
+
+    async fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.provider
+            .verify(public_key, signature, data)
+            .await
+            .map_err(crypto_error)
+    }
+}
+
+/// [`KeychainStorage`] storing keystore handles in place of signature
+/// private keys.
+///
+/// Before a signing identity is stored, its public key is checked against
+/// the public key of the handle in the keystore, which rejects software keys
+/// and handles of other key pairs. When the identity stored for a cipher
+/// suite is replaced, the key of the replaced identity is deleted from the
+/// keystore.
+#[derive(Clone, Debug)]
+pub struct HardwareKeychainStorage<S, K> {
+    storage: S,
+    keystore: K,
+}
+
+impl<S, K> HardwareKeychainStorage<S, K> {
+    pub fn new(storage: S, keystore: K) -> Self {
+        Self { storage, keystore }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<S, K> KeychainStorage for HardwareKeychainStorage<S, K>
+where
+    S: KeychainStorage,
+    K: HardwareKeystore,
+{
+    type Error = HardwareKeystoreError;
+
+    async fn signing_identity(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, Self::Error> {
+        self.storage
+            .signing_identity(cipher_suite)
+            .await
+            .map_err(|e| HardwareKeystoreError::KeychainStorageError(e.into_any_error()))
+    }
+
+    async fn insert(
+        &mut self,
+        cipher_suite: CipherSuite,
+        signing_identity: SigningIdentity,
+        signer: SignatureSecretKey,
+    ) -> Result<(), Self::Error> {
+        let public_key = self
+            .keystore
+            .public_key(signer.as_bytes())
+            .await
+            .map_err(keystore_error)?;
+
+        if public_key != signing_identity.signature_key {
+            return Err(HardwareKeystoreError::PublicKeyMismatch);
+        }
+
+        let replaced = self.signing_identity(cipher_suite).await?;
+
+        self.storage
+            .insert(cipher_suite, signing_identity, signer.clone())
+            .await
+            .map_err(|e| HardwareKeystoreError::KeychainStorageError(e.into_any_error()))?;
+
+        match replaced {
+            Some((_, replaced)) if replaced != signer => self
+                .keystore
+                .delete_key(replaced.as_bytes())
+                .await
+                .map_err(keystore_error),
+            _ => Ok(()),
+        }
+    }
+}
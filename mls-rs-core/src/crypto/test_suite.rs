@@ -2,13 +2,32 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+//! Conformance tests for [`CryptoProvider`] and [`CipherSuiteProvider`]
+//! implementations.
+//!
+//! Custom providers, for example backed by an HSM, can run these tests to
+//! check interoperability with other implementations before being used in
+//! production groups. [`verify_tests`] checks known answers for hashing,
+//! HKDF, MAC, AEAD, HPKE and signatures generated with other providers, and
+//! checks that invalid AEAD ciphertexts and signatures are rejected.
+//! [`verify_hpke_rfc_tests`] checks HPKE decryption and export against the
+//! test vectors of RFC 9180.
+//!
+//! The test vectors are not included in the published crate. Crates outside
+//! of the mls-rs repository can copy `test_data/crypto_provider.json` and
+//! `test_data/test_hpke.json` from the repository and use
+//! [`verify_tests_with_data`] and [`verify_hpke_rfc_tests_with_data`].
+//!
+//! All functions panic if the provider does not conform.
+
 use alloc::vec::Vec;
 use itertools::Itertools;
 
 use crate::crypto::HpkeContextR;
 
 use super::{
-    CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkeContextS, HpkePublicKey, HpkeSecretKey,
+    CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkeContextS, HpkePublicKey,
+    HpkeSecretKey, SignaturePublicKey,
 };
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "std"))]
@@ -24,7 +43,8 @@ const SERIALIZED_TEST_SUITES: &[u8] = include_bytes!(concat!(
 ));
 
 pub use hpke_rfc_conformance::{
-    verify_hpke_context_tests, verify_hpke_encap_tests, EncapOutput, TestHpke,
+    verify_hpke_context_tests, verify_hpke_encap_tests, verify_hpke_rfc_tests,
+    verify_hpke_rfc_tests_with_data, EncapOutput, TestHpke,
 };
 
 pub const DATA_SIZES: [usize; 5] = [0, 1, 16, 123, 2000];
@@ -84,14 +104,31 @@ fn create_or_load_tests<C: CryptoProvider>(crypto: &C) -> Vec<TestSuite> {
     }
 }
 
+/// Run the conformance tests with the test vectors of the mls-rs repository.
+///
+/// `signature_secret_key_compatible` indicates if `crypto` uses the same
+/// signature secret key format as other providers, in which case secret keys
+/// from the test vectors are also used to sign.
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub async fn verify_tests<C: CryptoProvider>(crypto: &C, signature_secret_key_compatible: bool) {
     #[cfg(any(target_arch = "wasm32", not(feature = "std")))]
-    let test_suites: Vec<TestSuite> = serde_json::from_slice(SERIALIZED_TEST_SUITES).unwrap();
+    let test_data = SERIALIZED_TEST_SUITES;
 
     #[cfg(all(not(target_arch = "wasm32"), feature = "std"))]
-    let test_suites: Vec<TestSuite> =
-        serde_json::from_slice(&std::fs::read(PATH).unwrap()).unwrap();
+    let test_data = &std::fs::read(PATH).unwrap();
+
+    verify_tests_with_data(crypto, test_data, signature_secret_key_compatible).await
+}
+
+/// Run the conformance tests with test vectors in the format of
+/// `test_data/crypto_provider.json`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn verify_tests_with_data<C: CryptoProvider>(
+    crypto: &C,
+    test_data: &[u8],
+    signature_secret_key_compatible: bool,
+) {
+    let test_suites: Vec<TestSuite> = serde_json::from_slice(test_data).unwrap();
 
     for test_suite in test_suites {
         let test_cs = test_suite.cipher_suite.into();
@@ -104,6 +141,7 @@ pub async fn verify_tests<C: CryptoProvider>(crypto: &C, signature_secret_key_co
 
         verify_hkdf_tests(&cs, test_suite.hkdf_tests).await;
         verify_aead_tests(&cs, test_suite.aead_tests).await;
+        verify_aead_edge_cases(&cs).await;
         verify_mac_tests(&cs, test_suite.mac_tests).await;
         verify_hpke_tests(&cs, test_suite.hpke_tests).await;
 
@@ -114,6 +152,7 @@ pub async fn verify_tests<C: CryptoProvider>(crypto: &C, signature_secret_key_co
         )
         .await;
 
+        verify_invalid_signatures(&cs).await;
         verify_hash_tests(&cs, test_suite.hash_tests).await;
     }
 }
@@ -163,6 +202,33 @@ async fn verify_signature_tests<C: CipherSuiteProvider>(
     }
 }
 
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn verify_invalid_signatures<C: CipherSuiteProvider>(cs: &C) {
+    let data = cs.random_bytes_vec(DATA_SIZES[2]).unwrap();
+    let (secret, public) = cs.signature_key_generate().await.unwrap();
+    let (_, other_public) = cs.signature_key_generate().await.unwrap();
+    let signature = cs.sign(&secret, &data).await.unwrap();
+
+    let mut modified_signature = signature.clone();
+    modified_signature[0] ^= 1;
+
+    let mut modified_data = data.clone();
+    modified_data[0] ^= 1;
+
+    let invalid_verifies: [(&SignaturePublicKey, &[u8], &[u8]); 5] = [
+        (&public, &modified_signature, &data),
+        (&public, &signature[..signature.len() - 1], &data),
+        (&public, &[], &data),
+        (&public, &signature, &modified_data),
+        (&other_public, &signature, &data),
+    ];
+
+    for (public, signature, data) in invalid_verifies {
+        let res = cs.verify(public, signature, data).await;
+        assert!(res.is_err());
+    }
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 async fn generate_signature_tests<C: CipherSuiteProvider>(cs: &C) -> Vec<SignatureTestCase> {
@@ -218,6 +284,63 @@ async fn verify_aead_tests<C: CipherSuiteProvider>(cs: &C, test_cases: Vec<AeadT
     }
 }
 
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn verify_aead_edge_cases<C: CipherSuiteProvider>(cs: &C) {
+    let key = cs.random_bytes_vec(cs.aead_key_size()).unwrap();
+    let nonce = cs.random_bytes_vec(cs.aead_nonce_size()).unwrap();
+    let aad = cs.random_bytes_vec(DATA_SIZES[2]).unwrap();
+    let pt = cs.random_bytes_vec(DATA_SIZES[3]).unwrap();
+
+    // Empty plaintexts may be rejected, but must be authenticated otherwise
+    if let Ok(ct) = cs.aead_seal(&key, &[], None, &nonce).await {
+        assert!(!ct.is_empty());
+        let opened = cs.aead_open(&key, &ct, None, &nonce).await.unwrap();
+        assert!(opened.is_empty());
+    }
+
+    // No additional data is the same as empty additional data
+    let ct = cs.aead_seal(&key, &pt, None, &nonce).await.unwrap();
+    let ct_empty_aad = cs.aead_seal(&key, &pt, Some(&[]), &nonce).await.unwrap();
+    assert_eq!(ct, ct_empty_aad);
+
+    let ct = cs.aead_seal(&key, &pt, Some(&aad), &nonce).await.unwrap();
+
+    let mut modified_ct = ct.clone();
+    *modified_ct.last_mut().unwrap() ^= 1;
+
+    let mut other_nonce = nonce.clone();
+    other_nonce[0] ^= 1;
+
+    let mut other_key = key.clone();
+    other_key[0] ^= 1;
+
+    // Key, ciphertext, additional data and nonce
+    type OpenInput<'a> = (&'a [u8], &'a [u8], Option<&'a [u8]>, &'a [u8]);
+
+    let invalid_opens: [OpenInput; 8] = [
+        (&key, &modified_ct, Some(&aad), &nonce),
+        (&key, &ct[..ct.len() - 1], Some(&aad), &nonce),
+        (&key, &ct[..1], Some(&aad), &nonce),
+        (&key, &[], Some(&aad), &nonce),
+        (&key, &ct, None, &nonce),
+        (&key, &ct, Some(&aad[1..]), &nonce),
+        (&key, &ct, Some(&aad), &other_nonce),
+        (&other_key, &ct, Some(&aad), &nonce),
+    ];
+
+    for (key, ct, aad, nonce) in invalid_opens {
+        let res = cs.aead_open(key, ct, aad, nonce).await;
+        assert!(res.is_err());
+    }
+
+    // Keys and nonces of the wrong size are rejected
+    let seal = cs.aead_seal(&key[1..], &pt, None, &nonce).await;
+    assert!(seal.is_err());
+
+    let seal = cs.aead_seal(&key, &pt, None, &nonce[1..]).await;
+    assert!(seal.is_err());
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 struct HpkeTestCases {
     #[serde(with = "hex::serde")]
@@ -516,7 +639,9 @@ async fn verify_hash_tests<C: CipherSuiteProvider>(cs: &C, test_cases: Vec<HashT
 mod hpke_rfc_conformance {
     use alloc::vec::Vec;
 
-    use crate::crypto::{CipherSuite, HpkeContextR, HpkeContextS, HpkeModeId};
+    use crate::crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeContextR, HpkeContextS, HpkeModeId,
+    };
 
     #[derive(serde::Deserialize, Debug, Clone)]
     pub struct TestCaseAlgo {
@@ -549,6 +674,8 @@ mod hpke_rfc_conformance {
     struct TestCase {
         #[serde(flatten)]
         algo: TestCaseAlgo,
+        #[serde(with = "hex::serde")]
+        info: Vec<u8>,
         #[serde(with = "hex::serde", rename(deserialize = "pkRm"))]
         pk_rm: Vec<u8>,
         #[serde(with = "hex::serde", rename(deserialize = "skRm"))]
@@ -606,6 +733,60 @@ mod hpke_rfc_conformance {
         serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
     }
 
+    /// Check HPKE decryption and export of `crypto` against the base mode
+    /// test vectors of RFC 9180 included in the mls-rs repository.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_hpke_rfc_tests<C: CryptoProvider>(crypto: &C) {
+        verify_hpke_rfc_test_cases(crypto, get_test_cases()).await
+    }
+
+    /// Check HPKE decryption and export of `crypto` against test vectors in
+    /// the format of RFC 9180 and `test_data/test_hpke.json`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_hpke_rfc_tests_with_data<C: CryptoProvider>(crypto: &C, test_data: &[u8]) {
+        verify_hpke_rfc_test_cases(crypto, serde_json::from_slice(test_data).unwrap()).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify_hpke_rfc_test_cases<C: CryptoProvider>(crypto: &C, test_cases: Vec<TestCase>) {
+        for test_case in test_cases
+            .into_iter()
+            .filter(|tc| tc.algo.mode == HpkeModeId::Base as u8)
+        {
+            let Some(cs) = test_case
+                .algo
+                .cipher_suite()
+                .and_then(|cs| crypto.cipher_suite_provider(cs))
+            else {
+                continue;
+            };
+
+            let mut context_r = cs
+                .hpke_setup_r(
+                    &test_case.enc,
+                    &test_case.sk_rm.into(),
+                    &test_case.pk_rm.into(),
+                    &test_case.info,
+                )
+                .await
+                .unwrap();
+
+            for enc_test_case in test_case.encryptions {
+                let pt = context_r
+                    .open(Some(&enc_test_case.aad), &enc_test_case.ciphertext)
+                    .await
+                    .unwrap();
+
+                assert_eq!(pt, enc_test_case.plaintext);
+            }
+
+            for test in test_case.exports {
+                let exported = context_r.export(&test.exporter_context, test.length).await;
+                assert_eq!(exported.unwrap(), test.exported_value);
+            }
+        }
+    }
+
     pub struct EncapOutput {
         pub enc: Vec<u8>,
         pub shared_secret: Vec<u8>,
@@ -0,0 +1,738 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Object-safe wrappers around the provider traits.
+//!
+//! The provider traits use associated types, which prevents using them as
+//! `Box<dyn ...>` directly. Each wrapper in this module erases the associated
+//! types of any provider, reporting errors as [`AnyError`], so that providers
+//! can be chosen at runtime without making the rest of the application
+//! generic over them. Cloning a wrapper clones the wrapped provider.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+use crate::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkeContextR,
+        HpkeContextS, HpkePublicKey, HpkeSecretKey, SignaturePublicKey, SignatureSecretKey,
+    },
+    error::{AnyError, IntoAnyError},
+    extension::ExtensionList,
+    group::{EpochRecord, GroupState, GroupStateStorage},
+    identity::{
+        CredentialType, CredentialValidity, IdentityProvider, MemberValidationContext,
+        SigningIdentity,
+    },
+    key_package::{KeyPackageData, KeyPackageQuery, KeyPackageStorage},
+    psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    time::MlsTime,
+};
+
+/// Adapter mapping the errors of the wrapped provider to [`AnyError`].
+#[derive(Clone)]
+struct Erased<T>(T);
+
+macro_rules! clone_box_trait {
+    ($clone_trait:ident, $provider:path) => {
+        trait $clone_trait: $provider {
+            fn clone_box(&self) -> Box<dyn $clone_trait>;
+        }
+
+        impl<T> $clone_trait for T
+        where
+            T: $provider + Clone + 'static,
+        {
+            fn clone_box(&self) -> Box<dyn $clone_trait> {
+                Box::new(self.clone())
+            }
+        }
+    };
+}
+
+clone_box_trait!(
+    CloneCryptoProvider,
+    CryptoProvider<CipherSuiteProvider = DynCipherSuiteProvider>
+);
+
+clone_box_trait!(
+    CloneCipherSuiteProvider,
+    CipherSuiteProvider<
+        Error = AnyError,
+        HpkeContextS = DynHpkeContextS,
+        HpkeContextR = DynHpkeContextR,
+    >
+);
+
+clone_box_trait!(CloneGroupStateStorage, GroupStateStorage<Error = AnyError>);
+clone_box_trait!(CloneKeyPackageStorage, KeyPackageStorage<Error = AnyError>);
+clone_box_trait!(
+    ClonePreSharedKeyStorage,
+    PreSharedKeyStorage<Error = AnyError>
+);
+clone_box_trait!(CloneIdentityProvider, IdentityProvider<Error = AnyError>);
+
+/// Object-safe [`CryptoProvider`] producing [`DynCipherSuiteProvider`]s.
+pub struct DynCryptoProvider(Box<dyn CloneCryptoProvider>);
+
+impl DynCryptoProvider {
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: CryptoProvider + Clone + 'static,
+        P::CipherSuiteProvider: 'static,
+    {
+        Self(Box::new(Erased(provider)))
+    }
+}
+
+impl Clone for DynCryptoProvider {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+impl<P> CryptoProvider for Erased<P>
+where
+    P: CryptoProvider,
+    P::CipherSuiteProvider: 'static,
+{
+    type CipherSuiteProvider = DynCipherSuiteProvider;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        self.0.supported_cipher_suites()
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        self.0
+            .cipher_suite_provider(cipher_suite)
+            .map(DynCipherSuiteProvider::new)
+    }
+}
+
+impl CryptoProvider for DynCryptoProvider {
+    type CipherSuiteProvider = DynCipherSuiteProvider;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        self.0.supported_cipher_suites()
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        self.0.cipher_suite_provider(cipher_suite)
+    }
+}
+
+/// Object-safe [`HpkeContextS`].
+pub struct DynHpkeContextS(Box<dyn HpkeContextS<Error = AnyError> + Send + Sync>);
+
+/// Object-safe [`HpkeContextR`].
+pub struct DynHpkeContextR(Box<dyn HpkeContextR<Error = AnyError> + Send + Sync>);
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<C: HpkeContextS + Send + Sync> HpkeContextS for Erased<C> {
+    type Error = AnyError;
+
+    async fn seal(&mut self, aad: Option<&[u8]>, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.0
+            .seal(aad, data)
+            .await
+            .map_err(IntoAnyError::into_any_error)
+    }
+
+    async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Self::Error> {
+        self.0
+            .export(exporter_context, len)
+            .await
+            .map_err(IntoAnyError::into_any_error)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl HpkeContextS for DynHpkeContextS {
+    type Error = AnyError;
+
+    async fn seal(&mut self, aad: Option<&[u8]>, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.0.seal(aad, data).await
+    }
+
+    async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Self::Error> {
+        self.0.export(exporter_context, len).await
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<C: HpkeContextR + Send + Sync> HpkeContextR for Erased<C> {
+    type Error = AnyError;
+
+    async fn open(
+        &mut self,
+        aad: Option<&[u8]>,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.0
+            .open(aad, ciphertext)
+            .await
+            .map_err(IntoAnyError::into_any_error)
+    }
+
+    async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Self::Error> {
+        self.0
+            .export(exporter_context, len)
+            .await
+            .map_err(IntoAnyError::into_any_error)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl HpkeContextR for DynHpkeContextR {
+    type Error = AnyError;
+
+    async fn open(
+        &mut self,
+        aad: Option<&[u8]>,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.0.open(aad, ciphertext).await
+    }
+
+    async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Self::Error> {
+        self.0.export(exporter_context, len).await
+    }
+}
+
+/// Object-safe [`CipherSuiteProvider`].
+pub struct DynCipherSuiteProvider(Box<dyn CloneCipherSuiteProvider>);
+
+impl DynCipherSuiteProvider {
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: CipherSuiteProvider + Clone + 'static,
+    {
+        Self(Box::new(Erased(provider)))
+    }
+}
+
+impl Clone for DynCipherSuiteProvider {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+macro_rules! delegate_cipher_suite_provider {
+    ([$($generics:tt)*] $ty:ty, [$($bounds:tt)*], $map_err:expr, $wrap_s:expr, $wrap_r:expr) => {
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+        #[cfg_attr(
+            all(not(target_arch = "wasm32"), mls_build_async),
+            maybe_async::must_be_async
+        )]
+        impl<$($generics)*> CipherSuiteProvider for $ty
+        where
+            $($bounds)*
+        {
+            type Error = AnyError;
+            type HpkeContextS = DynHpkeContextS;
+            type HpkeContextR = DynHpkeContextR;
+            fn cipher_suite(&self) -> CipherSuite {
+                self.0.cipher_suite()
+            }
+
+            async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+                self.0.hash(data).await.map_err($map_err)
+            }
+
+            async fn mac(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+                self.0.mac(key, data).await.map_err($map_err)
+            }
+
+            async fn aead_seal(
+                &self,
+                key: &[u8],
+                data: &[u8],
+                aad: Option<&[u8]>,
+                nonce: &[u8],
+            ) -> Result<Vec<u8>, Self::Error> {
+                self.0
+                    .aead_seal(key, data, aad, nonce)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn aead_open(
+                &self,
+                key: &[u8],
+                ciphertext: &[u8],
+                aad: Option<&[u8]>,
+                nonce: &[u8],
+            ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+                self.0
+                    .aead_open(key, ciphertext, aad, nonce)
+                    .await
+                    .map_err($map_err)
+            }
+
+            fn aead_key_size(&self) -> usize {
+                self.0.aead_key_size()
+            }
+
+            fn aead_nonce_size(&self) -> usize {
+                self.0.aead_nonce_size()
+            }
+
+            async fn kdf_extract(
+                &self,
+                salt: &[u8],
+                ikm: &[u8],
+            ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+                self.0.kdf_extract(salt, ikm).await.map_err($map_err)
+            }
+
+            async fn kdf_expand(
+                &self,
+                prk: &[u8],
+                info: &[u8],
+                len: usize,
+            ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+                self.0.kdf_expand(prk, info, len).await.map_err($map_err)
+            }
+
+            fn kdf_extract_size(&self) -> usize {
+                self.0.kdf_extract_size()
+            }
+
+            async fn hpke_seal(
+                &self,
+                remote_key: &HpkePublicKey,
+                info: &[u8],
+                aad: Option<&[u8]>,
+                pt: &[u8],
+            ) -> Result<HpkeCiphertext, Self::Error> {
+                self.0
+                    .hpke_seal(remote_key, info, aad, pt)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn hpke_open(
+                &self,
+                ciphertext: &HpkeCiphertext,
+                local_secret: &HpkeSecretKey,
+                local_public: &HpkePublicKey,
+                info: &[u8],
+                aad: Option<&[u8]>,
+            ) -> Result<Vec<u8>, Self::Error> {
+                self.0
+                    .hpke_open(ciphertext, local_secret, local_public, info, aad)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn hpke_setup_s(
+                &self,
+                remote_key: &HpkePublicKey,
+                info: &[u8],
+            ) -> Result<(Vec<u8>, Self::HpkeContextS), Self::Error> {
+                self.0
+                    .hpke_setup_s(remote_key, info)
+                    .await
+                    .map(|(kem_output, context)| (kem_output, $wrap_s(context)))
+                    .map_err($map_err)
+            }
+
+            async fn hpke_setup_r(
+                &self,
+                kem_output: &[u8],
+                local_secret: &HpkeSecretKey,
+                local_public: &HpkePublicKey,
+                info: &[u8],
+            ) -> Result<Self::HpkeContextR, Self::Error> {
+                self.0
+                    .hpke_setup_r(kem_output, local_secret, local_public, info)
+                    .await
+                    .map($wrap_r)
+                    .map_err($map_err)
+            }
+
+            async fn kem_derive(
+                &self,
+                ikm: &[u8],
+            ) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+                self.0.kem_derive(ikm).await.map_err($map_err)
+            }
+
+            async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+                self.0.kem_generate().await.map_err($map_err)
+            }
+
+            fn kem_public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+                self.0.kem_public_key_validate(key).map_err($map_err)
+            }
+
+            fn random_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+                self.0.random_bytes(out).map_err($map_err)
+            }
+
+            fn random_bytes_vec(&self, count: usize) -> Result<Vec<u8>, Self::Error> {
+                self.0.random_bytes_vec(count).map_err($map_err)
+            }
+
+            async fn signature_key_generate(
+                &self,
+            ) -> Result<(SignatureSecretKey, SignaturePublicKey), Self::Error> {
+                self.0.signature_key_generate().await.map_err($map_err)
+            }
+
+            async fn signature_key_derive_public(
+                &self,
+                secret_key: &SignatureSecretKey,
+            ) -> Result<SignaturePublicKey, Self::Error> {
+                self.0
+                    .signature_key_derive_public(secret_key)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn sign(
+                &self,
+                secret_key: &SignatureSecretKey,
+                data: &[u8],
+            ) -> Result<Vec<u8>, Self::Error> {
+                self.0.sign(secret_key, data).await.map_err($map_err)
+            }
+
+            async fn verify(
+                &self,
+                public_key: &SignaturePublicKey,
+                signature: &[u8],
+                data: &[u8],
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .verify(public_key, signature, data)
+                    .await
+                    .map_err($map_err)
+            }
+        }
+    };
+}
+
+fn erase_hpke_s<C>(context: C) -> DynHpkeContextS
+where
+    C: HpkeContextS + Send + Sync + 'static,
+{
+    DynHpkeContextS(Box::new(Erased(context)))
+}
+
+fn erase_hpke_r<C>(context: C) -> DynHpkeContextR
+where
+    C: HpkeContextR + Send + Sync + 'static,
+{
+    DynHpkeContextR(Box::new(Erased(context)))
+}
+
+delegate_cipher_suite_provider!(
+    [P] Erased<P>,
+    [P: CipherSuiteProvider, P::HpkeContextS: 'static, P::HpkeContextR: 'static],
+    IntoAnyError::into_any_error,
+    erase_hpke_s,
+    erase_hpke_r
+);
+
+delegate_cipher_suite_provider!(
+    [] DynCipherSuiteProvider,
+    [],
+    core::convert::identity,
+    core::convert::identity,
+    core::convert::identity
+);
+
+/// Object-safe [`GroupStateStorage`].
+pub struct DynGroupStateStorage(Box<dyn CloneGroupStateStorage>);
+
+impl DynGroupStateStorage {
+    pub fn new<S: GroupStateStorage + Clone + 'static>(storage: S) -> Self {
+        Self(Box::new(Erased(storage)))
+    }
+}
+
+impl Clone for DynGroupStateStorage {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+macro_rules! delegate_group_state_storage {
+    ([$($generics:tt)*] $ty:ty, $map_err:expr) => {
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+        impl<$($generics)*> GroupStateStorage for $ty {
+            type Error = AnyError;
+            async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+                self.0.state(group_id).await.map_err($map_err)
+            }
+
+            async fn epoch(
+                &self,
+                group_id: &[u8],
+                epoch_id: u64,
+            ) -> Result<Option<Vec<u8>>, Self::Error> {
+                self.0.epoch(group_id, epoch_id).await.map_err($map_err)
+            }
+
+            async fn write(
+                &mut self,
+                state: GroupState,
+                epoch_inserts: Vec<EpochRecord>,
+                epoch_updates: Vec<EpochRecord>,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .write(state, epoch_inserts, epoch_updates)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+                self.0.max_epoch_id(group_id).await.map_err($map_err)
+            }
+
+            async fn delete_epochs_under(
+                &mut self,
+                group_id: &[u8],
+                epoch_id: u64,
+            ) -> Result<bool, Self::Error> {
+                self.0
+                    .delete_epochs_under(group_id, epoch_id)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn integrity_counter(
+                &self,
+                group_id: &[u8],
+            ) -> Result<Option<u64>, Self::Error> {
+                self.0.integrity_counter(group_id).await.map_err($map_err)
+            }
+
+            async fn set_integrity_counter(
+                &mut self,
+                group_id: &[u8],
+                counter: u64,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .set_integrity_counter(group_id, counter)
+                    .await
+                    .map_err($map_err)
+            }
+        }
+    };
+}
+
+delegate_group_state_storage!([S: GroupStateStorage] Erased<S>, IntoAnyError::into_any_error);
+
+delegate_group_state_storage!([] DynGroupStateStorage, core::convert::identity);
+
+/// Object-safe [`KeyPackageStorage`].
+pub struct DynKeyPackageStorage(Box<dyn CloneKeyPackageStorage>);
+
+impl DynKeyPackageStorage {
+    pub fn new<S: KeyPackageStorage + Clone + 'static>(storage: S) -> Self {
+        Self(Box::new(Erased(storage)))
+    }
+}
+
+impl Clone for DynKeyPackageStorage {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+macro_rules! delegate_key_package_storage {
+    ([$($generics:tt)*] $ty:ty, $map_err:expr) => {
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+        impl<$($generics)*> KeyPackageStorage for $ty {
+            type Error = AnyError;
+            async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
+                self.0.delete(id).await.map_err($map_err)
+            }
+
+            async fn insert(&mut self, id: Vec<u8>, pkg: KeyPackageData) -> Result<(), Self::Error> {
+                self.0.insert(id, pkg).await.map_err($map_err)
+            }
+
+            async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
+                self.0.get(id).await.map_err($map_err)
+            }
+
+            async fn list(
+                &self,
+                query: &KeyPackageQuery,
+            ) -> Result<Option<Vec<(Vec<u8>, KeyPackageData)>>, Self::Error> {
+                self.0.list(query).await.map_err($map_err)
+            }
+
+            async fn count(&self, query: &KeyPackageQuery) -> Result<Option<usize>, Self::Error> {
+                self.0.count(query).await.map_err($map_err)
+            }
+        }
+    };
+}
+
+delegate_key_package_storage!([S: KeyPackageStorage] Erased<S>, IntoAnyError::into_any_error);
+
+delegate_key_package_storage!([] DynKeyPackageStorage, core::convert::identity);
+
+/// Object-safe [`PreSharedKeyStorage`].
+pub struct DynPreSharedKeyStorage(Box<dyn ClonePreSharedKeyStorage>);
+
+impl DynPreSharedKeyStorage {
+    pub fn new<S: PreSharedKeyStorage + Clone + 'static>(storage: S) -> Self {
+        Self(Box::new(Erased(storage)))
+    }
+}
+
+impl Clone for DynPreSharedKeyStorage {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+macro_rules! delegate_psk_storage {
+    ([$($generics:tt)*] $ty:ty, $map_err:expr) => {
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+        impl<$($generics)*> PreSharedKeyStorage for $ty {
+            type Error = AnyError;
+            async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+                self.0.get(id).await.map_err($map_err)
+            }
+
+            async fn get_many(
+                &self,
+                ids: &[ExternalPskId],
+            ) -> Result<Vec<Option<PreSharedKey>>, Self::Error> {
+                self.0.get_many(ids).await.map_err($map_err)
+            }
+
+            async fn contains(&self, id: &ExternalPskId) -> Result<bool, Self::Error> {
+                self.0.contains(id).await.map_err($map_err)
+            }
+        }
+    };
+}
+
+delegate_psk_storage!([S: PreSharedKeyStorage] Erased<S>, IntoAnyError::into_any_error);
+
+delegate_psk_storage!([] DynPreSharedKeyStorage, core::convert::identity);
+
+/// Object-safe [`IdentityProvider`].
+pub struct DynIdentityProvider(Box<dyn CloneIdentityProvider>);
+
+impl DynIdentityProvider {
+    pub fn new<P: IdentityProvider + Clone + 'static>(provider: P) -> Self {
+        Self(Box::new(Erased(provider)))
+    }
+}
+
+impl Clone for DynIdentityProvider {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+macro_rules! delegate_identity_provider {
+    ([$($generics:tt)*] $ty:ty, $map_err:expr) => {
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+        impl<$($generics)*> IdentityProvider for $ty {
+            type Error = AnyError;
+            async fn validate_member(
+                &self,
+                signing_identity: &SigningIdentity,
+                timestamp: Option<MlsTime>,
+                context: MemberValidationContext<'_>,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .validate_member(signing_identity, timestamp, context)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn validate_external_sender(
+                &self,
+                signing_identity: &SigningIdentity,
+                timestamp: Option<MlsTime>,
+                extensions: Option<&ExtensionList>,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .validate_external_sender(signing_identity, timestamp, extensions)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn identity(
+                &self,
+                signing_identity: &SigningIdentity,
+                extensions: &ExtensionList,
+            ) -> Result<Vec<u8>, Self::Error> {
+                self.0
+                    .identity(signing_identity, extensions)
+                    .await
+                    .map_err($map_err)
+            }
+
+            async fn valid_successor(
+                &self,
+                predecessor: &SigningIdentity,
+                successor: &SigningIdentity,
+                extensions: &ExtensionList,
+            ) -> Result<bool, Self::Error> {
+                self.0
+                    .valid_successor(predecessor, successor, extensions)
+                    .await
+                    .map_err($map_err)
+            }
+
+            fn supported_types(&self) -> Vec<CredentialType> {
+                self.0.supported_types()
+            }
+
+            fn credential_validity(
+                &self,
+                signing_identity: &SigningIdentity,
+            ) -> Result<Option<CredentialValidity>, Self::Error> {
+                self.0.credential_validity(signing_identity).map_err($map_err)
+            }
+        }
+    };
+}
+
+delegate_identity_provider!([P: IdentityProvider] Erased<P>, IntoAnyError::into_any_error);
+
+delegate_identity_provider!([] DynIdentityProvider, core::convert::identity);
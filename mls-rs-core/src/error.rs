@@ -61,3 +61,10 @@ impl IntoAnyError for mls_rs_codec::Error {
 }
 
 impl IntoAnyError for core::convert::Infallible {}
+
+impl IntoAnyError for AnyError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.0)
+    }
+}
@@ -164,6 +164,62 @@ impl ExtensionList {
     pub fn append(&mut self, others: Self) {
         self.0.extend(others.0);
     }
+
+    /// Compute the changes required to go from this list to `new`.
+    pub fn diff(&self, new: &ExtensionList) -> ExtensionDiff {
+        let mut diff = ExtensionDiff::default();
+
+        for old_ext in self.iter() {
+            match new.get(old_ext.extension_type) {
+                None => diff.removed.push(old_ext.clone()),
+                Some(new_ext) if &new_ext != old_ext => {
+                    diff.updated.push((old_ext.clone(), new_ext))
+                }
+                Some(_) => (),
+            }
+        }
+
+        diff.added = new
+            .iter()
+            .filter(|ext| !self.has_extension(ext.extension_type))
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
+
+/// Changes between two [ExtensionLists](ExtensionList), as computed by
+/// [ExtensionList::diff].
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExtensionDiff {
+    /// Extensions present only in the new list.
+    pub added: Vec<Extension>,
+    /// Extensions present only in the old list.
+    pub removed: Vec<Extension>,
+    /// Extensions present in both lists with different data, as `(old, new)` pairs.
+    pub updated: Vec<(Extension, Extension)>,
+}
+
+impl ExtensionDiff {
+    /// Determine if both lists contained the same extensions.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+
+    /// Determine if the extension with the given type was added, removed or updated.
+    pub fn has_changed(&self, ext_type: ExtensionType) -> bool {
+        self.added
+            .iter()
+            .chain(self.removed.iter())
+            .chain(self.updated.iter().map(|(old, _)| old))
+            .any(|ext| ext.extension_type == ext_type)
+    }
 }
 
 #[cfg(test)]
@@ -361,4 +417,39 @@ mod tests {
 
         assert_eq!(list, expected);
     }
+
+    #[test]
+    fn extension_list_diff() {
+        let old = ExtensionList::from(vec![
+            TestExtensionA(33).into_extension().unwrap(),
+            TestExtensionB(vec![34]).into_extension().unwrap(),
+        ]);
+
+        let new = ExtensionList::from(vec![
+            TestExtensionB(vec![35]).into_extension().unwrap(),
+            TestExtensionC(36).into_extension().unwrap(),
+        ]);
+
+        let diff = old.diff(&new);
+
+        assert_eq!(
+            diff.added,
+            vec![TestExtensionC(36).into_extension().unwrap()]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![TestExtensionA(33).into_extension().unwrap()]
+        );
+
+        assert_eq!(
+            diff.updated,
+            vec![(
+                TestExtensionB(vec![34]).into_extension().unwrap(),
+                TestExtensionB(vec![35]).into_extension().unwrap()
+            )]
+        );
+
+        assert!(diff.has_changed(<TestExtensionA as MlsExtension>::extension_type()));
+        assert!(old.diff(&old).is_empty());
+    }
 }
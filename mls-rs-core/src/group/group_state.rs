@@ -104,4 +104,50 @@ pub trait GroupStateStorage: Send + Sync {
     /// The [`EpochRecord::id`] value that is associated with a stored
     /// prior epoch for a particular group.
     async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error>;
+
+    /// Delete every prior epoch of a group with an [`EpochRecord::id`] lower
+    /// than `epoch_id`.
+    ///
+    /// This is requested when a group enforces an epoch retention policy in
+    /// `mls_rs`, and the deletion is then recorded in the deletion manifest
+    /// of the group. An implementation should only return `true` once the
+    /// deleted data can no longer be recovered from the storage medium.
+    ///
+    /// The default implementation returns `false`, signaling that deletion
+    /// on request is not supported.
+    async fn delete_epochs_under(
+        &mut self,
+        _group_id: &[u8],
+        _epoch_id: u64,
+    ) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    /// Highest storage integrity counter recorded for a group with
+    /// [`set_integrity_counter`](GroupStateStorage::set_integrity_counter).
+    ///
+    /// When a storage integrity key is set in `mls_rs`, each write of a group
+    /// state is sealed with a counter that is one more than this value, and
+    /// a group state sealed with a lower counter is rejected when it is
+    /// loaded, as it was rolled back. The counter should be kept where it
+    /// can't be rolled back together with the group states, such as a
+    /// monotonic counter of a secure element. Epoch records are sealed with
+    /// the same counter but are not checked against it.
+    ///
+    /// The default implementation returns `None`, which disables the check.
+    async fn integrity_counter(&self, _group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Record `counter` as the highest storage integrity counter of a group,
+    /// after the group state sealed with it was written.
+    ///
+    /// The default implementation does nothing.
+    async fn set_integrity_counter(
+        &mut self,
+        _group_id: &[u8],
+        _counter: u64,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
@@ -2,7 +2,9 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+mod anonymous;
 mod basic;
+mod bootstrap;
 mod credential;
 mod provider;
 mod signing_identity;
@@ -10,7 +12,9 @@ mod signing_identity;
 #[cfg(feature = "x509")]
 mod x509;
 
+pub use anonymous::*;
 pub use basic::*;
+pub use bootstrap::*;
 pub use credential::*;
 pub use provider::*;
 pub use signing_identity::*;
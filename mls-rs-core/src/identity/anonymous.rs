@@ -0,0 +1,191 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::fmt::{self, Debug};
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+use crate::{crypto::SignaturePublicKey, error::IntoAnyError, time::MlsTime};
+
+use super::{Credential, CredentialType, CustomCredential, MlsCredential};
+
+const PRESENTATION_LABEL: &[u8] = b"MLS 1.0 anonymous credential presentation";
+
+/// Privacy preserving credential scheme used to create a presentation, such as
+/// BBS+ proofs or Privacy Pass tokens.
+///
+/// Values are defined by the application.
+#[derive(
+    Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, MlsSize, MlsEncode, MlsDecode,
+)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(transparent)]
+pub struct AnonymousCredentialScheme(u16);
+
+impl AnonymousCredentialScheme {
+    pub const fn new(raw_value: u16) -> Self {
+        Self(raw_value)
+    }
+
+    pub const fn raw_value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for AnonymousCredentialScheme {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Credential proving that a member is authorized without revealing a stable
+/// long-term identity.
+///
+/// The presentation is a zero-knowledge proof or token created by the
+/// member for a single signature key, see
+/// [`AnonymousCredential::presentation_message`]. Presentations created for
+/// different signature keys can not be linked to each other.
+///
+/// Anonymous credentials are encoded as a [`CustomCredential`] of type
+/// [`AnonymousCredential::credential_type`] and validated by an identity
+/// provider using an [`AnonymousCredentialVerifier`].
+pub struct AnonymousCredential {
+    /// Scheme used to create the presentation.
+    pub scheme: AnonymousCredentialScheme,
+    /// Presentation of the credential, bound to the signature key of the member.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::vec_serde"))]
+    pub presentation: Vec<u8>,
+}
+
+impl Debug for AnonymousCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnonymousCredential")
+            .field("scheme", &self.scheme)
+            .field(
+                "presentation",
+                &crate::debug::pretty_bytes(&self.presentation),
+            )
+            .finish()
+    }
+}
+
+impl AnonymousCredential {
+    /// Credential type of anonymous credentials, in the range reserved for
+    /// private use.
+    pub const CREDENTIAL_TYPE: CredentialType = CredentialType::new(0xF0A0);
+
+    /// Create a new anonymous credential from a presentation created with
+    /// `scheme`.
+    pub fn new(scheme: AnonymousCredentialScheme, presentation: Vec<u8>) -> Self {
+        Self {
+            scheme,
+            presentation,
+        }
+    }
+
+    pub fn credential_type() -> CredentialType {
+        Self::CREDENTIAL_TYPE
+    }
+
+    /// Message that a presentation for `signature_key` must prove knowledge
+    /// of a credential over, for example as the presentation header of a BBS+
+    /// proof or the challenge of a Privacy Pass token.
+    ///
+    /// Binding the presentation to the signature key prevents it from being
+    /// replayed with another signature key.
+    pub fn presentation_message(signature_key: &SignaturePublicKey) -> Vec<u8> {
+        [PRESENTATION_LABEL, signature_key.as_bytes()].concat()
+    }
+
+    /// Decode an anonymous credential from `credential`.
+    ///
+    /// Returns `None` if `credential` is not an anonymous credential.
+    pub fn from_credential(credential: &Credential) -> Option<Result<Self, mls_rs_codec::Error>> {
+        credential
+            .as_custom()
+            .filter(|custom| custom.credential_type == Self::CREDENTIAL_TYPE)
+            .map(|custom| Self::mls_decode(&mut &*custom.data))
+    }
+}
+
+impl MlsCredential for AnonymousCredential {
+    type Error = mls_rs_codec::Error;
+
+    fn credential_type() -> CredentialType {
+        Self::CREDENTIAL_TYPE
+    }
+
+    fn into_credential(self) -> Result<Credential, Self::Error> {
+        Ok(Credential::Custom(CustomCredential::new(
+            Self::CREDENTIAL_TYPE,
+            self.mls_encode_to_vec()?,
+        )))
+    }
+}
+
+/// Verifier of [`AnonymousCredential`] presentations, implemented by the
+/// provider of a privacy preserving credential scheme.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait AnonymousCredentialVerifier: Send + Sync {
+    /// Error type that this verifier returns on internal failure or if a
+    /// presentation is invalid.
+    type Error: IntoAnyError;
+
+    /// Verify that `credential` is a valid presentation over `message`.
+    ///
+    /// `message` is the output of
+    /// [`AnonymousCredential::presentation_message`] for the signature key the
+    /// credential is used with. A `timestamp` value can optionally be supplied
+    /// to check the expiration of the credential or token.
+    async fn verify_presentation(
+        &self,
+        credential: &AnonymousCredential,
+        message: &[u8],
+        timestamp: Option<MlsTime>,
+    ) -> Result<(), Self::Error>;
+
+    /// Schemes that are supported by this verifier.
+    fn supported_schemes(&self) -> Vec<AnonymousCredentialScheme>;
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use mls_rs_codec::MlsEncode;
+
+    use crate::identity::{Credential, CustomCredential, MlsCredential};
+
+    use super::{AnonymousCredential, AnonymousCredentialScheme};
+
+    #[test]
+    fn anonymous_credential_round_trips_as_custom_credential() {
+        let anonymous = AnonymousCredential::new(AnonymousCredentialScheme::new(1), vec![1, 2, 3]);
+        let credential = anonymous.clone().into_credential().unwrap();
+
+        assert_eq!(
+            credential.credential_type(),
+            AnonymousCredential::CREDENTIAL_TYPE
+        );
+
+        let decoded = AnonymousCredential::from_credential(&credential);
+        assert_eq!(decoded.unwrap().unwrap(), anonymous);
+
+        let other = Credential::Custom(CustomCredential::new(
+            42.into(),
+            anonymous.mls_encode_to_vec().unwrap(),
+        ));
+
+        assert!(AnonymousCredential::from_credential(&other).is_none());
+    }
+}
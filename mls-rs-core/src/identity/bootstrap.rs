@@ -0,0 +1,63 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{
+    crypto::{CipherSuite, SignatureSecretKey},
+    error::IntoAnyError,
+};
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+use super::SigningIdentity;
+
+/// Hook used to provision a signing identity on demand.
+///
+/// A client invokes the bootstrap when it is asked to operate with a cipher
+/// suite for which its [`KeychainStorage`] holds no signing identity. This
+/// allows applications to, for example, generate a key pair and issue a
+/// certificate signing request to an X.509 CA, or register a freshly generated
+/// key with an identity directory.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait IdentityBootstrap: Send + Sync {
+    /// Error type that this bootstrap returns on internal failure.
+    type Error: IntoAnyError;
+
+    /// Provision a new signing identity and the matching signer for
+    /// `cipher_suite`.
+    ///
+    /// `None` should be returned if this bootstrap does not support
+    /// `cipher_suite`.
+    async fn bootstrap(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, Self::Error>;
+}
+
+/// Storage trait to maintain the signing identities of a client, indexed by
+/// cipher suite.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait KeychainStorage: Send + Sync {
+    /// Error type that the underlying storage mechanism returns on internal
+    /// failure.
+    type Error: IntoAnyError;
+
+    /// Get the signing identity and signer stored for `cipher_suite`.
+    ///
+    /// `None` should be returned if no identity is stored for `cipher_suite`.
+    async fn signing_identity(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, Self::Error>;
+
+    /// Store `signing_identity` and `signer` for `cipher_suite`, replacing any
+    /// previously stored value.
+    async fn insert(
+        &mut self,
+        cipher_suite: CipherSuite,
+        signing_identity: SigningIdentity,
+        signer: SignatureSecretKey,
+    ) -> Result<(), Self::Error>;
+}
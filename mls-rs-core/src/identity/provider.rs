@@ -6,6 +6,7 @@ use crate::{error::IntoAnyError, extension::ExtensionList, group::GroupContext,
 #[cfg(mls_build_async)]
 use alloc::boxed::Box;
 use alloc::vec::Vec;
+use core::time::Duration;
 
 use super::{CredentialType, SigningIdentity};
 
@@ -33,6 +34,37 @@ impl MemberValidationContext<'_> {
     }
 }
 
+/// Period during which a credential is valid, as reported by
+/// [`IdentityProvider::credential_validity`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct CredentialValidity {
+    pub not_before: MlsTime,
+    pub not_after: MlsTime,
+}
+
+impl CredentialValidity {
+    pub fn new(not_before: MlsTime, not_after: MlsTime) -> Self {
+        Self {
+            not_before,
+            not_after,
+        }
+    }
+
+    /// Whether the credential is valid at `time`.
+    pub fn is_valid_at(&self, time: MlsTime) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    /// Whether the credential is no longer valid at `time + window`. This
+    /// includes credentials that are already expired at `time`.
+    pub fn expires_within(&self, time: MlsTime, window: Duration) -> bool {
+        let deadline = time.seconds_since_epoch().saturating_add(window.as_secs());
+        self.not_after.seconds_since_epoch() < deadline
+    }
+}
+
 /// Identity system that can be used to validate a
 /// [`SigningIdentity`](mls-rs-core::identity::SigningIdentity)
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -91,4 +123,17 @@ pub trait IdentityProvider: Send + Sync {
 
     /// Credential types that are supported by this provider.
     fn supported_types(&self) -> Vec<CredentialType>;
+
+    /// Validity period of the credential in `signing_identity`, for
+    /// credentials that expire such as X.509 certificates.
+    ///
+    /// This allows applications to find members that need to renew their
+    /// credential before validation starts failing. The default
+    /// implementation returns `None`.
+    fn credential_validity(
+        &self,
+        _signing_identity: &SigningIdentity,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        Ok(None)
+    }
 }
@@ -8,7 +8,11 @@ use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
-use crate::{crypto::HpkeSecretKey, error::IntoAnyError};
+use crate::{
+    crypto::{CipherSuite, HpkeSecretKey},
+    error::IntoAnyError,
+    protocol_version::ProtocolVersion,
+};
 
 #[derive(Clone, PartialEq, Eq, MlsEncode, MlsDecode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -50,6 +54,67 @@ impl KeyPackageData {
             expiration,
         }
     }
+
+    /// Protocol version of the stored key package, read from the first
+    /// bytes of [`key_package_bytes`](Self::key_package_bytes).
+    pub fn protocol_version(&self) -> Option<ProtocolVersion> {
+        ProtocolVersion::mls_decode(&mut &*self.key_package_bytes).ok()
+    }
+
+    /// Cipher suite of the stored key package, read from the first bytes of
+    /// [`key_package_bytes`](Self::key_package_bytes).
+    pub fn cipher_suite(&self) -> Option<CipherSuite> {
+        let reader = &mut &*self.key_package_bytes;
+        ProtocolVersion::mls_decode(reader).ok()?;
+        CipherSuite::mls_decode(reader).ok()
+    }
+}
+
+/// Filter selecting stored key packages in [`KeyPackageStorage::list`] and
+/// [`KeyPackageStorage::count`]. Fields that are not set match every key
+/// package.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct KeyPackageQuery {
+    pub cipher_suite: Option<CipherSuite>,
+    pub protocol_version: Option<ProtocolVersion>,
+    /// Time in seconds since the Unix epoch at which selected key packages
+    /// must not be expired yet.
+    pub valid_at: Option<u64>,
+}
+
+impl KeyPackageQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cipher_suite(self, cipher_suite: CipherSuite) -> Self {
+        Self {
+            cipher_suite: Some(cipher_suite),
+            ..self
+        }
+    }
+
+    pub fn with_protocol_version(self, protocol_version: ProtocolVersion) -> Self {
+        Self {
+            protocol_version: Some(protocol_version),
+            ..self
+        }
+    }
+
+    pub fn with_valid_at(self, valid_at: u64) -> Self {
+        Self {
+            valid_at: Some(valid_at),
+            ..self
+        }
+    }
+
+    /// Whether `pkg` is selected by this query.
+    pub fn matches(&self, pkg: &KeyPackageData) -> bool {
+        (self.cipher_suite.is_none() || self.cipher_suite == pkg.cipher_suite())
+            && (self.protocol_version.is_none() || self.protocol_version == pkg.protocol_version())
+            && !matches!(self.valid_at, Some(time) if pkg.expiration < time)
+    }
 }
 
 /// Storage trait that maintains key package secrets.
@@ -81,4 +146,33 @@ pub trait KeyPackageStorage: Send + Sync {
     /// `None` should be returned in the event that no key packages are found
     /// that match `id`.
     async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error>;
+
+    /// Retrieve every stored [`KeyPackageData`] selected by `query`, with
+    /// its `id`, in no particular order.
+    ///
+    /// Implementations should index the cipher suite, protocol version and
+    /// expiration of stored key packages rather than decoding every stored
+    /// value. [`KeyPackageQuery::matches`] defines the selection.
+    ///
+    /// The default implementation returns `None`, signaling that queries are
+    /// not supported.
+    #[allow(clippy::type_complexity)]
+    async fn list(
+        &self,
+        _query: &KeyPackageQuery,
+    ) -> Result<Option<Vec<(Vec<u8>, KeyPackageData)>>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Number of stored key packages selected by `query`, or `None` if
+    /// queries are not supported.
+    ///
+    /// The default implementation counts the result of
+    /// [`list`](KeyPackageStorage::list).
+    async fn count(&self, query: &KeyPackageQuery) -> Result<Option<usize>, Self::Error> {
+        Ok(self
+            .list(query)
+            .await?
+            .map(|key_packages| key_packages.len()))
+    }
 }
@@ -9,8 +9,10 @@ extern crate alloc;
 #[cfg(all(test, target_arch = "wasm32"))]
 wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
+pub mod compression;
 pub mod crypto;
 pub mod debug;
+pub mod dyn_provider;
 pub mod error;
 pub mod extension;
 pub mod group;
@@ -2,7 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::error::IntoAnyError;
+use crate::{error::IntoAnyError, secret::SecretBytes};
 #[cfg(mls_build_async)]
 use alloc::boxed::Box;
 use alloc::vec::Vec;
@@ -16,11 +16,7 @@ use zeroize::Zeroizing;
 #[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Wrapper type that holds a pre-shared key value and zeroizes on drop.
-pub struct PreSharedKey(
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "crate::zeroizing_serde"))]
-    Zeroizing<Vec<u8>>,
-);
+pub struct PreSharedKey(SecretBytes);
 
 impl Debug for PreSharedKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -33,7 +29,7 @@ impl Debug for PreSharedKey {
 impl PreSharedKey {
     /// Create a new PreSharedKey.
     pub fn new(data: Vec<u8>) -> Self {
-        PreSharedKey(Zeroizing::new(data))
+        PreSharedKey(data.into())
     }
 
     /// Raw byte value.
@@ -50,7 +46,7 @@ impl From<Vec<u8>> for PreSharedKey {
 
 impl From<Zeroizing<Vec<u8>>> for PreSharedKey {
     fn from(bytes: Zeroizing<Vec<u8>>) -> Self {
-        Self(bytes)
+        Self(bytes.into())
     }
 }
 
@@ -130,8 +126,66 @@ pub trait PreSharedKeyStorage: Send + Sync {
     /// `None` should be returned if a pre-shared key can not be found for `id`.
     async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error>;
 
+    /// Get the pre-shared keys for all of `ids` at once, returning one
+    /// result per id in the same order.
+    ///
+    /// This is used to resolve all external PSKs of a commit with a single
+    /// lookup. The default implementation calls [`get`](Self::get) for each
+    /// id; storage backed by a remote service should override it to fetch
+    /// all keys in one round trip.
+    async fn get_many(
+        &self,
+        ids: &[ExternalPskId],
+    ) -> Result<Vec<Option<PreSharedKey>>, Self::Error> {
+        let mut keys = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            keys.push(self.get(id).await?);
+        }
+
+        Ok(keys)
+    }
+
     /// Determines if a PSK is located within the store
     async fn contains(&self, id: &ExternalPskId) -> Result<bool, Self::Error> {
         self.get(id).await.map(|key| key.is_some())
     }
 }
+
+/// Just-in-time source of external pre-shared keys missing from the
+/// [`PreSharedKeyStorage`] of a client, for example a secret manager.
+///
+/// The resolver is called with the id of each external PSK required to join
+/// a group or to process a commit that is not found in the storage.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait ExternalPskResolver: Send + Sync {
+    /// Error type that the resolver returns on internal failure.
+    type Error: IntoAnyError;
+
+    /// Fetch the pre-shared key for `id`.
+    ///
+    /// `None` should be returned if `id` is unknown, in which case the
+    /// operation requiring the key fails.
+    async fn resolve(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error>;
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use alloc::vec;
+
+    use super::PreSharedKey;
+
+    #[test]
+    fn psk_serde_matches_previous_format() {
+        // Serialized with the `zeroizing_serde` representation used before
+        // `PreSharedKey` held `SecretBytes`.
+        let serialized = r#""deadbeef01""#;
+        let psk = PreSharedKey::new(vec![0xde, 0xad, 0xbe, 0xef, 0x01]);
+
+        assert_eq!(serde_json::to_string(&psk).unwrap(), serialized);
+
+        let deserialized: PreSharedKey = serde_json::from_str(serialized).unwrap();
+        assert_eq!(deserialized, psk);
+    }
+}
@@ -7,7 +7,11 @@ use core::{
     fmt::{self, Debug},
     ops::{Deref, DerefMut},
 };
-use zeroize::Zeroizing;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+#[cfg(feature = "secret_allocator")]
+use alloc::boxed::Box;
 
 #[cfg_attr(
     all(feature = "ffi", not(test)),
@@ -55,3 +59,228 @@ impl DerefMut for Secret {
         &mut self.0
     }
 }
+
+/// Hooks used to protect the memory holding long-lived secrets, such as
+/// signature keys, HPKE secret keys, pre-shared keys and key schedule
+/// secrets.
+///
+/// [`protect`](SecretAllocator::protect) is called once a secret is in its
+/// final buffer, which is never resized or moved while the secret is alive.
+/// [`release`](SecretAllocator::release) is called with the same buffer after
+/// it was zeroized and right before it is freed. An implementation would
+/// typically lock the pages of the buffer in memory (e.g. with `mlock` from
+/// the `region` or `memsec` crates) and exclude them from core dumps.
+///
+/// Several secrets may share a memory page, so an implementation locking
+/// pages should keep track of how many live secrets use each page before
+/// unlocking it.
+///
+/// The allocator is installed for the whole process with
+/// [`set_secret_allocator`].
+#[cfg(feature = "secret_allocator")]
+pub trait SecretAllocator: Send + Sync {
+    /// Protect the memory holding a new secret.
+    fn protect(&self, secret: &mut [u8]);
+
+    /// Release the protection of a zeroized secret before it is freed.
+    fn release(&self, secret: &mut [u8]);
+}
+
+#[cfg(feature = "secret_allocator")]
+static SECRET_ALLOCATOR: std::sync::OnceLock<Box<dyn SecretAllocator>> = std::sync::OnceLock::new();
+
+/// Install the [`SecretAllocator`] used for all secrets created afterwards.
+///
+/// The allocator can only be set once. If it was already set, `allocator` is
+/// returned as an error.
+#[cfg(feature = "secret_allocator")]
+pub fn set_secret_allocator(
+    allocator: Box<dyn SecretAllocator>,
+) -> Result<(), Box<dyn SecretAllocator>> {
+    SECRET_ALLOCATOR.set(allocator)
+}
+
+/// Zeroize-on-drop buffer holding a long-lived secret.
+///
+/// The buffer is never resized after creation. With the `secret_allocator`
+/// feature, it is protected with the installed [`SecretAllocator`] for as
+/// long as it lives.
+pub struct SecretBytes {
+    bytes: Vec<u8>,
+    #[cfg(feature = "secret_allocator")]
+    allocator: Option<&'static dyn SecretAllocator>,
+}
+
+impl SecretBytes {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        #[cfg(feature = "secret_allocator")]
+        let mut bytes = bytes;
+
+        #[cfg(feature = "secret_allocator")]
+        let allocator = SECRET_ALLOCATOR
+            .get()
+            .filter(|_| !bytes.is_empty())
+            .map(|allocator| {
+                allocator.protect(&mut bytes);
+                &**allocator
+            });
+
+        Self {
+            bytes,
+            #[cfg(feature = "secret_allocator")]
+            allocator,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.bytes.as_mut_slice().zeroize();
+
+        #[cfg(feature = "secret_allocator")]
+        if let Some(allocator) = self.allocator {
+            allocator.release(&mut self.bytes);
+        }
+
+        self.bytes.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretBytes {}
+
+impl Clone for SecretBytes {
+    fn clone(&self) -> Self {
+        Self::new(self.bytes.clone())
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for SecretBytes {}
+
+impl Default for SecretBytes {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::debug::pretty_bytes(&self.bytes)
+            .named("SecretBytes")
+            .fmt(f)
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<Zeroizing<Vec<u8>>> for SecretBytes {
+    fn from(mut bytes: Zeroizing<Vec<u8>>) -> Self {
+        Self::new(core::mem::take(&mut *bytes))
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for SecretBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl MlsSize for SecretBytes {
+    fn mls_encoded_len(&self) -> usize {
+        mls_rs_codec::byte_vec::mls_encoded_len(self)
+    }
+}
+
+impl MlsEncode for SecretBytes {
+    fn mls_encode(&self, writer: &mut Vec<u8>) -> Result<(), mls_rs_codec::Error> {
+        mls_rs_codec::byte_vec::mls_encode(self, writer)
+    }
+}
+
+impl MlsDecode for SecretBytes {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        mls_rs_codec::byte_vec::mls_decode(reader)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SecretBytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::vec_serde::serialize(&self.bytes, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SecretBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::vec_serde::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SecretBytes {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Vec::<u8>::arbitrary(u).map(Self::new)
+    }
+}
+
+#[cfg(all(test, feature = "secret_allocator"))]
+mod tests {
+    use alloc::{boxed::Box, vec};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{set_secret_allocator, SecretAllocator, SecretBytes};
+
+    static PROTECTED: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountingAllocator;
+
+    impl SecretAllocator for CountingAllocator {
+        fn protect(&self, secret: &mut [u8]) {
+            PROTECTED.fetch_add(secret.len(), Ordering::SeqCst);
+        }
+
+        fn release(&self, secret: &mut [u8]) {
+            assert!(secret.iter().all(|&b| b == 0));
+            PROTECTED.fetch_sub(secret.len(), Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn secrets_are_protected_while_alive() {
+        assert!(set_secret_allocator(Box::new(CountingAllocator)).is_ok());
+        assert!(set_secret_allocator(Box::new(CountingAllocator)).is_err());
+
+        let secret = SecretBytes::from(vec![1u8; 32]);
+        let copy = secret.clone();
+        assert_eq!(PROTECTED.load(Ordering::SeqCst), 64);
+
+        drop(secret);
+        assert_eq!(PROTECTED.load(Ordering::SeqCst), 32);
+        assert_eq!(copy.as_bytes(), &[1u8; 32]);
+
+        drop(copy);
+        assert_eq!(PROTECTED.load(Ordering::SeqCst), 0);
+    }
+}
@@ -402,6 +402,7 @@ fn check_non_null_const<T>(r: *const T) -> Result<*const T, AwsLcCryptoError> {
 #[test]
 fn mls_core_tests() {
     mls_rs_core::crypto::test_suite::verify_tests(&AwsLcCryptoProvider::new(), true);
+    mls_rs_core::crypto::test_suite::verify_hpke_rfc_tests(&AwsLcCryptoProvider::new());
 
     for cs in AwsLcCryptoProvider::new().supported_cipher_suites() {
         let mut hpke = AwsLcCryptoProvider::new()
@@ -19,6 +19,7 @@ use crate::aws_lc_sys_impl::{
     X509_get_issuer_name, X509_get_pubkey, X509_get_serialNumber, X509_get_subject_name, X509_new,
     X509_set_issuer_name, X509_set_notAfter, X509_set_notBefore, X509_set_pubkey,
     X509_set_serialNumber, X509_set_subject_name, X509_set_version, X509_sign, ASN1_TIME, X509,
+    X509_NAME,
 };
 use mls_rs_core::{
     crypto::{CipherSuite, SignaturePublicKey, SignatureSecretKey},
@@ -41,6 +42,14 @@ use super::{
 
 pub struct Certificate(*mut X509);
 
+unsafe fn name_to_der(name: *mut X509_NAME) -> Result<Vec<u8>, AwsLcCryptoError> {
+    let len = check_int_return(i2d_X509_NAME(name, null_mut()))?;
+    let mut out = vec![0u8; len as usize];
+    check_res(i2d_X509_NAME(name, &mut out.as_mut_ptr()))?;
+
+    Ok(out)
+}
+
 impl Certificate {
     pub fn new() -> Result<Self, AwsLcCryptoError> {
         unsafe { check_non_null(X509_new()).map(Self) }
@@ -54,15 +63,11 @@ impl Certificate {
     }
 
     pub fn subject_bytes(&self) -> Result<Vec<u8>, AwsLcCryptoError> {
-        unsafe {
-            let name = check_non_null(X509_get_subject_name(self.0))?;
-
-            let len = check_int_return(i2d_X509_NAME(name, null_mut()))?;
-            let mut out = vec![0u8; len as usize];
-            check_res(i2d_X509_NAME(name, &mut out.as_mut_ptr()))?;
+        unsafe { name_to_der(check_non_null(X509_get_subject_name(self.0))?) }
+    }
 
-            Ok(out)
-        }
+    pub fn issuer_bytes(&self) -> Result<Vec<u8>, AwsLcCryptoError> {
+        unsafe { name_to_der(check_non_null(X509_get_issuer_name(self.0))?) }
     }
 
     pub fn subject_components(&self) -> Result<Vec<SubjectComponent>, AwsLcCryptoError> {
@@ -2,7 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use mls_rs_core::crypto::SignaturePublicKey;
+use mls_rs_core::{crypto::SignaturePublicKey, identity::CredentialValidity};
 use mls_rs_identity_x509::{
     DerCertificate, SubjectAltName, SubjectComponent, X509CertificateReader,
 };
@@ -29,6 +29,11 @@ impl X509CertificateReader for CertificateParser {
         parsed.subject_bytes()
     }
 
+    fn issuer_bytes(&self, certificate: &DerCertificate) -> Result<Option<Vec<u8>>, Self::Error> {
+        let parsed = Certificate::try_from(certificate)?;
+        parsed.issuer_bytes().map(Some)
+    }
+
     #[doc = " Parsed certificate subject field components."]
     fn subject_components(
         &self,
@@ -52,6 +57,17 @@ impl X509CertificateReader for CertificateParser {
         let parsed = Certificate::try_from(certificate)?;
         parsed.public_key()
     }
+
+    fn validity(
+        &self,
+        certificate: &DerCertificate,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        let parsed = Certificate::try_from(certificate)?;
+        Ok(Some(CredentialValidity::new(
+            parsed.not_before()?,
+            parsed.not_after()?,
+        )))
+    }
 }
 
 #[cfg(test)]
@@ -17,6 +17,8 @@ pub enum AeadError {
     InvalidCipherLen(usize),
     #[error("encrypted message cannot be empty")]
     EmptyPlaintext,
+    #[error("AEAD nonce of invalid length {0}. Expected length {1}")]
+    InvalidNonceLen(usize, usize),
     #[error("unsupported cipher suite")]
     UnsupportedCipherSuite,
 }
@@ -60,6 +62,12 @@ impl Aead {
 
         cipher.map(|cipher| Self { cipher, aead_id })
     }
+
+    fn check_nonce_len(&self, nonce: &[u8]) -> Result<(), AeadError> {
+        (nonce.len() == self.nonce_size())
+            .then_some(())
+            .ok_or_else(|| AeadError::InvalidNonceLen(nonce.len(), self.nonce_size()))
+    }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -83,6 +91,8 @@ impl AeadType for Aead {
             .then_some(())
             .ok_or(AeadError::EmptyPlaintext)?;
 
+        self.check_nonce_len(nonce)?;
+
         let mut tag = [0u8; AES_TAG_LEN];
         let aad = aad.unwrap_or_default();
 
@@ -104,6 +114,8 @@ impl AeadType for Aead {
             .then_some(())
             .ok_or(AeadError::InvalidCipherLen(ciphertext.len()))?;
 
+        self.check_nonce_len(nonce)?;
+
         let (data, tag) = ciphertext.split_at(ciphertext.len() - AES_TAG_LEN);
         let aad = aad.unwrap_or_default();
 
@@ -166,6 +178,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn invalid_nonce() {
+        for aead in get_aeads() {
+            let key = vec![42u8; aead.key_size()];
+            let data = b"top secret";
+
+            let too_short = vec![42u8; aead.nonce_size() - 1];
+
+            assert_matches!(
+                aead.seal(&key, data, None, &too_short),
+                Err(AeadError::InvalidNonceLen(_, _))
+            );
+
+            let ciphertext = aead
+                .seal(&key, data, None, &vec![42u8; aead.nonce_size()])
+                .unwrap();
+
+            let too_long = vec![42u8; aead.nonce_size() + 1];
+
+            assert_matches!(
+                aead.open(&key, &ciphertext, None, &too_long),
+                Err(AeadError::InvalidNonceLen(_, _))
+            );
+        }
+    }
+
     #[test]
     fn invalid_ciphertext() {
         for aead in get_aeads() {
@@ -3,8 +3,10 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use core::fmt::{self, Debug};
-use mls_rs_crypto_traits::Curve;
+use mls_rs_core::error::AnyError;
+use mls_rs_crypto_traits::{Curve, RandomSource};
 use thiserror::Error;
+use zeroize::Zeroizing;
 
 use openssl::{
     bn::{BigNum, BigNumContext},
@@ -18,6 +20,10 @@ use openssl::{
 pub type EcPublicKey = PKey<Public>;
 pub type EcPrivateKey = PKey<Private>;
 
+/// Number of times key generation draws new bytes from a [`RandomSource`]
+/// when they are not a valid secret key, before giving up.
+const RANDOM_SOURCE_KEY_ATTEMPTS: usize = 8;
+
 #[derive(Debug, Error)]
 pub enum EcError {
     #[error(transparent)]
@@ -27,6 +33,8 @@ pub enum EcError {
     InvalidKeyBytes,
     #[error("unsupported cipher suite")]
     UnsupportedCipherSuite,
+    #[error(transparent)]
+    RandomSourceError(AnyError),
 }
 
 #[inline(always)]
@@ -39,8 +47,15 @@ fn nist_curve_id(curve: Curve) -> Option<Nid> {
     }
 }
 
-pub fn generate_keypair(curve: Curve) -> Result<KeyPair, EcError> {
-    let secret = generate_private_key(curve)?;
+pub fn generate_keypair(
+    curve: Curve,
+    random_source: Option<&dyn RandomSource>,
+) -> Result<KeyPair, EcError> {
+    let secret = match random_source {
+        Some(source) => generate_private_key_from(curve, source)?,
+        None => generate_private_key(curve)?,
+    };
+
     let public = private_key_to_public(&secret)?;
     let secret = private_key_to_bytes(&secret)?;
     let public = pub_key_to_uncompressed(&public)?;
@@ -129,6 +144,37 @@ pub fn generate_private_key(curve: Curve) -> Result<EcPrivateKey, EcError> {
     Ok(key)
 }
 
+/// Generate a private key from bytes drawn from `source`, drawing again if
+/// they are not a valid secret key for `curve`.
+pub fn generate_private_key_from(
+    curve: Curve,
+    source: &dyn RandomSource,
+) -> Result<EcPrivateKey, EcError> {
+    let seed_size = match curve {
+        Curve::Ed25519 | Curve::Ed448 => curve.secret_key_size() / 2,
+        _ => curve.secret_key_size(),
+    };
+
+    let mut bytes = Zeroizing::new(vec![0u8; seed_size]);
+
+    for _ in 0..RANDOM_SOURCE_KEY_ATTEMPTS {
+        source
+            .fill_bytes(&mut bytes)
+            .map_err(EcError::RandomSourceError)?;
+
+        if let Some(mask) = curve.curve_bitmask() {
+            bytes[0] &= mask;
+        }
+
+        match private_key_from_bytes(&bytes, curve, true) {
+            Err(EcError::InvalidKeyBytes) => continue,
+            res => return res,
+        }
+    }
+
+    Err(EcError::InvalidKeyBytes)
+}
+
 fn private_key_from_bytes_nist(
     bytes: &[u8],
     nid: Nid,
@@ -396,7 +442,7 @@ mod tests {
     fn key_pair_can_be_generated() {
         SUPPORTED_CURVES.iter().copied().for_each(|curve| {
             assert_matches!(
-                generate_keypair(curve),
+                generate_keypair(curve, None),
                 Ok(_),
                 "Failed to generate key pair for {curve:?}"
             );
@@ -5,7 +5,7 @@
 use std::ops::Deref;
 
 use mls_rs_core::crypto::{CipherSuite, SignaturePublicKey, SignatureSecretKey};
-use mls_rs_crypto_traits::Curve;
+use mls_rs_crypto_traits::{Curve, SharedRandomSource};
 use openssl::hash::MessageDigest;
 
 #[cfg(feature = "x509")]
@@ -29,8 +29,8 @@ pub enum EcSignerError {
     InvalidSignature,
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
-pub struct EcSigner(Curve);
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcSigner(Curve, Option<SharedRandomSource>);
 
 impl Deref for EcSigner {
     type Target = Curve;
@@ -42,13 +42,19 @@ impl Deref for EcSigner {
 
 impl EcSigner {
     pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
-        Curve::from_ciphersuite(cipher_suite, true).map(Self)
+        Curve::from_ciphersuite(cipher_suite, true).map(|curve| Self(curve, None))
+    }
+
+    /// Generate keys with `random_source` instead of the random number
+    /// generator of OpenSSL.
+    pub fn with_random_source(self, random_source: Option<SharedRandomSource>) -> Self {
+        Self(self.0, random_source)
     }
 
     pub fn signature_key_generate(
         &self,
     ) -> Result<(SignatureSecretKey, SignaturePublicKey), EcSignerError> {
-        let key_pair = generate_keypair(self.0)?;
+        let key_pair = generate_keypair(self.0, self.1.as_deref())?;
         Ok((key_pair.secret.into(), key_pair.public.into()))
     }
 
@@ -163,7 +169,7 @@ mod test {
         let der_keys = get_test_public_keys_der();
 
         let convert = |keys: &TestKeys, curve: Curve| {
-            EcSigner(curve)
+            EcSigner(curve, None)
                 .signature_key_import_der_public(&keys.get_key_from_curve(curve))
                 .unwrap()
         };
@@ -187,7 +193,7 @@ mod test {
         let der_keys = get_test_secret_keys_der();
 
         let convert = |keys: &TestKeys, curve: Curve| {
-            EcSigner(curve)
+            EcSigner(curve, None)
                 .signature_key_import_der_private(&keys.get_key_from_curve(curve))
                 .unwrap()
         };
@@ -4,7 +4,7 @@
 
 use std::ops::Deref;
 
-use mls_rs_crypto_traits::{Curve, DhType};
+use mls_rs_crypto_traits::{Curve, DhType, SharedRandomSource};
 use thiserror::Error;
 
 use mls_rs_core::{
@@ -34,7 +34,7 @@ impl IntoAnyError for EcdhKemError {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Ecdh(Curve);
+pub struct Ecdh(Curve, Option<SharedRandomSource>);
 
 impl Deref for Ecdh {
     type Target = Curve;
@@ -46,7 +46,13 @@ impl Deref for Ecdh {
 
 impl Ecdh {
     pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
-        Curve::from_ciphersuite(cipher_suite, false).map(Self)
+        Curve::from_ciphersuite(cipher_suite, false).map(|curve| Self(curve, None))
+    }
+
+    /// Generate keys with `random_source` instead of the random number
+    /// generator of OpenSSL.
+    pub fn with_random_source(self, random_source: Option<SharedRandomSource>) -> Self {
+        Self(self.0, random_source)
     }
 }
 
@@ -75,7 +81,7 @@ impl DhType for Ecdh {
     }
 
     async fn generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
-        let key_pair = generate_keypair(self.0)?;
+        let key_pair = generate_keypair(self.0, self.1.as_deref())?;
         Ok((key_pair.secret.into(), key_pair.public.into()))
     }
 
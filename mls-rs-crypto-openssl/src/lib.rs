@@ -18,7 +18,7 @@ use mls_rs_crypto_hpke::{
     dhkem::DhKem,
     hpke::{Hpke, HpkeError},
 };
-use mls_rs_crypto_traits::{AeadType, KdfType, KemId, KemType};
+use mls_rs_crypto_traits::{AeadType, KdfType, KemId, KemType, RandomSource, SharedRandomSource};
 
 use ec::EcError;
 use ec_signer::{EcSigner, EcSignerError};
@@ -55,6 +55,8 @@ pub enum OpensslCryptoError {
     OpensslError(#[from] ErrorStack),
     #[error(transparent)]
     EcError(#[from] EcError),
+    #[error(transparent)]
+    RandomSourceError(AnyError),
 }
 
 impl IntoAnyError for OpensslCryptoError {
@@ -67,6 +69,7 @@ impl IntoAnyError for OpensslCryptoError {
 #[non_exhaustive]
 pub struct OpensslCryptoProvider {
     pub enabled_cipher_suites: Vec<CipherSuite>,
+    random_source: Option<SharedRandomSource>,
 }
 
 impl OpensslCryptoProvider {
@@ -77,9 +80,49 @@ impl OpensslCryptoProvider {
     pub fn with_enabled_cipher_suites(enabled_cipher_suites: Vec<CipherSuite>) -> Self {
         Self {
             enabled_cipher_suites,
+            random_source: None,
         }
     }
 
+    /// Draw random bytes and the randomness of key generation from
+    /// `random_source` instead of the random number generator of OpenSSL.
+    /// The health tests of `random_source` are run before it is used.
+    ///
+    /// Nonces of ECDSA signatures are still generated by OpenSSL, whose own
+    /// DRBG can be configured through an OpenSSL FIPS provider.
+    pub fn with_random_source<R: RandomSource + 'static>(
+        self,
+        random_source: R,
+    ) -> Result<Self, OpensslCryptoError> {
+        random_source
+            .health_check()
+            .map_err(OpensslCryptoError::RandomSourceError)?;
+
+        Ok(Self {
+            random_source: Some(SharedRandomSource::new(random_source)),
+            ..self
+        })
+    }
+
+    /// Run the health tests of the source set with
+    /// [`with_random_source`](Self::with_random_source), if any.
+    pub fn random_source_health_check(&self) -> Result<(), OpensslCryptoError> {
+        self.random_source
+            .as_ref()
+            .map_or(Ok(()), |source| source.health_check())
+            .map_err(OpensslCryptoError::RandomSourceError)
+    }
+
+    /// Reseed the source set with
+    /// [`with_random_source`](Self::with_random_source), if any. The random
+    /// number generator of OpenSSL reseeds itself.
+    pub fn reseed_random_source(&self, additional_input: &[u8]) -> Result<(), OpensslCryptoError> {
+        self.random_source
+            .as_ref()
+            .map_or(Ok(()), |source| source.reseed(additional_input))
+            .map_err(OpensslCryptoError::RandomSourceError)
+    }
+
     pub fn all_supported_cipher_suites() -> Vec<CipherSuite> {
         CipherSuite::all().collect()
     }
@@ -87,9 +130,7 @@ impl OpensslCryptoProvider {
 
 impl Default for OpensslCryptoProvider {
     fn default() -> Self {
-        Self {
-            enabled_cipher_suites: Self::all_supported_cipher_suites(),
-        }
+        Self::with_enabled_cipher_suites(Self::all_supported_cipher_suites())
     }
 }
 
@@ -109,12 +150,13 @@ impl CryptoProvider for OpensslCryptoProvider {
         }
 
         let kdf = Kdf::new(cipher_suite)?;
-        let ecdh = Ecdh::new(cipher_suite)?;
+        let ecdh = Ecdh::new(cipher_suite)?.with_random_source(self.random_source.clone());
         let kem_id = KemId::new(cipher_suite)?;
         let kem = DhKem::new(ecdh, kdf.clone(), kem_id as u16, kem_id.n_secret());
         let aead = Aead::new(cipher_suite)?;
 
         OpensslCipherSuite::new(cipher_suite, kem, kdf, aead)
+            .map(|cs| cs.with_random_source(self.random_source.clone()))
     }
 }
 
@@ -131,6 +173,7 @@ where
     hash: Hash,
     hpke: Hpke<KEM, KDF, AEAD>,
     ec_signer: EcSigner,
+    random_source: Option<SharedRandomSource>,
 }
 
 impl<KEM, KDF, AEAD> OpensslCipherSuite<KEM, KDF, AEAD>
@@ -149,11 +192,29 @@ where
             hash: Hash::new(cipher_suite).ok()?,
             hpke,
             ec_signer: EcSigner::new(cipher_suite)?,
+            random_source: None,
         })
     }
 
+    /// Draw random bytes and signature keys from `random_source` instead of
+    /// the random number generator of OpenSSL. HPKE keys are generated by the
+    /// KEM, which is configured separately, for example with
+    /// [`Ecdh::with_random_source`].
+    pub fn with_random_source(self, random_source: Option<SharedRandomSource>) -> Self {
+        Self {
+            ec_signer: self.ec_signer.with_random_source(random_source.clone()),
+            random_source,
+            ..self
+        }
+    }
+
     pub fn random_bytes(&self, out: &mut [u8]) -> Result<(), OpensslCryptoError> {
-        Ok(openssl::rand::rand_bytes(out)?)
+        match &self.random_source {
+            Some(source) => source
+                .fill_bytes(out)
+                .map_err(OpensslCryptoError::RandomSourceError),
+            None => Ok(openssl::rand::rand_bytes(out)?),
+        }
     }
 
     pub fn import_der_public_signing_key(
@@ -360,6 +421,48 @@ where
     }
 }
 
+#[cfg(test)]
+mod random_source_tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider};
+    use mls_rs_crypto_traits::test_utils::CountingRandomSource;
+
+    use crate::{OpensslCryptoError, OpensslCryptoProvider};
+
+    #[cfg(not(mls_build_async))]
+    #[test]
+    fn randomness_is_drawn_from_random_source() {
+        for cs in [CipherSuite::CURVE25519_AES128, CipherSuite::P521_AES256] {
+            let outputs = [
+                CountingRandomSource::healthy(),
+                CountingRandomSource::healthy(),
+            ]
+            .map(|source| {
+                let provider = OpensslCryptoProvider::new()
+                    .with_random_source(source)
+                    .unwrap();
+
+                let cs = provider.cipher_suite_provider(cs).unwrap();
+
+                (
+                    cs.random_bytes_vec(16).unwrap(),
+                    cs.signature_key_generate().unwrap(),
+                    cs.kem_generate().unwrap(),
+                )
+            });
+
+            assert_eq!(outputs[0], outputs[1]);
+            assert_eq!(outputs[0].0, [1; 16]);
+        }
+    }
+
+    #[test]
+    fn unhealthy_random_source_is_rejected() {
+        let res = OpensslCryptoProvider::new().with_random_source(CountingRandomSource::default());
+        assert_matches!(res, Err(OpensslCryptoError::RandomSourceError(_)));
+    }
+}
+
 #[cfg(not(mls_build_async))]
 #[test]
 fn mls_core_tests() {
@@ -368,6 +471,7 @@ fn mls_core_tests() {
     let provider = OpensslCryptoProvider::new();
 
     mls_rs_core::crypto::test_suite::verify_tests(&provider, true);
+    mls_rs_core::crypto::test_suite::verify_hpke_rfc_tests(&provider);
 
     for cs in OpensslCryptoProvider::all_supported_cipher_suites() {
         let mut hpke = provider.cipher_suite_provider(cs).unwrap().hpke;
@@ -7,7 +7,8 @@ use std::{net::IpAddr, ops::Deref};
 use mls_rs_core::{
     crypto::{CipherSuite, SignaturePublicKey, SignatureSecretKey},
     error::IntoAnyError,
-    identity::{CertificateChain, SigningIdentity},
+    identity::{CertificateChain, CredentialValidity, SigningIdentity},
+    time::MlsTime,
 };
 use mls_rs_identity_x509::{
     CertificateRequestParameters, DerCertificate, DerCertificateRequest, SubjectAltName,
@@ -15,6 +16,7 @@ use mls_rs_identity_x509::{
     X509RequestWriter,
 };
 use openssl::{
+    asn1::{Asn1Time, Asn1TimeRef},
     bn::BigNumContext,
     ec::PointConversionForm,
     error::ErrorStack,
@@ -203,6 +205,14 @@ impl mls_rs_identity_x509::X509CertificateReader for X509Reader {
             .map_err(Into::into)
     }
 
+    fn issuer_bytes(&self, certificate: &DerCertificate) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.parse_certificate(certificate)?
+            .issuer_name()
+            .to_der()
+            .map(Some)
+            .map_err(Into::into)
+    }
+
     fn subject_components(
         &self,
         certificate: &DerCertificate,
@@ -280,6 +290,27 @@ impl mls_rs_identity_x509::X509CertificateReader for X509Reader {
         let public_key = self.parse_certificate(certificate)?.public_key()?;
         pub_key_to_uncompressed(public_key).map(Into::into)
     }
+
+    fn validity(
+        &self,
+        certificate: &DerCertificate,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        let certificate = self.parse_certificate(certificate)?;
+
+        Ok(Some(CredentialValidity::new(
+            asn1_time_to_mls_time(certificate.not_before())?,
+            asn1_time_to_mls_time(certificate.not_after())?,
+        )))
+    }
+}
+
+fn asn1_time_to_mls_time(time: &Asn1TimeRef) -> Result<MlsTime, X509Error> {
+    let diff = Asn1Time::from_unix(0)?.diff(time)?;
+    let seconds = i64::from(diff.days) * 86400 + i64::from(diff.secs);
+
+    u64::try_from(seconds)
+        .map(MlsTime::from)
+        .map_err(|_| X509Error::InvalidCertificateLifetime)
 }
 
 fn ip_bytes_to_ip_addr(input: &[u8]) -> Option<IpAddr> {
@@ -580,6 +611,7 @@ mod tests {
         SubjectComponent, X509CertificateReader, X509RequestWriter,
     };
     use openssl::{
+        asn1::Asn1Time,
         pkey::PKey,
         x509::{X509Name, X509Req, X509},
     };
@@ -593,7 +625,7 @@ mod tests {
     };
 
     use super::{
-        pub_key_to_uncompressed,
+        asn1_time_to_mls_time, pub_key_to_uncompressed,
         test_utils::{
             load_github_leaf, load_ip_cert, load_test_ca, load_test_cert_chain,
             load_test_system_cert_chain,
@@ -601,6 +633,20 @@ mod tests {
         X509Error, X509Reader, X509Validator,
     };
 
+    #[test]
+    fn asn1_time_before_unix_epoch_is_rejected() {
+        let time = Asn1Time::from_unix(1_700_000_000).unwrap();
+        let expected = MlsTime::from(1_700_000_000);
+        assert_eq!(asn1_time_to_mls_time(&time).unwrap(), expected);
+
+        let time = Asn1Time::from_unix(-86_400).unwrap();
+
+        assert_matches!(
+            asn1_time_to_mls_time(&time),
+            Err(X509Error::InvalidCertificateLifetime)
+        );
+    }
+
     #[test]
     fn can_detect_invalid_ca_certificates() {
         assert_matches!(
@@ -31,6 +31,11 @@ pub enum AeadError {
         error("AEAD key of invalid length {0}. Expected length {1}")
     )]
     InvalidKeyLen(usize, usize),
+    #[cfg_attr(
+        feature = "std",
+        error("AEAD nonce of invalid length {0}. Expected length {1}")
+    )]
+    InvalidNonceLen(usize, usize),
     #[cfg_attr(feature = "std", error("unsupported cipher suite"))]
     UnsupportedCipherSuite,
 }
@@ -81,6 +86,10 @@ impl AeadType for Aead {
             .then_some(())
             .ok_or_else(|| AeadError::InvalidKeyLen(key.len(), self.key_size()))?;
 
+        (nonce.len() == self.nonce_size())
+            .then_some(())
+            .ok_or_else(|| AeadError::InvalidNonceLen(nonce.len(), self.nonce_size()))?;
+
         match self.0 {
             AeadId::Aes128Gcm => {
                 let cipher = Aes128Gcm::new(GenericArray::from_slice(key));
@@ -114,6 +123,10 @@ impl AeadType for Aead {
             .then_some(())
             .ok_or_else(|| AeadError::InvalidKeyLen(key.len(), self.key_size()))?;
 
+        (nonce.len() == self.nonce_size())
+            .then_some(())
+            .ok_or_else(|| AeadError::InvalidNonceLen(nonce.len(), self.nonce_size()))?;
+
         match self.0 {
             AeadId::Aes128Gcm => {
                 let cipher = Aes128Gcm::new(GenericArray::from_slice(key));
@@ -218,6 +231,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn invalid_nonce() {
+        for aead in get_aeads() {
+            let key = vec![42u8; aead.key_size()];
+            let data = b"top secret";
+
+            let too_short = vec![42u8; aead.nonce_size() - 1];
+
+            assert_matches!(
+                aead.seal(&key, data, None, &too_short),
+                Err(AeadError::InvalidNonceLen(_, _))
+            );
+
+            let ciphertext = aead
+                .seal(&key, data, None, &vec![42u8; aead.nonce_size()])
+                .unwrap();
+
+            let too_long = vec![42u8; aead.nonce_size() + 1];
+
+            assert_matches!(
+                aead.open(&key, &ciphertext, None, &too_long),
+                Err(AeadError::InvalidNonceLen(_, _))
+            );
+        }
+    }
+
     #[test]
     fn invalid_ciphertext() {
         for aead in get_aeads() {
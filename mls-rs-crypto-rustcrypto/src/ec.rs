@@ -5,8 +5,9 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use alloc::vec::Vec;
-use mls_rs_crypto_traits::Curve;
+use alloc::{vec, vec::Vec};
+use mls_rs_core::error::AnyError;
+use mls_rs_crypto_traits::{Curve, RandomSource};
 
 #[cfg(feature = "std")]
 use std::array::TryFromSliceError;
@@ -18,6 +19,11 @@ use core::fmt::{self, Debug};
 use ed25519_dalek::Signer;
 use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
 use rand_core::OsRng;
+use zeroize::Zeroizing;
+
+/// Number of times key generation draws new bytes from a [`RandomSource`]
+/// when they are not a valid secret key, before giving up.
+const RANDOM_SOURCE_KEY_ATTEMPTS: usize = 8;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum EcPublicKey {
@@ -51,6 +57,8 @@ pub enum EcError {
     SignatureError(p256::ecdsa::Error),
     #[cfg_attr(feature = "std", error("rand error: {0:?}"))]
     RandCoreError(rand_core::Error),
+    #[cfg_attr(feature = "std", error(transparent))]
+    RandomSourceError(AnyError),
     #[cfg_attr(feature = "std", error("ecdh key type mismatch"))]
     EcdhKeyTypeMismatch,
     #[cfg_attr(feature = "std", error("ec key is not an ecdh key"))]
@@ -150,6 +158,42 @@ pub fn generate_private_key(curve: Curve) -> Result<EcPrivateKey, EcError> {
     }
 }
 
+/// Generate a private key from bytes drawn from `source`, drawing again if
+/// they are not a valid secret key for `curve`.
+pub fn generate_private_key_from(
+    curve: Curve,
+    source: &dyn RandomSource,
+) -> Result<EcPrivateKey, EcError> {
+    let mut bytes = Zeroizing::new(vec![0u8; private_key_seed_size(curve)?]);
+
+    for _ in 0..RANDOM_SOURCE_KEY_ATTEMPTS {
+        source
+            .fill_bytes(&mut bytes)
+            .map_err(EcError::RandomSourceError)?;
+
+        let key = match curve {
+            Curve::Ed25519 => Ok(EcPrivateKey::Ed25519(
+                ed25519_dalek::SigningKey::from_bytes(bytes.as_slice().try_into()?),
+            )),
+            _ => private_key_from_bytes(&bytes, curve),
+        };
+
+        if let Ok(key) = key {
+            return Ok(key);
+        }
+    }
+
+    Err(EcError::EcKeyInvalidKeyData)
+}
+
+fn private_key_seed_size(curve: Curve) -> Result<usize, EcError> {
+    match curve {
+        Curve::P256 | Curve::X25519 | Curve::Ed25519 => Ok(32),
+        Curve::P384 => Ok(48),
+        _ => Err(EcError::UnsupportedCurve),
+    }
+}
+
 pub fn private_key_from_bytes(bytes: &[u8], curve: Curve) -> Result<EcPrivateKey, EcError> {
     match curve {
         Curve::P256 => p256::SecretKey::from_slice(bytes)
@@ -312,8 +356,15 @@ pub fn verify_ed25519(
     Ok(ed25519_dalek::Verifier::verify(public_key, data, &signature).is_ok())
 }
 
-pub fn generate_keypair(curve: Curve) -> Result<KeyPair, EcError> {
-    let secret = generate_private_key(curve)?;
+pub fn generate_keypair(
+    curve: Curve,
+    random_source: Option<&dyn RandomSource>,
+) -> Result<KeyPair, EcError> {
+    let secret = match random_source {
+        Some(source) => generate_private_key_from(curve, source)?,
+        None => generate_private_key(curve)?,
+    };
+
     let public = private_key_to_public(&secret)?;
     let secret = private_key_to_bytes(&secret)?;
     let public = pub_key_to_uncompressed(&public)?;
@@ -441,7 +492,7 @@ mod tests {
     fn key_pair_can_be_generated() {
         SUPPORTED_CURVES.iter().copied().for_each(|curve| {
             assert_matches!(
-                generate_keypair(curve),
+                generate_keypair(curve, None),
                 Ok(_),
                 "Failed to generate key pair for {curve:?}"
             );
@@ -10,7 +10,7 @@ use crate::ec::{
 use alloc::vec::Vec;
 use core::ops::Deref;
 use mls_rs_core::crypto::{CipherSuite, SignaturePublicKey, SignatureSecretKey};
-use mls_rs_crypto_traits::Curve;
+use mls_rs_crypto_traits::{Curve, SharedRandomSource};
 
 #[derive(Debug)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
@@ -29,8 +29,8 @@ impl From<EcError> for EcSignerError {
     }
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
-pub struct EcSigner(Curve);
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcSigner(Curve, Option<SharedRandomSource>);
 
 impl Deref for EcSigner {
     type Target = Curve;
@@ -42,17 +42,23 @@ impl Deref for EcSigner {
 
 impl EcSigner {
     pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
-        Curve::from_ciphersuite(cipher_suite, true).map(Self)
+        Curve::from_ciphersuite(cipher_suite, true).map(Self::new_from_curve)
     }
 
     pub fn new_from_curve(curve: Curve) -> Self {
-        Self(curve)
+        Self(curve, None)
+    }
+
+    /// Generate keys with `random_source` instead of the random number
+    /// generator of the operating system.
+    pub fn with_random_source(self, random_source: Option<SharedRandomSource>) -> Self {
+        Self(self.0, random_source)
     }
 
     pub fn signature_key_generate(
         &self,
     ) -> Result<(SignatureSecretKey, SignaturePublicKey), EcSignerError> {
-        let key_pair = generate_keypair(self.0)?;
+        let key_pair = generate_keypair(self.0, self.1.as_deref())?;
         Ok((key_pair.secret.into(), key_pair.public.into()))
     }
 
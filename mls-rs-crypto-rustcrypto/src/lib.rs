@@ -28,7 +28,7 @@ use mls_rs_crypto_hpke::{
     dhkem::DhKem,
     hpke::{Hpke, HpkeError},
 };
-use mls_rs_crypto_traits::{AeadType, KdfType, KemId, KemType};
+use mls_rs_crypto_traits::{AeadType, KdfType, KemId, KemType, RandomSource, SharedRandomSource};
 use rand_core::{OsRng, RngCore};
 
 use mls_rs_core::{
@@ -61,6 +61,8 @@ pub enum RustCryptoError {
     RandError(rand_core::Error),
     #[cfg_attr(feature = "std", error(transparent))]
     EcSignerError(EcSignerError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    RandomSourceError(AnyError),
 }
 
 impl From<rand_core::Error> for RustCryptoError {
@@ -98,6 +100,7 @@ impl IntoAnyError for RustCryptoError {
 #[non_exhaustive]
 pub struct RustCryptoProvider {
     pub enabled_cipher_suites: Vec<CipherSuite>,
+    random_source: Option<SharedRandomSource>,
 }
 
 impl RustCryptoProvider {
@@ -108,9 +111,47 @@ impl RustCryptoProvider {
     pub fn with_enabled_cipher_suites(enabled_cipher_suites: Vec<CipherSuite>) -> Self {
         Self {
             enabled_cipher_suites,
+            random_source: None,
         }
     }
 
+    /// Draw all randomness, including the randomness of key generation, from
+    /// `random_source` instead of the random number generator of the
+    /// operating system. The health tests of `random_source` are run before
+    /// it is used.
+    pub fn with_random_source<R: RandomSource + 'static>(
+        self,
+        random_source: R,
+    ) -> Result<Self, RustCryptoError> {
+        random_source
+            .health_check()
+            .map_err(RustCryptoError::RandomSourceError)?;
+
+        Ok(Self {
+            random_source: Some(SharedRandomSource::new(random_source)),
+            ..self
+        })
+    }
+
+    /// Run the health tests of the source set with
+    /// [`with_random_source`](Self::with_random_source), if any.
+    pub fn random_source_health_check(&self) -> Result<(), RustCryptoError> {
+        self.random_source
+            .as_ref()
+            .map_or(Ok(()), |source| source.health_check())
+            .map_err(RustCryptoError::RandomSourceError)
+    }
+
+    /// Reseed the source set with
+    /// [`with_random_source`](Self::with_random_source), if any. The random
+    /// number generator of the operating system reseeds itself.
+    pub fn reseed_random_source(&self, additional_input: &[u8]) -> Result<(), RustCryptoError> {
+        self.random_source
+            .as_ref()
+            .map_or(Ok(()), |source| source.reseed(additional_input))
+            .map_err(RustCryptoError::RandomSourceError)
+    }
+
     pub fn all_supported_cipher_suites() -> Vec<CipherSuite> {
         vec![
             CipherSuite::P256_AES128,
@@ -123,9 +164,7 @@ impl RustCryptoProvider {
 
 impl Default for RustCryptoProvider {
     fn default() -> Self {
-        Self {
-            enabled_cipher_suites: Self::all_supported_cipher_suites(),
-        }
+        Self::with_enabled_cipher_suites(Self::all_supported_cipher_suites())
     }
 }
 
@@ -145,12 +184,13 @@ impl CryptoProvider for RustCryptoProvider {
         }
 
         let kdf = Kdf::new(cipher_suite)?;
-        let ecdh = Ecdh::new(cipher_suite)?;
+        let ecdh = Ecdh::new(cipher_suite)?.with_random_source(self.random_source.clone());
         let kem_id = KemId::new(cipher_suite)?;
         let kem = DhKem::new(ecdh, kdf, kem_id as u16, kem_id.n_secret());
         let aead = Aead::new(cipher_suite)?;
 
         RustCryptoCipherSuite::new(cipher_suite, kem, kdf, aead)
+            .map(|cs| cs.with_random_source(self.random_source.clone()))
     }
 }
 
@@ -167,6 +207,7 @@ where
     hash: Hash,
     hpke: Hpke<KEM, KDF, AEAD>,
     ec_signer: EcSigner,
+    random_source: Option<SharedRandomSource>,
 }
 
 impl<KEM, KDF, AEAD> RustCryptoCipherSuite<KEM, KDF, AEAD>
@@ -185,11 +226,29 @@ where
             hash: Hash::new(cipher_suite).ok()?,
             hpke,
             ec_signer: EcSigner::new(cipher_suite)?,
+            random_source: None,
         })
     }
 
+    /// Draw random bytes and signature keys from `random_source` instead of
+    /// the random number generator of the operating system. HPKE keys are
+    /// generated by the KEM, which is configured separately, for example with
+    /// [`Ecdh::with_random_source`].
+    pub fn with_random_source(self, random_source: Option<SharedRandomSource>) -> Self {
+        Self {
+            ec_signer: self.ec_signer.with_random_source(random_source.clone()),
+            random_source,
+            ..self
+        }
+    }
+
     pub fn random_bytes(&self, out: &mut [u8]) -> Result<(), RustCryptoError> {
-        OsRng.try_fill_bytes(out).map_err(Into::into)
+        match &self.random_source {
+            Some(source) => source
+                .fill_bytes(out)
+                .map_err(RustCryptoError::RandomSourceError),
+            None => OsRng.try_fill_bytes(out).map_err(Into::into),
+        }
     }
 }
 
@@ -383,6 +442,7 @@ where
 fn mls_core_tests() {
     let provider = RustCryptoProvider::new();
     mls_rs_core::crypto::test_suite::verify_tests(&provider, true);
+    mls_rs_core::crypto::test_suite::verify_hpke_rfc_tests(&provider);
 
     for cs in RustCryptoProvider::all_supported_cipher_suites() {
         let mut hpke = provider.cipher_suite_provider(cs).unwrap().hpke;
@@ -392,6 +452,48 @@ fn mls_core_tests() {
     }
 }
 
+#[cfg(all(test, feature = "std"))]
+mod random_source_tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider};
+    use mls_rs_crypto_traits::test_utils::CountingRandomSource;
+
+    use crate::{RustCryptoError, RustCryptoProvider};
+
+    #[cfg(not(mls_build_async))]
+    #[test]
+    fn randomness_is_drawn_from_random_source() {
+        let outputs = [
+            CountingRandomSource::healthy(),
+            CountingRandomSource::healthy(),
+        ]
+        .map(|source| {
+            let provider = RustCryptoProvider::new()
+                .with_random_source(source)
+                .unwrap();
+
+            let cs = provider
+                .cipher_suite_provider(CipherSuite::CURVE25519_AES128)
+                .unwrap();
+
+            (
+                cs.random_bytes_vec(16).unwrap(),
+                cs.signature_key_generate().unwrap(),
+                cs.kem_generate().unwrap(),
+            )
+        });
+
+        assert_eq!(outputs[0], outputs[1]);
+        assert_eq!(outputs[0].0, [1; 16]);
+    }
+
+    #[test]
+    fn unhealthy_random_source_is_rejected() {
+        let res = RustCryptoProvider::new().with_random_source(CountingRandomSource::default());
+        assert_matches!(res, Err(RustCryptoError::RandomSourceError(_)));
+    }
+}
+
 #[cfg(all(test, target_arch = "wasm32"))]
 #[wasm_bindgen_test::wasm_bindgen_test]
 async fn mls_rs_core_test() {
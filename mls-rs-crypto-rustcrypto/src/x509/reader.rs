@@ -2,6 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use mls_rs_core::{identity::CredentialValidity, time::MlsTime};
 use mls_rs_identity_x509::{
     DerCertificate, SubjectAltName as MlsSubjectAltName, SubjectComponent, X509CertificateReader,
 };
@@ -39,6 +40,15 @@ impl X509CertificateReader for X509Reader {
             .map_err(Into::into)
     }
 
+    fn issuer_bytes(&self, certificate: &DerCertificate) -> Result<Option<Vec<u8>>, Self::Error> {
+        Certificate::from_der(certificate)?
+            .tbs_certificate
+            .issuer
+            .to_der()
+            .map(Some)
+            .map_err(Into::into)
+    }
+
     fn subject_components(
         &self,
         certificate: &DerCertificate,
@@ -79,6 +89,18 @@ impl X509CertificateReader for X509Reader {
             .map_err(Into::into)
             .map(Into::into)
     }
+
+    fn validity(
+        &self,
+        certificate: &DerCertificate,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        let validity = Certificate::from_der(certificate)?.tbs_certificate.validity;
+
+        Ok(Some(CredentialValidity::new(
+            MlsTime::from_duration_since_epoch(validity.not_before.to_unix_duration()),
+            MlsTime::from_duration_since_epoch(validity.not_after.to_unix_duration()),
+        )))
+    }
 }
 
 #[cfg(test)]
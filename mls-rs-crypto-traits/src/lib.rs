@@ -10,12 +10,17 @@ mod dh;
 mod ec;
 mod kdf;
 mod kem;
+mod random;
 
 pub use aead::{AeadId, AeadType, AEAD_ID_EXPORT_ONLY, AES_TAG_LEN};
 pub use dh::DhType;
 pub use ec::Curve;
 pub use kdf::{KdfId, KdfType};
 pub use kem::{KemId, KemResult, KemType};
+pub use random::{RandomSource, SharedRandomSource};
 
 #[cfg(feature = "mock")]
 pub mod mock;
+
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
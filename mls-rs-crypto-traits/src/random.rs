@@ -0,0 +1,63 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::{
+    fmt::{self, Debug},
+    ops::Deref,
+};
+
+use alloc::sync::Arc;
+use mls_rs_core::error::AnyError;
+
+/// Source of randomness used by a crypto provider in place of the random
+/// number generator of the operating system, for example a DRBG approved for
+/// a certified deployment.
+pub trait RandomSource: Send + Sync {
+    /// Fill `out` with random bytes.
+    fn fill_bytes(&self, out: &mut [u8]) -> Result<(), AnyError>;
+
+    /// Run the health tests of the source, failing if its output can't be
+    /// trusted.
+    fn health_check(&self) -> Result<(), AnyError> {
+        Ok(())
+    }
+
+    /// Reseed the source with fresh entropy, mixing in `additional_input`.
+    fn reseed(&self, _additional_input: &[u8]) -> Result<(), AnyError> {
+        Ok(())
+    }
+}
+
+/// [`RandomSource`] shared between a crypto provider and the cipher suite
+/// providers it creates.
+#[derive(Clone)]
+pub struct SharedRandomSource(Arc<dyn RandomSource>);
+
+impl SharedRandomSource {
+    pub fn new<R: RandomSource + 'static>(source: R) -> Self {
+        Self(Arc::new(source))
+    }
+}
+
+impl Deref for SharedRandomSource {
+    type Target = dyn RandomSource;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl Debug for SharedRandomSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedRandomSource")
+    }
+}
+
+impl PartialEq for SharedRandomSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0).cast::<()>() == Arc::as_ptr(&other.0).cast::<()>()
+    }
+}
+
+impl Eq for SharedRandomSource {}
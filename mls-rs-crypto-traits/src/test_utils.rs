@@ -0,0 +1,57 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use mls_rs_core::error::{AnyError, IntoAnyError};
+
+use crate::RandomSource;
+
+#[derive(Debug)]
+pub struct HealthCheckError;
+
+impl std::error::Error for HealthCheckError {}
+
+impl std::fmt::Display for HealthCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("health check failed")
+    }
+}
+
+impl IntoAnyError for HealthCheckError {
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// Deterministic [`RandomSource`] filling each request with a counter,
+/// starting at 1. The default source fails its health check.
+#[derive(Debug, Default)]
+pub struct CountingRandomSource {
+    counter: AtomicU8,
+    healthy: bool,
+}
+
+impl CountingRandomSource {
+    /// Source passing its health check.
+    pub fn healthy() -> Self {
+        Self {
+            healthy: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl RandomSource for CountingRandomSource {
+    fn fill_bytes(&self, out: &mut [u8]) -> Result<(), AnyError> {
+        out.fill(self.counter.fetch_add(1, Ordering::SeqCst) + 1);
+        Ok(())
+    }
+
+    fn health_check(&self) -> Result<(), AnyError> {
+        self.healthy
+            .then_some(())
+            .ok_or_else(|| HealthCheckError.into_any_error())
+    }
+}
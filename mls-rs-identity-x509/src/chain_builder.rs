@@ -0,0 +1,286 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{vec, vec::Vec};
+use mls_rs_core::{crypto::SignaturePublicKey, error::IntoAnyError, time::MlsTime};
+
+use crate::{
+    CertificateChain, DerCertificate, X509CertificateReader, X509CredentialValidator,
+    X509IdentityError, X509IdentityExtractor, X509IdentityProvider,
+};
+
+/// Maximum number of certificates in a chain built by
+/// [`ChainBuildingValidator`].
+pub const MAX_BUILT_CHAIN_LENGTH: usize = 8;
+
+/// Maximum number of candidate chains validated by
+/// [`ChainBuildingValidator`] for one credential.
+pub const MAX_CHAIN_BUILDING_ATTEMPTS: usize = 32;
+
+#[derive(Clone, Debug)]
+/// [`X509CredentialValidator`] completing certificate chains with a pool of
+/// intermediate certificates before validating them.
+///
+/// Credentials are often issued with only the leaf certificate, or with an
+/// incomplete chain. The chain in the credential is validated first and, if
+/// that fails, it is extended with intermediates from the pool whose subject
+/// matches the issuer of the last certificate, in the same way web PKI
+/// clients build paths. Every candidate chain is validated by the wrapped
+/// validator, so intermediates are never trusted on their own.
+///
+/// Each intermediate of the pool is tried at most once per credential, and
+/// at most [`MAX_CHAIN_BUILDING_ATTEMPTS`] candidate chains are validated, so
+/// that a pool with many cross-signed intermediates or intermediates sharing
+/// a subject does not make validation expensive. Chains are only completed if
+/// the reader implements
+/// [`issuer_bytes`](X509CertificateReader::issuer_bytes).
+pub struct ChainBuildingValidator<V, R> {
+    validator: V,
+    reader: R,
+    intermediates: Vec<(Vec<u8>, DerCertificate)>,
+}
+
+impl<V, R> ChainBuildingValidator<V, R>
+where
+    V: X509CredentialValidator,
+    R: X509CertificateReader,
+{
+    /// Create a validator completing chains with `intermediates`. The subject
+    /// of each intermediate is read with `reader`.
+    pub fn new(
+        validator: V,
+        reader: R,
+        intermediates: Vec<DerCertificate>,
+    ) -> Result<Self, X509IdentityError> {
+        let intermediates = intermediates
+            .into_iter()
+            .map(|cert| Ok((reader.subject_bytes(&cert).map_err(reader_error)?, cert)))
+            .collect::<Result<_, X509IdentityError>>()?;
+
+        Ok(Self {
+            validator,
+            reader,
+            intermediates,
+        })
+    }
+
+    fn build_and_validate(
+        &self,
+        chain: &mut Vec<DerCertificate>,
+        search: &mut ChainSearch,
+        timestamp: Option<MlsTime>,
+    ) -> Result<SignaturePublicKey, X509IdentityError> {
+        search.attempts += 1;
+
+        let error = match self
+            .validator
+            .validate_chain(&CertificateChain::from(chain.clone()), timestamp)
+        {
+            Ok(public_key) => return Ok(public_key),
+            Err(e) => X509IdentityError::X509ValidationError(e.into_any_error()),
+        };
+
+        if chain.len() >= MAX_BUILT_CHAIN_LENGTH {
+            return Err(error);
+        }
+
+        let Some(last) = chain.last() else {
+            return Err(error);
+        };
+
+        let Some(issuer) = self.reader.issuer_bytes(last).map_err(reader_error)? else {
+            return Err(error);
+        };
+
+        let mut res = Err(error);
+
+        for (index, (subject, cert)) in self.intermediates.iter().enumerate() {
+            if search.attempts >= MAX_CHAIN_BUILDING_ATTEMPTS {
+                break;
+            }
+
+            if *subject != issuer || search.tried[index] || chain.contains(cert) {
+                continue;
+            }
+
+            search.tried[index] = true;
+            chain.push(cert.clone());
+            res = self.build_and_validate(chain, search, timestamp);
+            chain.pop();
+
+            if res.is_ok() {
+                break;
+            }
+        }
+
+        res
+    }
+}
+
+impl<V, R> X509CredentialValidator for ChainBuildingValidator<V, R>
+where
+    V: X509CredentialValidator,
+    R: X509CertificateReader,
+{
+    type Error = X509IdentityError;
+
+    fn validate_chain(
+        &self,
+        chain: &CertificateChain,
+        timestamp: Option<MlsTime>,
+    ) -> Result<SignaturePublicKey, Self::Error> {
+        let mut search = ChainSearch {
+            attempts: 0,
+            tried: vec![false; self.intermediates.len()],
+        };
+
+        self.build_and_validate(&mut chain.to_vec(), &mut search, timestamp)
+    }
+}
+
+/// Progress of the search for a valid chain of one credential.
+struct ChainSearch {
+    /// Number of candidate chains validated.
+    attempts: usize,
+    /// Whether each intermediate of the pool was tried.
+    tried: Vec<bool>,
+}
+
+impl<IE, V, R> X509IdentityProvider<IE, ChainBuildingValidator<V, R>>
+where
+    IE: X509IdentityExtractor,
+    V: X509CredentialValidator,
+    R: X509CertificateReader,
+{
+    /// Create a new identity provider accepting credentials with incomplete
+    /// chains, which are completed with `intermediates` as described in
+    /// [`ChainBuildingValidator`].
+    pub fn with_intermediates(
+        identity_extractor: IE,
+        validator: V,
+        reader: R,
+        intermediates: Vec<DerCertificate>,
+    ) -> Result<Self, X509IdentityError> {
+        let validator = ChainBuildingValidator::new(validator, reader, intermediates)?;
+        Ok(Self::new(identity_extractor, validator))
+    }
+}
+
+fn reader_error<E: IntoAnyError>(e: E) -> X509IdentityError {
+    X509IdentityError::X509ReaderError(e.into_any_error())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use mls_rs_core::crypto::SignaturePublicKey;
+
+    use crate::{
+        test_utils::TestError, CertificateChain, DerCertificate, MockX509CertificateReader,
+        MockX509CredentialValidator, X509CredentialValidator, X509IdentityError,
+    };
+
+    use super::{ChainBuildingValidator, MAX_CHAIN_BUILDING_ATTEMPTS};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn cert(id: u8) -> DerCertificate {
+        DerCertificate::new(vec![id])
+    }
+
+    // Certificate `n` is issued by certificate `n + 1` and only the chain
+    // 1 -> 2 -> 3 is valid.
+    fn test_validator(
+        intermediates: Vec<DerCertificate>,
+    ) -> ChainBuildingValidator<MockX509CredentialValidator, MockX509CertificateReader> {
+        let mut reader = MockX509CertificateReader::new();
+
+        reader
+            .expect_subject_bytes()
+            .returning(|cert| Ok(cert.to_vec()));
+
+        reader
+            .expect_issuer_bytes()
+            .returning(|cert| Ok(Some(vec![cert[0] + 1])));
+
+        let mut validator = MockX509CredentialValidator::new();
+
+        validator.expect_validate_chain().returning(|chain, _| {
+            (*chain == CertificateChain::from(vec![cert(1), cert(2), cert(3)]))
+                .then(|| SignaturePublicKey::from(vec![1]))
+                .ok_or(TestError)
+        });
+
+        ChainBuildingValidator::new(validator, reader, intermediates).unwrap()
+    }
+
+    #[test]
+    fn leaf_only_chain_is_completed_from_pool() {
+        let validator = test_validator(vec![cert(9), cert(3), cert(2)]);
+
+        for chain in [vec![cert(1)], vec![cert(1), cert(2)]] {
+            let public_key = validator.validate_chain(&chain.into(), None).unwrap();
+            assert_eq!(public_key, SignaturePublicKey::from(vec![1]));
+        }
+
+        let res = test_validator(vec![cert(2)]).validate_chain(&vec![cert(1)].into(), None);
+        assert_matches!(res, Err(X509IdentityError::X509ValidationError(_)));
+    }
+
+    #[test]
+    fn chain_building_is_bounded() {
+        // All certificates share the same subject and issuer, so any sequence
+        // of intermediates is a candidate chain.
+        let mut reader = MockX509CertificateReader::new();
+        reader.expect_subject_bytes().returning(|_| Ok(vec![0]));
+        reader
+            .expect_issuer_bytes()
+            .returning(|_| Ok(Some(vec![0])));
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+
+        let mut validator = MockX509CredentialValidator::new();
+
+        validator.expect_validate_chain().returning(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Err(TestError)
+        });
+
+        let intermediates = (2..100).map(cert).collect();
+        let validator = ChainBuildingValidator::new(validator, reader, intermediates).unwrap();
+        let res = validator.validate_chain(&vec![cert(1)].into(), None);
+
+        assert_matches!(res, Err(X509IdentityError::X509ValidationError(_)));
+        assert_eq!(
+            attempts.load(Ordering::Relaxed),
+            MAX_CHAIN_BUILDING_ATTEMPTS
+        );
+    }
+
+    #[test]
+    fn chain_is_not_completed_without_issuer_support() {
+        let mut reader = MockX509CertificateReader::new();
+        reader
+            .expect_subject_bytes()
+            .returning(|cert| Ok(cert.to_vec()));
+        reader.expect_issuer_bytes().returning(|_| Ok(None));
+
+        let mut validator = MockX509CredentialValidator::new();
+        validator
+            .expect_validate_chain()
+            .times(1)
+            .returning(|_, _| Err(TestError));
+
+        let validator = ChainBuildingValidator::new(validator, reader, vec![cert(2)]).unwrap();
+        let res = validator.validate_chain(&vec![cert(1)].into(), None);
+
+        assert_matches!(res, Err(X509IdentityError::X509ValidationError(_)));
+    }
+}
@@ -3,7 +3,10 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use alloc::vec::Vec;
-use mls_rs_core::{error::IntoAnyError, identity::CertificateChain};
+use mls_rs_core::{
+    error::IntoAnyError,
+    identity::{CertificateChain, CredentialValidity},
+};
 
 use crate::{
     DerCertificate, SubjectComponent, X509CertificateReader, X509IdentityError,
@@ -94,6 +97,37 @@ where
 
         Ok(self.subject_bytes(predecessor_cert)? == self.subject_bytes(successor_cert)?)
     }
+
+    /// Validity period of `certificate_chain`, from the latest start to the
+    /// earliest end of the validity periods of its certificates.
+    ///
+    /// Returns `None` if the reader does not support reading the validity
+    /// period of a certificate.
+    pub fn credential_validity(
+        &self,
+        certificate_chain: &CertificateChain,
+    ) -> Result<Option<CredentialValidity>, X509IdentityError> {
+        let mut validities = certificate_chain.iter().map(|cert| {
+            self.reader
+                .validity(cert)
+                .map_err(|e| X509IdentityError::X509ReaderError(e.into_any_error()))
+        });
+
+        let first = validities
+            .next()
+            .ok_or(X509IdentityError::EmptyCertificateChain)??;
+
+        validities.try_fold(first, |acc, validity| {
+            let (Some(acc), Some(validity)) = (acc, validity?) else {
+                return Ok(None);
+            };
+
+            Ok(Some(CredentialValidity::new(
+                acc.not_before.max(validity.not_before),
+                acc.not_after.min(validity.not_after),
+            )))
+        })
+    }
 }
 
 impl<R> X509IdentityExtractor for SubjectIdentityExtractor<R>
@@ -113,6 +147,13 @@ where
     ) -> Result<bool, Self::Error> {
         self.valid_successor(predecessor, successor)
     }
+
+    fn credential_validity(
+        &self,
+        certificate_chain: &CertificateChain,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        self.credential_validity(certificate_chain)
+    }
 }
 
 fn get_certificate(
@@ -5,6 +5,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
+mod chain_builder;
 mod error;
 mod identity_extractor;
 mod provider;
@@ -14,6 +15,7 @@ mod util;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 
+pub use chain_builder::*;
 pub use error::*;
 pub use identity_extractor::*;
 pub use provider::*;
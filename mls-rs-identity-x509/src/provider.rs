@@ -9,7 +9,7 @@ use mls_rs_core::{
     crypto::SignaturePublicKey,
     error::IntoAnyError,
     extension::ExtensionList,
-    identity::{CredentialType, IdentityProvider, MemberValidationContext},
+    identity::{CredentialType, CredentialValidity, IdentityProvider, MemberValidationContext},
     time::MlsTime,
 };
 
@@ -35,6 +35,15 @@ pub trait X509IdentityExtractor {
         predecessor: &CertificateChain,
         successor: &CertificateChain,
     ) -> Result<bool, Self::Error>;
+
+    /// Validity period of `certificate_chain`, which ends as soon as any of
+    /// its certificates expires. The default implementation returns `None`.
+    fn credential_validity(
+        &self,
+        _certificate_chain: &CertificateChain,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        Ok(None)
+    }
 }
 
 #[cfg_attr(all(test, feature = "std"), automock(type Error = crate::test_utils::TestError;))]
@@ -132,6 +141,17 @@ where
     pub fn supported_types(&self) -> Vec<mls_rs_core::identity::CredentialType> {
         vec![CredentialType::X509]
     }
+
+    /// Validity period of the certificate chain of `signing_identity`, as
+    /// determined by the underlying identity extractor.
+    pub fn credential_validity(
+        &self,
+        signing_identity: &mls_rs_core::identity::SigningIdentity,
+    ) -> Result<Option<CredentialValidity>, X509IdentityError> {
+        self.identity_extractor
+            .credential_validity(&credential_to_chain(&signing_identity.credential)?)
+            .map_err(|e| X509IdentityError::IdentityExtractorError(e.into_any_error()))
+    }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -181,6 +201,13 @@ where
     fn supported_types(&self) -> Vec<CredentialType> {
         self.supported_types()
     }
+
+    fn credential_validity(
+        &self,
+        signing_identity: &mls_rs_core::identity::SigningIdentity,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        self.credential_validity(signing_identity)
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
@@ -5,7 +5,7 @@
 use crate::{DerCertificate, DerCertificateRequest};
 
 use alloc::vec::Vec;
-use mls_rs_core::{crypto::SignaturePublicKey, error::IntoAnyError};
+use mls_rs_core::{crypto::SignaturePublicKey, error::IntoAnyError, identity::CredentialValidity};
 
 #[cfg(all(test, feature = "std"))]
 use mockall::automock;
@@ -72,6 +72,15 @@ pub trait X509CertificateReader {
     /// Der encoded bytes of a certificate subject field.
     fn subject_bytes(&self, certificate: &DerCertificate) -> Result<Vec<u8>, Self::Error>;
 
+    /// Der encoded bytes of a certificate issuer field.
+    ///
+    /// The default implementation returns `None`, signaling that reading the
+    /// issuer is not supported. Chains are then not completed by
+    /// [`ChainBuildingValidator`](crate::ChainBuildingValidator).
+    fn issuer_bytes(&self, _certificate: &DerCertificate) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(None)
+    }
+
     /// Parsed certificate subject field components.
     fn subject_components(
         &self,
@@ -86,4 +95,15 @@ pub trait X509CertificateReader {
 
     /// Get the subject public key of a certificate.
     fn public_key(&self, certificate: &DerCertificate) -> Result<SignaturePublicKey, Self::Error>;
+
+    /// Get the validity period of a certificate.
+    ///
+    /// The default implementation returns `None`, signaling that reading the
+    /// validity period is not supported.
+    fn validity(
+        &self,
+        _certificate: &DerCertificate,
+    ) -> Result<Option<CredentialValidity>, Self::Error> {
+        Ok(None)
+    }
 }
@@ -68,6 +68,28 @@ impl SqLiteGroupStateStorage {
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
 
+    /// Delete the prior epochs of a group with an id lower than `epoch_id`.
+    pub fn delete_epochs_under(
+        &self,
+        group_id: &[u8],
+        epoch_id: u64,
+    ) -> Result<(), SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        // Overwrite deleted content instead of only unlinking it
+        connection
+            .pragma_update(None, "secure_delete", true)
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        connection
+            .execute(
+                "DELETE FROM epoch WHERE group_id = ? AND epoch_id < ?",
+                params![group_id, epoch_id],
+            )
+            .map(|_| ())
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
     pub fn max_epoch_retention(&self) -> u64 {
         self.max_epoch_retention
     }
@@ -210,6 +232,15 @@ impl GroupStateStorage for SqLiteGroupStateStorage {
     async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
         self.get_epoch_data(group_id, epoch_id)
     }
+
+    async fn delete_epochs_under(
+        &mut self,
+        group_id: &[u8],
+        epoch_id: u64,
+    ) -> Result<bool, Self::Error> {
+        SqLiteGroupStateStorage::delete_epochs_under(self, group_id, epoch_id)?;
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
@@ -490,4 +521,35 @@ mod tests {
 
         assert!(test_data.storage.group_ids().unwrap().is_empty());
     }
+
+    #[test]
+    fn delete_epochs_under() {
+        let test_data = setup_group_storage_test();
+
+        test_data
+            .storage
+            .update_group_state(
+                &test_data.group_id,
+                test_data.snapshot.clone(),
+                vec![test_epoch(1), test_epoch(2)],
+                vec![],
+            )
+            .unwrap();
+
+        test_data
+            .storage
+            .delete_epochs_under(&test_data.group_id, 2)
+            .unwrap();
+
+        let stored = |id| {
+            test_data
+                .storage
+                .get_epoch_data(&test_data.group_id, id)
+                .unwrap()
+        };
+
+        assert!(stored(0).is_none());
+        assert!(stored(1).is_none());
+        assert!(stored(2).is_some());
+    }
 }
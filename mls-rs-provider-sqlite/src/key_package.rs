@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use mls_rs_core::{
-    key_package::{KeyPackageData, KeyPackageStorage},
+    key_package::{KeyPackageData, KeyPackageQuery, KeyPackageStorage},
     mls_rs_codec::{MlsDecode, MlsEncode},
     time::MlsTime,
 };
@@ -34,10 +34,12 @@ impl SqLiteKeyPackageStorage {
 
         connection
             .execute(
-                "INSERT INTO key_package (id, expiration, data) VALUES (?,?,?)",
+                "INSERT INTO key_package (id, expiration, cipher_suite, protocol_version, data) VALUES (?,?,?,?,?)",
                 params![
                     id,
                     key_package.expiration,
+                    key_package.cipher_suite().map(u16::from),
+                    key_package.protocol_version().map(u16::from),
                     key_package
                         .mls_encode_to_vec()
                         .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?
@@ -119,6 +121,97 @@ impl SqLiteKeyPackageStorage {
             )
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
+
+    /// Key packages selected by `query`, with their ids.
+    pub fn list(
+        &self,
+        query: &KeyPackageQuery,
+    ) -> Result<Vec<(Vec<u8>, KeyPackageData)>, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT id, data FROM key_package WHERE {QUERY_FILTER}"
+            ))
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        let rows = statement
+            .query_map(query_params(query), |row| {
+                Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        rows.map(|row| {
+            let (id, data) = row.map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            let key_package = KeyPackageData::mls_decode(&mut data.as_slice())
+                .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?;
+
+            Ok((id, key_package))
+        })
+        .collect()
+    }
+
+    /// Number of key packages selected by `query`.
+    pub fn count_matching(&self, query: &KeyPackageQuery) -> Result<usize, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        connection
+            .query_row(
+                &format!("SELECT count(*) FROM key_package WHERE {QUERY_FILTER}"),
+                query_params(query),
+                |row| row.get(0),
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+}
+
+const QUERY_FILTER: &str = "(?1 IS NULL OR cipher_suite = ?1) \
+    AND (?2 IS NULL OR protocol_version = ?2) \
+    AND (?3 IS NULL OR expiration >= ?3)";
+
+fn query_params(query: &KeyPackageQuery) -> impl rusqlite::Params {
+    (
+        query.cipher_suite.map(u16::from),
+        query.protocol_version.map(u16::from),
+        query.valid_at,
+    )
+}
+
+/// Fill the cipher suite and protocol version columns of key packages stored
+/// before they existed.
+pub(crate) fn index_stored_key_packages(
+    connection: &Connection,
+) -> Result<(), SqLiteDataStorageError> {
+    let mut statement = connection
+        .prepare("SELECT id, data FROM key_package")
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    let rows = statement
+        .query_map(params![], |row| {
+            Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    for (id, data) in rows {
+        let key_package = KeyPackageData::mls_decode(&mut data.as_slice())
+            .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?;
+
+        connection
+            .execute(
+                "UPDATE key_package SET cipher_suite = ?, protocol_version = ? WHERE id = ?",
+                params![
+                    key_package.cipher_suite().map(u16::from),
+                    key_package.protocol_version().map(u16::from),
+                    id
+                ],
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+    }
+
+    Ok(())
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -137,6 +230,17 @@ impl KeyPackageStorage for SqLiteKeyPackageStorage {
     async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
         (*self).delete(id)
     }
+
+    async fn list(
+        &self,
+        query: &KeyPackageQuery,
+    ) -> Result<Option<Vec<(Vec<u8>, KeyPackageData)>>, Self::Error> {
+        SqLiteKeyPackageStorage::list(self, query).map(Some)
+    }
+
+    async fn count(&self, query: &KeyPackageQuery) -> Result<Option<usize>, Self::Error> {
+        self.count_matching(query).map(Some)
+    }
 }
 
 #[cfg(test)]
@@ -144,10 +248,20 @@ mod tests {
     use super::SqLiteKeyPackageStorage;
     use crate::{
         SqLiteDataStorageEngine, SqLiteDataStorageError,
-        {connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes},
+        {
+            connection_strategy::{FileConnectionStrategy, MemoryStrategy},
+            test_utils::gen_rand_bytes,
+        },
     };
     use assert_matches::assert_matches;
-    use mls_rs_core::{crypto::HpkeSecretKey, key_package::KeyPackageData};
+    use mls_rs_core::{
+        crypto::{CipherSuite, HpkeSecretKey},
+        key_package::{KeyPackageData, KeyPackageQuery},
+        mls_rs_codec::MlsEncode,
+        protocol_version::ProtocolVersion,
+    };
+    use rusqlite::{params, Connection};
+    use tempfile::tempdir;
 
     fn test_storage() -> SqLiteKeyPackageStorage {
         SqLiteDataStorageEngine::new(MemoryStrategy)
@@ -277,4 +391,81 @@ mod tests {
         assert_eq!(storage.count_at_time(1).unwrap(), 2);
         assert_eq!(storage.count_at_time(0).unwrap(), 2);
     }
+
+    fn test_key_package_of_kind(cipher_suite: CipherSuite, expiration: u64) -> KeyPackageData {
+        let key_package_bytes = [
+            u16::from(ProtocolVersion::MLS_10).to_be_bytes(),
+            u16::from(cipher_suite).to_be_bytes(),
+        ]
+        .concat();
+
+        KeyPackageData::new(
+            key_package_bytes,
+            HpkeSecretKey::from(gen_rand_bytes(32)),
+            HpkeSecretKey::from(gen_rand_bytes(32)),
+            expiration,
+        )
+    }
+
+    #[test]
+    fn key_packages_can_be_queried() {
+        let mut storage = test_storage();
+
+        let kinds = [
+            (CipherSuite::CURVE25519_AES128, 10),
+            (CipherSuite::CURVE25519_AES128, 20),
+            (CipherSuite::P256_AES128, 20),
+        ];
+
+        for (cipher_suite, expiration) in kinds {
+            let key_package = test_key_package_of_kind(cipher_suite, expiration);
+            storage.insert(&gen_rand_bytes(32), key_package).unwrap();
+        }
+
+        let query = KeyPackageQuery::new().with_cipher_suite(CipherSuite::CURVE25519_AES128);
+        assert_eq!(storage.count_matching(&query).unwrap(), 2);
+
+        let query = query.with_valid_at(15);
+        let found = storage.list(&query).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1.expiration, 20);
+
+        let query = KeyPackageQuery::new().with_protocol_version(ProtocolVersion::MLS_10);
+        assert_eq!(storage.count_matching(&query).unwrap(), 3);
+    }
+
+    #[test]
+    fn key_packages_stored_before_migration_are_indexed() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let key_package = test_key_package_of_kind(CipherSuite::P256_AES128, 10);
+
+        {
+            let connection = Connection::open(&path).unwrap();
+            crate::create_tables_v1(&connection).unwrap();
+
+            connection
+                .execute(
+                    "INSERT INTO key_package (id, expiration, data) VALUES (?,?,?)",
+                    params![
+                        b"id".to_vec(),
+                        key_package.expiration,
+                        key_package.mls_encode_to_vec().unwrap()
+                    ],
+                )
+                .unwrap();
+        }
+
+        let storage = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .key_package_storage()
+            .unwrap();
+
+        let query = KeyPackageQuery::new().with_cipher_suite(CipherSuite::P256_AES128);
+        assert_eq!(
+            storage.list(&query).unwrap(),
+            vec![(b"id".to_vec(), key_package)]
+        );
+    }
 }
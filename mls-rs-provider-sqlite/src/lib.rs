@@ -124,10 +124,14 @@ where
                 .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
         }
 
-        if current_schema != 1 {
+        if current_schema < 1 {
             create_tables_v1(&connection)?;
         }
 
+        if current_schema < 2 {
+            migrate_to_v2(&connection)?;
+        }
+
         Ok(connection)
     }
 
@@ -191,6 +195,32 @@ fn create_tables_v1(connection: &Connection) -> Result<(), SqLiteDataStorageErro
         .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
 }
 
+// Index the cipher suite and protocol version of key packages, so that they
+// can be queried without decoding every stored key package
+fn migrate_to_v2(connection: &Connection) -> Result<(), SqLiteDataStorageError> {
+    let transaction = connection
+        .unchecked_transaction()
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    transaction
+        .execute_batch(
+            "ALTER TABLE key_package ADD COLUMN cipher_suite INTEGER;
+            ALTER TABLE key_package ADD COLUMN protocol_version INTEGER;
+            CREATE INDEX key_package_kind ON key_package (cipher_suite, protocol_version, expiration);",
+        )
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    key_package::index_stored_key_packages(&transaction)?;
+
+    transaction
+        .execute_batch("PRAGMA user_version = 2;")
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    transaction
+        .commit()
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+}
+
 #[cfg(test)]
 mod tests {
     use tempfile::tempdir;
@@ -214,7 +244,7 @@ mod tests {
             .pragma_query_value(None, "user_version", |rows| rows.get::<_, u32>(0))
             .unwrap();
 
-        assert_eq!(current_schema, 1);
+        assert_eq!(current_schema, 2);
     }
 
     #[test]
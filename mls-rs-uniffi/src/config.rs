@@ -6,12 +6,15 @@ use mls_rs::{
     identity::basic,
     storage_provider::in_memory::InMemoryGroupStateStorage,
 };
+use mls_rs_core::crypto::HardwareKeystoreCryptoProvider;
 use mls_rs_crypto_openssl::OpensslCryptoProvider;
 
 use self::group_state::{GroupStateStorage, GroupStateStorageAdapter};
+use self::hardware_keystore::{ClientHardwareKeystore, HardwareKeystore};
 use crate::Error;
 
 pub mod group_state;
+pub mod hardware_keystore;
 
 #[derive(Debug, Clone)]
 pub(crate) struct ClientGroupStorage(Arc<dyn GroupStateStorage>);
@@ -59,7 +62,7 @@ impl mls_rs_core::group::GroupStateStorage for ClientGroupStorage {
 pub type UniFFIConfig = client_builder::WithIdentityProvider<
     basic::BasicIdentityProvider,
     client_builder::WithCryptoProvider<
-        OpensslCryptoProvider,
+        HardwareKeystoreCryptoProvider<OpensslCryptoProvider, ClientHardwareKeystore>,
         WithGroupStateStorage<ClientGroupStorage, client_builder::BaseConfig>,
     >,
 >;
@@ -70,6 +73,12 @@ pub struct ClientConfig {
     /// Use the ratchet tree extension. If this is false, then you
     /// must supply `ratchet_tree` out of band to clients.
     pub use_ratchet_tree_extension: bool,
+    /// Keystore holding the signature private key of the client. If
+    /// this is `None`, the secret key of the `SignatureKeypair` given
+    /// to the client is used in memory. Otherwise, it must be a
+    /// handle of a key in this keystore.
+    #[uniffi(default = None)]
+    pub hardware_keystore: Option<Arc<dyn HardwareKeystore>>,
 }
 
 impl Default for ClientConfig {
@@ -79,6 +88,7 @@ impl Default for ClientConfig {
                 InMemoryGroupStateStorage::new(),
             )),
             use_ratchet_tree_extension: true,
+            hardware_keystore: None,
         }
     }
 }
@@ -0,0 +1,167 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use mls_rs::error::{IntoAnyError, MlsError};
+use mls_rs::{CipherSuiteProvider, CryptoProvider};
+use mls_rs_crypto_openssl::OpensslCryptoProvider;
+
+use crate::{CipherSuite, Error, SignaturePublicKey};
+
+/// A key pair generated inside a [`HardwareKeystore`].
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct KeystoreKeypair {
+    /// Keystore-defined handle of the private key, for example the
+    /// alias of a key in the Android Keystore.
+    pub handle: Vec<u8>,
+    pub public_key: SignaturePublicKey,
+}
+
+/// Platform keystore holding signature private keys, such as the
+/// Android Keystore or the iOS Secure Enclave.
+///
+/// Keys are referred to by handles, which are used as the secret key
+/// of a [`SignatureKeypair`](crate::SignatureKeypair). Signatures are
+/// computed by the keystore, so private keys never enter the memory
+/// of the process.
+///
+/// See [`mls_rs_core::crypto::HardwareKeystore`] for details.
+//
+// See `GroupStateStorage` for the order of the attributes.
+#[cfg_attr(mls_build_async, uniffi::export(with_foreign))]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(not(mls_build_async), uniffi::export(with_foreign))]
+pub trait HardwareKeystore: Send + Sync + Debug {
+    async fn generate_key(&self, cipher_suite: CipherSuite) -> Result<KeystoreKeypair, Error>;
+
+    async fn public_key(&self, handle: Vec<u8>) -> Result<SignaturePublicKey, Error>;
+
+    async fn sign(
+        &self,
+        cipher_suite: CipherSuite,
+        handle: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, Error>;
+
+    async fn delete_key(&self, handle: Vec<u8>) -> Result<(), Error>;
+}
+
+/// Keystore used when no [`HardwareKeystore`] is configured.
+///
+/// Keys are used in memory with the OpenSSL crypto provider, and
+/// handles are the bytes of the private keys. Public keys are derived
+/// with the cipher suite of the client.
+#[derive(Debug)]
+pub(crate) struct SoftwareKeystore {
+    crypto_provider: OpensslCryptoProvider,
+    cipher_suite: CipherSuite,
+}
+
+impl SoftwareKeystore {
+    pub fn new(cipher_suite: CipherSuite) -> Self {
+        Self {
+            crypto_provider: OpensslCryptoProvider::new(),
+            cipher_suite,
+        }
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<impl CipherSuiteProvider, Error> {
+        Ok(self
+            .crypto_provider
+            .cipher_suite_provider(cipher_suite.into())
+            .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite.into()))?)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl HardwareKeystore for SoftwareKeystore {
+    async fn generate_key(&self, cipher_suite: CipherSuite) -> Result<KeystoreKeypair, Error> {
+        let (secret_key, public_key) = self
+            .cipher_suite_provider(cipher_suite)?
+            .signature_key_generate()
+            .await
+            .map_err(|err| MlsError::CryptoProviderError(err.into_any_error()))?;
+
+        Ok(KeystoreKeypair {
+            handle: secret_key.as_bytes().to_vec(),
+            public_key: public_key.into(),
+        })
+    }
+
+    async fn public_key(&self, handle: Vec<u8>) -> Result<SignaturePublicKey, Error> {
+        let public_key = self
+            .cipher_suite_provider(self.cipher_suite)?
+            .signature_key_derive_public(&handle.into())
+            .await
+            .map_err(|err| MlsError::CryptoProviderError(err.into_any_error()))?;
+
+        Ok(public_key.into())
+    }
+
+    async fn sign(
+        &self,
+        cipher_suite: CipherSuite,
+        handle: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        self.cipher_suite_provider(cipher_suite)?
+            .sign(&handle.into(), &data)
+            .await
+            .map_err(|err| MlsError::CryptoProviderError(err.into_any_error()).into())
+    }
+
+    async fn delete_key(&self, _handle: Vec<u8>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Adapt a [`HardwareKeystore`] to the mls-rs
+/// [`HardwareKeystore`](mls_rs_core::crypto::HardwareKeystore) trait.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientHardwareKeystore(Arc<dyn HardwareKeystore>);
+
+impl From<Arc<dyn HardwareKeystore>> for ClientHardwareKeystore {
+    fn from(value: Arc<dyn HardwareKeystore>) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl mls_rs_core::crypto::HardwareKeystore for ClientHardwareKeystore {
+    type Error = Error;
+
+    async fn generate_key(
+        &self,
+        cipher_suite: mls_rs::CipherSuite,
+    ) -> Result<(Vec<u8>, mls_rs::crypto::SignaturePublicKey), Self::Error> {
+        let keypair = self.0.generate_key(cipher_suite.try_into()?).await?;
+        Ok((keypair.handle, keypair.public_key.into()))
+    }
+
+    async fn public_key(
+        &self,
+        handle: &[u8],
+    ) -> Result<mls_rs::crypto::SignaturePublicKey, Self::Error> {
+        Ok(self.0.public_key(handle.to_vec()).await?.into())
+    }
+
+    async fn sign(
+        &self,
+        cipher_suite: mls_rs::CipherSuite,
+        handle: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.0
+            .sign(cipher_suite.try_into()?, handle.to_vec(), data.to_vec())
+            .await
+    }
+
+    async fn delete_key(&self, handle: &[u8]) -> Result<(), Self::Error> {
+        self.0.delete_key(handle.to_vec()).await
+    }
+}
@@ -21,6 +21,7 @@ mod config;
 
 use std::sync::Arc;
 
+use config::hardware_keystore::{ClientHardwareKeystore, HardwareKeystore, SoftwareKeystore};
 pub use config::ClientConfig;
 use config::UniFFIConfig;
 
@@ -34,6 +35,7 @@ use mls_rs::group;
 use mls_rs::identity::basic;
 use mls_rs::mls_rules;
 use mls_rs::{CipherSuiteProvider, CryptoProvider};
+use mls_rs_core::crypto::HardwareKeystoreCryptoProvider;
 use mls_rs_core::identity;
 use mls_rs_core::identity::{BasicCredential, IdentityProvider};
 use mls_rs_crypto_openssl::OpensslCryptoProvider;
@@ -267,6 +269,8 @@ pub enum ReceivedMessage {
     Welcome,
     /// Validated key package.
     KeyPackage,
+    /// Message of a future epoch, buffered until the group reaches it.
+    Buffered { epoch: u64 },
 }
 
 /// Supported cipher suites.
@@ -327,6 +331,29 @@ pub async fn generate_signature_keypair(
     })
 }
 
+/// Generate a MLS signature keypair inside a hardware keystore.
+///
+/// The secret key of the returned keypair is the handle of the
+/// private key in `keystore`. The client using it must be configured
+/// with the same keystore in [`ClientConfig::hardware_keystore`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+#[uniffi::export]
+pub async fn generate_hardware_signature_keypair(
+    cipher_suite: CipherSuite,
+    keystore: Arc<dyn HardwareKeystore>,
+) -> Result<SignatureKeypair, Error> {
+    let keypair = keystore.generate_key(cipher_suite).await?;
+
+    Ok(SignatureKeypair {
+        cipher_suite,
+        public_key: keypair.public_key,
+        secret_key: SignatureSecretKey {
+            bytes: keypair.handle,
+        },
+    })
+}
+
 /// An MLS client used to create key packages and manage groups.
 ///
 /// See [`mls_rs::Client`] for details.
@@ -354,7 +381,13 @@ impl Client {
         let cipher_suite = signature_keypair.cipher_suite;
         let public_key = signature_keypair.public_key;
         let secret_key = signature_keypair.secret_key;
-        let crypto_provider = OpensslCryptoProvider::new();
+        let hardware_keystore = client_config
+            .hardware_keystore
+            .unwrap_or_else(|| Arc::new(SoftwareKeystore::new(cipher_suite)));
+        let crypto_provider = HardwareKeystoreCryptoProvider::new(
+            OpensslCryptoProvider::new(),
+            ClientHardwareKeystore::from(hardware_keystore),
+        );
         let basic_credential = BasicCredential::new(id);
         let signing_identity =
             identity::SigningIdentity::new(basic_credential.into_credential(), public_key.into());
@@ -743,7 +776,8 @@ impl Group {
         let message = arc_unwrap_or_clone(message);
         let mut group = self.inner().await;
         match group.process_incoming_message(message.inner).await? {
-            group::ReceivedMessage::ApplicationMessage(application_message) => {
+            group::ReceivedMessage::ApplicationMessage(application_message)
+            | group::ReceivedMessage::TargetedMessage(application_message) => {
                 let sender =
                     Arc::new(index_to_identity(&group, application_message.sender_index)?.into());
                 let data = application_message.data().to_vec();
@@ -774,6 +808,7 @@ impl Group {
             group::ReceivedMessage::GroupInfo(_) => Ok(ReceivedMessage::GroupInfo),
             group::ReceivedMessage::Welcome => Ok(ReceivedMessage::Welcome),
             group::ReceivedMessage::KeyPackage(_) => Ok(ReceivedMessage::KeyPackage),
+            group::ReceivedMessage::Buffered(epoch) => Ok(ReceivedMessage::Buffered { epoch }),
         }
     }
 }
@@ -938,4 +973,93 @@ mod tests {
         assert_eq!(ratchet_tree, group.inner().export_tree());
         Ok(())
     }
+
+    #[test]
+    #[cfg(not(mls_build_async))]
+    fn test_hardware_keystore() -> Result<(), Error> {
+        use crate::config::hardware_keystore::KeystoreKeypair;
+
+        /// Keystore referring to software keys by alias, like a
+        /// platform keystore does.
+        #[derive(Debug)]
+        struct AliasKeystore {
+            keystore: SoftwareKeystore,
+            keys: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+        }
+
+        impl AliasKeystore {
+            fn private_key(&self, handle: &[u8]) -> Vec<u8> {
+                self.keys.lock().unwrap()[handle].clone()
+            }
+        }
+
+        impl HardwareKeystore for AliasKeystore {
+            fn generate_key(&self, cipher_suite: CipherSuite) -> Result<KeystoreKeypair, Error> {
+                let keypair = self.keystore.generate_key(cipher_suite)?;
+                let mut keys = self.keys.lock().unwrap();
+                let handle = format!("key-{}", keys.len()).into_bytes();
+                keys.insert(handle.clone(), keypair.handle);
+
+                Ok(KeystoreKeypair {
+                    handle,
+                    public_key: keypair.public_key,
+                })
+            }
+
+            fn public_key(&self, handle: Vec<u8>) -> Result<SignaturePublicKey, Error> {
+                self.keystore.public_key(self.private_key(&handle))
+            }
+
+            fn sign(
+                &self,
+                cipher_suite: CipherSuite,
+                handle: Vec<u8>,
+                data: Vec<u8>,
+            ) -> Result<Vec<u8>, Error> {
+                self.keystore
+                    .sign(cipher_suite, self.private_key(&handle), data)
+            }
+
+            fn delete_key(&self, handle: Vec<u8>) -> Result<(), Error> {
+                self.keys.lock().unwrap().remove(&handle);
+                Ok(())
+            }
+        }
+
+        let keystore = Arc::new(AliasKeystore {
+            keystore: SoftwareKeystore::new(CipherSuite::Curve25519Aes128),
+            keys: Mutex::default(),
+        });
+
+        let alice_keypair =
+            generate_hardware_signature_keypair(CipherSuite::Curve25519Aes128, keystore.clone())?;
+        assert_eq!(alice_keypair.secret_key.bytes, b"key-0");
+
+        let alice_config = ClientConfig {
+            hardware_keystore: Some(keystore),
+            ..ClientConfig::default()
+        };
+        let alice = Client::new(b"alice".to_vec(), alice_keypair, alice_config);
+
+        let bob_keypair = generate_signature_keypair(CipherSuite::Curve25519Aes128)?;
+        let bob = Client::new(b"bob".to_vec(), bob_keypair, ClientConfig::default());
+
+        let alice_group = alice.create_group(None)?;
+        let bob_key_package = bob.generate_key_package_message()?;
+        let commit = alice_group.add_members(vec![Arc::new(bob_key_package)])?;
+        alice_group.process_incoming_message(commit.commit_message)?;
+
+        let bob_group = bob
+            .join_group(None, &commit.welcome_message.unwrap())?
+            .group;
+        let message = alice_group.encrypt_application_message(b"hello, bob")?;
+        let received_message = bob_group.process_incoming_message(Arc::new(message))?;
+
+        let ReceivedMessage::ApplicationMessage { sender: _, data } = received_message else {
+            panic!("Wrong message type: {received_message:?}");
+        };
+        assert_eq!(data, b"hello, bob");
+
+        Ok(())
+    }
 }
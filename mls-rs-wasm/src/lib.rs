@@ -0,0 +1,467 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! wasm-bindgen wrapper around mls-rs.
+//!
+//! This is an opinionated wrapper exposing a [`Client`] and a [`Group`] to
+//! JavaScript, similar in scope to the UniFFI wrapper in `mls-rs-uniffi`.
+//! Cryptography is provided by [`WebCryptoProvider`], which uses the
+//! browser's SubtleCrypto API instead of a RustCrypto build. Only the cipher
+//! suites supported by SubtleCrypto are available, see
+//! [`WebCryptoProvider::all_supported_cipher_suites`].
+//!
+//! Every operation that performs cryptography returns a JavaScript
+//! `Promise`. Messages are passed in and out as MLS encoded bytes.
+//!
+//! The crate only contains code when built for `wasm32` with
+//! `RUSTFLAGS="--cfg mls_build_async"`.
+
+#![cfg(all(mls_build_async, target_arch = "wasm32"))]
+
+use std::{fmt::Display, rc::Rc};
+
+use futures::lock::Mutex;
+use js_sys::{Array, Uint8Array};
+use mls_rs::{
+    client_builder::{BaseConfig, WithCryptoProvider, WithIdentityProvider},
+    error::{IntoAnyError, MlsError},
+    group::{ProposalSender, ReceivedMessage as MlsReceivedMessage},
+    identity::{
+        basic::{BasicCredential, BasicIdentityProvider},
+        SigningIdentity,
+    },
+    mls_rules::{CommitOptions, DefaultMlsRules},
+    CipherSuite, CipherSuiteProvider, CryptoProvider, ExtensionList, MlsMessage,
+};
+use mls_rs_crypto_webcrypto::WebCryptoProvider;
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::future_to_promise;
+
+type WasmConfig =
+    WithIdentityProvider<BasicIdentityProvider, WithCryptoProvider<WebCryptoProvider, BaseConfig>>;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Promise<Client>")]
+    pub type PromiseClient;
+
+    #[wasm_bindgen(typescript_type = "Promise<Group>")]
+    pub type PromiseGroup;
+
+    #[wasm_bindgen(typescript_type = "Promise<CommitOutput>")]
+    pub type PromiseCommitOutput;
+
+    #[wasm_bindgen(typescript_type = "Promise<ReceivedMessage>")]
+    pub type PromiseReceivedMessage;
+
+    #[wasm_bindgen(typescript_type = "Promise<Uint8Array>")]
+    pub type PromiseBytes;
+
+    #[wasm_bindgen(typescript_type = "Promise<void>")]
+    pub type PromiseVoid;
+}
+
+fn to_js_error<E: Display>(err: E) -> JsValue {
+    JsError::new(&err.to_string()).into()
+}
+
+fn spawn<F, T, P>(future: F) -> P
+where
+    F: std::future::Future<Output = Result<T, JsValue>> + 'static,
+    T: Into<JsValue>,
+    P: JsCast,
+{
+    future_to_promise(async move { future.await.map(Into::into) }).unchecked_into()
+}
+
+fn message_to_bytes(message: &MlsMessage) -> Result<Vec<u8>, JsValue> {
+    message.to_bytes().map_err(to_js_error)
+}
+
+fn message_from_bytes(bytes: &[u8]) -> Result<MlsMessage, JsValue> {
+    MlsMessage::from_bytes(bytes).map_err(to_js_error)
+}
+
+/// An MLS client used to create key packages and manage groups.
+///
+/// See [`mls_rs::Client`] for details.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Client {
+    inner: mls_rs::Client<WasmConfig>,
+}
+
+#[wasm_bindgen]
+impl Client {
+    /// Create a new client with a freshly generated signature key pair.
+    ///
+    /// The user is identified by `id`, which is used to create a basic
+    /// credential. Groups created by this client include the ratchet tree
+    /// in their welcome messages.
+    pub fn create(id: Vec<u8>, cipher_suite: u16) -> PromiseClient {
+        spawn(async move {
+            let cipher_suite = CipherSuite::from(cipher_suite);
+            let crypto_provider = WebCryptoProvider::new();
+
+            let (secret_key, public_key) = crypto_provider
+                .cipher_suite_provider(cipher_suite)
+                .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))
+                .map_err(to_js_error)?
+                .signature_key_generate()
+                .await
+                .map_err(|e| to_js_error(MlsError::CryptoProviderError(e.into_any_error())))?;
+
+            let credential = BasicCredential::new(id).into_credential();
+            let signing_identity = SigningIdentity::new(credential, public_key);
+
+            let commit_options = CommitOptions::default()
+                .with_ratchet_tree_extension(true)
+                .with_single_welcome_message(true);
+
+            let inner = mls_rs::Client::builder()
+                .crypto_provider(crypto_provider)
+                .identity_provider(BasicIdentityProvider::new())
+                .mls_rules(DefaultMlsRules::new().with_commit_options(commit_options))
+                .signing_identity(signing_identity, secret_key, cipher_suite)
+                .build();
+
+            Ok(Client { inner })
+        })
+    }
+
+    /// Generate a new key package message for this client.
+    ///
+    /// See [`mls_rs::Client::generate_key_package_message`] for details.
+    #[wasm_bindgen(js_name = generateKeyPackageMessage)]
+    pub fn generate_key_package_message(&self) -> PromiseBytes {
+        let client = self.clone();
+
+        spawn(async move {
+            let message = client
+                .inner
+                .generate_key_package_message(Default::default(), Default::default())
+                .await
+                .map_err(to_js_error)?;
+
+            message_to_bytes(&message).map(|bytes| Uint8Array::from(&bytes[..]))
+        })
+    }
+
+    /// Create and immediately join a new group.
+    ///
+    /// If `group_id` is not given, a unique ID is generated.
+    ///
+    /// See [`mls_rs::Client::create_group`] and
+    /// [`mls_rs::Client::create_group_with_id`] for details.
+    #[wasm_bindgen(js_name = createGroup)]
+    pub fn create_group(&self, group_id: Option<Vec<u8>>) -> PromiseGroup {
+        let client = self.clone();
+
+        spawn(async move {
+            let extensions = ExtensionList::new();
+
+            let group = match group_id {
+                Some(group_id) => {
+                    client
+                        .inner
+                        .create_group_with_id(group_id, extensions, Default::default())
+                        .await
+                }
+                None => {
+                    client
+                        .inner
+                        .create_group(extensions, Default::default())
+                        .await
+                }
+            };
+
+            group.map(Group::new).map_err(to_js_error)
+        })
+    }
+
+    /// Join an existing group using a welcome message containing the
+    /// ratchet tree.
+    ///
+    /// See [`mls_rs::Client::join_group`] for details.
+    #[wasm_bindgen(js_name = joinGroup)]
+    pub fn join_group(&self, welcome_message: Vec<u8>) -> PromiseGroup {
+        let client = self.clone();
+
+        spawn(async move {
+            let welcome_message = message_from_bytes(&welcome_message)?;
+
+            client
+                .inner
+                .join_group(None, &welcome_message)
+                .await
+                .map(|(group, _)| Group::new(group))
+                .map_err(to_js_error)
+        })
+    }
+
+    /// Load a group previously written with [`Group::write_to_storage`].
+    ///
+    /// See [`mls_rs::Client::load_group`] for details.
+    #[wasm_bindgen(js_name = loadGroup)]
+    pub fn load_group(&self, group_id: Vec<u8>) -> PromiseGroup {
+        let client = self.clone();
+
+        spawn(async move {
+            client
+                .inner
+                .load_group(&group_id)
+                .await
+                .map(Group::new)
+                .map_err(to_js_error)
+        })
+    }
+}
+
+/// The output of a commit.
+///
+/// See [`mls_rs::group::CommitOutput`] for details.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct CommitOutput {
+    commit_message: Vec<u8>,
+    welcome_message: Option<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl CommitOutput {
+    /// The commit message to send to the other members of the group.
+    #[wasm_bindgen(getter, js_name = commitMessage)]
+    pub fn commit_message(&self) -> Vec<u8> {
+        self.commit_message.clone()
+    }
+
+    /// The welcome message to send to new members, if any.
+    #[wasm_bindgen(getter, js_name = welcomeMessage)]
+    pub fn welcome_message(&self) -> Option<Vec<u8>> {
+        self.welcome_message.clone()
+    }
+}
+
+impl TryFrom<mls_rs::group::CommitOutput> for CommitOutput {
+    type Error = JsValue;
+
+    fn try_from(output: mls_rs::group::CommitOutput) -> Result<Self, Self::Error> {
+        Ok(CommitOutput {
+            commit_message: message_to_bytes(&output.commit_message)?,
+            welcome_message: output
+                .welcome_messages
+                .first()
+                .map(message_to_bytes)
+                .transpose()?,
+        })
+    }
+}
+
+/// The kind of a [`ReceivedMessage`].
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceivedMessageKind {
+    ApplicationMessage,
+    Commit,
+    Proposal,
+    Other,
+}
+
+/// A message processed by [`Group::process_incoming_message`].
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct ReceivedMessage {
+    kind: ReceivedMessageKind,
+    sender: Option<u32>,
+    data: Option<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl ReceivedMessage {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> ReceivedMessageKind {
+        self.kind
+    }
+
+    /// Leaf index of the sender, for application messages and messages sent
+    /// by members.
+    #[wasm_bindgen(getter)]
+    pub fn sender(&self) -> Option<u32> {
+        self.sender
+    }
+
+    /// Decrypted data of an application message.
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Option<Vec<u8>> {
+        self.data.clone()
+    }
+}
+
+impl From<MlsReceivedMessage> for ReceivedMessage {
+    fn from(message: MlsReceivedMessage) -> Self {
+        let (kind, sender, data) = match message {
+            MlsReceivedMessage::ApplicationMessage(m) | MlsReceivedMessage::TargetedMessage(m) => (
+                ReceivedMessageKind::ApplicationMessage,
+                Some(m.sender_index),
+                Some(m.data().to_vec()),
+            ),
+            MlsReceivedMessage::Commit(m) => (ReceivedMessageKind::Commit, Some(m.committer), None),
+            MlsReceivedMessage::Proposal(m) => (
+                ReceivedMessageKind::Proposal,
+                match m.sender {
+                    ProposalSender::Member(index) => Some(index),
+                    _ => None,
+                },
+                None,
+            ),
+            _ => (ReceivedMessageKind::Other, None, None),
+        };
+
+        Self { kind, sender, data }
+    }
+}
+
+/// An MLS end-to-end encrypted group.
+///
+/// Operations on a group are serialized: an operation started while another
+/// one is pending waits for it to complete.
+///
+/// See [`mls_rs::Group`] for details.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Group {
+    inner: Rc<Mutex<mls_rs::Group<WasmConfig>>>,
+}
+
+impl Group {
+    fn new(group: mls_rs::Group<WasmConfig>) -> Self {
+        Self {
+            inner: Rc::new(Mutex::new(group)),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Group {
+    /// The group ID.
+    #[wasm_bindgen(js_name = groupId)]
+    pub fn group_id(&self) -> PromiseBytes {
+        let group = self.clone();
+
+        spawn(async move {
+            let group = group.inner.lock().await;
+            Ok(Uint8Array::from(group.group_id()))
+        })
+    }
+
+    /// Write the current state of the group to storage.
+    ///
+    /// See [`mls_rs::Group::write_to_storage`] for details.
+    #[wasm_bindgen(js_name = writeToStorage)]
+    pub fn write_to_storage(&self) -> PromiseVoid {
+        let group = self.clone();
+
+        spawn(async move {
+            let mut group = group.inner.lock().await;
+            group.write_to_storage().await.map_err(to_js_error)?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Commit the addition of the members owning the key package messages in
+    /// `key_packages`, an array of `Uint8Array`. The commit must be applied with
+    /// [`Group::apply_pending_commit`] once accepted by the delivery service.
+    ///
+    /// See [`mls_rs::group::CommitBuilder::add_member`] for details.
+    #[wasm_bindgen(js_name = addMembers)]
+    pub fn add_members(&self, key_packages: Array) -> PromiseCommitOutput {
+        let group = self.clone();
+
+        spawn(async move {
+            let mut group = group.inner.lock().await;
+            let mut commit_builder = group.commit_builder();
+
+            for key_package in key_packages.iter() {
+                commit_builder = commit_builder
+                    .add_member(message_from_bytes(&Uint8Array::new(&key_package).to_vec())?)
+                    .map_err(to_js_error)?;
+            }
+
+            let output = commit_builder.build().await.map_err(to_js_error)?;
+            CommitOutput::try_from(output)
+        })
+    }
+
+    /// Commit the removal of the members at leaf indexes `indexes`.
+    ///
+    /// See [`mls_rs::group::CommitBuilder::remove_member`] for details.
+    #[wasm_bindgen(js_name = removeMembers)]
+    pub fn remove_members(&self, indexes: Vec<u32>) -> PromiseCommitOutput {
+        let group = self.clone();
+
+        spawn(async move {
+            let mut group = group.inner.lock().await;
+            let mut commit_builder = group.commit_builder();
+
+            for index in indexes {
+                commit_builder = commit_builder.remove_member(index).map_err(to_js_error)?;
+            }
+
+            let output = commit_builder.build().await.map_err(to_js_error)?;
+            CommitOutput::try_from(output)
+        })
+    }
+
+    /// Apply the pending commit created by this member.
+    ///
+    /// See [`mls_rs::Group::apply_pending_commit`] for details.
+    #[wasm_bindgen(js_name = applyPendingCommit)]
+    pub fn apply_pending_commit(&self) -> PromiseVoid {
+        let group = self.clone();
+
+        spawn(async move {
+            let mut group = group.inner.lock().await;
+            group.apply_pending_commit().await.map_err(to_js_error)?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Encrypt an application message for the current epoch.
+    ///
+    /// See [`mls_rs::Group::encrypt_application_message`] for details.
+    #[wasm_bindgen(js_name = encryptApplicationMessage)]
+    pub fn encrypt_application_message(&self, message: Vec<u8>) -> PromiseBytes {
+        let group = self.clone();
+
+        spawn(async move {
+            let mut group = group.inner.lock().await;
+
+            let message = group
+                .encrypt_application_message(&message, Vec::new())
+                .await
+                .map_err(to_js_error)?;
+
+            message_to_bytes(&message).map(|bytes| Uint8Array::from(&bytes[..]))
+        })
+    }
+
+    /// Process an inbound message for this group.
+    ///
+    /// See [`mls_rs::Group::process_incoming_message`] for details.
+    #[wasm_bindgen(js_name = processIncomingMessage)]
+    pub fn process_incoming_message(&self, message: Vec<u8>) -> PromiseReceivedMessage {
+        let group = self.clone();
+
+        spawn(async move {
+            let message = message_from_bytes(&message)?;
+            let mut group = group.inner.lock().await;
+
+            group
+                .process_incoming_message(message)
+                .await
+                .map(ReceivedMessage::from)
+                .map_err(to_js_error)
+        })
+    }
+}
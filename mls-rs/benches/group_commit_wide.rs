@@ -0,0 +1,36 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+// The update path of each commit is encrypted to all other members. With the
+// `rayon` feature, path secrets are encrypted to the nodes of the update path
+// and to their resolutions in parallel. Compare with a run using
+// `RAYON_NUM_THREADS=1` to measure the effect on wide trees.
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use mls_rs::{test_utils::benchmarks::load_wide_group_states, CipherSuite};
+
+fn bench(c: &mut Criterion) {
+    let cipher_suite = CipherSuite::CURVE25519_AES128;
+    let group_states = load_wide_group_states(cipher_suite);
+    let mut bench_group = c.benchmark_group("group_commit_wide");
+
+    for group_states in group_states {
+        let size = group_states.sender.roster().members_iter().count();
+
+        bench_group.bench_with_input(
+            BenchmarkId::new(format!("{cipher_suite:?}"), size),
+            &size,
+            |b, _| {
+                b.iter_batched_ref(
+                    || group_states.sender.clone(),
+                    move |sender| sender.commit(vec![]).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+}
+
+criterion::criterion_group!(benches, bench);
+criterion::criterion_main!(benches);
@@ -7,31 +7,46 @@ use crate::client_builder::{recreate_config, BaseConfig, ClientBuilder, MakeConf
 use crate::client_config::ClientConfig;
 use crate::group::framing::MlsMessage;
 
-use crate::group::{cipher_suite_provider, validate_group_info_joiner, GroupInfo};
 use crate::group::{
-    framing::MlsMessagePayload, snapshot::Snapshot, ExportedTree, Group, NewMemberInfo,
+    cipher_suite_provider, key_schedule::kdf_expand_with_label, validate_group_info_joiner,
+    verify_group_info, GroupConfigOverrides, GroupInfo, GroupInfoReport, GroupInfoTrustConfig,
+    ValidationReport,
 };
 #[cfg(feature = "by_ref_proposal")]
 use crate::group::{
-    framing::{Content, PublicMessage, Sender, WireFormat},
+    framing::{Content, PublicMessage, Sender},
     message_signature::AuthenticatedContent,
     proposal::{AddProposal, Proposal},
 };
+use crate::group::{
+    framing::{MlsMessagePayload, WireFormat},
+    snapshot::Snapshot,
+    state_integrity::open_group_state,
+    ExportedTree, Group, LazyGroup, NewMemberInfo, SnapshotFormat,
+};
 use crate::identity::SigningIdentity;
-use crate::key_package::{KeyPackageGeneration, KeyPackageGenerator};
+use crate::key_package::{KeyPackageBuilder, KeyPackageGeneration, KeyPackageGenerator};
 use crate::protocol_version::ProtocolVersion;
 use crate::tree_kem::node::NodeIndex;
+use crate::tree_kem::Capabilities;
 use alloc::vec::Vec;
-use mls_rs_codec::MlsDecode;
-use mls_rs_core::crypto::{CryptoProvider, SignatureSecretKey};
+use mls_rs_core::compression::CompressionAlgorithm;
+use mls_rs_core::crypto::{CipherSuiteProvider, CryptoProvider, SignatureSecretKey};
 use mls_rs_core::error::{AnyError, IntoAnyError};
 use mls_rs_core::extension::{ExtensionError, ExtensionList, ExtensionType};
 use mls_rs_core::group::{GroupStateStorage, ProposalType};
-use mls_rs_core::identity::{CredentialType, IdentityProvider, MemberValidationContext};
-use mls_rs_core::key_package::KeyPackageStorage;
+use mls_rs_core::identity::{
+    CredentialType, IdentityBootstrap, IdentityProvider, KeychainStorage, MemberValidationContext,
+};
+use mls_rs_core::key_package::{KeyPackageQuery, KeyPackageStorage};
+#[cfg(feature = "std")]
+use mls_rs_core::time::MlsTime;
 
 use crate::group::external_commit::ExternalCommitBuilder;
 
+#[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+use crate::group::ArchivedGroup;
+
 #[cfg(feature = "by_ref_proposal")]
 use alloc::boxed::Box;
 
@@ -49,6 +64,12 @@ pub enum MlsError {
     #[cfg_attr(feature = "std", error(transparent))]
     GroupStorageError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
+    KeychainStorageError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    IdentityBootstrapError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    EpochSecretsVaultError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
     PskStoreError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
     MlsRulesError(AnyError),
@@ -106,6 +127,16 @@ pub enum MlsError {
         error("Signing identity is not allowed to externally propose")
     )]
     InvalidExternalSigningIdentity,
+    #[cfg_attr(
+        feature = "std",
+        error("signed tree head is required by trust anchors")
+    )]
+    MissingSignedTreeHead,
+    #[cfg_attr(
+        feature = "std",
+        error("signed tree head does not match the group or is not signed by a trust anchor")
+    )]
+    InvalidSignedTreeHead,
     #[cfg_attr(feature = "std", error("Missing ExternalPub extension"))]
     MissingExternalPubExtension,
     #[cfg_attr(feature = "std", error("Epoch not found"))]
@@ -156,6 +187,8 @@ pub enum MlsError {
     MembershipTagForNonMember,
     #[cfg_attr(feature = "std", error("No member found for given identity id."))]
     MemberNotFound,
+    #[cfg_attr(feature = "std", error("more than one member matches"))]
+    AmbiguousMember,
     #[cfg_attr(feature = "std", error("group not found"))]
     GroupNotFound,
     #[cfg_attr(feature = "std", error("unexpected PSK ID"))]
@@ -166,6 +199,18 @@ pub enum MlsError {
     GroupIdMismatch,
     #[cfg_attr(feature = "std", error("storage retention can not be zero"))]
     NonZeroRetentionRequired,
+    #[cfg_attr(feature = "std", error("future epoch message buffer is full"))]
+    FutureEpochBufferFull,
+    #[cfg_attr(
+        feature = "std",
+        error("group is in epoch {0} instead of the epoch expected by the commit")
+    )]
+    UnexpectedCommitEpoch(u64),
+    #[cfg_attr(
+        feature = "std",
+        error("confirmed transcript hash differs from the one expected by the commit")
+    )]
+    UnexpectedConfirmedTranscriptHash,
     #[cfg_attr(feature = "std", error("Too many PSK IDs to compute PSK secret"))]
     TooManyPskIds,
     #[cfg_attr(feature = "std", error("Missing required Psk"))]
@@ -279,6 +324,11 @@ pub enum MlsError {
         error("More than one GroupContextExtensions proposal")
     )]
     MoreThanOneGroupContextExtensionsProposal,
+    #[cfg_attr(
+        feature = "std",
+        error("group context extensions changed since the expected hash was computed")
+    )]
+    GroupContextExtensionsChanged,
     #[cfg_attr(feature = "std", error("Invalid proposal type for sender"))]
     InvalidProposalTypeForSender,
     #[cfg_attr(
@@ -300,6 +350,11 @@ pub enum MlsError {
     ExternalCommitWithMoreThanOneRemove,
     #[cfg_attr(feature = "std", error("Duplicate PSK IDs"))]
     DuplicatePskIds,
+    #[cfg_attr(
+        feature = "std",
+        error("resumption PSK is not from an epoch of this group or its ancestors")
+    )]
+    ResumptionPskOutsideLineage,
     #[cfg_attr(
         feature = "std",
         error("Invalid proposal type {0:?} in external commit")
@@ -338,6 +393,210 @@ pub enum MlsError {
     InvalidGroupInfo,
     #[cfg_attr(feature = "std", error("Invalid welcome message"))]
     InvalidWelcomeMessage,
+    #[cfg_attr(feature = "std", error("archived group is read-only"))]
+    ArchivedGroupReadOnly,
+    #[cfg_attr(feature = "std", error("epoch authenticator proof does not match"))]
+    InvalidEpochAuthenticatorProof,
+    #[cfg_attr(feature = "std", error("bridged message provenance does not match"))]
+    InvalidBridgeProvenance,
+    #[cfg_attr(
+        feature = "std",
+        error("message is from an epoch older than the maximum message age")
+    )]
+    MaxMessageAgeExceeded,
+    #[cfg_attr(feature = "std", error("invalid membership token"))]
+    InvalidMembershipToken,
+    #[cfg_attr(feature = "std", error("membership token expired"))]
+    MembershipTokenExpired,
+    #[cfg_attr(
+        feature = "std",
+        error("membership proof does not match the group context")
+    )]
+    InvalidMembershipProof,
+    #[cfg_attr(feature = "std", error("unknown extension {0:?}"))]
+    UnknownExtension(ExtensionType),
+    #[cfg_attr(
+        feature = "std",
+        error("targeted message is addressed to another member")
+    )]
+    TargetedMessageRecipientMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("pairwise channel is addressed to another member")
+    )]
+    PairwiseChannelPeerMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("member {0} lacks a capability required by the new group context extensions")
+    )]
+    MemberLacksCapabilities(u32),
+    #[cfg_attr(feature = "std", error("a member can't quarantine itself"))]
+    CannotQuarantineSelf,
+    #[cfg_attr(feature = "std", error("a group with this group id already exists"))]
+    GroupIdCollision,
+    #[cfg_attr(feature = "std", error("unexpected data after the encoded message"))]
+    UnexpectedTrailingData,
+    #[cfg_attr(
+        feature = "std",
+        error("stored group state failed its integrity check")
+    )]
+    StorageIntegrityViolation,
+    #[cfg_attr(feature = "std", error("stored group state can't be parsed"))]
+    StorageCorrupted,
+    #[cfg_attr(
+        feature = "std",
+        error("operation token does not match the pending operation")
+    )]
+    InvalidOperationToken,
+    #[cfg_attr(
+        feature = "std",
+        error("member {0} has not confirmed joining the group")
+    )]
+    UnconfirmedMembers(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("smallest possible commit of {0} bytes exceeds the size limit")
+    )]
+    CommitChunkTooLarge(usize),
+    #[cfg_attr(feature = "std", error("wire format {0:?} can not frame the message"))]
+    UnsupportedWireFormat(WireFormat),
+    #[cfg_attr(
+        feature = "std",
+        error("pending commit is signed with a signer that was replaced")
+    )]
+    PendingCommitSignerReplaced,
+    #[cfg_attr(
+        feature = "std",
+        error("the local member is receive-only in this group")
+    )]
+    ReceiveOnlyGroup,
+    #[cfg_attr(
+        feature = "std",
+        error("HPKE export encapsulation is addressed to another member")
+    )]
+    HpkeExportRecipientMismatch,
+    #[cfg_attr(feature = "std", error("unsupported group state version {0}"))]
+    UnsupportedSnapshotVersion(u16),
+    #[cfg_attr(
+        feature = "std",
+        error("group state was written with other crate features {0:#x}")
+    )]
+    UnsupportedSnapshotFeatures(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("commit request is addressed to another member")
+    )]
+    CommitRequestDelegateMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("delegated commit does not match the commit request")
+    )]
+    DelegatedCommitMismatch,
+    #[cfg_attr(feature = "std", error(transparent))]
+    RosterObserverError(AnyError),
+    #[cfg_attr(feature = "std", error("invalid message chunk"))]
+    InvalidMessageChunk,
+    #[cfg_attr(
+        feature = "std",
+        error("reassembled message does not match the integrity tag of the sender")
+    )]
+    ChunkedMessageIntegrityError,
+    #[cfg_attr(
+        feature = "std",
+        error("local member or shares do not match the members of the subset key")
+    )]
+    SubsetMembershipMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("control message received as {0:?} violates the wire format policy")
+    )]
+    UnexpectedWireFormat(WireFormat),
+    #[cfg_attr(feature = "std", error(transparent))]
+    MessageCompressorError(AnyError),
+    #[cfg_attr(
+        feature = "std",
+        error("application message compressed with unsupported algorithm {0:?}")
+    )]
+    UnsupportedCompressionAlgorithm(CompressionAlgorithm),
+    #[cfg_attr(feature = "std", error("invalid compressed application message"))]
+    InvalidCompressedApplicationData,
+    #[cfg_attr(feature = "std", error("invalid reinit key package announcement"))]
+    InvalidReinitKeyPackageAnnouncement,
+    #[cfg_attr(feature = "std", error("completed operation cannot be rolled back"))]
+    OperationCompleted,
+    #[cfg_attr(
+        feature = "std",
+        error("too many proposal types override the wire format policy")
+    )]
+    TooManyProposalTypeOverrides,
+    #[cfg_attr(feature = "std", error("key package storage does not support queries"))]
+    KeyPackageQueryUnsupported,
+}
+
+/// Recovery strategy for an [`MlsError`], returned by
+/// [`MlsError::recovery`].
+///
+/// This describes what the local member should do after a failed operation.
+/// The reason reported to the sender of a rejected message is given by
+/// [`RejectionCode`](crate::group::RejectionCode) instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorRecovery {
+    /// The failure is transient, typically a failure of a storage provider.
+    /// The operation may succeed if retried later.
+    Retry,
+    /// The local group state diverged from the state of the other members or
+    /// can no longer be used. The member must rejoin the group, for example
+    /// with an external commit.
+    Resync,
+    /// The operation or the incoming message was rejected. Retrying it fails
+    /// in the same way.
+    Reject,
+}
+
+impl MlsError {
+    /// Recovery strategy for this error, so that applications can decide
+    /// how to recover without matching individual variants.
+    ///
+    /// Errors of the identity provider, crypto provider and MLS rules are
+    /// treated as rejections, as this crate can not tell whether they are
+    /// transient.
+    pub fn recovery(&self) -> ErrorRecovery {
+        match self {
+            MlsError::KeyPackageRepoError(_)
+            | MlsError::GroupStorageError(_)
+            | MlsError::KeychainStorageError(_)
+            | MlsError::IdentityBootstrapError(_)
+            | MlsError::EpochSecretsVaultError(_)
+            | MlsError::PskStoreError(_)
+            | MlsError::FutureEpochBufferFull
+            | MlsError::UnconfirmedMembers(_)
+            | MlsError::RosterObserverError(_) => ErrorRecovery::Retry,
+            MlsError::UnexpectedCommitEpoch(_)
+            | MlsError::UnexpectedConfirmedTranscriptHash
+            | MlsError::InvalidConfirmationTag
+            | MlsError::InvalidTreeKemPrivateKey
+            | MlsError::UpdateErrorNoSecretKey
+            | MlsError::KeyMissing(_)
+            | MlsError::StorageIntegrityViolation
+            | MlsError::StorageCorrupted
+            | MlsError::UnsupportedSnapshotVersion(_)
+            | MlsError::UnsupportedSnapshotFeatures(_) => ErrorRecovery::Resync,
+            _ => ErrorRecovery::Reject,
+        }
+    }
+
+    /// Whether the operation may succeed if retried. See
+    /// [`ErrorRecovery::Retry`].
+    pub fn is_retryable(&self) -> bool {
+        self.recovery() == ErrorRecovery::Retry
+    }
+
+    /// Whether the member must rejoin the group. See
+    /// [`ErrorRecovery::Resync`].
+    pub fn requires_resync(&self) -> bool {
+        self.recovery() == ErrorRecovery::Resync
+    }
 }
 
 impl IntoAnyError for MlsError {
@@ -435,16 +694,105 @@ where
         leaf_node_extensions: ExtensionList,
     ) -> Result<MlsMessage, MlsError> {
         Ok(self
-            .generate_key_package(key_package_extensions, leaf_node_extensions)
+            .generate_key_package(key_package_extensions, leaf_node_extensions, None, None)
             .await?
             .key_package_message())
     }
 
+    /// Create a builder for a key package message with extensions,
+    /// capabilities and lifetime chosen for this key package only, instead
+    /// of those of the client configuration.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn key_package_builder(&self) -> KeyPackageBuilder<'_, C> {
+        KeyPackageBuilder::new(self)
+    }
+
+    /// Key package messages held in the [KeyPackageStorage] of this client
+    /// that are selected by `query`, in no particular order.
+    ///
+    /// Fails with [`MlsError::KeyPackageQueryUnsupported`] if the storage
+    /// does not implement [`KeyPackageStorage::list`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn stored_key_packages(
+        &self,
+        query: &KeyPackageQuery,
+    ) -> Result<Vec<MlsMessage>, MlsError> {
+        self.config
+            .key_package_repo()
+            .list(query)
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?
+            .ok_or(MlsError::KeyPackageQueryUnsupported)?
+            .into_iter()
+            .map(|(id, data)| {
+                KeyPackageGeneration::from_storage(id, data).map(|kp| kp.key_package_message())
+            })
+            .collect()
+    }
+
+    /// Number of key packages held in the [KeyPackageStorage] of this client
+    /// that are selected by `query`, for example to decide when to generate
+    /// more of them. See [`Client::stored_key_packages`] for storages that
+    /// do not support queries.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn stored_key_package_count(
+        &self,
+        query: &KeyPackageQuery,
+    ) -> Result<usize, MlsError> {
+        self.config
+            .key_package_repo()
+            .count(query)
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?
+            .ok_or(MlsError::KeyPackageQueryUnsupported)
+    }
+
+    /// Stored key package message to offer for joining a group using
+    /// `cipher_suite` and `protocol_version`, or `None` if there is none.
+    ///
+    /// Among the matching key packages that are not expired, the one that
+    /// remains valid the longest is returned, leaving the most time to use
+    /// it. Expiration is not checked without the `std` feature. See
+    /// [`Client::stored_key_packages`] for storages that do not support
+    /// queries.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn best_key_package(
+        &self,
+        cipher_suite: CipherSuite,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<MlsMessage>, MlsError> {
+        let query = KeyPackageQuery::new()
+            .with_cipher_suite(cipher_suite)
+            .with_protocol_version(protocol_version);
+
+        #[cfg(feature = "std")]
+        let query = query.with_valid_at(MlsTime::now().seconds_since_epoch());
+
+        let best = self
+            .config
+            .key_package_repo()
+            .list(&query)
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?
+            .ok_or(MlsError::KeyPackageQueryUnsupported)?
+            .into_iter()
+            .max_by_key(|(_, data)| data.expiration);
+
+        best.map(|(id, data)| {
+            KeyPackageGeneration::from_storage(id, data).map(|kp| kp.key_package_message())
+        })
+        .transpose()
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn generate_key_package(
+    pub(crate) async fn generate_key_package(
         &self,
         key_package_extensions: ExtensionList,
         leaf_node_extensions: ExtensionList,
+        capabilities: Option<Capabilities>,
+        lifetime_in_s: Option<u64>,
     ) -> Result<KeyPackageGeneration, MlsError> {
         let (signing_identity, cipher_suite) = self.signing_identity()?;
 
@@ -459,12 +807,22 @@ where
             cipher_suite_provider: &cipher_suite_provider,
             signing_key: self.signer()?,
             signing_identity,
+            grease: self.config.grease(),
         };
 
+        let mut lifetime = self.config.lifetime();
+
+        if let Some(lifetime_in_s) = lifetime_in_s {
+            lifetime.not_after = lifetime
+                .not_before
+                .checked_add(lifetime_in_s)
+                .ok_or(MlsError::TimeOverflow)?;
+        }
+
         let key_pkg_gen = key_package_generator
             .generate(
-                self.config.lifetime(),
-                self.config.capabilities(),
+                lifetime,
+                capabilities.unwrap_or_else(|| self.config.capabilities()),
                 key_package_extensions,
                 leaf_node_extensions,
             )
@@ -492,6 +850,10 @@ where
     /// It is recommended to use [create_group](Client::create_group)
     /// instead of this function because it guarantees that group_id values
     /// are globally unique.
+    ///
+    /// Fails with [`MlsError::GroupIdCollision`] if a group with `group_id`
+    /// is already present in the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) of this client.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn create_group_with_id(
         &self,
@@ -501,6 +863,8 @@ where
     ) -> Result<Group<C>, MlsError> {
         let (signing_identity, cipher_suite) = self.signing_identity()?;
 
+        self.check_group_id_available(&group_id).await?;
+
         Group::new(
             self.config.clone(),
             Some(group_id),
@@ -514,6 +878,52 @@ where
         .await
     }
 
+    /// Derive a group identifier from application provided `context` using
+    /// the KDF of the cipher suite of this client.
+    ///
+    /// Clients that agree on `context`, for example the sorted identities of
+    /// the participants of a conversation, derive the same group identifier.
+    /// This lets multiple creators racing to create the same conversation
+    /// converge on a single group when the identifier is passed to
+    /// [`Client::create_group_with_id`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn derive_group_id(&self, context: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let (_, cipher_suite) = self.signing_identity()?;
+        let cipher_suite_provider =
+            cipher_suite_provider(self.config.crypto_provider(), cipher_suite)?;
+
+        let secret = cipher_suite_provider
+            .kdf_extract(&[], context)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let group_id = kdf_expand_with_label(
+            &cipher_suite_provider,
+            &secret,
+            b"derived group id",
+            &[],
+            None,
+        )
+        .await?;
+
+        Ok(group_id.to_vec())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn check_group_id_available(&self, group_id: &[u8]) -> Result<(), MlsError> {
+        let existing = self
+            .config
+            .group_state_storage()
+            .state(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        match existing {
+            Some(_) => Err(MlsError::GroupIdCollision),
+            None => Ok(()),
+        }
+    }
+
     /// Create a MLS group.
     ///
     /// The `cipher_suite` provided must be supported by the
@@ -540,6 +950,90 @@ where
         .await
     }
 
+    /// Create a MLS group with configuration overrides that apply to this
+    /// group only.
+    ///
+    /// This function behaves the same way as
+    /// [create_group_with_id](Client::create_group_with_id), or
+    /// [create_group](Client::create_group) if `group_id` is `None`. The
+    /// overrides are stored with the group state.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn create_group_with_overrides(
+        &self,
+        group_id: Option<Vec<u8>>,
+        group_context_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+        config_overrides: GroupConfigOverrides,
+    ) -> Result<Group<C>, MlsError> {
+        let (signing_identity, cipher_suite) = self.signing_identity()?;
+
+        if let Some(group_id) = &group_id {
+            self.check_group_id_available(group_id).await?;
+        }
+
+        let group = Group::new(
+            self.config.clone(),
+            group_id,
+            cipher_suite,
+            self.version,
+            signing_identity.clone(),
+            group_context_extensions,
+            leaf_node_extensions,
+            self.signer()?.clone(),
+        )
+        .await?;
+
+        Ok(group.with_config_overrides(config_overrides))
+    }
+
+    /// Create a MLS group and add the members owning `key_packages` to it.
+    ///
+    /// This creates the group as [`Client::create_group_with_id`] (or
+    /// [`Client::create_group`] if `group_id` is `None`), commits the addition
+    /// of all members and applies the commit. The returned welcome messages
+    /// must be sent to the new members. The ratchet tree needed to join can
+    /// be obtained with [`Group::export_tree`] if the
+    /// [`MlsRules`](crate::MlsRules) in use do not include it in the welcome
+    /// messages.
+    ///
+    /// Nothing is written to storage, so the group can simply be discarded
+    /// if this fails.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn create_group_with_members(
+        &self,
+        group_id: Option<Vec<u8>>,
+        key_packages: Vec<MlsMessage>,
+        group_context_extensions: ExtensionList,
+    ) -> Result<(Group<C>, Vec<MlsMessage>), MlsError> {
+        let mut group = match group_id {
+            Some(group_id) => {
+                self.create_group_with_id(group_id, group_context_extensions, Default::default())
+                    .await?
+            }
+            None => {
+                self.create_group(group_context_extensions, Default::default())
+                    .await?
+            }
+        };
+
+        if key_packages.is_empty() {
+            return Ok((group, Vec::new()));
+        }
+
+        let commit_output = key_packages
+            .into_iter()
+            .try_fold(group.commit_builder(), |builder, key_package| {
+                builder.add_member(key_package)
+            })?
+            .build()
+            .await?;
+
+        group.apply_pending_commit().await?;
+
+        Ok((group, commit_output.welcome_messages))
+    }
+
     /// Join a MLS group via a welcome message created by a
     /// [Commit](crate::group::CommitOutput).
     ///
@@ -564,6 +1058,27 @@ where
         .await
     }
 
+    /// Join a MLS group via a welcome message with configuration overrides
+    /// that apply to this group only.
+    ///
+    /// This function behaves the same way as [join_group](Client::join_group).
+    /// The overrides are stored with the group state.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn join_group_with_overrides(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &MlsMessage,
+        config_overrides: GroupConfigOverrides,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        let (group, new_member_info) = self.join_group(tree_data, welcome_message).await?;
+
+        Ok((
+            group.with_config_overrides(config_overrides),
+            new_member_info,
+        ))
+    }
+
     /// Decrypt GroupInfo encrypted in the Welcome message without actually joining
     /// the group. The ratchet tree is not needed.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -608,6 +1123,62 @@ where
         Ok(())
     }
 
+    /// Verify a GroupInfo message before joining the group with an
+    /// [external commit](Client::external_commit_builder).
+    ///
+    /// The ratchet tree is taken from the group info extensions or from
+    /// `tree_data` and validated, the signature is checked against the
+    /// signer's leaf, the extensions are checked for consistency with the
+    /// client configuration and the cipher suite is checked against the
+    /// crypto provider and `trust_config`. Problems are collected in the
+    /// returned report instead of failing, so that an error is only returned
+    /// if `group_info_message` does not contain a group info.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_group_info(
+        &self,
+        group_info_message: &MlsMessage,
+        tree_data: Option<ExportedTree<'_>>,
+        trust_config: &GroupInfoTrustConfig,
+    ) -> Result<GroupInfoReport, MlsError> {
+        let MlsMessagePayload::GroupInfo(group_info) = &group_info_message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        verify_group_info(
+            &self.config,
+            group_info_message.version,
+            group_info,
+            tree_data,
+            trust_config,
+        )
+        .await
+    }
+
+    /// Explain why joining a group with `welcome_message` fails.
+    ///
+    /// The welcome message is matched against the key packages of this client,
+    /// the group info is verified as with [`Client::verify_group_info`] and the
+    /// capabilities of the key package are checked against the extensions of
+    /// the group. If no issue is found, joining is attempted without storing
+    /// the group and its error, if any, is reported. An error is only returned
+    /// if `welcome_message` is not a welcome message.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn diagnose_welcome(
+        &self,
+        welcome_message: &MlsMessage,
+        tree_data: Option<ExportedTree<'_>>,
+    ) -> Result<ValidationReport, MlsError> {
+        Group::diagnose_welcome(
+            welcome_message,
+            tree_data,
+            self.config.clone(),
+            self.signer()?.clone(),
+        )
+        .await
+    }
+
     /// 0-RTT add to an existing [group](crate::group::Group)
     ///
     /// External commits allow for immediate entry into a
@@ -662,13 +1233,9 @@ where
         ))
     }
 
-    /// Load an existing group state into this client using the
-    /// [GroupStateStorage](crate::GroupStateStorage) that
-    /// this client was configured to use.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    #[inline(never)]
-    pub async fn load_group(&self, group_id: &[u8]) -> Result<Group<C>, MlsError> {
-        let snapshot = self
+    async fn stored_group_state(&self, group_id: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let data = self
             .config
             .group_state_storage()
             .state(group_id)
@@ -676,11 +1243,58 @@ where
             .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
             .ok_or(MlsError::GroupNotFound)?;
 
-        let snapshot = Snapshot::mls_decode(&mut &*snapshot)?;
+        open_group_state(&self.config, group_id, data).await
+    }
+
+    /// Load an existing group state into this client using the
+    /// [GroupStateStorage](crate::GroupStateStorage) that
+    /// this client was configured to use.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[inline(never)]
+    pub async fn load_group(&self, group_id: &[u8]) -> Result<Group<C>, MlsError> {
+        let snapshot = self.stored_group_state(group_id).await?;
+
+        let snapshot = Snapshot::from_bytes(&snapshot)?;
 
         Group::from_snapshot(self.config.clone(), snapshot).await
     }
 
+    /// Read the [`SnapshotFormat`] of a stored group state, for example to
+    /// check that it can be loaded by this version of the crate before
+    /// calling [`Client::load_group`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn stored_snapshot_format(
+        &self,
+        group_id: &[u8],
+    ) -> Result<SnapshotFormat, MlsError> {
+        let snapshot = self.stored_group_state(group_id).await?;
+        SnapshotFormat::read(&snapshot)
+    }
+
+    /// Load an existing group state like [`Client::load_group`], deferring
+    /// the decoding of the ratchet tree and the private group state until the
+    /// group is first used.
+    ///
+    /// This reduces the cost of loading a group when only its context is
+    /// needed, e.g. to route a message by epoch. See [`LazyGroup`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn load_group_lazy(&self, group_id: &[u8]) -> Result<LazyGroup<C>, MlsError> {
+        let snapshot = self.stored_group_state(group_id).await?;
+
+        LazyGroup::new(self.config.clone(), snapshot)
+    }
+
+    /// Open a group archive previously created with
+    /// [`Group::archive`](crate::Group::archive) and serialized with
+    /// [`ArchivedGroup::to_bytes`].
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn open_archived_group(&self, archive: &[u8]) -> Result<ArchivedGroup<C>, MlsError> {
+        ArchivedGroup::from_bytes(&self.config, archive)
+    }
+
     /// Load an existing group state into this client using the
     /// [GroupStateStorage](crate::GroupStateStorage) that
     /// this client was configured to use. The tree is taken from
@@ -692,15 +1306,9 @@ where
         group_id: &[u8],
         tree_data: ExportedTree<'_>,
     ) -> Result<Group<C>, MlsError> {
-        let snapshot = self
-            .config
-            .group_state_storage()
-            .state(group_id)
-            .await
-            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
-            .ok_or(MlsError::GroupNotFound)?;
+        let snapshot = self.stored_group_state(group_id).await?;
 
-        let mut snapshot = Snapshot::mls_decode(&mut &*snapshot)?;
+        let mut snapshot = Snapshot::from_bytes(&snapshot)?;
         snapshot.state.public_tree.nodes = tree_data.0.into_owned();
 
         Group::from_snapshot(self.config.clone(), snapshot).await
@@ -749,7 +1357,7 @@ where
         .await?;
 
         let key_package = self
-            .generate_key_package(key_package_extensions, leaf_node_extensions)
+            .generate_key_package(key_package_extensions, leaf_node_extensions, None, None)
             .await?
             .key_package;
 
@@ -794,6 +1402,51 @@ where
             .ok_or(MlsError::SignerNotFound)
     }
 
+    /// Get a copy of this client that uses the signing identity stored for
+    /// `cipher_suite` in the [`KeychainStorage`] this client was configured to use.
+    ///
+    /// If the keychain holds no identity for `cipher_suite`, the configured
+    /// [`IdentityBootstrap`] is invoked to provision one and the result is
+    /// stored in the keychain. [`MlsError::SignerNotFound`] is returned if the
+    /// bootstrap can not provision an identity either.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn with_cipher_suite(&self, cipher_suite: CipherSuite) -> Result<Self, MlsError> {
+        let mut keychain = self.config.keychain_storage();
+
+        let stored = keychain
+            .signing_identity(cipher_suite)
+            .await
+            .map_err(|e| MlsError::KeychainStorageError(e.into_any_error()))?;
+
+        let (signing_identity, signer) = match stored {
+            Some(stored) => stored,
+            None => {
+                let (signing_identity, signer) = self
+                    .config
+                    .identity_bootstrap()
+                    .bootstrap(cipher_suite)
+                    .await
+                    .map_err(|e| MlsError::IdentityBootstrapError(e.into_any_error()))?
+                    .ok_or(MlsError::SignerNotFound)?;
+
+                keychain
+                    .insert(cipher_suite, signing_identity.clone(), signer.clone())
+                    .await
+                    .map_err(|e| MlsError::KeychainStorageError(e.into_any_error()))?;
+
+                (signing_identity, signer)
+            }
+        };
+
+        Ok(Client::new(
+            self.config.clone(),
+            Some(signer),
+            Some((signing_identity, cipher_suite)),
+            self.version,
+        ))
+    }
+
     /// The [KeyPackageStorage] that this client was configured to use.
     #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
     pub fn key_package_store(&self) -> <C as ClientConfig>::KeyPackageRepository {
@@ -886,10 +1539,13 @@ mod tests {
     use super::*;
     use crate::{
         crypto::test_utils::TestCryptoProvider,
+        extension::test_utils::{TestExtension, TEST_EXTENSION_TYPE},
         identity::test_utils::{get_test_basic_credential, get_test_signing_identity},
+        storage_provider::in_memory::{InMemoryKeyPackageStorage, InMemoryKeychainStorage},
         tree_kem::leaf_node::LeafNodeSource,
     };
     use assert_matches::assert_matches;
+    use mls_rs_core::key_package::KeyPackageData;
 
     #[cfg(feature = "by_ref_proposal")]
     use crate::group::message_processor::ProposalMessageDescription;
@@ -904,6 +1560,68 @@ mod tests {
     use crate::psk::{ExternalPskId, PreSharedKey};
     use alloc::vec;
 
+    #[test]
+    fn errors_are_classified_by_recovery() {
+        let storage_error = MlsError::GroupStorageError(MlsError::GroupNotFound.into_any_error());
+
+        assert!(storage_error.is_retryable());
+        assert!(MlsError::UnexpectedCommitEpoch(3).requires_resync());
+        assert!(MlsError::StorageCorrupted.requires_resync());
+        assert_eq!(MlsError::InvalidSignature.recovery(), ErrorRecovery::Reject);
+        assert_eq!(MlsError::InvalidEpoch.recovery(), ErrorRecovery::Reject);
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct CountingBootstrap(alloc::sync::Arc<core::sync::atomic::AtomicUsize>);
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl IdentityBootstrap for CountingBootstrap {
+        type Error = core::convert::Infallible;
+
+        async fn bootstrap(
+            &self,
+            cipher_suite: CipherSuite,
+        ) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, Self::Error> {
+            self.0.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+
+            if cipher_suite != TEST_CIPHER_SUITE {
+                return Ok(None);
+            }
+
+            Ok(Some(
+                get_test_signing_identity(cipher_suite, b"bootstrap").await,
+            ))
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn identity_is_bootstrapped_once_and_stored_in_keychain() {
+        let bootstrap = CountingBootstrap::default();
+        let keychain = InMemoryKeychainStorage::default();
+
+        let client = TestClientBuilder::new_for_test()
+            .identity_bootstrap(bootstrap.clone())
+            .keychain_storage(keychain.clone())
+            .build();
+
+        let provisioned = client.with_cipher_suite(TEST_CIPHER_SUITE).await.unwrap();
+
+        provisioned
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (stored_identity, _) = keychain.get(TEST_CIPHER_SUITE).unwrap();
+        assert_eq!(provisioned.signing_identity().unwrap().0, &stored_identity);
+
+        client.with_cipher_suite(TEST_CIPHER_SUITE).await.unwrap();
+        assert_eq!(bootstrap.0.load(core::sync::atomic::Ordering::SeqCst), 1);
+
+        let res = client.with_cipher_suite(CipherSuite::new(0xFFFF)).await;
+        assert_matches!(res, Err(MlsError::SignerNotFound));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_keygen() {
         // This is meant to test the inputs to the internal key package generator
@@ -946,6 +1664,154 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_builder_overrides_client_config() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"foo").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut capabilities = client.config.capabilities();
+        capabilities.extensions.push(TEST_EXTENSION_TYPE.into());
+
+        let mut extensions = ExtensionList::new();
+        extensions.set_from(TestExtension::from(1)).unwrap();
+
+        let key_package_message = client
+            .key_package_builder()
+            .key_package_extensions(extensions.clone())
+            .leaf_node_extensions(extensions.clone())
+            .capabilities(capabilities.clone())
+            .lifetime(3600)
+            .build()
+            .await
+            .unwrap();
+
+        let key_package = key_package_message.clone().into_key_package().unwrap();
+
+        assert_eq!(key_package.extensions, extensions);
+        assert_eq!(key_package.leaf_node.ungreased_extensions(), extensions);
+        assert_eq!(key_package.leaf_node.ungreased_capabilities(), capabilities);
+
+        assert_matches!(
+            key_package.leaf_node.leaf_node_source,
+            LeafNodeSource::KeyPackage(lifetime) if lifetime.not_after - lifetime.not_before == 3600
+        );
+
+        // The key package can be used to join a group
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(key_package_message)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        client
+            .join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn best_key_package_is_the_longest_valid() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"foo").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut key_packages = Vec::new();
+
+        for lifetime in [3600, 7200, 1800] {
+            let key_package = client
+                .key_package_builder()
+                .lifetime(lifetime)
+                .build()
+                .await
+                .unwrap();
+
+            key_packages.push(key_package);
+        }
+
+        let query = KeyPackageQuery::new().with_cipher_suite(TEST_CIPHER_SUITE);
+        let count = client.stored_key_package_count(&query).await.unwrap();
+        assert_eq!(count, 3);
+
+        let stored = client.stored_key_packages(&query).await.unwrap();
+        assert_eq!(stored.len(), 3);
+
+        let best = client
+            .best_key_package(TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION)
+            .await
+            .unwrap();
+
+        assert_eq!(best, Some(key_packages[1].clone()));
+
+        let other_cipher_suite = TestCryptoProvider::all_supported_cipher_suites()
+            .into_iter()
+            .find(|cs| cs != &TEST_CIPHER_SUITE)
+            .unwrap();
+
+        let best = client
+            .best_key_package(other_cipher_suite, TEST_PROTOCOL_VERSION)
+            .await
+            .unwrap();
+
+        assert_eq!(best, None);
+    }
+
+    #[derive(Clone, Default)]
+    struct KeyPackageStorageWithoutQueries(InMemoryKeyPackageStorage);
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl KeyPackageStorage for KeyPackageStorageWithoutQueries {
+        type Error = <InMemoryKeyPackageStorage as KeyPackageStorage>::Error;
+
+        async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
+            KeyPackageStorage::delete(&mut self.0, id).await
+        }
+
+        async fn insert(&mut self, id: Vec<u8>, pkg: KeyPackageData) -> Result<(), Self::Error> {
+            KeyPackageStorage::insert(&mut self.0, id, pkg).await
+        }
+
+        async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
+            KeyPackageStorage::get(&self.0, id).await
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_queries_fail_if_unsupported() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"foo").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .key_package_repo(KeyPackageStorageWithoutQueries::default())
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        client
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let res = client
+            .stored_key_package_count(&KeyPackageQuery::new())
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyPackageQueryUnsupported));
+
+        let res = client
+            .best_key_package(TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION)
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyPackageQueryUnsupported));
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn new_member_add_proposal_adds_to_group() {
@@ -1159,6 +2025,266 @@ mod tests {
         assert_eq!(bob.config.supported_extensions(), [33, 34].map(Into::into));
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn create_group_with_members() {
+        let alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let (bob, bob_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let (carol, carol_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let (group, welcome_messages) = alice
+            .create_group_with_members(
+                Some(b"group".to_vec()),
+                vec![bob_kp, carol_kp],
+                Default::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(group.group_id(), b"group");
+        assert_eq!(group.current_epoch(), 1);
+        assert_eq!(group.roster().members().len(), 3);
+
+        let tree = group.export_tree();
+
+        for (client, welcome) in [&bob, &carol]
+            .into_iter()
+            .zip(welcome_messages.iter().cycle())
+        {
+            let (joined, _) = client
+                .join_group(Some(tree.clone()), welcome)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                joined.epoch_authenticator().unwrap(),
+                group.epoch_authenticator().unwrap()
+            );
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn derived_group_ids_converge_and_collisions_are_detected() {
+        let alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let group_id = alice.derive_group_id(b"alice,bob").await.unwrap();
+        let same_context = bob.derive_group_id(b"alice,bob").await.unwrap();
+        let other_context = alice.derive_group_id(b"alice,carol").await.unwrap();
+
+        assert_eq!(group_id, same_context);
+        assert_ne!(group_id, other_context);
+
+        let mut group = alice
+            .create_group_with_id(group_id.clone(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        let res = alice
+            .create_group_with_id(group_id, Default::default(), Default::default())
+            .await;
+
+        assert_matches!(res.map(|_| ()), Err(MlsError::GroupIdCollision));
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn dyn_provider_client(name: &str) -> Client<impl ClientConfig> {
+        use crate::storage_provider::in_memory::{
+            InMemoryGroupStateStorage, InMemoryKeyPackageStorage, InMemoryPreSharedKeyStorage,
+        };
+        use mls_rs_core::dyn_provider::{
+            DynCryptoProvider, DynGroupStateStorage, DynIdentityProvider, DynKeyPackageStorage,
+            DynPreSharedKeyStorage,
+        };
+
+        let (signing_identity, signer) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, name.as_bytes()).await;
+
+        Client::builder()
+            .crypto_provider(DynCryptoProvider::new(TestCryptoProvider::new()))
+            .identity_provider(DynIdentityProvider::new(
+                crate::identity::basic::BasicIdentityProvider::new(),
+            ))
+            .group_state_storage(DynGroupStateStorage::new(
+                InMemoryGroupStateStorage::default(),
+            ))
+            .key_package_repo(DynKeyPackageStorage::new(
+                InMemoryKeyPackageStorage::default(),
+            ))
+            .psk_store(DynPreSharedKeyStorage::new(
+                InMemoryPreSharedKeyStorage::default(),
+            ))
+            .signing_identity(signing_identity, signer, TEST_CIPHER_SUITE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn client_works_with_dyn_providers() {
+        let alice = dyn_provider_client("alice").await;
+        let bob = dyn_provider_client("bob").await;
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (mut alice_group, welcome) = alice
+            .create_group_with_members(None, vec![key_package], Default::default())
+            .await
+            .unwrap();
+
+        let (bob_group, _) = bob.join_group(None, &welcome[0]).await.unwrap();
+
+        alice_group.write_to_storage().await.unwrap();
+        let loaded = alice.load_group(alice_group.group_id()).await.unwrap();
+
+        assert_eq!(
+            loaded.epoch_authenticator().unwrap(),
+            bob_group.epoch_authenticator().unwrap()
+        );
+    }
+
+    /// Keystore holding software keys, handing out the index of a key as its
+    /// handle.
+    #[cfg(feature = "std")]
+    #[derive(Clone, Default)]
+    struct TestKeystore(alloc::sync::Arc<std::sync::Mutex<Vec<SignatureSecretKey>>>);
+
+    #[cfg(feature = "std")]
+    impl TestKeystore {
+        fn key(&self, handle: &[u8]) -> Result<SignatureSecretKey, MlsError> {
+            let index = *handle.first().ok_or(MlsError::SignerNotFound)? as usize;
+            let keys = self.0.lock().unwrap();
+            keys.get(index).cloned().ok_or(MlsError::SignerNotFound)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl mls_rs_core::crypto::HardwareKeystore for TestKeystore {
+        type Error = MlsError;
+
+        async fn generate_key(
+            &self,
+            cipher_suite: CipherSuite,
+        ) -> Result<(Vec<u8>, crate::crypto::SignaturePublicKey), Self::Error> {
+            let provider = crate::crypto::test_utils::test_cipher_suite_provider(cipher_suite);
+            let (secret_key, public_key) = provider.signature_key_generate().await.unwrap();
+
+            let mut keys = self.0.lock().unwrap();
+            keys.push(secret_key);
+
+            Ok((vec![keys.len() as u8 - 1], public_key))
+        }
+
+        async fn public_key(
+            &self,
+            handle: &[u8],
+        ) -> Result<crate::crypto::SignaturePublicKey, Self::Error> {
+            let provider = crate::crypto::test_utils::test_cipher_suite_provider(TEST_CIPHER_SUITE);
+            let key = self.key(handle)?;
+            Ok(provider.signature_key_derive_public(&key).await.unwrap())
+        }
+
+        async fn sign(
+            &self,
+            cipher_suite: CipherSuite,
+            handle: &[u8],
+            data: &[u8],
+        ) -> Result<Vec<u8>, Self::Error> {
+            let provider = crate::crypto::test_utils::test_cipher_suite_provider(cipher_suite);
+            let key = self.key(handle)?;
+            Ok(provider.sign(&key, data).await.unwrap())
+        }
+
+        async fn delete_key(&self, handle: &[u8]) -> Result<(), Self::Error> {
+            self.key(handle)?;
+            self.0.lock().unwrap()[handle[0] as usize] = SignatureSecretKey::new(vec![]);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn client_signs_with_hardware_keystore() {
+        use mls_rs_core::crypto::{
+            HardwareKeychainStorage, HardwareKeystoreCryptoProvider, HardwareKeystoreError,
+        };
+
+        let keystore = TestKeystore::default();
+        let crypto =
+            HardwareKeystoreCryptoProvider::new(TestCryptoProvider::new(), keystore.clone());
+
+        let (handle, public_key) = crypto
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap()
+            .signature_key_generate()
+            .await
+            .unwrap();
+
+        assert_eq!(handle.as_bytes(), &[0]);
+
+        let identity =
+            SigningIdentity::new(get_test_basic_credential(b"alice".to_vec()), public_key);
+        let mut keychain =
+            HardwareKeychainStorage::new(InMemoryKeychainStorage::default(), keystore.clone());
+
+        let (other_identity, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let res = keychain
+            .insert(TEST_CIPHER_SUITE, other_identity, handle.clone())
+            .await;
+
+        assert_matches!(res, Err(HardwareKeystoreError::PublicKeyMismatch));
+
+        keychain
+            .insert(TEST_CIPHER_SUITE, identity, handle)
+            .await
+            .unwrap();
+
+        let alice = Client::builder()
+            .crypto_provider(crypto)
+            .identity_provider(crate::identity::basic::BasicIdentityProvider::new())
+            .keychain_storage(keychain)
+            .build()
+            .with_cipher_suite(TEST_CIPHER_SUITE)
+            .await
+            .unwrap();
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (_, welcome) = alice
+            .create_group_with_members(None, vec![key_package], Default::default())
+            .await
+            .unwrap();
+
+        bob.join_group(None, &welcome[0]).await.unwrap();
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn examine_welcome_message() {
         let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
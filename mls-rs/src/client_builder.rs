@@ -10,17 +10,19 @@ use crate::{
     cipher_suite::CipherSuite,
     client::Client,
     client_config::ClientConfig,
-    extension::ExtensionType,
+    compression::{ApplicationCompressionExt, NoCompression},
+    extension::{ExtensionType, MlsCodecExtension},
     group::{
         mls_rules::{DefaultMlsRules, MlsRules},
         proposal::ProposalType,
     },
     identity::CredentialType,
-    identity::SigningIdentity,
+    identity::{NoIdentityBootstrap, SigningIdentity},
     protocol_version::ProtocolVersion,
-    psk::{ExternalPskId, PreSharedKey},
+    psk::{ExternalPskId, ExternalPskResolver, PreSharedKey, ResolvingPskStore},
     storage_provider::in_memory::{
-        InMemoryGroupStateStorage, InMemoryKeyPackageStorage, InMemoryPreSharedKeyStorage,
+        InMemoryGroupStateStorage, InMemoryKeyPackageStorage, InMemoryKeychainStorage,
+        InMemoryPreSharedKeyStorage,
     },
     tree_kem::{Capabilities, Lifetime},
     Sealed,
@@ -30,6 +32,7 @@ use crate::{
 use crate::time::MlsTime;
 
 use alloc::vec::Vec;
+use mls_rs_core::secret::Secret;
 
 #[cfg(feature = "sqlite")]
 use mls_rs_provider_sqlite::{
@@ -43,6 +46,22 @@ use mls_rs_provider_sqlite::{
 #[cfg(feature = "private_message")]
 pub use crate::group::padding::PaddingMode;
 
+#[cfg(feature = "private_message")]
+pub use crate::group::mls_rules::ReceivedControlWireFormat;
+
+#[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+pub use crate::group::secret_tree::{KeyRetention, SkippedKeyStorage, TreeRatchetExt};
+
+pub use crate::extension::UnknownExtensionPolicy;
+
+mod profile;
+
+pub use profile::Profile;
+
+/// Default services of a client configuration.
+pub type DefaultServices =
+    Services<NoIdentityBootstrap, InMemoryKeychainStorage, DefaultEpochSecretsVault, NoCompression>;
+
 /// Base client configuration type when instantiating `ClientBuilder`
 pub type BaseConfig = Config<
     InMemoryKeyPackageStorage,
@@ -200,6 +219,7 @@ impl ClientBuilder<BaseConfig> {
             identity_provider: Missing,
             mls_rules: DefaultMlsRules::new(),
             crypto_provider: Missing,
+            services: Default::default(),
             signer: Default::default(),
             signing_identity: Default::default(),
             version: ProtocolVersion::MLS_10,
@@ -217,6 +237,7 @@ impl ClientBuilder<EmptyConfig> {
             identity_provider: Missing,
             mls_rules: Missing,
             crypto_provider: Missing,
+            services: Default::default(),
             signer: Default::default(),
             signing_identity: Default::default(),
             version: ProtocolVersion::MLS_10,
@@ -238,6 +259,7 @@ impl ClientBuilder<BaseSqlConfig> {
             identity_provider: Missing,
             mls_rules: DefaultMlsRules::new(),
             crypto_provider: Missing,
+            services: Default::default(),
             signer: Default::default(),
             signing_identity: Default::default(),
             version: ProtocolVersion::MLS_10,
@@ -304,6 +326,130 @@ impl<C: IntoConfig> ClientBuilder<C> {
         ClientBuilder(c)
     }
 
+    /// Set a key used to authenticate the group states written to the
+    /// [`GroupStateStorage`](crate::GroupStateStorage).
+    ///
+    /// Every group state and prior epoch is stored with a MAC computed with
+    /// this key and checked when the group is loaded. A value that was
+    /// modified results in [`MlsError::StorageIntegrityViolation`](crate::client::MlsError::StorageIntegrityViolation), and a value
+    /// that can't be parsed results in [`MlsError::StorageCorrupted`](crate::client::MlsError::StorageCorrupted). Groups
+    /// stored without a key can't be loaded once a key is set.
+    ///
+    /// A group state replaced with an older one is detected as well if the
+    /// storage keeps a high water mark with
+    /// [`GroupStateStorage::integrity_counter`](crate::GroupStateStorage::integrity_counter).
+    /// A prior epoch replaced with an older copy of the same epoch is not
+    /// detected, which can make message keys of that epoch that were deleted
+    /// after use available again.
+    pub fn storage_integrity_key(self, key: Vec<u8>) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.storage_integrity_key = Some(key.into());
+        ClientBuilder(c)
+    }
+
+    /// Set the retention of message keys used to decrypt received messages.
+    ///
+    /// By default, [`KeyRetention::Standard`] is used. See [`KeyRetention`] for the
+    /// retention windows of each setting.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub fn key_retention(self, key_retention: KeyRetention) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.key_retention = key_retention;
+        ClientBuilder(c)
+    }
+
+    /// Set how message keys skipped by received messages are stored.
+    ///
+    /// By default, [`SkippedKeyStorage::Keys`] is used. See
+    /// [`SkippedKeyStorage`] for the trade-offs of each setting.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub fn skipped_key_storage(
+        self,
+        skipped_key_storage: SkippedKeyStorage,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.skipped_key_storage = skipped_key_storage;
+        ClientBuilder(c)
+    }
+
+    /// Set how the client handles extensions whose type it does not support.
+    ///
+    /// By default, [`UnknownExtensionPolicy::Retain`] is used. Deployments that
+    /// must fail closed can use [`UnknownExtensionPolicy::Reject`].
+    pub fn unknown_extension_policy(
+        self,
+        policy: UnknownExtensionPolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.unknown_extension_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set whether GREASE values are added to key packages, leaf nodes and
+    /// group info messages.
+    ///
+    /// GREASE values, reserved by RFC 9420, exercise the handling of unknown
+    /// values in other implementations. They are added only if the `grease`
+    /// feature is enabled, in which case this setting defaults to `true`.
+    pub fn grease(self, grease: bool) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.grease = grease;
+        ClientBuilder(c)
+    }
+
+    /// Set whether groups created by this client require the capabilities it
+    /// is configured with.
+    ///
+    /// If enabled, a group created without a
+    /// [`RequiredCapabilitiesExt`](crate::extension::built_in::RequiredCapabilitiesExt)
+    /// gets one listing the custom [extension types](ClientBuilder::extension_type)
+    /// and [proposal types](ClientBuilder::custom_proposal_type) supported by
+    /// the client, so that members not supporting them can't be added. By
+    /// default, no such extension is added.
+    pub fn require_configured_capabilities(
+        self,
+        require: bool,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.require_configured_capabilities = require;
+        ClientBuilder(c)
+    }
+
+    /// Set the maximum age in seconds of prior epochs from which received
+    /// messages are decrypted.
+    ///
+    /// The age of an epoch is the time elapsed since the commit ending it was
+    /// processed. This bounds how long the keys of a prior epoch remain useful
+    /// to an attacker in wall-clock time, independently of the number of
+    /// epochs retained by the [`GroupStateStorage`](crate::GroupStateStorage).
+    /// Messages from older epochs are rejected with
+    /// [`MlsError::MaxMessageAgeExceeded`](crate::client::MlsError::MaxMessageAgeExceeded).
+    ///
+    /// By default, messages are accepted from any retained epoch. Epochs are
+    /// only timestamped with the `std` feature.
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    pub fn max_message_age(self, age_in_s: u64) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.max_message_age = Some(age_in_s);
+        ClientBuilder(c)
+    }
+
+    /// Set the wire format required for proposals and commits received from
+    /// members.
+    ///
+    /// Control messages violating the policy are rejected with
+    /// [`MlsError::UnexpectedWireFormat`](crate::client::MlsError::UnexpectedWireFormat).
+    /// By default, [`ReceivedControlWireFormat::Any`] is used.
+    #[cfg(feature = "private_message")]
+    pub fn received_control_wire_format(
+        self,
+        policy: ReceivedControlWireFormat,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.received_control_wire_format = policy;
+        ClientBuilder(c)
+    }
+
     /// Set the key package repository to be used by the client.
     ///
     /// By default, an in-memory repository is used.
@@ -321,6 +467,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            services: c.services,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -344,6 +491,37 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            services: c.services,
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set a resolver called for each external PSK not found in the PSK
+    /// store, for example to fetch it from a secret manager when a welcome
+    /// message references it.
+    ///
+    /// The resolver wraps the PSK store set so far, so the store and the
+    /// PSKs added with [`ClientBuilder::psk`] must be set before the resolver.
+    pub fn psk_resolver<R>(
+        self,
+        resolver: R,
+    ) -> ClientBuilder<WithPskStore<ResolvingPskStore<<C as IntoConfig>::PskStore, R>, C>>
+    where
+        R: ExternalPskResolver,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: ResolvingPskStore::new(c.psk_store, resolver),
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            services: c.services,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -370,6 +548,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             crypto_provider: c.crypto_provider,
             mls_rules: c.mls_rules,
+            services: c.services,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -394,6 +573,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            services: c.services,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -418,6 +598,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider,
+            services: c.services,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -445,6 +626,148 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules,
             crypto_provider: c.crypto_provider,
+            services: c.services,
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the hook used to provision a signing identity when none is stored in the keychain
+    /// for a requested cipher suite.
+    ///
+    /// See [`Client::with_cipher_suite`]. By default, no identity is ever provisioned.
+    pub fn identity_bootstrap<Ib>(
+        self,
+        identity_bootstrap: Ib,
+    ) -> ClientBuilder<WithIdentityBootstrap<Ib, C>>
+    where
+        Ib: IdentityBootstrap,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            services: Services {
+                identity_bootstrap,
+                keychain_storage: c.services.keychain_storage,
+                epoch_secrets_vault: c.services.epoch_secrets_vault,
+                message_compressor: c.services.message_compressor,
+            },
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the keychain storage holding the signing identities of the client.
+    ///
+    /// By default, an in-memory storage is used.
+    pub fn keychain_storage<Kc>(
+        self,
+        keychain_storage: Kc,
+    ) -> ClientBuilder<WithKeychainStorage<Kc, C>>
+    where
+        Kc: KeychainStorage,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            services: Services {
+                identity_bootstrap: c.services.identity_bootstrap,
+                keychain_storage,
+                epoch_secrets_vault: c.services.epoch_secrets_vault,
+                message_compressor: c.services.message_compressor,
+            },
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the vault holding the secrets of the key schedule of groups.
+    ///
+    /// By default, [`DefaultEpochSecretsVault`] keeps them in memory with the group state.
+    pub fn epoch_secrets_vault<Esv>(
+        self,
+        epoch_secrets_vault: Esv,
+    ) -> ClientBuilder<WithEpochSecretsVault<Esv, C>>
+    where
+        Esv: EpochSecretsVault,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            services: Services {
+                identity_bootstrap: c.services.identity_bootstrap,
+                keychain_storage: c.services.keychain_storage,
+                epoch_secrets_vault,
+                message_compressor: c.services.message_compressor,
+            },
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the compressor applied to application messages in groups using
+    /// the [`ApplicationCompressionExt`](crate::compression::ApplicationCompressionExt)
+    /// group context extension.
+    ///
+    /// If the compressor implements an algorithm, the extension is added to the
+    /// extension types supported by the client. By default, [`NoCompression`]
+    /// is used.
+    pub fn message_compressor<Mc>(
+        self,
+        message_compressor: Mc,
+    ) -> ClientBuilder<WithMessageCompressor<Mc, C>>
+    where
+        Mc: MessageCompressor,
+    {
+        let Config(mut c) = self.0.into_config();
+
+        let extension_type = ApplicationCompressionExt::extension_type();
+
+        if message_compressor.algorithm().is_some()
+            && !c.settings.extension_types.contains(&extension_type)
+        {
+            c.settings.extension_types.push(extension_type);
+        }
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            services: Services {
+                identity_bootstrap: c.services.identity_bootstrap,
+                keychain_storage: c.services.keychain_storage,
+                epoch_secrets_vault: c.services.epoch_secrets_vault,
+                message_compressor,
+            },
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -501,6 +824,10 @@ where
     C::IdentityProvider: IdentityProvider + Clone,
     C::MlsRules: MlsRules + Clone,
     C::CryptoProvider: CryptoProvider + Clone,
+    C::IdentityBootstrap: IdentityBootstrap + Clone,
+    C::KeychainStorage: KeychainStorage + Clone,
+    C::EpochSecretsVault: EpochSecretsVault + Clone,
+    C::MessageCompressor: MessageCompressor + Clone,
 {
     pub(crate) fn build_config(self) -> IntoConfigOutput<C> {
         let mut c = self.0.into_config();
@@ -539,6 +866,24 @@ impl<C: IntoConfig<PskStore = InMemoryPreSharedKeyStorage>> ClientBuilder<C> {
     }
 }
 
+impl<C: IntoConfig<MlsRules = DefaultMlsRules>> ClientBuilder<C> {
+    /// Apply the settings of a preset [`Profile`].
+    ///
+    /// This replaces the MLS rules, the key package lifetime, the
+    /// [GREASE](ClientBuilder::grease) setting and the
+    /// [required capabilities](ClientBuilder::require_configured_capabilities)
+    /// setting currently set in the builder. Settings changed after calling this function take precedence over
+    /// the profile.
+    pub fn profile(self, profile: Profile) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.lifetime_in_s = profile.key_package_lifetime();
+        c.0.settings.grease = profile.grease();
+        c.0.settings.require_configured_capabilities = profile.require_configured_capabilities();
+        c.0.mls_rules = profile.mls_rules();
+        ClientBuilder(c)
+    }
+}
+
 /// Marker type for required `ClientBuilder` services that have not been specified yet.
 #[derive(Debug)]
 pub struct Missing;
@@ -553,6 +898,7 @@ pub type WithKeyPackageRepo<K, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    IntoServicesOutput<C>,
 >;
 
 /// Change the PSK store used by a client configuration.
@@ -565,6 +911,7 @@ pub type WithPskStore<P, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    IntoServicesOutput<C>,
 >;
 
 /// Change the group state storage used by a client configuration.
@@ -577,6 +924,7 @@ pub type WithGroupStateStorage<G, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    IntoServicesOutput<C>,
 >;
 
 /// Change the identity validator used by a client configuration.
@@ -589,6 +937,7 @@ pub type WithIdentityProvider<I, C> = Config<
     I,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    IntoServicesOutput<C>,
 >;
 
 /// Change the proposal rules used by a client configuration.
@@ -601,6 +950,7 @@ pub type WithMlsRules<Pr, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     Pr,
     <C as IntoConfig>::CryptoProvider,
+    IntoServicesOutput<C>,
 >;
 
 /// Change the crypto provider used by a client configuration.
@@ -613,6 +963,79 @@ pub type WithCryptoProvider<Cp, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     Cp,
+    IntoServicesOutput<C>,
+>;
+
+/// Change the identity bootstrap used by a client configuration.
+///
+/// See [`ClientBuilder::identity_bootstrap`].
+pub type WithIdentityBootstrap<Ib, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    Services<
+        Ib,
+        <C as IntoConfig>::KeychainStorage,
+        <C as IntoConfig>::EpochSecretsVault,
+        <C as IntoConfig>::MessageCompressor,
+    >,
+>;
+
+/// Change the keychain storage used by a client configuration.
+///
+/// See [`ClientBuilder::keychain_storage`].
+pub type WithKeychainStorage<Kc, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    Services<
+        <C as IntoConfig>::IdentityBootstrap,
+        Kc,
+        <C as IntoConfig>::EpochSecretsVault,
+        <C as IntoConfig>::MessageCompressor,
+    >,
+>;
+
+/// Change the epoch secrets vault used by a client configuration.
+///
+/// See [`ClientBuilder::epoch_secrets_vault`].
+pub type WithEpochSecretsVault<Esv, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    Services<
+        <C as IntoConfig>::IdentityBootstrap,
+        <C as IntoConfig>::KeychainStorage,
+        Esv,
+        <C as IntoConfig>::MessageCompressor,
+    >,
+>;
+
+/// Change the message compressor used by a client configuration.
+///
+/// See [`ClientBuilder::message_compressor`].
+pub type WithMessageCompressor<Mc, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    Services<
+        <C as IntoConfig>::IdentityBootstrap,
+        <C as IntoConfig>::KeychainStorage,
+        <C as IntoConfig>::EpochSecretsVault,
+        Mc,
+    >,
 >;
 
 /// Helper alias for `Config`.
@@ -623,6 +1046,15 @@ pub type IntoConfigOutput<C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    IntoServicesOutput<C>,
+>;
+
+/// Helper alias for the services of a `Config`.
+pub type IntoServicesOutput<C> = Services<
+    <C as IntoConfig>::IdentityBootstrap,
+    <C as IntoConfig>::KeychainStorage,
+    <C as IntoConfig>::EpochSecretsVault,
+    <C as IntoConfig>::MessageCompressor,
 >;
 
 /// Helper alias to make a `Config` from a `ClientConfig`
@@ -633,9 +1065,16 @@ pub type MakeConfig<C> = Config<
     <C as ClientConfig>::IdentityProvider,
     <C as ClientConfig>::MlsRules,
     <C as ClientConfig>::CryptoProvider,
+    Services<
+        <C as ClientConfig>::IdentityBootstrap,
+        <C as ClientConfig>::KeychainStorage,
+        <C as ClientConfig>::EpochSecretsVault,
+        <C as ClientConfig>::MessageCompressor,
+    >,
 >;
 
-impl<Kpr, Ps, Gss, Ip, Pr, Cp> ClientConfig for ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp>
+impl<Kpr, Ps, Gss, Ip, Pr, Cp, Ib, Kc, Esv, Mc> ClientConfig
+    for ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Services<Ib, Kc, Esv, Mc>>
 where
     Kpr: KeyPackageStorage + Clone,
     Ps: PreSharedKeyStorage + Clone,
@@ -643,6 +1082,10 @@ where
     Ip: IdentityProvider + Clone,
     Pr: MlsRules + Clone,
     Cp: CryptoProvider + Clone,
+    Ib: IdentityBootstrap + Clone,
+    Kc: KeychainStorage + Clone,
+    Esv: EpochSecretsVault + Clone,
+    Mc: MessageCompressor + Clone,
 {
     type KeyPackageRepository = Kpr;
     type PskStore = Ps;
@@ -650,6 +1093,10 @@ where
     type IdentityProvider = Ip;
     type MlsRules = Pr;
     type CryptoProvider = Cp;
+    type IdentityBootstrap = Ib;
+    type KeychainStorage = Kc;
+    type EpochSecretsVault = Esv;
+    type MessageCompressor = Mc;
 
     fn supported_extensions(&self) -> Vec<ExtensionType> {
         self.settings.extension_types.clone()
@@ -683,6 +1130,22 @@ where
         self.crypto_provider.clone()
     }
 
+    fn identity_bootstrap(&self) -> Self::IdentityBootstrap {
+        self.services.identity_bootstrap.clone()
+    }
+
+    fn keychain_storage(&self) -> Self::KeychainStorage {
+        self.services.keychain_storage.clone()
+    }
+
+    fn epoch_secrets_vault(&self) -> Self::EpochSecretsVault {
+        self.services.epoch_secrets_vault.clone()
+    }
+
+    fn message_compressor(&self) -> Self::MessageCompressor {
+        self.services.message_compressor.clone()
+    }
+
     fn lifetime(&self) -> Lifetime {
         #[cfg(feature = "std")]
         let now_timestamp = MlsTime::now().seconds_since_epoch();
@@ -705,11 +1168,51 @@ where
     fn supported_custom_proposals(&self) -> Vec<crate::group::proposal::ProposalType> {
         self.settings.custom_proposal_types.clone()
     }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn key_retention(&self) -> KeyRetention {
+        self.settings.key_retention
+    }
+
+    fn storage_integrity_key(&self) -> Option<Secret> {
+        self.settings.storage_integrity_key.clone()
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn skipped_key_storage(&self) -> SkippedKeyStorage {
+        self.settings.skipped_key_storage
+    }
+
+    fn unknown_extension_policy(&self) -> UnknownExtensionPolicy {
+        self.settings.unknown_extension_policy
+    }
+
+    fn grease(&self) -> bool {
+        self.settings.grease
+    }
+
+    fn require_configured_capabilities(&self) -> bool {
+        self.settings.require_configured_capabilities
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    fn max_message_age(&self) -> Option<u64> {
+        self.settings.max_message_age
+    }
+
+    #[cfg(feature = "private_message")]
+    fn received_control_wire_format(&self) -> ReceivedControlWireFormat {
+        self.settings.received_control_wire_format
+    }
 }
 
-impl<Kpr, Ps, Gss, Ip, Pr, Cp> Sealed for Config<Kpr, Ps, Gss, Ip, Pr, Cp> {}
+impl<Kpr, Ps, Gss, Ip, Pr, Cp, Ib, Kc, Esv, Mc> Sealed
+    for Config<Kpr, Ps, Gss, Ip, Pr, Cp, Services<Ib, Kc, Esv, Mc>>
+{
+}
 
-impl<Kpr, Ps, Gss, Ip, Pr, Cp> MlsConfig for Config<Kpr, Ps, Gss, Ip, Pr, Cp>
+impl<Kpr, Ps, Gss, Ip, Pr, Cp, Ib, Kc, Esv, Mc> MlsConfig
+    for Config<Kpr, Ps, Gss, Ip, Pr, Cp, Services<Ib, Kc, Esv, Mc>>
 where
     Kpr: KeyPackageStorage + Clone,
 
@@ -718,8 +1221,12 @@ where
     Ip: IdentityProvider + Clone,
     Pr: MlsRules + Clone,
     Cp: CryptoProvider + Clone,
+    Ib: IdentityBootstrap + Clone,
+    Kc: KeychainStorage + Clone,
+    Esv: EpochSecretsVault + Clone,
+    Mc: MessageCompressor + Clone,
 {
-    type Output = ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp>;
+    type Output = ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Services<Ib, Kc, Esv, Mc>>;
 
     fn get(&self) -> &Self::Output {
         &self.0
@@ -745,6 +1252,10 @@ impl<T: MlsConfig> ClientConfig for T {
     type IdentityProvider = <T::Output as ClientConfig>::IdentityProvider;
     type MlsRules = <T::Output as ClientConfig>::MlsRules;
     type CryptoProvider = <T::Output as ClientConfig>::CryptoProvider;
+    type IdentityBootstrap = <T::Output as ClientConfig>::IdentityBootstrap;
+    type KeychainStorage = <T::Output as ClientConfig>::KeychainStorage;
+    type EpochSecretsVault = <T::Output as ClientConfig>::EpochSecretsVault;
+    type MessageCompressor = <T::Output as ClientConfig>::MessageCompressor;
 
     fn supported_extensions(&self) -> Vec<ExtensionType> {
         self.get().supported_extensions()
@@ -782,6 +1293,22 @@ impl<T: MlsConfig> ClientConfig for T {
         self.get().crypto_provider()
     }
 
+    fn identity_bootstrap(&self) -> Self::IdentityBootstrap {
+        self.get().identity_bootstrap()
+    }
+
+    fn keychain_storage(&self) -> Self::KeychainStorage {
+        self.get().keychain_storage()
+    }
+
+    fn epoch_secrets_vault(&self) -> Self::EpochSecretsVault {
+        self.get().epoch_secrets_vault()
+    }
+
+    fn message_compressor(&self) -> Self::MessageCompressor {
+        self.get().message_compressor()
+    }
+
     fn lifetime(&self) -> Lifetime {
         self.get().lifetime()
     }
@@ -797,6 +1324,42 @@ impl<T: MlsConfig> ClientConfig for T {
     fn supported_credential_types(&self) -> Vec<CredentialType> {
         self.get().supported_credential_types()
     }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn key_retention(&self) -> KeyRetention {
+        self.get().key_retention()
+    }
+
+    fn storage_integrity_key(&self) -> Option<Secret> {
+        self.get().storage_integrity_key()
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn skipped_key_storage(&self) -> SkippedKeyStorage {
+        self.get().skipped_key_storage()
+    }
+
+    fn unknown_extension_policy(&self) -> UnknownExtensionPolicy {
+        self.get().unknown_extension_policy()
+    }
+
+    fn grease(&self) -> bool {
+        self.get().grease()
+    }
+
+    fn require_configured_capabilities(&self) -> bool {
+        self.get().require_configured_capabilities()
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    fn max_message_age(&self) -> Option<u64> {
+        self.get().max_message_age()
+    }
+
+    #[cfg(feature = "private_message")]
+    fn received_control_wire_format(&self) -> ReceivedControlWireFormat {
+        self.get().received_control_wire_format()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -805,6 +1368,18 @@ pub(crate) struct Settings {
     pub(crate) protocol_versions: Vec<ProtocolVersion>,
     pub(crate) custom_proposal_types: Vec<ProposalType>,
     pub(crate) lifetime_in_s: u64,
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub(crate) key_retention: KeyRetention,
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub(crate) skipped_key_storage: SkippedKeyStorage,
+    pub(crate) unknown_extension_policy: UnknownExtensionPolicy,
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    pub(crate) max_message_age: Option<u64>,
+    #[cfg(feature = "private_message")]
+    pub(crate) received_control_wire_format: ReceivedControlWireFormat,
+    pub(crate) storage_integrity_key: Option<Secret>,
+    pub(crate) grease: bool,
+    pub(crate) require_configured_capabilities: bool,
     #[cfg(any(test, feature = "test_util"))]
     pub(crate) key_package_not_before: Option<u64>,
 }
@@ -816,6 +1391,18 @@ impl Default for Settings {
             protocol_versions: Default::default(),
             lifetime_in_s: 365 * 24 * 3600,
             custom_proposal_types: Default::default(),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            key_retention: Default::default(),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            skipped_key_storage: Default::default(),
+            unknown_extension_policy: Default::default(),
+            #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+            max_message_age: None,
+            #[cfg(feature = "private_message")]
+            received_control_wire_format: Default::default(),
+            storage_integrity_key: None,
+            grease: true,
+            require_configured_capabilities: false,
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
         }
@@ -837,6 +1424,18 @@ pub(crate) fn recreate_config<T: ClientConfig>(
                 let l = c.lifetime();
                 l.not_after - l.not_before
             },
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            key_retention: c.key_retention(),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            skipped_key_storage: c.skipped_key_storage(),
+            unknown_extension_policy: c.unknown_extension_policy(),
+            #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+            max_message_age: c.max_message_age(),
+            #[cfg(feature = "private_message")]
+            received_control_wire_format: c.received_control_wire_format(),
+            storage_integrity_key: c.storage_integrity_key(),
+            grease: c.grease(),
+            require_configured_capabilities: c.require_configured_capabilities(),
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
         },
@@ -846,6 +1445,12 @@ pub(crate) fn recreate_config<T: ClientConfig>(
         identity_provider: c.identity_provider(),
         mls_rules: c.mls_rules(),
         crypto_provider: c.crypto_provider(),
+        services: Services {
+            identity_bootstrap: c.identity_bootstrap(),
+            keychain_storage: c.keychain_storage(),
+            epoch_secrets_vault: c.epoch_secrets_vault(),
+            message_compressor: c.message_compressor(),
+        },
         signer,
         signing_identity,
         version,
@@ -861,13 +1466,20 @@ mod private {
         protocol_version::ProtocolVersion,
     };
 
-    use crate::client_builder::{IntoConfigOutput, Settings};
+    use crate::{
+        client_builder::{DefaultServices, IntoConfigOutput, Settings},
+        compression::NoCompression,
+        crypto::DefaultEpochSecretsVault,
+        identity::NoIdentityBootstrap,
+    };
 
     #[derive(Clone, Debug)]
-    pub struct Config<Kpr, Ps, Gss, Ip, Pr, Cp>(pub(crate) ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp>);
+    pub struct Config<Kpr, Ps, Gss, Ip, Pr, Cp, Sv = DefaultServices>(
+        pub(crate) ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Sv>,
+    );
 
     #[derive(Clone, Debug)]
-    pub struct ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp> {
+    pub struct ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Sv> {
         pub(crate) settings: Settings,
         pub(crate) key_package_repo: Kpr,
         pub(crate) psk_store: Ps,
@@ -875,11 +1487,33 @@ mod private {
         pub(crate) identity_provider: Ip,
         pub(crate) mls_rules: Pr,
         pub(crate) crypto_provider: Cp,
+        pub(crate) services: Sv,
         pub(crate) signer: Option<SignatureSecretKey>,
         pub(crate) signing_identity: Option<(SigningIdentity, CipherSuite)>,
         pub(crate) version: ProtocolVersion,
     }
 
+    /// Services of a client configuration that have a default, grouped in a
+    /// single parameter of [`Config`].
+    #[derive(Clone, Debug)]
+    pub struct Services<Ib, Kc, Esv, Mc> {
+        pub(crate) identity_bootstrap: Ib,
+        pub(crate) keychain_storage: Kc,
+        pub(crate) epoch_secrets_vault: Esv,
+        pub(crate) message_compressor: Mc,
+    }
+
+    impl Default for DefaultServices {
+        fn default() -> Self {
+            Self {
+                identity_bootstrap: NoIdentityBootstrap,
+                keychain_storage: Default::default(),
+                epoch_secrets_vault: DefaultEpochSecretsVault,
+                message_compressor: NoCompression,
+            }
+        }
+    }
+
     pub trait IntoConfig {
         type KeyPackageRepository;
         type PskStore;
@@ -887,17 +1521,27 @@ mod private {
         type IdentityProvider;
         type MlsRules;
         type CryptoProvider;
+        type IdentityBootstrap;
+        type KeychainStorage;
+        type EpochSecretsVault;
+        type MessageCompressor;
 
         fn into_config(self) -> IntoConfigOutput<Self>;
     }
 
-    impl<Kpr, Ps, Gss, Ip, Pr, Cp> IntoConfig for Config<Kpr, Ps, Gss, Ip, Pr, Cp> {
+    impl<Kpr, Ps, Gss, Ip, Pr, Cp, Ib, Kc, Esv, Mc> IntoConfig
+        for Config<Kpr, Ps, Gss, Ip, Pr, Cp, Services<Ib, Kc, Esv, Mc>>
+    {
         type KeyPackageRepository = Kpr;
         type PskStore = Ps;
         type GroupStateStorage = Gss;
         type IdentityProvider = Ip;
         type MlsRules = Pr;
         type CryptoProvider = Cp;
+        type IdentityBootstrap = Ib;
+        type KeychainStorage = Kc;
+        type EpochSecretsVault = Esv;
+        type MessageCompressor = Mc;
 
         fn into_config(self) -> Self {
             self
@@ -906,13 +1550,14 @@ mod private {
 }
 
 use mls_rs_core::{
-    crypto::{CryptoProvider, SignatureSecretKey},
+    compression::MessageCompressor,
+    crypto::{CryptoProvider, DefaultEpochSecretsVault, EpochSecretsVault, SignatureSecretKey},
     group::GroupStateStorage,
-    identity::IdentityProvider,
+    identity::{IdentityBootstrap, IdentityProvider, KeychainStorage},
     key_package::KeyPackageStorage,
     psk::PreSharedKeyStorage,
 };
-use private::{Config, ConfigInner, IntoConfig};
+use private::{Config, ConfigInner, IntoConfig, Services};
 
 #[cfg(test)]
 pub(crate) mod test_utils {
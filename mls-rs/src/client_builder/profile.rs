@@ -0,0 +1,223 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::group::mls_rules::{CommitOptions, DefaultMlsRules, EncryptionOptions};
+
+#[cfg(feature = "private_message")]
+use crate::group::padding::PaddingMode;
+
+const DAY_IN_S: u64 = 24 * 3600;
+
+/// Preset bundle of client settings.
+///
+/// A profile can be applied to a [`ClientBuilder`](super::ClientBuilder) using
+/// [`ClientBuilder::profile`](super::ClientBuilder::profile) so that every
+/// application in a fleet starts from the same settings. Individual settings
+/// can still be overridden by calling the corresponding builder methods after
+/// the profile is applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Profile {
+    /// Conservative settings following the security recommendations of RFC 9420.
+    ///
+    /// Control messages are encrypted and padded, every commit includes an update
+    /// path, external commits are not allowed and key packages expire after 90 days.
+    /// GREASE values are added to key packages, leaf nodes and group info messages,
+    /// and created groups require the extension and proposal types the client is
+    /// configured with.
+    StrictRfc,
+    /// Settings maximizing interoperability with other MLS implementations.
+    ///
+    /// Control messages are sent as public messages, update paths are only sent when
+    /// required by the protocol, external commits are allowed and key packages
+    /// expire after 365 days. No GREASE values are added, and created groups
+    /// don't require any capabilities beyond the ones requested by the application.
+    #[default]
+    Compat,
+}
+
+impl Profile {
+    /// Commit options used by this profile.
+    pub fn commit_options(&self) -> CommitOptions {
+        match self {
+            Profile::StrictRfc => CommitOptions::new()
+                .with_path_required(true)
+                .with_ratchet_tree_extension(true)
+                .with_single_welcome_message(true)
+                .with_allow_external_commit(false),
+            Profile::Compat => CommitOptions::new()
+                .with_path_required(false)
+                .with_ratchet_tree_extension(true)
+                .with_single_welcome_message(true)
+                .with_allow_external_commit(true),
+        }
+    }
+
+    /// Encryption options used by this profile.
+    pub fn encryption_options(&self) -> EncryptionOptions {
+        #[cfg(feature = "private_message")]
+        return match self {
+            Profile::StrictRfc => EncryptionOptions::new(true, PaddingMode::StepFunction),
+            Profile::Compat => EncryptionOptions::new(false, PaddingMode::StepFunction),
+        };
+
+        #[cfg(not(feature = "private_message"))]
+        EncryptionOptions::default()
+    }
+
+    /// Lifetime in seconds of key packages generated with this profile.
+    pub fn key_package_lifetime(&self) -> u64 {
+        match self {
+            Profile::StrictRfc => 90 * DAY_IN_S,
+            Profile::Compat => 365 * DAY_IN_S,
+        }
+    }
+
+    /// Whether GREASE values are added with this profile.
+    ///
+    /// See [`ClientBuilder::grease`](super::ClientBuilder::grease).
+    pub fn grease(&self) -> bool {
+        match self {
+            Profile::StrictRfc => true,
+            Profile::Compat => false,
+        }
+    }
+
+    /// Whether groups created with this profile require the capabilities the
+    /// client is configured with.
+    ///
+    /// See [`ClientBuilder::require_configured_capabilities`](super::ClientBuilder::require_configured_capabilities).
+    pub fn require_configured_capabilities(&self) -> bool {
+        match self {
+            Profile::StrictRfc => true,
+            Profile::Compat => false,
+        }
+    }
+
+    /// MLS rules combining the [commit options](Profile::commit_options) and
+    /// [encryption options](Profile::encryption_options) of this profile.
+    pub fn mls_rules(&self) -> DefaultMlsRules {
+        DefaultMlsRules::new()
+            .with_commit_options(self.commit_options())
+            .with_encryption_options(self.encryption_options())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Profile;
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_CUSTOM_PROPOSAL_TYPE},
+        client_builder::test_utils::TestClientBuilder,
+        client_config::ClientConfig,
+        extension::{test_utils::TEST_EXTENSION_TYPE, RequiredCapabilitiesExt},
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn profile_is_applied_to_builder() {
+        let config = TestClientBuilder::new_for_test()
+            .profile(Profile::StrictRfc)
+            .build_config();
+
+        assert_eq!(
+            config.0.mls_rules.commit_options,
+            Profile::StrictRfc.commit_options()
+        );
+
+        assert_eq!(
+            config.0.mls_rules.encryption_options,
+            Profile::StrictRfc.encryption_options()
+        );
+
+        let lifetime = config.lifetime();
+
+        assert_eq!(
+            lifetime.not_after - lifetime.not_before,
+            Profile::StrictRfc.key_package_lifetime()
+        );
+
+        assert_eq!(config.grease(), Profile::StrictRfc.grease());
+
+        assert_eq!(
+            config.require_configured_capabilities(),
+            Profile::StrictRfc.require_configured_capabilities()
+        );
+    }
+
+    #[test]
+    fn later_settings_override_profile() {
+        let config = TestClientBuilder::new_for_test()
+            .profile(Profile::StrictRfc)
+            .key_package_lifetime(42)
+            .build_config();
+
+        let lifetime = config.lifetime();
+        assert_eq!(lifetime.not_after - lifetime.not_before, 42);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn strict_rfc_groups_require_configured_capabilities() {
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(TEST_EXTENSION_TYPE.into())
+            .custom_proposal_type(TEST_CUSTOM_PROPOSAL_TYPE)
+            .profile(Profile::StrictRfc)
+            .build();
+
+        let group = client
+            .create_group(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let required_capabilities = group
+            .context()
+            .extensions
+            .get_as::<RequiredCapabilitiesExt>()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            required_capabilities.extensions,
+            vec![TEST_EXTENSION_TYPE.into()]
+        );
+
+        assert_eq!(
+            required_capabilities.proposals,
+            vec![TEST_CUSTOM_PROPOSAL_TYPE]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn compat_adds_no_grease_and_no_required_capabilities() {
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(TEST_EXTENSION_TYPE.into())
+            .profile(Profile::Compat)
+            .build();
+
+        let group = client
+            .create_group(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        assert!(group.context().extensions.is_empty());
+
+        let key_package = client
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap()
+            .into_key_package()
+            .unwrap();
+
+        let leaf_node = &key_package.leaf_node;
+        assert_eq!(leaf_node.capabilities, leaf_node.ungreased_capabilities());
+        assert!(key_package.extensions.is_empty());
+        assert!(leaf_node.extensions.is_empty());
+    }
+}
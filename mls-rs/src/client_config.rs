@@ -2,18 +2,28 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+#[cfg(feature = "private_message")]
+use crate::group::mls_rules::ReceivedControlWireFormat;
+#[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+use crate::group::secret_tree::{KeyRetention, SkippedKeyStorage};
 use crate::{
-    extension::ExtensionType,
+    extension::{ExtensionType, UnknownExtensionPolicy},
     group::{mls_rules::MlsRules, proposal::ProposalType},
     identity::CredentialType,
     protocol_version::ProtocolVersion,
     tree_kem::{leaf_node::ConfigProperties, Capabilities, Lifetime},
     ExtensionList,
 };
+
 use alloc::vec::Vec;
 use mls_rs_core::{
-    crypto::CryptoProvider, group::GroupStateStorage, identity::IdentityProvider,
-    key_package::KeyPackageStorage, psk::PreSharedKeyStorage,
+    compression::MessageCompressor,
+    crypto::{CryptoProvider, EpochSecretsVault},
+    group::GroupStateStorage,
+    identity::{IdentityBootstrap, IdentityProvider, KeychainStorage},
+    key_package::KeyPackageStorage,
+    psk::PreSharedKeyStorage,
+    secret::Secret,
 };
 
 pub trait ClientConfig: Send + Sync + Clone {
@@ -23,6 +33,10 @@ pub trait ClientConfig: Send + Sync + Clone {
     type IdentityProvider: IdentityProvider + Clone;
     type MlsRules: MlsRules + Clone;
     type CryptoProvider: CryptoProvider + Clone;
+    type IdentityBootstrap: IdentityBootstrap + Clone;
+    type KeychainStorage: KeychainStorage + Clone;
+    type EpochSecretsVault: EpochSecretsVault + Clone;
+    type MessageCompressor: MessageCompressor + Clone;
 
     fn supported_extensions(&self) -> Vec<ExtensionType>;
     fn supported_custom_proposals(&self) -> Vec<ProposalType>;
@@ -36,9 +50,49 @@ pub trait ClientConfig: Send + Sync + Clone {
     fn group_state_storage(&self) -> Self::GroupStateStorage;
     fn identity_provider(&self) -> Self::IdentityProvider;
     fn crypto_provider(&self) -> Self::CryptoProvider;
+    fn identity_bootstrap(&self) -> Self::IdentityBootstrap;
+    fn keychain_storage(&self) -> Self::KeychainStorage;
+    fn epoch_secrets_vault(&self) -> Self::EpochSecretsVault;
+    fn message_compressor(&self) -> Self::MessageCompressor;
 
     fn lifetime(&self) -> Lifetime;
 
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn key_retention(&self) -> KeyRetention {
+        KeyRetention::Standard
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn skipped_key_storage(&self) -> SkippedKeyStorage {
+        SkippedKeyStorage::Keys
+    }
+
+    fn unknown_extension_policy(&self) -> UnknownExtensionPolicy {
+        UnknownExtensionPolicy::Retain
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    fn max_message_age(&self) -> Option<u64> {
+        None
+    }
+
+    #[cfg(feature = "private_message")]
+    fn received_control_wire_format(&self) -> ReceivedControlWireFormat {
+        ReceivedControlWireFormat::Any
+    }
+
+    fn storage_integrity_key(&self) -> Option<Secret> {
+        None
+    }
+
+    fn grease(&self) -> bool {
+        true
+    }
+
+    fn require_configured_capabilities(&self) -> bool {
+        false
+    }
+
     fn capabilities(&self) -> Capabilities {
         Capabilities {
             protocol_versions: self.supported_protocol_versions(),
@@ -61,6 +115,7 @@ pub trait ClientConfig: Send + Sync + Clone {
         ConfigProperties {
             capabilities: self.capabilities(),
             extensions: leaf_node_extensions,
+            grease: self.grease(),
         }
     }
 }
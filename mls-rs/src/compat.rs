@@ -0,0 +1,230 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Import and export of key packages and welcome messages exchanged with
+//! OpenMLS.
+//!
+//! OpenMLS uses the RFC 9420 wire encoding, so artifacts wrapped in an
+//! `MLSMessage` can be used as is. Some OpenMLS APIs however serialize a
+//! `KeyPackage` or a `Welcome` without the `MLSMessage` framing, and
+//! artifacts read from files or transport buffers sometimes carry trailing
+//! data. [`OpenMlsCompat`] accepts these divergences when configured with
+//! [`CompatStrictness::Lenient`].
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode};
+
+use crate::{
+    client::MlsError,
+    group::{
+        framing::{MlsMessage, MlsMessagePayload},
+        Welcome,
+    },
+    KeyPackage, ProtocolVersion,
+};
+
+/// How strictly [`OpenMlsCompat`] parses imported artifacts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompatStrictness {
+    /// Only accept artifacts wrapped in an `MLSMessage` with no trailing
+    /// data.
+    #[default]
+    Strict,
+    /// Additionally accept bare `KeyPackage` and `Welcome` structures and
+    /// ignore data following the artifact.
+    Lenient,
+}
+
+/// Encoding produced by [`OpenMlsCompat`] when exporting an artifact.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArtifactFraming {
+    /// The artifact is wrapped in an `MLSMessage` as defined by RFC 9420.
+    #[default]
+    MlsMessage,
+    /// The bare `KeyPackage` or `Welcome` structure.
+    Bare,
+}
+
+/// Converter for key packages and welcome messages exchanged with OpenMLS.
+#[derive(Clone, Debug, Default)]
+pub struct OpenMlsCompat {
+    strictness: CompatStrictness,
+}
+
+impl OpenMlsCompat {
+    pub fn new(strictness: CompatStrictness) -> Self {
+        Self { strictness }
+    }
+
+    pub fn strictness(&self) -> CompatStrictness {
+        self.strictness
+    }
+
+    /// Import a key package serialized by OpenMLS.
+    pub fn import_key_package(&self, bytes: &[u8]) -> Result<MlsMessage, MlsError> {
+        self.import(
+            bytes,
+            |payload| matches!(payload, MlsMessagePayload::KeyPackage(_)),
+            |reader| {
+                let key_package = KeyPackage::mls_decode(reader)?;
+
+                Ok(MlsMessage::new(
+                    key_package.version,
+                    MlsMessagePayload::KeyPackage(key_package),
+                ))
+            },
+        )
+    }
+
+    /// Import a welcome message serialized by OpenMLS.
+    pub fn import_welcome(&self, bytes: &[u8]) -> Result<MlsMessage, MlsError> {
+        self.import(
+            bytes,
+            |payload| matches!(payload, MlsMessagePayload::Welcome(_)),
+            |reader| {
+                Ok(MlsMessage::new(
+                    ProtocolVersion::MLS_10,
+                    MlsMessagePayload::Welcome(Welcome::mls_decode(reader)?),
+                ))
+            },
+        )
+    }
+
+    /// Export a key package message with the given `framing`.
+    pub fn export_key_package(
+        &self,
+        message: &MlsMessage,
+        framing: ArtifactFraming,
+    ) -> Result<Vec<u8>, MlsError> {
+        let key_package = message
+            .as_key_package()
+            .ok_or(MlsError::UnexpectedMessageType)?;
+
+        match framing {
+            ArtifactFraming::MlsMessage => message.to_bytes(),
+            ArtifactFraming::Bare => key_package.mls_encode_to_vec().map_err(Into::into),
+        }
+    }
+
+    /// Export a welcome message with the given `framing`.
+    pub fn export_welcome(
+        &self,
+        message: &MlsMessage,
+        framing: ArtifactFraming,
+    ) -> Result<Vec<u8>, MlsError> {
+        let MlsMessagePayload::Welcome(welcome) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        match framing {
+            ArtifactFraming::MlsMessage => message.to_bytes(),
+            ArtifactFraming::Bare => welcome.mls_encode_to_vec().map_err(Into::into),
+        }
+    }
+
+    fn import<F, B>(
+        &self,
+        bytes: &[u8],
+        expected: F,
+        decode_bare: B,
+    ) -> Result<MlsMessage, MlsError>
+    where
+        F: Fn(&MlsMessagePayload) -> bool,
+        B: Fn(&mut &[u8]) -> Result<MlsMessage, MlsError>,
+    {
+        let framed = self.decode_all(bytes, |reader| {
+            let message = MlsMessage::mls_decode(reader)?;
+
+            expected(&message.payload)
+                .then_some(message)
+                .ok_or(MlsError::UnexpectedMessageType)
+        });
+
+        match (framed, self.strictness) {
+            (Ok(message), _) => Ok(message),
+            (Err(_), CompatStrictness::Lenient) => self.decode_all(bytes, decode_bare),
+            (Err(e), CompatStrictness::Strict) => Err(e),
+        }
+    }
+
+    fn decode_all<D>(&self, bytes: &[u8], decode: D) -> Result<MlsMessage, MlsError>
+    where
+        D: Fn(&mut &[u8]) -> Result<MlsMessage, MlsError>,
+    {
+        let reader = &mut &*bytes;
+        let message = decode(reader)?;
+
+        if !reader.is_empty() && self.strictness == CompatStrictness::Strict {
+            return Err(MlsError::UnexpectedTrailingData);
+        }
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{
+        test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+    };
+    use crate::client::MlsError;
+    use crate::group::test_utils::test_group;
+
+    use super::{ArtifactFraming, CompatStrictness, OpenMlsCompat};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn bare_and_padded_artifacts_need_lenient_mode() {
+        let strict = OpenMlsCompat::new(CompatStrictness::Strict);
+        let lenient = OpenMlsCompat::new(CompatStrictness::Lenient);
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let bare = strict
+            .export_key_package(&key_package, ArtifactFraming::Bare)
+            .unwrap();
+
+        assert!(strict.import_key_package(&bare).is_err());
+        assert_eq!(lenient.import_key_package(&bare).unwrap(), key_package);
+
+        let mut padded = strict
+            .export_key_package(&key_package, ArtifactFraming::MlsMessage)
+            .unwrap();
+
+        padded.extend_from_slice(&[0; 4]);
+
+        let res = strict.import_key_package(&padded);
+        assert_matches!(res, Err(MlsError::UnexpectedTrailingData));
+        assert_eq!(lenient.import_key_package(&padded).unwrap(), key_package);
+
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let welcome = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .welcome_messages
+            .remove(0);
+
+        let bare = strict
+            .export_welcome(&welcome, ArtifactFraming::Bare)
+            .unwrap();
+
+        assert!(strict.import_welcome(&bare).is_err());
+        assert_eq!(lenient.import_welcome(&bare).unwrap(), welcome);
+
+        let res = strict.export_welcome(&welcome, ArtifactFraming::MlsMessage);
+        assert_eq!(res.unwrap(), welcome.to_bytes().unwrap());
+        assert!(lenient
+            .import_key_package(&welcome.to_bytes().unwrap())
+            .is_err());
+    }
+}
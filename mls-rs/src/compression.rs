@@ -0,0 +1,515 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Compression of application messages before encryption.
+//!
+//! Groups opt into compression with the [`ApplicationCompressionExt`] group
+//! context extension, which selects the algorithm used by all members. A
+//! client configured with a [`MessageCompressor`] through
+//! [`ClientBuilder::message_compressor`](crate::client_builder::ClientBuilder::message_compressor)
+//! advertises support for the extension in its capabilities, so that the
+//! extension can only be set once every member supports it.
+//!
+//! The size of a compressed message depends on its content, which lets an
+//! observer of ciphertexts learn about the content of messages that mix
+//! secret and attacker controlled data. To limit this, the padding of a
+//! compressed message is computed from its size before compression. With
+//! [`PaddingMode::None`](crate::client_builder::PaddingMode::None), the
+//! compressed size is visible, and messages with sensitive content should be
+//! sent with
+//! [`Group::encrypt_application_message_uncompressed`](crate::Group::encrypt_application_message_uncompressed).
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::extension::{ExtensionType, MlsCodecExtension};
+
+#[cfg(feature = "private_message")]
+use mls_rs_codec::VarInt;
+#[cfg(feature = "private_message")]
+use mls_rs_core::error::IntoAnyError;
+#[cfg(feature = "private_message")]
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+#[cfg(feature = "private_message")]
+use crate::{client::MlsError, group::framing::ApplicationData};
+
+pub use mls_rs_core::compression::{CompressionAlgorithm, MessageCompressor};
+
+/// Group context extension enabling compression of application messages
+/// with [`algorithm`](ApplicationCompressionExt::algorithm).
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct ApplicationCompressionExt {
+    pub algorithm: CompressionAlgorithm,
+}
+
+impl ApplicationCompressionExt {
+    /// Extension type of the compression extension, from the private use
+    /// range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A1);
+
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self { algorithm }
+    }
+}
+
+impl MlsCodecExtension for ApplicationCompressionExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
+/// Compressor that does not compress application messages.
+///
+/// This is the compressor used by a
+/// [`ClientBuilder`](crate::client_builder::ClientBuilder) unless one is set
+/// with
+/// [`ClientBuilder::message_compressor`](crate::client_builder::ClientBuilder::message_compressor).
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct NoCompression;
+
+impl MessageCompressor for NoCompression {
+    type Error = Infallible;
+
+    fn algorithm(&self) -> Option<CompressionAlgorithm> {
+        None
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Ok(data.to_vec())
+    }
+
+    fn decompress(&self, data: &[u8], _decompressed_len: usize) -> Result<Vec<u8>, Self::Error> {
+        Ok(data.to_vec())
+    }
+}
+
+/// Error returned by [`DeflateCompressor`] and [`ZstdCompressor`].
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[non_exhaustive]
+pub enum CompressionError {
+    #[cfg_attr(feature = "std", error("compression failed"))]
+    CompressionFailed,
+    #[cfg_attr(feature = "std", error("decompression failed"))]
+    DecompressionFailed,
+}
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+impl mls_rs_core::error::IntoAnyError for CompressionError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// Compressor for [`CompressionAlgorithm::DEFLATE`].
+#[cfg(feature = "deflate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+#[derive(Clone, Copy, Debug)]
+pub struct DeflateCompressor {
+    level: u8,
+}
+
+#[cfg(feature = "deflate")]
+impl DeflateCompressor {
+    /// Compressor using compression `level`, from 0 to 10.
+    pub fn new(level: u8) -> Self {
+        Self { level }
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl Default for DeflateCompressor {
+    fn default() -> Self {
+        Self::new(6)
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl MessageCompressor for DeflateCompressor {
+    type Error = CompressionError;
+
+    fn algorithm(&self) -> Option<CompressionAlgorithm> {
+        Some(CompressionAlgorithm::DEFLATE)
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Ok(miniz_oxide::deflate::compress_to_vec(data, self.level))
+    }
+
+    fn decompress(&self, data: &[u8], decompressed_len: usize) -> Result<Vec<u8>, Self::Error> {
+        miniz_oxide::inflate::decompress_to_vec_with_limit(data, decompressed_len)
+            .map_err(|_| CompressionError::DecompressionFailed)
+    }
+}
+
+/// Compressor for [`CompressionAlgorithm::ZSTD`].
+#[cfg(feature = "zstd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+#[derive(Clone, Copy, Debug)]
+pub struct ZstdCompressor {
+    level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdCompressor {
+    /// Compressor using compression `level`, as defined by Zstandard.
+    pub fn new(level: i32) -> Self {
+        Self { level }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Default for ZstdCompressor {
+    fn default() -> Self {
+        Self::new(zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl MessageCompressor for ZstdCompressor {
+    type Error = CompressionError;
+
+    fn algorithm(&self) -> Option<CompressionAlgorithm> {
+        Some(CompressionAlgorithm::ZSTD)
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        zstd::bulk::compress(data, self.level).map_err(|_| CompressionError::CompressionFailed)
+    }
+
+    fn decompress(&self, data: &[u8], decompressed_len: usize) -> Result<Vec<u8>, Self::Error> {
+        zstd::bulk::decompress(data, decompressed_len)
+            .map_err(|_| CompressionError::DecompressionFailed)
+    }
+}
+
+/// Content of application messages sent in groups with the
+/// [`ApplicationCompressionExt`] extension. `algorithm` is `None` for
+/// messages sent uncompressed.
+#[cfg(feature = "private_message")]
+#[derive(MlsSize, MlsEncode, MlsDecode, ZeroizeOnDrop)]
+struct CompressedApplicationData {
+    #[zeroize(skip)]
+    algorithm: Option<CompressionAlgorithm>,
+    #[zeroize(skip)]
+    uncompressed_len: VarInt,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    data: Vec<u8>,
+}
+
+/// Frame `data` for a group compressing application messages with
+/// `algorithm`. The data is compressed if `compress` is set, `compressor`
+/// implements `algorithm` and compression makes it shorter.
+///
+/// Returns the framed data and the number of bytes saved by compression.
+#[cfg(feature = "private_message")]
+pub(crate) fn compress_application_data<Mc: MessageCompressor>(
+    compressor: &Mc,
+    algorithm: CompressionAlgorithm,
+    data: &[u8],
+    compress: bool,
+) -> Result<(ApplicationData, usize), MlsError> {
+    let uncompressed_len = VarInt::try_from(data.len())?;
+
+    let compressed = (compress && compressor.algorithm() == Some(algorithm))
+        .then(|| compressor.compress(data))
+        .transpose()
+        .map_err(|e| MlsError::MessageCompressorError(e.into_any_error()))?
+        .map(Zeroizing::new);
+
+    let framed = match compressed {
+        Some(compressed) if compressed.len() < data.len() => CompressedApplicationData {
+            algorithm: Some(algorithm),
+            uncompressed_len,
+            data: compressed.to_vec(),
+        },
+        _ => CompressedApplicationData {
+            algorithm: None,
+            uncompressed_len,
+            data: data.to_vec(),
+        },
+    };
+
+    let saved = data.len() - framed.data.len();
+
+    Ok((framed.mls_encode_to_vec()?.into(), saved))
+}
+
+/// Recover the application data framed by the sender with
+/// [`compress_application_data`] in a group using `algorithm`.
+#[cfg(feature = "private_message")]
+pub(crate) fn decompress_application_data<Mc: MessageCompressor>(
+    compressor: &Mc,
+    algorithm: CompressionAlgorithm,
+    data: &ApplicationData,
+) -> Result<ApplicationData, MlsError> {
+    let reader = &mut data.as_bytes();
+
+    let mut framed = CompressedApplicationData::mls_decode(reader)
+        .map_err(|_| MlsError::InvalidCompressedApplicationData)?;
+
+    if !reader.is_empty() {
+        return Err(MlsError::InvalidCompressedApplicationData);
+    }
+
+    let uncompressed_len = u32::from(framed.uncompressed_len) as usize;
+
+    let uncompressed = match framed.algorithm {
+        None => core::mem::take(&mut framed.data),
+        Some(used) if used != algorithm || compressor.algorithm() != Some(used) => {
+            return Err(MlsError::UnsupportedCompressionAlgorithm(used))
+        }
+        // Checked before decompression to bound the memory used by
+        // decompression bombs
+        Some(_) if uncompressed_len > compressor.max_decompressed_size() => {
+            return Err(MlsError::InvalidCompressedApplicationData)
+        }
+        Some(_) => compressor
+            .decompress(&framed.data, uncompressed_len)
+            .map_err(|e| MlsError::MessageCompressorError(e.into_any_error()))?,
+    };
+
+    (uncompressed.len() == uncompressed_len)
+        .then(|| uncompressed.into())
+        .ok_or(MlsError::InvalidCompressedApplicationData)
+}
+
+#[cfg(all(test, feature = "private_message"))]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::extension::ExtensionList;
+
+    use super::{
+        compress_application_data, decompress_application_data, ApplicationCompressionExt,
+        CompressionAlgorithm, MessageCompressor,
+    };
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_builder::{ClientBuilder, MlsConfig, PaddingMode},
+        crypto::test_utils::TestCryptoProvider,
+        group::{framing::ApplicationData, ReceivedMessage},
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        mls_rules::{DefaultMlsRules, EncryptionOptions},
+        Client,
+    };
+
+    const TEST_ALGORITHM: CompressionAlgorithm = CompressionAlgorithm::new(0xFFFF);
+
+    /// Run-length encoding of bytes as (count, byte) pairs.
+    #[derive(Clone, Debug)]
+    struct RunLengthCompressor {
+        max_decompressed_size: usize,
+    }
+
+    impl Default for RunLengthCompressor {
+        fn default() -> Self {
+            Self {
+                max_decompressed_size: 4096,
+            }
+        }
+    }
+
+    impl MessageCompressor for RunLengthCompressor {
+        type Error = mls_rs_codec::Error;
+
+        fn algorithm(&self) -> Option<CompressionAlgorithm> {
+            Some(TEST_ALGORITHM)
+        }
+
+        fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let mut compressed = Vec::new();
+
+            for &byte in data {
+                match compressed.len() {
+                    len if len >= 2 && compressed[len - 1] == byte && compressed[len - 2] < 255 => {
+                        compressed[len - 2] += 1
+                    }
+                    _ => compressed.extend([1, byte]),
+                }
+            }
+
+            Ok(compressed)
+        }
+
+        fn decompress(&self, data: &[u8], decompressed_len: usize) -> Result<Vec<u8>, Self::Error> {
+            let mut decompressed = Vec::new();
+
+            for run in data.chunks(2) {
+                let [count, byte] = run else {
+                    return Err(mls_rs_codec::Error::UnexpectedEOF);
+                };
+
+                if decompressed.len() + *count as usize > decompressed_len {
+                    return Err(mls_rs_codec::Error::UnexpectedEOF);
+                }
+
+                decompressed.extend(vec![*byte; *count as usize]);
+            }
+
+            Ok(decompressed)
+        }
+
+        fn max_decompressed_size(&self) -> usize {
+            self.max_decompressed_size
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client(name: &str, padding_mode: PaddingMode) -> Client<impl MlsConfig> {
+        let (signing_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, name.as_bytes()).await;
+
+        ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(BasicIdentityProvider::new())
+            .mls_rules(
+                DefaultMlsRules::new()
+                    .with_encryption_options(EncryptionOptions::new(false, padding_mode)),
+            )
+            .message_compressor(RunLengthCompressor::default())
+            .used_protocol_version(TEST_PROTOCOL_VERSION)
+            .signing_identity(signing_identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_message_len(padding_mode: PaddingMode, compress: bool) -> usize {
+        let alice = test_client("alice", padding_mode).await;
+        let bob = test_client("bob", padding_mode).await;
+
+        let mut extensions = ExtensionList::new();
+
+        extensions
+            .set_from(ApplicationCompressionExt::new(TEST_ALGORITHM))
+            .unwrap();
+
+        let mut alice_group = alice
+            .create_group(extensions, Default::default())
+            .await
+            .unwrap();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice_group.apply_pending_commit().await.unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap();
+
+        let message = vec![b'a'; 1000];
+
+        let ciphertext = match compress {
+            true => alice_group
+                .encrypt_application_message(&message, vec![])
+                .await
+                .unwrap(),
+            false => alice_group
+                .encrypt_application_message_uncompressed(&message, vec![])
+                .await
+                .unwrap(),
+        };
+
+        let len = ciphertext.to_bytes().unwrap().len();
+
+        let received = bob_group
+            .process_incoming_message(ciphertext)
+            .await
+            .unwrap();
+
+        assert_matches!(received, ReceivedMessage::ApplicationMessage(m) if m.data() == message);
+
+        len
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn compressed_messages_are_padded_as_uncompressed_messages() {
+        let compressed = test_message_len(PaddingMode::StepFunction, true).await;
+        let uncompressed = test_message_len(PaddingMode::StepFunction, false).await;
+
+        assert_eq!(compressed, uncompressed);
+
+        let compressed = test_message_len(PaddingMode::None, true).await;
+        let uncompressed = test_message_len(PaddingMode::None, false).await;
+
+        assert!(compressed < uncompressed);
+    }
+
+    #[test]
+    fn oversized_compressed_messages_are_rejected() {
+        let sender = RunLengthCompressor::default();
+
+        let receiver = RunLengthCompressor {
+            max_decompressed_size: 100,
+        };
+
+        let (framed, saved) =
+            compress_application_data(&sender, TEST_ALGORITHM, &[0; 1000], true).unwrap();
+
+        assert!(saved > 0);
+
+        assert_matches!(
+            decompress_application_data(&receiver, TEST_ALGORITHM, &framed),
+            Err(MlsError::InvalidCompressedApplicationData)
+        );
+
+        assert_matches!(
+            decompress_application_data(&sender, CompressionAlgorithm::DEFLATE, &framed),
+            Err(MlsError::UnsupportedCompressionAlgorithm(TEST_ALGORITHM))
+        );
+
+        let data: ApplicationData =
+            decompress_application_data(&sender, TEST_ALGORITHM, &framed).unwrap();
+
+        assert_eq!(data.as_bytes(), [0; 1000]);
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn deflate_round_trip() {
+        let compressor = super::DeflateCompressor::default();
+        let data = vec![b'a'; 1000];
+
+        let compressed = compressor.compress(&data).unwrap();
+        assert!(compressed.len() < data.len());
+
+        assert_eq!(compressor.decompress(&compressed, 1000).unwrap(), data);
+        assert!(compressor.decompress(&compressed, 999).is_err());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        let compressor = super::ZstdCompressor::default();
+        let data = vec![b'a'; 1000];
+
+        let compressed = compressor.compress(&data).unwrap();
+        assert!(compressed.len() < data.len());
+
+        assert_eq!(compressor.decompress(&compressed, 1000).unwrap(), data);
+        assert!(compressor.decompress(&compressed, 999).is_err());
+    }
+}
@@ -9,8 +9,12 @@ pub use mls_rs_core::crypto::{
     SignatureSecretKey,
 };
 
+pub use mls_rs_core::crypto::{DefaultEpochSecretsVault, SecretHandle};
+
 pub use mls_rs_core::secret::Secret;
 
+pub use crate::signer::{ApplicationSignatureLabel, PROTOCOL_SIGNATURE_LABELS};
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use cfg_if::cfg_if;
@@ -2,12 +2,48 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-pub use mls_rs_core::extension::{ExtensionType, MlsCodecExtension, MlsExtension};
+pub use mls_rs_core::extension::{ExtensionDiff, ExtensionType, MlsCodecExtension, MlsExtension};
 
 pub(crate) use built_in::*;
 #[cfg(feature = "last_resort_key_package_ext")]
 pub(crate) use recommended::*;
 
+/// Handling of extensions whose type is not supported by the client.
+///
+/// An extension type is known if it is one of the default extension types
+/// defined by RFC 9420, a type registered with
+/// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type)
+/// or a GREASE value.
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum UnknownExtensionPolicy {
+    /// Retain unknown extensions found in key packages, leaf nodes and the
+    /// group context. Retained extensions can be inspected with
+    /// [`Group::unknown_extensions`](crate::Group::unknown_extensions).
+    #[default]
+    Retain,
+    /// Fail with [`MlsError::UnknownExtension`](crate::error::MlsError::UnknownExtension)
+    /// when joining a group, or processing or creating a commit, that
+    /// introduces an unknown extension in a key package, leaf node or the
+    /// group context.
+    Reject,
+}
+
+pub(crate) fn is_known_extension(
+    extension_type: ExtensionType,
+    supported_extensions: &[ExtensionType],
+) -> bool {
+    #[cfg(feature = "last_resort_key_package_ext")]
+    if extension_type == ExtensionType::LAST_RESORT_KEY_PACKAGE {
+        return true;
+    }
+
+    extension_type.is_default()
+        || supported_extensions.contains(&extension_type)
+        || crate::grease::is_grease_value(*extension_type)
+}
+
 /// Default extension types required by the MLS RFC.
 pub mod built_in;
 
@@ -9,9 +9,10 @@ use mls_rs_core::extension::{ExtensionType, MlsCodecExtension};
 
 use mls_rs_core::{group::ProposalType, identity::CredentialType};
 
+use mls_rs_core::extension::{ExtensionError, ExtensionList};
+
 #[cfg(feature = "by_ref_proposal")]
 use mls_rs_core::{
-    extension::ExtensionList,
     identity::{IdentityProvider, SigningIdentity},
     time::MlsTime,
 };
@@ -58,6 +59,19 @@ impl ApplicationIdExt {
     pub fn identifier(&self) -> &[u8] {
         &self.identifier
     }
+
+    /// Leaf node extensions containing only this extension, for example to
+    /// generate a key package with
+    /// [`Client::generate_key_package_message`](crate::Client::generate_key_package_message).
+    ///
+    /// The leaf node must list [`ExtensionType::APPLICATION_ID`] in its
+    /// capabilities, see
+    /// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type).
+    pub fn to_leaf_node_extensions(&self) -> Result<ExtensionList, ExtensionError> {
+        let mut extensions = ExtensionList::new();
+        extensions.set_from(self.clone())?;
+        Ok(extensions)
+    }
 }
 
 impl MlsCodecExtension for ApplicationIdExt {
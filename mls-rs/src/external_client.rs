@@ -5,12 +5,14 @@
 use crate::{
     client::MlsError,
     group::{framing::MlsMessage, message_processor::validate_key_package, ExportedTree},
+    iter::wrap_iter,
     KeyPackage,
 };
 
 pub mod builder;
 mod config;
 mod group;
+mod roster_observer;
 
 pub(crate) use config::ExternalClientConfig;
 use mls_rs_core::{
@@ -18,9 +20,17 @@ use mls_rs_core::{
     identity::SigningIdentity,
 };
 
+use alloc::vec::Vec;
 use builder::{ExternalBaseConfig, ExternalClientBuilder};
 
+#[cfg(all(not(mls_build_async), feature = "rayon"))]
+use rayon::prelude::*;
+
+#[cfg(mls_build_async)]
+use futures::StreamExt;
+
 pub use group::{ExternalGroup, ExternalReceivedMessage, ExternalSnapshot};
+pub use roster_observer::{NoopRosterObserver, RosterEvent, RosterObserver};
 
 /// A client capable of observing a group's state without having
 /// private keys required to read content.
@@ -136,6 +146,29 @@ where
         Ok(key_package)
     }
 
+    /// Validate a batch of key packages as done by
+    /// [`validate_key_package`](Self::validate_key_package).
+    ///
+    /// The result of each key package is returned in the order of
+    /// `key_packages`, so that a single invalid key package does not fail the
+    /// whole batch. Signatures are verified in parallel when the `rayon`
+    /// feature is enabled.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn validate_key_packages(
+        &self,
+        key_packages: Vec<MlsMessage>,
+    ) -> Vec<Result<KeyPackage, MlsError>> {
+        let results = wrap_iter(key_packages);
+
+        #[cfg(mls_build_async)]
+        let results = results.then(|key_package| self.validate_key_package(key_package));
+
+        #[cfg(not(mls_build_async))]
+        let results = results.map(|key_package| self.validate_key_package(key_package));
+
+        results.collect().await
+    }
+
     /// The [IdentityProvider](crate::IdentityProvider) that this client was configured to use.
     pub fn identity_provider(&self) -> <C as ExternalClientConfig>::IdentityProvider {
         self.config.identity_provider()
@@ -144,8 +177,15 @@ where
 
 #[cfg(test)]
 pub(crate) mod tests_utils {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
     use crate::{
-        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::framing::{MlsMessage, MlsMessagePayload},
         key_package::test_utils::test_key_package_message,
     };
 
@@ -159,4 +199,36 @@ pub(crate) mod tests_utils {
 
         assert_eq!(kp.into_key_package().unwrap(), validated_kp);
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_client_can_validate_key_package_batch() {
+        let valid =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "john").await;
+
+        let mut invalid =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "jane")
+                .await
+                .into_key_package()
+                .unwrap();
+
+        invalid.signature = vec![0; invalid.signature.len()];
+
+        let invalid = MlsMessage::new(
+            TEST_PROTOCOL_VERSION,
+            MlsMessagePayload::KeyPackage(invalid),
+        );
+
+        let server = TestExternalClientBuilder::new_for_test().build();
+
+        let results = server
+            .validate_key_packages(vec![invalid, valid.clone()])
+            .await;
+
+        assert_eq!(results.len(), 2);
+        assert_matches!(results[0], Err(MlsError::InvalidSignature));
+        assert_eq!(
+            results[1].as_ref().unwrap(),
+            &valid.into_key_package().unwrap()
+        );
+    }
 }
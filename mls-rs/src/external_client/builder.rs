@@ -9,7 +9,7 @@
 use crate::{
     crypto::SignaturePublicKey,
     extension::ExtensionType,
-    external_client::{ExternalClient, ExternalClientConfig},
+    external_client::{ExternalClient, ExternalClientConfig, NoopRosterObserver, RosterObserver},
     group::{
         mls_rules::{DefaultMlsRules, MlsRules},
         proposal::ProposalType,
@@ -25,7 +25,7 @@ use std::{
 };
 
 /// Base client configuration type when instantiating `ExternalClientBuilder`
-pub type ExternalBaseConfig = Config<Missing, DefaultMlsRules, Missing>;
+pub type ExternalBaseConfig = Config<Missing, DefaultMlsRules, Missing, NoopRosterObserver>;
 
 /// Builder for [`ExternalClient`]
 ///
@@ -112,6 +112,7 @@ impl ExternalClientBuilder<ExternalBaseConfig> {
             identity_provider: Missing,
             mls_rules: DefaultMlsRules::new(),
             crypto_provider: Missing,
+            roster_observer: NoopRosterObserver,
             signing_data: None,
         }))
     }
@@ -224,6 +225,7 @@ impl<C: IntoConfig> ExternalClientBuilder<C> {
             identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            roster_observer: c.roster_observer,
             signing_data: c.signing_data,
         }))
     }
@@ -244,6 +246,7 @@ impl<C: IntoConfig> ExternalClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider,
+            roster_observer: c.roster_observer,
             signing_data: c.signing_data,
         }))
     }
@@ -265,6 +268,27 @@ impl<C: IntoConfig> ExternalClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules,
             crypto_provider: c.crypto_provider,
+            roster_observer: c.roster_observer,
+            signing_data: c.signing_data,
+        }))
+    }
+
+    /// Set the observer notified of the membership changes of the groups
+    /// observed by the client. See [`RosterObserver`].
+    pub fn roster_observer<Ro>(
+        self,
+        roster_observer: Ro,
+    ) -> ExternalClientBuilder<WithRosterObserver<Ro, C>>
+    where
+        Ro: RosterObserver,
+    {
+        let Config(c) = self.0.into_config();
+        ExternalClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            roster_observer,
             signing_data: c.signing_data,
         }))
     }
@@ -286,6 +310,7 @@ where
     C::IdentityProvider: IdentityProvider + Clone,
     C::MlsRules: MlsRules + Clone,
     C::CryptoProvider: CryptoProvider + Clone,
+    C::RosterObserver: RosterObserver + Clone,
 {
     pub(crate) fn build_config(self) -> IntoConfigOutput<C> {
         let mut c = self.0.into_config();
@@ -315,37 +340,62 @@ pub struct Missing;
 /// Change the identity validator used by a client configuration.
 ///
 /// See [`ExternalClientBuilder::identity_provider`].
-pub type WithIdentityProvider<I, C> =
-    Config<I, <C as IntoConfig>::MlsRules, <C as IntoConfig>::CryptoProvider>;
+pub type WithIdentityProvider<I, C> = Config<
+    I,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::RosterObserver,
+>;
 
 /// Change the proposal filter used by a client configuration.
 ///
 /// See [`ExternalClientBuilder::mls_rules`].
-pub type WithMlsRules<Pr, C> =
-    Config<<C as IntoConfig>::IdentityProvider, Pr, <C as IntoConfig>::CryptoProvider>;
+pub type WithMlsRules<Pr, C> = Config<
+    <C as IntoConfig>::IdentityProvider,
+    Pr,
+    <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::RosterObserver,
+>;
 
 /// Change the crypto provider used by a client configuration.
 ///
 /// See [`ExternalClientBuilder::crypto_provider`].
-pub type WithCryptoProvider<Cp, C> =
-    Config<<C as IntoConfig>::IdentityProvider, <C as IntoConfig>::MlsRules, Cp>;
+pub type WithCryptoProvider<Cp, C> = Config<
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    Cp,
+    <C as IntoConfig>::RosterObserver,
+>;
+
+/// Change the roster observer used by a client configuration.
+///
+/// See [`ExternalClientBuilder::roster_observer`].
+pub type WithRosterObserver<Ro, C> = Config<
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    Ro,
+>;
 
 /// Helper alias for `Config`.
 pub type IntoConfigOutput<C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::RosterObserver,
 >;
 
-impl<Ip, Pr, Cp> ExternalClientConfig for ConfigInner<Ip, Pr, Cp>
+impl<Ip, Pr, Cp, Ro> ExternalClientConfig for ConfigInner<Ip, Pr, Cp, Ro>
 where
     Ip: IdentityProvider + Clone,
     Pr: MlsRules + Clone,
     Cp: CryptoProvider + Clone,
+    Ro: RosterObserver + Clone,
 {
     type IdentityProvider = Ip;
     type MlsRules = Pr;
     type CryptoProvider = Cp;
+    type RosterObserver = Ro;
 
     fn supported_extensions(&self) -> Vec<ExtensionType> {
         self.settings.extension_types.clone()
@@ -374,6 +424,10 @@ where
         self.mls_rules.clone()
     }
 
+    fn roster_observer(&self) -> Self::RosterObserver {
+        self.roster_observer.clone()
+    }
+
     fn max_epoch_jitter(&self) -> Option<u64> {
         self.settings.max_epoch_jitter
     }
@@ -387,15 +441,16 @@ where
     }
 }
 
-impl<Ip, Mpf, Cp> Sealed for Config<Ip, Mpf, Cp> {}
+impl<Ip, Mpf, Cp, Ro> Sealed for Config<Ip, Mpf, Cp, Ro> {}
 
-impl<Ip, Pr, Cp> MlsConfig for Config<Ip, Pr, Cp>
+impl<Ip, Pr, Cp, Ro> MlsConfig for Config<Ip, Pr, Cp, Ro>
 where
     Ip: IdentityProvider + Clone,
     Pr: MlsRules + Clone,
     Cp: CryptoProvider + Clone,
+    Ro: RosterObserver + Clone,
 {
-    type Output = ConfigInner<Ip, Pr, Cp>;
+    type Output = ConfigInner<Ip, Pr, Cp, Ro>;
 
     fn get(&self) -> &Self::Output {
         &self.0
@@ -420,6 +475,7 @@ impl<T: MlsConfig> ExternalClientConfig for T {
     type IdentityProvider = <T::Output as ExternalClientConfig>::IdentityProvider;
     type MlsRules = <T::Output as ExternalClientConfig>::MlsRules;
     type CryptoProvider = <T::Output as ExternalClientConfig>::CryptoProvider;
+    type RosterObserver = <T::Output as ExternalClientConfig>::RosterObserver;
 
     fn supported_extensions(&self) -> Vec<ExtensionType> {
         self.get().supported_extensions()
@@ -449,6 +505,10 @@ impl<T: MlsConfig> ExternalClientConfig for T {
         self.get().mls_rules()
     }
 
+    fn roster_observer(&self) -> Self::RosterObserver {
+        self.get().roster_observer()
+    }
+
     fn cache_proposals(&self) -> bool {
         self.get().cache_proposals()
     }
@@ -525,14 +585,15 @@ mod private {
     use super::{IntoConfigOutput, Settings};
 
     #[derive(Clone, Debug)]
-    pub struct Config<Ip, Pr, Cp>(pub(crate) ConfigInner<Ip, Pr, Cp>);
+    pub struct Config<Ip, Pr, Cp, Ro>(pub(crate) ConfigInner<Ip, Pr, Cp, Ro>);
 
     #[derive(Clone, Debug)]
-    pub struct ConfigInner<Ip, Mpf, Cp> {
+    pub struct ConfigInner<Ip, Mpf, Cp, Ro> {
         pub(crate) settings: Settings,
         pub(crate) identity_provider: Ip,
         pub(crate) mls_rules: Mpf,
         pub(crate) crypto_provider: Cp,
+        pub(crate) roster_observer: Ro,
         pub(crate) signing_data: Option<(SignatureSecretKey, SigningIdentity)>,
     }
 
@@ -540,14 +601,16 @@ mod private {
         type IdentityProvider;
         type MlsRules;
         type CryptoProvider;
+        type RosterObserver;
 
         fn into_config(self) -> IntoConfigOutput<Self>;
     }
 
-    impl<Ip, Pr, Cp> IntoConfig for Config<Ip, Pr, Cp> {
+    impl<Ip, Pr, Cp, Ro> IntoConfig for Config<Ip, Pr, Cp, Ro> {
         type IdentityProvider = Ip;
         type MlsRules = Pr;
         type CryptoProvider = Cp;
+        type RosterObserver = Ro;
 
         fn into_config(self) -> Self {
             self
@@ -7,6 +7,7 @@ use mls_rs_core::identity::IdentityProvider;
 use crate::{
     crypto::SignaturePublicKey,
     extension::ExtensionType,
+    external_client::RosterObserver,
     group::{mls_rules::MlsRules, proposal::ProposalType},
     identity::CredentialType,
     protocol_version::ProtocolVersion,
@@ -18,6 +19,7 @@ pub trait ExternalClientConfig: Send + Sync + Clone {
     type IdentityProvider: IdentityProvider + Clone;
     type MlsRules: MlsRules + Clone;
     type CryptoProvider: CryptoProvider;
+    type RosterObserver: RosterObserver + Clone;
 
     fn supported_extensions(&self) -> Vec<ExtensionType>;
     fn supported_custom_proposals(&self) -> Vec<ProposalType>;
@@ -28,6 +30,8 @@ pub trait ExternalClientConfig: Send + Sync + Clone {
 
     fn mls_rules(&self) -> Self::MlsRules;
 
+    fn roster_observer(&self) -> Self::RosterObserver;
+
     fn cache_proposals(&self) -> bool;
 
     fn max_epoch_jitter(&self) -> Option<u64> {
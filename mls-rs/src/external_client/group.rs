@@ -11,7 +11,7 @@ use mls_rs_core::{
 use crate::{
     cipher_suite::CipherSuite,
     client::MlsError,
-    external_client::ExternalClientConfig,
+    external_client::{roster_observer::roster_events, ExternalClientConfig, RosterObserver},
     group::{
         cipher_suite_provider,
         confirmation_tag::ConfirmationTag,
@@ -25,9 +25,9 @@ use crate::{
         proposal_filter::ProposalInfo,
         snapshot::RawGroupState,
         state::GroupState,
-        transcript_hash::InterimTranscriptHash,
-        validate_tree_and_info_joiner, ContentType, ExportedTree, GroupContext, GroupInfo, Roster,
-        Welcome,
+        transcript_hash::{EpochTranscript, InterimTranscriptHash},
+        validate_tree_and_info_joiner, ContentType, ExportedTree, GroupContext, GroupInfo,
+        GroupView, Roster, Welcome,
     },
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
@@ -70,7 +70,7 @@ use crate::{
 };
 
 #[cfg(feature = "private_message")]
-use crate::group::framing::PrivateMessage;
+use crate::group::{framing::PrivateMessage, targeted_message::TargetedMessage};
 
 use alloc::boxed::Box;
 
@@ -166,7 +166,10 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
     /// * Commits will result in the same outcome as a standard group.
     /// However, the integrity of the resulting group state can only be partially
     /// verified, since the external group does have access to the group
-    /// secrets required to do a complete check.
+    /// secrets required to do a complete check. Update paths and proposals are
+    /// applied to the ratchet tree of the group, so the
+    /// [roster](ExternalGroup::roster) is kept up to date without the tree
+    /// being sent along with commits.
     ///
     /// * Application messages are always encrypted so they result in a no-op
     /// that returns [ExternalReceivedMessage::Ciphertext]
@@ -493,6 +496,39 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
         &self.group_state().context
     }
 
+    /// Transcript hashes and confirmation tag of the current epoch, see
+    /// [`Group::epoch_transcript`](crate::Group::epoch_transcript).
+    pub fn epoch_transcript(&self) -> EpochTranscript {
+        EpochTranscript::new(self.group_state())
+    }
+
+    /// Signing identity of the sender of a proposal or commit sent as a
+    /// [`PublicMessage`](crate::WireFormat::PublicMessage) in the current
+    /// epoch, see [`Group::public_message_sender`](crate::Group::public_message_sender).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn public_message_sender(
+        &self,
+        message: &MlsMessage,
+    ) -> Result<SigningIdentity, MlsError> {
+        let MlsMessagePayload::Plain(plaintext) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        crate::group::message_verifier::verified_sender_identity(
+            &self.cipher_suite_provider,
+            plaintext,
+            &self.state,
+        )
+        .await
+    }
+
+    /// Serializable summary of the public state of the group, see
+    /// [`Group::view`](crate::Group::view).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn view(&self) -> Result<GroupView, MlsError> {
+        GroupView::new(&self.state, &self.config.identity_provider(), None).await
+    }
+
     /// Export the current ratchet tree used within the group.
     pub fn export_tree(&self) -> Result<Vec<u8>, MlsError> {
         self.group_state()
@@ -502,12 +538,30 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
             .map_err(Into::into)
     }
 
+    /// Render the current ratchet tree for debugging, see
+    /// [`Group::debug_tree`](crate::Group::debug_tree).
+    #[cfg(feature = "debug_utils")]
+    pub fn debug_tree(
+        &self,
+        format: crate::group::DebugTreeFormat,
+    ) -> Result<alloc::string::String, MlsError> {
+        crate::tree_kem::tree_utils::build_debug_tree(&self.group_state().public_tree.nodes, format)
+    }
+
     /// Get the current roster of the group.
     #[inline(always)]
     pub fn roster(&self) -> Roster {
         self.group_state().public_tree.roster()
     }
 
+    /// Member at a specific index in the group state.
+    ///
+    /// These indexes correspond to indexes in content descriptions within
+    /// [`ExternalReceivedMessage`].
+    pub fn member_at_index(&self, index: u32) -> Option<Member> {
+        self.group_state().member_at_index(index)
+    }
+
     /// Get the
     /// [transcript hash](https://messaginglayersecurity.rocks/mls-protocol/draft-ietf-mls-protocol.html#name-transcript-hashes)
     /// for the current epoch that the group is in.
@@ -607,6 +661,16 @@ where
         )))
     }
 
+    #[cfg(feature = "private_message")]
+    async fn process_targeted_message(
+        &mut self,
+        _message: TargetedMessage,
+    ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
+        Ok(EventOrContent::Event(ExternalReceivedMessage::Ciphertext(
+            ContentType::Application,
+        )))
+    }
+
     async fn update_key_schedule(
         &mut self,
         _secrets: Option<(TreeKemPrivate, PathSecret)>,
@@ -614,6 +678,14 @@ where
         confirmation_tag: &ConfirmationTag,
         provisional_public_state: ProvisionalState,
     ) -> Result<(), MlsError> {
+        let events = roster_events(&self.state, &provisional_public_state);
+
+        self.config
+            .roster_observer()
+            .roster_changed(&self.state.context.group_id, &events)
+            .await
+            .map_err(|e| MlsError::RosterObserverError(e.into_any_error()))?;
+
         self.state.context = provisional_public_state.group_context;
         #[cfg(feature = "by_ref_proposal")]
         self.state.proposals.clear();
@@ -1035,6 +1107,45 @@ mod tests {
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_can_identify_public_message_sender() {
+        let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let server = make_external_group(&alice).await;
+
+        let mut commit_output = alice.commit(Vec::new()).await.unwrap();
+
+        let sender = server
+            .public_message_sender(&commit_output.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(&sender, alice.current_member_signing_identity().unwrap());
+
+        match commit_output.commit_message.payload {
+            MlsMessagePayload::Plain(ref mut plain) => plain.auth.signature = Vec::new().into(),
+            _ => panic!("Unexpected non-plaintext data"),
+        };
+
+        let res = server
+            .public_message_sender(&commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_view_matches_member_view() {
+        let alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let server = make_external_group(&alice).await;
+
+        let view = server.view().await.unwrap();
+        let member_view = alice.view().await.unwrap();
+
+        assert_eq!(view.self_index, None);
+        assert_eq!(view.epoch, member_view.epoch);
+        assert_eq!(view.members, member_view.members);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_group_rejects_unencrypted_application_message() {
         let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -1416,4 +1527,39 @@ mod tests {
 
         assert_eq!(restored.group_state(), server.group_state());
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_tracks_tree_across_commits() {
+        let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut server = make_external_group(&alice).await;
+
+        let (mut bob, commit) = alice.join("bob").await;
+        server.process_incoming_message(commit).await.unwrap();
+
+        // Bob's commit updates his path in the tree
+        let commit = bob.commit(vec![]).await.unwrap().commit_message;
+        bob.apply_pending_commit().await.unwrap();
+        alice.process_message(commit.clone()).await.unwrap();
+        server.process_incoming_message(commit).await.unwrap();
+
+        assert_eq!(server.member_at_index(1), bob.member_at_index(1));
+        assert_eq!(server.roster().members(), alice.roster().members());
+        assert_eq!(alice.state, server.state);
+
+        let commit = alice
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.apply_pending_commit().await.unwrap();
+        server.process_incoming_message(commit).await.unwrap();
+
+        assert_eq!(server.member_at_index(1), None);
+        assert_eq!(server.roster().members().len(), 1);
+        assert_eq!(alice.state, server.state);
+    }
 }
@@ -0,0 +1,236 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::convert::Infallible;
+
+use mls_rs_core::{error::IntoAnyError, extension::ExtensionDiff};
+
+use crate::{
+    group::{message_processor::ProvisionalState, GroupState, RosterDiffMember, RosterDiffUpdate},
+    tree_kem::leaf_node::LeafNode,
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+/// Change observed by an [`ExternalGroup`](crate::external_client::ExternalGroup)
+/// when processing a commit.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RosterEvent {
+    /// A member was added, including by an external commit.
+    MemberAdded(RosterDiffMember),
+    /// A member was removed. A member replacing its own leaf with an external
+    /// commit is reported as removed and added.
+    MemberRemoved(RosterDiffMember),
+    /// A member changed its signing identity, for example to rotate its
+    /// credential.
+    MemberUpdated(RosterDiffUpdate),
+    /// The group context extensions changed.
+    ExtensionsChanged(ExtensionDiff),
+    /// The group moved to a new epoch. This is always the last event
+    /// reported for a commit.
+    EpochAdvanced { epoch: u64 },
+}
+
+/// Callbacks of an [`ExternalClient`](crate::external_client::ExternalClient)
+/// reporting the membership changes of the groups it observes, set with
+/// [`ExternalClientBuilder::roster_observer`](crate::external_client::builder::ExternalClientBuilder::roster_observer).
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait RosterObserver: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Called with the events caused by a commit, after the commit is
+    /// validated and before the group state is updated.
+    ///
+    /// Removals are reported first, followed by updates and additions, each
+    /// in leaf index order. If an error is returned, processing the commit
+    /// fails and the group state is left unchanged.
+    async fn roster_changed(
+        &self,
+        group_id: &[u8],
+        events: &[RosterEvent],
+    ) -> Result<(), Self::Error>;
+}
+
+/// [`RosterObserver`] ignoring all events. This is the default observer of an
+/// [`ExternalClient`](crate::external_client::ExternalClient).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopRosterObserver;
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl RosterObserver for NoopRosterObserver {
+    type Error = Infallible;
+
+    async fn roster_changed(
+        &self,
+        _group_id: &[u8],
+        _events: &[RosterEvent],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+pub(crate) fn roster_events(previous: &GroupState, next: &ProvisionalState) -> Vec<RosterEvent> {
+    let from = previous.public_tree.nodes.leaves().collect::<Vec<_>>();
+    let to = next.public_tree.nodes.leaves().collect::<Vec<_>>();
+
+    let removed_indexes = next
+        .applied_proposals
+        .remove_proposals()
+        .iter()
+        .map(|p| p.proposal.to_remove())
+        .collect::<Vec<_>>();
+
+    let (mut removed, mut updated, mut added) = (Vec::new(), Vec::new(), Vec::new());
+
+    for index in 0..from.len().max(to.len()) {
+        let previous = from.get(index).copied().flatten();
+        let current = to.get(index).copied().flatten();
+        let index = index as u32;
+
+        let member = |leaf: &LeafNode| RosterDiffMember {
+            index,
+            signing_identity: leaf.signing_identity.clone(),
+        };
+
+        match (previous, current) {
+            (None, None) => {}
+            (None, Some(current)) => added.push(RosterEvent::MemberAdded(member(current))),
+            (Some(previous), None) => removed.push(RosterEvent::MemberRemoved(member(previous))),
+            (Some(previous), Some(current)) if removed_indexes.contains(&index) => {
+                removed.push(RosterEvent::MemberRemoved(member(previous)));
+                added.push(RosterEvent::MemberAdded(member(current)));
+            }
+            (Some(previous), Some(current)) => {
+                if previous.signing_identity != current.signing_identity {
+                    updated.push(RosterEvent::MemberUpdated(RosterDiffUpdate {
+                        index,
+                        previous: previous.signing_identity.clone(),
+                        current: current.signing_identity.clone(),
+                    }));
+                }
+            }
+        }
+    }
+
+    let mut events = removed;
+    events.append(&mut updated);
+    events.append(&mut added);
+
+    let extension_diff = previous
+        .context
+        .extensions
+        .diff(&next.group_context.extensions);
+
+    if !extension_diff.is_empty() {
+        events.push(RosterEvent::ExtensionsChanged(extension_diff));
+    }
+
+    events.push(RosterEvent::EpochAdvanced {
+        epoch: next.group_context.epoch,
+    });
+
+    events
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use std::sync::Mutex;
+
+    use assert_matches::assert_matches;
+    use core::convert::Infallible;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        external_client::{tests_utils::TestExternalClientBuilder, ExternalGroup},
+        group::test_utils::test_group,
+    };
+
+    use super::{RosterEvent, RosterObserver};
+
+    #[derive(Clone, Default)]
+    struct RecordingObserver(Arc<Mutex<Vec<RosterEvent>>>);
+
+    impl RecordingObserver {
+        fn take(&self) -> Vec<RosterEvent> {
+            core::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl RosterObserver for RecordingObserver {
+        type Error = Infallible;
+
+        async fn roster_changed(
+            &self,
+            _group_id: &[u8],
+            events: &[RosterEvent],
+        ) -> Result<(), Self::Error> {
+            self.0.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn roster_observer_reports_membership_changes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let observer = RecordingObserver::default();
+
+        let config = TestExternalClientBuilder::new_for_test()
+            .roster_observer(observer.clone())
+            .build_config();
+
+        let group_info = alice
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let mut server = ExternalGroup::join(config, None, group_info, None)
+            .await
+            .unwrap();
+
+        let (bob, commit) = alice.join("bob").await;
+        server.process_incoming_message(commit).await.unwrap();
+
+        let events = observer.take();
+        assert_eq!(events.len(), 2);
+
+        assert_matches!(&events[0], RosterEvent::MemberAdded(member)
+            if member.index == 1 && Some(&member.signing_identity) == bob.current_member_signing_identity().ok());
+
+        assert_matches!(events[1], RosterEvent::EpochAdvanced { epoch: 1 });
+
+        let commit = alice
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.apply_pending_commit().await.unwrap();
+        server.process_incoming_message(commit).await.unwrap();
+
+        assert_matches!(
+            observer.take().as_slice(),
+            [RosterEvent::MemberRemoved(member), RosterEvent::EpochAdvanced { epoch: 2 }]
+                if member.index == 1
+        );
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        server.process_incoming_message(commit).await.unwrap();
+
+        assert_eq!(
+            observer.take(),
+            vec![RosterEvent::EpochAdvanced { epoch: 3 }]
+        );
+    }
+}
@@ -11,6 +11,20 @@ use crate::{
     tree_kem::leaf_node::LeafNode,
 };
 
+pub(crate) const GREASE_VALUES: &[u16] = &[
+    0x0A0A, 0x1A1A, 0x2A2A, 0x3A3A, 0x4A4A, 0x5A5A, 0x6A6A, 0x7A7A, 0x8A8A, 0x9A9A, 0xAAAA, 0xBABA,
+    0xCACA, 0xDADA, 0xEAEA,
+];
+
+/// Whether `value` is one of the GREASE values reserved by RFC 9420.
+///
+/// GREASE values are recognized regardless of the `grease` feature, so that
+/// clients which do not grease their own messages still accept greased messages
+/// from other clients.
+pub(crate) fn is_grease_value(value: u16) -> bool {
+    GREASE_VALUES.contains(&value)
+}
+
 impl LeafNode {
     pub fn ungreased_capabilities(&self) -> Capabilities {
         let mut capabilitites = self.capabilities.clone();
@@ -77,12 +91,7 @@ mod grease_functions {
         extension::{Extension, ExtensionList, ExtensionType},
     };
 
-    use super::MlsError;
-
-    pub const GREASE_VALUES: &[u16] = &[
-        0x0A0A, 0x1A1A, 0x2A2A, 0x3A3A, 0x4A4A, 0x5A5A, 0x6A6A, 0x7A7A, 0x8A8A, 0x9A9A, 0xAAAA,
-        0xBABA, 0xCACA, 0xDADA, 0xEAEA,
-    ];
+    use super::{MlsError, GREASE_VALUES};
 
     pub fn grease<T: From<u16>, P: CipherSuiteProvider>(
         array: &mut Vec<T>,
@@ -166,7 +175,7 @@ mod tests {
         group::test_utils::test_group,
     };
 
-    use super::grease_functions::GREASE_VALUES;
+    use super::GREASE_VALUES;
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn key_package_is_greased() {
@@ -0,0 +1,149 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{client_config::ClientConfig, group::Group};
+
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct AppDataEntry {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    namespace: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    key: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    value: Vec<u8>,
+}
+
+impl Debug for AppDataEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppDataEntry")
+            .field(
+                "namespace",
+                &mls_rs_core::debug::pretty_bytes(&self.namespace),
+            )
+            .field("key", &mls_rs_core::debug::pretty_bytes(&self.key))
+            .field("value", &mls_rs_core::debug::pretty_bytes(&self.value))
+            .finish()
+    }
+}
+
+/// Application key-value data stored along with the group state. Entries are
+/// kept sorted by namespace and key so that the encoding is deterministic.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AppData(Vec<AppDataEntry>);
+
+impl AppData {
+    fn find(&self, namespace: &[u8], key: &[u8]) -> Result<usize, usize> {
+        self.0.binary_search_by(|entry| {
+            (entry.namespace.as_slice(), entry.key.as_slice()).cmp(&(namespace, key))
+        })
+    }
+
+    fn get(&self, namespace: &[u8], key: &[u8]) -> Option<&[u8]> {
+        self.find(namespace, key)
+            .ok()
+            .map(|i| self.0[i].value.as_slice())
+    }
+
+    fn set(&mut self, namespace: &[u8], key: &[u8], value: Vec<u8>) {
+        match self.find(namespace, key) {
+            Ok(i) => self.0[i].value = value,
+            Err(i) => self.0.insert(
+                i,
+                AppDataEntry {
+                    namespace: namespace.to_vec(),
+                    key: key.to_vec(),
+                    value,
+                },
+            ),
+        }
+    }
+
+    fn remove(&mut self, namespace: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        self.find(namespace, key)
+            .ok()
+            .map(|i| self.0.remove(i).value)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Set the application data stored under `key` in `namespace`.
+    ///
+    /// Application data is part of the group state. It is persisted by the
+    /// next call to [`Group::write_to_storage`], in the same
+    /// [`GroupStateStorage::write`](crate::GroupStateStorage::write) as the
+    /// epoch changes processed since the previous write. Applications can use
+    /// it to keep data such as the last read message consistent with the
+    /// stored group state.
+    pub fn app_data_set(&mut self, namespace: &[u8], key: &[u8], value: Vec<u8>) {
+        self.app_data.set(namespace, key, value)
+    }
+
+    /// Application data stored under `key` in `namespace`, if any.
+    pub fn app_data_get(&self, namespace: &[u8], key: &[u8]) -> Option<&[u8]> {
+        self.app_data.get(namespace, key)
+    }
+
+    /// Remove the application data stored under `key` in `namespace`,
+    /// returning it if it existed.
+    pub fn app_data_remove(&mut self, namespace: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        self.app_data.remove(namespace, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_config::ClientConfig,
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn app_data_is_persisted_with_group_state() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        alice.app_data_set(b"chat", b"last_read", vec![1]);
+        alice.app_data_set(b"chat", b"draft", vec![2]);
+        alice.app_data_set(b"calls", b"last_read", vec![3]);
+        alice.app_data_set(b"chat", b"last_read", vec![4]);
+
+        assert_eq!(alice.app_data_remove(b"chat", b"draft"), Some(vec![2]));
+        assert_eq!(alice.app_data_remove(b"chat", b"draft"), None);
+
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+        alice.write_to_storage().await.unwrap();
+
+        let restored = TestClientBuilder::new_for_test()
+            .group_state_storage(alice.config.group_state_storage())
+            .build()
+            .load_group(alice.group_id())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            restored.app_data_get(b"chat", b"last_read"),
+            Some(&[4u8][..])
+        );
+        assert_eq!(
+            restored.app_data_get(b"calls", b"last_read"),
+            Some(&[3u8][..])
+        );
+        assert_eq!(restored.app_data_get(b"chat", b"draft"), None);
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::Group,
+    signer::{ApplicationSignatureLabel, Signable},
+    tree_kem::node::LeafIndex,
+};
+
+struct ApplicationSignatureContent<'a> {
+    label: &'a ApplicationSignatureLabel,
+    data: &'a [u8],
+    signature: Vec<u8>,
+}
+
+impl<'a> Signable<'a> for ApplicationSignatureContent<'_> {
+    const SIGN_LABEL: &'static str = "application";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        Ok(self.data.to_vec())
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+
+    fn sign_label(&self) -> &str {
+        self.label.full_label()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Sign `data` with the signature key of this member, using `label` for
+    /// domain separation from protocol signatures.
+    ///
+    /// The signature can be verified by other members with
+    /// [`Group::verify_application_signature`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn sign_application_data(
+        &self,
+        label: &ApplicationSignatureLabel,
+        data: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let mut content = ApplicationSignatureContent {
+            label,
+            data,
+            signature: Vec::new(),
+        };
+
+        content
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(content.signature)
+    }
+
+    /// Verify a signature created by the member at `signer_index` with
+    /// [`Group::sign_application_data`].
+    ///
+    /// The signature is verified with the signature key of the member in the
+    /// current epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_application_signature(
+        &self,
+        signer_index: u32,
+        label: &ApplicationSignatureLabel,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<(), MlsError> {
+        let signer = self
+            .current_epoch_tree()
+            .get_leaf_node(LeafIndex(signer_index))?;
+
+        ApplicationSignatureContent {
+            label,
+            data,
+            signature: signature.to_vec(),
+        }
+        .verify(
+            &self.cipher_suite_provider,
+            &signer.signing_identity.signature_key,
+            &(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::ApplicationSignatureLabel,
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_signatures_are_bound_to_label_and_signer() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let label = ApplicationSignatureLabel::new("receipt");
+        assert_eq!(label.label(), "receipt");
+        assert_eq!(label.full_label(), "application receipt");
+
+        let signature = alice
+            .sign_application_data(&label, b"message 1")
+            .await
+            .unwrap();
+
+        bob.verify_application_signature(0, &label, b"message 1", &signature)
+            .await
+            .unwrap();
+
+        let other_label = ApplicationSignatureLabel::new("vote");
+
+        let res = bob
+            .verify_application_signature(0, &other_label, b"message 1", &signature)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+
+        let res = bob
+            .verify_application_signature(1, &label, b"message 1", &signature)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        cipher_suite_provider,
+        ciphertext_processor::CiphertextProcessor,
+        epoch::PriorEpoch,
+        framing::{Content, ContentType, MlsMessagePayload},
+        message_processor::ApplicationMessageDescription,
+        message_verifier::{verify_auth_content_signature, SignaturePublicKeysContainer},
+        GroupContext, Sender,
+    },
+    CryptoProvider, MlsMessage,
+};
+
+#[derive(Clone, PartialEq, MlsEncode, MlsDecode, MlsSize)]
+struct GroupArchive {
+    version: u16,
+    // Ordered from the oldest retained epoch to the epoch the group was in
+    // when it was archived.
+    epochs: Vec<PriorEpoch>,
+}
+
+/// Compact, read-only form of a [`Group`](crate::Group).
+///
+/// An archived group only retains the sender data secret and the secret tree
+/// of the epoch the group was archived at, and of any prior epoch that was
+/// still retained by the [`GroupStateStorage`](crate::GroupStateStorage), which
+/// are required to decrypt application messages sent in those epochs.
+/// Resumption secrets, private keys for the ratchet tree, the key schedule and
+/// the signing key are discarded, so an archived group can never produce a
+/// commit, proposal or application message and can never advance to a new
+/// epoch.
+///
+/// Archives are created with [`Group::archive`](crate::Group::archive) and
+/// restored with [`Client::open_archived_group`](crate::Client::open_archived_group).
+#[derive(Clone)]
+pub struct ArchivedGroup<C>
+where
+    C: ClientConfig,
+{
+    cipher_suite_provider: <C::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
+    archive: GroupArchive,
+}
+
+impl<C> Debug for ArchivedGroup<C>
+where
+    C: ClientConfig,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedGroup")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(self.group_id()),
+            )
+            .field("oldest_epoch", &self.oldest_epoch())
+            .field("current_epoch", &self.current_epoch())
+            .finish()
+    }
+}
+
+impl<C> ArchivedGroup<C>
+where
+    C: ClientConfig,
+{
+    pub(crate) fn new(config: &C, epochs: Vec<PriorEpoch>) -> Result<Self, MlsError> {
+        #[cfg(feature = "psk")]
+        let epochs = epochs
+            .into_iter()
+            .map(|mut epoch| {
+                epoch.secrets.resumption_secret = Vec::new().into();
+                epoch
+            })
+            .collect();
+
+        Self::from_archive(config, GroupArchive { version: 1, epochs })
+    }
+
+    pub(crate) fn from_bytes(config: &C, bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::from_archive(config, GroupArchive::mls_decode(&mut &*bytes)?)
+    }
+
+    fn from_archive(config: &C, archive: GroupArchive) -> Result<Self, MlsError> {
+        let cipher_suite = archive
+            .epochs
+            .last()
+            .ok_or(MlsError::EpochNotFound)?
+            .context
+            .cipher_suite;
+
+        Ok(Self {
+            cipher_suite_provider: cipher_suite_provider(config.crypto_provider(), cipher_suite)?,
+            archive,
+        })
+    }
+
+    /// Serialize the archive so that it can be stored and later opened with
+    /// [`Client::open_archived_group`](crate::Client::open_archived_group).
+    ///
+    /// Decrypting messages consumes message keys, so the archive should be
+    /// serialized again after calling
+    /// [`ArchivedGroup::decrypt_message`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.archive.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Unique identifier for the archived group.
+    pub fn group_id(&self) -> &[u8] {
+        &self.context().group_id
+    }
+
+    /// Group context of the epoch the group was in when it was archived.
+    pub fn context(&self) -> &GroupContext {
+        &self.latest_epoch().context
+    }
+
+    /// The epoch the group was in when it was archived.
+    pub fn current_epoch(&self) -> u64 {
+        self.latest_epoch().epoch_id()
+    }
+
+    /// The oldest epoch whose messages can still be decrypted.
+    pub fn oldest_epoch(&self) -> u64 {
+        self.archive
+            .epochs
+            .first()
+            .map_or_else(|| self.current_epoch(), |e| e.epoch_id())
+    }
+
+    /// Decrypt an application message sent in any epoch retained by the
+    /// archive.
+    ///
+    /// Handshake messages are rejected with
+    /// [`MlsError::ArchivedGroupReadOnly`] since an archived group can no
+    /// longer change state.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn decrypt_message(
+        &mut self,
+        message: MlsMessage,
+    ) -> Result<ApplicationMessageDescription, MlsError> {
+        let MlsMessagePayload::Cipher(ciphertext) = message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        if ciphertext.content_type != ContentType::Application {
+            return Err(MlsError::ArchivedGroupReadOnly);
+        }
+
+        if ciphertext.group_id != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        let epoch = self
+            .archive
+            .epochs
+            .iter_mut()
+            .find(|e| e.epoch_id() == ciphertext.epoch)
+            .ok_or(MlsError::EpochNotFound)?;
+
+        let auth_content = CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone())
+            .open(&ciphertext)
+            .await?;
+
+        verify_auth_content_signature(
+            &self.cipher_suite_provider,
+            SignaturePublicKeysContainer::List(&epoch.signature_public_keys),
+            &epoch.context,
+            &auth_content,
+            #[cfg(feature = "by_ref_proposal")]
+            &[],
+        )
+        .await?;
+
+        let content = auth_content.content;
+
+        let (Content::Application(data), Sender::Member(sender_index)) =
+            (content.content, content.sender)
+        else {
+            return Err(MlsError::InvalidSender);
+        };
+
+        Ok(ApplicationMessageDescription {
+            sender_index,
+            data,
+            authenticated_data: content.authenticated_data,
+            sender_quarantined: false,
+        })
+    }
+
+    fn latest_epoch(&self) -> &PriorEpoch {
+        // `from_archive` guarantees that at least one epoch is present.
+        &self.archive.epochs[self.archive.epochs.len() - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            mls_rules::{DefaultMlsRules, EncryptionOptions},
+            padding::PaddingMode,
+            test_utils::test_group_custom_config,
+        },
+    };
+
+    use super::ArchivedGroup;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn archived_group_decrypts_past_epochs_but_is_read_only() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.mls_rules(
+                DefaultMlsRules::default()
+                    .with_encryption_options(EncryptionOptions::new(true, PaddingMode::None)),
+            )
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        let old_message = alice
+            .encrypt_application_message(b"old", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit).await.unwrap();
+
+        let new_message = alice
+            .encrypt_application_message(b"new", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+
+        let archive = bob.archive().await.unwrap().to_bytes().unwrap();
+        let mut archived = ArchivedGroup::from_bytes(&bob.config, &archive).unwrap();
+
+        assert_eq!(archived.group_id(), bob.group_id());
+        assert_eq!(archived.current_epoch(), bob.current_epoch());
+        assert_eq!(archived.oldest_epoch(), bob.current_epoch() - 1);
+
+        #[cfg(feature = "psk")]
+        assert!(archived
+            .archive
+            .epochs
+            .iter()
+            .all(|e| e.secrets.resumption_secret.is_empty()));
+
+        let received = archived.decrypt_message(new_message).await.unwrap();
+        assert_eq!(received.data(), b"new");
+
+        let received = archived.decrypt_message(old_message).await.unwrap();
+        assert_eq!(received.data(), b"old");
+
+        let res = archived.decrypt_message(commit).await;
+        assert_matches!(res, Err(MlsError::ArchivedGroupReadOnly));
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError, secret::Secret};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{ApplicationMessageDescription, Group},
+    MlsMessage,
+};
+
+const BRIDGE_EXPORTER_LABEL: &[u8] = b"MLS bridge provenance";
+
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+struct ProvenanceHeader {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    source_group_id: Vec<u8>,
+    source_epoch: u64,
+    source_sender_index: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    source_authenticated_data: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    nonce: Vec<u8>,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct ProvenanceAad<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    destination_group_id: &'a [u8],
+    header: &'a ProvenanceHeader,
+}
+
+/// Application message that was relayed from a source group into the group
+/// it was received in by a member of both groups.
+///
+/// The relayed data is encrypted with a key exported from the source group,
+/// and the provenance header, carried in the authenticated data of the
+/// relayed message, is authenticated along with it. Members of the
+/// destination group can read the claimed provenance with
+/// [`BridgedMessage::from_application_message`]. Members that also belong to
+/// the source group can decrypt the data, which checks that the message was
+/// relayed by a member of the source group, with
+/// [`Group::open_bridged_message`](crate::Group::open_bridged_message).
+#[derive(Clone, PartialEq, Eq)]
+pub struct BridgedMessage {
+    header: ProvenanceHeader,
+    ciphertext: Vec<u8>,
+}
+
+impl Debug for BridgedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BridgedMessage")
+            .field(
+                "source_group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.header.source_group_id),
+            )
+            .field("source_epoch", &self.header.source_epoch)
+            .field("source_sender_index", &self.header.source_sender_index)
+            .field(
+                "ciphertext",
+                &mls_rs_core::debug::pretty_bytes(&self.ciphertext),
+            )
+            .finish()
+    }
+}
+
+impl BridgedMessage {
+    /// Parse the provenance header of an application message received in the
+    /// destination group.
+    pub fn from_application_message(
+        message: &ApplicationMessageDescription,
+    ) -> Result<Self, MlsError> {
+        Ok(Self {
+            header: ProvenanceHeader::mls_decode(&mut &*message.authenticated_data)?,
+            ciphertext: message.data().to_vec(),
+        })
+    }
+
+    /// Identifier of the group the message was originally sent in.
+    pub fn source_group_id(&self) -> &[u8] {
+        &self.header.source_group_id
+    }
+
+    /// Epoch of the source group in which the message was originally sent.
+    pub fn source_epoch(&self) -> u64 {
+        self.header.source_epoch
+    }
+
+    /// Leaf index of the original sender within the source group.
+    pub fn source_sender_index(&self) -> u32 {
+        self.header.source_sender_index
+    }
+
+    /// Authenticated data of the original message.
+    pub fn source_authenticated_data(&self) -> &[u8] {
+        &self.header.source_authenticated_data
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Relay an application message received in `source` into this group.
+    ///
+    /// The message data is encrypted with a key exported from the current
+    /// epoch of `source` and bound to the identifier of this group, along
+    /// with a provenance header identifying the source group, epoch and
+    /// sender, so it can not be replayed into another destination group.
+    /// The result is then encrypted for this group. `message` must have been
+    /// received in the current epoch of `source`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn bridge_application_message<S>(
+        &mut self,
+        source: &Group<S>,
+        message: &ApplicationMessageDescription,
+    ) -> Result<MlsMessage, MlsError>
+    where
+        S: ClientConfig + Clone,
+    {
+        let cs = &source.cipher_suite_provider;
+
+        let header = ProvenanceHeader {
+            source_group_id: source.group_id().to_vec(),
+            source_epoch: source.current_epoch(),
+            source_sender_index: message.sender_index,
+            source_authenticated_data: message.authenticated_data.clone(),
+            nonce: cs
+                .random_bytes_vec(cs.aead_nonce_size())
+                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?,
+        };
+
+        let key = source.bridge_key(self.group_id()).await?;
+
+        let aad = ProvenanceAad {
+            destination_group_id: self.group_id(),
+            header: &header,
+        };
+
+        let ciphertext = cs
+            .aead_seal(
+                key.as_bytes(),
+                message.data(),
+                Some(&aad.mls_encode_to_vec()?),
+                &header.nonce,
+            )
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        self.encrypt_application_message(&ciphertext, header.mls_encode_to_vec()?)
+            .await
+    }
+
+    /// Decrypt the data of `message`, received in the group identified by
+    /// `destination_group_id`, checking that it was relayed from this group
+    /// by one of its members.
+    ///
+    /// Decryption requires this group to still be in the epoch the message
+    /// was originally sent in.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn open_bridged_message(
+        &self,
+        destination_group_id: &[u8],
+        message: &BridgedMessage,
+    ) -> Result<Vec<u8>, MlsError> {
+        if message.source_group_id() != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if message.source_epoch() != self.current_epoch() {
+            return Err(MlsError::EpochNotFound);
+        }
+
+        let key = self.bridge_key(destination_group_id).await?;
+
+        let aad = ProvenanceAad {
+            destination_group_id,
+            header: &message.header,
+        };
+
+        let data = self
+            .cipher_suite_provider
+            .aead_open(
+                key.as_bytes(),
+                &message.ciphertext,
+                Some(&aad.mls_encode_to_vec()?),
+                &message.header.nonce,
+            )
+            .await
+            .map_err(|_| MlsError::InvalidBridgeProvenance)?;
+
+        Ok(data.to_vec())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn bridge_key(&self, destination_group_id: &[u8]) -> Result<Secret, MlsError> {
+        self.export_secret(
+            BRIDGE_EXPORTER_LABEL,
+            destination_group_id,
+            self.cipher_suite_provider.aead_key_size(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::{test_group, TestGroup},
+        group::ApplicationMessageDescription,
+        MlsMessage,
+    };
+
+    use super::BridgedMessage;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn receive(group: &mut TestGroup, message: MlsMessage) -> ApplicationMessageDescription {
+        let received = group.process_incoming_message(message).await.unwrap();
+
+        let crate::group::ReceivedMessage::ApplicationMessage(message) = received else {
+            panic!("expected application message");
+        };
+
+        message
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn bridged_message_carries_verifiable_provenance() {
+        let mut source = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = source.join("bob").await;
+
+        let (client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let mut destination = TestGroup {
+            group: client
+                .create_group_with_id(
+                    b"announcements".to_vec(),
+                    Default::default(),
+                    Default::default(),
+                )
+                .await
+                .unwrap(),
+        };
+
+        let (mut carol, _) = destination.join("carol").await;
+
+        let message = bob
+            .encrypt_application_message(b"hello", b"source ad".to_vec())
+            .await
+            .unwrap();
+
+        let received = receive(&mut source, message).await;
+
+        let relayed = destination
+            .bridge_application_message(&source, &received)
+            .await
+            .unwrap();
+
+        let received = receive(&mut carol, relayed).await;
+        let bridged = BridgedMessage::from_application_message(&received).unwrap();
+
+        assert_eq!(bridged.source_group_id(), source.group_id());
+        assert_eq!(bridged.source_epoch(), source.current_epoch());
+        assert_eq!(bridged.source_sender_index(), 1);
+        assert_eq!(bridged.source_authenticated_data(), b"source ad");
+        assert_ne!(received.data(), b"hello");
+
+        let data = bob
+            .open_bridged_message(destination.group_id(), &bridged)
+            .await
+            .unwrap();
+
+        assert_eq!(data, b"hello");
+
+        let res = bob.open_bridged_message(b"elsewhere", &bridged).await;
+        assert_matches!(res, Err(MlsError::InvalidBridgeProvenance));
+
+        let mut tampered = bridged.clone();
+        tampered.header.source_sender_index = 0;
+
+        let res = bob
+            .open_bridged_message(destination.group_id(), &tampered)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidBridgeProvenance));
+    }
+}
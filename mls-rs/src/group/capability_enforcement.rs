@@ -0,0 +1,237 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_core::{error::IntoAnyError, extension::ExtensionList};
+
+use crate::{
+    client::MlsError,
+    group::{
+        mls_rules::{CommitDirection, CommitOptions, CommitSource, EncryptionOptions, MlsRules},
+        proposal::{Proposal, RemoveProposal},
+        proposal_filter::{ProposalBundle, ProposalSource},
+        GroupContext, Roster, Sender,
+    },
+};
+
+/// Action taken by [`CapabilityEnforcementRules`] when a commit changes the
+/// group context extensions in a way that some members do not support.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CapabilityPolicy {
+    /// Fail the commit with [`MlsError::MemberLacksCapabilities`].
+    #[default]
+    Block,
+    /// Remove the members lacking a capability as part of the commit. The
+    /// commit still fails if the committer lacks a capability.
+    RemoveIncapable,
+    /// Leave the group context extensions proposal out of the commit so that
+    /// it can be committed once all members support it.
+    Defer,
+}
+
+/// [`MlsRules`] enforcing that members support the capabilities required by
+/// new group context extensions before a commit is created.
+///
+/// Without enforcement, committing a group context extensions proposal that
+/// some members do not support fails with
+/// [`MlsError::UnsupportedGroupExtension`] or a missing required capability
+/// error. These rules resolve the conflict according to a
+/// [`CapabilityPolicy`] instead. They only apply to commits created by this
+/// client, after the wrapped rules have filtered the proposals. Received
+/// commits are validated as usual.
+#[derive(Clone, Debug)]
+pub struct CapabilityEnforcementRules<R> {
+    inner: R,
+    policy: CapabilityPolicy,
+}
+
+impl<R> CapabilityEnforcementRules<R> {
+    pub fn new(inner: R, policy: CapabilityPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn policy(&self) -> CapabilityPolicy {
+        self.policy
+    }
+
+    fn enforce(
+        &self,
+        source: &CommitSource,
+        current_roster: &Roster,
+        new_extensions: &ExtensionList,
+        mut proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, MlsError> {
+        let incapable = current_roster
+            .members_lacking_capabilities(new_extensions)?
+            .into_iter()
+            .map(|member| member.index)
+            .filter(|index| {
+                !proposals
+                    .remove_proposals()
+                    .iter()
+                    .any(|p| p.proposal.to_remove() == *index)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(&first) = incapable.first() else {
+            return Ok(proposals);
+        };
+
+        match (self.policy, source.member_index()) {
+            (CapabilityPolicy::Defer, _) => {
+                proposals.retain_by_type::<ExtensionList, _, MlsError>(|_| Ok(false))?;
+            }
+            (CapabilityPolicy::RemoveIncapable, Some(committer))
+                if !incapable.contains(&committer) =>
+            {
+                for index in incapable {
+                    proposals.add(
+                        Proposal::Remove(RemoveProposal::from(index)),
+                        Sender::Member(committer),
+                        ProposalSource::ByValue,
+                    );
+                }
+            }
+            _ => return Err(MlsError::MemberLacksCapabilities(first)),
+        }
+
+        Ok(proposals)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<R: MlsRules> MlsRules for CapabilityEnforcementRules<R> {
+    type Error = MlsError;
+
+    async fn filter_proposals(
+        &self,
+        direction: CommitDirection,
+        source: CommitSource,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+        proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, Self::Error> {
+        let proposals = self
+            .inner
+            .filter_proposals(
+                direction,
+                source.clone(),
+                current_roster,
+                current_context,
+                proposals,
+            )
+            .await
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
+
+        let new_extensions = proposals
+            .group_context_ext_proposals()
+            .first()
+            .map(|p| p.proposal.clone());
+
+        match new_extensions {
+            Some(new_extensions) if direction == CommitDirection::Send => {
+                self.enforce(&source, current_roster, &new_extensions, proposals)
+            }
+            _ => Ok(proposals),
+        }
+    }
+
+    fn commit_options(
+        &self,
+        new_roster: &Roster,
+        new_context: &GroupContext,
+        proposals: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        self.inner
+            .commit_options(new_roster, new_context, proposals)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    fn encryption_options(
+        &self,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+    ) -> Result<EncryptionOptions, Self::Error> {
+        self.inner
+            .encryption_options(current_roster, current_context)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::extension::{Extension, ExtensionList, ExtensionType};
+
+    use crate::client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE};
+    use crate::client::MlsError;
+    use crate::mls_rules::DefaultMlsRules;
+
+    use super::{CapabilityEnforcementRules, CapabilityPolicy};
+
+    const EXT_TYPE: ExtensionType = ExtensionType::new(999);
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn capability_policy_is_applied_to_group_context_changes() {
+        let new_extensions = ExtensionList::from(vec![Extension::new(EXT_TYPE, vec![])]);
+
+        for policy in [
+            CapabilityPolicy::Block,
+            CapabilityPolicy::RemoveIncapable,
+            CapabilityPolicy::Defer,
+        ] {
+            let alice = TestClientBuilder::new_for_test()
+                .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+                .await
+                .extension_type(EXT_TYPE)
+                .mls_rules(CapabilityEnforcementRules::new(
+                    DefaultMlsRules::new(),
+                    policy,
+                ))
+                .build();
+
+            let bob = TestClientBuilder::new_for_test()
+                .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+                .await
+                .build();
+
+            let key_package = bob
+                .generate_key_package_message(Default::default(), Default::default())
+                .await
+                .unwrap();
+
+            let (mut group, _) = alice
+                .create_group_with_members(None, vec![key_package], Default::default())
+                .await
+                .unwrap();
+
+            let res = group
+                .commit_builder()
+                .set_group_context_ext(new_extensions.clone())
+                .unwrap()
+                .build()
+                .await
+                .map(|_| ());
+
+            if policy == CapabilityPolicy::Block {
+                assert_matches!(res, Err(MlsError::MlsRulesError(_)));
+                continue;
+            }
+
+            res.unwrap();
+            group.apply_pending_commit().await.unwrap();
+
+            let removed = group.roster().member_with_index(1).is_err();
+            let applied = group.context().extensions.has_extension(EXT_TYPE);
+
+            assert_eq!(removed, policy == CapabilityPolicy::RemoveIncapable);
+            assert_eq!(applied, policy == CapabilityPolicy::RemoveIncapable);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::iter;
+use mls_rs_codec::MlsSize;
+use mls_rs_core::crypto::SignatureSecretKey;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{proposal::Proposal, CommitOutput, FlushMode, Group},
+    identity::SigningIdentity,
+    ExtensionList,
+};
+
+/// Changes of a [`CommitBuilder`](super::CommitBuilder) that are only made by
+/// the first commit of a [`ChunkedCommit`].
+pub(super) struct FirstChunk {
+    pub proposals: Vec<Proposal>,
+    pub new_signer: Option<SignatureSecretKey>,
+    pub new_signing_identity: Option<SigningIdentity>,
+    pub new_leaf_node_extensions: Option<ExtensionList>,
+}
+
+/// Sequence of commits created by
+/// [`CommitBuilder::build_chunked`](super::CommitBuilder::build_chunked),
+/// together adding more members than fit in a single message.
+///
+/// The first commit contains every proposal other than adds, followed by as
+/// many adds as fit in the size limit. Each following commit contains as
+/// many of the remaining adds as fit. Commits are built one at a time by
+/// [`ChunkedCommit::next_commit`], each in the epoch created by the previous one.
+pub struct ChunkedCommit<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    group: &'a mut Group<C>,
+    max_size: usize,
+    adds: Vec<Proposal>,
+    first: Option<FirstChunk>,
+    authenticated_data: Vec<u8>,
+    group_info_extensions: ExtensionList,
+    next_epoch: Option<u64>,
+}
+
+impl<'a, C> ChunkedCommit<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    pub(super) fn new(
+        group: &'a mut Group<C>,
+        max_size: usize,
+        adds: Vec<Proposal>,
+        first: FirstChunk,
+        authenticated_data: Vec<u8>,
+        group_info_extensions: ExtensionList,
+    ) -> Self {
+        Self {
+            group,
+            max_size,
+            adds,
+            first: Some(first),
+            authenticated_data,
+            group_info_extensions,
+            next_epoch: None,
+        }
+    }
+
+    /// Number of adds not included in a commit yet.
+    pub fn remaining_adds(&self) -> usize {
+        self.adds.len()
+    }
+
+    /// Build the next commit of the sequence, or return `None` once the last
+    /// commit was applied.
+    ///
+    /// The commit and its welcome messages each encode to at most the size
+    /// limit, and the commit is left pending as by
+    /// [`CommitBuilder::build`](super::CommitBuilder::build). Once the
+    /// delivery service accepted it, calling `next_commit` again applies it before
+    /// building the following commit, unless it was already applied with
+    /// [`Group::apply_pending_commit`] or by processing it. If it was
+    /// rejected, the sequence can be dropped and the pending commit cleared
+    /// with [`Group::clear_pending_commit`]; calling `next_commit` then fails with
+    /// [`MlsError::UnexpectedCommitEpoch`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::CommitChunkTooLarge`] if a commit with a single
+    /// add, or with only the proposals of the first commit, exceeds the size
+    /// limit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn next_commit(&mut self) -> Result<Option<CommitOutput>, MlsError> {
+        if let Some(epoch) = self.next_epoch {
+            if self.group.has_pending_commit() && self.group.current_epoch() + 1 == epoch {
+                self.group.apply_pending_commit().await?;
+            }
+
+            if self.group.current_epoch() != epoch {
+                return Err(MlsError::UnexpectedCommitEpoch(self.group.current_epoch()));
+            }
+        }
+
+        if self.first.is_none() && self.adds.is_empty() {
+            return Ok(None);
+        }
+
+        let min_count = usize::from(self.first.is_none());
+        let mut count = self.adds.len();
+
+        loop {
+            // Attempts are made on a copy so that discarded commits neither
+            // consume keys of the group nor reach storage
+            let mut attempt = self.group.clone();
+            attempt.flush_mode = FlushMode::Manual;
+
+            let output = self.build_chunk(&mut attempt, count).await?;
+
+            let size = iter::once(&output.commit_message)
+                .chain(&output.welcome_messages)
+                .map(|message| message.mls_encoded_len())
+                .max()
+                .unwrap_or_default();
+
+            if size <= self.max_size {
+                attempt.flush_mode = self.group.flush_mode;
+                *self.group = attempt;
+                self.group.state_changed().await?;
+
+                self.adds.drain(..count);
+                self.first = None;
+                self.next_epoch = Some(self.group.current_epoch() + 1);
+
+                return Ok(Some(output));
+            }
+
+            if count <= min_count {
+                return Err(MlsError::CommitChunkTooLarge(size));
+            }
+
+            // Messages grow about linearly with the number of adds
+            count = (count * self.max_size / size).clamp(min_count, count - 1);
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn build_chunk(
+        &self,
+        group: &mut Group<C>,
+        count: usize,
+    ) -> Result<CommitOutput, MlsError> {
+        let mut builder = group
+            .commit_builder()
+            .authenticated_data(self.authenticated_data.clone())
+            .set_group_info_ext(self.group_info_extensions.clone());
+
+        if let Some(first) = &self.first {
+            builder = builder.raw_proposals(first.proposals.clone());
+
+            if let (Some(signer), Some(signing_identity)) =
+                (&first.new_signer, &first.new_signing_identity)
+            {
+                builder =
+                    builder.set_new_signing_identity(signer.clone(), signing_identity.clone());
+            }
+
+            if let Some(extensions) = &first.new_leaf_node_extensions {
+                builder = builder.set_leaf_node_extensions(extensions.clone());
+            }
+        }
+
+        builder
+            .raw_proposals(self.adds[..count].to_vec())
+            .build()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec::Vec};
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsSize;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_n_member_group,
+        key_package::test_utils::test_key_package_message,
+        MlsMessage,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_packages(count: usize) -> Vec<MlsMessage> {
+        let mut key_packages = Vec::new();
+
+        for i in 0..count {
+            let name = format!("member {i}");
+
+            key_packages.push(
+                test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, &name).await,
+            );
+        }
+
+        key_packages
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn adds_are_split_across_commits_within_limit() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let key_packages = key_packages(10).await;
+
+        let mut single = groups[0].clone();
+
+        let single_add = single
+            .commit_builder()
+            .add_member(key_packages[0].clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let single_add_size = single_add
+            .welcome_messages
+            .iter()
+            .chain([&single_add.commit_message])
+            .map(|message| message.mls_encoded_len())
+            .max()
+            .unwrap();
+
+        let max_size = 4 * single_add_size;
+
+        let mut builder = groups[0].commit_builder();
+
+        for key_package in key_packages {
+            builder = builder.add_member(key_package).unwrap();
+        }
+
+        let mut chunked = builder.build_chunked(max_size).unwrap();
+        let mut commits = Vec::new();
+
+        while let Some(output) = chunked.next_commit().await.unwrap() {
+            assert!(output.commit_message.mls_encoded_len() <= max_size);
+
+            for welcome in &output.welcome_messages {
+                assert!(welcome.mls_encoded_len() <= max_size);
+            }
+
+            commits.push(output.commit_message);
+        }
+
+        assert!(commits.len() > 1);
+        assert_eq!(chunked.remaining_adds(), 0);
+
+        for commit in commits {
+            groups[1].process_incoming_message(commit).await.unwrap();
+        }
+
+        assert_eq!(groups[0].roster().members_iter().count(), 12);
+        assert_eq!(groups[0].current_epoch(), groups[1].current_epoch());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn too_small_limit_fails_without_changing_group() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let key_packages = key_packages(2).await;
+
+        let res = groups[0]
+            .commit_builder()
+            .add_member(key_packages[0].clone())
+            .unwrap()
+            .add_member(key_packages[1].clone())
+            .unwrap()
+            .build_chunked(16)
+            .unwrap()
+            .next_commit()
+            .await;
+
+        assert_matches!(res, Err(MlsError::CommitChunkTooLarge(_)));
+        assert!(!groups[0].has_pending_commit());
+    }
+}
@@ -0,0 +1,306 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{ApplicationMessageDescription, Group},
+    MlsMessage,
+};
+
+const STREAM_ID_LEN: usize = 16;
+
+/// Application data of each message of a chunked application message.
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct MessageChunk {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    stream_id: Vec<u8>,
+    index: u32,
+    count: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    data: Vec<u8>,
+    /// Hash of the whole payload, only set in the last chunk.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    stream_tag: Vec<u8>,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct StreamTagInput<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    stream_id: &'a [u8],
+    count: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    payload: &'a [u8],
+}
+
+impl StreamTagInput<'_> {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn hash<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<Vec<u8>, MlsError> {
+        cipher_suite_provider
+            .hash(&self.mls_encode_to_vec()?)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PartialStream {
+    sender_index: u32,
+    stream_id: Vec<u8>,
+    count: u32,
+    chunks: Vec<(u32, Vec<u8>)>,
+    stream_tag: Option<Vec<u8>>,
+}
+
+/// Reassembles the payloads sent with
+/// [`Group::encrypt_application_message_chunked`] from the received
+/// application messages.
+///
+/// Chunks can be received in any order and interleaved with chunks of other
+/// payloads, from the same sender or from other senders.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkedMessageReassembler {
+    streams: Vec<PartialStream>,
+}
+
+impl ChunkedMessageReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of payloads of which some but not all chunks were received.
+    pub fn pending_streams(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Drop the chunks received from the member at `sender_index`, for
+    /// example after the member was removed.
+    pub fn discard_sender(&mut self, sender_index: u32) {
+        self.streams.retain(|s| s.sender_index != sender_index);
+    }
+
+    /// Add a chunk received in `group`, returning the payload once all of its
+    /// chunks were received.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::InvalidMessageChunk`] if the application data is
+    /// not a chunk or is inconsistent with previous chunks of the same
+    /// payload, and [`MlsError::ChunkedMessageIntegrityError`] if the
+    /// reassembled payload does not match the integrity tag of the sender.
+    /// The chunks of the payload are dropped in the latter case.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn receive<C>(
+        &mut self,
+        group: &Group<C>,
+        message: &ApplicationMessageDescription,
+    ) -> Result<Option<Vec<u8>>, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        let chunk = MessageChunk::mls_decode(&mut message.data())
+            .map_err(|_| MlsError::InvalidMessageChunk)?;
+
+        let is_last = chunk.index.checked_add(1) == Some(chunk.count);
+
+        if chunk.index >= chunk.count || is_last == chunk.stream_tag.is_empty() {
+            return Err(MlsError::InvalidMessageChunk);
+        }
+
+        let sender_index = message.sender_index;
+
+        let position = match self
+            .streams
+            .iter()
+            .position(|s| s.sender_index == sender_index && s.stream_id == chunk.stream_id)
+        {
+            Some(position) => position,
+            None => {
+                self.streams.push(PartialStream {
+                    sender_index,
+                    stream_id: chunk.stream_id.clone(),
+                    count: chunk.count,
+                    chunks: Vec::new(),
+                    stream_tag: None,
+                });
+
+                self.streams.len() - 1
+            }
+        };
+
+        let stream = &mut self.streams[position];
+
+        if stream.count != chunk.count || stream.chunks.iter().any(|(i, _)| *i == chunk.index) {
+            return Err(MlsError::InvalidMessageChunk);
+        }
+
+        stream.chunks.push((chunk.index, chunk.data));
+
+        if is_last {
+            stream.stream_tag = Some(chunk.stream_tag);
+        }
+
+        if stream.chunks.len() < stream.count as usize {
+            return Ok(None);
+        }
+
+        let mut stream = self.streams.swap_remove(position);
+        stream.chunks.sort_by_key(|(i, _)| *i);
+
+        let payload = stream
+            .chunks
+            .into_iter()
+            .flat_map(|(_, data)| data)
+            .collect::<Vec<_>>();
+
+        let stream_tag = StreamTagInput {
+            stream_id: &stream.stream_id,
+            count: stream.count,
+            payload: &payload,
+        }
+        .hash(&group.cipher_suite_provider)
+        .await?;
+
+        match stream.stream_tag == Some(stream_tag) {
+            true => Ok(Some(payload)),
+            false => Err(MlsError::ChunkedMessageIntegrityError),
+        }
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt a payload too large for a single message as a sequence of
+    /// application messages, each carrying at most `chunk_size` bytes of the
+    /// payload.
+    ///
+    /// Each message carries the position of its chunk in the sequence, and
+    /// the last one carries a hash of the whole payload, which is checked by
+    /// [`ChunkedMessageReassembler`] once all chunks are received.
+    /// `authenticated_data` is sent with every message.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_application_message_chunked(
+        &mut self,
+        message: &[u8],
+        chunk_size: usize,
+        authenticated_data: Vec<u8>,
+    ) -> Result<Vec<MlsMessage>, MlsError> {
+        let stream_id = self
+            .cipher_suite_provider
+            .random_bytes_vec(STREAM_ID_LEN)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let chunks = match message.is_empty() {
+            true => alloc::vec![message],
+            false => message.chunks(chunk_size.max(1)).collect(),
+        };
+
+        let count = u32::try_from(chunks.len()).map_err(|_| MlsError::InvalidMessageChunk)?;
+
+        let stream_tag = StreamTagInput {
+            stream_id: &stream_id,
+            count,
+            payload: message,
+        }
+        .hash(&self.cipher_suite_provider)
+        .await?;
+
+        let mut messages = Vec::with_capacity(chunks.len());
+
+        for (index, data) in (0..count).zip(chunks) {
+            let chunk = MessageChunk {
+                stream_id: stream_id.clone(),
+                index,
+                count,
+                data: data.to_vec(),
+                stream_tag: match index + 1 == count {
+                    true => stream_tag.clone(),
+                    false => Vec::new(),
+                },
+            };
+
+            messages.push(
+                self.encrypt_application_message(
+                    &chunk.mls_encode_to_vec()?,
+                    authenticated_data.clone(),
+                )
+                .await?,
+            );
+        }
+
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    use super::ChunkedMessageReassembler;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn chunked_message_round_trip() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let payload = (0..10_000u32).map(|i| i as u8).collect::<Vec<_>>();
+
+        let messages = alice
+            .encrypt_application_message_chunked(&payload, 1000, Vec::new())
+            .await
+            .unwrap();
+
+        assert_eq!(messages.len(), 10);
+
+        let mut received = Vec::new();
+
+        for message in messages {
+            let ReceivedMessage::ApplicationMessage(description) =
+                bob.process_incoming_message(message).await.unwrap()
+            else {
+                panic!("expected application message");
+            };
+
+            received.push(description);
+        }
+
+        let mut reassembler = ChunkedMessageReassembler::new();
+
+        // Chunks can arrive in any order
+        let last = received.pop().unwrap();
+        received.insert(3, last);
+
+        for description in &received[..9] {
+            let res = reassembler.receive(&bob, description).await.unwrap();
+            assert!(res.is_none());
+        }
+
+        let duplicate = reassembler.receive(&bob, &received[0]).await;
+        assert_matches!(duplicate, Err(MlsError::InvalidMessageChunk));
+
+        let res = reassembler.receive(&bob, &received[9]).await.unwrap();
+        assert_eq!(res, Some(payload));
+        assert_eq!(reassembler.pending_streams(), 0);
+    }
+}
@@ -13,7 +13,7 @@ use super::{
     framing::{ContentType, FramedContent, Sender, WireFormat},
     message_signature::AuthenticatedContent,
     padding::PaddingMode,
-    secret_tree::{KeyType, MessageKeyData},
+    secret_tree::{KeyRetention, KeyType, MessageKeyData, RatchetDerivation, SkippedKeyStorage},
     GroupContext,
 };
 use crate::{
@@ -26,19 +26,35 @@ use zeroize::Zeroizing;
 
 mod message_key;
 mod reuse_guard;
-mod sender_data_key;
+pub(crate) mod sender_data_key;
 
 #[cfg(feature = "private_message")]
 use super::framing::{PrivateContentAAD, PrivateMessage, PrivateMessageContent};
 
-#[cfg(test)]
-pub use sender_data_key::test_utils::*;
+#[cfg(all(feature = "debug_utils", feature = "private_message"))]
+use super::KeyDeletionAudit;
+
+#[cfg(any(test, feature = "test_vectors"))]
+pub use sender_data_key::test_vectors::SenderDataTestCase;
 
 pub(crate) trait GroupStateProvider {
     fn group_context(&self) -> &GroupContext;
     fn self_index(&self) -> LeafIndex;
     fn epoch_secrets_mut(&mut self) -> &mut EpochSecrets;
     fn epoch_secrets(&self) -> &EpochSecrets;
+
+    fn key_retention(&self) -> KeyRetention {
+        KeyRetention::Standard
+    }
+
+    fn skipped_key_storage(&self) -> SkippedKeyStorage {
+        SkippedKeyStorage::Keys
+    }
+
+    #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+    fn key_deletion_audit_mut(&mut self) -> Option<&mut KeyDeletionAudit> {
+        None
+    }
 }
 
 pub(crate) struct CiphertextProcessor<'a, GS, CP>
@@ -71,12 +87,25 @@ where
         key_type: KeyType,
     ) -> Result<MessageKeyData, MlsError> {
         let self_index = NodeIndex::from(self.group_state.self_index());
+        let derivation = RatchetDerivation::of(self.group_state.group_context());
 
-        self.group_state
+        let key = self
+            .group_state
             .epoch_secrets_mut()
             .secret_tree
-            .next_message_key(&self.cipher_suite_provider, self_index, key_type)
-            .await
+            .next_message_key(
+                &self.cipher_suite_provider,
+                self_index,
+                key_type,
+                derivation,
+            )
+            .await?;
+
+        #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+        self.audit_deleted_key(self.group_state.self_index(), key_type, &key)
+            .await?;
+
+        Ok(key)
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -86,13 +115,70 @@ where
         key_type: KeyType,
         generation: u32,
     ) -> Result<MessageKeyData, MlsError> {
+        #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+        let sender_index = sender;
+
         let sender = NodeIndex::from(sender);
+        let retention = self.group_state.key_retention();
+        let skipped_keys = self.group_state.skipped_key_storage();
+        let derivation = RatchetDerivation::of(self.group_state.group_context());
+        let secret_tree = &mut self.group_state.epoch_secrets_mut().secret_tree;
 
-        self.group_state
-            .epoch_secrets_mut()
-            .secret_tree
-            .message_key_generation(&self.cipher_suite_provider, sender, key_type, generation)
-            .await
+        let key = secret_tree
+            .message_key_generation(
+                &self.cipher_suite_provider,
+                sender,
+                key_type,
+                generation,
+                retention,
+                skipped_keys,
+                derivation,
+            )
+            .await?;
+
+        // Verify that the key can not be derived a second time.
+        #[cfg(debug_assertions)]
+        if retention == KeyRetention::Strict {
+            let rederived = secret_tree
+                .clone()
+                .message_key_generation(
+                    &self.cipher_suite_provider,
+                    sender,
+                    key_type,
+                    generation,
+                    retention,
+                    skipped_keys,
+                    derivation,
+                )
+                .await;
+
+            debug_assert!(rederived.is_err(), "consumed message key was not deleted");
+        }
+
+        #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+        self.audit_deleted_key(sender_index, key_type, &key).await?;
+
+        Ok(key)
+    }
+
+    #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn audit_deleted_key(
+        &mut self,
+        sender: LeafIndex,
+        key_type: KeyType,
+        key: &MessageKeyData,
+    ) -> Result<(), MlsError> {
+        let epoch = self.group_state.group_context().epoch;
+
+        match self.group_state.key_deletion_audit_mut() {
+            Some(audit) => {
+                audit
+                    .record(&self.cipher_suite_provider, epoch, sender, key_type, key)
+                    .await
+            }
+            None => Ok(()),
+        }
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -100,6 +186,7 @@ where
         &mut self,
         auth_content: AuthenticatedContent,
         padding: PaddingMode,
+        hidden_len: usize,
     ) -> Result<PrivateMessage, MlsError> {
         if Sender::Member(*self.group_state.self_index()) != auth_content.content.sender {
             return Err(MlsError::InvalidSender);
@@ -134,8 +221,14 @@ where
 
         let mut serialized_private_content = private_content.mls_encode_to_vec()?;
 
-        // Apply padding to private content based on the current padding mode.
-        serialized_private_content.resize(padding.padded_size(serialized_private_content.len()), 0);
+        // Apply padding to private content based on the current padding mode. The padded size
+        // covers `hidden_len` additional bytes, such as the bytes saved by compression.
+        let padded_size = match padding {
+            PaddingMode::None => serialized_private_content.len(),
+            _ => padding.padded_size(serialized_private_content.len() + hidden_len),
+        };
+
+        serialized_private_content.resize(padded_size, 0);
 
         let serialized_private_content = Zeroizing::new(serialized_private_content);
 
@@ -327,7 +420,7 @@ mod test {
             let mut ciphertext_processor = test_processor(&mut test_data.group, cipher_suite);
 
             let ciphertext = ciphertext_processor
-                .seal(test_data.content.clone(), PaddingMode::StepFunction)
+                .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
                 .await
                 .unwrap();
 
@@ -347,12 +440,12 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let ciphertext_step = ciphertext_processor
-            .seal(test_data.content.clone(), PaddingMode::StepFunction)
+            .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
             .await
             .unwrap();
 
         let ciphertext_no_pad = ciphertext_processor
-            .seal(test_data.content.clone(), PaddingMode::None)
+            .seal(test_data.content.clone(), PaddingMode::None, 0)
             .await
             .unwrap();
 
@@ -367,7 +460,7 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let res = ciphertext_processor
-            .seal(test_data.content, PaddingMode::None)
+            .seal(test_data.content, PaddingMode::None, 0)
             .await;
 
         assert_matches!(res, Err(MlsError::InvalidSender))
@@ -380,7 +473,7 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let ciphertext = ciphertext_processor
-            .seal(test_data.content, PaddingMode::None)
+            .seal(test_data.content, PaddingMode::None, 0)
             .await
             .unwrap();
 
@@ -396,7 +489,7 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let mut ciphertext = ciphertext_processor
-            .seal(test_data.content.clone(), PaddingMode::StepFunction)
+            .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
             .await
             .unwrap();
 
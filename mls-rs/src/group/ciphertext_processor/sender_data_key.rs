@@ -63,7 +63,7 @@ impl<CP: CipherSuiteProvider + Debug> Debug for SenderDataKey<'_, CP> {
 
 impl<'a, CP: CipherSuiteProvider> SenderDataKey<'a, CP> {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub(super) async fn new(
+    pub(crate) async fn new(
         sender_data_secret: &SenderDataSecret,
         ciphertext: &[u8],
         cipher_suite_provider: &'a CP,
@@ -136,15 +136,19 @@ impl<'a, CP: CipherSuiteProvider> SenderDataKey<'a, CP> {
     }
 }
 
-#[cfg(test)]
-pub(crate) mod test_utils {
+#[cfg(any(test, feature = "test_vectors"))]
+pub(crate) mod test_vectors {
     use alloc::vec::Vec;
-    use mls_rs_core::crypto::CipherSuiteProvider;
+    use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+    use crate::client::MlsError;
 
     use super::SenderDataKey;
 
-    #[derive(Debug, serde::Serialize, serde::Deserialize)]
-    pub struct InteropSenderData {
+    /// Sender data key derivation test vector, as embedded in
+    /// `secret-tree.json`.
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    pub struct SenderDataTestCase {
         #[serde(with = "hex::serde")]
         pub sender_data_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
@@ -155,23 +159,30 @@ pub(crate) mod test_utils {
         pub nonce: Vec<u8>,
     }
 
-    impl InteropSenderData {
-        #[cfg(not(mls_build_async))]
-        #[cfg_attr(coverage_nightly, coverage(off))]
-        pub(crate) fn new<P: CipherSuiteProvider>(cs: &P) -> Self {
-            let secret = cs.random_bytes_vec(cs.kdf_extract_size()).unwrap().into();
-            let ciphertext = cs.random_bytes_vec(77).unwrap();
-            let key = SenderDataKey::new(&secret, &ciphertext, cs).unwrap();
-            let secret = (*secret).clone();
+    impl SenderDataTestCase {
+        /// Generate a test case with a random sender data secret and
+        /// ciphertext.
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        pub async fn generate<P: CipherSuiteProvider>(cs: &P) -> Result<Self, MlsError> {
+            let secret = cs
+                .random_bytes_vec(cs.kdf_extract_size())
+                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-            Self {
+            let ciphertext = cs
+                .random_bytes_vec(77)
+                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+            let key = SenderDataKey::new(&secret.clone().into(), &ciphertext, cs).await?;
+
+            Ok(Self {
                 ciphertext,
                 key: key.key.to_vec(),
                 nonce: key.nonce.to_vec(),
                 sender_data_secret: secret,
-            }
+            })
         }
 
+        #[cfg(test)]
         #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
         pub async fn verify<P: CipherSuiteProvider>(&self, cs: &P) {
             let secret = self.sender_data_secret.clone().into();
@@ -13,14 +13,15 @@ use crate::{
     cipher_suite::CipherSuite,
     client::MlsError,
     client_config::ClientConfig,
-    extension::RatchetTreeExt,
-    identity::SigningIdentity,
+    extension::{ExtensionType, RatchetTreeExt},
+    identity::{Credential, SigningIdentity},
     protocol_version::ProtocolVersion,
     signer::Signable,
+    time::MlsTime,
     tree_kem::{
         kem::TreeKem, node::LeafIndex, path_secret::PathSecret, TreeKemPrivate, UpdatePath,
     },
-    ExtensionList, MlsRules,
+    Extension, ExtensionList, MlsRules,
 };
 
 #[cfg(all(not(mls_build_async), feature = "rayon"))]
@@ -28,16 +29,19 @@ use {crate::iter::ParallelIteratorExt, rayon::prelude::*};
 
 use crate::tree_kem::leaf_node::LeafNode;
 
-#[cfg(not(feature = "private_message"))]
 use crate::WireFormat;
 
 #[cfg(feature = "psk")]
 use crate::{
-    group::{JustPreSharedKeyID, PskGroupId, ResumptionPSKUsage, ResumptionPsk},
+    group::{
+        proposal::PreSharedKeyProposal, JustPreSharedKeyID, PskGroupId, ResumptionPSKUsage,
+        ResumptionPsk,
+    },
     psk::ExternalPskId,
 };
 
 use super::{
+    chunked_commit::{ChunkedCommit, FirstChunk},
     confirmation_tag::ConfirmationTag,
     framing::{Content, MlsMessage, MlsMessagePayload, Sender},
     key_schedule::{KeySchedule, WelcomeSecret},
@@ -45,7 +49,12 @@ use super::{
     message_processor::{path_update_required, MessageProcessor},
     message_signature::AuthenticatedContent,
     mls_rules::CommitDirection,
+    padding::GroupInfoPadding,
     proposal::{Proposal, ProposalOrRef},
+    proposal_filter::ProposalBundle,
+    quarantine::QuarantineRules,
+    roster::{member_from_leaf_node, Member},
+    validation_report::SkippedKeyPackage,
     EncryptedGroupSecrets, ExportedTree, Group, GroupContext, GroupInfo, Welcome,
 };
 
@@ -55,6 +64,14 @@ use super::proposal_cache::prepare_commit;
 #[cfg(feature = "custom_proposal")]
 use super::proposal::CustomProposal;
 
+/// Proposals to include in a commit.
+pub(crate) enum CommitProposals {
+    /// All cached proposals followed by the given proposals by value.
+    Cached(Vec<Proposal>),
+    /// Exactly the proposals in the bundle, ignoring the proposal cache.
+    Bundle(ProposalBundle),
+}
+
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(mls_rs_core::arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -91,6 +108,19 @@ impl CommitSecrets {
     }
 }
 
+/// Handling of the cached proposals committed by reference in a pending
+/// commit that was rejected, see [`Group::reject_pending_commit`].
+#[cfg(feature = "by_ref_proposal")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectedCommitProposals {
+    /// Keep the proposals cached, so that they are committed again by the
+    /// next commit.
+    Keep,
+    /// Remove the proposals from the cache, so that they are not committed
+    /// again.
+    Discard,
+}
+
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
@@ -123,6 +153,8 @@ pub struct CommitOutput {
     pub unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
     /// Indicator that the commit contains a path update
     pub contains_update_path: bool,
+    /// Summary of the changes made by the commit.
+    pub summary: CommitSummary,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
@@ -160,6 +192,68 @@ impl CommitOutput {
     pub fn unused_proposals(&self) -> &[crate::mls_rules::ProposalInfo<Proposal>] {
         &self.unused_proposals
     }
+
+    /// Summary of the changes made by the commit.
+    #[cfg(feature = "ffi")]
+    pub fn summary(&self) -> &CommitSummary {
+        &self.summary
+    }
+}
+
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+/// Summary of the changes made by a commit, computed when the commit is built.
+pub struct CommitSummary {
+    /// Members added by the commit, at their leaf index in the new epoch.
+    pub added: Vec<Member>,
+    /// Members removed by the commit, as they were in the current epoch.
+    pub removed: Vec<Member>,
+    /// Pre-shared key proposals applied by the commit.
+    #[cfg(feature = "psk")]
+    pub psks: Vec<PreSharedKeyProposal>,
+    /// Indicator that the commit contains a path update.
+    pub path_update: bool,
+    /// New group context extensions, if the commit changes them.
+    pub group_context_extensions: Option<ExtensionList>,
+    /// Epoch of the group once the commit is applied.
+    pub epoch: u64,
+}
+
+#[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
+impl CommitSummary {
+    /// Members added by the commit, at their leaf index in the new epoch.
+    #[cfg(feature = "ffi")]
+    pub fn added(&self) -> &[Member] {
+        &self.added
+    }
+
+    /// Members removed by the commit, as they were in the current epoch.
+    #[cfg(feature = "ffi")]
+    pub fn removed(&self) -> &[Member] {
+        &self.removed
+    }
+
+    /// Indicator that the commit contains a path update.
+    #[cfg(feature = "ffi")]
+    pub fn path_update(&self) -> bool {
+        self.path_update
+    }
+
+    /// New group context extensions, if the commit changes them.
+    #[cfg(feature = "ffi")]
+    pub fn group_context_extensions(&self) -> Option<&ExtensionList> {
+        self.group_context_extensions.as_ref()
+    }
+
+    /// Epoch of the group once the commit is applied.
+    #[cfg(feature = "ffi")]
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
 }
 
 /// Build a commit with multiple proposals by-value.
@@ -180,6 +274,26 @@ where
     new_signer: Option<SignatureSecretKey>,
     new_signing_identity: Option<SigningIdentity>,
     new_leaf_node_extensions: Option<ExtensionList>,
+    removals: Vec<RemovalTarget>,
+    expected_epoch: Option<u64>,
+    expected_confirmed_hash: Option<Vec<u8>>,
+}
+
+/// Member removed by [`CommitBuilder::remove_member_by_identity`] or
+/// [`CommitBuilder::remove_member_by_credential`], resolved to a leaf index
+/// when the commit is built.
+enum RemovalTarget {
+    SigningIdentity(SigningIdentity),
+    Credential(Credential),
+}
+
+impl RemovalTarget {
+    fn matches(&self, signing_identity: &SigningIdentity) -> bool {
+        match self {
+            RemovalTarget::SigningIdentity(target) => target == signing_identity,
+            RemovalTarget::Credential(target) => *target == signing_identity.credential,
+        }
+    }
 }
 
 impl<'a, C> CommitBuilder<'a, C>
@@ -219,6 +333,32 @@ where
         Ok(self)
     }
 
+    /// Insert a [`RemoveProposal`](crate::group::proposal::RemoveProposal)
+    /// removing the member using `signing_identity` into the current commit
+    /// that is being built.
+    ///
+    /// The leaf index of the member is resolved when the commit is built.
+    /// Building fails with [`MlsError::MemberNotFound`] if no member uses
+    /// `signing_identity` and with [`MlsError::AmbiguousMember`] if more than
+    /// one member does.
+    pub fn remove_member_by_identity(mut self, signing_identity: SigningIdentity) -> Self {
+        self.removals
+            .push(RemovalTarget::SigningIdentity(signing_identity));
+
+        self
+    }
+
+    /// Insert a [`RemoveProposal`](crate::group::proposal::RemoveProposal)
+    /// removing the member using `credential` into the current commit that is
+    /// being built.
+    ///
+    /// The leaf index is resolved in the same way as
+    /// [`CommitBuilder::remove_member_by_identity`].
+    pub fn remove_member_by_credential(mut self, credential: Credential) -> Self {
+        self.removals.push(RemovalTarget::Credential(credential));
+        self
+    }
+
     /// Insert a
     /// [`GroupContextExtensions`](crate::group::proposal::Proposal::GroupContextExtensions)
     /// into the current commit that is being built.
@@ -228,6 +368,45 @@ where
         Ok(self)
     }
 
+    /// Add or replace a single group context extension, keeping all other
+    /// extensions.
+    ///
+    /// Unlike [`CommitBuilder::set_group_context_ext`], the change is merged
+    /// into the extensions of the current epoch, or into the
+    /// [`GroupContextExtensions`](crate::group::proposal::Proposal::GroupContextExtensions)
+    /// proposal already added to this commit.
+    pub fn add_group_context_extension(mut self, extension: Extension) -> Self {
+        self.update_group_context_extensions(|extensions| extensions.set(extension));
+        self
+    }
+
+    /// Remove a single group context extension, keeping all other extensions.
+    ///
+    /// The change is merged in the same way as
+    /// [`CommitBuilder::add_group_context_extension`].
+    pub fn remove_group_context_extension(mut self, extension_type: ExtensionType) -> Self {
+        self.update_group_context_extensions(|extensions| extensions.remove(extension_type));
+        self
+    }
+
+    fn update_group_context_extensions<F: FnOnce(&mut ExtensionList)>(&mut self, update: F) {
+        let existing = self.proposals.iter_mut().find_map(|p| match p {
+            Proposal::GroupContextExtensions(extensions) => Some(extensions),
+            _ => None,
+        });
+
+        match existing {
+            Some(extensions) => update(extensions),
+            None => {
+                let mut extensions = self.group.context().extensions.clone();
+                update(&mut extensions);
+
+                self.proposals
+                    .push(self.group.group_context_extensions_proposal(extensions));
+            }
+        }
+    }
+
     /// Insert a
     /// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal) with
     /// an external PSK into the current commit that is being built.
@@ -256,6 +435,34 @@ where
         Ok(self)
     }
 
+    /// Insert a
+    /// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal) with
+    /// a resumption PSK from epoch `psk_epoch` of the group `psk_group_id`,
+    /// which is either the current group or one of its
+    /// [ancestors](crate::psk::GroupLineage).
+    ///
+    /// An ancestor can only be used up to the epoch in which the next group of
+    /// the lineage was created. Other epochs and unrelated groups result in
+    /// [`MlsError::ResumptionPskOutsideLineage`].
+    #[cfg(feature = "psk")]
+    pub fn add_resumption_psk_from_lineage(
+        mut self,
+        psk_group_id: Vec<u8>,
+        psk_epoch: u64,
+    ) -> Result<Self, MlsError> {
+        let psk_id = self.group.lineage.resumption_psk(
+            self.group.group_id(),
+            self.group.current_epoch(),
+            psk_group_id,
+            psk_epoch,
+        )?;
+
+        let key_id = JustPreSharedKeyID::Resumption(psk_id);
+        let proposal = self.group.psk_proposal(key_id)?;
+        self.proposals.push(proposal);
+        Ok(self)
+    }
+
     /// Insert a [`ReInitProposal`](crate::group::proposal::ReInitProposal) into
     /// the current commit that is being built.
     pub fn reinit(
@@ -336,6 +543,117 @@ where
         }
     }
 
+    /// Fail [`build`](Self::build) with [`MlsError::UnexpectedCommitEpoch`]
+    /// if the group is no longer in `epoch`.
+    ///
+    /// Applications sharing a group between tasks can use this to avoid
+    /// committing on top of a state that was advanced by another task after
+    /// deciding to commit, which would create a fork if both commits are
+    /// sent.
+    pub fn expecting_epoch(self, epoch: u64) -> Self {
+        Self {
+            expected_epoch: Some(epoch),
+            ..self
+        }
+    }
+
+    /// Fail [`build`](Self::build) with
+    /// [`MlsError::UnexpectedConfirmedTranscriptHash`] if the confirmed
+    /// transcript hash of the group is no longer `hash`.
+    ///
+    /// The confirmed transcript hash identifies the current state more
+    /// precisely than [`expecting_epoch`](Self::expecting_epoch), as it
+    /// also differs between forks of the same epoch.
+    pub fn expecting_confirmed_hash(self, hash: Vec<u8>) -> Self {
+        Self {
+            expected_confirmed_hash: Some(hash),
+            ..self
+        }
+    }
+
+    /// Leave out of the commit the members added with
+    /// [`add_member`](Self::add_member) whose key package can't be added to
+    /// the group, for example because it lacks a capability required by the
+    /// group, instead of failing [`build`](Self::build).
+    ///
+    /// Each key package is checked as with
+    /// [`Group::diagnose_key_package`], with the same meaning of `time`, and
+    /// the key packages left out are returned with the report of the issues
+    /// found. As in the commit, the members removed by this builder are
+    /// removed first, the group context extensions set by this builder are
+    /// used, and each key package is checked against the members added
+    /// before it. Proposals received by reference are not taken into
+    /// account.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn skip_incompatible_members(
+        mut self,
+        time: Option<MlsTime>,
+    ) -> (CommitBuilder<'a, C>, Vec<SkippedKeyPackage>) {
+        let all_proposals = core::mem::take(&mut self.proposals);
+        let mut proposals = Vec::with_capacity(all_proposals.len());
+        let mut skipped = Vec::new();
+
+        let new_extensions = all_proposals
+            .iter()
+            .find_map(|p| match p {
+                Proposal::GroupContextExtensions(extensions) => Some(extensions.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.group.context().extensions.clone());
+
+        let removed = all_proposals
+            .iter()
+            .filter_map(|p| match p {
+                Proposal::Remove(remove) => Some(remove.to_remove),
+                _ => None,
+            })
+            .chain(
+                self.removals
+                    .iter()
+                    .filter_map(|target| self.removal_index(target).ok())
+                    .map(LeafIndex),
+            )
+            .collect::<Vec<_>>();
+
+        let mut tree = self.group.state.public_tree.clone();
+
+        if !removed.is_empty() {
+            // An invalid or unresolved removal fails `build` regardless of the
+            // added members.
+            let _ = tree
+                .remove_leaves(
+                    removed,
+                    &self.group.config.identity_provider(),
+                    &self.group.cipher_suite_provider,
+                )
+                .await;
+        }
+
+        for proposal in all_proposals {
+            let Proposal::Add(add) = &proposal else {
+                proposals.push(proposal);
+                continue;
+            };
+
+            let report = self
+                .group
+                .diagnose_new_member(&add.key_package, &mut tree, &new_extensions, time)
+                .await;
+
+            match report.is_ok() {
+                true => proposals.push(proposal),
+                false => skipped.push(SkippedKeyPackage {
+                    key_package: add.key_package.clone(),
+                    report,
+                }),
+            }
+        }
+
+        self.proposals = proposals;
+
+        (self, skipped)
+    }
+
     /// Finalize the commit to send.
     ///
     /// # Errors
@@ -345,21 +663,27 @@ where
     /// MLS RFC, or if they do not pass the custom rules defined by the current
     /// [proposal rules](crate::client_builder::ClientBuilder::mls_rules).
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn build(self) -> Result<CommitOutput, MlsError> {
+    pub async fn build(mut self) -> Result<CommitOutput, MlsError> {
+        self.check_expected_state()?;
+        let proposals = self.resolved_proposals()?;
+
         let (output, pending_commit) = self
             .group
             .commit_internal(
-                self.proposals,
+                CommitProposals::Cached(proposals),
                 None,
                 self.authenticated_data,
                 self.group_info_extensions,
                 self.new_signer,
                 self.new_signing_identity,
                 self.new_leaf_node_extensions,
+                #[cfg(feature = "private_message")]
+                None,
             )
             .await?;
 
         self.group.pending_commit = Some(pending_commit);
+        self.group.state_changed().await?;
 
         Ok(output)
     }
@@ -369,22 +693,104 @@ where
     ///
     /// A detached commit can be applied using `Group::apply_detached_commit`.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn build_detached(self) -> Result<(CommitOutput, CommitSecrets), MlsError> {
+    pub async fn build_detached(mut self) -> Result<(CommitOutput, CommitSecrets), MlsError> {
+        self.check_expected_state()?;
+        let proposals = self.resolved_proposals()?;
+
         let (output, pending_commit) = self
             .group
             .commit_internal(
-                self.proposals,
+                CommitProposals::Cached(proposals),
                 None,
                 self.authenticated_data,
                 self.group_info_extensions,
                 self.new_signer,
                 self.new_signing_identity,
                 self.new_leaf_node_extensions,
+                #[cfg(feature = "private_message")]
+                None,
             )
             .await?;
 
+        self.group.state_changed().await?;
+
         Ok((output, CommitSecrets(pending_commit)))
     }
+
+    /// Split the commit into a sequence of commits whose messages encode to
+    /// at most `max_size` bytes each, for adding more members than fit in a
+    /// single message of the delivery service. See [`ChunkedCommit`].
+    ///
+    /// No commit is created until [`ChunkedCommit::next_commit`] is called.
+    pub fn build_chunked(mut self, max_size: usize) -> Result<ChunkedCommit<'a, C>, MlsError> {
+        self.check_expected_state()?;
+
+        let (adds, proposals) = self
+            .resolved_proposals()?
+            .into_iter()
+            .partition(|proposal| matches!(proposal, Proposal::Add(_)));
+
+        let first = FirstChunk {
+            proposals,
+            new_signer: self.new_signer,
+            new_signing_identity: self.new_signing_identity,
+            new_leaf_node_extensions: self.new_leaf_node_extensions,
+        };
+
+        Ok(ChunkedCommit::new(
+            self.group,
+            max_size,
+            adds,
+            first,
+            self.authenticated_data,
+            self.group_info_extensions,
+        ))
+    }
+
+    fn check_expected_state(&self) -> Result<(), MlsError> {
+        let context = self.group.context();
+
+        if matches!(self.expected_epoch, Some(epoch) if epoch != context.epoch) {
+            return Err(MlsError::UnexpectedCommitEpoch(context.epoch));
+        }
+
+        if matches!(&self.expected_confirmed_hash, Some(hash) if **context.confirmed_transcript_hash != *hash)
+        {
+            return Err(MlsError::UnexpectedConfirmedTranscriptHash);
+        }
+
+        Ok(())
+    }
+
+    fn resolved_proposals(&mut self) -> Result<Vec<Proposal>, MlsError> {
+        let mut proposals = core::mem::take(&mut self.proposals);
+
+        for target in &self.removals {
+            let index = self.removal_index(target)?;
+            proposals.push(self.group.remove_proposal(index)?);
+        }
+
+        Ok(proposals)
+    }
+
+    fn removal_index(&self, target: &RemovalTarget) -> Result<u32, MlsError> {
+        let mut matching = self
+            .group
+            .state
+            .public_tree
+            .nodes
+            .non_empty_leaves()
+            .filter(|(_, leaf)| target.matches(&leaf.signing_identity))
+            .map(|(index, _)| *index);
+
+        let index = matching.next().ok_or(MlsError::MemberNotFound)?;
+
+        if matching.next().is_some() {
+            return Err(MlsError::AmbiguousMember);
+        }
+
+        Ok(index)
+    }
 }
 
 impl<C> Group<C>
@@ -454,6 +860,43 @@ where
             .await
     }
 
+    /// Commit exactly the proposals in `bundle`, without including the
+    /// proposals cached by this group in the current epoch.
+    ///
+    /// This supports architectures where the delivery service decides the
+    /// content of each commit, for example by assembling a bundle from the
+    /// external proposals it received. Proposals by reference must be known
+    /// to all members, including this one, and proposals by value must be
+    /// sent by this member. The bundle is still validated according to the
+    /// RFC and filtered by the current
+    /// [proposal rules](crate::client_builder::ClientBuilder::mls_rules).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn commit_bundle(
+        &mut self,
+        bundle: ProposalBundle,
+        authenticated_data: Vec<u8>,
+    ) -> Result<CommitOutput, MlsError> {
+        let (output, pending_commit) = self
+            .commit_internal(
+                CommitProposals::Bundle(bundle),
+                None,
+                authenticated_data,
+                Default::default(),
+                None,
+                None,
+                None,
+                #[cfg(feature = "private_message")]
+                None,
+            )
+            .await?;
+
+        self.pending_commit = Some(pending_commit);
+        self.state_changed().await?;
+
+        Ok(output)
+    }
+
     /// Create a new commit builder that can include proposals
     /// by-value.
     pub fn commit_builder(&mut self) -> CommitBuilder<C> {
@@ -465,6 +908,9 @@ where
             new_signer: Default::default(),
             new_signing_identity: Default::default(),
             new_leaf_node_extensions: Default::default(),
+            removals: Default::default(),
+            expected_epoch: None,
+            expected_confirmed_hash: None,
         }
     }
 
@@ -474,13 +920,14 @@ where
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(super) async fn commit_internal(
         &mut self,
-        proposals: Vec<Proposal>,
+        proposals: CommitProposals,
         external_leaf: Option<&LeafNode>,
         authenticated_data: Vec<u8>,
         mut welcome_group_info_extensions: ExtensionList,
         new_signer: Option<SignatureSecretKey>,
         new_signing_identity: Option<SigningIdentity>,
         new_leaf_node_extensions: Option<ExtensionList>,
+        #[cfg(feature = "private_message")] wire_format: Option<WireFormat>,
     ) -> Result<(CommitOutput, CommitGeneration), MlsError> {
         if self.pending_commit.is_some() {
             return Err(MlsError::ExistingPendingCommit);
@@ -490,7 +937,7 @@ where
             return Err(MlsError::GroupUsedAfterReInit);
         }
 
-        let mls_rules = self.config.mls_rules();
+        let mls_rules = QuarantineRules::new(self.config.mls_rules(), &self.quarantine);
 
         let is_external = external_leaf.is_some();
 
@@ -512,11 +959,30 @@ where
         #[cfg(not(feature = "std"))]
         let time = None;
 
-        #[cfg(feature = "by_ref_proposal")]
-        let proposals = self.state.proposals.prepare_commit(sender, proposals);
+        let proposals = match proposals {
+            #[cfg(feature = "by_ref_proposal")]
+            CommitProposals::Cached(proposals) => {
+                self.state.proposals.prepare_commit(sender, proposals)
+            }
+            #[cfg(not(feature = "by_ref_proposal"))]
+            CommitProposals::Cached(proposals) => prepare_commit(sender, proposals),
+            CommitProposals::Bundle(bundle) => {
+                // Proposals by value are attributed to the committer by receivers
+                let other_sender = bundle
+                    .iter_proposals()
+                    .any(|p| !p.is_by_reference() && p.sender != sender);
+
+                if other_sender {
+                    return Err(MlsError::InvalidSender);
+                }
 
-        #[cfg(not(feature = "by_ref_proposal"))]
-        let proposals = prepare_commit(sender, proposals);
+                bundle
+            }
+        };
+
+        if !is_external {
+            self.check_receive_only_commit(&proposals, &authenticated_data)?;
+        }
 
         let mut provisional_state = self
             .state
@@ -533,6 +999,8 @@ where
             )
             .await?;
 
+        self.check_unknown_extensions(&provisional_state, external_leaf)?;
+
         let (mut provisional_private_tree, _) =
             self.provisional_private_tree(&provisional_state)?;
 
@@ -558,7 +1026,11 @@ where
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
 
         let perform_path_update = commit_options.path_required
-            || path_update_required(&provisional_state.applied_proposals);
+            || path_update_required(&provisional_state.applied_proposals)
+            || self.commit_path_policy_requires_path(
+                &provisional_state.public_tree,
+                provisional_private_tree.self_index,
+            )?;
 
         let (update_path, path_secrets, commit_secret) = if perform_path_update {
             // If populating the path field: Create an UpdatePath using the new tree. Any new
@@ -624,6 +1096,13 @@ where
         #[cfg(not(feature = "psk"))]
         let psk_secret = self.get_psk();
 
+        let summary = self.commit_summary(
+            &provisional_state.applied_proposals,
+            &provisional_state.indexes_of_added_kpkgs,
+            perform_path_update,
+            provisional_group_context.epoch,
+        )?;
+
         let added_key_pkgs: Vec<_> = provisional_state
             .applied_proposals
             .additions
@@ -643,7 +1122,10 @@ where
             Content::Commit(Box::new(commit)),
             old_signer,
             #[cfg(feature = "private_message")]
-            self.encryption_options()?.control_wire_format(sender),
+            match wire_format {
+                Some(wire_format) => wire_format,
+                None => self.encryption_options()?.commit_wire_format(sender),
+            },
             #[cfg(not(feature = "private_message"))]
             WireFormat::PublicMessage,
             authenticated_data,
@@ -669,6 +1151,7 @@ where
             self.state.public_tree.total_leaf_count(),
             &psk_secret,
             &self.cipher_suite_provider,
+            &self.config.epoch_secrets_vault(),
         )
         .await?;
 
@@ -687,6 +1170,10 @@ where
                 tree_data: ExportedTree::new(provisional_state.public_tree.nodes.clone()),
             });
 
+        let group_info_padding = commit_options
+            .group_info_padding
+            .negotiated(&provisional_group_context)?;
+
         // Generate external commit group info if required by commit_options
         let external_commit_group_info = match commit_options.allow_external_commit {
             true => {
@@ -695,7 +1182,10 @@ where
                 extensions.set_from({
                     key_schedule_result
                         .key_schedule
-                        .get_external_key_pair_ext(&self.cipher_suite_provider)
+                        .get_external_key_pair_ext(
+                            &self.cipher_suite_provider,
+                            &self.config.epoch_secrets_vault(),
+                        )
                         .await?
                 })?;
 
@@ -709,6 +1199,7 @@ where
                         extensions,
                         &confirmation_tag,
                         new_signer_ref,
+                        group_info_padding,
                     )
                     .await?;
 
@@ -732,6 +1223,7 @@ where
                 welcome_group_info_extensions,
                 &confirmation_tag,
                 new_signer_ref,
+                group_info_padding,
             )
             .await?;
 
@@ -795,6 +1287,11 @@ where
 
         let welcome_messages =
             if commit_options.single_welcome_message && !encrypted_path_secrets.is_empty() {
+                let mut encrypted_path_secrets = encrypted_path_secrets;
+
+                group_info_padding
+                    .pad_group_secrets(&self.cipher_suite_provider, &mut encrypted_path_secrets)?;
+
                 vec![self.make_welcome_message(encrypted_path_secrets, encrypted_group_info)]
             } else {
                 encrypted_path_secrets
@@ -803,6 +1300,13 @@ where
                     .collect()
             };
 
+        // The key schedule of the new epoch is derived again when the commit
+        // is applied.
+        key_schedule_result
+            .key_schedule
+            .release(&self.config.epoch_secrets_vault())
+            .await?;
+
         let commit_message = self.format_for_wire(auth_content.clone()).await?;
 
         let pending_commit = CommitGeneration {
@@ -828,11 +1332,50 @@ where
             contains_update_path: perform_path_update,
             #[cfg(feature = "by_ref_proposal")]
             unused_proposals: provisional_state.unused_proposals,
+            summary,
         };
 
         Ok((output, pending_commit))
     }
 
+    fn commit_summary(
+        &self,
+        proposals: &ProposalBundle,
+        indexes_of_added_kpkgs: &[LeafIndex],
+        path_update: bool,
+        epoch: u64,
+    ) -> Result<CommitSummary, MlsError> {
+        let added = proposals
+            .additions
+            .iter()
+            .zip(indexes_of_added_kpkgs)
+            .map(|(p, &index)| member_from_leaf_node(&p.proposal.key_package.leaf_node, index))
+            .collect();
+
+        let removed = proposals
+            .removals
+            .iter()
+            .map(|p| {
+                let index = p.proposal.to_remove;
+                let leaf_node = self.state.public_tree.get_leaf_node(index)?;
+                Ok(member_from_leaf_node(leaf_node, index))
+            })
+            .collect::<Result<_, MlsError>>()?;
+
+        Ok(CommitSummary {
+            added,
+            removed,
+            #[cfg(feature = "psk")]
+            psks: proposals.psks.iter().map(|p| p.proposal.clone()).collect(),
+            path_update,
+            group_context_extensions: proposals
+                .group_context_extensions
+                .last()
+                .map(|p| p.proposal.clone()),
+            epoch,
+        })
+    }
+
     // Construct a GroupInfo reflecting the new state
     // Group ID, epoch, tree, and confirmed transcript hash from the new state
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -842,6 +1385,7 @@ where
         extensions: ExtensionList,
         confirmation_tag: &ConfirmationTag,
         signer: &SignatureSecretKey,
+        padding: GroupInfoPadding,
     ) -> Result<GroupInfo, MlsError> {
         let mut group_info = GroupInfo {
             group_context: group_context.clone(),
@@ -851,7 +1395,11 @@ where
             signature: vec![],
         };
 
-        group_info.grease(self.cipher_suite_provider())?;
+        if self.config.grease() {
+            group_info.grease(self.cipher_suite_provider())?;
+        }
+
+        padding.pad(&mut group_info);
 
         // Sign the GroupInfo using the member's private signing key
         group_info
@@ -906,16 +1454,19 @@ pub(crate) mod test_utils {
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use mls_rs_core::{
         error::IntoAnyError,
-        extension::ExtensionType,
+        extension::{ExtensionType, MlsExtension},
         identity::{CredentialType, IdentityProvider, MemberValidationContext},
         time::MlsTime,
     };
 
     use crate::extension::RequiredCapabilitiesExt;
     use crate::{
-        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client::test_utils::{
+            test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+        },
         client_builder::{
             test_utils::TestClientConfig, BaseConfig, ClientBuilder, WithCryptoProvider,
             WithIdentityProvider,
@@ -923,7 +1474,7 @@ mod tests {
         client_config::ClientConfig,
         crypto::test_utils::TestCryptoProvider,
         extension::test_utils::{TestExtension, TEST_EXTENSION_TYPE},
-        group::test_utils::{test_group, test_group_custom},
+        group::test_utils::{test_group, test_group_custom, TEST_GROUP},
         group::{
             proposal::ProposalType,
             test_utils::{test_group_custom_config, test_n_member_group},
@@ -941,6 +1492,8 @@ mod tests {
     use crate::extension::ExternalSendersExt;
     #[cfg(feature = "by_ref_proposal")]
     use crate::group::mls_rules::DefaultMlsRules;
+    #[cfg(feature = "by_ref_proposal")]
+    use crate::group::proposal_filter::ProposalSource;
 
     #[cfg(feature = "psk")]
     use crate::{
@@ -1096,6 +1649,111 @@ mod tests {
         assert_commit_builder_output(group, commit_output, vec![expected_remove], 0);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_commit_builder_remove_by_identity() {
+        let mut group = test_commit_builder_group().await;
+        let test_key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        group
+            .commit_builder()
+            .add_member(test_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        let signing_identity = group.member_at_index(1).unwrap().signing_identity;
+
+        let commit_output = group
+            .commit_builder()
+            .remove_member_by_identity(signing_identity)
+            .build()
+            .await
+            .unwrap();
+
+        let expected_remove = group.remove_proposal(1).unwrap();
+
+        assert_commit_builder_output(group, commit_output, vec![expected_remove], 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_commit_builder_remove_by_unknown_credential() {
+        let mut group = test_commit_builder_group().await;
+
+        let res = group
+            .commit_builder()
+            .remove_member_by_credential(get_test_basic_credential(b"carol".to_vec()))
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::MemberNotFound));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_commit_builder_expected_state() {
+        let mut group = test_commit_builder_group().await;
+
+        let epoch = group.current_epoch();
+        let hash = group.context().confirmed_transcript_hash.to_vec();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        let res = group.commit_builder().expecting_epoch(epoch).build().await;
+        assert_matches!(res, Err(MlsError::UnexpectedCommitEpoch(e)) if e == epoch + 1);
+        assert!(!group.has_pending_commit());
+
+        let res = group
+            .commit_builder()
+            .expecting_confirmed_hash(hash)
+            .build_detached()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnexpectedConfirmedTranscriptHash));
+
+        let hash = group.context().confirmed_transcript_hash.to_vec();
+
+        group
+            .commit_builder()
+            .expecting_epoch(epoch + 1)
+            .expecting_confirmed_hash(hash)
+            .build()
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_bundle_ignores_cached_proposals() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let proposal = bob.propose_remove(0, vec![]).await.unwrap();
+        alice.process_incoming_message(proposal).await.unwrap();
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let add = alice.add_proposal(key_package).unwrap();
+
+        let mut bundle = ProposalBundle::default();
+        bundle.add(add.clone(), Sender::Member(1), ProposalSource::ByValue);
+
+        let res = alice.commit_bundle(bundle, vec![]).await;
+        assert_matches!(res, Err(MlsError::InvalidSender));
+
+        let mut bundle = ProposalBundle::default();
+        bundle.add(add.clone(), Sender::Member(0), ProposalSource::ByValue);
+
+        let commit_output = alice.commit_bundle(bundle, vec![]).await.unwrap();
+
+        assert_commit_builder_output(alice.group, commit_output, vec![add], 1);
+    }
+
     #[cfg(feature = "psk")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_commit_builder_psk() {
@@ -1142,6 +1800,94 @@ mod tests {
         assert_commit_builder_output(group, commit_output, vec![expected_ext], 0);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_commit_builder_merges_group_context_extension_changes() {
+        let mut group = test_commit_builder_group().await;
+
+        let required_capabilities = RequiredCapabilitiesExt::default().into_extension().unwrap();
+
+        group
+            .commit_builder()
+            .add_group_context_extension(required_capabilities.clone())
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        let other = Extension::new(ExtensionType::new(42), vec![1]);
+
+        let commit_output = group
+            .commit_builder()
+            .add_group_context_extension(other.clone())
+            .remove_group_context_extension(ExtensionType::EXTERNAL_SENDERS)
+            .build()
+            .await
+            .unwrap();
+
+        let expected_ext = vec![required_capabilities, other].into();
+        let expected_ext = group.group_context_extensions_proposal(expected_ext);
+
+        assert_commit_builder_output(group, commit_output, vec![expected_ext], 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_output_summarizes_changes() {
+        let mut group = test_commit_builder_group().await;
+
+        let bob_key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        group
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        let bob = group.roster().member_with_index(1).unwrap();
+
+        let carol_key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let mut extensions = ExtensionList::default();
+
+        extensions
+            .set_from(RequiredCapabilitiesExt::default())
+            .unwrap();
+
+        let summary = group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .add_member(carol_key_package)
+            .unwrap()
+            .set_group_context_ext(extensions.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .summary;
+
+        assert_eq!(summary.removed, vec![bob]);
+        assert_eq!(summary.added.len(), 1);
+        assert_eq!(summary.added[0].index, 1);
+
+        assert_eq!(
+            summary.added[0].signing_identity.credential,
+            get_test_basic_credential(b"carol".to_vec())
+        );
+
+        assert!(summary.path_update);
+        assert_eq!(summary.group_context_extensions, Some(extensions));
+
+        group.apply_pending_commit().await.unwrap();
+        assert_eq!(summary.epoch, group.current_epoch());
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_commit_builder_reinit() {
         let mut group = test_commit_builder_group().await;
@@ -1655,4 +2401,116 @@ mod tests {
         group.apply_detached_commit(secrets).await.unwrap();
         assert_eq!(group.context().epoch, 1);
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn incompatible_members_are_skipped() {
+        let required = RequiredCapabilitiesExt {
+            extensions: vec![43.into()],
+            ..Default::default()
+        };
+
+        let mut extensions = ExtensionList::new();
+        extensions.set_from(required).unwrap();
+
+        let alice = TestClientBuilder::new_for_test()
+            .extension_type(43.into())
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let mut group = alice
+            .create_group_with_id(TEST_GROUP.to_vec(), extensions, Default::default())
+            .await
+            .unwrap();
+
+        let (_, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let carol_key_package = TestClientBuilder::new_for_test()
+            .extension_type(43.into())
+            .with_random_signing_identity("carol", TEST_CIPHER_SUITE)
+            .await
+            .build()
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (builder, skipped) = group
+            .commit_builder()
+            .add_member(bob_key_package.clone())
+            .unwrap()
+            .add_member(carol_key_package)
+            .unwrap()
+            .skip_incompatible_members(None)
+            .await;
+
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(
+            &skipped[0].key_package,
+            bob_key_package.as_key_package().unwrap()
+        );
+
+        assert_matches!(
+            skipped[0].report.issues.as_slice(),
+            [crate::group::ValidationIssue::MissingCapability { .. }]
+        );
+
+        let output = builder.build().await.unwrap();
+        assert_eq!(output.summary.added.len(), 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_added_by_the_same_builder_are_checked_together() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let bob_other_key_package = bob
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (builder, skipped) = group
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .add_member(bob_other_key_package.clone())
+            .unwrap()
+            .skip_incompatible_members(None)
+            .await;
+
+        assert_eq!(skipped.len(), 1);
+
+        assert_eq!(
+            &skipped[0].key_package,
+            bob_other_key_package.as_key_package().unwrap()
+        );
+
+        assert_matches!(
+            skipped[0].report.issues.as_slice(),
+            [crate::group::ValidationIssue::DuplicateMember(1)]
+        );
+
+        let output = builder.build().await.unwrap();
+        assert_eq!(output.summary.added.len(), 1);
+        group.apply_pending_commit().await.unwrap();
+
+        // A member removed by the same builder can be replaced.
+        let bob_identity = group.member_at_index(1).unwrap().signing_identity.clone();
+
+        let (builder, skipped) = group
+            .commit_builder()
+            .remove_member_by_identity(bob_identity)
+            .add_member(bob_other_key_package)
+            .unwrap()
+            .skip_incompatible_members(None)
+            .await;
+
+        assert!(skipped.is_empty());
+
+        let output = builder.build().await.unwrap();
+        assert_eq!(output.summary.added.len(), 1);
+        assert_eq!(output.summary.removed.len(), 1);
+    }
 }
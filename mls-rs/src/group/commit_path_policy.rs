@@ -0,0 +1,211 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::Group,
+    tree_kem::{node::LeafIndex, TreeKemPublic},
+};
+
+/// Health of the filtered direct path of a member in the ratchet tree.
+///
+/// Blank nodes and unmerged leaves enlarge the resolution of the nodes on
+/// the path, and therefore the number of HPKE encryptions needed by the next
+/// commits with a path. Both are cleared by a commit of the member that
+/// includes a path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PathHealth {
+    /// Number of nodes on the filtered direct path.
+    pub path_length: u32,
+    /// Number of blank nodes on the filtered direct path.
+    pub blank_nodes: u32,
+    /// Number of unmerged leaves of the nodes on the filtered direct path.
+    pub unmerged_leaves: u32,
+}
+
+impl PathHealth {
+    pub(crate) fn of(tree: &TreeKemPublic, index: LeafIndex) -> Result<Self, MlsError> {
+        let mut health = PathHealth {
+            path_length: 0,
+            blank_nodes: 0,
+            unmerged_leaves: 0,
+        };
+
+        for node in tree.nodes.direct_copath(index) {
+            if tree.nodes.is_resolution_empty(node.copath) {
+                continue;
+            }
+
+            health.path_length += 1;
+
+            match tree.nodes.borrow_node(node.path)? {
+                Some(_) => {
+                    let parent = tree.nodes.borrow_as_parent(node.path)?;
+                    health.unmerged_leaves += parent.unmerged_leaves.len() as u32;
+                }
+                None => health.blank_nodes += 1,
+            }
+        }
+
+        Ok(health)
+    }
+}
+
+/// Policy upgrading commits that don't require a path to commits with a
+/// path when the direct path of the committer is in poor health. See
+/// [`PathHealth`].
+///
+/// The policy only ever adds a path; a path required by the proposals or by
+/// [`CommitOptions::path_required`](crate::mls_rules::CommitOptions::path_required)
+/// is always included.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct CommitPathPolicy {
+    /// Number of blank nodes above which a path is included.
+    pub max_blank_nodes: Option<u32>,
+    /// Number of unmerged leaves above which a path is included.
+    pub max_unmerged_leaves: Option<u32>,
+}
+
+impl CommitPathPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_blank_nodes(self, max_blank_nodes: u32) -> Self {
+        Self {
+            max_blank_nodes: Some(max_blank_nodes),
+            ..self
+        }
+    }
+
+    pub fn with_max_unmerged_leaves(self, max_unmerged_leaves: u32) -> Self {
+        Self {
+            max_unmerged_leaves: Some(max_unmerged_leaves),
+            ..self
+        }
+    }
+
+    /// Whether a commit by a member with a direct path of the given health
+    /// should include a path.
+    pub fn requires_path(&self, health: &PathHealth) -> bool {
+        let exceeds = |max: Option<u32>, value: u32| max.map_or(false, |max| value > max);
+
+        exceeds(self.max_blank_nodes, health.blank_nodes)
+            || exceeds(self.max_unmerged_leaves, health.unmerged_leaves)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Health of the direct path of the local member in the current epoch.
+    pub fn path_health(&self) -> Result<PathHealth, MlsError> {
+        PathHealth::of(self.current_epoch_tree(), self.private_tree.self_index)
+    }
+
+    /// Whether the [`CommitPathPolicy`] of this group requires a commit by
+    /// the member at `committer` to include a path, given the tree resulting
+    /// from the proposals of the commit.
+    pub(crate) fn commit_path_policy_requires_path(
+        &self,
+        tree: &TreeKemPublic,
+        committer: LeafIndex,
+    ) -> Result<bool, MlsError> {
+        let Some(policy) = &self.config_overrides.commit_path_policy else {
+            return Ok(false);
+        };
+
+        Ok(policy.requires_path(&PathHealth::of(tree, committer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::{
+            test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+        },
+        client_config::ClientConfig,
+        group::{Group, GroupConfigOverrides},
+    };
+
+    use super::CommitPathPolicy;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn add_member<C: ClientConfig + Clone>(group: &mut Group<C>, name: &str) -> bool {
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+        let commit = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        commit.contains_update_path
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn degraded_path_upgrades_commit_to_path_commit() {
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let overrides = GroupConfigOverrides::new()
+            .with_commit_path_policy(CommitPathPolicy::new().with_max_blank_nodes(1));
+
+        let mut group = client
+            .create_group_with_overrides(None, Default::default(), Default::default(), overrides)
+            .await
+            .unwrap();
+
+        let contains_update_path = add_member(&mut group, "bob").await;
+        assert!(!contains_update_path);
+
+        let health = group.path_health().unwrap();
+        assert_eq!(health.path_length, 1);
+        assert_eq!(health.blank_nodes, 1);
+
+        let contains_update_path = add_member(&mut group, "carol").await;
+        assert!(contains_update_path);
+
+        let health = group.path_health().unwrap();
+        assert_eq!(health.path_length, 2);
+        assert_eq!(health.blank_nodes, 0);
+        assert_eq!(health.unmerged_leaves, 0);
+
+        let contains_update_path = add_member(&mut group, "dave").await;
+        assert!(!contains_update_path);
+
+        let health = group.path_health().unwrap();
+        assert_eq!(health.blank_nodes, 0);
+        assert_eq!(health.unmerged_leaves, 1);
+    }
+
+    #[test]
+    fn policy_thresholds_are_exclusive() {
+        let policy = CommitPathPolicy::new().with_max_unmerged_leaves(2);
+
+        let health = |unmerged_leaves| super::PathHealth {
+            path_length: 3,
+            blank_nodes: 3,
+            unmerged_leaves,
+        };
+
+        assert!(!policy.requires_path(&health(2)));
+        assert!(policy.requires_path(&health(3)));
+    }
+}
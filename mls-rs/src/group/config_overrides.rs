@@ -0,0 +1,298 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client_config::ClientConfig,
+    group::{CommitPathPolicy, FutureEpochBufferConfig, Group, ReceiveOnlyPolicy},
+};
+
+#[cfg(feature = "prior_epoch")]
+use crate::group::EpochRetentionPolicy;
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::LivenessPolicy;
+
+#[cfg(feature = "private_message")]
+use crate::group::{mls_rules::EncryptionOptions, padding::PaddingMode, UnconfirmedMemberPolicy};
+
+/// Configuration of a single group that takes precedence over the
+/// configuration of the client.
+///
+/// Overrides are set when creating or joining a group with
+/// [`Client::create_group_with_overrides`](crate::Client::create_group_with_overrides)
+/// or [`Client::join_group_with_overrides`](crate::Client::join_group_with_overrides)
+/// and are stored with the group state. Settings that are not overridden
+/// follow the client configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct GroupConfigOverrides {
+    /// Padding used for encrypted messages instead of the one returned by
+    /// [`MlsRules::encryption_options`](crate::MlsRules::encryption_options).
+    #[cfg(feature = "private_message")]
+    pub padding_mode: Option<PaddingMode>,
+    /// Whether control messages are encrypted, instead of the setting returned
+    /// by [`MlsRules::encryption_options`](crate::MlsRules::encryption_options).
+    /// This also replaces any
+    /// [`ControlWireFormatPolicy`](crate::mls_rules::ControlWireFormatPolicy).
+    #[cfg(feature = "private_message")]
+    #[mls_codec(with = "optional_bool")]
+    pub encrypt_control_messages: Option<bool>,
+    /// Number of past epochs from which messages can be decrypted. The secret
+    /// tree of older epochs is erased as the group advances, even if the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) retains them longer.
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    pub max_epoch_retention: Option<u64>,
+    /// Buffering of messages received before the commit creating their
+    /// epoch. Such messages are rejected if not set.
+    pub future_epoch_buffer: Option<FutureEpochBufferConfig>,
+    /// Blocking of application messages while added members are
+    /// unconfirmed. Application messages are never blocked if not set.
+    #[cfg(feature = "private_message")]
+    pub unconfirmed_member_policy: Option<UnconfirmedMemberPolicy>,
+    /// Receive-only mode of the local member. The member can send messages
+    /// if not set.
+    pub receive_only: Option<ReceiveOnlyPolicy>,
+    /// Deletion of past epochs from the
+    /// [`GroupStateStorage`](crate::GroupStateStorage). Past epochs are only
+    /// deleted by the storage itself if not set.
+    #[cfg(feature = "prior_epoch")]
+    pub epoch_retention: Option<EpochRetentionPolicy>,
+    /// Inclusion of a path in commits when the direct path of the local
+    /// member degrades. A path is only included when required otherwise if
+    /// not set.
+    pub commit_path_policy: Option<CommitPathPolicy>,
+    /// Cadence of the liveness updates of the local member. A liveness
+    /// update is never due if not set.
+    #[cfg(feature = "by_ref_proposal")]
+    pub liveness: Option<LivenessPolicy>,
+}
+
+impl GroupConfigOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "private_message")]
+    pub fn with_padding_mode(self, padding_mode: PaddingMode) -> Self {
+        Self {
+            padding_mode: Some(padding_mode),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "private_message")]
+    pub fn with_control_message_encryption(self, encrypt_control_messages: bool) -> Self {
+        Self {
+            encrypt_control_messages: Some(encrypt_control_messages),
+            ..self
+        }
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    pub fn with_max_epoch_retention(self, max_epoch_retention: u64) -> Self {
+        Self {
+            max_epoch_retention: Some(max_epoch_retention),
+            ..self
+        }
+    }
+
+    pub fn with_future_epoch_buffer(self, future_epoch_buffer: FutureEpochBufferConfig) -> Self {
+        Self {
+            future_epoch_buffer: Some(future_epoch_buffer),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "private_message")]
+    pub fn with_unconfirmed_member_policy(
+        self,
+        unconfirmed_member_policy: UnconfirmedMemberPolicy,
+    ) -> Self {
+        Self {
+            unconfirmed_member_policy: Some(unconfirmed_member_policy),
+            ..self
+        }
+    }
+
+    pub fn with_receive_only(self, receive_only: ReceiveOnlyPolicy) -> Self {
+        Self {
+            receive_only: Some(receive_only),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "prior_epoch")]
+    pub fn with_epoch_retention(self, epoch_retention: EpochRetentionPolicy) -> Self {
+        Self {
+            epoch_retention: Some(epoch_retention),
+            ..self
+        }
+    }
+
+    pub fn with_commit_path_policy(self, commit_path_policy: CommitPathPolicy) -> Self {
+        Self {
+            commit_path_policy: Some(commit_path_policy),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn with_liveness_policy(self, liveness: LivenessPolicy) -> Self {
+        Self {
+            liveness: Some(liveness),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "private_message")]
+    pub(crate) fn apply_to(&self, options: EncryptionOptions) -> EncryptionOptions {
+        let options = match self.padding_mode {
+            Some(padding_mode) => EncryptionOptions {
+                padding_mode,
+                ..options
+            },
+            None => options,
+        };
+
+        match self.encrypt_control_messages {
+            Some(encrypt_control_messages) => EncryptionOptions {
+                encrypt_control_messages,
+                control_wire_format_policy: None,
+                ..options
+            },
+            None => options,
+        }
+    }
+
+    /// Whether messages from `epoch_id` may be decrypted in `current_epoch`.
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    pub(crate) fn epoch_retained(&self, epoch_id: u64, current_epoch: u64) -> bool {
+        self.max_epoch_retention
+            .map_or(true, |max| current_epoch.saturating_sub(epoch_id) <= max)
+    }
+}
+
+// Booleans are encoded as a single byte, as in the TLS presentation language
+#[cfg(feature = "private_message")]
+mod optional_bool {
+    use alloc::vec::Vec;
+    use mls_rs_codec::{Error, MlsDecode, MlsEncode, MlsSize};
+
+    pub fn mls_encoded_len(value: &Option<bool>) -> usize {
+        value.map(u8::from).mls_encoded_len()
+    }
+
+    pub fn mls_encode(value: &Option<bool>, writer: &mut Vec<u8>) -> Result<(), Error> {
+        value.map(u8::from).mls_encode(writer)
+    }
+
+    pub fn mls_decode(reader: &mut &[u8]) -> Result<Option<bool>, Error> {
+        Option::<u8>::mls_decode(reader)?
+            .map(|value| match value {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(Error::UnsupportedEnumDiscriminant),
+            })
+            .transpose()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    pub(crate) fn with_config_overrides(self, config_overrides: GroupConfigOverrides) -> Self {
+        Self {
+            config_overrides,
+            ..self
+        }
+    }
+}
+
+#[cfg(all(test, feature = "private_message", feature = "prior_epoch"))]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::padding::PaddingMode,
+        WireFormat,
+    };
+
+    use super::GroupConfigOverrides;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn overrides_apply_per_group_and_are_stored() {
+        let (alice_client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let (bob_client, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let overrides = GroupConfigOverrides::new()
+            .with_padding_mode(PaddingMode::None)
+            .with_control_message_encryption(true)
+            .with_max_epoch_retention(1);
+
+        let mut alice = alice_client
+            .create_group_with_overrides(
+                None,
+                Default::default(),
+                Default::default(),
+                overrides.clone(),
+            )
+            .await
+            .unwrap();
+
+        let commit_output = alice
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (mut bob, _) = bob_client
+            .join_group_with_overrides(None, &commit_output.welcome_messages[0], overrides.clone())
+            .await
+            .unwrap();
+
+        alice.write_to_storage().await.unwrap();
+        let mut alice = alice_client.load_group(alice.group_id()).await.unwrap();
+        assert_eq!(alice.config_overrides(), &overrides);
+
+        let old_message = alice
+            .encrypt_application_message(b"old", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        assert_eq!(commit.wire_format(), WireFormat::PrivateMessage);
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let recent_message = alice
+            .encrypt_application_message(b"recent", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        bob.process_incoming_message(recent_message).await.unwrap();
+
+        let res = bob.process_incoming_message(old_message).await;
+        assert_matches!(res, Err(MlsError::EpochNotFound));
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::time::Duration;
+use mls_rs_core::{
+    error::IntoAnyError,
+    identity::{CredentialValidity, IdentityProvider, SigningIdentity},
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, Member},
+};
+
+#[cfg(feature = "std")]
+use crate::time::MlsTime;
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Validity period of the credential of the member with `index`, as
+    /// reported by
+    /// [`IdentityProvider::credential_validity`](crate::IdentityProvider::credential_validity).
+    ///
+    /// `None` is returned for credentials that do not expire.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn member_credential_validity(
+        &self,
+        index: u32,
+    ) -> Result<Option<CredentialValidity>, MlsError> {
+        let member = self.roster().member_with_index(index)?;
+        self.credential_validity(&member.signing_identity)
+    }
+
+    /// All members of the group with the validity period of their
+    /// credential, as in [`Group::member_credential_validity`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn members_with_credential_validity(
+        &self,
+    ) -> Result<Vec<(Member, Option<CredentialValidity>)>, MlsError> {
+        self.roster()
+            .members_iter()
+            .map(|member| {
+                let validity = self.credential_validity(&member.signing_identity)?;
+                Ok((member, validity))
+            })
+            .collect()
+    }
+
+    /// Members whose credential will no longer be valid `window` from now,
+    /// including members whose credential already expired.
+    ///
+    /// Applications can use this to prompt members to renew their credential
+    /// before it starts failing validation, for example when they send an
+    /// update proposal. Members with credentials that do not expire are not
+    /// reported.
+    #[cfg(feature = "std")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn members_with_expiring_credentials(
+        &self,
+        window: Duration,
+    ) -> Result<Vec<(Member, CredentialValidity)>, MlsError> {
+        let now = MlsTime::now();
+
+        Ok(self
+            .members_with_credential_validity()?
+            .into_iter()
+            .filter_map(|(member, validity)| Some((member, validity?)))
+            .filter(|(_, validity)| validity.expires_within(now, window))
+            .collect())
+    }
+
+    fn credential_validity(
+        &self,
+        signing_identity: &SigningIdentity,
+    ) -> Result<Option<CredentialValidity>, MlsError> {
+        self.config
+            .identity_provider()
+            .credential_validity(signing_identity)
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use alloc::{vec, vec::Vec};
+    use core::{convert::Infallible, time::Duration};
+    use mls_rs_core::{
+        extension::ExtensionList,
+        identity::{
+            CredentialType, CredentialValidity, IdentityProvider, MemberValidationContext,
+            SigningIdentity,
+        },
+        time::MlsTime,
+    };
+
+    use crate::{
+        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_builder::ClientBuilder,
+        crypto::test_utils::TestCryptoProvider,
+        identity::test_utils::get_test_signing_identity,
+    };
+
+    const ALICE_CREDENTIAL_LIFETIME: u64 = 600;
+
+    #[derive(Clone)]
+    struct ExpiringIdentityProvider;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl IdentityProvider for ExpiringIdentityProvider {
+        type Error = Infallible;
+
+        async fn validate_member(
+            &self,
+            _: &SigningIdentity,
+            _: Option<MlsTime>,
+            _: MemberValidationContext<'_>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn validate_external_sender(
+            &self,
+            _: &SigningIdentity,
+            _: Option<MlsTime>,
+            _: Option<&ExtensionList>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn identity(
+            &self,
+            signing_identity: &SigningIdentity,
+            _: &ExtensionList,
+        ) -> Result<Vec<u8>, Self::Error> {
+            Ok(signing_identity.signature_key.to_vec())
+        }
+
+        async fn valid_successor(
+            &self,
+            _: &SigningIdentity,
+            _: &SigningIdentity,
+            _: &ExtensionList,
+        ) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+
+        fn supported_types(&self) -> Vec<CredentialType> {
+            vec![CredentialType::BASIC]
+        }
+
+        // Only the credential of alice expires.
+        fn credential_validity(
+            &self,
+            signing_identity: &SigningIdentity,
+        ) -> Result<Option<CredentialValidity>, Self::Error> {
+            let is_alice = signing_identity
+                .credential
+                .as_basic()
+                .map_or(false, |c| c.identifier == b"alice");
+
+            let now = MlsTime::now().seconds_since_epoch();
+
+            Ok(is_alice.then(|| {
+                CredentialValidity::new(now.into(), (now + ALICE_CREDENTIAL_LIFETIME).into())
+            }))
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn expiring_credentials_are_reported() {
+        let (signing_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let alice = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(ExpiringIdentityProvider)
+            .signing_identity(signing_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut group = alice
+            .create_group(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        assert!(group.member_credential_validity(0).unwrap().is_some());
+        assert_eq!(group.member_credential_validity(1).unwrap(), None);
+
+        let expiring = group
+            .members_with_expiring_credentials(Duration::from_secs(3600))
+            .unwrap();
+
+        assert_eq!(expiring.len(), 1);
+        assert_eq!(expiring[0].0.index, 0);
+
+        let expiring = group
+            .members_with_expiring_credentials(Duration::from_secs(60))
+            .unwrap();
+
+        assert!(expiring.is_empty());
+    }
+}
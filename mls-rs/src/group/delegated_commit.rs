@@ -0,0 +1,318 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Commits delegated by a constrained device to another device of the same
+//! user.
+//!
+//! The requester signs a [`CommitRequest`] listing the proposals it wants to
+//! commit and addressed to a delegate, typically another leaf of the same
+//! user. The delegate verifies the request and commits exactly the requested
+//! proposals, performing the path computation on behalf of the requester.
+//! Once the commit is received, the requester checks with
+//! [`Group::verify_delegated_commit`] that the delegate committed the
+//! requested proposals and nothing else.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::Sender,
+        proposal::Proposal,
+        proposal_filter::{ProposalBundle, ProposalSource},
+        CommitEffect, CommitMessageDescription, CommitOutput, Group,
+    },
+    signer::Signable,
+    tree_kem::node::LeafIndex,
+};
+
+#[derive(MlsSize, MlsEncode)]
+struct CommitRequestTBS<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    requester_index: LeafIndex,
+    delegate_index: LeafIndex,
+    proposals: &'a [Proposal],
+}
+
+/// Request by a member to commit proposals on its behalf, created with
+/// [`Group::request_commit`] and committed by the delegate with
+/// [`Group::commit_requested`].
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct CommitRequest {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    requester_index: LeafIndex,
+    delegate_index: LeafIndex,
+    proposals: Vec<Proposal>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for CommitRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommitRequest")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("requester_index", &self.requester_index)
+            .field("delegate_index", &self.delegate_index)
+            .field("proposals", &self.proposals)
+            .finish()
+    }
+}
+
+impl CommitRequest {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch in which the proposals must be committed.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf index of the member that created the request.
+    pub fn requester_index(&self) -> u32 {
+        *self.requester_index
+    }
+
+    /// Leaf index of the member asked to commit the proposals.
+    pub fn delegate_index(&self) -> u32 {
+        *self.delegate_index
+    }
+
+    pub fn proposals(&self) -> &[Proposal] {
+        &self.proposals
+    }
+}
+
+impl<'a> Signable<'a> for CommitRequest {
+    const SIGN_LABEL: &'static str = "CommitRequestTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        CommitRequestTBS {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            requester_index: self.requester_index,
+            delegate_index: self.delegate_index,
+            proposals: &self.proposals,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Ask the member at `delegate_index` to commit `proposals` in the
+    /// current epoch.
+    ///
+    /// The proposals are committed by value by the delegate, so they must be
+    /// valid when sent by the delegate. In particular, update proposals
+    /// can't be delegated.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn request_commit(
+        &self,
+        delegate_index: u32,
+        proposals: Vec<Proposal>,
+    ) -> Result<CommitRequest, MlsError> {
+        let delegate_index = LeafIndex(delegate_index);
+
+        self.current_epoch_tree().get_leaf_node(delegate_index)?;
+
+        let mut request = CommitRequest {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            requester_index: self.private_tree.self_index,
+            delegate_index,
+            proposals,
+            signature: Vec::new(),
+        };
+
+        request
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(request)
+    }
+
+    /// Commit the proposals of a [`CommitRequest`] addressed to the local
+    /// member, after verifying the signature of the requester.
+    ///
+    /// The commit contains exactly the requested proposals and none of the
+    /// proposals cached in the current epoch. It is applied with
+    /// [`Group::apply_pending_commit`] as any other commit.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn commit_requested(
+        &mut self,
+        request: &CommitRequest,
+    ) -> Result<CommitOutput, MlsError> {
+        if request.group_id != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if request.epoch != self.current_epoch() {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        if request.delegate_index != self.private_tree.self_index {
+            return Err(MlsError::CommitRequestDelegateMismatch);
+        }
+
+        let requester = self
+            .current_epoch_tree()
+            .get_leaf_node(request.requester_index)?;
+
+        request
+            .verify(
+                &self.cipher_suite_provider,
+                &requester.signing_identity.signature_key,
+                &(),
+            )
+            .await?;
+
+        let sender = Sender::Member(*self.private_tree.self_index);
+        let mut bundle = ProposalBundle::default();
+
+        for proposal in request.proposals.iter().cloned() {
+            bundle.add(proposal, sender, ProposalSource::ByValue);
+        }
+
+        self.commit_bundle(bundle, Vec::new()).await
+    }
+
+    /// Check that a commit received in response to a [`CommitRequest`]
+    /// created by the local member was made by the delegate in the requested
+    /// epoch and applies exactly the requested proposals, in any order.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn verify_delegated_commit(
+        &self,
+        request: &CommitRequest,
+        commit: &CommitMessageDescription,
+    ) -> Result<(), MlsError> {
+        let new_epoch = match &commit.effect {
+            CommitEffect::NewEpoch(new_epoch) => new_epoch,
+            CommitEffect::Removed { new_epoch, .. } => new_epoch,
+            CommitEffect::ReInit(_) => return Err(MlsError::DelegatedCommitMismatch),
+        };
+
+        if commit.is_external
+            || commit.committer != *request.delegate_index
+            || new_epoch.epoch != request.epoch + 1
+            || new_epoch.prior_state.context.group_id != request.group_id
+        {
+            return Err(MlsError::DelegatedCommitMismatch);
+        }
+
+        let mut requested = request.proposals.iter().collect::<Vec<_>>();
+
+        for applied in &new_epoch.applied_proposals {
+            let position = requested
+                .iter()
+                .position(|proposal| **proposal == applied.proposal)
+                .ok_or(MlsError::DelegatedCommitMismatch)?;
+
+            requested.swap_remove(position);
+        }
+
+        match requested.is_empty() {
+            true => Ok(()),
+            false => Err(MlsError::DelegatedCommitMismatch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            proposal::{Proposal, RemoveProposal},
+            test_utils::test_group,
+            ReceivedMessage,
+        },
+    };
+
+    use super::CommitRequest;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn delegated_commit_round_trip() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut phone, _) = alice.join("phone").await;
+        let (mut watch, commit) = alice.join("watch").await;
+        let (_, commit2) = alice.join("carol").await;
+
+        phone.process_incoming_message(commit).await.unwrap();
+        phone
+            .process_incoming_message(commit2.clone())
+            .await
+            .unwrap();
+        watch.process_incoming_message(commit2).await.unwrap();
+
+        let remove = Proposal::Remove(RemoveProposal::from(3));
+        let request = watch.request_commit(1, vec![remove.clone()]).await.unwrap();
+        let request = CommitRequest::from_bytes(&request.to_bytes().unwrap()).unwrap();
+
+        let res = alice.commit_requested(&request).await;
+        assert_matches!(res, Err(MlsError::CommitRequestDelegateMismatch));
+
+        let output = phone.commit_requested(&request).await.unwrap();
+        phone.apply_pending_commit().await.unwrap();
+
+        let received = watch
+            .process_incoming_message(output.commit_message)
+            .await
+            .unwrap();
+
+        let ReceivedMessage::Commit(description) = received else {
+            panic!("expected commit");
+        };
+
+        watch
+            .verify_delegated_commit(&request, &description)
+            .unwrap();
+
+        let other = watch.request_commit(1, vec![remove]).await.unwrap();
+        let res = watch.verify_delegated_commit(&other, &description);
+        assert_matches!(res, Err(MlsError::DelegatedCommitMismatch));
+    }
+}
@@ -7,13 +7,19 @@ use crate::psk::PreSharedKey;
 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
 use crate::tree_kem::node::NodeIndex;
 #[cfg(feature = "prior_epoch")]
-use crate::{crypto::SignaturePublicKey, group::GroupContext, tree_kem::node::LeafIndex};
+use crate::{
+    crypto::SignaturePublicKey,
+    group::GroupContext,
+    identity::{Credential, SigningIdentity},
+    tree_kem::node::LeafIndex,
+};
 use alloc::vec::Vec;
 use core::{
     fmt::{self, Debug},
     ops::Deref,
 };
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::secret::SecretBytes;
 use zeroize::Zeroizing;
 
 #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
@@ -23,17 +29,78 @@ use super::ciphertext_processor::GroupStateProvider;
 use crate::group::secret_tree::SecretTree;
 
 #[cfg(feature = "prior_epoch")]
-#[derive(Debug, Clone, MlsEncode, MlsDecode, MlsSize, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct PriorEpoch {
     pub(crate) context: GroupContext,
     pub(crate) self_index: LeafIndex,
     pub(crate) secrets: EpochSecrets,
     pub(crate) signature_public_keys: Vec<Option<SignaturePublicKey>>,
+    /// Empty for epochs stored before the epoch authenticator was recorded.
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
+    pub(crate) epoch_authenticator: Zeroizing<Vec<u8>>,
+    /// Seconds since unix epoch at which the commit ending this epoch was
+    /// processed, if a clock was available. `None` for epochs stored before
+    /// the retirement time was recorded, which are not subject to age limits.
+    pub(crate) retired_at: Option<u64>,
+    /// Credentials of the members, by leaf index as `signature_public_keys`.
+    /// Empty for epochs stored before the credentials were recorded.
+    pub(crate) credentials: Vec<Option<Credential>>,
 }
 
 #[cfg(feature = "prior_epoch")]
 impl PriorEpoch {
+    /// Decode a prior epoch stored by the
+    /// [`GroupStateStorage`](crate::GroupStateStorage). Fields added to the
+    /// record are appended, and take their default value when reading records
+    /// written before they were added.
+    pub(crate) fn from_record(record: &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        let reader = &mut &*record;
+
+        #[allow(unused_mut)]
+        let mut epoch = Self {
+            context: MlsDecode::mls_decode(reader)?,
+            self_index: MlsDecode::mls_decode(reader)?,
+            secrets: MlsDecode::mls_decode(reader)?,
+            signature_public_keys: MlsDecode::mls_decode(reader)?,
+            epoch_authenticator: decode_trailing(reader, mls_rs_codec::byte_vec::mls_decode)?,
+            retired_at: decode_trailing(reader, MlsDecode::mls_decode)?,
+            credentials: decode_trailing(reader, MlsDecode::mls_decode)?,
+        };
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        {
+            let ratchet_states = decode_trailing(reader, MlsDecode::mls_decode)?;
+            epoch
+                .secrets
+                .secret_tree
+                .set_ratchet_states(ratchet_states)?;
+        }
+
+        Ok(epoch)
+    }
+
+    /// Signing identities of the members, by leaf index. `None` if the
+    /// credentials of the members were not recorded.
+    pub(crate) fn signing_identities(&self) -> Option<Vec<Option<SigningIdentity>>> {
+        if self.credentials.len() != self.signature_public_keys.len() {
+            return None;
+        }
+
+        let identities = self
+            .signature_public_keys
+            .iter()
+            .zip(&self.credentials)
+            .map(|(key, credential)| {
+                key.clone()
+                    .zip(credential.clone())
+                    .map(|(key, credential)| SigningIdentity::new(credential, key))
+            })
+            .collect();
+
+        Some(identities)
+    }
+
     #[inline(always)]
     pub(crate) fn epoch_id(&self) -> u64 {
         self.context.epoch
@@ -45,6 +112,83 @@ impl PriorEpoch {
     }
 }
 
+// The checkpoints and subtrees of the ratchets of the secret tree are
+// appended to the record, see `RatchetStates`.
+#[cfg(feature = "prior_epoch")]
+impl MlsSize for PriorEpoch {
+    fn mls_encoded_len(&self) -> usize {
+        let len = self.context.mls_encoded_len()
+            + self.self_index.mls_encoded_len()
+            + self.secrets.mls_encoded_len()
+            + self.signature_public_keys.mls_encoded_len()
+            + mls_rs_codec::byte_vec::mls_encoded_len(&self.epoch_authenticator)
+            + self.retired_at.mls_encoded_len()
+            + self.credentials.mls_encoded_len();
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        return len + self.secrets.secret_tree.ratchet_states().mls_encoded_len();
+        #[cfg(not(any(feature = "secret_tree_access", feature = "private_message")))]
+        return len;
+    }
+}
+
+#[cfg(feature = "prior_epoch")]
+impl MlsEncode for PriorEpoch {
+    fn mls_encode(&self, writer: &mut Vec<u8>) -> Result<(), mls_rs_codec::Error> {
+        self.context.mls_encode(writer)?;
+        self.self_index.mls_encode(writer)?;
+        self.secrets.mls_encode(writer)?;
+        self.signature_public_keys.mls_encode(writer)?;
+        mls_rs_codec::byte_vec::mls_encode(&self.epoch_authenticator, writer)?;
+        self.retired_at.mls_encode(writer)?;
+        self.credentials.mls_encode(writer)?;
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        self.secrets
+            .secret_tree
+            .ratchet_states()
+            .mls_encode(writer)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "prior_epoch")]
+impl MlsDecode for PriorEpoch {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        #[allow(unused_mut)]
+        let mut epoch = Self {
+            context: MlsDecode::mls_decode(reader)?,
+            self_index: MlsDecode::mls_decode(reader)?,
+            secrets: MlsDecode::mls_decode(reader)?,
+            signature_public_keys: MlsDecode::mls_decode(reader)?,
+            epoch_authenticator: mls_rs_codec::byte_vec::mls_decode(reader)?,
+            retired_at: MlsDecode::mls_decode(reader)?,
+            credentials: MlsDecode::mls_decode(reader)?,
+        };
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        epoch
+            .secrets
+            .secret_tree
+            .set_ratchet_states(MlsDecode::mls_decode(reader)?)?;
+
+        Ok(epoch)
+    }
+}
+
+#[cfg(feature = "prior_epoch")]
+fn decode_trailing<T: Default>(
+    reader: &mut &[u8],
+    decode: impl FnOnce(&mut &[u8]) -> Result<T, mls_rs_codec::Error>,
+) -> Result<T, mls_rs_codec::Error> {
+    if reader.is_empty() {
+        Ok(T::default())
+    } else {
+        decode(reader)
+    }
+}
+
 #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
 impl GroupStateProvider for PriorEpoch {
     fn group_context(&self) -> &GroupContext {
@@ -78,11 +222,7 @@ pub(crate) struct EpochSecrets {
 
 #[derive(Clone, PartialEq, MlsEncode, MlsDecode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub(crate) struct SenderDataSecret(
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
-    Zeroizing<Vec<u8>>,
-);
+pub(crate) struct SenderDataSecret(SecretBytes);
 
 impl Debug for SenderDataSecret {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -108,13 +248,13 @@ impl Deref for SenderDataSecret {
 
 impl From<Vec<u8>> for SenderDataSecret {
     fn from(bytes: Vec<u8>) -> Self {
-        Self(Zeroizing::new(bytes))
+        Self(bytes.into())
     }
 }
 
 impl From<Zeroizing<Vec<u8>>> for SenderDataSecret {
     fn from(bytes: Zeroizing<Vec<u8>>) -> Self {
-        Self(bytes)
+        Self(bytes.into())
     }
 }
 
@@ -160,6 +300,9 @@ pub(crate) mod test_utils {
             self_index: LeafIndex(0),
             secrets: get_test_epoch_secrets(cipher_suite),
             signature_public_keys: Default::default(),
+            epoch_authenticator: Default::default(),
+            retired_at: None,
+            credentials: Default::default(),
         }
     }
 }
@@ -0,0 +1,229 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::client::MlsError;
+
+/// Epoch authenticator that was compared out-of-band for a past epoch, for
+/// example a safety number verified in person between two members.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EpochAuthenticatorPin {
+    pub epoch: u64,
+    pub epoch_authenticator: Vec<u8>,
+}
+
+impl EpochAuthenticatorPin {
+    pub fn new(epoch: u64, epoch_authenticator: Vec<u8>) -> Self {
+        Self {
+            epoch,
+            epoch_authenticator,
+        }
+    }
+}
+
+impl Debug for EpochAuthenticatorPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EpochAuthenticatorPin")
+            .field("epoch", &self.epoch)
+            .field(
+                "epoch_authenticator",
+                &mls_rs_core::debug::pretty_bytes(&self.epoch_authenticator),
+            )
+            .finish()
+    }
+}
+
+/// Chain of consecutive epoch authenticators ending at the epoch in which the
+/// proof was exported.
+///
+/// An existing member exports the proof with
+/// [`Group::epoch_authenticator_proof`](crate::Group::epoch_authenticator_proof)
+/// right after adding a new member and sends it to the new member. The new
+/// member verifies it with
+/// [`Group::verify_epoch_authenticator_proof`](crate::Group::verify_epoch_authenticator_proof)
+/// against epoch authenticators it learned out-of-band. A delivery service
+/// that cloned the group into a fork it controls can not produce a chain that
+/// both ends at the joiner's epoch and matches the pins of the original group.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct EpochAuthenticatorProof {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) group_id: Vec<u8>,
+    pub(crate) first_epoch: u64,
+    pub(crate) epoch_authenticators: Vec<EpochAuthenticator>,
+}
+
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub(crate) struct EpochAuthenticator(#[mls_codec(with = "mls_rs_codec::byte_vec")] Vec<u8>);
+
+impl Debug for EpochAuthenticatorProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EpochAuthenticatorProof")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("first_epoch", &self.first_epoch)
+            .field("last_epoch", &self.last_epoch())
+            .finish()
+    }
+}
+
+impl EpochAuthenticatorProof {
+    pub(crate) fn new(
+        group_id: Vec<u8>,
+        first_epoch: u64,
+        epoch_authenticators: Vec<Vec<u8>>,
+    ) -> Self {
+        Self {
+            group_id,
+            first_epoch,
+            epoch_authenticators: epoch_authenticators
+                .into_iter()
+                .map(EpochAuthenticator)
+                .collect(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Oldest epoch covered by the proof.
+    pub fn first_epoch(&self) -> u64 {
+        self.first_epoch
+    }
+
+    /// Epoch in which the proof was exported.
+    pub fn last_epoch(&self) -> u64 {
+        (self.first_epoch + self.epoch_authenticators.len() as u64).saturating_sub(1)
+    }
+
+    /// Epoch authenticator claimed by the proof for `epoch`.
+    pub fn epoch_authenticator(&self, epoch: u64) -> Option<&[u8]> {
+        let index = epoch.checked_sub(self.first_epoch)?;
+
+        self.epoch_authenticators
+            .get(usize::try_from(index).ok()?)
+            .map(|a| a.0.as_slice())
+    }
+
+    pub(crate) fn verify(
+        &self,
+        group_id: &[u8],
+        current_epoch: u64,
+        current_epoch_authenticator: &[u8],
+        pins: &[EpochAuthenticatorPin],
+    ) -> Result<(), MlsError> {
+        if self.group_id != group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        let ends_at_current_epoch = self.last_epoch() == current_epoch
+            && self.epoch_authenticator(current_epoch) == Some(current_epoch_authenticator);
+
+        let pins_match = !pins.is_empty()
+            && pins.iter().all(|pin| {
+                self.epoch_authenticator(pin.epoch) == Some(pin.epoch_authenticator.as_slice())
+            });
+
+        (ends_at_current_epoch && pins_match)
+            .then_some(())
+            .ok_or(MlsError::InvalidEpochAuthenticatorProof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+    };
+
+    use super::{EpochAuthenticatorPin, EpochAuthenticatorProof};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joiner_verifies_epoch_authenticator_chain_against_pins() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let pin = EpochAuthenticatorPin::new(
+            alice.current_epoch(),
+            alice.epoch_authenticator().unwrap().to_vec(),
+        );
+
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        let (bob, _) = alice.join("bob").await;
+
+        let proof = alice.epoch_authenticator_proof(3).await.unwrap();
+        let proof = EpochAuthenticatorProof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(proof.first_epoch(), pin.epoch);
+        assert_eq!(proof.last_epoch(), bob.current_epoch());
+
+        bob.verify_epoch_authenticator_proof(&proof, core::slice::from_ref(&pin))
+            .unwrap();
+
+        let wrong_pin = EpochAuthenticatorPin::new(pin.epoch, vec![0u8; 32]);
+
+        assert_matches!(
+            bob.verify_epoch_authenticator_proof(&proof, &[wrong_pin]),
+            Err(MlsError::InvalidEpochAuthenticatorProof)
+        );
+
+        assert_matches!(
+            bob.verify_epoch_authenticator_proof(&proof, &[]),
+            Err(MlsError::InvalidEpochAuthenticatorProof)
+        );
+
+        // A proof that does not reach the joiner's epoch is rejected
+        let stale_proof = EpochAuthenticatorProof {
+            epoch_authenticators: proof.epoch_authenticators[..2].to_vec(),
+            ..proof
+        };
+
+        assert_matches!(
+            bob.verify_epoch_authenticator_proof(&stale_proof, &[pin]),
+            Err(MlsError::InvalidEpochAuthenticatorProof)
+        );
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proof_ends_at_epochs_stored_without_epoch_authenticator() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        for _ in 0..3 {
+            alice.commit(vec![]).await.unwrap();
+            alice.apply_pending_commit().await.unwrap();
+        }
+
+        // Epoch 1 was stored before epoch authenticators were recorded
+        alice
+            .state_repo
+            .get_epoch_mut(1)
+            .await
+            .unwrap()
+            .unwrap()
+            .epoch_authenticator = Default::default();
+
+        let proof = alice.epoch_authenticator_proof(10).await.unwrap();
+
+        assert_eq!(proof.first_epoch(), 2);
+        assert_eq!(proof.last_epoch(), alice.current_epoch());
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::mem;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+#[cfg(feature = "std")]
+use crate::time::MlsTime;
+
+/// Retention policy for the secrets of past epochs of a group.
+///
+/// The policy is enforced every time the group state is written to storage:
+/// prior epochs that are no longer allowed are deleted with
+/// [`GroupStateStorage::delete_epochs_under`](crate::GroupStateStorage::delete_epochs_under)
+/// and each deletion is recorded in the
+/// [deletion manifest](Group::deletion_manifest) of the group. Nothing is
+/// deleted or recorded if the storage does not support deletion on request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EpochRetentionPolicy {
+    /// Number of past epochs that are kept. Older epochs are deleted.
+    pub max_epochs: Option<u64>,
+    /// Time in seconds for which a past epoch is kept after the group moved
+    /// to the next epoch. Epochs the local member joined in are only subject
+    /// to [`max_epochs`](Self::max_epochs), as their end was not observed.
+    ///
+    /// Only enforced with the `std` feature, which provides a clock. The
+    /// value is stored with the group state regardless, so that the policy is
+    /// not lost when the state is loaded by a build without a clock.
+    pub max_age: Option<u64>,
+}
+
+impl EpochRetentionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_epochs(self, max_epochs: u64) -> Self {
+        Self {
+            max_epochs: Some(max_epochs),
+            ..self
+        }
+    }
+
+    pub fn with_max_age(self, max_age_in_s: u64) -> Self {
+        Self {
+            max_age: Some(max_age_in_s),
+            ..self
+        }
+    }
+}
+
+/// Range of past epochs deleted from storage under an
+/// [`EpochRetentionPolicy`].
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EpochDeletion {
+    /// First deleted epoch.
+    pub first_epoch: u64,
+    /// Last deleted epoch, included in the range.
+    pub last_epoch: u64,
+    /// Time of the deletion, in seconds since the Unix epoch. Not available
+    /// without the `std` feature.
+    pub deleted_at: Option<u64>,
+}
+
+/// Deletions performed so far, stored with the group state so that they can
+/// be reported after the group is reloaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct EpochRetentionLog {
+    /// Oldest epoch that was not deleted yet.
+    retained_from: u64,
+    manifest: Vec<EpochDeletion>,
+}
+
+impl EpochRetentionLog {
+    /// Log of a group whose first known epoch is `epoch`.
+    pub(crate) fn new(epoch: u64) -> Self {
+        Self {
+            retained_from: epoch,
+            manifest: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, delete_under: u64) {
+        #[cfg(feature = "std")]
+        let deleted_at = Some(MlsTime::now().seconds_since_epoch());
+
+        #[cfg(not(feature = "std"))]
+        let deleted_at = None;
+
+        self.manifest.push(EpochDeletion {
+            first_epoch: self.retained_from,
+            last_epoch: delete_under - 1,
+            deleted_at,
+        });
+
+        self.retained_from = delete_under;
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Past epochs deleted from storage under the [`EpochRetentionPolicy`]
+    /// of this group, oldest first.
+    ///
+    /// An epoch range is recorded only after the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) confirmed its
+    /// deletion. The manifest is persisted with the group state.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn deletion_manifest(&self) -> &[EpochDeletion] {
+        &self.epoch_retention.manifest
+    }
+
+    /// Remove and return the recorded deletions, for example once they are
+    /// exported to an audit log. See [`Group::deletion_manifest`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn take_deletion_manifest(&mut self) -> Result<Vec<EpochDeletion>, MlsError> {
+        let manifest = mem::take(&mut self.epoch_retention.manifest);
+        self.state_changed().await?;
+
+        Ok(manifest)
+    }
+
+    /// Delete the past epochs that the [`EpochRetentionPolicy`] of this
+    /// group no longer allows to keep.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn enforce_epoch_retention(&mut self) -> Result<(), MlsError> {
+        let Some(policy) = &self.config_overrides.epoch_retention else {
+            return Ok(());
+        };
+
+        let current_epoch = self.current_epoch();
+        let retained_from = self.epoch_retention.retained_from;
+
+        let delete_under = policy
+            .max_epochs
+            .map_or(0, |max| current_epoch.saturating_sub(max));
+
+        #[cfg(feature = "std")]
+        let delete_under = match policy.max_age {
+            Some(max_age) => self.expired_epochs_end(delete_under, max_age).await?,
+            None => delete_under,
+        };
+
+        if delete_under <= retained_from {
+            return Ok(());
+        }
+
+        if self.state_repo.delete_epochs_under(delete_under).await? {
+            self.epoch_retention.record(delete_under);
+        }
+
+        Ok(())
+    }
+
+    /// First epoch after `start` that is not older than `max_age` seconds.
+    /// Epochs missing from storage count as expired.
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn expired_epochs_end(&self, start: u64, max_age: u64) -> Result<u64, MlsError> {
+        let now = MlsTime::now().seconds_since_epoch();
+        let mut epoch_id = start.max(self.epoch_retention.retained_from);
+
+        while epoch_id < self.current_epoch() {
+            let expired = match self.state_repo.get_epoch(epoch_id).await? {
+                Some(epoch) => epoch.retired_at.map_or(false, |retired_at| {
+                    now.saturating_sub(retired_at) >= max_age
+                }),
+                None => true,
+            };
+
+            if !expired {
+                break;
+            }
+
+            epoch_id += 1;
+        }
+
+        Ok(epoch_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::group::GroupStateStorage;
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+        client_config::ClientConfig,
+        group::{Group, GroupConfigOverrides},
+        storage_provider::in_memory::InMemoryGroupStateStorage,
+    };
+
+    use super::{EpochDeletion, EpochRetentionPolicy};
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn advance<C: ClientConfig + Clone>(group: &mut Group<C>, n: u64) {
+        for _ in 0..n {
+            group.commit(vec![]).await.unwrap();
+            group.apply_pending_commit().await.unwrap();
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn expired_epochs_are_deleted_and_recorded() {
+        let storage = InMemoryGroupStateStorage::new()
+            .with_max_epoch_retention(10)
+            .unwrap();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(storage.clone())
+            .build();
+
+        let overrides = GroupConfigOverrides::new()
+            .with_epoch_retention(EpochRetentionPolicy::new().with_max_epochs(2));
+
+        let mut group = client
+            .create_group_with_overrides(None, Default::default(), Default::default(), overrides)
+            .await
+            .unwrap();
+
+        advance(&mut group, 5).await;
+        group.write_to_storage().await.unwrap();
+
+        let group_id = group.group_id().to_vec();
+
+        for epoch_id in 0..5 {
+            let stored = storage.epoch(&group_id, epoch_id).await.unwrap();
+            assert_eq!(stored.is_some(), epoch_id >= 3);
+        }
+
+        assert_matches!(
+            group.deletion_manifest(),
+            [EpochDeletion {
+                first_epoch: 0,
+                last_epoch: 2,
+                ..
+            }]
+        );
+
+        advance(&mut group, 1).await;
+        group.write_to_storage().await.unwrap();
+
+        let stored = storage.epoch(&group_id, 3).await.unwrap();
+        assert!(stored.is_none());
+
+        let mut group = client.load_group(&group_id).await.unwrap();
+
+        assert_matches!(
+            group.deletion_manifest(),
+            [
+                EpochDeletion {
+                    first_epoch: 0,
+                    last_epoch: 2,
+                    ..
+                },
+                EpochDeletion {
+                    first_epoch: 3,
+                    last_epoch: 3,
+                    ..
+                }
+            ]
+        );
+
+        let manifest = group.take_deletion_manifest().await.unwrap();
+        assert_eq!(manifest.len(), 2);
+        assert!(group.deletion_manifest().is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn epochs_older_than_max_age_are_deleted() {
+        let storage = InMemoryGroupStateStorage::new()
+            .with_max_epoch_retention(10)
+            .unwrap();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(storage.clone())
+            .build();
+
+        let overrides = GroupConfigOverrides::new()
+            .with_epoch_retention(EpochRetentionPolicy::new().with_max_age(0));
+
+        let mut group = client
+            .create_group_with_overrides(None, Default::default(), Default::default(), overrides)
+            .await
+            .unwrap();
+
+        advance(&mut group, 3).await;
+        group.write_to_storage().await.unwrap();
+
+        let group_id = group.group_id().to_vec();
+
+        let max_epoch_id = storage.max_epoch_id(&group_id).await.unwrap();
+        assert_eq!(max_epoch_id, None);
+
+        assert_matches!(
+            group.deletion_manifest(),
+            [EpochDeletion {
+                first_epoch: 0,
+                last_epoch: 2,
+                deleted_at: Some(_)
+            }]
+        );
+
+        let group = client.load_group(&group_id).await.unwrap();
+        let policy = group.config_overrides.epoch_retention.as_ref().unwrap();
+
+        assert_eq!(policy.max_age, Some(0));
+    }
+}
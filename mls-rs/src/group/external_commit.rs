@@ -2,8 +2,12 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::{
-    crypto::SignatureSecretKey, extension::ExtensionList, identity::SigningIdentity,
+    crypto::{CipherSuiteProvider, SignatureSecretKey},
+    extension::ExtensionList,
+    identity::SigningIdentity,
 };
 
 use crate::{
@@ -13,8 +17,10 @@ use crate::{
         epoch::SenderDataSecret,
         key_schedule::{InitSecret, KeySchedule},
         proposal::{ExternalInit, Proposal, RemoveProposal},
-        EpochSecrets, ExternalPubExt, LeafIndex, LeafNode, MlsError, TreeKemPrivate,
+        CommitProposals, EpochSecrets, ExternalPubExt, GroupContext, LeafIndex, LeafNode, MlsError,
+        TreeKemPrivate,
     },
+    signer::Signable,
     Group, MlsMessage,
 };
 
@@ -43,6 +49,150 @@ use crate::group::{
 
 use super::{validate_tree_and_info_joiner, ExportedTree};
 
+/// Tree hash of a group at a given epoch, signed by a trusted party such as
+/// the delivery service.
+///
+/// A client joining with [`ExternalCommitBuilder::with_tree_data`] obtains
+/// the ratchet tree and the group info from an untrusted source. Supplying a
+/// signed tree head with [`ExternalCommitBuilder::with_signed_tree_head`]
+/// makes the external commit fail unless the tree matches a tree hash signed
+/// by one of the
+/// [trust anchors](ExternalCommitBuilder::with_tree_head_trust_anchors).
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct SignedTreeHead {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    tree_hash: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for SignedTreeHead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignedTreeHead")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field(
+                "tree_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.tree_hash),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct SignedTreeHeadTBS<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    tree_hash: &'a [u8],
+}
+
+impl<'a> Signable<'a> for SignedTreeHead {
+    const SIGN_LABEL: &'static str = "SignedTreeHeadTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        SignedTreeHeadTBS {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            tree_hash: &self.tree_hash,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl SignedTreeHead {
+    /// Sign the tree hash of epoch `epoch` of the group `group_id`, as found
+    /// in the group context of that epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn new<P: CipherSuiteProvider>(
+        group_id: Vec<u8>,
+        epoch: u64,
+        tree_hash: Vec<u8>,
+        signer: &SignatureSecretKey,
+        cipher_suite_provider: &P,
+    ) -> Result<Self, MlsError> {
+        let mut tree_head = Self {
+            group_id,
+            epoch,
+            tree_hash,
+            signature: Vec::new(),
+        };
+
+        tree_head.sign(cipher_suite_provider, signer, &()).await?;
+
+        Ok(tree_head)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn tree_hash(&self) -> &[u8] {
+        &self.tree_hash
+    }
+
+    /// Check that this tree head is for the epoch of `context` and was signed
+    /// by one of `trust_anchors`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn verify_for<P: CipherSuiteProvider>(
+        &self,
+        context: &GroupContext,
+        trust_anchors: &[SigningIdentity],
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        if self.group_id != context.group_id
+            || self.epoch != context.epoch
+            || self.tree_hash != context.tree_hash
+        {
+            return Err(MlsError::InvalidSignedTreeHead);
+        }
+
+        for anchor in trust_anchors {
+            if self
+                .verify(cipher_suite_provider, &anchor.signature_key, &())
+                .await
+                .is_ok()
+            {
+                return Ok(());
+            }
+        }
+
+        Err(MlsError::InvalidSignedTreeHead)
+    }
+}
+
 /// A builder that aids with the construction of an external commit.
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type(opaque))]
 pub struct ExternalCommitBuilder<C: ClientConfig> {
@@ -51,6 +201,8 @@ pub struct ExternalCommitBuilder<C: ClientConfig> {
     leaf_node_extensions: ExtensionList,
     config: C,
     tree_data: Option<ExportedTree<'static>>,
+    signed_tree_head: Option<SignedTreeHead>,
+    tree_head_trust_anchors: Vec<SigningIdentity>,
     to_remove: Option<u32>,
     #[cfg(feature = "psk")]
     external_psks: Vec<ExternalPskId>,
@@ -69,6 +221,8 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
     ) -> Self {
         Self {
             tree_data: None,
+            signed_tree_head: None,
+            tree_head_trust_anchors: Vec::new(),
             to_remove: None,
             authenticated_data: Vec::new(),
             signer,
@@ -94,6 +248,29 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
         }
     }
 
+    #[must_use]
+    /// Require the ratchet tree to match `signed_tree_head`, which must be
+    /// signed by one of the
+    /// [trust anchors](ExternalCommitBuilder::with_tree_head_trust_anchors).
+    pub fn with_signed_tree_head(self, signed_tree_head: SignedTreeHead) -> Self {
+        Self {
+            signed_tree_head: Some(signed_tree_head),
+            ..self
+        }
+    }
+
+    #[must_use]
+    /// Signing identities, such as the external senders of the delivery
+    /// service, trusted to sign tree heads. Once trust anchors are set, the
+    /// external commit fails without a
+    /// [signed tree head](ExternalCommitBuilder::with_signed_tree_head).
+    pub fn with_tree_head_trust_anchors(self, trust_anchors: Vec<SigningIdentity>) -> Self {
+        Self {
+            tree_head_trust_anchors: trust_anchors,
+            ..self
+        }
+    }
+
     #[must_use]
     /// Propose the removal of an old version of the client as part of the external commit.
     /// Only one such proposal is allowed.
@@ -175,6 +352,18 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
             .get_as::<ExternalPubExt>()?
             .ok_or(MlsError::MissingExternalPubExtension)?;
 
+        if self.signed_tree_head.is_some() || !self.tree_head_trust_anchors.is_empty() {
+            self.signed_tree_head
+                .as_ref()
+                .ok_or(MlsError::MissingSignedTreeHead)?
+                .verify_for(
+                    &group_info.group_context,
+                    &self.tree_head_trust_anchors,
+                    &cipher_suite,
+                )
+                .await?;
+        }
+
         let public_tree = validate_tree_and_info_joiner(
             protocol_version,
             &group_info,
@@ -196,6 +385,10 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
         let (init_secret, kem_output) =
             InitSecret::encode_for_external(&cipher_suite, &external_pub_ext.external_pub).await?;
 
+        let init_secret = init_secret
+            .import(&cipher_suite, &self.config.epoch_secrets_vault())
+            .await?;
+
         let epoch_secrets = EpochSecrets {
             #[cfg(feature = "psk")]
             resumption_secret: PreSharedKey::new(vec![]),
@@ -260,13 +453,15 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
 
         let (commit_output, pending_commit) = group
             .commit_internal(
-                proposals,
+                CommitProposals::Cached(proposals),
                 Some(&leaf_node),
                 self.authenticated_data,
                 Default::default(),
                 None,
                 None,
                 None,
+                #[cfg(feature = "private_message")]
+                None,
             )
             .await?;
 
@@ -0,0 +1,130 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+/// When a [`Group`] writes its state to the
+/// [`GroupStateStorage`](crate::GroupStateStorage).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlushMode {
+    /// Write the state after every operation that changes it: encrypting a
+    /// message, processing a message, proposing, committing and applying a
+    /// commit.
+    ///
+    /// Message keys consumed by an operation are deleted from storage before
+    /// the operation returns, at the cost of one storage write per operation.
+    /// If the write fails, the operation returns the storage error but the
+    /// state in memory is changed and [`Group::flush`] can retry the write.
+    Immediate,
+    /// Only write the state when [`Group::flush`] or
+    /// [`Group::write_to_storage`] is called.
+    ///
+    /// Senders of many messages can batch the changes of several operations
+    /// into a single write. Message keys consumed in the meantime are deleted
+    /// in memory but remain in storage until the next write, so forward
+    /// secrecy only holds for storage once the state is flushed.
+    #[default]
+    Manual,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Set when the state of this group is written to storage. The mode is
+    /// not persisted and is [`FlushMode::Manual`] for a loaded group.
+    ///
+    /// Switching to [`FlushMode::Immediate`] does not write changes made
+    /// before; call [`Group::flush`] to write them.
+    pub fn set_flush_mode(&mut self, flush_mode: FlushMode) {
+        self.flush_mode = flush_mode;
+    }
+
+    pub fn flush_mode(&self) -> FlushMode {
+        self.flush_mode
+    }
+
+    /// Returns true if the state of this group changed since it was last
+    /// written to storage.
+    pub fn has_unflushed_changes(&self) -> bool {
+        self.unflushed_changes
+    }
+
+    /// Write the state of this group to storage if it changed since the last
+    /// write.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn flush(&mut self) -> Result<(), MlsError> {
+        if !self.unflushed_changes {
+            return Ok(());
+        }
+
+        self.write_to_storage().await
+    }
+
+    /// Record a change of the state, writing it right away in
+    /// [`FlushMode::Immediate`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn state_changed(&mut self) -> Result<(), MlsError> {
+        self.unflushed_changes = true;
+
+        match self.flush_mode {
+            FlushMode::Immediate => self.flush().await,
+            FlushMode::Manual => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use mls_rs_core::group::GroupStateStorage;
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+        group::test_utils::TEST_GROUP,
+        storage_provider::in_memory::InMemoryGroupStateStorage,
+    };
+
+    use super::FlushMode;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn flush_mode_controls_storage_writes() {
+        let storage = InMemoryGroupStateStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(storage.clone())
+            .build();
+
+        let mut group = client
+            .create_group_with_id(TEST_GROUP.to_vec(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        assert_eq!(group.flush_mode(), FlushMode::Manual);
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        assert!(group.has_unflushed_changes());
+        let stored = storage.state(TEST_GROUP).await.unwrap();
+        assert!(stored.is_none());
+
+        group.flush().await.unwrap();
+
+        assert!(!group.has_unflushed_changes());
+        let stored = client.load_group(TEST_GROUP).await.unwrap();
+        assert_eq!(stored.current_epoch(), 1);
+
+        group.set_flush_mode(FlushMode::Immediate);
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        assert!(!group.has_unflushed_changes());
+        let stored = client.load_group(TEST_GROUP).await.unwrap();
+        assert_eq!(stored.current_epoch(), 2);
+    }
+}
@@ -23,6 +23,9 @@ use zeroize::ZeroizeOnDrop;
 #[cfg(feature = "private_message")]
 use alloc::boxed::Box;
 
+#[cfg(feature = "private_message")]
+use super::targeted_message::TargetedMessage;
+
 #[cfg(feature = "custom_proposal")]
 use crate::group::proposal::{CustomProposal, ProposalOrRef};
 
@@ -426,6 +429,8 @@ impl MlsMessage {
             MlsMessagePayload::Welcome(_) => WireFormat::Welcome,
             MlsMessagePayload::GroupInfo(_) => WireFormat::GroupInfo,
             MlsMessagePayload::KeyPackage(_) => WireFormat::KeyPackage,
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Targeted(_) => WireFormat::TargetedMessage,
         }
     }
 
@@ -440,6 +445,8 @@ impl MlsMessage {
             #[cfg(feature = "private_message")]
             MlsMessagePayload::Cipher(c) => Some(c.epoch),
             MlsMessagePayload::GroupInfo(gi) => Some(gi.group_context.epoch),
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Targeted(t) => Some(t.epoch),
             _ => None,
         }
     }
@@ -460,6 +467,8 @@ impl MlsMessage {
             #[cfg(feature = "private_message")]
             MlsMessagePayload::Cipher(p) => Some(&p.group_id),
             MlsMessagePayload::GroupInfo(p) => Some(&p.group_context.group_id),
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Targeted(p) => Some(&p.group_id),
             MlsMessagePayload::KeyPackage(_) | MlsMessagePayload::Welcome(_) => None,
         }
     }
@@ -557,6 +566,8 @@ pub(crate) enum MlsMessagePayload {
     Welcome(Welcome) = 3u16,
     GroupInfo(GroupInfo) = 4u16,
     KeyPackage(KeyPackage) = 5u16,
+    #[cfg(feature = "private_message")]
+    Targeted(TargetedMessage) = 6u16,
 }
 
 impl From<PublicMessage> for MlsMessagePayload {
@@ -579,6 +590,8 @@ pub enum WireFormat {
     Welcome = 3u16,
     GroupInfo = 4u16,
     KeyPackage = 5u16,
+    #[cfg(feature = "private_message")]
+    TargetedMessage = 6u16,
 }
 
 #[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
@@ -0,0 +1,367 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{collections::VecDeque, vec::Vec};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::MlsMessagePayload, message_processor::MessageProcessor, Group, ReceivedMessage,
+    },
+    MlsMessage,
+};
+
+/// Message dropped from a full [`FutureEpochBufferConfig`] buffer to make
+/// room for a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum EvictionPolicy {
+    /// Drop the message received first.
+    #[default]
+    DropOldest = 0u8,
+    /// Drop a message of the furthest epoch, which is the last one to become
+    /// processable. The new message is rejected if no buffered message is
+    /// further ahead.
+    DropFurthestEpoch = 1u8,
+    /// Keep the buffered messages and fail processing of the new one with
+    /// [`MlsError::FutureEpochBufferFull`].
+    RejectNew = 2u8,
+}
+
+/// Buffering of messages sent in epochs the group has not reached yet.
+///
+/// A message for a future epoch is usually received when the commit creating
+/// that epoch is delivered late. With buffering enabled through
+/// [`GroupConfigOverrides::with_future_epoch_buffer`](crate::group::GroupConfigOverrides::with_future_epoch_buffer),
+/// such messages are held by the group and replayed as soon as a commit
+/// brings the group to their epoch. The outcome of replayed messages is
+/// obtained with [`Group::take_replayed_messages`].
+///
+/// The buffer lives in memory only and is not written to storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FutureEpochBufferConfig {
+    /// Maximum number of buffered messages.
+    pub capacity: u32,
+    /// Maximum distance between the current epoch and the epoch of a
+    /// buffered message. Messages further ahead are rejected as before.
+    pub max_epoch_gap: u64,
+    /// Message dropped when the buffer is full.
+    pub eviction_policy: EvictionPolicy,
+}
+
+impl FutureEpochBufferConfig {
+    /// Buffer up to `capacity` messages for the next epoch.
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            capacity,
+            max_epoch_gap: 1,
+            eviction_policy: EvictionPolicy::default(),
+        }
+    }
+
+    pub fn with_max_epoch_gap(self, max_epoch_gap: u64) -> Self {
+        Self {
+            max_epoch_gap,
+            ..self
+        }
+    }
+
+    pub fn with_eviction_policy(self, eviction_policy: EvictionPolicy) -> Self {
+        Self {
+            eviction_policy,
+            ..self
+        }
+    }
+}
+
+/// Counters describing the activity of the future epoch message buffer of a
+/// group since it was created or loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FutureEpochBufferMetrics {
+    /// Number of messages currently buffered.
+    pub pending: usize,
+    /// Number of messages accepted into the buffer.
+    pub buffered: u64,
+    /// Number of buffered messages processed successfully after the group
+    /// reached their epoch.
+    pub replayed: u64,
+    /// Number of buffered messages that failed processing once replayed.
+    pub replay_failures: u64,
+    /// Number of buffered messages dropped by the eviction policy.
+    pub evicted: u64,
+    /// Number of messages rejected because the buffer was full.
+    pub rejected: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FutureEpochBuffer {
+    messages: VecDeque<(u64, MlsMessage)>,
+    replayed: Vec<ReceivedMessage>,
+    metrics: FutureEpochBufferMetrics,
+}
+
+impl FutureEpochBuffer {
+    fn insert(
+        &mut self,
+        config: &FutureEpochBufferConfig,
+        epoch: u64,
+        message: MlsMessage,
+    ) -> Result<(), MlsError> {
+        if self.messages.len() >= config.capacity as usize {
+            let evicted = match config.eviction_policy {
+                EvictionPolicy::DropOldest => (!self.messages.is_empty()).then_some(0),
+                EvictionPolicy::DropFurthestEpoch => self
+                    .messages
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (e, _))| *e > epoch)
+                    .max_by_key(|(_, (e, _))| *e)
+                    .map(|(i, _)| i),
+                EvictionPolicy::RejectNew => None,
+            };
+
+            let Some(evicted) = evicted else {
+                self.metrics.rejected += 1;
+                return Err(MlsError::FutureEpochBufferFull);
+            };
+
+            self.messages.remove(evicted);
+            self.metrics.evicted += 1;
+        }
+
+        self.messages.push_back((epoch, message));
+        self.metrics.buffered += 1;
+
+        Ok(())
+    }
+
+    /// Remove the first message that is no longer ahead of `epoch`.
+    fn take(&mut self, epoch: u64) -> Option<MlsMessage> {
+        let index = self.messages.iter().position(|(e, _)| *e <= epoch)?;
+
+        self.messages.remove(index).map(|(_, message)| message)
+    }
+
+    pub(crate) fn metrics(&self) -> FutureEpochBufferMetrics {
+        FutureEpochBufferMetrics {
+            pending: self.messages.len(),
+            ..self.metrics
+        }
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Buffer `message` if it was sent in a future epoch that buffering is
+    /// configured to hold. Returns the message back if it should be processed
+    /// normally.
+    pub(crate) fn buffer_future_epoch_message(
+        &mut self,
+        message: MlsMessage,
+    ) -> Result<BufferOutcome, MlsError> {
+        let Some(config) = self.config_overrides.future_epoch_buffer else {
+            return Ok(BufferOutcome::Process(message));
+        };
+
+        let context = &self.state.context;
+
+        let epoch = match &message.payload {
+            MlsMessagePayload::Plain(p) if p.content.group_id == context.group_id => {
+                p.content.epoch
+            }
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Cipher(c) if c.group_id == context.group_id => c.epoch,
+            _ => return Ok(BufferOutcome::Process(message)),
+        };
+
+        let ahead = epoch.saturating_sub(context.epoch);
+
+        if ahead == 0 || ahead > config.max_epoch_gap {
+            return Ok(BufferOutcome::Process(message));
+        }
+
+        self.future_epoch_buffer.insert(&config, epoch, message)?;
+
+        Ok(BufferOutcome::Buffered(epoch))
+    }
+
+    /// Process buffered messages the group caught up with, in the order they
+    /// were received. Replaying a commit may make more messages processable.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn replay_future_epoch_messages(&mut self) {
+        while let Some(message) = self.future_epoch_buffer.take(self.state.context.epoch) {
+            let res = MessageProcessor::process_incoming_message(
+                self,
+                message,
+                #[cfg(feature = "by_ref_proposal")]
+                true,
+            )
+            .await;
+
+            match res {
+                Ok(received) => {
+                    self.future_epoch_buffer.metrics.replayed += 1;
+                    self.future_epoch_buffer.replayed.push(received);
+                }
+                Err(_) => self.future_epoch_buffer.metrics.replay_failures += 1,
+            }
+        }
+    }
+
+    /// Messages from future epochs processed after the group reached their
+    /// epoch, in processing order. Messages that failed processing are
+    /// counted in [`FutureEpochBufferMetrics::replay_failures`] and not
+    /// returned.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn take_replayed_messages(&mut self) -> Vec<ReceivedMessage> {
+        core::mem::take(&mut self.future_epoch_buffer.replayed)
+    }
+
+    /// Activity of the future epoch message buffer configured with
+    /// [`GroupConfigOverrides::with_future_epoch_buffer`](crate::group::GroupConfigOverrides::with_future_epoch_buffer).
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn future_epoch_buffer_metrics(&self) -> FutureEpochBufferMetrics {
+        self.future_epoch_buffer.metrics()
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum BufferOutcome {
+    Buffered(u64),
+    Process(MlsMessage),
+}
+
+#[cfg(all(test, feature = "private_message"))]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            test_utils::{test_n_member_group, TestGroup},
+            GroupConfigOverrides, ReceivedMessage,
+        },
+    };
+
+    use super::{EvictionPolicy, FutureEpochBufferConfig};
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_groups(config: FutureEpochBufferConfig) -> (TestGroup, TestGroup) {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let bob = groups.pop().unwrap();
+        let alice = groups.pop().unwrap();
+
+        let overrides = GroupConfigOverrides::new().with_future_epoch_buffer(config);
+
+        let bob = TestGroup {
+            group: bob.group.with_config_overrides(overrides),
+        };
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn future_epoch_messages_are_replayed_after_commit() {
+        let (mut alice, mut bob) = test_groups(FutureEpochBufferConfig::new(4)).await;
+        let epoch = bob.current_epoch();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+
+        let message = alice
+            .encrypt_application_message(b"early", vec![])
+            .await
+            .unwrap();
+
+        let res = bob.process_incoming_message(message).await.unwrap();
+        assert_matches!(res, ReceivedMessage::Buffered(e) if e == epoch + 1);
+
+        let next_commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+
+        let message = alice
+            .encrypt_application_message(b"too early", vec![])
+            .await
+            .unwrap();
+
+        let res = bob.process_incoming_message(message).await;
+        assert!(res.is_err());
+
+        let res = bob.process_incoming_message(commit).await.unwrap();
+        assert_matches!(res, ReceivedMessage::Commit(_));
+
+        let replayed = bob.take_replayed_messages();
+
+        assert_matches!(
+            replayed.as_slice(),
+            [ReceivedMessage::ApplicationMessage(m)] if m.data() == b"early"
+        );
+
+        let metrics = bob.future_epoch_buffer_metrics();
+        assert_eq!(
+            (metrics.buffered, metrics.replayed, metrics.pending),
+            (1, 1, 0)
+        );
+
+        bob.process_incoming_message(next_commit).await.unwrap();
+        assert_eq!(bob.current_epoch(), alice.current_epoch());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn full_buffer_applies_eviction_policy() {
+        for policy in [EvictionPolicy::DropOldest, EvictionPolicy::RejectNew] {
+            let config = FutureEpochBufferConfig::new(1).with_eviction_policy(policy);
+            let (mut alice, mut bob) = test_groups(config).await;
+
+            let commit = alice.commit(vec![]).await.unwrap().commit_message;
+            alice.apply_pending_commit().await.unwrap();
+
+            let first = alice
+                .encrypt_application_message(b"first", vec![])
+                .await
+                .unwrap();
+
+            let second = alice
+                .encrypt_application_message(b"second", vec![])
+                .await
+                .unwrap();
+
+            bob.process_incoming_message(first).await.unwrap();
+            let res = bob.process_incoming_message(second).await;
+
+            bob.process_incoming_message(commit).await.unwrap();
+
+            let replayed = bob.take_replayed_messages();
+            let metrics = bob.future_epoch_buffer_metrics();
+
+            let expected: &[u8] = if policy == EvictionPolicy::DropOldest {
+                assert_matches!(res, Ok(ReceivedMessage::Buffered(_)));
+                assert_eq!((metrics.evicted, metrics.rejected), (1, 0));
+                b"second"
+            } else {
+                assert_matches!(res, Err(MlsError::FutureEpochBufferFull));
+                assert_eq!((metrics.evicted, metrics.rejected), (0, 1));
+                b"first"
+            };
+
+            assert_matches!(
+                replayed.as_slice(),
+                [ReceivedMessage::ApplicationMessage(m)] if m.data() == expected
+            );
+        }
+    }
+}
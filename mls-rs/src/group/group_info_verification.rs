@@ -0,0 +1,353 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::identity::SigningIdentity;
+
+#[cfg(feature = "by_ref_proposal")]
+use mls_rs_core::error::IntoAnyError;
+
+use crate::{
+    cipher_suite::CipherSuite,
+    client::MlsError,
+    client_config::ClientConfig,
+    extension::{ExternalPubExt, RatchetTreeExt, RequiredCapabilitiesExt},
+    group::{
+        cipher_suite_provider, external_commit::SignedTreeHead, validate_tree_joiner, ExportedTree,
+        GroupInfo,
+    },
+    protocol_version::ProtocolVersion,
+    signer::Signable,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::extension::ExternalSendersExt;
+
+/// Policy applied by
+/// [`Client::verify_group_info`](crate::Client::verify_group_info) on top of
+/// the checks required by the protocol.
+#[derive(Clone, Debug, Default)]
+pub struct GroupInfoTrustConfig {
+    cipher_suites: Option<Vec<CipherSuite>>,
+    signed_tree_head: Option<SignedTreeHead>,
+    tree_head_trust_anchors: Vec<SigningIdentity>,
+}
+
+impl GroupInfoTrustConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[must_use]
+    /// Only accept groups using one of `cipher_suites`. By default, any
+    /// cipher suite supported by the crypto provider is accepted.
+    pub fn with_cipher_suites(self, cipher_suites: Vec<CipherSuite>) -> Self {
+        Self {
+            cipher_suites: Some(cipher_suites),
+            ..self
+        }
+    }
+
+    #[must_use]
+    /// Require the ratchet tree to match `signed_tree_head`, see
+    /// [`ExternalCommitBuilder::with_signed_tree_head`](crate::group::external_commit::ExternalCommitBuilder::with_signed_tree_head).
+    pub fn with_signed_tree_head(self, signed_tree_head: SignedTreeHead) -> Self {
+        Self {
+            signed_tree_head: Some(signed_tree_head),
+            ..self
+        }
+    }
+
+    #[must_use]
+    /// Signing identities trusted to sign tree heads, see
+    /// [`ExternalCommitBuilder::with_tree_head_trust_anchors`](crate::group::external_commit::ExternalCommitBuilder::with_tree_head_trust_anchors).
+    pub fn with_tree_head_trust_anchors(self, trust_anchors: Vec<SigningIdentity>) -> Self {
+        Self {
+            tree_head_trust_anchors: trust_anchors,
+            ..self
+        }
+    }
+}
+
+/// Problem found in a group info by
+/// [`Client::verify_group_info`](crate::Client::verify_group_info).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GroupInfoIssue {
+    /// The protocol version is not supported by the client.
+    UnsupportedProtocolVersion(ProtocolVersion),
+    /// The cipher suite is not supported by the crypto provider. No further
+    /// checks are performed.
+    UnsupportedCipherSuite(CipherSuite),
+    /// The cipher suite is not allowed by the [`GroupInfoTrustConfig`].
+    CipherSuiteNotAllowed(CipherSuite),
+    /// The ratchet tree is missing or failed validation.
+    InvalidRatchetTree(MlsError),
+    /// The ratchet tree provided separately differs from the one in the
+    /// group info extensions. The tree in the extensions is used.
+    ConflictingRatchetTree,
+    /// The leaf of the signer is blank or outside of the ratchet tree.
+    SignerNotFound(u32),
+    /// The signature does not verify with the key of the signer's leaf.
+    InvalidSignature,
+    /// The group info does not contain an
+    /// [`ExternalPubExt`](crate::extension::built_in::ExternalPubExt)
+    /// required for external commits.
+    MissingExternalPub,
+    /// An extension of the group info or the group context could not be
+    /// decoded, an external sender was rejected by the identity provider or
+    /// the client does not support the required capabilities of the group.
+    InvalidExtensions(MlsError),
+    /// The ratchet tree does not match a tree head signed by a trust anchor.
+    UntrustedTreeHead(MlsError),
+}
+
+/// Result of [`Client::verify_group_info`](crate::Client::verify_group_info).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct GroupInfoReport {
+    pub group_id: Vec<u8>,
+    pub epoch: u64,
+    pub cipher_suite: CipherSuite,
+    pub signer: u32,
+    pub issues: Vec<GroupInfoIssue>,
+}
+
+impl GroupInfoReport {
+    /// Whether no issue was found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn verify_group_info<C: ClientConfig>(
+    config: &C,
+    protocol_version: ProtocolVersion,
+    group_info: &GroupInfo,
+    tree_data: Option<ExportedTree<'_>>,
+    trust_config: &GroupInfoTrustConfig,
+) -> Result<GroupInfoReport, MlsError> {
+    let context = &group_info.group_context;
+
+    let mut report = GroupInfoReport {
+        group_id: context.group_id.clone(),
+        epoch: context.epoch,
+        cipher_suite: context.cipher_suite,
+        signer: *group_info.signer,
+        issues: Vec::new(),
+    };
+
+    let issues = &mut report.issues;
+
+    if !config.version_supported(protocol_version) || context.protocol_version != protocol_version {
+        issues.push(GroupInfoIssue::UnsupportedProtocolVersion(protocol_version));
+    }
+
+    if let Some(allowed) = &trust_config.cipher_suites {
+        if !allowed.contains(&context.cipher_suite) {
+            issues.push(GroupInfoIssue::CipherSuiteNotAllowed(context.cipher_suite));
+        }
+    }
+
+    let Ok(cs) = cipher_suite_provider(config.crypto_provider(), context.cipher_suite) else {
+        issues.push(GroupInfoIssue::UnsupportedCipherSuite(context.cipher_suite));
+        return Ok(report);
+    };
+
+    let id_provider = config.identity_provider();
+
+    match group_info.extensions.get_as::<RatchetTreeExt>() {
+        Ok(Some(ext)) if matches!(&tree_data, Some(t) if *t != ext.tree_data) => {
+            issues.push(GroupInfoIssue::ConflictingRatchetTree)
+        }
+        Err(e) => issues.push(GroupInfoIssue::InvalidExtensions(e.into())),
+        _ => {}
+    }
+
+    match validate_tree_joiner(group_info, tree_data, &id_provider, &cs).await {
+        Ok(tree) => match tree.get_leaf_node(group_info.signer) {
+            Ok(signer) => {
+                let signature_key = &signer.signing_identity.signature_key;
+
+                if group_info.verify(&cs, signature_key, &()).await.is_err() {
+                    issues.push(GroupInfoIssue::InvalidSignature);
+                }
+            }
+            Err(_) => issues.push(GroupInfoIssue::SignerNotFound(*group_info.signer)),
+        },
+        Err(e) => issues.push(GroupInfoIssue::InvalidRatchetTree(e)),
+    }
+
+    match group_info.extensions.get_as::<ExternalPubExt>() {
+        Ok(Some(_)) => {}
+        Ok(None) => issues.push(GroupInfoIssue::MissingExternalPub),
+        Err(e) => issues.push(GroupInfoIssue::InvalidExtensions(e.into())),
+    }
+
+    if let Err(e) = check_required_capabilities(config, group_info) {
+        issues.push(GroupInfoIssue::InvalidExtensions(e));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    match context.extensions.get_as::<ExternalSendersExt>() {
+        Ok(Some(ext_senders)) => {
+            let res = ext_senders
+                .verify_all(&id_provider, None, &context.extensions)
+                .await;
+
+            if let Err(e) = res {
+                let e = MlsError::IdentityProviderError(e.into_any_error());
+                issues.push(GroupInfoIssue::InvalidExtensions(e));
+            }
+        }
+        Ok(None) => {}
+        Err(e) => issues.push(GroupInfoIssue::InvalidExtensions(e.into())),
+    }
+
+    if trust_config.signed_tree_head.is_some() || !trust_config.tree_head_trust_anchors.is_empty() {
+        let res = match &trust_config.signed_tree_head {
+            Some(tree_head) => {
+                tree_head
+                    .verify_for(context, &trust_config.tree_head_trust_anchors, &cs)
+                    .await
+            }
+            None => Err(MlsError::MissingSignedTreeHead),
+        };
+
+        if let Err(e) = res {
+            issues.push(GroupInfoIssue::UntrustedTreeHead(e));
+        }
+    }
+
+    Ok(report)
+}
+
+fn check_required_capabilities<C: ClientConfig>(
+    config: &C,
+    group_info: &GroupInfo,
+) -> Result<(), MlsError> {
+    let Some(required) = group_info
+        .group_context
+        .extensions
+        .get_as::<RequiredCapabilitiesExt>()?
+    else {
+        return Ok(());
+    };
+
+    let capabilities = config.capabilities();
+
+    if let Some(ext) = required
+        .extensions
+        .iter()
+        .find(|e| !capabilities.extensions.contains(e))
+    {
+        return Err(MlsError::RequiredExtensionNotFound(*ext));
+    }
+
+    if let Some(proposal) = required
+        .proposals
+        .iter()
+        .find(|p| !capabilities.proposals.contains(p))
+    {
+        return Err(MlsError::RequiredProposalNotFound(*proposal));
+    }
+
+    if let Some(credential) = required
+        .credentials
+        .iter()
+        .find(|c| !capabilities.credentials.contains(c))
+    {
+        return Err(MlsError::RequiredCredentialNotFound(*credential));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{
+        test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+    };
+    use crate::client::MlsError;
+    use crate::crypto::test_utils::TestCryptoProvider;
+    use crate::group::test_utils::test_group;
+
+    use super::{GroupInfoIssue, GroupInfoTrustConfig};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn verify_group_info_reports_issues() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let group_info = alice
+            .group_info_message_allowing_ext_commit(false)
+            .await
+            .unwrap();
+
+        let tree = alice.export_tree().into_owned();
+        let trust_config = GroupInfoTrustConfig::new();
+
+        let report = bob
+            .verify_group_info(&group_info, Some(tree.clone()), &trust_config)
+            .await
+            .unwrap();
+
+        assert!(report.is_ok());
+        assert_eq!(report.epoch, alice.current_epoch());
+
+        let report = bob
+            .verify_group_info(&group_info, None, &trust_config)
+            .await
+            .unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [GroupInfoIssue::InvalidRatchetTree(
+                MlsError::RatchetTreeNotFound
+            )]
+        );
+
+        let other_suite = TestCryptoProvider::all_supported_cipher_suites()
+            .into_iter()
+            .find(|cs| *cs != TEST_CIPHER_SUITE)
+            .unwrap();
+
+        let trust_config = GroupInfoTrustConfig::new()
+            .with_cipher_suites(vec![other_suite])
+            .with_tree_head_trust_anchors(vec![alice
+                .current_member_signing_identity()
+                .unwrap()
+                .clone()]);
+
+        let report = bob
+            .verify_group_info(&group_info, Some(tree), &trust_config)
+            .await
+            .unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [
+                GroupInfoIssue::CipherSuiteNotAllowed(cs),
+                GroupInfoIssue::UntrustedTreeHead(MlsError::MissingSignedTreeHead)
+            ] if *cs == TEST_CIPHER_SUITE
+        );
+
+        let group_info = alice.group_info_message(true).await.unwrap();
+
+        let report = bob
+            .verify_group_info(&group_info, None, &GroupInfoTrustConfig::new())
+            .await
+            .unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [GroupInfoIssue::MissingExternalPub]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Export-only HPKE contexts addressed to the leaf key of a group member.
+//!
+//! Applications can use these contexts to derive keys for auxiliary sealed
+//! channels, such as encrypted attachments addressed to a subset of the
+//! members, with the crypto provider and cipher suite of the group. The HPKE
+//! `info` is bound to the group, the epoch and the recipient, so that an
+//! encapsulation can't be replayed to another member or in another epoch.
+//!
+//! HPKE is used in base mode: an [`HpkeExportEncapsulation`] does not
+//! authenticate its sender. Applications that need to know the sender should
+//! deliver the encapsulation inside an MLS message.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider, HpkeContextR, HpkeContextS},
+    error::IntoAnyError,
+    secret::Secret,
+};
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::Group, tree_kem::node::LeafIndex,
+};
+
+const HPKE_EXPORT_LABEL: &[u8] = b"MLS 1.0 application HPKE export";
+
+type GroupHpkeExportSender<C> =
+    HpkeExportSender<<<C as ClientConfig>::CryptoProvider as CryptoProvider>::CipherSuiteProvider>;
+
+type GroupHpkeExportReceiver<C> = HpkeExportReceiver<
+    <<C as ClientConfig>::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
+>;
+
+#[derive(MlsSize, MlsEncode)]
+struct HpkeExportInfo<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    label: &'a [u8],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    recipient_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    info: &'a [u8],
+}
+
+/// KEM output sent by the creator of an [`HpkeExportSender`] to the
+/// recipient, which opens it with [`Group::hpke_export_receiver`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct HpkeExportEncapsulation {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    recipient_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    kem_output: Vec<u8>,
+}
+
+impl Debug for HpkeExportEncapsulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HpkeExportEncapsulation")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("recipient_index", &self.recipient_index)
+            .field(
+                "kem_output",
+                &mls_rs_core::debug::pretty_bytes(&self.kem_output),
+            )
+            .finish()
+    }
+}
+
+impl HpkeExportEncapsulation {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf index of the member the encapsulation is addressed to.
+    pub fn recipient_index(&self) -> u32 {
+        *self.recipient_index
+    }
+
+    fn info<'a>(&'a self, info: &'a [u8]) -> HpkeExportInfo<'a> {
+        HpkeExportInfo {
+            label: HPKE_EXPORT_LABEL,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            recipient_index: self.recipient_index,
+            info,
+        }
+    }
+}
+
+/// Sender side of an export-only HPKE context, created with
+/// [`Group::hpke_export_sender`].
+pub struct HpkeExportSender<P: CipherSuiteProvider> {
+    context: P::HpkeContextS,
+    encapsulation: HpkeExportEncapsulation,
+}
+
+impl<P: CipherSuiteProvider> Debug for HpkeExportSender<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HpkeExportSender")
+            .field("encapsulation", &self.encapsulation)
+            .finish()
+    }
+}
+
+impl<P: CipherSuiteProvider> HpkeExportSender<P> {
+    /// Encapsulation that must be delivered to the recipient.
+    pub fn encapsulation(&self) -> &HpkeExportEncapsulation {
+        &self.encapsulation
+    }
+
+    /// Export a secret of `len` bytes. The recipient exports the same secret
+    /// for the same `exporter_context`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Secret, MlsError> {
+        self.context
+            .export(exporter_context, len)
+            .await
+            .map(Into::into)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+/// Receiver side of an export-only HPKE context, created with
+/// [`Group::hpke_export_receiver`].
+pub struct HpkeExportReceiver<P: CipherSuiteProvider> {
+    context: P::HpkeContextR,
+}
+
+impl<P: CipherSuiteProvider> Debug for HpkeExportReceiver<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HpkeExportReceiver").finish()
+    }
+}
+
+impl<P: CipherSuiteProvider> HpkeExportReceiver<P> {
+    /// Export a secret of `len` bytes. See [`HpkeExportSender::export`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Secret, MlsError> {
+        self.context
+            .export(exporter_context, len)
+            .await
+            .map(Into::into)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Set up an export-only HPKE context to the leaf key of the member at
+    /// `recipient_index`.
+    ///
+    /// `info` is application specific and must be passed unchanged to
+    /// [`Group::hpke_export_receiver`] by the recipient, which has to be in
+    /// the same epoch.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn hpke_export_sender(
+        &self,
+        recipient_index: u32,
+        info: &[u8],
+    ) -> Result<GroupHpkeExportSender<C>, MlsError> {
+        let recipient_index = LeafIndex(recipient_index);
+        let recipient = self.current_epoch_tree().get_leaf_node(recipient_index)?;
+
+        let mut encapsulation = HpkeExportEncapsulation {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            recipient_index,
+            kem_output: Vec::new(),
+        };
+
+        let info = encapsulation.info(info).mls_encode_to_vec()?;
+
+        let (kem_output, context) = self
+            .cipher_suite_provider
+            .hpke_setup_s(&recipient.public_key, &info)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        encapsulation.kem_output = kem_output;
+
+        Ok(HpkeExportSender {
+            context,
+            encapsulation,
+        })
+    }
+
+    /// Set up the receiving side of an export-only HPKE context created by
+    /// another member with [`Group::hpke_export_sender`], using the secret
+    /// key of the local leaf.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn hpke_export_receiver(
+        &self,
+        encapsulation: &HpkeExportEncapsulation,
+        info: &[u8],
+    ) -> Result<GroupHpkeExportReceiver<C>, MlsError> {
+        if encapsulation.group_id != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if encapsulation.epoch != self.current_epoch() {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        if encapsulation.recipient_index != self.private_tree.self_index {
+            return Err(MlsError::HpkeExportRecipientMismatch);
+        }
+
+        let secret_key = self
+            .private_tree
+            .secret_keys
+            .first()
+            .and_then(Option::as_ref)
+            .ok_or(MlsError::InvalidTreeKemPrivateKey)?;
+
+        let public_key = &self.current_user_leaf_node()?.public_key;
+        let info = encapsulation.info(info).mls_encode_to_vec()?;
+
+        let context = self
+            .cipher_suite_provider
+            .hpke_setup_r(&encapsulation.kem_output, secret_key, public_key, &info)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        Ok(HpkeExportReceiver { context })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::client::MlsError;
+    use crate::group::test_utils::test_group;
+
+    use super::HpkeExportEncapsulation;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn hpke_export_round_trip() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (carol, commit) = alice.join("carol").await;
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let sender = alice.hpke_export_sender(1, b"attachment").await.unwrap();
+        let sent = sender.export(b"key", 32).await.unwrap();
+
+        let bytes = sender.encapsulation().to_bytes().unwrap();
+        let encapsulation = HpkeExportEncapsulation::from_bytes(&bytes).unwrap();
+        assert_eq!(encapsulation.recipient_index(), 1);
+
+        let receiver = bob
+            .hpke_export_receiver(&encapsulation, b"attachment")
+            .await
+            .unwrap();
+
+        let received = receiver.export(b"key", 32).await.unwrap();
+        assert_eq!(sent, received);
+
+        let other = receiver.export(b"other key", 32).await.unwrap();
+        assert_ne!(sent, other);
+
+        let receiver = bob
+            .hpke_export_receiver(&encapsulation, b"thumbnail")
+            .await
+            .unwrap();
+
+        let received = receiver.export(b"key", 32).await.unwrap();
+        assert_ne!(sent, received);
+
+        let res = carol
+            .hpke_export_receiver(&encapsulation, b"attachment")
+            .await;
+        assert_matches!(res, Err(MlsError::HpkeExportRecipientMismatch));
+    }
+}
@@ -7,3 +7,4 @@ mod passive_client;
 mod serialization;
 mod tree_kem;
 mod tree_modifications;
+mod welcome;
@@ -2,12 +2,15 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::vec;
 use alloc::vec::Vec;
 use mls_rs_codec::{MlsDecode, MlsEncode};
 
 use mls_rs_core::extension::ExtensionList;
 
 use crate::{
+    client::test_utils::TEST_CIPHER_SUITE,
+    crypto::test_utils::TestCryptoProvider,
     group::{
         framing::ContentType,
         proposal::{
@@ -16,56 +19,27 @@ use crate::{
         },
         Commit, GroupSecrets, MlsMessage,
     },
+    test_vectors::messages::{generate, MessagesTestCase},
     tree_kem::node::NodeVec,
 };
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
-struct TestCase {
-    #[serde(with = "hex::serde")]
-    mls_welcome: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    mls_group_info: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    mls_key_package: Vec<u8>,
-
-    #[serde(with = "hex::serde")]
-    ratchet_tree: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    group_secrets: Vec<u8>,
-
-    #[serde(with = "hex::serde")]
-    add_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    update_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    remove_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    pre_shared_key_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    re_init_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    external_init_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    group_context_extensions_proposal: Vec<u8>,
-
-    #[serde(with = "hex::serde")]
-    commit: Vec<u8>,
-
-    #[serde(with = "hex::serde")]
-    public_message_application: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    public_message_proposal: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    public_message_commit: Vec<u8>,
-    #[serde(with = "hex::serde")]
-    private_message: Vec<u8>,
-}
-
 // The test vector can be found here:
 // https://github.com/mlswg/mls-implementations/blob/main/test-vectors/messages.json
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
 async fn serialization() {
-    let test_cases: Vec<TestCase> = load_test_case_json!(serialization, Vec::<TestCase>::new());
+    #[cfg(not(mls_build_async))]
+    let test_cases: Vec<MessagesTestCase> = load_test_case_json!(
+        serialization,
+        vec![generate(&TestCryptoProvider::new(), TEST_CIPHER_SUITE).unwrap()]
+    );
+
+    #[cfg(mls_build_async)]
+    let test_cases: Vec<MessagesTestCase> = load_test_case_json!(
+        serialization,
+        vec![generate(&TestCryptoProvider::new(), TEST_CIPHER_SUITE)
+            .await
+            .unwrap()]
+    );
 
     for test_case in test_cases.into_iter() {
         let message = MlsMessage::from_bytes(&test_case.mls_welcome).unwrap();
@@ -167,3 +141,14 @@ async fn serialization() {
         message.into_ciphertext().unwrap();
     }
 }
+
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn unsupported_cipher_suite_is_rejected() {
+    let cipher_suite = crate::CipherSuite::new(0xfefe);
+    let res = generate(&TestCryptoProvider::new(), cipher_suite).await;
+
+    assert_matches::assert_matches!(
+        res,
+        Err(crate::client::MlsError::UnsupportedCipherSuite(cs)) if cs == cipher_suite
+    );
+}
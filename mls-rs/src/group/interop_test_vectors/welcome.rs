@@ -0,0 +1,143 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::MlsDecode;
+use mls_rs_core::crypto::{
+    CipherSuite, CryptoProvider, DefaultEpochSecretsVault, HpkeSecretKey, SignaturePublicKey,
+};
+
+use crate::{
+    crypto::test_utils::{try_test_cipher_suite_provider, TestCryptoProvider},
+    group::{
+        key_schedule::{KeySchedule, WelcomeSecret},
+        GroupInfo, GroupSecrets,
+    },
+    psk::secret::PskSecret,
+    signer::Signable,
+    test_vectors::welcome::{generate, WelcomeTestCase},
+    tree_kem::hpke_encryption::HpkeEncryptable,
+    MlsMessage,
+};
+
+// The test vector can be found here:
+// https://github.com/mlswg/mls-implementations/blob/main/test-vectors/welcome.json
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn interop_welcome() {
+    #[cfg(not(mls_build_async))]
+    let test_cases: Vec<WelcomeTestCase> =
+        load_test_case_json!(interop_welcome, generate(&TestCryptoProvider::new()));
+
+    #[cfg(mls_build_async)]
+    let test_cases: Vec<WelcomeTestCase> =
+        load_test_case_json!(interop_welcome, generate(&TestCryptoProvider::new()).await);
+
+    for test_case in test_cases {
+        let Some(cs) = try_test_cipher_suite_provider(test_case.cipher_suite) else {
+            continue;
+        };
+
+        let key_package = MlsMessage::from_bytes(&test_case.key_package)
+            .unwrap()
+            .into_key_package()
+            .unwrap();
+
+        let welcome = MlsMessage::from_bytes(&test_case.welcome)
+            .unwrap()
+            .into_welcome()
+            .unwrap();
+
+        let key_package_ref = key_package.to_reference(&cs).await.unwrap();
+
+        let secrets = welcome
+            .secrets
+            .iter()
+            .find(|s| s.new_member == key_package_ref)
+            .unwrap();
+
+        let group_secrets = GroupSecrets::decrypt(
+            &cs,
+            &HpkeSecretKey::from(test_case.init_priv),
+            &key_package.hpke_init_key,
+            &welcome.encrypted_group_info,
+            &secrets.encrypted_group_secrets,
+        )
+        .await
+        .unwrap();
+
+        let psk_secret = PskSecret::new(&cs);
+
+        let welcome_secret =
+            WelcomeSecret::from_joiner_secret(&cs, &group_secrets.joiner_secret, &psk_secret)
+                .await
+                .unwrap();
+
+        let group_info = welcome_secret
+            .decrypt(&welcome.encrypted_group_info)
+            .await
+            .unwrap();
+
+        let group_info = GroupInfo::mls_decode(&mut &**group_info).unwrap();
+
+        group_info
+            .verify(&cs, &SignaturePublicKey::from(test_case.signer_pub), &())
+            .await
+            .unwrap();
+
+        let key_schedule = KeySchedule::from_joiner(
+            &cs,
+            &DefaultEpochSecretsVault,
+            &group_secrets.joiner_secret,
+            &group_info.group_context,
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            32,
+            &psk_secret,
+        )
+        .await
+        .unwrap();
+
+        let tag_matches = group_info
+            .confirmation_tag
+            .matches(
+                &key_schedule.confirmation_key,
+                &group_info.group_context.confirmed_transcript_hash,
+                &cs,
+            )
+            .await
+            .unwrap();
+
+        assert!(tag_matches);
+    }
+}
+
+#[derive(Clone)]
+struct ProviderWithUnavailableSuite(TestCryptoProvider);
+
+impl CryptoProvider for ProviderWithUnavailableSuite {
+    type CipherSuiteProvider = <TestCryptoProvider as CryptoProvider>::CipherSuiteProvider;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        let mut cipher_suites = self.0.supported_cipher_suites();
+        cipher_suites.push(CipherSuite::new(0xfefe));
+        cipher_suites
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        self.0.cipher_suite_provider(cipher_suite)
+    }
+}
+
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn unavailable_cipher_suites_are_skipped() {
+    let crypto_provider = ProviderWithUnavailableSuite(TestCryptoProvider::new());
+    let test_cases = generate(&crypto_provider).await;
+
+    assert_eq!(
+        test_cases.len(),
+        crypto_provider.0.supported_cipher_suites().len()
+    );
+}
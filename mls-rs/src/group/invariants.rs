@@ -0,0 +1,161 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{transcript_hash::InterimTranscriptHash, Group},
+};
+
+/// Inconsistency found in the state of a group by [`Group::check_invariants`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvariantViolation {
+    /// The tree hash of the ratchet tree does not match the tree hash in the
+    /// group context.
+    TreeHashMismatch,
+    /// The secret tree of the current epoch was not created for the number of
+    /// leaves in the ratchet tree.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    SecretTreeSizeMismatch { secret_tree: u32, public_tree: u32 },
+    /// The interim transcript hash was not derived from the confirmed
+    /// transcript hash and the confirmation tag of the current epoch.
+    InterimTranscriptHashMismatch,
+    /// The leaf of this member is blank in the ratchet tree.
+    MissingOwnLeaf(u32),
+}
+
+/// Result of [`Group::check_invariants`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InvariantReport {
+    pub violations: Vec<InvariantViolation>,
+}
+
+impl InvariantReport {
+    /// Whether no inconsistency was found.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Validate the internal consistency of the group state.
+    ///
+    /// This recomputes the tree hash and the interim transcript hash of the
+    /// current epoch and is meant to catch state corrupted by a storage
+    /// provider. In debug builds with the `invariant_checks` feature, the
+    /// group panics if the check fails after loading state or changing epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn check_invariants(&self) -> Result<InvariantReport, MlsError> {
+        let mut violations = Vec::new();
+
+        let tree_hash = self
+            .state
+            .public_tree
+            .clone()
+            .tree_hash(&self.cipher_suite_provider)
+            .await?;
+
+        if tree_hash != self.state.context.tree_hash {
+            violations.push(InvariantViolation::TreeHashMismatch);
+        }
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        {
+            let secret_tree = *self.epoch_secrets.secret_tree.leaf_count();
+            let public_tree = self.state.public_tree.total_leaf_count();
+
+            if secret_tree != public_tree {
+                violations.push(InvariantViolation::SecretTreeSizeMismatch {
+                    secret_tree,
+                    public_tree,
+                });
+            }
+        }
+
+        let interim_transcript_hash = InterimTranscriptHash::create(
+            &self.cipher_suite_provider,
+            &self.state.context.confirmed_transcript_hash,
+            &self.state.confirmation_tag,
+        )
+        .await?;
+
+        if interim_transcript_hash != self.state.interim_transcript_hash {
+            violations.push(InvariantViolation::InterimTranscriptHashMismatch);
+        }
+
+        let self_index = self.private_tree.self_index;
+
+        if self.state.public_tree.get_leaf_node(self_index).is_err() {
+            violations.push(InvariantViolation::MissingOwnLeaf(*self_index));
+        }
+
+        Ok(InvariantReport { violations })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn debug_assert_invariants(&self) -> Result<(), MlsError> {
+        #[cfg(debug_assertions)]
+        {
+            let report = self.check_invariants().await?;
+            assert!(
+                report.is_ok(),
+                "group state invariants violated: {report:?}"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    use super::InvariantViolation;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn consistent_group_passes_invariant_checks() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = bob.commit(vec![]).await.unwrap().commit_message;
+        bob.apply_pending_commit().await.unwrap();
+        alice.process_message(commit).await.unwrap();
+
+        for group in [&alice, &bob] {
+            let report = group.check_invariants().await.unwrap();
+            assert!(report.is_ok());
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn corrupted_state_is_reported() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        alice.state.context.tree_hash = vec![0; 32];
+        alice.state.interim_transcript_hash = vec![1; 32].into();
+
+        let report = alice.check_invariants().await.unwrap();
+
+        assert_eq!(
+            report.violations,
+            vec![
+                InvariantViolation::TreeHashMismatch,
+                InvariantViolation::InterimTranscriptHashMismatch
+            ]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Audit of the deletion of message keys derived from the secret tree, for
+//! security reviews and tests of forward secrecy.
+//!
+//! Once enabled with [`Group::enable_key_deletion_audit`], every message key
+//! handed out by the secret tree of the current epoch, to encrypt or decrypt
+//! a message, is recorded with the time at which it was removed from the
+//! group state. Only the hash of the key is recorded, never the key itself.
+//! Keys of skipped generations retained for out of order messages are only
+//! recorded once used. The audit is kept in memory and is not part of the
+//! stored group state.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError, time::MlsTime};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{secret_tree::KeyType, Group, MessageKey},
+    tree_kem::node::LeafIndex,
+};
+
+/// Message key removed from the secret tree, see [`KeyDeletionAudit`].
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeletedKeyRecord {
+    pub epoch: u64,
+    /// Leaf index of the sender of the messages protected by the key.
+    pub leaf_index: u32,
+    pub key_type: KeyType,
+    pub generation: u32,
+    /// Hash of the key, computed with the cipher suite of the group.
+    pub key_hash: Vec<u8>,
+    pub deleted_at: MlsTime,
+}
+
+impl Debug for DeletedKeyRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeletedKeyRecord")
+            .field("epoch", &self.epoch)
+            .field("leaf_index", &self.leaf_index)
+            .field("key_type", &self.key_type)
+            .field("generation", &self.generation)
+            .field(
+                "key_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.key_hash),
+            )
+            .field("deleted_at", &self.deleted_at)
+            .finish()
+    }
+}
+
+/// Record of the message keys deleted by a group, see the
+/// [module level](self) documentation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyDeletionAudit {
+    records: Vec<DeletedKeyRecord>,
+}
+
+impl KeyDeletionAudit {
+    pub fn records(&self) -> &[DeletedKeyRecord] {
+        &self.records
+    }
+
+    /// Record of the deletion of the key of `generation` used by the sender
+    /// at `leaf_index` in `epoch`, if the key was deleted.
+    pub fn deleted_key(
+        &self,
+        epoch: u64,
+        leaf_index: u32,
+        key_type: KeyType,
+        generation: u32,
+    ) -> Option<&DeletedKeyRecord> {
+        self.records.iter().find(|r| {
+            r.epoch == epoch
+                && r.leaf_index == leaf_index
+                && r.key_type == key_type
+                && r.generation == generation
+        })
+    }
+
+    /// Whether the key of `generation` used by the sender at `leaf_index` in
+    /// `epoch` was deleted at or before `time`.
+    pub fn was_deleted_before(
+        &self,
+        epoch: u64,
+        leaf_index: u32,
+        key_type: KeyType,
+        generation: u32,
+        time: MlsTime,
+    ) -> bool {
+        self.deleted_key(epoch, leaf_index, key_type, generation)
+            .map_or(false, |r| r.deleted_at <= time)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn record<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        epoch: u64,
+        leaf_index: LeafIndex,
+        key_type: KeyType,
+        key: &MessageKey,
+    ) -> Result<(), MlsError> {
+        let key_hash = cipher_suite_provider
+            .hash(key.key())
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        self.records.push(DeletedKeyRecord {
+            epoch,
+            leaf_index: *leaf_index,
+            key_type,
+            generation: key.generation(),
+            key_hash,
+            deleted_at: MlsTime::now(),
+        });
+
+        Ok(())
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Start recording the message keys deleted by this group. See
+    /// [`KeyDeletionAudit`].
+    pub fn enable_key_deletion_audit(&mut self) {
+        self.key_deletion_audit.get_or_insert_with(Default::default);
+    }
+
+    /// Message keys deleted since [`Group::enable_key_deletion_audit`] was
+    /// called, or `None` if the audit is not enabled.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn key_deletion_audit(&self) -> Option<&KeyDeletionAudit> {
+        self.key_deletion_audit.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use mls_rs_core::time::MlsTime;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{secret_tree::KeyType, test_utils::test_group},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn consumed_message_keys_are_recorded() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let epoch = alice.current_epoch();
+
+        alice.enable_key_deletion_audit();
+        bob.enable_key_deletion_audit();
+
+        let before = MlsTime::now();
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        bob.process_incoming_message(message).await.unwrap();
+
+        let sent = alice.key_deletion_audit().unwrap();
+        let received = bob.key_deletion_audit().unwrap();
+
+        assert_eq!(sent.records().len(), 1);
+        assert_eq!(received.records().len(), 1);
+
+        let record = received
+            .deleted_key(epoch, 0, KeyType::Application, 0)
+            .unwrap();
+
+        assert_eq!(record.key_hash, sent.records()[0].key_hash);
+
+        assert!(received.was_deleted_before(epoch, 0, KeyType::Application, 0, MlsTime::now()));
+        assert!(!received.was_deleted_before(epoch, 0, KeyType::Application, 1, MlsTime::now()));
+        assert!(record.deleted_at >= before);
+    }
+}
@@ -18,7 +18,11 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
-use mls_rs_core::error::IntoAnyError;
+use mls_rs_core::{
+    crypto::{EpochSecretsVault, SecretHandle},
+    error::IntoAnyError,
+    secret::SecretBytes,
+};
 use zeroize::Zeroizing;
 
 use crate::crypto::{HpkeContextR, HpkeContextS, HpkePublicKey, HpkeSecretKey};
@@ -26,43 +30,30 @@ use crate::crypto::{HpkeContextR, HpkeContextS, HpkePublicKey, HpkeSecretKey};
 use super::epoch::{EpochSecrets, SenderDataSecret};
 use super::message_signature::AuthenticatedContent;
 
+/// Secrets of the key schedule that outlive the derivation of an epoch. All
+/// of them except the authentication secret, which is public to members
+/// through [`Group::epoch_authenticator`](crate::Group::epoch_authenticator),
+/// are held by the [`EpochSecretsVault`] of the client.
 #[derive(Clone, PartialEq, Eq, Default, MlsEncode, MlsDecode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeySchedule {
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
-    exporter_secret: Zeroizing<Vec<u8>>,
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
-    pub authentication_secret: Zeroizing<Vec<u8>>,
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
-    external_secret: Zeroizing<Vec<u8>>,
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
-    membership_key: Zeroizing<Vec<u8>>,
-    init_secret: InitSecret,
+    exporter_secret: SecretHandle,
+    pub authentication_secret: SecretBytes,
+    external_secret: SecretHandle,
+    membership_key: SecretHandle,
+    init_secret: SecretHandle,
 }
 
 impl Debug for KeySchedule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("KeySchedule")
-            .field(
-                "exporter_secret",
-                &mls_rs_core::debug::pretty_bytes(&self.exporter_secret),
-            )
+            .field("exporter_secret", &self.exporter_secret)
             .field(
                 "authentication_secret",
                 &mls_rs_core::debug::pretty_bytes(&self.authentication_secret),
             )
-            .field(
-                "external_secret",
-                &mls_rs_core::debug::pretty_bytes(&self.external_secret),
-            )
-            .field(
-                "membership_key",
-                &mls_rs_core::debug::pretty_bytes(&self.membership_key),
-            )
+            .field("external_secret", &self.external_secret)
+            .field("membership_key", &self.membership_key)
             .field("init_secret", &self.init_secret)
             .finish()
     }
@@ -76,7 +67,7 @@ pub(crate) struct KeyScheduleDerivationResult {
 }
 
 impl KeySchedule {
-    pub fn new(init_secret: InitSecret) -> Self {
+    pub fn new(init_secret: SecretHandle) -> Self {
         KeySchedule {
             init_secret,
             ..Default::default()
@@ -84,23 +75,27 @@ impl KeySchedule {
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn derive_for_external<P: CipherSuiteProvider>(
+    pub async fn derive_for_external<P: CipherSuiteProvider, V: EpochSecretsVault>(
         &self,
         kem_output: &[u8],
         cipher_suite: &P,
+        vault: &V,
     ) -> Result<KeySchedule, MlsError> {
-        let (secret, public) = self.get_external_key_pair(cipher_suite).await?;
+        let (secret, public) = self.get_external_key_pair(cipher_suite, vault).await?;
 
         let init_secret =
             InitSecret::decode_for_external(cipher_suite, kem_output, &secret, &public).await?;
 
-        Ok(KeySchedule::new(init_secret))
+        Ok(KeySchedule::new(
+            init_secret.import(cipher_suite, vault).await?,
+        ))
     }
 
     /// Returns the derived epoch as well as the joiner secret required for building welcome
     /// messages
+    #[allow(clippy::too_many_arguments)]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub(crate) async fn from_key_schedule<P: CipherSuiteProvider>(
+    pub(crate) async fn from_key_schedule<P: CipherSuiteProvider, V: EpochSecretsVault>(
         last_key_schedule: &KeySchedule,
         commit_secret: &PathSecret,
         context: &GroupContext,
@@ -108,24 +103,35 @@ impl KeySchedule {
         secret_tree_size: u32,
         psk_secret: &PskSecret,
         cipher_suite_provider: &P,
+        vault: &V,
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
-        let joiner_seed = cipher_suite_provider
-            .kdf_extract(&last_key_schedule.init_secret.0, commit_secret)
+        let joiner_seed = vault
+            .extract(
+                cipher_suite_provider,
+                &last_key_schedule.init_secret,
+                commit_secret,
+            )
             .await
-            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+            .map_err(vault_error)?;
 
-        let joiner_secret = kdf_expand_with_label(
+        let joiner = vault_expand_with_label(
+            vault,
             cipher_suite_provider,
             &joiner_seed,
             b"joiner",
             &context.mls_encode_to_vec()?,
             None,
         )
-        .await?
-        .into();
+        .await?;
+
+        let joiner_secret = JoinerSecret(vault.export(&joiner).await.map_err(vault_error)?);
+
+        release(vault, joiner_seed).await?;
+        release(vault, joiner).await?;
 
         let key_schedule_result = Self::from_joiner(
             cipher_suite_provider,
+            vault,
             &joiner_secret,
             context,
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
@@ -143,25 +149,44 @@ impl KeySchedule {
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub(crate) async fn from_joiner<P: CipherSuiteProvider>(
+    pub(crate) async fn from_joiner<P: CipherSuiteProvider, V: EpochSecretsVault>(
         cipher_suite_provider: &P,
+        vault: &V,
         joiner_secret: &JoinerSecret,
         context: &GroupContext,
         #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
         secret_tree_size: u32,
         psk_secret: &PskSecret,
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
-        let epoch_seed =
-            get_pre_epoch_secret(cipher_suite_provider, psk_secret, joiner_secret).await?;
+        let joiner = vault
+            .import(cipher_suite_provider, &joiner_secret.0)
+            .await
+            .map_err(vault_error)?;
+
+        let epoch_seed = vault
+            .extract(cipher_suite_provider, &joiner, psk_secret)
+            .await
+            .map_err(vault_error)?;
+
         let context = context.mls_encode_to_vec()?;
 
-        let epoch_secret =
-            kdf_expand_with_label(cipher_suite_provider, &epoch_seed, b"epoch", &context, None)
-                .await?;
+        let epoch_secret = vault_expand_with_label(
+            vault,
+            cipher_suite_provider,
+            &epoch_seed,
+            b"epoch",
+            &context,
+            None,
+        )
+        .await?;
+
+        release(vault, joiner).await?;
+        release(vault, epoch_seed).await?;
 
         Self::from_epoch_secret(
             cipher_suite_provider,
-            &epoch_secret,
+            vault,
+            epoch_secret,
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             secret_tree_size,
         )
@@ -169,19 +194,24 @@ impl KeySchedule {
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub(crate) async fn from_random_epoch_secret<P: CipherSuiteProvider>(
+    pub(crate) async fn from_random_epoch_secret<P: CipherSuiteProvider, V: EpochSecretsVault>(
         cipher_suite_provider: &P,
+        vault: &V,
         #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
         secret_tree_size: u32,
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
-        let epoch_secret = cipher_suite_provider
-            .random_bytes_vec(cipher_suite_provider.kdf_extract_size())
-            .map(Zeroizing::new)
-            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+        let epoch_secret = vault
+            .generate(
+                cipher_suite_provider,
+                cipher_suite_provider.kdf_extract_size(),
+            )
+            .await
+            .map_err(vault_error)?;
 
         Self::from_epoch_secret(
             cipher_suite_provider,
-            &epoch_secret,
+            vault,
+            epoch_secret,
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             secret_tree_size,
         )
@@ -189,94 +219,161 @@ impl KeySchedule {
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn from_epoch_secret<P: CipherSuiteProvider>(
+    async fn from_epoch_secret<P: CipherSuiteProvider, V: EpochSecretsVault>(
         cipher_suite_provider: &P,
-        epoch_secret: &[u8],
+        vault: &V,
+        epoch_secret: SecretHandle,
         #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
         secret_tree_size: u32,
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
-        let secrets_producer = SecretsProducer::new(cipher_suite_provider, epoch_secret);
+        let secrets_producer = SecretsProducer::new(cipher_suite_provider, vault, &epoch_secret);
 
         let epoch_secrets = EpochSecrets {
             #[cfg(feature = "psk")]
-            resumption_secret: PreSharedKey::from(secrets_producer.derive(b"resumption").await?),
+            resumption_secret: PreSharedKey::from(
+                secrets_producer.derive_exported(b"resumption").await?,
+            ),
             sender_data_secret: SenderDataSecret::from(
-                secrets_producer.derive(b"sender data").await?,
+                secrets_producer.derive_exported(b"sender data").await?,
             ),
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             secret_tree: SecretTree::new(
                 secret_tree_size,
-                secrets_producer.derive(b"encryption").await?,
+                secrets_producer.derive_exported(b"encryption").await?,
             ),
         };
 
         let key_schedule = Self {
             exporter_secret: secrets_producer.derive(b"exporter").await?,
-            authentication_secret: secrets_producer.derive(b"authentication").await?,
+            authentication_secret: secrets_producer
+                .derive_exported(b"authentication")
+                .await?
+                .into(),
             external_secret: secrets_producer.derive(b"external").await?,
             membership_key: secrets_producer.derive(b"membership").await?,
-            init_secret: InitSecret(secrets_producer.derive(b"init").await?),
+            init_secret: secrets_producer.derive(b"init").await?,
         };
 
+        let confirmation_key = secrets_producer.derive_exported(b"confirm").await?;
+
+        release(vault, epoch_secret).await?;
+
         Ok(KeyScheduleDerivationResult {
             key_schedule,
-            confirmation_key: secrets_producer.derive(b"confirm").await?,
+            confirmation_key,
             joiner_secret: Zeroizing::new(vec![]).into(),
             epoch_secrets,
         })
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn export_secret<P: CipherSuiteProvider>(
+    pub async fn export_secret<P: CipherSuiteProvider, V: EpochSecretsVault>(
         &self,
         label: &[u8],
         context: &[u8],
         len: usize,
         cipher_suite: &P,
+        vault: &V,
     ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
-        let secret = kdf_derive_secret(cipher_suite, &self.exporter_secret, label).await?;
+        let secret =
+            vault_expand_with_label(vault, cipher_suite, &self.exporter_secret, label, &[], None)
+                .await?;
 
         let context_hash = cipher_suite
             .hash(context)
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-        kdf_expand_with_label(cipher_suite, &secret, b"exported", &context_hash, Some(len)).await
+        let exported = vault_expand_with_label(
+            vault,
+            cipher_suite,
+            &secret,
+            b"exported",
+            &context_hash,
+            Some(len),
+        )
+        .await?;
+
+        let bytes = vault.export(&exported).await.map_err(vault_error)?;
+
+        release(vault, secret).await?;
+        release(vault, exported).await?;
+
+        Ok(bytes)
+    }
+
+    /// Release the secrets held by the vault for this key schedule, once no
+    /// stored group state refers to them anymore.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn release<V: EpochSecretsVault>(self, vault: &V) -> Result<(), MlsError> {
+        let handles = [
+            self.exporter_secret,
+            self.external_secret,
+            self.membership_key,
+            self.init_secret,
+        ];
+
+        // Key schedules created from an init secret only hold that secret
+        for handle in handles.into_iter().filter(|h| !h.as_bytes().is_empty()) {
+            release(vault, handle).await?;
+        }
+
+        Ok(())
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn get_membership_tag<P: CipherSuiteProvider>(
+    pub async fn membership_key<V: EpochSecretsVault>(
+        &self,
+        vault: &V,
+    ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        vault
+            .export(&self.membership_key)
+            .await
+            .map_err(vault_error)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn get_membership_tag<P: CipherSuiteProvider, V: EpochSecretsVault>(
         &self,
         content: &AuthenticatedContent,
         context: &GroupContext,
         cipher_suite_provider: &P,
+        vault: &V,
     ) -> Result<MembershipTag, MlsError> {
         MembershipTag::create(
             content,
             context,
-            &self.membership_key,
+            &self.membership_key(vault).await?,
             cipher_suite_provider,
         )
         .await
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn get_external_key_pair<P: CipherSuiteProvider>(
+    pub async fn get_external_key_pair<P: CipherSuiteProvider, V: EpochSecretsVault>(
         &self,
         cipher_suite: &P,
+        vault: &V,
     ) -> Result<(HpkeSecretKey, HpkePublicKey), MlsError> {
+        let external_secret = vault
+            .export(&self.external_secret)
+            .await
+            .map_err(vault_error)?;
+
         cipher_suite
-            .kem_derive(&self.external_secret)
+            .kem_derive(&external_secret)
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn get_external_key_pair_ext<P: CipherSuiteProvider>(
+    pub async fn get_external_key_pair_ext<P: CipherSuiteProvider, V: EpochSecretsVault>(
         &self,
         cipher_suite: &P,
+        vault: &V,
     ) -> Result<ExternalPubExt, MlsError> {
-        let (_external_secret, external_pub) = self.get_external_key_pair(cipher_suite).await?;
+        let (_external_secret, external_pub) =
+            self.get_external_key_pair(cipher_suite, vault).await?;
 
         Ok(ExternalPubExt { external_pub })
     }
@@ -328,6 +425,40 @@ pub(crate) async fn kdf_derive_secret<P: CipherSuiteProvider>(
     kdf_expand_with_label(cipher_suite_provider, secret, label, &[], None).await
 }
 
+/// [`kdf_expand_with_label`] performed by `vault` on the secret referred to
+/// by `secret`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn vault_expand_with_label<P: CipherSuiteProvider, V: EpochSecretsVault>(
+    vault: &V,
+    cipher_suite_provider: &P,
+    secret: &SecretHandle,
+    label: &[u8],
+    context: &[u8],
+    len: Option<usize>,
+) -> Result<SecretHandle, MlsError> {
+    let len = len.unwrap_or(cipher_suite_provider.kdf_extract_size());
+    let label = Label::new(len as u16, label, context);
+
+    vault
+        .expand(
+            cipher_suite_provider,
+            secret,
+            &label.mls_encode_to_vec()?,
+            len,
+        )
+        .await
+        .map_err(vault_error)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn release<V: EpochSecretsVault>(vault: &V, handle: SecretHandle) -> Result<(), MlsError> {
+    vault.release(handle).await.map_err(vault_error)
+}
+
+fn vault_error<E: IntoAnyError>(e: E) -> MlsError {
+    MlsError::EpochSecretsVaultError(e.into_any_error())
+}
+
 #[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 pub(crate) struct JoinerSecret(#[mls_codec(with = "mls_rs_codec::byte_vec")] Zeroizing<Vec<u8>>);
 
@@ -357,15 +488,17 @@ pub(crate) async fn get_pre_epoch_secret<P: CipherSuiteProvider>(
         .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
 }
 
-struct SecretsProducer<'a, P: CipherSuiteProvider> {
+struct SecretsProducer<'a, P: CipherSuiteProvider, V: EpochSecretsVault> {
     cipher_suite_provider: &'a P,
-    epoch_secret: &'a [u8],
+    vault: &'a V,
+    epoch_secret: &'a SecretHandle,
 }
 
-impl<'a, P: CipherSuiteProvider> SecretsProducer<'a, P> {
-    fn new(cipher_suite_provider: &'a P, epoch_secret: &'a [u8]) -> Self {
+impl<'a, P: CipherSuiteProvider, V: EpochSecretsVault> SecretsProducer<'a, P, V> {
+    fn new(cipher_suite_provider: &'a P, vault: &'a V, epoch_secret: &'a SecretHandle) -> Self {
         Self {
             cipher_suite_provider,
+            vault,
             epoch_secret,
         }
     }
@@ -374,8 +507,26 @@ impl<'a, P: CipherSuiteProvider> SecretsProducer<'a, P> {
     // KDF extract size but then inputs secrets as MAC keys etc, therefore, we require that these
     // lengths match in the crypto provider
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn derive(&self, label: &[u8]) -> Result<Zeroizing<Vec<u8>>, MlsError> {
-        kdf_derive_secret(self.cipher_suite_provider, self.epoch_secret, label).await
+    async fn derive(&self, label: &[u8]) -> Result<SecretHandle, MlsError> {
+        vault_expand_with_label(
+            self.vault,
+            self.cipher_suite_provider,
+            self.epoch_secret,
+            label,
+            &[],
+            None,
+        )
+        .await
+    }
+
+    /// Derive a secret used outside of the vault.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn derive_exported(&self, label: &[u8]) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let handle = self.derive(label).await?;
+        let secret = self.vault.export(&handle).await.map_err(vault_error)?;
+        release(self.vault, handle).await?;
+
+        Ok(secret)
     }
 }
 
@@ -383,11 +534,7 @@ const EXPORTER_CONTEXT: &[u8] = b"MLS 1.0 external init secret";
 
 #[derive(Clone, Eq, PartialEq, MlsEncode, MlsDecode, MlsSize, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct InitSecret(
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
-    Zeroizing<Vec<u8>>,
-);
+pub struct InitSecret(SecretBytes);
 
 impl Debug for InitSecret {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -414,7 +561,7 @@ impl InitSecret {
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-        Ok((InitSecret(Zeroizing::new(init_secret)), kem_output))
+        Ok((InitSecret(init_secret.into()), kem_output))
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -432,10 +579,22 @@ impl InitSecret {
         context
             .export(EXPORTER_CONTEXT, cipher_suite.kdf_extract_size())
             .await
-            .map(Zeroizing::new)
-            .map(InitSecret)
+            .map(|secret| InitSecret(secret.into()))
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
     }
+
+    /// Move the init secret into `vault`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn import<P: CipherSuiteProvider, V: EpochSecretsVault>(
+        self,
+        cipher_suite: &P,
+        vault: &V,
+    ) -> Result<SecretHandle, MlsError> {
+        vault
+            .import(cipher_suite, &self.0)
+            .await
+            .map_err(vault_error)
+    }
 }
 
 pub(crate) struct WelcomeSecret<'a, P: CipherSuiteProvider> {
@@ -502,428 +661,463 @@ async fn get_welcome_secret<P: CipherSuiteProvider>(
     kdf_derive_secret(cipher_suite, &epoch_seed, b"welcome").await
 }
 
-#[cfg(test)]
-pub(crate) mod test_utils {
-    use alloc::vec;
-    use alloc::vec::Vec;
-    use mls_rs_core::crypto::CipherSuiteProvider;
-    use zeroize::Zeroizing;
-
-    use crate::{cipher_suite::CipherSuite, crypto::test_utils::test_cipher_suite_provider};
-
-    use super::{InitSecret, JoinerSecret, KeySchedule};
-
-    #[cfg(all(feature = "rfc_compliant", not(mls_build_async)))]
-    use mls_rs_core::error::IntoAnyError;
-
-    #[cfg(all(feature = "rfc_compliant", not(mls_build_async)))]
-    use super::MlsError;
-
-    impl From<JoinerSecret> for Vec<u8> {
-        fn from(mut value: JoinerSecret) -> Self {
-            core::mem::take(&mut value.0)
-        }
-    }
-
-    pub(crate) fn get_test_key_schedule(cipher_suite: CipherSuite) -> KeySchedule {
-        let key_size = test_cipher_suite_provider(cipher_suite).kdf_extract_size();
-        let fake_secret = Zeroizing::new(vec![1u8; key_size]);
-
-        KeySchedule {
-            exporter_secret: fake_secret.clone(),
-            authentication_secret: fake_secret.clone(),
-            external_secret: fake_secret.clone(),
-            membership_key: fake_secret,
-            init_secret: InitSecret::new(vec![0u8; key_size]),
-        }
-    }
-
-    impl InitSecret {
-        pub fn new(init_secret: Vec<u8>) -> Self {
-            InitSecret(Zeroizing::new(init_secret))
-        }
-
-        #[cfg(all(feature = "rfc_compliant", test, not(mls_build_async)))]
-        #[cfg_attr(coverage_nightly, coverage(off))]
-        pub fn random<P: CipherSuiteProvider>(cipher_suite: &P) -> Result<Self, MlsError> {
-            cipher_suite
-                .random_bytes_vec(cipher_suite.kdf_extract_size())
-                .map(Zeroizing::new)
-                .map(InitSecret)
-                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
-        }
-    }
-
-    #[cfg(feature = "rfc_compliant")]
-    impl KeySchedule {
-        pub fn set_membership_key(&mut self, key: Vec<u8>) {
-            self.membership_key = Zeroizing::new(key)
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::client::test_utils::TEST_PROTOCOL_VERSION;
-    use crate::crypto::test_utils::try_test_cipher_suite_provider;
-    use crate::group::key_schedule::{
-        get_welcome_secret, kdf_derive_secret, kdf_expand_with_label,
+#[cfg(any(test, feature = "test_vectors"))]
+pub(crate) mod test_vectors {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
     };
-    use crate::group::GroupContext;
-    use alloc::string::String;
-    use alloc::vec::Vec;
     use mls_rs_codec::MlsEncode;
-    use mls_rs_core::crypto::CipherSuiteProvider;
-    use mls_rs_core::extension::ExtensionList;
-
-    #[cfg(all(not(mls_build_async), feature = "rfc_compliant"))]
-    use crate::{
-        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
-        group::{
-            key_schedule::KeyScheduleDerivationResult, test_utils::random_bytes, InitSecret,
-            PskSecret,
-        },
+    use mls_rs_core::{
+        crypto::{CipherSuiteProvider, DefaultEpochSecretsVault},
+        error::IntoAnyError,
+        protocol_version::ProtocolVersion,
     };
 
-    #[cfg(all(not(mls_build_async), feature = "rfc_compliant"))]
-    use alloc::{string::ToString, vec};
-
-    #[cfg(target_arch = "wasm32")]
-    use wasm_bindgen_test::wasm_bindgen_test as test;
-    use zeroize::Zeroizing;
+    use crate::{client::MlsError, group::GroupContext, psk::secret::PskSecret};
 
-    use super::test_utils::get_test_key_schedule;
-    use super::KeySchedule;
+    use super::{get_welcome_secret, KeySchedule, KeyScheduleDerivationResult};
 
-    #[derive(serde::Deserialize, serde::Serialize)]
-    struct TestCase {
-        cipher_suite: u16,
+    /// Key schedule test vector in the format of `key-schedule.json`.
+    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+    pub struct KeyScheduleTestCase {
+        pub cipher_suite: u16,
         #[serde(with = "hex::serde")]
-        group_id: Vec<u8>,
+        pub group_id: Vec<u8>,
         #[serde(with = "hex::serde")]
-        initial_init_secret: Vec<u8>,
-        epochs: Vec<KeyScheduleEpoch>,
+        pub initial_init_secret: Vec<u8>,
+        pub epochs: Vec<KeyScheduleEpoch>,
     }
 
-    #[derive(serde::Deserialize, serde::Serialize)]
-    struct KeyScheduleEpoch {
+    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+    pub struct KeyScheduleEpoch {
         #[serde(with = "hex::serde")]
-        commit_secret: Vec<u8>,
+        pub commit_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        psk_secret: Vec<u8>,
+        pub psk_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        confirmed_transcript_hash: Vec<u8>,
+        pub confirmed_transcript_hash: Vec<u8>,
         #[serde(with = "hex::serde")]
-        tree_hash: Vec<u8>,
+        pub tree_hash: Vec<u8>,
 
         #[serde(with = "hex::serde")]
-        group_context: Vec<u8>,
+        pub group_context: Vec<u8>,
 
         #[serde(with = "hex::serde")]
-        joiner_secret: Vec<u8>,
+        pub joiner_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        welcome_secret: Vec<u8>,
+        pub welcome_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        init_secret: Vec<u8>,
+        pub init_secret: Vec<u8>,
 
         #[serde(with = "hex::serde")]
-        sender_data_secret: Vec<u8>,
-        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        pub sender_data_secret: Vec<u8>,
+        /// Empty if neither the `private_message` nor the
+        /// `secret_tree_access` feature is enabled.
         #[serde(with = "hex::serde")]
-        encryption_secret: Vec<u8>,
+        pub encryption_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        exporter_secret: Vec<u8>,
+        pub exporter_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        epoch_authenticator: Vec<u8>,
+        pub epoch_authenticator: Vec<u8>,
         #[serde(with = "hex::serde")]
-        external_secret: Vec<u8>,
+        pub external_secret: Vec<u8>,
         #[serde(with = "hex::serde")]
-        confirmation_key: Vec<u8>,
+        pub confirmation_key: Vec<u8>,
         #[serde(with = "hex::serde")]
-        membership_key: Vec<u8>,
-        #[cfg(feature = "psk")]
+        pub membership_key: Vec<u8>,
+        /// Empty if the `psk` feature is not enabled.
         #[serde(with = "hex::serde")]
-        resumption_psk: Vec<u8>,
+        pub resumption_psk: Vec<u8>,
 
         #[serde(with = "hex::serde")]
-        external_pub: Vec<u8>,
+        pub external_pub: Vec<u8>,
 
-        exporter: KeyScheduleExporter,
+        pub exporter: KeyScheduleExporter,
     }
 
-    #[derive(serde::Deserialize, serde::Serialize)]
-    struct KeyScheduleExporter {
-        label: String,
+    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+    pub struct KeyScheduleExporter {
+        pub label: String,
         #[serde(with = "hex::serde")]
-        context: Vec<u8>,
-        length: usize,
+        pub context: Vec<u8>,
+        pub length: usize,
         #[serde(with = "hex::serde")]
-        secret: Vec<u8>,
+        pub secret: Vec<u8>,
     }
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_key_schedule() {
-        let test_cases: Vec<TestCase> =
-            load_test_case_json!(key_schedule_test_vector, generate_test_vector());
+    /// Generate a key schedule test vector covering two epochs with random
+    /// inputs for the cipher suite of `cs`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate<P: CipherSuiteProvider>(cs: &P) -> Result<KeyScheduleTestCase, MlsError> {
+        let key_size = cs.kdf_extract_size();
+
+        let mut group_context = GroupContext {
+            protocol_version: ProtocolVersion::MLS_10,
+            cipher_suite: cs.cipher_suite(),
+            group_id: b"my group 5".to_vec(),
+            epoch: 0,
+            tree_hash: random_bytes(cs, key_size)?,
+            confirmed_transcript_hash: random_bytes(cs, key_size)?.into(),
+            extensions: Default::default(),
+        };
 
-        for test_case in test_cases {
-            let Some(cs_provider) = try_test_cipher_suite_provider(test_case.cipher_suite) else {
-                continue;
-            };
+        let initial_init_secret = random_bytes(cs, key_size)?;
 
-            let mut key_schedule = get_test_key_schedule(cs_provider.cipher_suite());
-            key_schedule.init_secret.0 = Zeroizing::new(test_case.initial_init_secret);
-
-            for (i, epoch) in test_case.epochs.into_iter().enumerate() {
-                let context = GroupContext {
-                    protocol_version: TEST_PROTOCOL_VERSION,
-                    cipher_suite: cs_provider.cipher_suite(),
-                    group_id: test_case.group_id.clone(),
-                    epoch: i as u64,
-                    tree_hash: epoch.tree_hash,
-                    confirmed_transcript_hash: epoch.confirmed_transcript_hash.into(),
-                    extensions: ExtensionList::new(),
-                };
-
-                assert_eq!(context.mls_encode_to_vec().unwrap(), epoch.group_context);
-
-                let psk = epoch.psk_secret.into();
-                let commit = epoch.commit_secret.into();
-
-                let key_schedule_res = KeySchedule::from_key_schedule(
-                    &key_schedule,
-                    &commit,
-                    &context,
-                    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-                    32,
-                    &psk,
-                    &cs_provider,
-                )
-                .await
-                .unwrap();
+        let mut key_schedule = KeySchedule::new(initial_init_secret.clone().into());
 
-                key_schedule = key_schedule_res.key_schedule;
+        let mut epochs = Vec::new();
 
-                let welcome =
-                    get_welcome_secret(&cs_provider, &key_schedule_res.joiner_secret, &psk)
-                        .await
-                        .unwrap();
+        for _ in 0..2 {
+            let commit_secret = random_bytes(cs, key_size)?;
+            let psk_secret = PskSecret::new(cs);
 
-                assert_eq!(*welcome, epoch.welcome_secret);
+            let key_schedule_res = KeySchedule::from_key_schedule(
+                &key_schedule,
+                &commit_secret.clone().into(),
+                &group_context,
+                #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+                32,
+                &psk_secret,
+                cs,
+                &DefaultEpochSecretsVault,
+            )
+            .await?;
 
-                let expected: Vec<u8> = key_schedule_res.joiner_secret.into();
-                assert_eq!(epoch.joiner_secret, expected);
+            epochs.push(
+                KeyScheduleEpoch::new(
+                    &key_schedule_res,
+                    &psk_secret,
+                    commit_secret,
+                    &group_context,
+                    cs,
+                )
+                .await?,
+            );
 
-                assert_eq!(&key_schedule.init_secret.0.to_vec(), &epoch.init_secret);
+            key_schedule = key_schedule_res.key_schedule;
 
-                assert_eq!(
-                    epoch.sender_data_secret,
-                    *key_schedule_res.epoch_secrets.sender_data_secret.to_vec()
-                );
+            group_context.epoch += 1;
+            group_context.confirmed_transcript_hash = random_bytes(cs, key_size)?.into();
+            group_context.tree_hash = random_bytes(cs, key_size)?;
+        }
 
-                #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-                assert_eq!(
-                    epoch.encryption_secret,
-                    *key_schedule_res.epoch_secrets.secret_tree.get_root_secret()
-                );
+        Ok(KeyScheduleTestCase {
+            cipher_suite: cs.cipher_suite().into(),
+            group_id: group_context.group_id,
+            initial_init_secret,
+            epochs,
+        })
+    }
 
-                assert_eq!(epoch.exporter_secret, key_schedule.exporter_secret.to_vec());
+    impl KeyScheduleEpoch {
+        #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+        async fn new<P: CipherSuiteProvider>(
+            key_schedule_res: &KeyScheduleDerivationResult,
+            psk_secret: &PskSecret,
+            commit_secret: Vec<u8>,
+            group_context: &GroupContext,
+            cs: &P,
+        ) -> Result<Self, MlsError> {
+            let key_schedule = &key_schedule_res.key_schedule;
 
-                assert_eq!(
-                    epoch.epoch_authenticator,
-                    key_schedule.authentication_secret.to_vec()
-                );
+            let (_external_sec, external_pub) = key_schedule
+                .get_external_key_pair(cs, &DefaultEpochSecretsVault)
+                .await?;
 
-                assert_eq!(epoch.external_secret, key_schedule.external_secret.to_vec());
+            let mut exporter = KeyScheduleExporter {
+                label: "exporter label 15".to_string(),
+                context: b"exporter context".to_vec(),
+                length: 64,
+                secret: Vec::new(),
+            };
 
-                assert_eq!(
-                    epoch.confirmation_key,
-                    key_schedule_res.confirmation_key.to_vec()
-                );
+            exporter.secret = key_schedule
+                .export_secret(
+                    exporter.label.as_bytes(),
+                    &exporter.context,
+                    exporter.length,
+                    cs,
+                    &DefaultEpochSecretsVault,
+                )
+                .await?
+                .to_vec();
 
-                assert_eq!(epoch.membership_key, key_schedule.membership_key.to_vec());
+            let welcome_secret =
+                get_welcome_secret(cs, &key_schedule_res.joiner_secret, psk_secret)
+                    .await?
+                    .to_vec();
 
+            Ok(KeyScheduleEpoch {
+                commit_secret,
+                welcome_secret,
+                psk_secret: psk_secret.to_vec(),
+                group_context: group_context.mls_encode_to_vec()?,
+                joiner_secret: key_schedule_res.joiner_secret.0.to_vec(),
+                init_secret: key_schedule.init_secret.as_bytes().to_vec(),
+                sender_data_secret: key_schedule_res.epoch_secrets.sender_data_secret.to_vec(),
+                #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+                encryption_secret: key_schedule_res.epoch_secrets.secret_tree.get_root_secret(),
+                #[cfg(not(any(feature = "secret_tree_access", feature = "private_message")))]
+                encryption_secret: Vec::new(),
+                exporter_secret: key_schedule.exporter_secret.as_bytes().to_vec(),
+                epoch_authenticator: key_schedule.authentication_secret.to_vec(),
+                external_secret: key_schedule.external_secret.as_bytes().to_vec(),
+                confirmation_key: key_schedule_res.confirmation_key.to_vec(),
+                membership_key: key_schedule.membership_key.as_bytes().to_vec(),
                 #[cfg(feature = "psk")]
-                {
-                    let expected: Vec<u8> =
-                        key_schedule_res.epoch_secrets.resumption_secret.to_vec();
+                resumption_psk: key_schedule_res.epoch_secrets.resumption_secret.to_vec(),
+                #[cfg(not(feature = "psk"))]
+                resumption_psk: Vec::new(),
+                external_pub: external_pub.to_vec(),
+                exporter,
+                confirmed_transcript_hash: group_context.confirmed_transcript_hash.to_vec(),
+                tree_hash: group_context.tree_hash.clone(),
+            })
+        }
+    }
 
-                    assert_eq!(epoch.resumption_psk, expected);
-                }
+    fn random_bytes<P: CipherSuiteProvider>(cs: &P, len: usize) -> Result<Vec<u8>, MlsError> {
+        cs.random_bytes_vec(len)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use mls_rs_core::{
+        crypto::{CipherSuiteProvider, SecretHandle},
+        secret::SecretBytes,
+    };
 
-                let (_external_sec, external_pub) = key_schedule
-                    .get_external_key_pair(&cs_provider)
-                    .await
-                    .unwrap();
+    use crate::{cipher_suite::CipherSuite, crypto::test_utils::test_cipher_suite_provider};
 
-                assert_eq!(epoch.external_pub, *external_pub);
+    use super::{JoinerSecret, KeySchedule};
 
-                let exp = epoch.exporter;
+    impl From<JoinerSecret> for Vec<u8> {
+        fn from(mut value: JoinerSecret) -> Self {
+            core::mem::take(&mut value.0)
+        }
+    }
 
-                let exported = key_schedule
-                    .export_secret(exp.label.as_bytes(), &exp.context, exp.length, &cs_provider)
-                    .await
-                    .unwrap();
+    pub(crate) fn get_test_key_schedule(cipher_suite: CipherSuite) -> KeySchedule {
+        let key_size = test_cipher_suite_provider(cipher_suite).kdf_extract_size();
+        let fake_secret = SecretHandle::new(vec![1u8; key_size]);
 
-                assert_eq!(exported.to_vec(), exp.secret);
-            }
+        KeySchedule {
+            exporter_secret: fake_secret.clone(),
+            authentication_secret: SecretBytes::new(vec![1u8; key_size]),
+            external_secret: fake_secret.clone(),
+            membership_key: fake_secret,
+            init_secret: SecretHandle::new(vec![0u8; key_size]),
+        }
+    }
+
+    impl KeySchedule {
+        /// Membership key of a key schedule derived with the default vault.
+        pub fn membership_key_bytes(&self) -> &[u8] {
+            self.membership_key.as_bytes()
         }
     }
 
+    #[cfg(feature = "rfc_compliant")]
+    impl KeySchedule {
+        pub fn set_membership_key(&mut self, key: Vec<u8>) {
+            self.membership_key = key.into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::crypto::test_utils::try_test_cipher_suite_provider;
+    use crate::group::key_schedule::{
+        get_welcome_secret, kdf_derive_secret, kdf_expand_with_label,
+    };
+    use crate::group::GroupContext;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use mls_rs_codec::MlsEncode;
+    use mls_rs_core::crypto::{CipherSuiteProvider, DefaultEpochSecretsVault};
+    use mls_rs_core::extension::ExtensionList;
+
     #[cfg(all(not(mls_build_async), feature = "rfc_compliant"))]
-    #[cfg_attr(coverage_nightly, coverage(off))]
-    fn generate_test_vector() -> Vec<TestCase> {
-        let mut test_cases = vec![];
+    use crate::crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider};
 
-        for cipher_suite in TestCryptoProvider::all_supported_cipher_suites() {
-            let cs_provider = test_cipher_suite_provider(cipher_suite);
-            let key_size = cs_provider.kdf_extract_size();
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
 
-            let mut group_context = GroupContext {
+    use super::test_utils::get_test_key_schedule;
+    use super::test_vectors::KeyScheduleTestCase as TestCase;
+    use super::KeySchedule;
+
+    #[cfg(feature = "std")]
+    use crate::crypto::test_utils::test_cipher_suite_provider as test_cs;
+    #[cfg(feature = "std")]
+    use crate::group::test_utils::get_test_group_context;
+    #[cfg(feature = "std")]
+    use crate::psk::secret::PskSecret;
+    #[cfg(feature = "std")]
+    use crate::tree_kem::path_secret::PathSecret;
+    #[cfg(feature = "std")]
+    use mls_rs_core::crypto::{EpochSecretsVault, SecretHandle};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_key_schedule() {
+        let test_cases: Vec<TestCase> =
+            load_test_case_json!(key_schedule_test_vector, generate_test_vector());
+
+        for test_case in test_cases {
+            verify_test_case(test_case).await;
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify_test_case(test_case: TestCase) {
+        let Some(cs_provider) = try_test_cipher_suite_provider(test_case.cipher_suite) else {
+            return;
+        };
+
+        let mut key_schedule = get_test_key_schedule(cs_provider.cipher_suite());
+        key_schedule.init_secret = test_case.initial_init_secret.into();
+
+        for (i, epoch) in test_case.epochs.into_iter().enumerate() {
+            let context = GroupContext {
                 protocol_version: TEST_PROTOCOL_VERSION,
                 cipher_suite: cs_provider.cipher_suite(),
-                group_id: b"my group 5".to_vec(),
-                epoch: 0,
-                tree_hash: random_bytes(key_size),
-                confirmed_transcript_hash: random_bytes(key_size).into(),
-                extensions: Default::default(),
+                group_id: test_case.group_id.clone(),
+                epoch: i as u64,
+                tree_hash: epoch.tree_hash,
+                confirmed_transcript_hash: epoch.confirmed_transcript_hash.into(),
+                extensions: ExtensionList::new(),
             };
 
-            let initial_init_secret = InitSecret::random(&cs_provider).unwrap();
-            let mut key_schedule = get_test_key_schedule(cs_provider.cipher_suite());
-            key_schedule.init_secret = initial_init_secret.clone();
+            assert_eq!(context.mls_encode_to_vec().unwrap(), epoch.group_context);
 
-            let commit_secret = random_bytes(key_size).into();
-            let psk_secret = PskSecret::new(&cs_provider);
+            let psk = epoch.psk_secret.into();
+            let commit = epoch.commit_secret.into();
 
             let key_schedule_res = KeySchedule::from_key_schedule(
                 &key_schedule,
-                &commit_secret,
-                &group_context,
+                &commit,
+                &context,
                 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
                 32,
-                &psk_secret,
+                &psk,
                 &cs_provider,
+                &DefaultEpochSecretsVault,
             )
+            .await
             .unwrap();
 
-            key_schedule = key_schedule_res.key_schedule.clone();
+            key_schedule = key_schedule_res.key_schedule;
 
-            let epoch1 = KeyScheduleEpoch::new(
-                key_schedule_res,
-                psk_secret,
-                commit_secret.to_vec(),
-                &group_context,
-                &cs_provider,
+            let welcome = get_welcome_secret(&cs_provider, &key_schedule_res.joiner_secret, &psk)
+                .await
+                .unwrap();
+
+            assert_eq!(*welcome, epoch.welcome_secret);
+
+            let expected: Vec<u8> = key_schedule_res.joiner_secret.into();
+            assert_eq!(epoch.joiner_secret, expected);
+
+            assert_eq!(
+                &key_schedule.init_secret.as_bytes().to_vec(),
+                &epoch.init_secret
             );
 
-            group_context.epoch += 1;
-            group_context.confirmed_transcript_hash = random_bytes(key_size).into();
-            group_context.tree_hash = random_bytes(key_size);
+            assert_eq!(
+                epoch.sender_data_secret,
+                *key_schedule_res.epoch_secrets.sender_data_secret.to_vec()
+            );
 
-            let commit_secret = random_bytes(key_size).into();
-            let psk_secret = PskSecret::new(&cs_provider);
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            assert_eq!(
+                epoch.encryption_secret,
+                *key_schedule_res.epoch_secrets.secret_tree.get_root_secret()
+            );
 
-            let key_schedule_res = KeySchedule::from_key_schedule(
-                &key_schedule,
-                &commit_secret,
-                &group_context,
-                #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-                32,
-                &psk_secret,
-                &cs_provider,
-            )
-            .unwrap();
+            assert_eq!(
+                epoch.exporter_secret,
+                key_schedule.exporter_secret.as_bytes().to_vec()
+            );
 
-            let epoch2 = KeyScheduleEpoch::new(
-                key_schedule_res,
-                psk_secret,
-                commit_secret.to_vec(),
-                &group_context,
-                &cs_provider,
+            assert_eq!(
+                epoch.epoch_authenticator,
+                key_schedule.authentication_secret.to_vec()
             );
 
-            let test_case = TestCase {
-                cipher_suite: cs_provider.cipher_suite().into(),
-                group_id: group_context.group_id.clone(),
-                initial_init_secret: initial_init_secret.0.to_vec(),
-                epochs: vec![epoch1, epoch2],
-            };
+            assert_eq!(
+                epoch.external_secret,
+                key_schedule.external_secret.as_bytes().to_vec()
+            );
 
-            test_cases.push(test_case);
-        }
+            assert_eq!(
+                epoch.confirmation_key,
+                key_schedule_res.confirmation_key.to_vec()
+            );
 
-        test_cases
-    }
+            assert_eq!(
+                epoch.membership_key,
+                key_schedule.membership_key.as_bytes().to_vec()
+            );
 
-    #[cfg(not(all(not(mls_build_async), feature = "rfc_compliant")))]
-    fn generate_test_vector() -> Vec<TestCase> {
-        panic!("Tests cannot be generated in async mode");
-    }
+            #[cfg(feature = "psk")]
+            {
+                let expected: Vec<u8> = key_schedule_res.epoch_secrets.resumption_secret.to_vec();
 
-    #[cfg(all(not(mls_build_async), feature = "rfc_compliant"))]
-    impl KeyScheduleEpoch {
-        #[cfg_attr(coverage_nightly, coverage(off))]
-        fn new<P: CipherSuiteProvider>(
-            key_schedule_res: KeyScheduleDerivationResult,
-            psk_secret: PskSecret,
-            commit_secret: Vec<u8>,
-            group_context: &GroupContext,
-            cs: &P,
-        ) -> Self {
-            let (_external_sec, external_pub) = key_schedule_res
-                .key_schedule
-                .get_external_key_pair(cs)
+                assert_eq!(epoch.resumption_psk, expected);
+            }
+
+            let (_external_sec, external_pub) = key_schedule
+                .get_external_key_pair(&cs_provider, &DefaultEpochSecretsVault)
+                .await
                 .unwrap();
 
-            let mut exporter = KeyScheduleExporter {
-                label: "exporter label 15".to_string(),
-                context: b"exporter context".to_vec(),
-                length: 64,
-                secret: vec![],
-            };
+            assert_eq!(epoch.external_pub, *external_pub);
+
+            let exp = epoch.exporter;
 
-            exporter.secret = key_schedule_res
-                .key_schedule
+            let exported = key_schedule
                 .export_secret(
-                    exporter.label.as_bytes(),
-                    &exporter.context,
-                    exporter.length,
-                    cs,
+                    exp.label.as_bytes(),
+                    &exp.context,
+                    exp.length,
+                    &cs_provider,
+                    &DefaultEpochSecretsVault,
                 )
-                .unwrap()
-                .to_vec();
-
-            let welcome_secret =
-                get_welcome_secret(cs, &key_schedule_res.joiner_secret, &psk_secret)
-                    .unwrap()
-                    .to_vec();
+                .await
+                .unwrap();
 
-            KeyScheduleEpoch {
-                commit_secret,
-                welcome_secret,
-                psk_secret: psk_secret.to_vec(),
-                group_context: group_context.mls_encode_to_vec().unwrap(),
-                joiner_secret: key_schedule_res.joiner_secret.into(),
-                init_secret: key_schedule_res.key_schedule.init_secret.0.to_vec(),
-                sender_data_secret: key_schedule_res.epoch_secrets.sender_data_secret.to_vec(),
-                #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-                encryption_secret: key_schedule_res.epoch_secrets.secret_tree.get_root_secret(),
-                exporter_secret: key_schedule_res.key_schedule.exporter_secret.to_vec(),
-                epoch_authenticator: key_schedule_res.key_schedule.authentication_secret.to_vec(),
-                external_secret: key_schedule_res.key_schedule.external_secret.to_vec(),
-                confirmation_key: key_schedule_res.confirmation_key.to_vec(),
-                membership_key: key_schedule_res.key_schedule.membership_key.to_vec(),
-                #[cfg(feature = "psk")]
-                resumption_psk: key_schedule_res.epoch_secrets.resumption_secret.to_vec(),
-                external_pub: external_pub.to_vec(),
-                exporter,
-                confirmed_transcript_hash: group_context.confirmed_transcript_hash.to_vec(),
-                tree_hash: group_context.tree_hash.clone(),
-            }
+            assert_eq!(exported.to_vec(), exp.secret);
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn generated_test_vector_is_valid() {
+        let cs = crate::crypto::test_utils::test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let test_case = super::test_vectors::generate(&cs).await.unwrap();
+
+        verify_test_case(test_case).await;
+    }
+
+    #[cfg(all(not(mls_build_async), feature = "rfc_compliant"))]
+    #[cfg_attr(coverage_nightly, coverage(off))]
+    fn generate_test_vector() -> Vec<TestCase> {
+        TestCryptoProvider::all_supported_cipher_suites()
+            .into_iter()
+            .map(|cs| super::test_vectors::generate(&test_cipher_suite_provider(cs)).unwrap())
+            .collect()
+    }
+
+    #[cfg(not(all(not(mls_build_async), feature = "rfc_compliant")))]
+    fn generate_test_vector() -> Vec<TestCase> {
+        panic!("Tests cannot be generated in async mode");
+    }
+
     #[derive(Debug, serde::Serialize, serde::Deserialize)]
     struct ExpandWithLabelTestCase {
         #[serde(with = "hex::serde")]
@@ -985,4 +1179,126 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "std")]
+    type VaultSecrets = std::collections::HashMap<Vec<u8>, zeroize::Zeroizing<Vec<u8>>>;
+
+    #[cfg(feature = "std")]
+    #[derive(Clone, Default)]
+    struct OpaqueVault {
+        secrets: std::sync::Arc<std::sync::Mutex<VaultSecrets>>,
+    }
+
+    #[cfg(feature = "std")]
+    impl OpaqueVault {
+        fn insert(&self, secret: zeroize::Zeroizing<Vec<u8>>) -> SecretHandle {
+            let mut secrets = self.secrets.lock().unwrap();
+            let handle = (secrets.len() as u64).to_be_bytes().to_vec();
+            secrets.insert(handle.clone(), secret);
+            handle.into()
+        }
+
+        fn get(&self, handle: &SecretHandle) -> zeroize::Zeroizing<Vec<u8>> {
+            self.secrets.lock().unwrap()[handle.as_bytes()].clone()
+        }
+    }
+
+    // Handles are indexes into a map instead of the secrets themselves.
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl EpochSecretsVault for OpaqueVault {
+        type Error = core::convert::Infallible;
+
+        async fn import<P: CipherSuiteProvider>(
+            &self,
+            _: &P,
+            secret: &[u8],
+        ) -> Result<SecretHandle, Self::Error> {
+            Ok(self.insert(secret.to_vec().into()))
+        }
+
+        async fn generate<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            len: usize,
+        ) -> Result<SecretHandle, Self::Error> {
+            Ok(self.insert(cs.random_bytes_vec(len).unwrap().into()))
+        }
+
+        async fn extract<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            salt: &SecretHandle,
+            ikm: &[u8],
+        ) -> Result<SecretHandle, Self::Error> {
+            let prk = cs.kdf_extract(&self.get(salt), ikm).await.unwrap();
+            Ok(self.insert(prk))
+        }
+
+        async fn expand<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            prk: &SecretHandle,
+            info: &[u8],
+            len: usize,
+        ) -> Result<SecretHandle, Self::Error> {
+            let okm = cs.kdf_expand(&self.get(prk), info, len).await.unwrap();
+            Ok(self.insert(okm))
+        }
+
+        async fn export(
+            &self,
+            handle: &SecretHandle,
+        ) -> Result<zeroize::Zeroizing<Vec<u8>>, Self::Error> {
+            Ok(self.get(handle))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn derive_with_vault<V: EpochSecretsVault>(
+        vault: &V,
+        init_secret: SecretHandle,
+    ) -> (SecretHandle, Vec<u8>) {
+        let cs = test_cs(TEST_CIPHER_SUITE);
+        let commit_secret = PathSecret::from(vec![1u8; cs.kdf_extract_size()]);
+
+        let res = KeySchedule::from_key_schedule(
+            &KeySchedule::new(init_secret),
+            &commit_secret,
+            &get_test_group_context(1, TEST_CIPHER_SUITE).await,
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            2,
+            &PskSecret::new(&cs),
+            &cs,
+            vault,
+        )
+        .await
+        .unwrap();
+
+        let exported = res
+            .key_schedule
+            .export_secret(b"label", b"context", 16, &cs, vault)
+            .await
+            .unwrap();
+
+        (res.key_schedule.init_secret, exported.to_vec())
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn opaque_vault_derives_same_secrets_as_default() {
+        let vault = OpaqueVault::default();
+        let init_secret = vault.insert(vec![2u8; 32].into());
+
+        let (opaque_init, opaque_exported) = derive_with_vault(&vault, init_secret).await;
+
+        let (init, exported) =
+            derive_with_vault(&DefaultEpochSecretsVault, vec![2u8; 32].into()).await;
+
+        assert_ne!(opaque_init, init);
+        assert_eq!(vault.get(&opaque_init).as_slice(), init.as_bytes());
+        assert_eq!(opaque_exported, exported);
+    }
 }
@@ -0,0 +1,159 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{snapshot::Snapshot, Group, GroupContext},
+    CipherSuite,
+};
+
+/// Handle to a stored [`Group`] whose state is only decoded when it is first
+/// needed.
+///
+/// Loading a lazy group only decodes the [`GroupContext`], so the group id,
+/// epoch and extensions are available without decoding the ratchet tree and
+/// the private state. The rest of the group is decoded by the first call to
+/// [`LazyGroup::group`], e.g. to query the roster or to process a message.
+/// Prior epochs are fetched from the
+/// [`GroupStateStorage`](crate::GroupStateStorage) only when a message from
+/// one of them is decrypted.
+///
+/// Lazy groups are created with
+/// [`Client::load_group_lazy`](crate::Client::load_group_lazy).
+pub struct LazyGroup<C>
+where
+    C: ClientConfig,
+{
+    config: C,
+    context: GroupContext,
+    snapshot: Vec<u8>,
+    group: Option<Group<C>>,
+}
+
+impl<C> Debug for LazyGroup<C>
+where
+    C: ClientConfig,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyGroup")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(self.group_id()),
+            )
+            .field("epoch", &self.current_epoch())
+            .field("loaded", &self.is_loaded())
+            .finish()
+    }
+}
+
+impl<C> LazyGroup<C>
+where
+    C: ClientConfig + Clone,
+{
+    pub(crate) fn new(config: C, snapshot: Vec<u8>) -> Result<Self, MlsError> {
+        Ok(Self {
+            config,
+            context: Snapshot::decode_context(&snapshot)?,
+            snapshot,
+            group: None,
+        })
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.context.group_id
+    }
+
+    pub fn current_epoch(&self) -> u64 {
+        self.context.epoch
+    }
+
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.context.cipher_suite
+    }
+
+    /// Group context of the stored epoch. Once the group is
+    /// [loaded](LazyGroup::group) and has advanced, this is the context of the
+    /// epoch it was loaded at.
+    pub fn context(&self) -> &GroupContext {
+        &self.context
+    }
+
+    /// Whether the full group state has been decoded.
+    pub fn is_loaded(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// Access the group, decoding its full state on the first call.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn group(&mut self) -> Result<&mut Group<C>, MlsError> {
+        let group = match self.group.take() {
+            Some(group) => group,
+            None => self.load().await?,
+        };
+
+        Ok(self.group.insert(group))
+    }
+
+    /// Convert into a [`Group`], decoding its full state if it was not
+    /// already decoded.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn into_group(self) -> Result<Group<C>, MlsError> {
+        match self.group {
+            Some(group) => Ok(group),
+            None => self.load().await,
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn load(&self) -> Result<Group<C>, MlsError> {
+        let snapshot = Snapshot::from_bytes(&self.snapshot)?;
+        Group::from_snapshot(self.config.clone(), snapshot).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_config::ClientConfig,
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn lazy_group_decodes_state_on_first_use() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.write_to_storage().await.unwrap();
+
+        let mut lazy = TestClientBuilder::new_for_test()
+            .group_state_storage(bob.config.group_state_storage())
+            .build()
+            .load_group_lazy(bob.group_id())
+            .await
+            .unwrap();
+
+        assert!(!lazy.is_loaded());
+        assert_eq!(lazy.group_id(), bob.group_id());
+        assert_eq!(lazy.current_epoch(), bob.current_epoch());
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+
+        let group = lazy.group().await.unwrap();
+        assert_eq!(group.roster().members().len(), 2);
+        group.process_incoming_message(commit).await.unwrap();
+
+        assert!(lazy.is_loaded());
+
+        let group = lazy.into_group().await.unwrap();
+        assert_eq!(group.current_epoch(), alice.current_epoch());
+    }
+}
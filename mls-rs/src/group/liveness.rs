@@ -0,0 +1,254 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::time::Duration;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client_config::ClientConfig,
+    group::Group,
+    tree_kem::{node::LeafIndex, TreeKemPublic},
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::{client::MlsError, MlsMessage};
+
+#[cfg(feature = "std")]
+use crate::time::MlsTime;
+
+/// Cadence of the liveness updates sent with [`Group::create_liveness_update`].
+///
+/// A liveness update is an update proposal of the own leaf without any other
+/// change. Sent periodically, it lets other members know that the member is
+/// still active and, once committed, refreshes the key material of its leaf
+/// for post-compromise security.
+#[cfg(feature = "by_ref_proposal")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct LivenessPolicy {
+    /// Minimum number of seconds between two liveness updates.
+    pub interval_seconds: u64,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl LivenessPolicy {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval_seconds: interval.as_secs(),
+        }
+    }
+}
+
+/// Time at which each leaf was last added or updated, as observed by the
+/// local member, in seconds since the Unix epoch. An update proposal counts
+/// as an update as soon as it is received. This is local bookkeeping and not
+/// part of the group state agreed upon by members. Times are not recorded
+/// without the `std` feature.
+#[derive(Clone, Debug, Default, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct LivenessLog(Vec<Option<u64>>);
+
+impl LivenessLog {
+    pub(crate) fn new(self_index: LeafIndex) -> Self {
+        let mut log = Self::default();
+        log.record(self_index);
+        log
+    }
+
+    pub(crate) fn get(&self, index: LeafIndex) -> Option<u64> {
+        self.0.get(*index as usize).copied().flatten()
+    }
+
+    pub(crate) fn record(&mut self, index: LeafIndex) {
+        let Some(now) = now() else {
+            return;
+        };
+
+        let i = *index as usize;
+
+        if self.0.len() <= i {
+            self.0.resize(i + 1, None);
+        }
+
+        self.0[i] = Some(now);
+    }
+
+    /// Record the current time for every leaf of `new_tree` that differs from
+    /// the leaf at the same index in `old_tree`.
+    pub(crate) fn record_commit(&mut self, old_tree: &TreeKemPublic, new_tree: &TreeKemPublic) {
+        let old_leaves = old_tree.leaves().chain(core::iter::repeat(None));
+
+        let changes = new_tree
+            .leaves()
+            .zip(old_leaves)
+            .enumerate()
+            .map(|(i, (new, old))| {
+                let changed = new.map(|n| &n.signature) != old.map(|o| &o.signature);
+                (LeafIndex(i as u32), new.is_some(), changed)
+            })
+            .collect::<Vec<_>>();
+
+        self.0.truncate(changes.len());
+
+        for (index, occupied, changed) in changes {
+            if !occupied {
+                if let Some(time) = self.0.get_mut(*index as usize) {
+                    *time = None;
+                }
+            } else if changed {
+                self.record(index);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn now() -> Option<u64> {
+    Some(MlsTime::now().seconds_since_epoch())
+}
+
+#[cfg(not(feature = "std"))]
+fn now() -> Option<u64> {
+    None
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Time at which the member at leaf `index` was last added or updated,
+    /// including by an update proposal that is not committed yet, in seconds
+    /// since the Unix epoch.
+    ///
+    /// Returns `None` for blank leaves, for members that did not update
+    /// since the local member joined, and without the `std` feature.
+    pub fn last_update_time(&self, index: u32) -> Option<u64> {
+        self.liveness_log.get(LeafIndex(index))
+    }
+
+    /// Leaf indexes of the members that were not observed updating within
+    /// `max_age`, including members with no known update time. See
+    /// [`Group::last_update_time`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn members_not_updated_within(&self, max_age: Duration) -> Vec<u32> {
+        let now = now();
+
+        self.roster()
+            .members_iter()
+            .map(|member| member.index)
+            .filter(|index| {
+                let last_update = self.last_update_time(*index);
+
+                now.zip(last_update).map_or(true, |(now, last_update)| {
+                    now.saturating_sub(last_update) > max_age.as_secs()
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the [`LivenessPolicy`] of this group requires a new liveness
+    /// update, because the local member did not update within
+    /// [`interval_seconds`](LivenessPolicy::interval_seconds). Without the
+    /// `std` feature, an update is always due if a policy is set.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn is_liveness_update_due(&self) -> bool {
+        let Some(policy) = &self.config_overrides.liveness else {
+            return false;
+        };
+
+        let last_update = self.last_update_time(*self.private_tree.self_index);
+
+        now().zip(last_update).map_or(true, |(now, last_update)| {
+            now.saturating_sub(last_update) >= policy.interval_seconds
+        })
+    }
+
+    /// Create an update proposal of the own leaf if a liveness update is
+    /// due according to the [`LivenessPolicy`] of this group, and `None`
+    /// otherwise.
+    ///
+    /// Applications call this periodically, for example when the
+    /// application comes to the foreground, and send the returned proposal
+    /// to the group as any other proposal.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn create_liveness_update(&mut self) -> Result<Option<MlsMessage>, MlsError> {
+        if !self.is_liveness_update_due() {
+            return Ok(None);
+        }
+
+        let proposal = self.propose_update(Vec::new()).await?;
+        self.liveness_log.record(self.private_tree.self_index);
+
+        Ok(Some(proposal))
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "by_ref_proposal"))]
+mod tests {
+    use alloc::vec;
+    use core::time::Duration;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{
+            proposal::Proposal, test_utils::test_group, GroupConfigOverrides, ReceivedMessage,
+        },
+    };
+
+    use super::LivenessPolicy;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn liveness_update_follows_cadence() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        assert!(!bob.is_liveness_update_due());
+        let proposal = bob.create_liveness_update().await.unwrap();
+        assert!(proposal.is_none());
+
+        let overrides = GroupConfigOverrides::new()
+            .with_liveness_policy(LivenessPolicy::new(Duration::from_secs(3600)));
+
+        bob.config_overrides = overrides;
+
+        // Bob's leaf was added in the last commit, so no update is due yet
+        assert!(!bob.is_liveness_update_due());
+        assert_eq!(
+            bob.members_not_updated_within(Duration::from_secs(60)),
+            vec![0]
+        );
+
+        let overrides = GroupConfigOverrides::new()
+            .with_liveness_policy(LivenessPolicy::new(Duration::from_secs(0)));
+
+        bob.config_overrides = overrides;
+
+        let proposal = bob.create_liveness_update().await.unwrap().unwrap();
+
+        let received = alice.process_incoming_message(proposal).await.unwrap();
+
+        assert_matches::assert_matches!(
+            received,
+            ReceivedMessage::Proposal(p) if matches!(p.proposal, Proposal::Update(_))
+        );
+
+        assert!(alice.last_update_time(1).is_some());
+        assert!(alice
+            .members_not_updated_within(Duration::from_secs(60))
+            .is_empty());
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        assert!(bob.last_update_time(0).is_some());
+        assert!(bob
+            .members_not_updated_within(Duration::from_secs(60))
+            .is_empty());
+    }
+}
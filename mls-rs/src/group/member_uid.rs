@@ -0,0 +1,227 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::Group,
+    tree_kem::{leaf_node::LeafNode, node::LeafIndex, TreeKemPublic},
+};
+
+#[derive(MlsSize, MlsEncode)]
+struct MemberUidInput<'a> {
+    leaf_node: &'a LeafNode,
+    epoch: u64,
+}
+
+/// Identifier of a member that, unlike its leaf index, is never reused after
+/// the member is removed.
+///
+/// The identifier is the hash of the leaf node of the member when it was
+/// added and of the epoch in which it was added. Members added before the
+/// local member joined the group are identified by their leaf node and the
+/// epoch at the time of joining, so their identifiers are only stable for
+/// the local member. Members added later get the same identifier for every
+/// member that processed the commit adding them.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberUid(
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    Vec<u8>,
+);
+
+impl Debug for MemberUid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        mls_rs_core::debug::pretty_bytes(&self.0)
+            .named("MemberUid")
+            .fmt(f)
+    }
+}
+
+impl MemberUid {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn compute<P: CipherSuiteProvider>(
+        cs: &P,
+        leaf_node: &LeafNode,
+        epoch: u64,
+    ) -> Result<Self, MlsError> {
+        let input = MemberUidInput { leaf_node, epoch }.mls_encode_to_vec()?;
+
+        cs.hash(&input)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+            .map(Self)
+    }
+}
+
+impl From<Vec<u8>> for MemberUid {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+/// [`MemberUid`] of each occupied leaf, indexed by leaf index. This is local
+/// bookkeeping and not part of the group state agreed upon by members.
+#[derive(Clone, Debug, Default, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct MemberUids(Vec<Option<MemberUid>>);
+
+impl MemberUids {
+    /// Identify every member of `tree`, as added in `epoch`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn new<P: CipherSuiteProvider>(
+        cs: &P,
+        tree: &TreeKemPublic,
+        epoch: u64,
+    ) -> Result<Self, MlsError> {
+        let mut uids = Vec::new();
+
+        for leaf in tree.leaves() {
+            uids.push(match leaf {
+                Some(leaf) => Some(MemberUid::compute(cs, leaf, epoch).await?),
+                None => None,
+            });
+        }
+
+        Ok(Self(uids))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn get(&self, index: LeafIndex) -> Option<&MemberUid> {
+        self.0.get(*index as usize).and_then(Option::as_ref)
+    }
+
+    pub(crate) fn index_of(&self, uid: &MemberUid) -> Option<LeafIndex> {
+        self.0
+            .iter()
+            .position(|u| u.as_ref() == Some(uid))
+            .map(|i| LeafIndex(i as u32))
+    }
+
+    /// Identifiers after a commit moving from `old_tree` to `new_tree` in
+    /// `epoch`. Leaves that were blank in `old_tree` or `removed` by the
+    /// commit get a new identifier.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn next_epoch<P: CipherSuiteProvider>(
+        &self,
+        cs: &P,
+        old_tree: &TreeKemPublic,
+        new_tree: &TreeKemPublic,
+        removed: &[LeafIndex],
+        epoch: u64,
+    ) -> Result<Self, MlsError> {
+        let old_leaves = old_tree.leaves().chain(core::iter::repeat(None));
+        let mut uids = Vec::new();
+
+        for (i, (new, old)) in new_tree.leaves().zip(old_leaves).enumerate() {
+            let index = LeafIndex(i as u32);
+
+            let uid = match (new, self.get(index)) {
+                (None, _) => None,
+                (Some(_), Some(uid)) if old.is_some() && !removed.contains(&index) => {
+                    Some(uid.clone())
+                }
+                (Some(new), _) => Some(MemberUid::compute(cs, new, epoch).await?),
+            };
+
+            uids.push(uid);
+        }
+
+        Ok(Self(uids))
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Stable identifier of the member at leaf `index`, or `None` if the leaf
+    /// is blank. See [`MemberUid`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn member_uid(&self, index: u32) -> Option<&MemberUid> {
+        self.member_uids.get(LeafIndex(index))
+    }
+
+    /// Current leaf index of the member identified by `uid`, or `None` if the
+    /// member is no longer part of the group.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn member_index_by_uid(&self, uid: &MemberUid) -> Option<u32> {
+        self.member_uids.index_of(uid).map(|index| *index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::group::snapshot::{test_utils::encode_layout, Snapshot};
+    use crate::group::{test_utils::test_group, Group};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_uids_survive_updates_and_are_not_reused() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let bob_uid = alice.member_uid(1).unwrap().clone();
+        assert_eq!(bob.member_uid(1), Some(&bob_uid));
+        assert_eq!(alice.member_index_by_uid(&bob_uid), Some(1));
+
+        let commit = bob.commit(vec![]).await.unwrap().commit_message;
+        bob.apply_pending_commit().await.unwrap();
+        alice.process_incoming_message(commit).await.unwrap();
+
+        assert_eq!(alice.member_uid(1), Some(&bob_uid));
+
+        alice
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+        assert_eq!(alice.member_uid(1), None);
+        assert_eq!(alice.member_index_by_uid(&bob_uid), None);
+
+        let (carol, _) = alice.join("carol").await;
+        let carol_uid = alice.member_uid(1).unwrap();
+
+        assert_ne!(carol_uid, &bob_uid);
+        assert_eq!(carol.member_uid(1), Some(carol_uid));
+        assert_eq!(alice.member_index_by_uid(carol_uid), Some(1));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_uids_are_computed_for_older_group_states() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        // Layout of the group state before member UIDs were appended
+        let bytes = encode_layout(&alice.snapshot(), 9);
+        let snapshot = Snapshot::decode_version(&mut &*bytes, 9).unwrap();
+
+        let restored = Group::from_snapshot(alice.config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        let bob_uid = restored.member_uid(1).unwrap();
+        assert_eq!(restored.member_index_by_uid(bob_uid), Some(1));
+        assert!(restored.member_uid(0).is_some());
+    }
+}
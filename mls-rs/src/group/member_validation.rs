@@ -0,0 +1,244 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::{
+    error::IntoAnyError,
+    identity::{IdentityProvider, MemberValidationContext},
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, Member},
+    time::MlsTime,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::MlsMessage;
+
+/// Member of the current roster rejected by the
+/// [`IdentityProvider`](crate::IdentityProvider), as reported by
+/// [`Group::member_validation_refresh`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct InvalidMember {
+    pub member: Member,
+    /// Error returned by
+    /// [`IdentityProvider::validate_member`](crate::IdentityProvider::validate_member).
+    pub error: MlsError,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Validate every member of the current roster again with the
+    /// [`IdentityProvider`](crate::IdentityProvider) of the client, returning
+    /// the members that are now rejected.
+    ///
+    /// Members are only validated when they join or update their leaf. This
+    /// lets applications find members that no longer pass validation after a
+    /// change of the identity provider state, for example after trust
+    /// anchors were updated or a certificate was revoked. `time` is passed to
+    /// the identity provider as with
+    /// [`Group::process_incoming_message_with_time`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn member_validation_refresh(
+        &self,
+        time: Option<MlsTime>,
+    ) -> Result<Vec<InvalidMember>, MlsError> {
+        let identity_provider = self.config.identity_provider();
+        let context = self.context();
+        let mut invalid = Vec::new();
+
+        for member in self.roster().members_iter() {
+            let member_context = MemberValidationContext::ForCommit {
+                current_context: context,
+                new_extensions: &context.extensions,
+            };
+
+            if let Err(e) = identity_provider
+                .validate_member(&member.signing_identity, time, member_context)
+                .await
+            {
+                invalid.push(InvalidMember {
+                    member,
+                    error: MlsError::IdentityProviderError(e.into_any_error()),
+                });
+            }
+        }
+
+        Ok(invalid)
+    }
+
+    /// Run [`Group::member_validation_refresh`] and create a remove proposal
+    /// for each rejected member other than the local member.
+    ///
+    /// The proposal messages are returned in the order of the rejected
+    /// members and must be sent to the group. `authenticated_data` is sent
+    /// with each proposal as with [`Group::propose_remove`].
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_remove_invalid_members(
+        &mut self,
+        time: Option<MlsTime>,
+        authenticated_data: Vec<u8>,
+    ) -> Result<(Vec<InvalidMember>, Vec<MlsMessage>), MlsError> {
+        let invalid = self.member_validation_refresh(time).await?;
+        let self_index = self.current_member_index();
+        let mut proposals = Vec::new();
+
+        for index in invalid
+            .iter()
+            .map(|invalid| invalid.member.index)
+            .filter(|index| *index != self_index)
+        {
+            proposals.push(
+                self.propose_remove(index, authenticated_data.clone())
+                    .await?,
+            );
+        }
+
+        Ok((invalid, proposals))
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "by_ref_proposal"))]
+mod tests {
+    use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+    use mls_rs_core::{
+        error::IntoAnyError,
+        extension::ExtensionList,
+        identity::{CredentialType, IdentityProvider, MemberValidationContext, SigningIdentity},
+        time::MlsTime,
+    };
+    use std::sync::Mutex;
+
+    use crate::{
+        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_builder::ClientBuilder,
+        crypto::test_utils::TestCryptoProvider,
+        identity::test_utils::get_test_signing_identity,
+    };
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("identity revoked")]
+    struct RevokedError;
+
+    impl IntoAnyError for RevokedError {
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct RevocableIdentityProvider {
+        revoked: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl IdentityProvider for RevocableIdentityProvider {
+        type Error = RevokedError;
+
+        async fn validate_member(
+            &self,
+            signing_identity: &SigningIdentity,
+            _: Option<MlsTime>,
+            _: MemberValidationContext<'_>,
+        ) -> Result<(), Self::Error> {
+            let identifier = &signing_identity.credential.as_basic().unwrap().identifier;
+
+            match self.revoked.lock().unwrap().contains(identifier) {
+                true => Err(RevokedError),
+                false => Ok(()),
+            }
+        }
+
+        async fn validate_external_sender(
+            &self,
+            _: &SigningIdentity,
+            _: Option<MlsTime>,
+            _: Option<&ExtensionList>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn identity(
+            &self,
+            signing_identity: &SigningIdentity,
+            _: &ExtensionList,
+        ) -> Result<Vec<u8>, Self::Error> {
+            Ok(signing_identity.signature_key.to_vec())
+        }
+
+        async fn valid_successor(
+            &self,
+            _: &SigningIdentity,
+            _: &SigningIdentity,
+            _: &ExtensionList,
+        ) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+
+        fn supported_types(&self) -> Vec<CredentialType> {
+            vec![CredentialType::BASIC]
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn revoked_members_are_reported_and_removed() {
+        let (signing_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let identity_provider = RevocableIdentityProvider::default();
+
+        let alice = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(identity_provider.clone())
+            .signing_identity(signing_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut group = alice
+            .create_group(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        let invalid = group.member_validation_refresh(None).await.unwrap();
+        assert!(invalid.is_empty());
+
+        identity_provider
+            .revoked
+            .lock()
+            .unwrap()
+            .extend([b"alice".to_vec(), b"bob".to_vec()]);
+
+        let (invalid, proposals) = group
+            .propose_remove_invalid_members(None, Vec::new())
+            .await
+            .unwrap();
+
+        let indexes = invalid.iter().map(|i| i.member.index).collect::<Vec<_>>();
+        assert_eq!(indexes, vec![0, 1]);
+
+        // The local member is reported but not proposed for removal
+        assert_eq!(proposals.len(), 1);
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    crypto::CipherSuiteProvider,
+    group::{Group, GroupContext},
+    identity::SigningIdentity,
+    signer::Signable,
+    tree_kem::{leaf_node::LeafNode, node::LeafIndex, tree_hash_from_path, TreeHashPathStep},
+};
+
+/// Leaf of the ratchet tree with the nodes needed to recompute the tree hash
+/// of the group context from it.
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+struct LeafInclusion {
+    leaf_index: LeafIndex,
+    leaf_node: LeafNode,
+    path: Vec<TreeHashPathStep>,
+}
+
+impl LeafInclusion {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify<P: CipherSuiteProvider>(
+        &self,
+        tree_hash: &[u8],
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        let computed = tree_hash_from_path(
+            self.leaf_index,
+            &self.leaf_node,
+            &self.path,
+            cipher_suite_provider,
+        )
+        .await?;
+
+        (computed.as_deref() == Some(tree_hash))
+            .then_some(())
+            .ok_or(MlsError::InvalidMembershipProof)
+    }
+}
+
+/// Proof, verifiable by anyone, that a member was part of a group at a given
+/// epoch.
+///
+/// A member of the group creates the proof with
+/// [`Group::membership_proof`](crate::Group::membership_proof). It contains the
+/// group context of the epoch, the leaf of the proven member and of the member
+/// that created the proof, the nodes needed to recompute the tree hash of the
+/// group context from each leaf, and a signature of the proving member over
+/// all of it.
+///
+/// [`MembershipProof::verify`] does not require any group state. It only
+/// shows that the proving member vouched for the group context, so the
+/// context should be compared with one obtained independently when the
+/// proving member is not trusted. The credentials of both members are not
+/// validated and should be checked by the caller with an
+/// [`IdentityProvider`](crate::IdentityProvider).
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct MembershipProof {
+    group_context: GroupContext,
+    member: LeafInclusion,
+    prover: LeafInclusion,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for MembershipProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MembershipProof")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(self.group_id()),
+            )
+            .field("epoch", &self.epoch())
+            .field("member_index", &self.member_index())
+            .field("prover_index", &self.prover_index())
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct MembershipProofTBS<'a> {
+    group_context: &'a GroupContext,
+    member: &'a LeafInclusion,
+    prover: &'a LeafInclusion,
+}
+
+impl<'a> Signable<'a> for MembershipProof {
+    const SIGN_LABEL: &'static str = "MembershipProofTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        MembershipProofTBS {
+            group_context: &self.group_context,
+            member: &self.member,
+            prover: &self.prover,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl MembershipProof {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Group context of the epoch in which the proof was created.
+    pub fn group_context(&self) -> &GroupContext {
+        &self.group_context
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_context.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.group_context.epoch
+    }
+
+    /// Index of the proven member.
+    pub fn member_index(&self) -> u32 {
+        *self.member.leaf_index
+    }
+
+    /// Signing identity of the proven member.
+    pub fn member_signing_identity(&self) -> &SigningIdentity {
+        &self.member.leaf_node.signing_identity
+    }
+
+    /// Index of the member that created the proof.
+    pub fn prover_index(&self) -> u32 {
+        *self.prover.leaf_index
+    }
+
+    /// Signing identity of the member that created the proof.
+    pub fn prover_signing_identity(&self) -> &SigningIdentity {
+        &self.prover.leaf_node.signing_identity
+    }
+
+    /// Verify that both the proven member and the proving member are leaves
+    /// of the tree committed to by the group context, and that the proof was
+    /// signed by the proving member.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        if cipher_suite_provider.cipher_suite() != self.group_context.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        let tree_hash = &self.group_context.tree_hash;
+
+        self.member.verify(tree_hash, cipher_suite_provider).await?;
+        self.prover.verify(tree_hash, cipher_suite_provider).await?;
+
+        Signable::verify(
+            self,
+            cipher_suite_provider,
+            &self.prover.leaf_node.signing_identity.signature_key,
+            &(),
+        )
+        .await
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`MembershipProof`] showing that the member at `member_index`
+    /// is part of the group in the current epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn membership_proof(&self, member_index: u32) -> Result<MembershipProof, MlsError> {
+        let tree = &self.state.public_tree;
+
+        let inclusion = |leaf_index| {
+            Ok::<_, MlsError>(LeafInclusion {
+                leaf_index,
+                leaf_node: tree.get_leaf_node(leaf_index)?.clone(),
+                path: tree.tree_hash_path(leaf_index)?,
+            })
+        };
+
+        let mut proof = MembershipProof {
+            group_context: self.context().clone(),
+            member: inclusion(LeafIndex(member_index))?,
+            prover: inclusion(LeafIndex(self.current_member_index()))?,
+            signature: Vec::new(),
+        };
+
+        proof
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+    };
+
+    use super::MembershipProof;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_proof_verifies_without_group_state() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+        alice.join("carol").await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let proof = alice.membership_proof(2).await.unwrap();
+        let proof = MembershipProof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(proof.member_index(), 2);
+        assert_eq!(proof.prover_index(), 0);
+        assert_eq!(proof.group_context(), alice.context());
+        proof.verify(&cs).await.unwrap();
+
+        let mut proof = alice.membership_proof(1).await.unwrap();
+        proof.member.leaf_index = proof.prover.leaf_index;
+        let res = proof.verify(&cs).await;
+        assert_matches!(res, Err(MlsError::InvalidMembershipProof));
+
+        let mut proof = alice.membership_proof(1).await.unwrap();
+        proof.group_context.epoch += 1;
+        let res = proof.verify(&cs).await;
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+}
@@ -0,0 +1,348 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{error::IntoAnyError, secret::Secret};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, crypto::CipherSuiteProvider, group::Group,
+    time::MlsTime, CipherSuite,
+};
+
+const MEMBERSHIP_TOKEN_LABEL: &[u8] = b"membership token";
+const NONCE_SIZE: usize = 16;
+
+/// Key used to mint and verify [`MembershipToken`]s for a single auxiliary
+/// service, such as a file store or an SFU.
+///
+/// The key is exported from the current epoch with the name of the service as
+/// context. It can be handed to the service without revealing any other group
+/// secret, and the service only learns that the holder of a token was a member
+/// at the given epoch.
+///
+/// Every member derives the same key, so the member index of a token is only
+/// as trustworthy as the other members of the group.
+#[derive(Clone)]
+pub struct MembershipTokenKey {
+    group_id: Vec<u8>,
+    epoch: u64,
+    cipher_suite: CipherSuite,
+    audience: Vec<u8>,
+    key: Secret,
+}
+
+impl Debug for MembershipTokenKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MembershipTokenKey")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "audience",
+                &mls_rs_core::debug::pretty_bytes(&self.audience),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl MembershipTokenKey {
+    /// Restore a key received from a member of the group, as returned by
+    /// [`MembershipTokenKey::key`].
+    pub fn new(
+        group_id: Vec<u8>,
+        epoch: u64,
+        cipher_suite: CipherSuite,
+        audience: Vec<u8>,
+        key: Vec<u8>,
+    ) -> Self {
+        Self {
+            group_id,
+            epoch,
+            cipher_suite,
+            audience,
+            key: key.into(),
+        }
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    pub fn audience(&self) -> &[u8] {
+        &self.audience
+    }
+
+    pub fn key(&self) -> &[u8] {
+        self.key.as_bytes()
+    }
+
+    /// Verify that `token` was minted with this key and has not expired at
+    /// `now`.
+    ///
+    /// Tokens are not consumed by verification. A service that requires
+    /// tokens to be used only once must remember the
+    /// [nonce](MembershipToken::nonce) of accepted tokens until they expire.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        token: &MembershipToken,
+        cipher_suite_provider: &P,
+        now: MlsTime,
+    ) -> Result<(), MlsError> {
+        if cipher_suite_provider.cipher_suite() != self.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        if token.group_id != self.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if token.epoch != self.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        if token.not_after < now.seconds_since_epoch() {
+            return Err(MlsError::MembershipTokenExpired);
+        }
+
+        let tag = self.tag(token, cipher_suite_provider).await?;
+
+        bool::from(tag.ct_eq(&token.tag))
+            .then_some(())
+            .ok_or(MlsError::InvalidMembershipToken)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn tag<P: CipherSuiteProvider>(
+        &self,
+        token: &MembershipToken,
+        cipher_suite_provider: &P,
+    ) -> Result<Vec<u8>, MlsError> {
+        let content = MembershipTokenContent {
+            audience: &self.audience,
+            group_id: &token.group_id,
+            epoch: token.epoch,
+            member_index: token.member_index,
+            not_after: token.not_after,
+            nonce: &token.nonce,
+        }
+        .mls_encode_to_vec()?;
+
+        cipher_suite_provider
+            .mac(&self.key, &content)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct MembershipTokenContent<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    audience: &'a [u8],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    member_index: u32,
+    not_after: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    nonce: &'a [u8],
+}
+
+/// Short-lived token proving that a member of a group held the
+/// [`MembershipTokenKey`] of an epoch, created with
+/// [`Group::membership_token`](crate::Group::membership_token).
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct MembershipToken {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) group_id: Vec<u8>,
+    pub(crate) epoch: u64,
+    pub(crate) member_index: u32,
+    pub(crate) not_after: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) nonce: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) tag: Vec<u8>,
+}
+
+impl Debug for MembershipToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MembershipToken")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("member_index", &self.member_index)
+            .field("not_after", &self.not_after)
+            .field("nonce", &mls_rs_core::debug::pretty_bytes(&self.nonce))
+            .finish()
+    }
+}
+
+impl MembershipToken {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Index of the member that minted the token.
+    pub fn member_index(&self) -> u32 {
+        self.member_index
+    }
+
+    /// Time after which the token is no longer valid.
+    pub fn not_after(&self) -> MlsTime {
+        MlsTime::from(self.not_after)
+    }
+
+    /// Random value identifying this token.
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Export the key used by the auxiliary service identified by `audience`
+    /// to verify [`MembershipToken`]s of the current epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn membership_token_key(
+        &self,
+        audience: &[u8],
+    ) -> Result<MembershipTokenKey, MlsError> {
+        let key = self
+            .export_secret(
+                MEMBERSHIP_TOKEN_LABEL,
+                audience,
+                self.cipher_suite_provider.kdf_extract_size(),
+            )
+            .await?;
+
+        Ok(MembershipTokenKey {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            cipher_suite: self.cipher_suite(),
+            audience: audience.to_vec(),
+            key,
+        })
+    }
+
+    /// Mint a token proving membership in the current epoch to the auxiliary
+    /// service identified by `audience`, valid until `not_after`.
+    ///
+    /// The service verifies the token with the key returned by
+    /// [`Group::membership_token_key`] for the same epoch and audience.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn membership_token(
+        &self,
+        audience: &[u8],
+        not_after: MlsTime,
+    ) -> Result<MembershipToken, MlsError> {
+        let key = self.membership_token_key(audience).await?;
+
+        let nonce = self
+            .cipher_suite_provider
+            .random_bytes_vec(NONCE_SIZE)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let mut token = MembershipToken {
+            group_id: key.group_id.clone(),
+            epoch: key.epoch,
+            member_index: self.current_member_index(),
+            not_after: not_after.seconds_since_epoch(),
+            nonce,
+            tag: Vec::new(),
+        };
+
+        token.tag = key.tag(&token, &self.cipher_suite_provider).await?;
+
+        Ok(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+        time::MlsTime,
+    };
+
+    use super::MembershipToken;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_token_is_bound_to_audience_epoch_and_expiry() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let key = alice.membership_token_key(b"file store").await.unwrap();
+
+        let token = bob
+            .membership_token(b"file store", MlsTime::from(100))
+            .await
+            .unwrap();
+
+        let token = MembershipToken::from_bytes(&token.to_bytes().unwrap()).unwrap();
+        assert_eq!(token.member_index(), 1);
+
+        key.verify(&token, &cs, MlsTime::from(100)).await.unwrap();
+
+        let res = key.verify(&token, &cs, MlsTime::from(101)).await;
+        assert_matches!(res, Err(MlsError::MembershipTokenExpired));
+
+        let other_key = alice.membership_token_key(b"sfu").await.unwrap();
+        let res = other_key.verify(&token, &cs, MlsTime::from(0)).await;
+        assert_matches!(res, Err(MlsError::InvalidMembershipToken));
+
+        let forged = MembershipToken {
+            member_index: 0,
+            ..token.clone()
+        };
+
+        let res = key.verify(&forged, &cs, MlsTime::from(0)).await;
+        assert_matches!(res, Err(MlsError::InvalidMembershipToken));
+
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        let next_key = alice.membership_token_key(b"file store").await.unwrap();
+        let res = next_key.verify(&token, &cs, MlsTime::from(0)).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{FlushMode, Group, ProposalMessageDescription, ReceivedMessage},
+    MlsMessage,
+};
+
+/// Outcome of [`Group::process_incoming_messages`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct MessageBatchReport {
+    /// Result of processing each message of the batch, in order.
+    pub results: Vec<Result<ReceivedMessage, MlsError>>,
+}
+
+impl MessageBatchReport {
+    /// Proposals received and cached by the group.
+    pub fn proposals(&self) -> impl Iterator<Item = &ProposalMessageDescription> {
+        self.results.iter().filter_map(|res| match res {
+            Ok(ReceivedMessage::Proposal(proposal)) => Some(proposal),
+            _ => None,
+        })
+    }
+
+    /// Messages that could not be processed, with their position in the
+    /// batch.
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &MlsError)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, res)| res.as_ref().err().map(|e| (i, e)))
+    }
+
+    /// Number of messages that were processed successfully.
+    pub fn processed_count(&self) -> usize {
+        self.results.iter().filter(|res| res.is_ok()).count()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Process many inbound messages for this group, such as a large number
+    /// of proposals by reference received from a sync job.
+    ///
+    /// Each message is processed in order as by
+    /// [`Group::process_incoming_message`]. A message that fails does not
+    /// stop the batch; its error is reported in the returned
+    /// [`MessageBatchReport`] and the state is left as before that message.
+    ///
+    /// In [`FlushMode::Immediate`], the state is written to storage once
+    /// after the whole batch rather than after every message. If that write
+    /// fails, the storage error is returned, but the messages remain
+    /// processed in memory and [`Group::flush`] can retry the write.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn process_incoming_messages(
+        &mut self,
+        messages: Vec<MlsMessage>,
+    ) -> Result<MessageBatchReport, MlsError> {
+        let flush_mode = mem::replace(&mut self.flush_mode, FlushMode::Manual);
+        let mut results = Vec::with_capacity(messages.len());
+
+        for message in messages {
+            results.push(self.process_incoming_message(message).await);
+        }
+
+        self.flush_mode = flush_mode;
+
+        if self.unflushed_changes {
+            self.state_changed().await?;
+        }
+
+        Ok(MessageBatchReport { results })
+    }
+}
+
+#[cfg(all(test, feature = "by_ref_proposal"))]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{
+            test_utils::{test_group, test_n_member_group},
+            FlushMode,
+        },
+        key_package::test_utils::test_key_package_message,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn batch_of_proposals_is_cached_and_committed() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let mut messages = Vec::new();
+
+        for name in ["carol", "dave", "erin"] {
+            let key_package =
+                test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+            messages.push(groups[0].propose_add(key_package, vec![]).await.unwrap());
+        }
+
+        // A message of another group fails without stopping the batch
+        let mut other = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let other_group_message = other.commit(vec![]).await.unwrap().commit_message;
+        messages.insert(1, other_group_message);
+
+        groups[1].set_flush_mode(FlushMode::Immediate);
+
+        let report = groups[1].process_incoming_messages(messages).await.unwrap();
+
+        assert_eq!(report.results.len(), 4);
+        assert_eq!(report.processed_count(), 3);
+        assert_eq!(report.proposals().count(), 3);
+
+        let failures = report.failures().collect::<Vec<_>>();
+        assert_eq!(failures.len(), 1);
+        assert_matches!(failures[0], (1, _));
+
+        assert!(!groups[1].has_unflushed_changes());
+
+        let commit = groups[1].commit(vec![]).await.unwrap();
+        assert_eq!(commit.welcome_messages.len(), 1);
+        groups[1].apply_pending_commit().await.unwrap();
+
+        assert_eq!(groups[1].roster().members_iter().count(), 5);
+    }
+}
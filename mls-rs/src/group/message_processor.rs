@@ -21,6 +21,7 @@ use crate::{
     key_package::validate_key_package_properties,
     time::MlsTime,
     tree_kem::{
+        leaf_node::LeafNode,
         leaf_node_validator::{LeafNodeValidator, ValidationContext},
         node::LeafIndex,
         path_secret::PathSecret,
@@ -35,6 +36,7 @@ use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_core::{
+    extension::ExtensionDiff,
     identity::{IdentityProvider, MemberValidationContext},
     protocol_version::ProtocolVersion,
     psk::PreSharedKeyStorage,
@@ -50,7 +52,10 @@ use super::proposal::Proposal;
 use super::proposal_filter::ProposalInfo;
 
 #[cfg(feature = "private_message")]
-use crate::group::framing::PrivateMessage;
+use crate::group::{
+    framing::PrivateMessage, mls_rules::ReceivedControlWireFormat,
+    targeted_message::TargetedMessage,
+};
 
 #[derive(Debug)]
 pub(crate) struct ProvisionalState {
@@ -94,12 +99,18 @@ pub struct NewEpoch {
     pub prior_state: GroupState,
     pub applied_proposals: Vec<ProposalInfo<Proposal>>,
     pub unused_proposals: Vec<ProposalInfo<Proposal>>,
+    /// Changes to the group context extensions made by this commit.
+    pub extension_diff: ExtensionDiff,
 }
 
 impl NewEpoch {
     fn new(prior_state: GroupState, provisional_state: &ProvisionalState) -> NewEpoch {
         NewEpoch {
             epoch: provisional_state.group_context.epoch,
+            extension_diff: prior_state
+                .context
+                .extensions
+                .diff(&provisional_state.group_context.extensions),
             prior_state,
             unused_proposals: provisional_state.unused_proposals.clone(),
             applied_proposals: provisional_state
@@ -129,6 +140,10 @@ impl NewEpoch {
     pub fn unused_proposals(&self) -> &[ProposalInfo<Proposal>] {
         &self.unused_proposals
     }
+
+    pub fn extension_diff(&self) -> &ExtensionDiff {
+        &self.extension_diff
+    }
 }
 
 #[cfg_attr(
@@ -166,6 +181,13 @@ pub enum ReceivedMessage {
     Welcome,
     /// Validated key package
     KeyPackage(KeyPackage),
+    /// A targeted message addressed to this member was decrypted.
+    #[cfg(feature = "private_message")]
+    TargetedMessage(ApplicationMessageDescription),
+    /// The message was sent in the given future epoch and is buffered until
+    /// the group reaches it. See
+    /// [`FutureEpochBufferConfig`](crate::group::FutureEpochBufferConfig).
+    Buffered(u64),
 }
 
 impl TryFrom<ApplicationMessageDescription> for ReceivedMessage {
@@ -216,9 +238,10 @@ pub struct ApplicationMessageDescription {
     /// Index of this user in the group state.
     pub sender_index: u32,
     /// Received application data.
-    data: ApplicationData,
+    pub(crate) data: ApplicationData,
     /// Plaintext authenticated data in the received MLS packet.
     pub authenticated_data: Vec<u8>,
+    pub(crate) sender_quarantined: bool,
 }
 
 impl Debug for ApplicationMessageDescription {
@@ -230,6 +253,7 @@ impl Debug for ApplicationMessageDescription {
                 "authenticated_data",
                 &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
             )
+            .field("sender_quarantined", &self.sender_quarantined)
             .finish()
     }
 }
@@ -239,6 +263,13 @@ impl ApplicationMessageDescription {
     pub fn data(&self) -> &[u8] {
         self.data.as_bytes()
     }
+
+    /// Returns `true` if the sender was quarantined by the local member
+    /// when the message was processed. See
+    /// [`Group::quarantine_member`](crate::group::Group::quarantine_member).
+    pub fn is_sender_quarantined(&self) -> bool {
+        self.sender_quarantined
+    }
 }
 
 #[cfg_attr(
@@ -475,6 +506,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
             }
             #[cfg(feature = "private_message")]
             MlsMessagePayload::Cipher(cipher_text) => self.process_ciphertext(&cipher_text).await,
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Targeted(targeted) => self.process_targeted_message(targeted).await,
             MlsMessagePayload::GroupInfo(group_info) => {
                 validate_group_info_member(
                     self.group_state(),
@@ -528,11 +561,12 @@ pub(crate) trait MessageProcessor: Send + Sync {
         #[cfg(feature = "by_ref_proposal")] cache_proposal: bool,
         time_sent: Option<MlsTime>,
     ) -> Result<Self::OutputType, MlsError> {
+        let sender = auth_content.content.sender;
+
         let event = match auth_content.content.content {
             #[cfg(feature = "private_message")]
             Content::Application(data) => {
                 let authenticated_data = auth_content.content.authenticated_data;
-                let sender = auth_content.content.sender;
 
                 self.process_application_message(data, sender, authenticated_data)
                     .and_then(Self::OutputType::try_from)
@@ -548,6 +582,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 .map(Self::OutputType::from),
         }?;
 
+        self.confirm_sender(&sender);
+
         Ok(event)
     }
 
@@ -565,7 +601,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
         Ok(ApplicationMessageDescription {
             authenticated_data,
             sender_index,
-            data,
+            data: self.decode_application_data(data)?,
+            sender_quarantined: self.sender_quarantined(sender_index),
         })
     }
 
@@ -584,6 +621,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
         )
         .await?;
 
+        self.proposal_received(&proposal);
+
         let group_state = self.group_state_mut();
 
         if cache_proposal {
@@ -670,6 +709,11 @@ pub(crate) trait MessageProcessor: Send + Sync {
             });
         }
 
+        self.check_unknown_extensions(
+            &provisional_state,
+            commit.path.as_ref().map(|path| &path.leaf_node),
+        )?;
+
         let update_path = match commit.path {
             Some(update_path) => Some(
                 validate_update_path(
@@ -756,6 +800,39 @@ pub(crate) trait MessageProcessor: Send + Sync {
     #[cfg(feature = "private_message")]
     fn min_epoch_available(&self) -> Option<u64>;
 
+    #[cfg(feature = "private_message")]
+    fn sender_quarantined(&self, _sender_index: u32) -> bool {
+        false
+    }
+
+    #[cfg(feature = "private_message")]
+    fn received_control_wire_format(&self) -> ReceivedControlWireFormat {
+        ReceivedControlWireFormat::Any
+    }
+
+    /// Recover the application data sent by the application from the content
+    /// of an application message.
+    #[cfg(feature = "private_message")]
+    fn decode_application_data(&self, data: ApplicationData) -> Result<ApplicationData, MlsError> {
+        Ok(data)
+    }
+
+    /// Record that `sender` sent a valid message, which confirms members
+    /// added by a previous commit.
+    fn confirm_sender(&mut self, _sender: &Sender) {}
+
+    /// Record that a valid proposal was received.
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_received(&mut self, _proposal: &ProposalMessageDescription) {}
+
+    fn check_unknown_extensions(
+        &self,
+        _provisional_state: &ProvisionalState,
+        _path_leaf: Option<&LeafNode>,
+    ) -> Result<(), MlsError> {
+        Ok(())
+    }
+
     fn check_metadata(&self, message: &MlsMessage) -> Result<(), MlsError> {
         let context = &self.group_state().context;
 
@@ -828,6 +905,17 @@ pub(crate) trait MessageProcessor: Send + Sync {
             {
                 return Err(MlsError::UnencryptedApplicationMessage);
             }
+
+            #[cfg(feature = "private_message")]
+            if content_type != ContentType::Application {
+                let sender = match &message.payload {
+                    MlsMessagePayload::Plain(plaintext) => Some(&plaintext.content.sender),
+                    _ => None,
+                };
+
+                self.received_control_wire_format()
+                    .check(message.wire_format(), sender)?;
+            }
         }
 
         Ok(())
@@ -863,6 +951,12 @@ pub(crate) trait MessageProcessor: Send + Sync {
         cipher_text: &PrivateMessage,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError>;
 
+    #[cfg(feature = "private_message")]
+    async fn process_targeted_message(
+        &mut self,
+        message: TargetedMessage,
+    ) -> Result<EventOrContent<Self::OutputType>, MlsError>;
+
     async fn verify_plaintext_authentication(
         &self,
         message: PublicMessage,
@@ -15,10 +15,11 @@ use crate::{
 };
 
 #[cfg(feature = "by_ref_proposal")]
-use crate::{extension::ExternalSendersExt, identity::SigningIdentity};
+use crate::extension::ExternalSendersExt;
+use crate::identity::SigningIdentity;
 
 use super::{
-    key_schedule::KeySchedule,
+    membership_tag::MembershipTag,
     message_signature::{AuthenticatedContent, MessageSigningContext},
     state::GroupState,
 };
@@ -37,7 +38,7 @@ pub(crate) enum SignaturePublicKeysContainer<'a> {
 pub(crate) async fn verify_plaintext_authentication<P: CipherSuiteProvider>(
     cipher_suite_provider: &P,
     plaintext: PublicMessage,
-    key_schedule: Option<&KeySchedule>,
+    membership_key: Option<&[u8]>,
     state: &GroupState,
 ) -> Result<AuthenticatedContent, MlsError> {
     let tag = plaintext.membership_tag.clone();
@@ -52,10 +53,14 @@ pub(crate) async fn verify_plaintext_authentication<P: CipherSuiteProvider>(
     // Verify the membership tag if needed
     match &auth_content.content.sender {
         Sender::Member(_) => {
-            if let Some(key_schedule) = key_schedule {
-                let expected_tag = &key_schedule
-                    .get_membership_tag(&auth_content, context, cipher_suite_provider)
-                    .await?;
+            if let Some(membership_key) = membership_key {
+                let expected_tag = &MembershipTag::create(
+                    &auth_content,
+                    context,
+                    membership_key,
+                    cipher_suite_provider,
+                )
+                .await?;
 
                 let plaintext_tag = tag.as_ref().ok_or(MlsError::InvalidMembershipTag)?;
 
@@ -97,6 +102,56 @@ fn external_signers(context: &GroupContext) -> Vec<SigningIdentity> {
         })
 }
 
+/// Signing identity of the sender of `plaintext`, whose signature is verified
+/// with it.
+///
+/// Unlike [`verify_plaintext_authentication`], the membership tag is not
+/// verified and `plaintext` is not consumed.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn verified_sender_identity<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    plaintext: &PublicMessage,
+    state: &GroupState,
+) -> Result<SigningIdentity, MlsError> {
+    let context = &state.context;
+
+    if plaintext.content.group_id != context.group_id {
+        return Err(MlsError::GroupIdMismatch);
+    }
+
+    if plaintext.content.epoch != context.epoch {
+        return Err(MlsError::InvalidEpoch);
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    let external_signers = external_signers(context);
+
+    let sender_identity = signing_identity_for_sender(
+        &state.public_tree,
+        &plaintext.content.sender,
+        &plaintext.content.content,
+        #[cfg(feature = "by_ref_proposal")]
+        &external_signers,
+    )?;
+
+    let auth_content = AuthenticatedContent::from(plaintext.clone());
+
+    let signing_context = MessageSigningContext {
+        group_context: Some(context),
+        protocol_version: context.protocol_version,
+    };
+
+    auth_content
+        .verify(
+            cipher_suite_provider,
+            &sender_identity.signature_key,
+            &signing_context,
+        )
+        .await?;
+
+    Ok(sender_identity.clone())
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn verify_auth_content_signature<P: CipherSuiteProvider>(
     cipher_suite_provider: &P,
@@ -105,7 +160,7 @@ pub(crate) async fn verify_auth_content_signature<P: CipherSuiteProvider>(
     auth_content: &AuthenticatedContent,
     #[cfg(feature = "by_ref_proposal")] external_signers: &[SigningIdentity],
 ) -> Result<(), MlsError> {
-    let sender_public_key = signing_identity_for_sender(
+    let sender_public_key = signature_key_for_sender(
         signature_keys_container,
         &auth_content.content.sender,
         &auth_content.content.content,
@@ -119,22 +174,70 @@ pub(crate) async fn verify_auth_content_signature<P: CipherSuiteProvider>(
     };
 
     auth_content
-        .verify(cipher_suite_provider, &sender_public_key, &context)
+        .verify(cipher_suite_provider, sender_public_key, &context)
         .await?;
 
     Ok(())
 }
 
-fn signing_identity_for_sender(
-    signature_keys_container: SignaturePublicKeysContainer,
+fn signature_key_for_sender<'a>(
+    signature_keys_container: SignaturePublicKeysContainer<'a>,
     sender: &Sender,
-    content: &super::framing::Content,
-    #[cfg(feature = "by_ref_proposal")] external_signers: &[SigningIdentity],
-) -> Result<SignaturePublicKey, MlsError> {
+    content: &'a super::framing::Content,
+    #[cfg(feature = "by_ref_proposal")] external_signers: &'a [SigningIdentity],
+) -> Result<&'a SignaturePublicKey, MlsError> {
+    match signature_keys_container {
+        SignaturePublicKeysContainer::RatchetTree(tree) => signing_identity_for_sender(
+            tree,
+            sender,
+            content,
+            #[cfg(feature = "by_ref_proposal")]
+            external_signers,
+        )
+        .map(|identity| &identity.signature_key),
+        #[cfg(feature = "private_message")]
+        SignaturePublicKeysContainer::List(list) => match sender {
+            Sender::Member(leaf_index) => list
+                .get(*leaf_index as usize)
+                .and_then(|key| key.as_ref())
+                .ok_or(MlsError::LeafNotFound(*leaf_index)),
+            _ => signing_identity_for_non_member(
+                sender,
+                content,
+                #[cfg(feature = "by_ref_proposal")]
+                external_signers,
+            )
+            .map(|identity| &identity.signature_key),
+        },
+    }
+}
+
+fn signing_identity_for_sender<'a>(
+    tree: &'a TreeKemPublic,
+    sender: &Sender,
+    content: &'a super::framing::Content,
+    #[cfg(feature = "by_ref_proposal")] external_signers: &'a [SigningIdentity],
+) -> Result<&'a SigningIdentity, MlsError> {
     match sender {
         Sender::Member(leaf_index) => {
-            signing_identity_for_member(signature_keys_container, LeafIndex(*leaf_index))
+            Ok(&tree.get_leaf_node(LeafIndex(*leaf_index))?.signing_identity)
         }
+        _ => signing_identity_for_non_member(
+            sender,
+            content,
+            #[cfg(feature = "by_ref_proposal")]
+            external_signers,
+        ),
+    }
+}
+
+fn signing_identity_for_non_member<'a>(
+    sender: &Sender,
+    content: &'a super::framing::Content,
+    #[cfg(feature = "by_ref_proposal")] external_signers: &'a [SigningIdentity],
+) -> Result<&'a SigningIdentity, MlsError> {
+    match sender {
+        Sender::Member(_) => Err(MlsError::InvalidSender),
         #[cfg(feature = "by_ref_proposal")]
         Sender::External(external_key_index) => {
             signing_identity_for_external(*external_key_index, external_signers)
@@ -145,43 +248,23 @@ fn signing_identity_for_sender(
     }
 }
 
-fn signing_identity_for_member(
-    signature_keys_container: SignaturePublicKeysContainer,
-    leaf_index: LeafIndex,
-) -> Result<SignaturePublicKey, MlsError> {
-    match signature_keys_container {
-        SignaturePublicKeysContainer::RatchetTree(tree) => Ok(tree
-            .get_leaf_node(leaf_index)?
-            .signing_identity
-            .signature_key
-            .clone()), // TODO: We can probably get rid of this clone
-        #[cfg(feature = "private_message")]
-        SignaturePublicKeysContainer::List(list) => list
-            .get(leaf_index.0 as usize)
-            .cloned()
-            .flatten()
-            .ok_or(MlsError::LeafNotFound(*leaf_index)),
-    }
-}
-
 #[cfg(feature = "by_ref_proposal")]
 fn signing_identity_for_external(
     index: u32,
     external_signers: &[SigningIdentity],
-) -> Result<SignaturePublicKey, MlsError> {
+) -> Result<&SigningIdentity, MlsError> {
     external_signers
         .get(index as usize)
-        .map(|spk| spk.signature_key.clone())
         .ok_or(MlsError::UnknownSigningIdentityForExternalSender)
 }
 
 fn signing_identity_for_new_member_commit(
     content: &super::framing::Content,
-) -> Result<SignaturePublicKey, MlsError> {
+) -> Result<&SigningIdentity, MlsError> {
     match content {
         super::framing::Content::Commit(commit) => {
             if let Some(path) = &commit.path {
-                Ok(path.leaf_node.signing_identity.signature_key.clone())
+                Ok(&path.leaf_node.signing_identity)
             } else {
                 Err(MlsError::CommitMissingPath)
             }
@@ -194,15 +277,11 @@ fn signing_identity_for_new_member_commit(
 #[cfg(feature = "by_ref_proposal")]
 fn signing_identity_for_new_member_proposal(
     content: &super::framing::Content,
-) -> Result<SignaturePublicKey, MlsError> {
+) -> Result<&SigningIdentity, MlsError> {
     match content {
         super::framing::Content::Proposal(proposal) => {
             if let Proposal::Add(p) = proposal.as_ref() {
-                Ok(p.key_package
-                    .leaf_node
-                    .signing_identity
-                    .signature_key
-                    .clone())
+                Ok(&p.key_package.leaf_node.signing_identity)
             } else {
                 Err(MlsError::ExpectedAddProposalForNewMemberProposal)
             }
@@ -230,6 +309,7 @@ mod tests {
     };
     use alloc::vec;
     use assert_matches::assert_matches;
+    use mls_rs_core::crypto::DefaultEpochSecretsVault;
 
     #[cfg(feature = "by_ref_proposal")]
     use crate::{extension::ExternalSendersExt, ExtensionList};
@@ -325,7 +405,7 @@ mod tests {
         verify_plaintext_authentication(
             &env.bob.cipher_suite_provider,
             message,
-            Some(&env.bob.key_schedule),
+            Some(env.bob.key_schedule.membership_key_bytes()),
             &env.bob.state,
         )
         .await
@@ -363,6 +443,7 @@ mod tests {
                 &AuthenticatedContent::from(message.clone()),
                 env.alice.context(),
                 &test_cipher_suite_provider(env.alice.cipher_suite()),
+                &DefaultEpochSecretsVault,
             )
             .await
             .unwrap()
@@ -371,7 +452,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &env.bob.cipher_suite_provider,
             message,
-            Some(&env.bob.key_schedule),
+            Some(env.bob.key_schedule.membership_key_bytes()),
             &env.bob.state,
         )
         .await;
@@ -388,7 +469,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &env.bob.cipher_suite_provider,
             message,
-            Some(&env.bob.key_schedule),
+            Some(env.bob.key_schedule.membership_key_bytes()),
             &env.bob.state,
         )
         .await;
@@ -405,7 +486,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &env.bob.cipher_suite_provider,
             message,
-            Some(&env.bob.key_schedule),
+            Some(env.bob.key_schedule.membership_key_bytes()),
             &env.bob.state,
         )
         .await;
@@ -468,7 +549,7 @@ mod tests {
         verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await
@@ -488,7 +569,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await;
@@ -513,7 +594,7 @@ mod tests {
         let res: Result<AuthenticatedContent, MlsError> = verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await;
@@ -536,7 +617,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await;
@@ -579,7 +660,7 @@ mod tests {
         verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await
@@ -602,7 +683,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await;
@@ -628,7 +709,7 @@ mod tests {
         let res = verify_plaintext_authentication(
             &test_group.cipher_suite_provider,
             message,
-            Some(&test_group.key_schedule),
+            Some(test_group.key_schedule.membership_key_bytes()),
             &test_group.state,
         )
         .await;
@@ -2,17 +2,20 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::group::{proposal_filter::ProposalBundle, Roster};
-
-#[cfg(feature = "private_message")]
 use crate::{
-    group::{padding::PaddingMode, Sender},
-    WireFormat,
+    client::MlsError,
+    group::{padding::GroupInfoPadding, proposal_filter::ProposalBundle, Roster, Sender},
 };
 
-use alloc::boxed::Box;
-use core::convert::Infallible;
-use mls_rs_core::{error::IntoAnyError, group::Member, identity::SigningIdentity};
+#[cfg(feature = "private_message")]
+use crate::{group::padding::PaddingMode, WireFormat};
+
+use alloc::{boxed::Box, vec::Vec};
+use mls_rs_core::{
+    error::IntoAnyError,
+    group::{Member, ProposalType},
+    identity::SigningIdentity,
+};
 
 use super::GroupContext;
 
@@ -30,6 +33,25 @@ pub enum CommitSource {
     NewMember(SigningIdentity),
 }
 
+impl CommitSource {
+    /// Signing identity of the committer.
+    pub fn signing_identity(&self) -> &SigningIdentity {
+        match self {
+            CommitSource::ExistingMember(member) => &member.signing_identity,
+            CommitSource::NewMember(signing_identity) => signing_identity,
+        }
+    }
+
+    /// Index of the committer within the current roster, or `None` if the
+    /// committer is joining via external commit.
+    pub fn member_index(&self) -> Option<u32> {
+        match self {
+            CommitSource::ExistingMember(member) => Some(member.index),
+            CommitSource::NewMember(_) => None,
+        }
+    }
+}
+
 /// Options controlling commit generation
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
@@ -38,6 +60,13 @@ pub struct CommitOptions {
     pub ratchet_tree_extension: bool,
     pub single_welcome_message: bool,
     pub allow_external_commit: bool,
+    /// Padding of welcome messages and of the group info allowing external
+    /// commits. This also applies to the group info messages created with
+    /// [`Group::group_info_message`](crate::Group::group_info_message). It is
+    /// ignored in groups using the
+    /// [`GroupInfoPaddingExt`](crate::mls_rules::GroupInfoPaddingExt) group
+    /// context extension.
+    pub group_info_padding: GroupInfoPadding,
 }
 
 impl Default for CommitOptions {
@@ -47,6 +76,7 @@ impl Default for CommitOptions {
             ratchet_tree_extension: true,
             single_welcome_message: true,
             allow_external_commit: false,
+            group_info_padding: GroupInfoPadding::None,
         }
     }
 }
@@ -83,6 +113,149 @@ impl CommitOptions {
             ..self
         }
     }
+
+    pub fn with_group_info_padding(self, group_info_padding: GroupInfoPadding) -> Self {
+        Self {
+            group_info_padding,
+            ..self
+        }
+    }
+}
+
+/// Per content type policy deciding which control messages are sent as
+/// [`PrivateMessage`](WireFormat::PrivateMessage).
+///
+/// This allows, for example, sending proposals in plaintext so that a
+/// delivery service can inspect them while keeping commits encrypted.
+/// Proposal types without an explicit entry follow the default proposal
+/// setting. At most
+/// [`MAX_PROPOSAL_TYPE_OVERRIDES`](Self::MAX_PROPOSAL_TYPE_OVERRIDES) proposal
+/// types can have an explicit entry.
+#[cfg(feature = "private_message")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ControlWireFormatPolicy {
+    /// Send commits as private messages.
+    pub encrypt_commits: bool,
+    /// Send proposals as private messages unless overridden by their type.
+    pub encrypt_proposals: bool,
+    proposal_overrides: [Option<(ProposalType, bool)>; Self::MAX_PROPOSAL_TYPE_OVERRIDES],
+}
+
+#[cfg(feature = "private_message")]
+impl ControlWireFormatPolicy {
+    /// Maximum number of proposal types overriding the default proposal
+    /// setting.
+    pub const MAX_PROPOSAL_TYPE_OVERRIDES: usize = 8;
+
+    /// Policy applying the same setting to every control message.
+    pub fn uniform(encrypt: bool) -> Self {
+        Self {
+            encrypt_commits: encrypt,
+            encrypt_proposals: encrypt,
+            proposal_overrides: Default::default(),
+        }
+    }
+
+    /// Set whether commits are sent as private messages.
+    pub fn with_commit_encryption(self, encrypt_commits: bool) -> Self {
+        Self {
+            encrypt_commits,
+            ..self
+        }
+    }
+
+    /// Set whether proposals without an override for their type are sent as
+    /// private messages.
+    pub fn with_proposal_encryption(self, encrypt_proposals: bool) -> Self {
+        Self {
+            encrypt_proposals,
+            ..self
+        }
+    }
+
+    /// Override the default proposal setting for proposals of type
+    /// `proposal_type`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::TooManyProposalTypeOverrides`] if
+    /// [`MAX_PROPOSAL_TYPE_OVERRIDES`](Self::MAX_PROPOSAL_TYPE_OVERRIDES)
+    /// other proposal types are already overridden.
+    pub fn with_proposal_type_encryption(
+        mut self,
+        proposal_type: ProposalType,
+        encrypt: bool,
+    ) -> Result<Self, MlsError> {
+        let entry = self
+            .proposal_overrides
+            .iter_mut()
+            .find(|entry| entry.map_or(true, |(t, _)| t == proposal_type))
+            .ok_or(MlsError::TooManyProposalTypeOverrides)?;
+
+        *entry = Some((proposal_type, encrypt));
+
+        Ok(self)
+    }
+
+    /// Whether proposals of type `proposal_type` are sent as private messages.
+    pub fn encrypt_proposal(&self, proposal_type: ProposalType) -> bool {
+        self.proposal_overrides
+            .iter()
+            .flatten()
+            .find_map(|(t, encrypt)| (*t == proposal_type).then_some(*encrypt))
+            .unwrap_or(self.encrypt_proposals)
+    }
+}
+
+/// Wire format required for proposals and commits received from members.
+///
+/// Application messages are always required to be sent as
+/// [`PrivateMessage`](WireFormat::PrivateMessage). This policy additionally
+/// lets a deployment reject control messages that peers with a different
+/// [`ControlWireFormatPolicy`] send in the wrong wire format, so that a
+/// misconfigured peer can't silently downgrade the confidentiality of group
+/// traffic. It is set with
+/// [`ClientBuilder::received_control_wire_format`](crate::client_builder::ClientBuilder::received_control_wire_format).
+#[cfg(feature = "private_message")]
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum ReceivedControlWireFormat {
+    /// Accept control messages in either wire format.
+    #[default]
+    Any,
+    /// Reject control messages sent by members as
+    /// [`PublicMessage`](WireFormat::PublicMessage). Messages from senders
+    /// that are not members, such as external commits, can only be sent as
+    /// public messages and are still accepted.
+    Encrypted,
+    /// Reject control messages sent as
+    /// [`PrivateMessage`](WireFormat::PrivateMessage), for deployments in
+    /// which the delivery service must inspect them.
+    Plaintext,
+}
+
+#[cfg(feature = "private_message")]
+impl ReceivedControlWireFormat {
+    pub(crate) fn check(
+        &self,
+        wire_format: WireFormat,
+        sender: Option<&Sender>,
+    ) -> Result<(), MlsError> {
+        let accepted = match self {
+            Self::Any => true,
+            Self::Encrypted => {
+                wire_format == WireFormat::PrivateMessage
+                    || !matches!(sender, Some(Sender::Member(_)))
+            }
+            Self::Plaintext => wire_format == WireFormat::PublicMessage,
+        };
+
+        accepted
+            .then_some(())
+            .ok_or(MlsError::UnexpectedWireFormat(wire_format))
+    }
 }
 
 /// Options controlling encryption of control and application messages
@@ -93,25 +266,197 @@ pub struct EncryptionOptions {
     pub encrypt_control_messages: bool,
     #[cfg(feature = "private_message")]
     pub padding_mode: PaddingMode,
+    /// Fine grained policy for control messages. If set, it takes precedence
+    /// over `encrypt_control_messages`.
+    #[cfg(feature = "private_message")]
+    pub control_wire_format_policy: Option<ControlWireFormatPolicy>,
 }
 
 #[cfg(feature = "private_message")]
 impl EncryptionOptions {
+    /// Options applying `encrypt_control_messages` to every control message.
     pub fn new(encrypt_control_messages: bool, padding_mode: PaddingMode) -> Self {
         Self {
             encrypt_control_messages,
             padding_mode,
+            control_wire_format_policy: None,
+        }
+    }
+
+    /// Decide per content type which control messages are encrypted,
+    /// instead of applying `encrypt_control_messages` to all of them.
+    pub fn with_control_wire_format_policy(self, policy: ControlWireFormatPolicy) -> Self {
+        Self {
+            control_wire_format_policy: Some(policy),
+            ..self
         }
     }
 
-    pub(crate) fn control_wire_format(&self, sender: Sender) -> WireFormat {
+    pub(crate) fn commit_wire_format(&self, sender: Sender) -> WireFormat {
+        let encrypt = self
+            .control_wire_format_policy
+            .as_ref()
+            .map_or(self.encrypt_control_messages, |p| p.encrypt_commits);
+
+        Self::member_wire_format(sender, encrypt)
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    pub(crate) fn proposal_wire_format(
+        &self,
+        sender: Sender,
+        proposal_type: ProposalType,
+    ) -> WireFormat {
+        let encrypt = self
+            .control_wire_format_policy
+            .as_ref()
+            .map_or(self.encrypt_control_messages, |p| {
+                p.encrypt_proposal(proposal_type)
+            });
+
+        Self::member_wire_format(sender, encrypt)
+    }
+
+    fn member_wire_format(sender: Sender, encrypt: bool) -> WireFormat {
         match sender {
-            Sender::Member(_) if self.encrypt_control_messages => WireFormat::PrivateMessage,
+            Sender::Member(_) if encrypt => WireFormat::PrivateMessage,
             _ => WireFormat::PublicMessage,
         }
     }
 }
 
+/// Kind of proposal sender distinguished by a [`ProposalSenderPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProposalSenderType {
+    /// A current member of the group.
+    Member,
+    /// An external sender listed in the
+    /// [`ExternalSendersExt`](crate::extension::ExternalSendersExt).
+    External,
+    /// A new member, either proposing their own addition or committing
+    /// externally.
+    NewMember,
+}
+
+impl From<&Sender> for ProposalSenderType {
+    fn from(sender: &Sender) -> Self {
+        match sender {
+            Sender::Member(_) => Self::Member,
+            #[cfg(feature = "by_ref_proposal")]
+            Sender::External(_) => Self::External,
+            #[cfg(feature = "by_ref_proposal")]
+            Sender::NewMemberProposal => Self::NewMember,
+            Sender::NewMemberCommit => Self::NewMember,
+        }
+    }
+}
+
+/// Declarative policy deciding which sender types may send which proposal
+/// types.
+///
+/// The policy is applied on top of the sender rules of RFC 9420, which are
+/// always enforced, so it can only make them stricter. Pairs of sender type
+/// and proposal type without an explicit entry follow the default setting of
+/// the sender type, which allows everything unless changed with
+/// [`ProposalSenderPolicy::with_sender_default`].
+///
+/// Each member of a group must use the same policy, otherwise commits
+/// accepted by one member are rejected by others.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProposalSenderPolicy {
+    sender_defaults: Vec<(ProposalSenderType, bool)>,
+    permissions: Vec<(ProposalSenderType, ProposalType, bool)>,
+}
+
+impl ProposalSenderPolicy {
+    /// Policy allowing everything allowed by RFC 9420.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Allow proposals of type `proposal_type` from senders of type `sender`.
+    pub fn allow(self, sender: ProposalSenderType, proposal_type: ProposalType) -> Self {
+        self.with_permission(sender, proposal_type, true)
+    }
+
+    /// Reject proposals of type `proposal_type` from senders of type `sender`.
+    pub fn deny(self, sender: ProposalSenderType, proposal_type: ProposalType) -> Self {
+        self.with_permission(sender, proposal_type, false)
+    }
+
+    /// Set whether senders of type `sender` may send proposal types without an
+    /// explicit entry.
+    pub fn with_sender_default(mut self, sender: ProposalSenderType, allowed: bool) -> Self {
+        match self.sender_defaults.iter_mut().find(|(s, _)| *s == sender) {
+            Some(entry) => entry.1 = allowed,
+            None => self.sender_defaults.push((sender, allowed)),
+        }
+
+        self
+    }
+
+    fn with_permission(
+        mut self,
+        sender: ProposalSenderType,
+        proposal_type: ProposalType,
+        allowed: bool,
+    ) -> Self {
+        match self
+            .permissions
+            .iter_mut()
+            .find(|(s, t, _)| *s == sender && *t == proposal_type)
+        {
+            Some(entry) => entry.2 = allowed,
+            None => self.permissions.push((sender, proposal_type, allowed)),
+        }
+
+        self
+    }
+
+    /// Whether senders of type `sender` may send proposals of type
+    /// `proposal_type`.
+    pub fn is_allowed(&self, sender: ProposalSenderType, proposal_type: ProposalType) -> bool {
+        self.permissions
+            .iter()
+            .find_map(|(s, t, allowed)| (*s == sender && *t == proposal_type).then_some(*allowed))
+            .or_else(|| {
+                self.sender_defaults
+                    .iter()
+                    .find_map(|(s, allowed)| (*s == sender).then_some(*allowed))
+            })
+            .unwrap_or(true)
+    }
+
+    /// Apply the policy to `proposals`. Disallowed by reference proposals are
+    /// removed when preparing a commit, any other disallowed proposal results
+    /// in [`MlsError::InvalidProposalTypeForSender`].
+    pub(crate) fn apply(
+        &self,
+        direction: CommitDirection,
+        mut proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, MlsError> {
+        let check = |sender: &Sender, proposal_type, by_reference: bool| {
+            if self.is_allowed(sender.into(), proposal_type) {
+                Ok(true)
+            } else if by_reference && direction == CommitDirection::Send {
+                Ok(false)
+            } else {
+                Err(MlsError::InvalidProposalTypeForSender)
+            }
+        };
+
+        proposals.retain(|p| check(&p.sender, p.proposal.proposal_type(), p.is_by_reference()))?;
+
+        #[cfg(feature = "custom_proposal")]
+        proposals
+            .retain_custom(|p| check(&p.sender, p.proposal.proposal_type(), p.is_by_reference()))?;
+
+        Ok(proposals)
+    }
+}
+
 /// A set of user controlled rules that customize the behavior of MLS.
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
@@ -134,6 +479,12 @@ pub trait MlsRules: Send + Sync {
     /// out instead. This is to avoid the deadlock situation when no commit can be generated
     /// after receiving an invalid set of proposal messages.
     ///
+    /// The `source`, `current_roster` and `current_context` give a read-only view of the
+    /// committer, the current members and the current group context extensions. This
+    /// allows implementing policies such as "only members listed in a group context
+    /// extension may change the group context extensions" without duplicating group
+    /// state outside of the library.
+    ///
     /// `ProposalBundle` can be arbitrarily modified. For example, a Remove proposal that
     /// removes a moderator can result in adding a GroupContextExtensions proposal that updates
     /// the moderator list in the group context. The resulting `ProposalBundle` is validated
@@ -218,10 +569,13 @@ delegate_mls_rules!(&T);
 
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
-/// Default MLS rules with pass-through proposal filter and customizable options.
+/// Default MLS rules with customizable options. Proposals are only filtered
+/// by the [`ProposalSenderPolicy`], which allows everything allowed by
+/// RFC 9420 unless configured otherwise.
 pub struct DefaultMlsRules {
     pub commit_options: CommitOptions,
     pub encryption_options: EncryptionOptions,
+    pub proposal_sender_policy: ProposalSenderPolicy,
 }
 
 impl DefaultMlsRules {
@@ -235,15 +589,23 @@ impl DefaultMlsRules {
     pub fn with_commit_options(self, commit_options: CommitOptions) -> Self {
         Self {
             commit_options,
-            encryption_options: self.encryption_options,
+            ..self
         }
     }
 
     /// Set encryption options.
     pub fn with_encryption_options(self, encryption_options: EncryptionOptions) -> Self {
         Self {
-            commit_options: self.commit_options,
             encryption_options,
+            ..self
+        }
+    }
+
+    /// Set the policy deciding which senders may send which proposal types.
+    pub fn with_proposal_sender_policy(self, proposal_sender_policy: ProposalSenderPolicy) -> Self {
+        Self {
+            proposal_sender_policy,
+            ..self
         }
     }
 }
@@ -251,17 +613,17 @@ impl DefaultMlsRules {
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
 impl MlsRules for DefaultMlsRules {
-    type Error = Infallible;
+    type Error = MlsError;
 
     async fn filter_proposals(
         &self,
-        _direction: CommitDirection,
+        direction: CommitDirection,
         _source: CommitSource,
         _current_roster: &Roster,
         _: &GroupContext,
         proposals: ProposalBundle,
     ) -> Result<ProposalBundle, Self::Error> {
-        Ok(proposals)
+        self.proposal_sender_policy.apply(direction, proposals)
     }
 
     fn commit_options(
@@ -281,3 +643,92 @@ impl MlsRules for DefaultMlsRules {
         Ok(self.encryption_options)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::group::ProposalType;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group_custom_config,
+    };
+
+    use super::{DefaultMlsRules, ProposalSenderPolicy, ProposalSenderType};
+
+    #[cfg(feature = "private_message")]
+    #[test]
+    fn control_wire_format_policy_overrides_are_bounded() {
+        use super::ControlWireFormatPolicy;
+
+        let mut policy = ControlWireFormatPolicy::uniform(false)
+            .with_proposal_type_encryption(ProposalType::ADD, true)
+            .unwrap()
+            .with_proposal_type_encryption(ProposalType::ADD, false)
+            .unwrap();
+
+        assert!(!policy.encrypt_proposal(ProposalType::ADD));
+
+        for i in 1..ControlWireFormatPolicy::MAX_PROPOSAL_TYPE_OVERRIDES as u16 {
+            policy = policy
+                .with_proposal_type_encryption(ProposalType::new(0xf000 + i), true)
+                .unwrap();
+        }
+
+        assert!(policy.encrypt_proposal(ProposalType::new(0xf001)));
+        assert!(!policy.encrypt_proposal(ProposalType::REMOVE));
+
+        let res = policy.with_proposal_type_encryption(ProposalType::REMOVE, true);
+        assert_matches!(res, Err(MlsError::TooManyProposalTypeOverrides));
+    }
+
+    #[test]
+    fn proposal_sender_policy_resolves_permissions() {
+        let policy = ProposalSenderPolicy::new()
+            .with_sender_default(ProposalSenderType::External, false)
+            .allow(ProposalSenderType::External, ProposalType::ADD)
+            .deny(ProposalSenderType::Member, ProposalType::RE_INIT);
+
+        assert!(policy.is_allowed(ProposalSenderType::External, ProposalType::ADD));
+        assert!(!policy.is_allowed(ProposalSenderType::External, ProposalType::REMOVE));
+        assert!(!policy.is_allowed(ProposalSenderType::Member, ProposalType::RE_INIT));
+        assert!(policy.is_allowed(ProposalSenderType::Member, ProposalType::REMOVE));
+        assert!(policy.is_allowed(ProposalSenderType::NewMember, ProposalType::REMOVE));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposal_sender_policy_is_applied_to_commits() {
+        let policy =
+            ProposalSenderPolicy::new().deny(ProposalSenderType::Member, ProposalType::REMOVE);
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.mls_rules(DefaultMlsRules::new().with_proposal_sender_policy(policy))
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+        let (_, commit) = alice.join("carol").await;
+        bob.process_message(commit).await.unwrap();
+
+        let proposal = bob.propose_remove(2, vec![]).await.unwrap();
+        alice.process_message(proposal).await.unwrap();
+
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+        assert_eq!(alice.roster().members_iter().count(), 3);
+
+        let res = alice
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MlsRulesError(_)));
+    }
+}
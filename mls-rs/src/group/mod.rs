@@ -2,6 +2,9 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::boxed::Box;
+#[cfg(feature = "debug_utils")]
+use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
@@ -12,6 +15,7 @@ use mls_rs_core::extension::MlsExtension;
 use mls_rs_core::identity::MemberValidationContext;
 use mls_rs_core::secret::Secret;
 use mls_rs_core::time::MlsTime;
+use zeroize::Zeroizing;
 
 use crate::cipher_suite::CipherSuite;
 use crate::client::MlsError;
@@ -20,6 +24,7 @@ use crate::crypto::{HpkeCiphertext, SignatureSecretKey};
 #[cfg(feature = "last_resort_key_package_ext")]
 use crate::extension::LastResortKeyPackageExt;
 use crate::extension::RatchetTreeExt;
+use crate::extension::RequiredCapabilitiesExt;
 use crate::identity::SigningIdentity;
 use crate::key_package::{KeyPackage, KeyPackageGeneration, KeyPackageRef};
 use crate::protocol_version::ProtocolVersion;
@@ -41,19 +46,31 @@ pub use state::GroupState;
 #[cfg(feature = "by_ref_proposal")]
 use crate::crypto::{HpkePublicKey, HpkeSecretKey};
 
+#[cfg(feature = "by_ref_proposal")]
+use crate::extension::ExtensionType;
+
 use crate::extension::ExternalPubExt;
 
 use self::message_hash::MessageHash;
 #[cfg(feature = "private_message")]
-use self::mls_rules::{EncryptionOptions, MlsRules};
+use self::mls_rules::EncryptionOptions;
+use self::mls_rules::MlsRules;
+#[cfg(feature = "private_message")]
+use crate::compression::{
+    compress_application_data, decompress_application_data, ApplicationCompressionExt,
+    CompressionAlgorithm,
+};
 
 #[cfg(feature = "psk")]
 pub use self::resumption::ReinitClient;
 
+#[cfg(all(feature = "psk", feature = "private_message"))]
+pub use self::reinit_key_packages::ReinitKeyPackageCollector;
+
 #[cfg(feature = "psk")]
 use crate::psk::{
-    resolver::PskResolver, secret::PskSecretInput, ExternalPskId, JustPreSharedKeyID, PskGroupId,
-    ResumptionPSKUsage, ResumptionPsk,
+    resolver::PskResolver, secret::PskSecretInput, ExternalPskId, GroupLineage, JustPreSharedKeyID,
+    PskGroupId, ResumptionPSKUsage, ResumptionPsk,
 };
 
 #[cfg(feature = "private_message")]
@@ -84,7 +101,11 @@ use secret_tree::*;
 #[cfg(feature = "prior_epoch")]
 use self::epoch::PriorEpoch;
 
+use self::app_data::AppData;
 use self::epoch::EpochSecrets;
+use self::future_epoch_buffer::{BufferOutcome, FutureEpochBuffer};
+use self::liveness::LivenessLog;
+use self::member_uid::MemberUids;
 pub use self::message_processor::{
     ApplicationMessageDescription, CommitEffect, CommitMessageDescription, NewEpoch,
     ProposalMessageDescription, ProposalSender, ReceivedMessage,
@@ -92,48 +113,161 @@ pub use self::message_processor::{
 use self::message_processor::{EventOrContent, MessageProcessor, ProvisionalState};
 #[cfg(feature = "by_ref_proposal")]
 use self::proposal_ref::ProposalRef;
+use self::quarantine::Quarantine;
+use self::removal_template::RemovalTemplates;
+use self::roster_export::LeafUpdateEpochs;
+use self::state_integrity::StateIntegrity;
 use self::state_repo::GroupStateRepository;
+#[cfg(feature = "private_message")]
+use self::targeted_message::TargetedMessage;
+use self::unconfirmed_members::UnconfirmedMembers;
+use self::unknown_extensions::{introduced_extensions, reject_unknown_extensions};
 pub use group_info::GroupInfo;
+pub use group_info_verification::{GroupInfoIssue, GroupInfoReport, GroupInfoTrustConfig};
 
 pub use self::framing::{ContentType, Sender};
+#[cfg(feature = "debug_utils")]
+pub use crate::tree_kem::tree_utils::DebugTreeFormat;
+#[cfg(feature = "private_message")]
+pub use bridge::BridgedMessage;
+pub use chunked_commit::ChunkedCommit;
+#[cfg(feature = "private_message")]
+pub use chunked_message::ChunkedMessageReassembler;
 pub use commit::*;
+pub use commit_path_policy::{CommitPathPolicy, PathHealth};
+pub use config_overrides::GroupConfigOverrides;
+pub use delegated_commit::CommitRequest;
+pub use flush::FlushMode;
+pub use future_epoch_buffer::{EvictionPolicy, FutureEpochBufferConfig, FutureEpochBufferMetrics};
+pub use hpke_export::{HpkeExportEncapsulation, HpkeExportReceiver, HpkeExportSender};
+#[cfg(feature = "invariant_checks")]
+pub use invariants::{InvariantReport, InvariantViolation};
+#[cfg(all(feature = "debug_utils", feature = "private_message"))]
+pub use key_deletion_audit::{DeletedKeyRecord, KeyDeletionAudit};
+pub use lazy::LazyGroup;
+#[cfg(feature = "by_ref_proposal")]
+pub use liveness::LivenessPolicy;
+pub use member_uid::MemberUid;
+pub use member_validation::InvalidMember;
+pub use membership_proof::MembershipProof;
+pub use membership_token::{MembershipToken, MembershipTokenKey};
+pub use message_batch::MessageBatchReport;
 pub use mls_rs_core::group::GroupContext;
+pub use operation::OperationToken;
+pub use pairwise_channel::{PairwiseChannel, PairwiseChannelInit};
+pub use receive_only::ReceiveOnlyPolicy;
+pub use rejection::{RejectionCode, RejectionReport};
 pub use roster::*;
+#[cfg(feature = "prior_epoch")]
+pub use roster_diff::{RosterDiff, RosterDiffMember, RosterDiffUpdate};
+pub use roster_export::RosterExportFormat;
+pub use safety_code::{SafetyCode, SafetyCodeConfig, SafetyCodeEncoding, MAX_SAFETY_CODE_LENGTH};
+#[cfg(all(feature = "debug_utils", feature = "private_message"))]
+pub use secret_tree::KeyType;
+pub use simulation::{SimulationConfig, SimulationStorage, SimulationVault};
+pub use snapshot_format::{SnapshotFeatures, SnapshotFormat};
+pub use subset_key::{SubsetKey, SubsetKeyShares};
+pub use transcript_hash::EpochTranscript;
+pub use unconfirmed_members::UnconfirmedMember;
+#[cfg(feature = "private_message")]
+pub use unconfirmed_members::UnconfirmedMemberPolicy;
+pub use unknown_extensions::UnknownExtensions;
+#[cfg(feature = "by_ref_proposal")]
+pub use update_proposal_builder::UpdateProposalBuilder;
+pub use validation_report::{
+    MissingCapability, SkippedKeyPackage, ValidationIssue, ValidationReport,
+};
+pub use view::{EpochView, GroupView, MemberView};
 
+pub(crate) use group_info_verification::verify_group_info;
 pub(crate) use mls_rs_core::group::ConfirmedTranscriptHash;
+pub(crate) use operation::PendingOperation;
 pub(crate) use util::*;
 
 #[cfg(all(feature = "by_ref_proposal", feature = "external_client"))]
 pub use self::message_processor::CachedProposal;
 
+#[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+mod archive;
 #[cfg(feature = "private_message")]
-mod ciphertext_processor;
+pub(crate) mod ciphertext_processor;
+#[cfg(feature = "prior_epoch")]
+mod epoch_proof;
+#[cfg(feature = "prior_epoch")]
+mod epoch_retention;
 
+mod app_data;
+mod application_signature;
+#[cfg(feature = "private_message")]
+mod bridge;
+pub(crate) mod capability_enforcement;
+mod chunked_commit;
+#[cfg(feature = "private_message")]
+mod chunked_message;
 mod commit;
+mod commit_path_policy;
+mod config_overrides;
 pub(crate) mod confirmation_tag;
+mod credential_expiry;
+mod delegated_commit;
 pub(crate) mod epoch;
+mod flush;
 pub(crate) mod framing;
+mod future_epoch_buffer;
 mod group_info;
+mod group_info_verification;
+mod hpke_export;
+#[cfg(feature = "invariant_checks")]
+mod invariants;
+#[cfg(all(feature = "debug_utils", feature = "private_message"))]
+mod key_deletion_audit;
 pub(crate) mod key_schedule;
+mod lazy;
+mod liveness;
+mod member_uid;
+mod member_validation;
+mod membership_proof;
 mod membership_tag;
+mod membership_token;
+mod message_batch;
 pub(crate) mod message_hash;
 pub(crate) mod message_processor;
 pub(crate) mod message_signature;
 pub(crate) mod message_verifier;
 pub mod mls_rules;
-#[cfg(feature = "private_message")]
+mod operation;
 pub(crate) mod padding;
+mod pairwise_channel;
 /// Proposals to evolve a MLS [`Group`]
 pub mod proposal;
 mod proposal_cache;
 pub(crate) mod proposal_filter;
 #[cfg(feature = "by_ref_proposal")]
 pub(crate) mod proposal_ref;
+mod quarantine;
+mod receive_only;
+#[cfg(feature = "private_message")]
+mod reframe;
+#[cfg(all(feature = "psk", feature = "private_message"))]
+mod reinit_key_packages;
+mod rejection;
+mod removal_template;
 #[cfg(feature = "psk")]
 mod resumption;
 mod roster;
+#[cfg(feature = "prior_epoch")]
+mod roster_diff;
+mod roster_export;
+mod safety_code;
+mod signed_seed;
+mod simulation;
 pub(crate) mod snapshot;
+mod snapshot_format;
 pub(crate) mod state;
+pub(crate) mod state_integrity;
+mod subset_key;
+#[cfg(feature = "private_message")]
+pub(crate) mod targeted_message;
 
 #[cfg(feature = "prior_epoch")]
 pub(crate) mod state_repo;
@@ -143,7 +277,13 @@ pub(crate) mod state_repo_light;
 pub(crate) use state_repo_light as state_repo;
 
 pub(crate) mod transcript_hash;
+mod unconfirmed_members;
+mod unknown_extensions;
+#[cfg(feature = "by_ref_proposal")]
+mod update_proposal_builder;
 mod util;
+mod validation_report;
+mod view;
 
 /// External commit building.
 pub mod external_commit;
@@ -161,8 +301,20 @@ mod exported_tree;
 
 pub use exported_tree::ExportedTree;
 
+#[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+pub use archive::ArchivedGroup;
+
+#[cfg(feature = "prior_epoch")]
+pub use epoch_proof::{EpochAuthenticatorPin, EpochAuthenticatorProof};
+
+#[cfg(feature = "prior_epoch")]
+pub use epoch_retention::{EpochDeletion, EpochRetentionPolicy};
+
+#[cfg(feature = "prior_epoch")]
+use epoch_retention::EpochRetentionLog;
+
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
-struct GroupSecrets {
+pub(crate) struct GroupSecrets {
     joiner_secret: JoinerSecret,
     path_secret: Option<PathSecret>,
     psks: Vec<PreSharedKeyID>,
@@ -171,7 +323,7 @@ struct GroupSecrets {
 impl HpkeEncryptable for GroupSecrets {
     const ENCRYPT_LABEL: &'static str = "Welcome";
 
-    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
+    fn from_bytes(bytes: Zeroizing<Vec<u8>>) -> Result<Self, MlsError> {
         Self::mls_decode(&mut bytes.as_slice()).map_err(Into::into)
     }
 
@@ -257,6 +409,20 @@ impl NewMemberInfo {
 /// to advance to the next epoch by applying existing proposals sent in
 /// the current epoch by-reference along with an optional set of proposals
 /// that are included by-value using a [`CommitBuilder`].
+///
+/// # Cancellation
+///
+/// With an async runtime, the future of a method taking `&mut self` may be
+/// dropped at any await point, for example on a timeout. The group remains
+/// usable, but the operation may be partially applied: a commit may have
+/// consumed a handshake key without being returned, or a message may have
+/// been decrypted, consuming its key, without being returned. Writes to
+/// storage in [`FlushMode::Immediate`] happen after the state in memory is
+/// changed, and [`Group::has_unflushed_changes`] tells whether a dropped
+/// write must be retried with [`Group::flush`].
+///
+/// Operations started with [`Group::begin_operation`] can instead be retried
+/// or rolled back as a whole after their future is dropped.
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type(opaque))]
 #[derive(Clone)]
 pub struct Group<C>
@@ -265,7 +431,11 @@ where
 {
     config: C,
     cipher_suite_provider: <C::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
-    state_repo: GroupStateRepository<C::GroupStateStorage, C::KeyPackageRepository>,
+    state_repo: GroupStateRepository<
+        C::GroupStateStorage,
+        C::KeyPackageRepository,
+        <C::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
+    >,
     pub(crate) state: GroupState,
     epoch_secrets: EpochSecrets,
     private_tree: TreeKemPrivate,
@@ -279,6 +449,28 @@ where
     #[cfg(test)]
     pub(crate) commit_modifiers: CommitModifiers,
     pub(crate) signer: SignatureSecretKey,
+    leaf_update_epochs: LeafUpdateEpochs,
+    member_uids: MemberUids,
+    liveness_log: LivenessLog,
+    app_data: AppData,
+    config_overrides: GroupConfigOverrides,
+    #[cfg(feature = "psk")]
+    lineage: GroupLineage,
+    quarantine: Quarantine,
+    unconfirmed_members: UnconfirmedMembers,
+    future_epoch_buffer: FutureEpochBuffer,
+    flush_mode: FlushMode,
+    unflushed_changes: bool,
+    /// Key schedules of past epochs, whose secrets are released from the
+    /// vault once the state of a later epoch is written to storage.
+    retired_key_schedules: Vec<KeySchedule>,
+    operation: Option<Box<PendingOperation<C>>>,
+    next_operation_id: u64,
+    removal_templates: Option<RemovalTemplates>,
+    #[cfg(feature = "prior_epoch")]
+    epoch_retention: EpochRetentionLog,
+    #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+    key_deletion_audit: Option<KeyDeletionAudit>,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen)]
@@ -294,10 +486,33 @@ where
         cipher_suite: CipherSuite,
         protocol_version: ProtocolVersion,
         signing_identity: SigningIdentity,
-        group_context_extensions: ExtensionList,
+        mut group_context_extensions: ExtensionList,
         leaf_node_extensions: ExtensionList,
         signer: SignatureSecretKey,
     ) -> Result<Self, MlsError> {
+        reject_unknown_extensions(&config, [&group_context_extensions, &leaf_node_extensions])?;
+
+        if config.require_configured_capabilities()
+            && !group_context_extensions
+                .has_extension(crate::extension::ExtensionType::REQUIRED_CAPABILITIES)
+        {
+            let required_capabilities = RequiredCapabilitiesExt {
+                extensions: config
+                    .supported_extensions()
+                    .into_iter()
+                    .filter(|ext_type| !ext_type.is_default())
+                    .collect(),
+                proposals: config.supported_custom_proposals(),
+                credentials: Vec::new(),
+            };
+
+            if !required_capabilities.extensions.is_empty()
+                || !required_capabilities.proposals.is_empty()
+            {
+                group_context_extensions.set_from(required_capabilities)?;
+            }
+        }
+
         let cipher_suite_provider = cipher_suite_provider(config.crypto_provider(), cipher_suite)?;
 
         let (leaf_node, leaf_node_secret) = LeafNode::generate(
@@ -358,10 +573,12 @@ where
             config.group_state_storage(),
             config.key_package_repo(),
             None,
-        )?;
+        )?
+        .with_integrity(StateIntegrity::from_config(&config, &cipher_suite_provider));
 
         let key_schedule_result = KeySchedule::from_random_epoch_secret(
             &cipher_suite_provider,
+            &config.epoch_secrets_vault(),
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             public_tree.total_leaf_count(),
         )
@@ -381,6 +598,14 @@ where
         )
         .await?;
 
+        let leaf_update_epochs = LeafUpdateEpochs::new(private_tree.self_index, context.epoch);
+        let member_uids =
+            MemberUids::new(&cipher_suite_provider, &public_tree, context.epoch).await?;
+        let liveness_log = LivenessLog::new(private_tree.self_index);
+
+        #[cfg(feature = "prior_epoch")]
+        let epoch_retention = EpochRetentionLog::new(context.epoch);
+
         Ok(Self {
             config,
             state: GroupState::new(context, public_tree, interim_hash, confirmation_tag),
@@ -397,6 +622,26 @@ where
             #[cfg(feature = "psk")]
             previous_psk: None,
             signer,
+            leaf_update_epochs,
+            member_uids,
+            liveness_log,
+            app_data: Default::default(),
+            config_overrides: Default::default(),
+            #[cfg(feature = "psk")]
+            lineage: Default::default(),
+            quarantine: Default::default(),
+            unconfirmed_members: Default::default(),
+            future_epoch_buffer: Default::default(),
+            flush_mode: Default::default(),
+            unflushed_changes: true,
+            retired_key_schedules: Vec::new(),
+            operation: None,
+            next_operation_id: 0,
+            removal_templates: None,
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention,
+            #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+            key_deletion_audit: None,
         })
     }
 
@@ -489,6 +734,7 @@ where
         // other derived secrets for the current epoch.
         let key_schedule_result = KeySchedule::from_joiner(
             &cipher_suite_provider,
+            &config.epoch_secrets_vault(),
             &group_secrets.joiner_secret,
             &group_info.group_context,
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
@@ -536,6 +782,15 @@ where
         used_key_package_ref: Option<KeyPackageRef>,
         signer: SignatureSecretKey,
     ) -> Result<(Self, NewMemberInfo), MlsError> {
+        reject_unknown_extensions(
+            &config,
+            core::iter::once(&group_info.group_context.extensions).chain(
+                public_tree
+                    .non_empty_leaves()
+                    .map(|(_, leaf)| &leaf.extensions),
+            ),
+        )?;
+
         let cs = group_info.group_context.cipher_suite;
 
         let cs = config
@@ -558,7 +813,19 @@ where
             config.group_state_storage(),
             config.key_package_repo(),
             used_key_package_ref,
-        )?;
+        )?
+        .with_integrity(StateIntegrity::from_config(&config, &cs));
+
+        let leaf_update_epochs =
+            LeafUpdateEpochs::new(private_tree.self_index, group_info.group_context.epoch);
+
+        let member_uids =
+            MemberUids::new(&cs, &public_tree, group_info.group_context.epoch).await?;
+
+        let liveness_log = LivenessLog::new(private_tree.self_index);
+
+        #[cfg(feature = "prior_epoch")]
+        let epoch_retention = EpochRetentionLog::new(group_info.group_context.epoch);
 
         let group = Group {
             config,
@@ -581,6 +848,26 @@ where
             #[cfg(feature = "psk")]
             previous_psk: None,
             signer,
+            leaf_update_epochs,
+            member_uids,
+            liveness_log,
+            app_data: Default::default(),
+            config_overrides: Default::default(),
+            #[cfg(feature = "psk")]
+            lineage: Default::default(),
+            quarantine: Default::default(),
+            unconfirmed_members: Default::default(),
+            future_epoch_buffer: Default::default(),
+            flush_mode: Default::default(),
+            unflushed_changes: true,
+            retired_key_schedules: Vec::new(),
+            operation: None,
+            next_operation_id: 0,
+            removal_templates: None,
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention,
+            #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+            key_deletion_audit: None,
         };
 
         Ok((
@@ -621,6 +908,28 @@ where
         self.current_user_leaf_node().map(|ln| &ln.signing_identity)
     }
 
+    /// Signing identity of the sender of a proposal or commit sent as a
+    /// [`PublicMessage`](WireFormat::PublicMessage) in the current epoch,
+    /// after verifying the signature of `message` with it.
+    ///
+    /// The sender is looked up in the current ratchet tree, in the external
+    /// senders extension or in the content of the message for new members.
+    /// This does not otherwise process `message`, which allows attributing
+    /// messages for rate limiting or auditing before processing them. In
+    /// particular, the membership tag is not verified.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn public_message_sender(
+        &self,
+        message: &MlsMessage,
+    ) -> Result<SigningIdentity, MlsError> {
+        let MlsMessagePayload::Plain(plaintext) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        verified_sender_identity(&self.cipher_suite_provider, plaintext, &self.state).await
+    }
+
     /// Member at a specific index in the group state.
     ///
     /// These indexes correspond to indexes in content descriptions within
@@ -636,6 +945,8 @@ where
         proposal: Proposal,
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
+        self.check_not_receive_only()?;
+
         let sender = Sender::Member(*self.private_tree.self_index);
 
         let auth_content = AuthenticatedContent::new_signed(
@@ -645,7 +956,8 @@ where
             Content::Proposal(alloc::boxed::Box::new(proposal.clone())),
             &self.signer,
             #[cfg(feature = "private_message")]
-            self.encryption_options()?.control_wire_format(sender),
+            self.encryption_options()?
+                .proposal_wire_format(sender, proposal.proposal_type()),
             #[cfg(not(feature = "private_message"))]
             WireFormat::PublicMessage,
             authenticated_data,
@@ -665,6 +977,8 @@ where
             .insert_own(proposal_desc, &message, sender, &self.cipher_suite_provider)
             .await?;
 
+        self.state_changed().await?;
+
         Ok(message)
     }
 
@@ -673,6 +987,51 @@ where
         &self.context().group_id
     }
 
+    /// Create a compact, read-only [`ArchivedGroup`] from this group.
+    ///
+    /// The archive is able to decrypt application messages sent in the
+    /// current epoch as well as in any prior epoch still retained by the
+    /// [`GroupStateStorage`](crate::GroupStateStorage), but it holds no key
+    /// material that would allow creating new messages or processing
+    /// commits. Once archived, the group state may be deleted from storage
+    /// to free resources used by dormant groups.
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn archive(&self) -> Result<ArchivedGroup<C>, MlsError> {
+        let mut epochs = self.state_repo.prior_epochs().await?;
+        epochs.push(self.current_epoch_as_prior());
+
+        ArchivedGroup::new(&self.config, epochs)
+    }
+
+    #[cfg(feature = "prior_epoch")]
+    fn current_epoch_as_prior(&self) -> PriorEpoch {
+        let (signature_public_keys, credentials) = self
+            .state
+            .public_tree
+            .leaves()
+            .map(|l| {
+                let identity = l.map(|n| &n.signing_identity);
+
+                (
+                    identity.map(|i| i.signature_key.clone()),
+                    identity.map(|i| i.credential.clone()),
+                )
+            })
+            .unzip();
+
+        PriorEpoch {
+            context: self.context().clone(),
+            self_index: self.private_tree.self_index,
+            secrets: self.epoch_secrets.clone(),
+            signature_public_keys,
+            epoch_authenticator: self.key_schedule.authentication_secret.to_vec().into(),
+            retired_at: None,
+            credentials,
+        }
+    }
+
     fn provisional_private_tree(
         &self,
         provisional_state: &ProvisionalState,
@@ -816,7 +1175,7 @@ where
         &mut self,
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
-        let proposal = self.update_proposal(None, None, None).await?;
+        let proposal = self.update_proposal(None, None, None, None).await?;
         self.proposal_message(proposal, authenticated_data).await
     }
 
@@ -846,7 +1205,7 @@ where
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
         let proposal = self
-            .update_proposal(Some(signer), Some(signing_identity), None)
+            .update_proposal(Some(signer), Some(signing_identity), None, None)
             .await?;
 
         self.proposal_message(proposal, authenticated_data).await
@@ -859,18 +1218,26 @@ where
         signer: Option<SignatureSecretKey>,
         signing_identity: Option<SigningIdentity>,
         leaf_node_extensions: Option<ExtensionList>,
+        capabilities: Option<Capabilities>,
     ) -> Result<Proposal, MlsError> {
         // Grab a copy of the current node and update it to have new key material
         let mut new_leaf_node: LeafNode = self.current_user_leaf_node()?.clone();
 
         let new_leaf_node_extensions =
             leaf_node_extensions.unwrap_or(new_leaf_node.ungreased_extensions());
+
+        let mut leaf_properties = self.config.leaf_properties(new_leaf_node_extensions);
+
+        if let Some(capabilities) = capabilities {
+            leaf_properties.capabilities = capabilities;
+        }
+
         let secret_key = new_leaf_node
             .update(
                 &self.cipher_suite_provider,
                 self.group_id(),
                 self.current_member_index(),
-                Some(self.config.leaf_properties(new_leaf_node_extensions)),
+                Some(leaf_properties),
                 signing_identity,
                 signer.as_ref().unwrap_or(&self.signer),
             )
@@ -976,7 +1343,9 @@ where
     ///
     /// Once a [`ReInitProposal`](proposal::ReInitProposal)
     /// has been sent, another group member can complete reinitialization of
-    /// the group by calling [`Group::get_reinit_client`].
+    /// the group by calling [`Group::get_reinit_client`]. Members can send
+    /// their key packages for the new group in-band with
+    /// [`Group::announce_reinit_key_package`].
     ///
     /// `authenticated_data` will be sent unencrypted along with the contents
     /// of the proposal message.
@@ -1024,6 +1393,8 @@ where
     /// current set of extension that are in use. In order for an existing
     /// extension to not be overwritten by this proposal, it must be included
     /// in the new set of extensions being proposed.
+    /// [`Group::propose_group_context_extensions_merge`] builds the new set
+    /// from the current one.
     ///
     ///
     /// `authenticated_data` will be sent unencrypted along with the contents
@@ -1039,6 +1410,79 @@ where
         self.proposal_message(proposal, authenticated_data).await
     }
 
+    /// Create a proposal message that sets the extensions of the current
+    /// epoch with the extensions in `add` added or replaced and the
+    /// extensions of type in `remove` removed.
+    ///
+    /// The proposal still contains the complete list of extensions. Only one
+    /// group context extensions proposal can be committed per epoch, so
+    /// concurrent proposals should be merged by the committer with
+    /// [`CommitBuilder::add_group_context_extension`] and
+    /// [`CommitBuilder::remove_group_context_extension`].
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn propose_group_context_extensions_merge(
+        &mut self,
+        add: ExtensionList,
+        remove: Vec<ExtensionType>,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let mut extensions = self.context().extensions.clone();
+
+        remove
+            .into_iter()
+            .for_each(|extension_type| extensions.remove(extension_type));
+
+        extensions.extend(add.iter().cloned());
+
+        self.propose_group_context_extensions(extensions, authenticated_data)
+            .await
+    }
+
+    /// Create a proposal message that sets extensions stored in the group
+    /// state, only if the current extensions hash to `expected_hash`.
+    ///
+    /// `expected_hash` is obtained with
+    /// [`Group::group_context_extensions_hash`] when the new extensions are
+    /// computed. If the extensions were changed in the meantime,
+    /// [`MlsError::GroupContextExtensionsChanged`] is returned instead of
+    /// overwriting the change.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn propose_group_context_extensions_if_unchanged(
+        &mut self,
+        expected_hash: &[u8],
+        extensions: ExtensionList,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        if self.group_context_extensions_hash().await? != expected_hash {
+            return Err(MlsError::GroupContextExtensionsChanged);
+        }
+
+        self.propose_group_context_extensions(extensions, authenticated_data)
+            .await
+    }
+
+    /// Hash of the group context extensions of the current epoch, used as
+    /// the guard of [`Group::propose_group_context_extensions_if_unchanged`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn group_context_extensions_hash(&self) -> Result<Vec<u8>, MlsError> {
+        let extensions = self.context().extensions.mls_encode_to_vec()?;
+
+        self.cipher_suite_provider
+            .hash(&extensions)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
     fn group_context_extensions_proposal(&self, extensions: ExtensionList) -> Proposal {
         Proposal::GroupContextExtensions(extensions)
     }
@@ -1071,7 +1515,7 @@ where
     ) -> Result<MlsMessage, MlsError> {
         #[cfg(feature = "private_message")]
         let payload = if content.wire_format == WireFormat::PrivateMessage {
-            MlsMessagePayload::Cipher(self.create_ciphertext(content).await?)
+            MlsMessagePayload::Cipher(self.create_ciphertext(content, 0).await?)
         } else {
             MlsMessagePayload::Plain(self.create_plaintext(content).await?)
         };
@@ -1089,7 +1533,12 @@ where
         let membership_tag = if matches!(auth_content.content.sender, Sender::Member(_)) {
             let tag = self
                 .key_schedule
-                .get_membership_tag(&auth_content, self.context(), &self.cipher_suite_provider)
+                .get_membership_tag(
+                    &auth_content,
+                    self.context(),
+                    &self.cipher_suite_provider,
+                    &self.config.epoch_secrets_vault(),
+                )
                 .await?;
 
             Some(tag)
@@ -1109,24 +1558,61 @@ where
     async fn create_ciphertext(
         &mut self,
         auth_content: AuthenticatedContent,
+        hidden_len: usize,
     ) -> Result<PrivateMessage, MlsError> {
         let padding_mode = self.encryption_options()?.padding_mode;
 
         let mut encryptor = CiphertextProcessor::new(self, self.cipher_suite_provider.clone());
 
-        encryptor.seal(auth_content, padding_mode).await
+        encryptor.seal(auth_content, padding_mode, hidden_len).await
     }
 
     /// Encrypt an application message using the current group state.
     ///
     /// `authenticated_data` will be sent unencrypted along with the contents
     /// of the proposal message.
+    ///
+    /// In groups with the
+    /// [`ApplicationCompressionExt`](crate::compression::ApplicationCompressionExt)
+    /// extension, the message is compressed before it is encrypted.
     #[cfg(feature = "private_message")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn encrypt_application_message(
         &mut self,
         message: &[u8],
         authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        self.encrypt_application_message_inner(message, authenticated_data, true)
+            .await
+    }
+
+    /// Encrypt an application message without compressing it, even in groups
+    /// with the
+    /// [`ApplicationCompressionExt`](crate::compression::ApplicationCompressionExt)
+    /// extension.
+    ///
+    /// This should be used for messages mixing secrets with content that
+    /// may be chosen by an attacker, whose compressed size reveals
+    /// information about the secrets. See the
+    /// [`compression`](crate::compression) module.
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_application_message_uncompressed(
+        &mut self,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        self.encrypt_application_message_inner(message, authenticated_data, false)
+            .await
+    }
+
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn encrypt_application_message_inner(
+        &mut self,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+        compress: bool,
     ) -> Result<MlsMessage, MlsError> {
         // A group member that has observed one or more proposals within an epoch MUST send a Commit message
         // before sending application data
@@ -1135,18 +1621,42 @@ where
             return Err(MlsError::CommitRequired);
         }
 
+        self.check_not_receive_only()?;
+        self.check_unconfirmed_member_policy()?;
+
+        // Bytes saved by compression are covered by the padding, so that the
+        // ciphertext does not reveal how well the message compressed
+        let (data, hidden_len) = match self.application_compression()? {
+            Some(algorithm) => compress_application_data(
+                &self.config.message_compressor(),
+                algorithm,
+                message,
+                compress,
+            )?,
+            None => (message.to_vec().into(), 0),
+        };
+
         let auth_content = AuthenticatedContent::new_signed(
             &self.cipher_suite_provider,
             self.context(),
             Sender::Member(*self.private_tree.self_index),
-            Content::Application(message.to_vec().into()),
+            Content::Application(data),
             &self.signer,
             WireFormat::PrivateMessage,
             authenticated_data,
         )
         .await?;
 
-        self.format_for_wire(auth_content).await
+        let ciphertext = self.create_ciphertext(auth_content, hidden_len).await?;
+
+        let message = MlsMessage::new(
+            self.protocol_version(),
+            MlsMessagePayload::Cipher(ciphertext),
+        );
+
+        self.state_changed().await?;
+
+        Ok(message)
     }
 
     #[cfg(feature = "private_message")]
@@ -1176,12 +1686,33 @@ where
         } else {
             #[cfg(feature = "prior_epoch")]
             {
+                if self.config.key_retention() == KeyRetention::Strict
+                    || !self
+                        .config_overrides
+                        .epoch_retained(epoch_id, self.context().epoch)
+                {
+                    return Err(MlsError::EpochNotFound);
+                }
+
                 let epoch = self
                     .state_repo
                     .get_epoch_mut(epoch_id)
                     .await?
                     .ok_or(MlsError::EpochNotFound)?;
 
+                #[cfg(feature = "std")]
+                if let Some((max_age, retired_at)) =
+                    self.config.max_message_age().zip(epoch.retired_at)
+                {
+                    let age = MlsTime::now()
+                        .seconds_since_epoch()
+                        .saturating_sub(retired_at);
+
+                    if age > max_age {
+                        return Err(MlsError::MaxMessageAgeExceeded);
+                    }
+                }
+
                 let content = CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone())
                     .open(message)
                     .await?;
@@ -1217,7 +1748,12 @@ where
             .content
             .clone();
 
-        self.process_commit(content, None).await
+        let description = self.process_commit(content, None).await?;
+
+        self.replay_future_epoch_messages().await;
+        self.state_changed().await?;
+
+        Ok(description)
     }
 
     /// Apply a detached commit that was created by [`Group::commit_detached`] or
@@ -1246,6 +1782,44 @@ where
         self.pending_commit = None
     }
 
+    /// Clear the currently pending commit after it was rejected, for example
+    /// by the delivery service, and handle the proposals it committed by
+    /// reference according to `proposals`.
+    ///
+    /// Proposals cached after the commit was created are kept in all cases.
+    /// Returns the references of the removed proposals.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn reject_pending_commit(
+        &mut self,
+        proposals: RejectedCommitProposals,
+    ) -> Result<Vec<ProposalRef>, MlsError> {
+        let pending = self
+            .pending_commit
+            .take()
+            .ok_or(MlsError::PendingCommitNotFound)?;
+
+        if proposals == RejectedCommitProposals::Keep {
+            return Ok(Vec::new());
+        }
+
+        let Content::Commit(commit) = &pending.content.content.content else {
+            return Ok(Vec::new());
+        };
+
+        let mut removed = Vec::new();
+
+        for p in &commit.proposals {
+            if let proposal::ProposalOrRef::Reference(r) = p {
+                if self.state.proposals.remove(r) {
+                    removed.push(r.clone());
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
     /// Returns true if the client has received or issued a proposal
     /// that needs to be committed to with [`Group::commit`] before encrypting an
     /// application message.
@@ -1261,7 +1835,9 @@ where
     /// Changes to the group's state as a result of processing `message` will
     /// not be persisted by the
     /// [`GroupStateStorage`](crate::GroupStateStorage)
-    /// in use by this group until [`Group::write_to_storage`] is called.
+    /// in use by this group until [`Group::write_to_storage`] is called,
+    /// unless the [flush mode](Group::set_flush_mode) is
+    /// [`FlushMode::Immediate`].
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     #[inline(never)]
     pub async fn process_incoming_message(
@@ -1291,13 +1867,26 @@ where
             }
         }
 
-        MessageProcessor::process_incoming_message(
+        let message = match self.buffer_future_epoch_message(message)? {
+            BufferOutcome::Buffered(epoch) => return Ok(ReceivedMessage::Buffered(epoch)),
+            BufferOutcome::Process(message) => message,
+        };
+
+        let received = MessageProcessor::process_incoming_message(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
         )
-        .await
+        .await?;
+
+        if matches!(received, ReceivedMessage::Commit(_)) {
+            self.replay_future_epoch_messages().await;
+        }
+
+        self.state_changed().await?;
+
+        Ok(received)
     }
 
     /// Process an inbound message for this group, providing additional context
@@ -1315,21 +1904,63 @@ where
     /// Changes to the group's state as a result of processing `message` will
     /// not be persisted by the
     /// [`GroupStateStorage`](crate::GroupStateStorage)
-    /// in use by this group until [`Group::write_to_storage`] is called.
+    /// in use by this group until [`Group::write_to_storage`] is called,
+    /// unless the [flush mode](Group::set_flush_mode) is
+    /// [`FlushMode::Immediate`].
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn process_incoming_message_with_time(
         &mut self,
         message: MlsMessage,
         time: MlsTime,
     ) -> Result<ReceivedMessage, MlsError> {
-        MessageProcessor::process_incoming_message_with_time(
+        let message = match self.buffer_future_epoch_message(message)? {
+            BufferOutcome::Buffered(epoch) => return Ok(ReceivedMessage::Buffered(epoch)),
+            BufferOutcome::Process(message) => message,
+        };
+
+        let received = MessageProcessor::process_incoming_message_with_time(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
             Some(time),
         )
-        .await
+        .await?;
+
+        if matches!(received, ReceivedMessage::Commit(_)) {
+            self.replay_future_epoch_messages().await;
+        }
+
+        self.state_changed().await?;
+
+        Ok(received)
+    }
+
+    /// Validate an inbound message without processing it.
+    ///
+    /// All checks performed by [`Group::process_incoming_message`] are
+    /// applied, including decryption, signature and membership verification
+    /// and validation of proposals and commits, and the message that would
+    /// have been received is returned. The state of this group is left
+    /// unchanged: decryption keys are not consumed, ratchets do not advance
+    /// and commits are not applied, so `message` can be processed later.
+    ///
+    /// This is meant for members that relay messages and need to vet them
+    /// first. The group state is copied for each call.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn validate_incoming_message(
+        &self,
+        message: &MlsMessage,
+    ) -> Result<ReceivedMessage, MlsError> {
+        let mut group = self.clone();
+
+        // Validation neither buffers the message nor replays buffered ones,
+        // and never writes to storage
+        group.config_overrides.future_epoch_buffer = None;
+        group.future_epoch_buffer = Default::default();
+        group.flush_mode = FlushMode::Manual;
+
+        group.process_incoming_message(message.clone()).await
     }
 
     /// Find a group member by
@@ -1376,7 +2007,10 @@ where
 
         extensions.set_from({
             self.key_schedule
-                .get_external_key_pair_ext(&self.cipher_suite_provider)
+                .get_external_key_pair_ext(
+                    &self.cipher_suite_provider,
+                    &self.config.epoch_secrets_vault(),
+                )
                 .await?
         })?;
 
@@ -1414,7 +2048,21 @@ where
             signature: Vec::new(),
         };
 
-        info.grease(self.cipher_suite_provider())?;
+        if self.config.grease() {
+            info.grease(self.cipher_suite_provider())?;
+        }
+
+        self.config
+            .mls_rules()
+            .commit_options(
+                &self.roster(),
+                self.context(),
+                &proposal_filter::ProposalBundle::default(),
+            )
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?
+            .group_info_padding
+            .negotiated(self.context())?
+            .pad(&mut info);
 
         info.sign(&self.cipher_suite_provider, &self.signer, &())
             .await?;
@@ -1435,7 +2083,71 @@ where
     /// [epoch_authenticator](https://messaginglayersecurity.rocks/mls-protocol/draft-ietf-mls-protocol.html#name-key-schedule)
     /// of the current epoch.
     pub fn epoch_authenticator(&self) -> Result<Secret, MlsError> {
-        Ok(self.key_schedule.authentication_secret.clone().into())
+        Ok(self.key_schedule.authentication_secret.to_vec().into())
+    }
+
+    /// Transcript hashes and confirmation tag of the current epoch.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn epoch_transcript(&self) -> EpochTranscript {
+        EpochTranscript::new(&self.state)
+    }
+
+    /// Export a proof chaining the epoch authenticators of up to `max_epochs`
+    /// most recent epochs, ending with the current epoch.
+    ///
+    /// Only epochs still retained by the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) can be included, and
+    /// epochs stored by a version of the crate that did not record their epoch
+    /// authenticator end the chain.
+    #[cfg(feature = "prior_epoch")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn epoch_authenticator_proof(
+        &self,
+        max_epochs: usize,
+    ) -> Result<EpochAuthenticatorProof, MlsError> {
+        let mut epochs = self.state_repo.prior_epochs().await?;
+        epochs.push(self.current_epoch_as_prior());
+
+        let epochs = &epochs[epochs.len().saturating_sub(max_epochs.max(1))..];
+
+        // Epochs stored without their epoch authenticator end the chain
+        let start = epochs
+            .iter()
+            .rposition(|e| e.epoch_authenticator.is_empty())
+            .map_or(0, |i| i + 1);
+
+        let epochs = &epochs[start..];
+
+        Ok(EpochAuthenticatorProof::new(
+            self.group_id().to_vec(),
+            epochs[0].epoch_id(),
+            epochs
+                .iter()
+                .map(|e| e.epoch_authenticator.to_vec())
+                .collect(),
+        ))
+    }
+
+    /// Verify a proof created by another member with
+    /// [`Group::epoch_authenticator_proof`].
+    ///
+    /// The proof must end with the current epoch of this group and agree
+    /// with every epoch authenticator in `pins`, which should have been
+    /// obtained out-of-band. At least one pin is required.
+    #[cfg(feature = "prior_epoch")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn verify_epoch_authenticator_proof(
+        &self,
+        proof: &EpochAuthenticatorProof,
+        pins: &[EpochAuthenticatorPin],
+    ) -> Result<(), MlsError> {
+        proof.verify(
+            self.group_id(),
+            self.current_epoch(),
+            &self.key_schedule.authentication_secret,
+            pins,
+        )
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -1446,7 +2158,13 @@ where
         len: usize,
     ) -> Result<Secret, MlsError> {
         self.key_schedule
-            .export_secret(label, context, len, &self.cipher_suite_provider)
+            .export_secret(
+                label,
+                context,
+                len,
+                &self.cipher_suite_provider,
+                &self.config.epoch_secrets_vault(),
+            )
             .await
             .map(Into::into)
     }
@@ -1459,6 +2177,31 @@ where
         ExportedTree::new_borrowed(&self.current_epoch_tree().nodes)
     }
 
+    /// Configuration overrides of this group, set when the group was created
+    /// or joined.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn config_overrides(&self) -> &GroupConfigOverrides {
+        &self.config_overrides
+    }
+
+    /// Groups this group was created from by reinitialization or branching.
+    #[cfg(feature = "psk")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn lineage(&self) -> &GroupLineage {
+        &self.lineage
+    }
+
+    /// Render the current epoch's ratchet tree for debugging.
+    ///
+    /// The output shows node indices, blank nodes, unmerged leaves and the
+    /// first bytes of parent hashes. It is meant for comparing trees across
+    /// implementations and is not a stable format.
+    #[cfg(feature = "debug_utils")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn debug_tree(&self, format: DebugTreeFormat) -> Result<String, MlsError> {
+        crate::tree_kem::tree_utils::build_debug_tree(&self.current_epoch_tree().nodes, format)
+    }
+
     /// Current version of the MLS protocol in use by this group.
     pub fn protocol_version(&self) -> ProtocolVersion {
         self.context().protocol_version
@@ -1512,11 +2255,24 @@ where
         }
     }
 
+    /// Algorithm used to compress application messages, set by the
+    /// [`ApplicationCompressionExt`](crate::compression::ApplicationCompressionExt)
+    /// group context extension.
+    #[cfg(feature = "private_message")]
+    fn application_compression(&self) -> Result<Option<CompressionAlgorithm>, MlsError> {
+        Ok(self
+            .context()
+            .extensions
+            .get_as::<ApplicationCompressionExt>()?
+            .map(|ext| ext.algorithm))
+    }
+
     #[cfg(feature = "private_message")]
     pub(crate) fn encryption_options(&self) -> Result<EncryptionOptions, MlsError> {
         self.config
             .mls_rules()
             .encryption_options(&self.roster(), self.group_context())
+            .map(|options| self.config_overrides.apply_to(options))
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
     }
 
@@ -1535,6 +2291,7 @@ where
                 &self.cipher_suite_provider,
                 crate::tree_kem::node::NodeIndex::from(self.private_tree.self_index),
                 KeyType::Application,
+                RatchetDerivation::of(self.context()),
             )
             .await
     }
@@ -1553,6 +2310,9 @@ where
                 crate::tree_kem::node::NodeIndex::from(sender),
                 KeyType::Application,
                 generation,
+                self.config.key_retention(),
+                self.config.skipped_key_storage(),
+                RatchetDerivation::of(self.context()),
             )
             .await
     }
@@ -1585,6 +2345,7 @@ impl<C: ClientConfig> Group<C> {
                 <C as ClientConfig>::GroupStateStorage,
                 <C as ClientConfig>::KeyPackageRepository,
                 <C as ClientConfig>::PskStore,
+                _,
             > {
                 group_context: None,
                 current_epoch: None,
@@ -1717,6 +2478,19 @@ where
     fn epoch_secrets(&self) -> &EpochSecrets {
         &self.epoch_secrets
     }
+
+    fn key_retention(&self) -> KeyRetention {
+        self.config.key_retention()
+    }
+
+    fn skipped_key_storage(&self) -> SkippedKeyStorage {
+        self.config.skipped_key_storage()
+    }
+
+    #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+    fn key_deletion_audit_mut(&mut self) -> Option<&mut KeyDeletionAudit> {
+        self.key_deletion_audit.as_mut()
+    }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -1745,14 +2519,29 @@ where
             .map(EventOrContent::Content)
     }
 
+    #[cfg(feature = "private_message")]
+    async fn process_targeted_message(
+        &mut self,
+        message: TargetedMessage,
+    ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
+        self.decrypt_targeted_message(message)
+            .await
+            .map(|m| EventOrContent::Event(ReceivedMessage::TargetedMessage(m)))
+    }
+
     async fn verify_plaintext_authentication(
         &self,
         message: PublicMessage,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
+        let membership_key = self
+            .key_schedule
+            .membership_key(&self.config.epoch_secrets_vault())
+            .await?;
+
         let auth_content = verify_plaintext_authentication(
             &self.cipher_suite_provider,
             message,
-            Some(&self.key_schedule),
+            Some(&membership_key),
             &self.state,
         )
         .await?;
@@ -1827,11 +2616,11 @@ where
         confirmation_tag: &ConfirmationTag,
         provisional_state: ProvisionalState,
     ) -> Result<(), MlsError> {
-        let commit_secret = if let Some(secrets) = secrets {
-            self.private_tree = secrets.0;
-            secrets.1
-        } else {
-            PathSecret::empty(&self.cipher_suite_provider)
+        // The private tree is replaced along with the rest of the epoch state
+        // below, so that it is not updated alone if this future is dropped
+        let (private_tree, commit_secret) = match secrets {
+            Some((private_tree, commit_secret)) => (Some(private_tree), commit_secret),
+            None => (None, PathSecret::empty(&self.cipher_suite_provider)),
         };
 
         // Use the commit_secret, the psk_secret, the provisional GroupContext, and the init secret
@@ -1846,7 +2635,11 @@ where
         {
             Some(ext_init) if self.pending_commit.is_none() => {
                 self.key_schedule
-                    .derive_for_external(&ext_init.proposal.kem_output, &self.cipher_suite_provider)
+                    .derive_for_external(
+                        &ext_init.proposal.kem_output,
+                        &self.cipher_suite_provider,
+                        &self.config.epoch_secrets_vault(),
+                    )
                     .await?
             }
             _ => self.key_schedule.clone(),
@@ -1868,6 +2661,7 @@ where
             provisional_state.public_tree.total_leaf_count(),
             &psk,
             &self.cipher_suite_provider,
+            &self.config.epoch_secrets_vault(),
         )
         .await?;
 
@@ -1885,29 +2679,96 @@ where
             return Err(MlsError::InvalidConfirmationTag);
         }
 
-        #[cfg(feature = "prior_epoch")]
-        let signature_public_keys = self
-            .state
-            .public_tree
-            .leaves()
-            .map(|l| l.map(|n| n.signing_identity.signature_key.clone()))
-            .collect();
+        let removed = provisional_state
+            .applied_proposals
+            .remove_proposals()
+            .iter()
+            .map(|p| LeafIndex(p.proposal.to_remove()))
+            .collect::<Vec<_>>();
 
-        #[cfg(feature = "prior_epoch")]
+        let member_uids = self
+            .member_uids
+            .next_epoch(
+                &self.cipher_suite_provider,
+                &self.state.public_tree,
+                &provisional_state.public_tree,
+                &removed,
+                provisional_state.group_context.epoch,
+            )
+            .await?;
+
+        #[cfg(all(feature = "prior_epoch", feature = "std"))]
         let past_epoch = PriorEpoch {
-            context: self.context().clone(),
-            self_index: self.private_tree.self_index,
-            secrets: self.epoch_secrets.clone(),
-            signature_public_keys,
+            retired_at: Some(MlsTime::now().seconds_since_epoch()),
+            ..self.current_epoch_as_prior()
+        };
+
+        #[cfg(all(feature = "prior_epoch", not(feature = "std")))]
+        let past_epoch = self.current_epoch_as_prior();
+
+        #[cfg(all(
+            feature = "prior_epoch",
+            any(feature = "secret_tree_access", feature = "private_message")
+        ))]
+        let past_epoch = match self.config.key_retention() {
+            KeyRetention::Strict => PriorEpoch {
+                secrets: EpochSecrets {
+                    secret_tree: SecretTree::empty(),
+                    ..past_epoch.secrets
+                },
+                ..past_epoch
+            },
+            KeyRetention::Standard => past_epoch,
         };
 
+        #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+        let past_epoch_id = past_epoch.epoch_id();
+
         #[cfg(feature = "prior_epoch")]
         self.state_repo.insert(past_epoch).await?;
 
+        // Erase the secret tree of the epoch that is no longer retained
+        #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+        if let Some(expired_id) = self
+            .config_overrides
+            .max_epoch_retention
+            .and_then(|max| past_epoch_id.checked_sub(max))
+        {
+            if let Some(expired) = self.state_repo.get_epoch_mut(expired_id).await? {
+                expired.secrets.secret_tree = SecretTree::empty();
+            }
+        }
+
+        if let Some(private_tree) = private_tree {
+            self.private_tree = private_tree;
+        }
+
         self.epoch_secrets = key_schedule_result.epoch_secrets;
         self.state.context = provisional_state.group_context;
         self.state.interim_transcript_hash = interim_transcript_hash;
-        self.key_schedule = key_schedule_result.key_schedule;
+        let retired = core::mem::replace(&mut self.key_schedule, key_schedule_result.key_schedule);
+        self.retired_key_schedules.push(retired);
+
+        self.leaf_update_epochs.record(
+            &self.state.public_tree,
+            &provisional_state.public_tree,
+            self.state.context.epoch,
+        );
+
+        self.member_uids = member_uids;
+
+        self.liveness_log
+            .record_commit(&self.state.public_tree, &provisional_state.public_tree);
+
+        self.quarantine
+            .forget_removed(&provisional_state.applied_proposals);
+
+        self.unconfirmed_members.record_commit(
+            &provisional_state.applied_proposals,
+            &provisional_state.indexes_of_added_kpkgs,
+            self.state.context.epoch,
+        );
+
         self.state.public_tree = provisional_state.public_tree;
         self.state.confirmation_tag = new_confirmation_tag;
 
@@ -1923,6 +2784,9 @@ where
 
         self.pending_commit = None;
 
+        #[cfg(feature = "invariant_checks")]
+        self.debug_assert_invariants().await?;
+
         Ok(())
     }
 
@@ -1966,6 +2830,50 @@ where
         None
     }
 
+    #[cfg(feature = "private_message")]
+    fn sender_quarantined(&self, sender_index: u32) -> bool {
+        self.is_member_quarantined(sender_index)
+    }
+
+    #[cfg(feature = "private_message")]
+    fn received_control_wire_format(&self) -> mls_rules::ReceivedControlWireFormat {
+        self.config.received_control_wire_format()
+    }
+
+    #[cfg(feature = "private_message")]
+    fn decode_application_data(&self, data: ApplicationData) -> Result<ApplicationData, MlsError> {
+        match self.application_compression()? {
+            Some(algorithm) => {
+                decompress_application_data(&self.config.message_compressor(), algorithm, &data)
+            }
+            None => Ok(data),
+        }
+    }
+
+    fn confirm_sender(&mut self, sender: &Sender) {
+        self.unconfirmed_members.confirm_sender(sender);
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_received(&mut self, proposal: &ProposalMessageDescription) {
+        if let (Proposal::Update(_), ProposalSender::Member(index)) =
+            (&proposal.proposal, proposal.sender)
+        {
+            self.liveness_log.record(LeafIndex(index));
+        }
+    }
+
+    fn check_unknown_extensions(
+        &self,
+        provisional_state: &ProvisionalState,
+        path_leaf: Option<&LeafNode>,
+    ) -> Result<(), MlsError> {
+        reject_unknown_extensions(
+            &self.config,
+            introduced_extensions(provisional_state, path_leaf),
+        )
+    }
+
     fn cipher_suite_provider(&self) -> &Self::CipherSuiteProvider {
         &self.cipher_suite_provider
     }
@@ -2010,7 +2918,7 @@ mod tests {
     #[cfg(feature = "prior_epoch")]
     use crate::group::padding::PaddingMode;
 
-    use crate::{extension::RequiredCapabilitiesExt, key_package::test_utils::test_key_package};
+    use crate::key_package::test_utils::test_key_package;
 
     #[cfg(all(feature = "by_ref_proposal", feature = "custom_proposal"))]
     use super::test_utils::test_group_custom_config;
@@ -2403,7 +3311,51 @@ mod tests {
             .join_group(None, &commit_output.welcome_messages[0])
             .await?;
 
-        Ok(())
+        Ok(())
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_context_extensions_proposals_do_not_clobber_changes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let initial_hash = alice.group_context_extensions_hash().await.unwrap();
+
+        let external_senders = ExternalSendersExt::new(vec![]).into_extension().unwrap();
+
+        alice
+            .propose_group_context_extensions_merge(
+                vec![external_senders.clone()].into(),
+                vec![ExtensionType::REQUIRED_CAPABILITIES],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        assert_eq!(alice.context().extensions, vec![external_senders].into());
+
+        let res = alice
+            .propose_group_context_extensions_if_unchanged(
+                &initial_hash,
+                ExtensionList::new(),
+                vec![],
+            )
+            .await;
+
+        assert_matches!(res, Err(MlsError::GroupContextExtensionsChanged));
+
+        let current_hash = alice.group_context_extensions_hash().await.unwrap();
+
+        alice
+            .propose_group_context_extensions_if_unchanged(
+                &current_hash,
+                ExtensionList::new(),
+                vec![],
+            )
+            .await
+            .unwrap();
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -2462,10 +3414,27 @@ mod tests {
         let (mut test_group, _) =
             group_context_extension_proposal_test(extension_list.clone()).await;
 
+        let prior_extensions = test_group.state.context.extensions.clone();
         let update = test_group.apply_pending_commit().await.unwrap();
 
-        // FIXME: Test Reporting
-        assert_matches!(update.effect, CommitEffect::NewEpoch(_));
+        let CommitEffect::NewEpoch(new_epoch) = update.effect else {
+            panic!("unexpected commit effect")
+        };
+
+        assert_eq!(
+            new_epoch.extension_diff.updated,
+            vec![(
+                prior_extensions
+                    .get(ExtensionType::REQUIRED_CAPABILITIES)
+                    .unwrap(),
+                extension_list
+                    .get(ExtensionType::REQUIRED_CAPABILITIES)
+                    .unwrap()
+            )]
+        );
+
+        assert!(new_epoch.extension_diff.added.is_empty());
+        assert!(new_epoch.extension_diff.removed.is_empty());
 
         assert_eq!(test_group.state.context.extensions, extension_list)
     }
@@ -2611,6 +3580,186 @@ mod tests {
         assert!(with_padding.mls_encoded_len() > without_padding.mls_encoded_len());
     }
 
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn validating_messages_does_not_consume_them() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let validated = bob.validate_incoming_message(&message).await.unwrap();
+        assert_matches!(validated, ReceivedMessage::ApplicationMessage(m) if m.data() == b"hello");
+
+        let received = bob.process_incoming_message(message).await.unwrap();
+        assert_matches!(received, ReceivedMessage::ApplicationMessage(m) if m.data() == b"hello");
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        let epoch = bob.current_epoch();
+
+        let validated = bob.validate_incoming_message(&commit).await.unwrap();
+        assert_matches!(validated, ReceivedMessage::Commit(_));
+        assert_eq!(bob.current_epoch(), epoch);
+
+        bob.process_incoming_message(commit.clone()).await.unwrap();
+        assert_eq!(bob.current_epoch(), epoch + 1);
+
+        let res = bob.validate_incoming_message(&commit).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+
+    #[cfg(all(feature = "out_of_order", feature = "prior_epoch"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn strict_key_retention_rejects_skipped_and_prior_epoch_messages() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.key_retention = KeyRetention::Strict
+            })
+            .await
+            .unwrap();
+
+        let first = alice
+            .encrypt_application_message(b"first", vec![])
+            .await
+            .unwrap();
+
+        let second = alice
+            .encrypt_application_message(b"second", vec![])
+            .await
+            .unwrap();
+
+        let third = alice
+            .encrypt_application_message(b"third", vec![])
+            .await
+            .unwrap();
+
+        bob.process_incoming_message(second).await.unwrap();
+
+        let res = bob.process_incoming_message(first).await;
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let res = bob.process_incoming_message(third).await;
+        assert_matches!(res, Err(MlsError::EpochNotFound));
+    }
+
+    #[cfg(all(feature = "private_message", feature = "prior_epoch", feature = "std"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn max_message_age_rejects_messages_from_old_epochs() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| c.0.settings.max_message_age = Some(3600))
+            .await
+            .unwrap();
+
+        let first = alice
+            .encrypt_application_message(b"first", vec![])
+            .await
+            .unwrap();
+
+        let second = alice
+            .encrypt_application_message(b"second", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        bob.process_incoming_message(first).await.unwrap();
+
+        let prior_epoch_id = bob.current_epoch() - 1;
+
+        let prior_epoch = bob
+            .state_repo
+            .get_epoch_mut(prior_epoch_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let retired_at = prior_epoch.retired_at.unwrap();
+        prior_epoch.retired_at = Some(retired_at - 7200);
+
+        let res = bob.process_incoming_message(second).await;
+        assert_matches!(res, Err(MlsError::MaxMessageAgeExceeded));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn epoch_transcript_is_shared_by_members() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let transcript = alice.epoch_transcript();
+        assert_eq!(transcript, bob.epoch_transcript());
+        assert_eq!(transcript.epoch(), alice.current_epoch());
+
+        assert_eq!(
+            transcript.confirmed_transcript_hash(),
+            &alice.context().confirmed_transcript_hash[..]
+        );
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let next = bob.epoch_transcript();
+        assert_eq!(next, alice.epoch_transcript());
+        assert_ne!(next.confirmation_tag(), transcript.confirmation_tag());
+        assert_ne!(
+            next.interim_transcript_hash(),
+            transcript.interim_transcript_hash()
+        );
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn received_control_wire_format_is_enforced() {
+        use crate::group::mls_rules::ReceivedControlWireFormat;
+
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.received_control_wire_format = ReceivedControlWireFormat::Encrypted
+            })
+            .await
+            .unwrap();
+
+        let (mut carol, commit) = alice
+            .join_with_custom_config("carol", false, |c| {
+                c.0.settings.received_control_wire_format = ReceivedControlWireFormat::Plaintext
+            })
+            .await
+            .unwrap();
+
+        let res = bob.process_incoming_message(commit).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::UnexpectedWireFormat(WireFormat::PublicMessage))
+        );
+
+        alice.config_overrides.encrypt_control_messages = Some(true);
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+
+        let res = carol.process_incoming_message(commit).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::UnexpectedWireFormat(WireFormat::PrivateMessage))
+        );
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_commit_requires_external_pub_extension() {
         let protocol_version = TEST_PROTOCOL_VERSION;
@@ -2840,6 +3989,74 @@ mod tests {
         alice_group.process_message(commit).await.unwrap();
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_commit_verifies_signed_tree_head() {
+        use crate::client::test_utils::TestClientBuilder;
+        use external_commit::SignedTreeHead;
+
+        let alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let (ds_identity, ds_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"ds").await;
+        let (other_identity, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"other").await;
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let context = alice_group.context();
+
+        let tree_head = SignedTreeHead::new(
+            context.group_id.clone(),
+            context.epoch,
+            context.tree_hash.clone(),
+            &ds_key,
+            &cs,
+        )
+        .await
+        .unwrap();
+
+        let tree_head = SignedTreeHead::from_bytes(&tree_head.to_bytes().unwrap()).unwrap();
+
+        let group_info = alice_group
+            .group_info_message_allowing_ext_commit(false)
+            .await
+            .unwrap();
+
+        let join = |trust_anchors, tree_head: Option<SignedTreeHead>| {
+            let builder = bob
+                .external_commit_builder()
+                .unwrap()
+                .with_tree_data(alice_group.export_tree().into_owned())
+                .with_tree_head_trust_anchors(trust_anchors);
+
+            match tree_head {
+                Some(tree_head) => builder.with_signed_tree_head(tree_head),
+                None => builder,
+            }
+        };
+
+        let res = join(vec![ds_identity.clone()], None)
+            .build(group_info.clone())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MissingSignedTreeHead));
+
+        let res = join(vec![other_identity], Some(tree_head.clone()))
+            .build(group_info.clone())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::InvalidSignedTreeHead));
+
+        join(vec![ds_identity], Some(tree_head))
+            .build(group_info)
+            .await
+            .unwrap();
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_membership_tag_from_non_member() {
         let (mut alice_group, mut bob_group) =
@@ -2994,6 +4211,75 @@ mod tests {
             .unwrap();
     }
 
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn subgroup_can_use_resumption_psk_from_parent_group() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        alice.write_to_storage().await.unwrap();
+        bob.write_to_storage().await.unwrap();
+
+        let new_key_pkg = Client::new(
+            bob.config.clone(),
+            Some(bob.signer.clone()),
+            Some((
+                bob.current_member_signing_identity().unwrap().clone(),
+                TEST_CIPHER_SUITE,
+            )),
+            TEST_PROTOCOL_VERSION,
+        )
+        .generate_key_package_message(Default::default(), Default::default())
+        .await
+        .unwrap();
+
+        let (mut alice_sub_group, welcome) = alice
+            .branch(b"subgroup".to_vec(), vec![new_key_pkg])
+            .await
+            .unwrap();
+
+        let (mut bob_sub_group, _) = bob.join_subgroup(&welcome[0], None).await.unwrap();
+
+        let ancestors = bob_sub_group.lineage().ancestors();
+        assert_eq!(ancestors.len(), 1);
+        assert_eq!(ancestors[0].group_id(), alice.group_id());
+        assert_eq!(ancestors[0].epoch(), alice.current_epoch());
+        assert_eq!(alice_sub_group.lineage(), bob_sub_group.lineage());
+
+        let commit = alice_sub_group
+            .commit_builder()
+            .add_resumption_psk_from_lineage(alice.group_id().to_vec(), 1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice_sub_group.apply_pending_commit().await.unwrap();
+        bob_sub_group
+            .process_incoming_message(commit)
+            .await
+            .unwrap();
+
+        let res = alice_sub_group
+            .commit_builder()
+            .add_resumption_psk_from_lineage(alice.group_id().to_vec(), alice.current_epoch() + 1)
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::ResumptionPskOutsideLineage));
+
+        let res = alice_sub_group
+            .commit_builder()
+            .add_resumption_psk_from_lineage(b"unrelated".to_vec(), 1)
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::ResumptionPskOutsideLineage));
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn joining_group_fails_if_unsupported<F>(
         f: F,
@@ -4341,6 +5627,55 @@ mod tests {
         assert_eq!(new_epoch.applied_proposals[0].sender, Sender::Member(0));
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn control_wire_format_policy_is_applied_per_content_type() {
+        use crate::group::mls_rules::{ControlWireFormatPolicy, EncryptionOptions};
+
+        let policy = ControlWireFormatPolicy::uniform(false)
+            .with_commit_encryption(true)
+            .with_proposal_type_encryption(ProposalType::REMOVE, true)
+            .unwrap();
+
+        let (alice, _) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "alice",
+            Default::default(),
+            Default::default(),
+            |c| {
+                c.0.mls_rules.encryption_options = EncryptionOptions::new(false, PaddingMode::None)
+                    .with_control_wire_format_policy(policy)
+            },
+        )
+        .await;
+
+        let mut alice = TestGroup {
+            group: alice
+                .create_group(Default::default(), Default::default())
+                .await
+                .unwrap(),
+        };
+
+        let (mut bob, _) = alice.join("bob").await;
+        let (_, commit) = alice.join("carol").await;
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let update = alice.propose_update(vec![]).await.unwrap();
+        assert_eq!(update.wire_format(), WireFormat::PublicMessage);
+
+        let remove = alice.propose_remove(2, vec![]).await.unwrap();
+        assert_eq!(remove.wire_format(), WireFormat::PrivateMessage);
+
+        bob.process_incoming_message(update).await.unwrap();
+        bob.process_incoming_message(remove).await.unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        assert_eq!(commit.wire_format(), WireFormat::PrivateMessage);
+
+        bob.process_incoming_message(commit).await.unwrap();
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn commit_clears_proposals() {
@@ -4382,6 +5717,40 @@ mod tests {
         assert!(!group.commit_required());
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rejected_commit_keeps_or_discards_proposals() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        group
+            .propose_group_context_extensions(ExtensionList::new(), vec![])
+            .await
+            .unwrap();
+
+        group.commit(vec![]).await.unwrap();
+
+        let removed = group
+            .reject_pending_commit(RejectedCommitProposals::Keep)
+            .unwrap();
+
+        assert!(removed.is_empty());
+        assert!(!group.has_pending_commit());
+        assert!(group.commit_required());
+
+        group.commit(vec![]).await.unwrap();
+
+        let removed = group
+            .reject_pending_commit(RejectedCommitProposals::Discard)
+            .unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert!(!group.commit_required());
+        assert!(group.state.proposals.own_proposals.is_empty());
+
+        let res = group.reject_pending_commit(RejectedCommitProposals::Discard);
+        assert_matches!(res, Err(MlsError::PendingCommitNotFound));
+    }
+
     // Testing with std is sufficient. Non-std creates incompatible storage and a lot of special cases.
     #[cfg(feature = "std")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
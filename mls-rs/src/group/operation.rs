@@ -0,0 +1,477 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{boxed::Box, vec::Vec};
+use core::mem;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{CommitOutput, FlushMode, Group, ReceivedMessage},
+    MlsMessage,
+};
+
+/// Token identifying an operation started with [`Group::begin_operation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperationToken {
+    id: u64,
+}
+
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
+enum OperationOutcome {
+    Commit(CommitOutput),
+    Message(ReceivedMessage),
+}
+
+#[derive(Clone)]
+pub(crate) struct PendingOperation<C>
+where
+    C: ClientConfig,
+{
+    token: OperationToken,
+    rollback: Group<C>,
+    attempted: bool,
+    outcome: Option<OperationOutcome>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Start an operation that can be retried or rolled back if its future
+    /// is dropped before it completes.
+    ///
+    /// The state of the group is copied when the operation starts. The
+    /// returned token is then passed to [`Group::commit_with_token`] or
+    /// [`Group::process_incoming_message_with_token`]:
+    ///
+    /// * if the future of the call is dropped, calling it again with the same
+    ///   token first restores the copied state, so the operation is never
+    ///   applied on top of a partially updated group;
+    /// * once the call completes, calling it again with the same token returns
+    ///   the same result without performing the operation twice. If the write
+    ///   to storage in [`FlushMode::Immediate`] did not complete, it is
+    ///   retried.
+    ///
+    /// [`Group::rollback_operation`] restores the copied state as long as the
+    /// operation did not complete, and [`Group::finish_operation`] keeps the
+    /// result. An operation that is still pending when another one starts is
+    /// finished first.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn begin_operation(&mut self) -> OperationToken {
+        if let Some(mut operation) = self.operation.take() {
+            self.finish(&mut operation);
+        }
+
+        let token = OperationToken {
+            id: self.next_operation_id,
+        };
+
+        self.next_operation_id += 1;
+
+        self.operation = Some(Box::new(PendingOperation {
+            token: token.clone(),
+            rollback: self.clone(),
+            attempted: false,
+            outcome: None,
+        }));
+
+        token
+    }
+
+    /// Token of the operation started with [`Group::begin_operation`] that
+    /// is neither finished nor rolled back.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn pending_operation(&self) -> Option<&OperationToken> {
+        self.operation.as_ref().map(|operation| &operation.token)
+    }
+
+    /// Same as [`Group::commit`] as part of the operation identified by
+    /// `token`. See [`Group::begin_operation`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn commit_with_token(
+        &mut self,
+        token: &OperationToken,
+        authenticated_data: Vec<u8>,
+    ) -> Result<CommitOutput, MlsError> {
+        let outcome = match self.resume_operation(token)? {
+            Some(outcome) => outcome,
+            None => {
+                let flush_mode = mem::replace(&mut self.flush_mode, FlushMode::Manual);
+                let res = self.commit(authenticated_data).await;
+                self.flush_mode = flush_mode;
+
+                self.record_outcome(OperationOutcome::Commit(res?))
+            }
+        };
+
+        self.flush_operation().await?;
+
+        match outcome {
+            OperationOutcome::Commit(output) => Ok(output),
+            _ => Err(MlsError::InvalidOperationToken),
+        }
+    }
+
+    /// Same as [`Group::process_incoming_message`] as part of the operation
+    /// identified by `token`. See [`Group::begin_operation`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn process_incoming_message_with_token(
+        &mut self,
+        token: &OperationToken,
+        message: MlsMessage,
+    ) -> Result<ReceivedMessage, MlsError> {
+        let outcome = match self.resume_operation(token)? {
+            Some(outcome) => outcome,
+            None => {
+                let flush_mode = mem::replace(&mut self.flush_mode, FlushMode::Manual);
+                let res = self.process_incoming_message(message).await;
+                self.flush_mode = flush_mode;
+
+                self.record_outcome(OperationOutcome::Message(res?))
+            }
+        };
+
+        self.flush_operation().await?;
+
+        match outcome {
+            OperationOutcome::Message(received) => Ok(received),
+            _ => Err(MlsError::InvalidOperationToken),
+        }
+    }
+
+    /// Restore the state of the group from before the operation identified by
+    /// `token` started.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::OperationCompleted`] if a call that is part of the
+    /// operation completed, in which case the operation stays pending.
+    /// Restoring the state would restore the secrets consumed by the call,
+    /// such as the keys used to decrypt a message, breaking forward secrecy.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn rollback_operation(&mut self, token: &OperationToken) -> Result<(), MlsError> {
+        let operation = self.take_operation(token)?;
+
+        if operation.outcome.is_some() {
+            self.operation = Some(operation);
+            return Err(MlsError::OperationCompleted);
+        }
+
+        *self = operation.rollback;
+
+        Ok(())
+    }
+
+    /// Keep the result of the operation identified by `token` and release the
+    /// copy of the state taken when it started. An operation whose last call
+    /// did not complete is rolled back instead.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn finish_operation(&mut self, token: &OperationToken) -> Result<(), MlsError> {
+        let mut operation = self.take_operation(token)?;
+        self.finish(&mut operation);
+
+        Ok(())
+    }
+
+    fn finish(&mut self, operation: &mut PendingOperation<C>) {
+        if operation.attempted && operation.outcome.is_none() {
+            mem::swap(self, &mut operation.rollback);
+        }
+    }
+
+    fn take_operation(
+        &mut self,
+        token: &OperationToken,
+    ) -> Result<Box<PendingOperation<C>>, MlsError> {
+        match self.operation.take() {
+            Some(operation) if operation.token == *token => Ok(operation),
+            operation => {
+                self.operation = operation;
+                Err(MlsError::InvalidOperationToken)
+            }
+        }
+    }
+
+    /// Prepare a call that is part of the operation identified by `token`,
+    /// returning the outcome of a previous call that completed or restoring
+    /// the state changed by a previous call that did not.
+    fn resume_operation(
+        &mut self,
+        token: &OperationToken,
+    ) -> Result<Option<OperationOutcome>, MlsError> {
+        let mut operation = self.take_operation(token)?;
+
+        if operation.outcome.is_none() && operation.attempted {
+            *self = operation.rollback.clone();
+        }
+
+        operation.attempted = true;
+        let outcome = operation.outcome.clone();
+        self.operation = Some(operation);
+
+        Ok(outcome)
+    }
+
+    fn record_outcome(&mut self, outcome: OperationOutcome) -> OperationOutcome {
+        if let Some(operation) = self.operation.as_mut() {
+            operation.outcome = Some(outcome.clone());
+        }
+
+        outcome
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn flush_operation(&mut self) -> Result<(), MlsError> {
+        match self.flush_mode {
+            FlushMode::Immediate => self.flush().await,
+            FlushMode::Manual => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn completed_operation_is_idempotent() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let token = alice.group.begin_operation();
+        let first = alice.group.commit_with_token(&token, vec![]).await.unwrap();
+        let second = alice.group.commit_with_token(&token, vec![]).await.unwrap();
+        assert_eq!(first.commit_message, second.commit_message);
+
+        let res = alice
+            .group
+            .process_incoming_message_with_token(&token, first.commit_message.clone())
+            .await;
+        assert_matches!(res, Err(MlsError::InvalidOperationToken));
+
+        let res = alice.group.rollback_operation(&token);
+        assert_matches!(res, Err(MlsError::OperationCompleted));
+        assert_eq!(alice.group.pending_operation(), Some(&token));
+
+        alice.group.finish_operation(&token).unwrap();
+        assert!(alice.group.has_pending_commit());
+        assert_eq!(alice.group.pending_operation(), None);
+
+        let res = alice.group.commit_with_token(&token, vec![]).await;
+        assert_matches!(res, Err(MlsError::InvalidOperationToken));
+
+        let commit = first.commit_message;
+        alice.group.apply_pending_commit().await.unwrap();
+
+        let token = bob.group.begin_operation();
+        let epoch = bob.group.current_epoch();
+
+        for _ in 0..2 {
+            let received = bob
+                .group
+                .process_incoming_message_with_token(&token, commit.clone())
+                .await
+                .unwrap();
+
+            assert_matches!(received, ReceivedMessage::Commit(_));
+            assert_eq!(bob.group.current_epoch(), epoch + 1);
+        }
+
+        bob.group.finish_operation(&token).unwrap();
+        assert_eq!(bob.group.pending_operation(), None);
+        assert_eq!(
+            bob.group.epoch_authenticator().unwrap(),
+            alice.group.epoch_authenticator().unwrap()
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn operation_is_rolled_back_before_completion() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let epoch = alice.group.current_epoch();
+
+        let token = alice.group.begin_operation();
+        alice.group.rollback_operation(&token).unwrap();
+
+        assert_eq!(alice.group.pending_operation(), None);
+        assert_eq!(alice.group.current_epoch(), epoch);
+    }
+
+    #[cfg(all(mls_build_async, feature = "prior_epoch"))]
+    mod cancellation {
+        use alloc::{boxed::Box, vec, vec::Vec};
+        use core::{
+            convert::Infallible,
+            future::Future,
+            pin::Pin,
+            task::{Context, Poll},
+        };
+        use futures::FutureExt;
+        use mls_rs_core::group::{EpochRecord, GroupState, GroupStateStorage};
+
+        use crate::{
+            client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+            group::{FlushMode, ReceivedMessage},
+            storage_provider::in_memory::InMemoryGroupStateStorage,
+        };
+
+        // Future that is pending the first time it is polled, like a storage
+        // waiting for I/O.
+        #[derive(Default)]
+        struct YieldOnce(bool);
+
+        impl Future for YieldOnce {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    return Poll::Ready(());
+                }
+
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        #[derive(Clone, Default)]
+        struct YieldingStorage(InMemoryGroupStateStorage);
+
+        #[maybe_async::must_be_async]
+        impl GroupStateStorage for YieldingStorage {
+            type Error = Infallible;
+
+            async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+                YieldOnce::default().await;
+                self.0.state(group_id).await
+            }
+
+            async fn epoch(
+                &self,
+                group_id: &[u8],
+                epoch_id: u64,
+            ) -> Result<Option<Vec<u8>>, Self::Error> {
+                YieldOnce::default().await;
+                self.0.epoch(group_id, epoch_id).await
+            }
+
+            async fn write(
+                &mut self,
+                state: GroupState,
+                epoch_inserts: Vec<EpochRecord>,
+                epoch_updates: Vec<EpochRecord>,
+            ) -> Result<(), Self::Error> {
+                YieldOnce::default().await;
+                self.0.write(state, epoch_inserts, epoch_updates).await
+            }
+
+            async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+                YieldOnce::default().await;
+                self.0.max_epoch_id(group_id).await
+            }
+
+            async fn delete_epochs_under(
+                &mut self,
+                group_id: &[u8],
+                epoch_id: u64,
+            ) -> Result<bool, Self::Error> {
+                YieldOnce::default().await;
+                self.0.delete_epochs_under(group_id, epoch_id).await
+            }
+        }
+
+        #[crate::futures_test]
+        async fn dropped_operation_is_retried() {
+            let alice = TestClientBuilder::new_for_test()
+                .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+                .await
+                .group_state_storage(YieldingStorage::default())
+                .build();
+
+            let bob = TestClientBuilder::new_for_test()
+                .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+                .await
+                .group_state_storage(YieldingStorage::default())
+                .build();
+
+            let mut alice_group = alice
+                .create_group(Default::default(), Default::default())
+                .await
+                .unwrap();
+
+            let key_package = bob
+                .generate_key_package_message(Default::default(), Default::default())
+                .await
+                .unwrap();
+
+            let welcome = alice_group
+                .commit_builder()
+                .add_member(key_package)
+                .unwrap()
+                .build()
+                .await
+                .unwrap()
+                .welcome_messages
+                .remove(0);
+
+            alice_group.apply_pending_commit().await.unwrap();
+
+            let (mut bob_group, _) = bob.join_group(None, &welcome).await.unwrap();
+
+            // Alice's commit is created but the write to storage is dropped.
+            alice_group.set_flush_mode(FlushMode::Immediate);
+            let token = alice_group.begin_operation();
+
+            let res = alice_group.commit_with_token(&token, vec![]).now_or_never();
+
+            assert!(res.is_none());
+            assert!(alice_group.has_unflushed_changes());
+
+            let commit = alice_group
+                .commit_with_token(&token, vec![])
+                .await
+                .unwrap()
+                .commit_message;
+
+            assert!(!alice_group.has_unflushed_changes());
+            alice_group.apply_pending_commit().await.unwrap();
+
+            // Bob's processing of the commit is dropped while the epoch is
+            // being updated.
+            let token = bob_group.begin_operation();
+
+            let res = bob_group
+                .process_incoming_message_with_token(&token, commit.clone())
+                .now_or_never();
+
+            assert!(res.is_none());
+
+            let received = bob_group
+                .process_incoming_message_with_token(&token, commit)
+                .await
+                .unwrap();
+
+            assert!(matches!(received, ReceivedMessage::Commit(_)));
+            bob_group.finish_operation(&token).unwrap();
+
+            assert_eq!(
+                bob_group.epoch_authenticator().unwrap(),
+                alice_group.epoch_authenticator().unwrap()
+            );
+        }
+    }
+}
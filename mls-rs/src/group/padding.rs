@@ -2,18 +2,32 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::vec;
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, HpkeCiphertext},
+    error::IntoAnyError,
+    extension::{Extension, ExtensionType, MlsCodecExtension},
+};
+
+use crate::{client::MlsError, KeyPackageRef};
+
+use super::{EncryptedGroupSecrets, GroupContext, GroupInfo};
+
 /// Padding used when sending an encrypted group message.
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum PaddingMode {
     /// Step function based on the size of the message being sent.
     /// The amount of padding used will increase with the size of the original
     /// message.
     #[default]
-    StepFunction,
+    StepFunction = 0u8,
     /// No padding.
-    None,
+    None = 1u8,
 }
 
 impl PaddingMode {
@@ -36,9 +50,186 @@ impl PaddingMode {
     }
 }
 
+/// Padding of [`GroupInfo`](crate::group::GroupInfo) and welcome messages.
+///
+/// The size of a group info, and therefore of a welcome message, reveals the
+/// size of the ratchet tree and of the group context extensions to the
+/// delivery service. Padding rounds the size of the group info up to a
+/// bucket. Padding is carried in a group info extension of type
+/// [`GroupInfoPadding::EXTENSION_TYPE`], which receivers ignore. The size of
+/// the signature is not covered, as it may vary for some signature schemes.
+///
+/// The number of encrypted group secrets in a welcome message reveals how
+/// many members are added by a commit. With padding, this number is rounded
+/// up to a power of two by adding entries of the same size that are addressed
+/// to no key package. This only hides the number of new members with
+/// [`single_welcome_message`](crate::mls_rules::CommitOptions::single_welcome_message)
+/// set to true, as otherwise one welcome message is sent to each new member.
+///
+/// The padding used by all members of a group can be agreed on with the
+/// [`GroupInfoPaddingExt`] group context extension, which takes precedence
+/// over
+/// [`CommitOptions::group_info_padding`](crate::mls_rules::CommitOptions::group_info_padding).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum GroupInfoPadding {
+    /// No padding.
+    #[default]
+    None = 0u8,
+    /// Same step function as [`PaddingMode::StepFunction`].
+    StepFunction = 1u8,
+    /// Round the size up to a multiple of the given number of bytes.
+    BlockSize(u32) = 2u8,
+}
+
+impl GroupInfoPadding {
+    /// Extension type of the padding, from the private use range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A0);
+
+    /// Padding set by the [`GroupInfoPaddingExt`] in `group_context`, or
+    /// `self` if the group doesn't use the extension.
+    pub(crate) fn negotiated(self, group_context: &GroupContext) -> Result<Self, MlsError> {
+        Ok(group_context
+            .extensions
+            .get_as::<GroupInfoPaddingExt>()?
+            .map_or(self, |ext| ext.padding))
+    }
+
+    fn is_none(&self) -> bool {
+        matches!(
+            self,
+            GroupInfoPadding::None | GroupInfoPadding::BlockSize(0)
+        )
+    }
+
+    fn padded_size(&self, size: usize) -> usize {
+        match *self {
+            GroupInfoPadding::None | GroupInfoPadding::BlockSize(0) => size,
+            GroupInfoPadding::StepFunction => PaddingMode::StepFunction.padded_size(size),
+            GroupInfoPadding::BlockSize(block_size) => {
+                let block_size = block_size as usize;
+
+                match size % block_size {
+                    0 => size,
+                    remainder => size + block_size - remainder,
+                }
+            }
+        }
+    }
+
+    /// Add padding to an unsigned `group_info`.
+    pub(crate) fn pad(&self, group_info: &mut GroupInfo) {
+        if self.is_none() {
+            return;
+        }
+
+        let set_padding = |group_info: &mut GroupInfo, len: usize| {
+            group_info
+                .extensions
+                .set(Extension::new(Self::EXTENSION_TYPE, vec![0; len]));
+
+            group_info.mls_encoded_len()
+        };
+
+        let unpadded = set_padding(group_info, 0);
+        let target = self.padded_size(unpadded);
+        let len = target - unpadded;
+
+        // Longer padding may need longer length prefixes, in which case the
+        // padding is shortened unless that makes the prefixes short again.
+        let size = set_padding(group_info, len);
+
+        if size > target && set_padding(group_info, len - (size - target)) != target {
+            set_padding(group_info, len);
+        }
+    }
+
+    /// Add entries to `secrets` until their number is a power of two. The
+    /// added entries have the size of the first entry and random content, so
+    /// that no new member decrypts them.
+    pub(crate) fn pad_group_secrets<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        secrets: &mut Vec<EncryptedGroupSecrets>,
+    ) -> Result<(), MlsError> {
+        let Some(first) = secrets.first() else {
+            return Ok(());
+        };
+
+        if self.is_none() {
+            return Ok(());
+        }
+
+        let ref_len = first.new_member.len();
+        let kem_output_len = first.encrypted_group_secrets.kem_output.len();
+        let ciphertext_len = first.encrypted_group_secrets.ciphertext.len();
+
+        let random = |len| {
+            cipher_suite_provider
+                .random_bytes_vec(len)
+                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+        };
+
+        for _ in secrets.len()..secrets.len().next_power_of_two() {
+            secrets.push(EncryptedGroupSecrets {
+                new_member: KeyPackageRef::from(random(ref_len)?),
+                encrypted_group_secrets: HpkeCiphertext {
+                    kem_output: random(kem_output_len)?,
+                    ciphertext: random(ciphertext_len)?,
+                },
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Group context extension setting the [`GroupInfoPadding`] used by all
+/// members when creating group info and welcome messages.
+///
+/// Receivers are not affected by padding. The extension type must still be
+/// listed in the capabilities of every member, for example with
+/// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type),
+/// which lets members signal that they agree to pad.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct GroupInfoPaddingExt {
+    pub padding: GroupInfoPadding,
+}
+
+impl GroupInfoPaddingExt {
+    /// Extension type of the padding extension, from the private use range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A2);
+
+    pub fn new(padding: GroupInfoPadding) -> Self {
+        Self { padding }
+    }
+}
+
+impl MlsCodecExtension for GroupInfoPaddingExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PaddingMode;
+    use super::{GroupInfoPadding, GroupInfoPaddingExt, PaddingMode};
+    use mls_rs_codec::MlsSize;
+
+    use crate::{
+        client::test_utils::{
+            test_client_with_key_pkg, test_client_with_key_pkg_custom, TEST_CIPHER_SUITE,
+            TEST_PROTOCOL_VERSION,
+        },
+        group::{
+            mls_rules::{CommitOptions, DefaultMlsRules},
+            test_utils::test_group_custom_config,
+            GroupInfo,
+        },
+        ExtensionList, MlsMessage,
+    };
 
     use alloc::vec;
     use alloc::vec::Vec;
@@ -106,4 +297,170 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_group_info_padding_block_size() {
+        assert_eq!(GroupInfoPadding::BlockSize(256).padded_size(1), 256);
+        assert_eq!(GroupInfoPadding::BlockSize(256).padded_size(256), 256);
+        assert_eq!(GroupInfoPadding::BlockSize(256).padded_size(257), 512);
+        assert_eq!(GroupInfoPadding::BlockSize(0).padded_size(257), 257);
+    }
+
+    fn unsigned_size(message: MlsMessage) -> usize {
+        let mut group_info: GroupInfo = message.into_group_info().unwrap();
+        group_info.signature.clear();
+        group_info.mls_encoded_len()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_info_is_padded() {
+        let options = CommitOptions::new()
+            .with_allow_external_commit(true)
+            .with_group_info_padding(GroupInfoPadding::BlockSize(1024));
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.mls_rules(DefaultMlsRules::new().with_commit_options(options))
+        })
+        .await;
+
+        let (bob, bob_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let output = alice
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let group_info = output.external_commit_group_info.unwrap();
+        assert_eq!(unsigned_size(group_info) % 1024, 0);
+
+        let group_info = alice.group_info_message(true).await.unwrap();
+        assert_eq!(unsigned_size(group_info) % 1024, 0);
+
+        bob.join_group(None, &output.welcome_messages[0])
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_group_secrets_are_padded() {
+        let options = CommitOptions::new().with_group_info_padding(GroupInfoPadding::StepFunction);
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.mls_rules(DefaultMlsRules::new().with_commit_options(options))
+        })
+        .await;
+
+        let mut commit_builder = alice.commit_builder();
+        let mut clients = Vec::new();
+
+        for name in ["bob", "carol", "dave"] {
+            let (client, key_package) =
+                test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+            commit_builder = commit_builder.add_member(key_package).unwrap();
+            clients.push(client);
+        }
+
+        let output = commit_builder.build().await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        let welcome = output.welcome_messages[0].clone().into_welcome().unwrap();
+        assert_eq!(welcome.secrets.len(), 4);
+
+        for client in clients {
+            client
+                .join_group(None, &output.welcome_messages[0])
+                .await
+                .unwrap();
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn padding_is_negotiated_with_group_context_extension() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.extension_type(GroupInfoPaddingExt::EXTENSION_TYPE)
+        })
+        .await;
+
+        let mut extensions = alice.context().extensions.clone();
+
+        extensions
+            .set_from(GroupInfoPaddingExt::new(GroupInfoPadding::BlockSize(1024)))
+            .unwrap();
+
+        alice
+            .commit_builder()
+            .set_group_context_ext(extensions)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let group_info = alice.group_info_message(true).await.unwrap();
+        assert_eq!(unsigned_size(group_info) % 1024, 0);
+
+        let (bob, bob_kp) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "bob",
+            ExtensionList::new(),
+            ExtensionList::new(),
+            |c| {
+                c.0.settings
+                    .extension_types
+                    .push(GroupInfoPaddingExt::EXTENSION_TYPE)
+            },
+        )
+        .await;
+
+        let (carol, carol_kp) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "carol",
+            ExtensionList::new(),
+            ExtensionList::new(),
+            |c| {
+                c.0.settings
+                    .extension_types
+                    .push(GroupInfoPaddingExt::EXTENSION_TYPE)
+            },
+        )
+        .await;
+
+        let output = alice
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .add_member(carol_kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let welcome = output.welcome_messages[0].clone().into_welcome().unwrap();
+        assert_eq!(welcome.secrets.len(), 2);
+
+        let (bob, _) = bob
+            .join_group(None, &output.welcome_messages[0])
+            .await
+            .unwrap();
+
+        let group_info = bob.group_info_message(true).await.unwrap();
+        assert_eq!(unsigned_size(group_info) % 1024, 0);
+
+        carol
+            .join_group(None, &output.welcome_messages[0])
+            .await
+            .unwrap();
+    }
 }
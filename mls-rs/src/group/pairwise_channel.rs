@@ -0,0 +1,443 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Lightweight 1:1 channels between two members of a group.
+//!
+//! A pairwise channel is bootstrapped from the current epoch without creating
+//! an MLS group for the pair. The initiator encrypts a fresh seed with HPKE to
+//! the leaf key of the peer and signs the result. Both members combine the
+//! seed with a secret exported from the epoch, so that the channel is only
+//! readable by the two members and is bound to the group and epoch it was
+//! created in. Each direction then uses a symmetric hash ratchet, providing
+//! forward secrecy for individual messages. The channel does not heal from
+//! a compromise by itself; a new channel should be created after the group
+//! advances to a new epoch.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider, HpkeCiphertext},
+    error::IntoAnyError,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        key_schedule::kdf_expand_with_label,
+        signed_seed::{Seed, SeedContext, SeedTBS, SignedSeed},
+        Group,
+    },
+    signer::Signable,
+    tree_kem::node::LeafIndex,
+};
+
+const PAIRWISE_CHANNEL_EXPORTER_LABEL: &[u8] = b"pairwise channel";
+
+/// Maximum number of messages that can be skipped by the receiving side of a
+/// [`PairwiseChannel`].
+const MAX_GENERATION_GAP: u32 = 1024;
+
+type GroupPairwiseChannel<C> =
+    PairwiseChannel<<<C as ClientConfig>::CryptoProvider as CryptoProvider>::CipherSuiteProvider>;
+
+/// Message sent by the initiator of a [`PairwiseChannel`] to the peer, which
+/// accepts it with [`Group::accept_pairwise_channel`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct PairwiseChannelInit {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    initiator_index: LeafIndex,
+    responder_index: LeafIndex,
+    hpke_ciphertext: HpkeCiphertext,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for PairwiseChannelInit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PairwiseChannelInit")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("initiator_index", &self.initiator_index)
+            .field("responder_index", &self.responder_index)
+            .finish()
+    }
+}
+
+impl PairwiseChannelInit {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf index of the member that created the channel.
+    pub fn initiator_index(&self) -> u32 {
+        *self.initiator_index
+    }
+
+    /// Leaf index of the member the channel was created with.
+    pub fn responder_index(&self) -> u32 {
+        *self.responder_index
+    }
+
+    fn context(&self) -> SeedContext<'_, LeafIndex> {
+        SeedContext {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            sender_index: self.initiator_index,
+            recipients: &self.responder_index,
+        }
+    }
+}
+
+impl<'a> Signable<'a> for PairwiseChannelInit {
+    const SIGN_LABEL: &'static str = "PairwiseChannelInitTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        SeedTBS {
+            context: &self.context(),
+            ciphertexts: &self.hpke_ciphertext,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl SignedSeed for PairwiseChannelInit {
+    const ENCRYPT_LABEL: &'static str = "PairwiseChannelSeed";
+    const EXPORTER_LABEL: &'static [u8] = PAIRWISE_CHANNEL_EXPORTER_LABEL;
+
+    fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    fn sender_index(&self) -> LeafIndex {
+        self.initiator_index
+    }
+
+    fn encoded_context(&self) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        self.context().mls_encode_to_vec()
+    }
+}
+
+#[derive(Clone, MlsSize, MlsEncode, MlsDecode)]
+struct PairwiseMessage {
+    generation: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    ciphertext: Vec<u8>,
+}
+
+struct MessageKey {
+    key: Zeroizing<Vec<u8>>,
+    nonce: Zeroizing<Vec<u8>>,
+}
+
+#[derive(Clone)]
+struct ChainSecret {
+    secret: Zeroizing<Vec<u8>>,
+    generation: u32,
+}
+
+impl ChainSecret {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn next<P: CipherSuiteProvider>(&self, cs: &P) -> Result<Self, MlsError> {
+        Ok(Self {
+            secret: kdf_expand_with_label(cs, &self.secret, b"next", &[], None).await?,
+            generation: self.generation + 1,
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn message_key<P: CipherSuiteProvider>(&self, cs: &P) -> Result<MessageKey, MlsError> {
+        let key =
+            kdf_expand_with_label(cs, &self.secret, b"key", &[], Some(cs.aead_key_size())).await?;
+
+        let nonce =
+            kdf_expand_with_label(cs, &self.secret, b"nonce", &[], Some(cs.aead_nonce_size()))
+                .await?;
+
+        Ok(MessageKey { key, nonce })
+    }
+}
+
+/// 1:1 channel with another member of a group, created with
+/// [`Group::initiate_pairwise_channel`] or
+/// [`Group::accept_pairwise_channel`].
+///
+/// Messages sealed by one side are opened by the other side. Messages must be
+/// opened in the order they were sealed; a message may be skipped, but a
+/// message older than the last opened message can no longer be opened.
+#[derive(Clone)]
+pub struct PairwiseChannel<P> {
+    cipher_suite_provider: P,
+    peer_index: u32,
+    send: ChainSecret,
+    receive: ChainSecret,
+}
+
+impl<P> Debug for PairwiseChannel<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PairwiseChannel")
+            .field("peer_index", &self.peer_index)
+            .field("send_generation", &self.send.generation)
+            .field("receive_generation", &self.receive.generation)
+            .finish()
+    }
+}
+
+impl<P: CipherSuiteProvider> PairwiseChannel<P> {
+    /// Leaf index of the other member of the channel.
+    pub fn peer_index(&self) -> u32 {
+        self.peer_index
+    }
+
+    /// Encrypt `data` for the peer.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn seal(&mut self, data: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+        let generation = self.send.generation;
+        let MessageKey { key, nonce } = self.send.message_key(cs).await?;
+
+        let ciphertext = cs
+            .aead_seal(&key, data, Some(&generation.to_be_bytes()), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        self.send = self.send.next(cs).await?;
+
+        PairwiseMessage {
+            generation,
+            ciphertext,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+
+    /// Decrypt a message sealed by the peer.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn open(&mut self, message: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+        let message = PairwiseMessage::mls_decode(&mut &*message)?;
+
+        if message.generation < self.receive.generation {
+            return Err(MlsError::KeyMissing(message.generation));
+        }
+
+        if message.generation - self.receive.generation > MAX_GENERATION_GAP {
+            return Err(MlsError::InvalidFutureGeneration(message.generation));
+        }
+
+        let mut chain = self.receive.clone();
+
+        while chain.generation < message.generation {
+            chain = chain.next(cs).await?;
+        }
+
+        let MessageKey { key, nonce } = chain.message_key(cs).await?;
+
+        let data = cs
+            .aead_open(
+                &key,
+                &message.ciphertext,
+                Some(&message.generation.to_be_bytes()),
+                &nonce,
+            )
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        self.receive = chain.next(cs).await?;
+
+        Ok(data.to_vec())
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`PairwiseChannel`] with the member at `peer_index`.
+    ///
+    /// The returned [`PairwiseChannelInit`] must be delivered to the peer,
+    /// which accepts it with [`Group::accept_pairwise_channel`] in the same
+    /// epoch.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn initiate_pairwise_channel(
+        &self,
+        peer_index: u32,
+    ) -> Result<(GroupPairwiseChannel<C>, PairwiseChannelInit), MlsError> {
+        let responder_index = LeafIndex(peer_index);
+        let initiator_index = self.private_tree.self_index;
+
+        if responder_index == initiator_index {
+            return Err(MlsError::PairwiseChannelPeerMismatch);
+        }
+
+        let context = SeedContext {
+            group_id: self.group_id(),
+            epoch: self.current_epoch(),
+            sender_index: initiator_index,
+            recipients: &responder_index,
+        }
+        .mls_encode_to_vec()?;
+
+        let seed = self.generate_seed()?;
+        let hpke_ciphertext = self.encrypt_seed(&seed, &context, responder_index).await?;
+
+        let mut init = PairwiseChannelInit {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            initiator_index,
+            responder_index,
+            hpke_ciphertext,
+            signature: Vec::new(),
+        };
+
+        init.sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        let channel = self.pairwise_channel(&init, &seed, true).await?;
+
+        Ok((channel, init))
+    }
+
+    /// Accept a [`PairwiseChannel`] created by another member with
+    /// [`Group::initiate_pairwise_channel`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn accept_pairwise_channel(
+        &self,
+        init: &PairwiseChannelInit,
+    ) -> Result<GroupPairwiseChannel<C>, MlsError> {
+        self.check_seed_epoch(init)?;
+
+        if init.responder_index != self.private_tree.self_index
+            || init.initiator_index == init.responder_index
+        {
+            return Err(MlsError::PairwiseChannelPeerMismatch);
+        }
+
+        let seed = self.open_seed(init, &init.hpke_ciphertext).await?;
+
+        self.pairwise_channel(init, &seed, false).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn pairwise_channel(
+        &self,
+        init: &PairwiseChannelInit,
+        seed: &Seed<PairwiseChannelInit>,
+        is_initiator: bool,
+    ) -> Result<GroupPairwiseChannel<C>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+        let root = self.seed_secret(init, seed).await?;
+
+        let initiator = ChainSecret {
+            secret: kdf_expand_with_label(cs, &root, b"initiator", &[], None).await?,
+            generation: 0,
+        };
+
+        let responder = ChainSecret {
+            secret: kdf_expand_with_label(cs, &root, b"responder", &[], None).await?,
+            generation: 0,
+        };
+
+        let (send, receive, peer_index) = if is_initiator {
+            (initiator, responder, init.responder_index)
+        } else {
+            (responder, initiator, init.initiator_index)
+        };
+
+        Ok(PairwiseChannel {
+            cipher_suite_provider: cs.clone(),
+            peer_index: *peer_index,
+            send,
+            receive,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::client::MlsError;
+    use crate::group::test_utils::test_group;
+
+    use super::PairwiseChannelInit;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn pairwise_channel_round_trip() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut carol, commit) = alice.join("carol").await;
+
+        let (mut alice_channel, init) = alice.initiate_pairwise_channel(1).await.unwrap();
+        let init = PairwiseChannelInit::from_bytes(&init.to_bytes().unwrap()).unwrap();
+
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let mut bob_channel = bob.accept_pairwise_channel(&init).await.unwrap();
+        assert_eq!(bob_channel.peer_index(), 0);
+
+        let first = alice_channel.seal(b"typing").await.unwrap();
+        let second = alice_channel.seal(b"read").await.unwrap();
+
+        let opened = bob_channel.open(&second).await.unwrap();
+        assert_eq!(opened, b"read");
+
+        let res = bob_channel.open(&first).await;
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+
+        let reply = bob_channel.seal(b"ack").await.unwrap();
+        let opened = alice_channel.open(&reply).await.unwrap();
+        assert_eq!(opened, b"ack");
+
+        let res = carol.accept_pairwise_channel(&init).await;
+        assert_matches!(res, Err(MlsError::PairwiseChannelPeerMismatch));
+
+        let commit = carol.commit(vec![]).await.unwrap().commit_message;
+        carol.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let res = bob.accept_pairwise_channel(&init).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+}
@@ -200,6 +200,21 @@ impl ProposalCache {
         Ok(proposals)
     }
 
+    /// Remove the proposal with reference `proposal_ref`, returning `true`
+    /// if it was cached.
+    pub fn remove(&mut self, proposal_ref: &ProposalRef) -> bool {
+        let own_hash = self
+            .own_proposals
+            .iter()
+            .find_map(|(hash, p)| (&p.proposal_ref == proposal_ref).then(|| hash.clone()));
+
+        if let Some(hash) = own_hash {
+            self.own_proposals.remove(&hash);
+        }
+
+        self.proposals.remove(proposal_ref).is_some()
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn get_own<CS: CipherSuiteProvider>(
         &self,
@@ -210,6 +225,23 @@ impl ProposalCache {
 
         Ok(self.own_proposals.get(&message_hash).cloned())
     }
+
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn remove_own<CS: CipherSuiteProvider>(
+        &mut self,
+        cs: &CS,
+        message: &MlsMessage,
+    ) -> Result<Option<ProposalMessageDescription>, MlsError> {
+        let message_hash = MessageHash::compute(cs, message).await?;
+        let proposal = self.own_proposals.remove(&message_hash);
+
+        if let Some(proposal) = &proposal {
+            self.proposals.remove(&proposal.proposal_ref);
+        }
+
+        Ok(proposal)
+    }
 }
 
 #[cfg(not(feature = "by_ref_proposal"))]
@@ -727,7 +759,7 @@ mod tests {
     use mls_rs_core::psk::{PreSharedKey, PreSharedKeyStorage};
     use mls_rs_core::{
         extension::MlsExtension,
-        identity::{Credential, CredentialType, CustomCredential},
+        identity::{Credential, CredentialType, CustomCredential, SigningIdentity},
     };
 
     fn test_sender() -> u32 {
@@ -3025,6 +3057,7 @@ mod tests {
                     ..Capabilities::default()
                 },
                 extensions: Default::default(),
+                grease: true,
             };
 
             let (leaf, secret) = LeafNode::generate(
@@ -3597,6 +3630,7 @@ mod tests {
             cipher_suite_provider: &test_cipher_suite_provider(TEST_CIPHER_SUITE),
             signing_identity: &signing_identity,
             signing_key: &secret_key,
+            grease: true,
         };
 
         generator
@@ -3921,6 +3955,79 @@ mod tests {
         assert_eq!(committed, Vec::new());
     }
 
+    struct OwnerOnlyExtensionChanges {
+        owner: SigningIdentity,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl MlsRules for OwnerOnlyExtensionChanges {
+        type Error = MlsError;
+
+        async fn filter_proposals(
+            &self,
+            _: CommitDirection,
+            source: CommitSource,
+            current_roster: &Roster,
+            _: &GroupContext,
+            proposals: ProposalBundle,
+        ) -> Result<ProposalBundle, Self::Error> {
+            let owner_index = current_roster
+                .member_with_signing_identity(&self.owner)
+                .map(|member| member.index);
+
+            let allowed = proposals.group_context_extensions.is_empty()
+                || (owner_index.is_some() && owner_index == source.member_index());
+
+            allowed.then_some(proposals).ok_or(MlsError::InvalidSender)
+        }
+
+        #[cfg_attr(coverage_nightly, coverage(off))]
+        fn commit_options(
+            &self,
+            _: &Roster,
+            _: &GroupContext,
+            _: &ProposalBundle,
+        ) -> Result<CommitOptions, Self::Error> {
+            Ok(Default::default())
+        }
+
+        #[cfg_attr(coverage_nightly, coverage(off))]
+        fn encryption_options(
+            &self,
+            _: &Roster,
+            _: &GroupContext,
+        ) -> Result<EncryptionOptions, Self::Error> {
+            Ok(Default::default())
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn user_defined_filter_can_use_committer_and_roster() {
+        let (alice, mut tree) = new_tree("alice").await;
+        let bob = add_member(&mut tree, "bob").await;
+
+        let owner = tree.get_leaf_node(alice).unwrap().signing_identity.clone();
+
+        let res = CommitSender::new(&tree, alice, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+            .with_additional([Proposal::GroupContextExtensions(Default::default())])
+            .with_user_rules(OwnerOnlyExtensionChanges {
+                owner: owner.clone(),
+            })
+            .send()
+            .await;
+
+        assert!(res.is_ok());
+
+        let res = CommitSender::new(&tree, bob, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+            .with_additional([Proposal::GroupContextExtensions(Default::default())])
+            .with_user_rules(OwnerOnlyExtensionChanges { owner })
+            .send()
+            .await;
+
+        assert_matches!(res, Err(MlsError::MlsRulesError(_)));
+    }
+
     struct FailureMlsRules;
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -0,0 +1,238 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        mls_rules::{CommitDirection, CommitOptions, CommitSource, EncryptionOptions, MlsRules},
+        proposal_filter::ProposalBundle,
+        CommitBuilder, Group, GroupContext, Roster, Sender,
+    },
+    tree_kem::node::LeafIndex,
+};
+
+/// Members quarantined by the local member. This is local policy and not
+/// part of the group state agreed upon by members.
+#[derive(Clone, Debug, Default, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Quarantine(Vec<LeafIndex>);
+
+impl Quarantine {
+    pub(crate) fn contains(&self, index: LeafIndex) -> bool {
+        self.0.contains(&index)
+    }
+
+    fn insert(&mut self, index: LeafIndex) {
+        if !self.contains(index) {
+            self.0.push(index);
+        }
+    }
+
+    fn remove(&mut self, index: LeafIndex) -> bool {
+        let len = self.0.len();
+        self.0.retain(|i| *i != index);
+        self.0.len() != len
+    }
+
+    /// Release the leaves emptied by `proposals` so that the quarantine does
+    /// not apply to a new member reusing them.
+    pub(crate) fn forget_removed(&mut self, proposals: &ProposalBundle) {
+        for removal in proposals.remove_proposals() {
+            self.remove(removal.proposal.to_remove);
+        }
+    }
+}
+
+/// [`MlsRules`] layer applied when preparing a commit. By-reference
+/// proposals sent by quarantined members are filtered out before the
+/// configured rules run. Received commits are not affected.
+pub(crate) struct QuarantineRules<R> {
+    inner: R,
+    quarantine: Quarantine,
+}
+
+impl<R> QuarantineRules<R> {
+    pub(crate) fn new(inner: R, quarantine: &Quarantine) -> Self {
+        Self {
+            inner,
+            quarantine: quarantine.clone(),
+        }
+    }
+
+    fn is_rejected(&self, sender: &Sender, by_reference: bool) -> bool {
+        matches!(sender, Sender::Member(index) if by_reference && self.quarantine.contains(LeafIndex(*index)))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<R: MlsRules> MlsRules for QuarantineRules<R> {
+    type Error = R::Error;
+
+    async fn filter_proposals(
+        &self,
+        direction: CommitDirection,
+        source: CommitSource,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+        mut proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, Self::Error> {
+        if direction == CommitDirection::Send {
+            proposals
+                .retain(|p| Ok::<_, R::Error>(!self.is_rejected(&p.sender, p.is_by_reference())))?;
+
+            #[cfg(feature = "custom_proposal")]
+            proposals.retain_custom(|p| {
+                Ok::<_, R::Error>(!self.is_rejected(&p.sender, p.is_by_reference()))
+            })?;
+        }
+
+        self.inner
+            .filter_proposals(
+                direction,
+                source,
+                current_roster,
+                current_context,
+                proposals,
+            )
+            .await
+    }
+
+    fn commit_options(
+        &self,
+        new_roster: &Roster,
+        new_context: &GroupContext,
+        proposals: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        self.inner
+            .commit_options(new_roster, new_context, proposals)
+    }
+
+    fn encryption_options(
+        &self,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+    ) -> Result<EncryptionOptions, Self::Error> {
+        self.inner
+            .encryption_options(current_roster, current_context)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Quarantine the member at leaf `index` in local policy.
+    ///
+    /// Application messages from a quarantined member are still decrypted
+    /// but are flagged with
+    /// [`ApplicationMessageDescription::is_sender_quarantined`](crate::group::ApplicationMessageDescription::is_sender_quarantined).
+    /// Proposals sent by a quarantined member are left out of commits created
+    /// by the local member. The quarantine is lifted automatically once the
+    /// member is removed from the group, see
+    /// [`Group::quarantine_removal_commit`].
+    pub fn quarantine_member(&mut self, index: u32) -> Result<(), MlsError> {
+        let index = LeafIndex(index);
+
+        if index == self.private_tree.self_index {
+            return Err(MlsError::CannotQuarantineSelf);
+        }
+
+        self.state.public_tree.get_leaf_node(index)?;
+        self.quarantine.insert(index);
+
+        Ok(())
+    }
+
+    /// Lift the quarantine of the member at leaf `index`. Returns `false` if
+    /// the member was not quarantined.
+    pub fn release_member(&mut self, index: u32) -> bool {
+        self.quarantine.remove(LeafIndex(index))
+    }
+
+    /// Returns `true` if the member at leaf `index` is quarantined.
+    pub fn is_member_quarantined(&self, index: u32) -> bool {
+        self.quarantine.contains(LeafIndex(index))
+    }
+
+    /// Leaf indexes of all quarantined members.
+    pub fn quarantined_members(&self) -> Vec<u32> {
+        self.quarantine.0.iter().map(|i| **i).collect()
+    }
+
+    /// Create a commit builder prepared with a removal of every quarantined
+    /// member. Further proposals can be added before the commit is built.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn quarantine_removal_commit(&mut self) -> Result<CommitBuilder<'_, C>, MlsError> {
+        let quarantined = self.quarantined_members();
+
+        quarantined
+            .into_iter()
+            .try_fold(self.commit_builder(), |builder, index| {
+                builder.remove_member(index)
+            })
+    }
+}
+
+#[cfg(all(test, feature = "private_message", feature = "by_ref_proposal"))]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::client::MlsError;
+    use crate::group::test_utils::{process_commit, test_n_member_group};
+    use crate::group::{CommitEffect, ReceivedMessage};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn quarantined_member_is_flagged_filtered_and_removed() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        groups[0].quarantine_member(1).unwrap();
+
+        let res = groups[0].quarantine_member(0);
+        assert_matches!(res, Err(MlsError::CannotQuarantineSelf));
+
+        for (sender, quarantined) in [(1, true), (2, false)] {
+            let message = groups[sender]
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            let received = groups[0].process_message(message).await.unwrap();
+
+            assert_matches!(
+                received,
+                ReceivedMessage::ApplicationMessage(m) if m.is_sender_quarantined() == quarantined
+            );
+        }
+
+        let proposal = groups[1].propose_update(vec![]).await.unwrap();
+        groups[0].process_message(proposal).await.unwrap();
+
+        let commit = groups[0].commit(vec![]).await.unwrap().commit_message;
+        let effect = groups[0].apply_pending_commit().await.unwrap().effect;
+
+        assert_matches!(effect, CommitEffect::NewEpoch(e) if e.applied_proposals.is_empty());
+        process_commit(&mut groups, commit, 0).await;
+
+        groups[0]
+            .quarantine_removal_commit()
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        groups[0].apply_pending_commit().await.unwrap();
+
+        assert!(groups[0].roster().member_with_index(1).is_err());
+        assert!(groups[0].quarantined_members().is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{commit::CommitProposals, proposal_filter::ProposalBundle, CommitOutput, Group},
+};
+
+/// Receive-only mode for members that must never author content, such as
+/// bots or auditors.
+///
+/// In this mode, sending application messages, proposals and commits fails
+/// with [`MlsError::ReceiveOnlyGroup`], while incoming messages are processed
+/// as usual. The only exception is the update of the own leaf required by
+/// [`max_epochs_without_update`](Self::max_epochs_without_update), performed
+/// with [`Group::forced_update`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ReceiveOnlyPolicy {
+    /// Number of epochs after which the member must update its leaf, for
+    /// example to meet post-compromise security requirements of the delivery
+    /// service. The member never updates if not set.
+    pub max_epochs_without_update: Option<u64>,
+}
+
+impl ReceiveOnlyPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_epochs_without_update(self, max_epochs_without_update: u64) -> Self {
+        Self {
+            max_epochs_without_update: Some(max_epochs_without_update),
+        }
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Whether the local member is in receive-only mode in this group. See
+    /// [`ReceiveOnlyPolicy`].
+    pub fn is_receive_only(&self) -> bool {
+        self.config_overrides.receive_only.is_some()
+    }
+
+    /// Whether the [`ReceiveOnlyPolicy`] of this group requires the local
+    /// member to update its leaf, because it was last updated at least
+    /// [`max_epochs_without_update`](ReceiveOnlyPolicy::max_epochs_without_update)
+    /// epochs ago.
+    pub fn is_update_forced(&self) -> bool {
+        let Some(max) = self
+            .config_overrides
+            .receive_only
+            .as_ref()
+            .and_then(|policy| policy.max_epochs_without_update)
+        else {
+            return false;
+        };
+
+        self.leaf_update_epochs
+            .get(self.private_tree.self_index)
+            .map_or(true, |epoch| {
+                self.current_epoch().saturating_sub(epoch) >= max
+            })
+    }
+
+    /// Commit an update of the leaf of the local member if required by the
+    /// [`ReceiveOnlyPolicy`] of this group, and `None` otherwise.
+    ///
+    /// Receive-only members call this after processing incoming commits. The
+    /// commit contains no proposals, not even those cached in the current
+    /// epoch, and only updates the path of the local member. It is applied
+    /// with [`Group::apply_pending_commit`] as any other commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn forced_update(&mut self) -> Result<Option<CommitOutput>, MlsError> {
+        if !self.is_update_forced() {
+            return Ok(None);
+        }
+
+        let (output, pending_commit) = self
+            .commit_internal(
+                CommitProposals::Bundle(ProposalBundle::default()),
+                None,
+                Default::default(),
+                Default::default(),
+                None,
+                None,
+                None,
+                #[cfg(feature = "private_message")]
+                None,
+            )
+            .await?;
+
+        self.pending_commit = Some(pending_commit);
+        self.state_changed().await?;
+
+        Ok(Some(output))
+    }
+
+    pub(crate) fn check_not_receive_only(&self) -> Result<(), MlsError> {
+        match self.is_receive_only() {
+            true => Err(MlsError::ReceiveOnlyGroup),
+            false => Ok(()),
+        }
+    }
+
+    /// Only commits updating the own leaf as required by the policy are
+    /// allowed in receive-only mode.
+    pub(crate) fn check_receive_only_commit(
+        &self,
+        proposals: &ProposalBundle,
+        authenticated_data: &[u8],
+    ) -> Result<(), MlsError> {
+        let forced_update =
+            proposals.length() == 0 && authenticated_data.is_empty() && self.is_update_forced();
+
+        match forced_update {
+            true => Ok(()),
+            false => self.check_not_receive_only(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_n_member_group, GroupConfigOverrides},
+        key_package::test_utils::test_key_package_message,
+    };
+
+    use super::ReceiveOnlyPolicy;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receive_only_member_only_commits_forced_updates() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let policy = ReceiveOnlyPolicy::new().with_max_epochs_without_update(2);
+        groups[1].config_overrides = GroupConfigOverrides::new().with_receive_only(policy);
+
+        assert!(groups[1].is_receive_only());
+        assert!(!groups[1].is_update_forced());
+
+        #[cfg(feature = "private_message")]
+        {
+            let res = groups[1].encrypt_application_message(b"hi", vec![]).await;
+            assert_matches!(res, Err(MlsError::ReceiveOnlyGroup));
+        }
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            let res = groups[1].propose_add(key_package.clone(), vec![]).await;
+            assert_matches!(res, Err(MlsError::ReceiveOnlyGroup));
+        }
+
+        let res = groups[1]
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::ReceiveOnlyGroup));
+
+        let res = groups[1].forced_update().await;
+        assert_matches!(res, Ok(None));
+
+        let res = groups[1].commit(vec![]).await;
+        assert_matches!(res, Err(MlsError::ReceiveOnlyGroup));
+
+        // Ratchets still advance on receive
+        for _ in 0..2 {
+            let commit = groups[0].commit(vec![]).await.unwrap();
+            groups[0].apply_pending_commit().await.unwrap();
+
+            groups[1]
+                .process_incoming_message(commit.commit_message)
+                .await
+                .unwrap();
+        }
+
+        assert!(groups[1].is_update_forced());
+
+        let commit = groups[1].forced_update().await.unwrap().unwrap();
+        assert!(commit.contains_update_path);
+        groups[1].apply_pending_commit().await.unwrap();
+
+        groups[0]
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert!(!groups[1].is_update_forced());
+        assert_eq!(groups[0].current_epoch(), groups[1].current_epoch());
+    }
+}
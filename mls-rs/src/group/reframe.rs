@@ -0,0 +1,305 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(feature = "by_ref_proposal")]
+use alloc::boxed::Box;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        commit::{CommitGeneration, CommitProposals},
+        framing::{Content, WireFormat},
+        CommitOutput, Group,
+    },
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::{
+    group::{
+        framing::Sender, message_processor::ProposalMessageDescription,
+        message_signature::AuthenticatedContent,
+    },
+    MlsMessage,
+};
+
+#[cfg(not(feature = "by_ref_proposal"))]
+use crate::group::proposal_cache::resolve_for_commit;
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Replace the pending commit by a commit with the same proposals and
+    /// authenticated data, framed as `wire_format` instead of the framing
+    /// chosen by the [encryption options](crate::mls_rules::EncryptionOptions).
+    ///
+    /// The wire format is covered by the signature of the commit and by the
+    /// transcript hash of the next epoch, so the commit message, the welcome
+    /// messages and the group info of the previous output all change and must
+    /// not be sent. The update path is generated again with new secrets.
+    ///
+    /// Group info extensions set with
+    /// [`CommitBuilder::set_group_info_ext`](crate::group::CommitBuilder::set_group_info_ext)
+    /// are not kept. A commit that replaced the signer of this member can not
+    /// be reframed, as the previous signer is no longer available.
+    ///
+    /// `wire_format` must be [`WireFormat::PublicMessage`] or
+    /// [`WireFormat::PrivateMessage`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn reframe_pending_commit(
+        &mut self,
+        wire_format: WireFormat,
+    ) -> Result<CommitOutput, MlsError> {
+        check_framing(wire_format)?;
+
+        let pending_commit = self
+            .pending_commit
+            .take()
+            .ok_or(MlsError::PendingCommitNotFound)?;
+
+        match self.rebuild_commit(&pending_commit, wire_format).await {
+            Ok((output, pending_commit)) => {
+                self.pending_commit = Some(pending_commit);
+                self.state_changed().await?;
+
+                Ok(output)
+            }
+            Err(e) => {
+                self.pending_commit = Some(pending_commit);
+                Err(e)
+            }
+        }
+    }
+
+    /// Replace a proposal `message` sent by this member in the current epoch
+    /// by the same proposal framed as `wire_format`.
+    ///
+    /// The proposal reference changes with the framing, so the proposal
+    /// cached for the next commit is replaced as well and `message` must not
+    /// be sent. Fails with [`MlsError::ProposalNotFound`] if `message` is not
+    /// a proposal of this member cached in the current epoch.
+    ///
+    /// `wire_format` must be [`WireFormat::PublicMessage`] or
+    /// [`WireFormat::PrivateMessage`].
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn reframe_proposal(
+        &mut self,
+        message: &MlsMessage,
+        wire_format: WireFormat,
+    ) -> Result<MlsMessage, MlsError> {
+        check_framing(wire_format)?;
+
+        let previous = self
+            .state
+            .proposals
+            .get_own(&self.cipher_suite_provider, message)
+            .await?
+            .ok_or(MlsError::ProposalNotFound)?;
+
+        let auth_content = AuthenticatedContent::new_signed(
+            &self.cipher_suite_provider,
+            self.context(),
+            Sender::Member(*self.private_tree.self_index),
+            Content::Proposal(Box::new(previous.proposal.clone())),
+            &self.signer,
+            wire_format,
+            previous.authenticated_data,
+        )
+        .await?;
+
+        let sender = auth_content.content.sender;
+
+        let proposal_desc = ProposalMessageDescription::new(
+            &self.cipher_suite_provider,
+            &auth_content,
+            previous.proposal,
+        )
+        .await?;
+
+        let reframed = self.format_for_wire(auth_content).await?;
+
+        self.state
+            .proposals
+            .remove_own(&self.cipher_suite_provider, message)
+            .await?;
+
+        self.state
+            .proposals
+            .insert_own(
+                proposal_desc,
+                &reframed,
+                sender,
+                &self.cipher_suite_provider,
+            )
+            .await?;
+
+        self.state_changed().await?;
+
+        Ok(reframed)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn rebuild_commit(
+        &mut self,
+        pending_commit: &CommitGeneration,
+        wire_format: WireFormat,
+    ) -> Result<(CommitOutput, CommitGeneration), MlsError> {
+        let content = &pending_commit.content.content;
+
+        let Content::Commit(commit) = &content.content else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        let current_identity = &self.current_user_leaf_node()?.signing_identity;
+        let path_leaf = commit.path.as_ref().map(|path| &path.leaf_node);
+
+        // The commit was signed with the previous signer, which was replaced
+        // by the new one when the commit was built
+        if path_leaf.map_or(false, |leaf| {
+            leaf.signing_identity.signature_key != current_identity.signature_key
+        }) {
+            return Err(MlsError::PendingCommitSignerReplaced);
+        }
+
+        let new_signing_identity = path_leaf
+            .map(|leaf| &leaf.signing_identity)
+            .filter(|identity| *identity != current_identity)
+            .cloned();
+
+        let new_leaf_node_extensions = path_leaf.map(|leaf| leaf.ungreased_extensions());
+
+        #[cfg(feature = "by_ref_proposal")]
+        let proposals = self
+            .state
+            .proposals
+            .resolve_for_commit(content.sender, commit.proposals.clone())?;
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let proposals = resolve_for_commit(content.sender, commit.proposals.clone())?;
+
+        self.commit_internal(
+            CommitProposals::Bundle(proposals),
+            None,
+            content.authenticated_data.clone(),
+            Default::default(),
+            None,
+            new_signing_identity,
+            new_leaf_node_extensions,
+            Some(wire_format),
+        )
+        .await
+    }
+}
+
+fn check_framing(wire_format: WireFormat) -> Result<(), MlsError> {
+    match wire_format {
+        WireFormat::PublicMessage | WireFormat::PrivateMessage => Ok(()),
+        _ => Err(MlsError::UnsupportedWireFormat(wire_format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{framing::WireFormat, test_utils::test_n_member_group, ReceivedMessage},
+        key_package::test_utils::test_key_package_message,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn pending_commit_can_be_reframed() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let public_commit = groups[0]
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            public_commit.commit_message.wire_format(),
+            WireFormat::PublicMessage
+        );
+
+        let res = groups[0].reframe_pending_commit(WireFormat::Welcome).await;
+        assert_matches!(
+            res,
+            Err(MlsError::UnsupportedWireFormat(WireFormat::Welcome))
+        );
+
+        let commit = groups[0]
+            .reframe_pending_commit(WireFormat::PrivateMessage)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            commit.commit_message.wire_format(),
+            WireFormat::PrivateMessage
+        );
+
+        assert_eq!(commit.welcome_messages.len(), 1);
+
+        groups[0].apply_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(groups[0].roster().members_iter().count(), 3);
+        assert_eq!(groups[1].roster().members_iter().count(), 3);
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn own_proposal_can_be_reframed() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let proposal = groups[0].propose_add(key_package, vec![]).await.unwrap();
+
+        let reframed = groups[0]
+            .reframe_proposal(&proposal, WireFormat::PrivateMessage)
+            .await
+            .unwrap();
+
+        assert_eq!(reframed.wire_format(), WireFormat::PrivateMessage);
+
+        // The previous message is no longer known as a proposal of this member
+        let res = groups[0]
+            .reframe_proposal(&proposal, WireFormat::PublicMessage)
+            .await;
+
+        assert_matches!(res, Err(MlsError::ProposalNotFound));
+
+        let received = groups[1].process_incoming_message(reframed).await.unwrap();
+        assert_matches!(received, ReceivedMessage::Proposal(_));
+
+        let commit = groups[0].commit(vec![]).await.unwrap();
+        groups[0].apply_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(groups[1].roster().members_iter().count(), 3);
+    }
+}
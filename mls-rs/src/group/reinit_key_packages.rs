@@ -0,0 +1,295 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::SignatureSecretKey,
+    error::IntoAnyError,
+    identity::{IdentityProvider, SigningIdentity},
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{ApplicationMessageDescription, Group},
+    tree_kem::node::LeafIndex,
+    MlsMessage,
+};
+
+/// Application data of a message announcing the key package of a member for
+/// the reinitialized group.
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct ReinitKeyPackageAnnouncement {
+    key_package: MlsMessage,
+}
+
+/// Collects the key packages announced with
+/// [`Group::announce_reinit_key_package`] by the other members of a group
+/// with a pending reinit, so that they can be passed to
+/// [`ReinitClient::commit`](crate::group::ReinitClient::commit).
+///
+/// Each announced key package is checked against the parameters of the
+/// reinit and the identity of its sender. A member announcing more than once
+/// replaces its previous key package.
+#[derive(Clone, Debug)]
+pub struct ReinitKeyPackageCollector {
+    expected: Vec<u32>,
+    key_packages: Vec<(u32, MlsMessage)>,
+}
+
+impl ReinitKeyPackageCollector {
+    /// Add the key package announced in a received application message,
+    /// returning `true` once key packages of all members were collected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::InvalidReinitKeyPackageAnnouncement`] if the
+    /// application data is not an announcement or was sent by a member that
+    /// is not expected to announce, [`MlsError::ProtocolVersionMismatch`] or
+    /// [`MlsError::CipherSuiteMismatch`] if the key package does not match
+    /// the reinit, and [`MlsError::InvalidSuccessor`] if its identity is not
+    /// a valid successor of the identity of the sender.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn receive<C>(
+        &mut self,
+        group: &Group<C>,
+        message: &ApplicationMessageDescription,
+    ) -> Result<bool, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        let reinit = group
+            .state
+            .pending_reinit
+            .as_ref()
+            .ok_or(MlsError::PendingReInitNotFound)?;
+
+        let sender_index = message.sender_index;
+
+        if !self.expected.contains(&sender_index) {
+            return Err(MlsError::InvalidReinitKeyPackageAnnouncement);
+        }
+
+        let announcement = ReinitKeyPackageAnnouncement::mls_decode(&mut message.data())
+            .map_err(|_| MlsError::InvalidReinitKeyPackageAnnouncement)?;
+
+        let key_package = announcement
+            .key_package
+            .as_key_package()
+            .ok_or(MlsError::InvalidReinitKeyPackageAnnouncement)?;
+
+        if key_package.version != reinit.new_version() {
+            return Err(MlsError::ProtocolVersionMismatch);
+        }
+
+        if key_package.cipher_suite != reinit.new_cipher_suite() {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        let predecessor = &group
+            .state
+            .public_tree
+            .get_leaf_node(LeafIndex(sender_index))?
+            .signing_identity;
+
+        group
+            .config
+            .identity_provider()
+            .valid_successor(
+                predecessor,
+                &key_package.leaf_node.signing_identity,
+                reinit.new_group_context_extensions(),
+            )
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?
+            .then_some(())
+            .ok_or(MlsError::InvalidSuccessor)?;
+
+        self.key_packages.retain(|(i, _)| *i != sender_index);
+        self.key_packages
+            .push((sender_index, announcement.key_package));
+
+        Ok(self.is_complete())
+    }
+
+    /// Whether key packages of all members were collected.
+    pub fn is_complete(&self) -> bool {
+        self.key_packages.len() == self.expected.len()
+    }
+
+    /// Indexes of the members whose key package was not collected yet.
+    pub fn missing_members(&self) -> Vec<u32> {
+        self.expected
+            .iter()
+            .copied()
+            .filter(|i| self.key_packages.iter().all(|(j, _)| i != j))
+            .collect()
+    }
+
+    /// Collected key packages, ordered by the index of the announcing member.
+    pub fn into_key_packages(mut self) -> Vec<MlsMessage> {
+        self.key_packages.sort_by_key(|(i, _)| *i);
+        self.key_packages.into_iter().map(|(_, kp)| kp).collect()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Announce a key package for the group reinitialized by a committed
+    /// [`ReInitProposal`](crate::group::proposal::ReInitProposal), as an
+    /// application message to the other members.
+    ///
+    /// The member committing the reinit collects announced key packages with
+    /// [`ReinitKeyPackageCollector`]. `new_signer` and `new_signing_identity`
+    /// are used as in [`Group::get_reinit_client`], and the same values must
+    /// be passed to it later in order to join the reinitialized group.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn announce_reinit_key_package(
+        &mut self,
+        new_signer: Option<SignatureSecretKey>,
+        new_signing_identity: Option<SigningIdentity>,
+    ) -> Result<MlsMessage, MlsError> {
+        let key_package = self
+            .reinit_client(new_signer, new_signing_identity)?
+            .generate_key_package()
+            .await?;
+
+        let announcement = ReinitKeyPackageAnnouncement { key_package };
+
+        self.encrypt_application_message(&announcement.mls_encode_to_vec()?, Vec::new())
+            .await
+    }
+
+    /// Start collecting the key packages announced by the other members for
+    /// the group reinitialized by a committed
+    /// [`ReInitProposal`](crate::group::proposal::ReInitProposal).
+    pub fn reinit_key_package_collector(&self) -> Result<ReinitKeyPackageCollector, MlsError> {
+        if self.state.pending_reinit.is_none() {
+            return Err(MlsError::PendingReInitNotFound);
+        }
+
+        let self_index = self.current_member_index();
+
+        let expected = self
+            .state
+            .public_tree
+            .non_empty_leaves()
+            .map(|(index, _)| *index)
+            .filter(|index| *index != self_index)
+            .collect();
+
+        Ok(ReinitKeyPackageCollector {
+            expected,
+            key_packages: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn reinit_with_announced_key_packages() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                TEST_CIPHER_SUITE,
+                Default::default(),
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit).await.unwrap();
+
+        let mut collector = alice.reinit_key_package_collector().unwrap();
+        assert_eq!(collector.missing_members(), vec![1]);
+
+        let announcement = bob.announce_reinit_key_package(None, None).await.unwrap();
+
+        let message = alice.process_message(announcement).await.unwrap();
+        let message = assert_matches!(message, ReceivedMessage::ApplicationMessage(m) => m);
+
+        let complete = collector.receive(&alice.group, &message).await.unwrap();
+        assert!(complete);
+
+        let (alice_group, welcome) = alice
+            .group
+            .get_reinit_client(None, None)
+            .unwrap()
+            .commit(collector.into_key_packages(), Default::default())
+            .await
+            .unwrap();
+
+        let (bob_group, _) = bob
+            .group
+            .get_reinit_client(None, None)
+            .unwrap()
+            .join(&welcome[0], None)
+            .await
+            .unwrap();
+
+        assert_eq!(alice_group.group_id(), bob_group.group_id());
+        assert_eq!(bob_group.roster().members().len(), 2);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn collector_rejects_other_application_messages() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                TEST_CIPHER_SUITE,
+                Default::default(),
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit).await.unwrap();
+
+        let mut collector = alice.reinit_key_package_collector().unwrap();
+
+        let message = bob
+            .encrypt_application_message(b"hello", Vec::new())
+            .await
+            .unwrap();
+
+        let message = alice.process_message(message).await.unwrap();
+        let message = assert_matches!(message, ReceivedMessage::ApplicationMessage(m) => m);
+
+        let res = collector.receive(&alice.group, &message).await;
+        assert_matches!(res, Err(MlsError::InvalidReinitKeyPackageAnnouncement));
+        assert!(!collector.is_complete());
+    }
+}
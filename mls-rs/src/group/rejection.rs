@@ -0,0 +1,229 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::string::String;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+/// Reason for which a receiver rejected a message, to be reported to the
+/// delivery service or to the sender.
+///
+/// The numeric values are stable and may be used on the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u16)]
+#[non_exhaustive]
+pub enum RejectionCode {
+    /// The receiver failed for local reasons, such as a storage or crypto
+    /// provider error. The message may be processed if delivered again.
+    LocalFailure = 1,
+    /// The receiver is not in the state expected by the message, for example
+    /// in another epoch or without the keys to decrypt it. The sender or the
+    /// receiver must resynchronize with the group.
+    ResyncNeeded = 2,
+    /// The message can't be parsed or violates the protocol.
+    Malformed = 3,
+    /// The sender is not allowed to send the message, or its signature or
+    /// authentication tags are invalid.
+    Unauthorized = 4,
+    /// The message uses a protocol version, cipher suite, extension or
+    /// proposal type not supported by the receiver.
+    Unsupported = 5,
+}
+
+impl RejectionCode {
+    /// The code reported for a message rejected with `error`.
+    pub fn for_error(error: &MlsError) -> Self {
+        match error {
+            MlsError::CryptoProviderError(_)
+            | MlsError::KeyPackageRepoError(_)
+            | MlsError::GroupStorageError(_)
+            | MlsError::KeychainStorageError(_)
+            | MlsError::IdentityBootstrapError(_)
+            | MlsError::EpochSecretsVaultError(_)
+            | MlsError::PskStoreError(_)
+            | MlsError::InvalidTreeKemPrivateKey
+            | MlsError::TimeOverflow
+            | MlsError::ArchivedGroupReadOnly
+            | MlsError::StorageIntegrityViolation
+            | MlsError::StorageCorrupted => Self::LocalFailure,
+
+            // A confirmation tag that does not match a correctly signed commit
+            // means that the key schedules of sender and receiver diverged
+            MlsError::InvalidEpoch
+            | MlsError::EpochNotFound
+            | MlsError::KeyMissing(_)
+            | MlsError::InvalidFutureGeneration(_)
+            | MlsError::FutureEpochBufferFull
+            | MlsError::MaxMessageAgeExceeded
+            | MlsError::UnexpectedCommitEpoch(_)
+            | MlsError::UnexpectedConfirmedTranscriptHash
+            | MlsError::InvalidConfirmationTag
+            | MlsError::ProposalNotFound
+            | MlsError::MissingRequiredPsk
+            | MlsError::OldGroupStateNotFound
+            | MlsError::WelcomeKeyPackageNotFound
+            | MlsError::RatchetTreeNotFound
+            | MlsError::GroupNotFound
+            | MlsError::GroupUsedAfterReInit
+            | MlsError::UpdateErrorNoSecretKey => Self::ResyncNeeded,
+
+            MlsError::IdentityProviderError(_)
+            | MlsError::MlsRulesError(_)
+            | MlsError::InvalidSignature
+            | MlsError::InvalidMembershipTag
+            | MlsError::MembershipTagForNonMember
+            | MlsError::InvalidSender
+            | MlsError::ExternalSenderCannotCommit
+            | MlsError::UnknownSigningIdentityForExternalSender
+            | MlsError::ExternalProposalsDisabled
+            | MlsError::InvalidExternalSigningIdentity
+            | MlsError::MissingSignedTreeHead
+            | MlsError::InvalidSignedTreeHead
+            | MlsError::InvalidSuccessor
+            | MlsError::DifferentIdentityInUpdate(_)
+            | MlsError::InvalidProposalTypeForSender
+            | MlsError::OnlyMembersCanCommitProposalsByRef
+            | MlsError::ExternalCommitRemovesOtherIdentity
+            | MlsError::InvalidEpochAuthenticatorProof
+            | MlsError::InvalidBridgeProvenance
+            | MlsError::InvalidMembershipToken
+            | MlsError::MembershipTokenExpired
+            | MlsError::InvalidMembershipProof => Self::Unauthorized,
+
+            MlsError::UnsupportedProtocolVersion(_)
+            | MlsError::ProtocolVersionMismatch
+            | MlsError::UnsupportedCipherSuite(_)
+            | MlsError::CipherSuiteMismatch
+            | MlsError::UnsupportedGroupExtension(_)
+            | MlsError::UnsupportedCustomProposal(_)
+            | MlsError::UnknownExtension(_)
+            | MlsError::UnsupportedWireFormat(_)
+            | MlsError::RequiredExtensionNotFound(_)
+            | MlsError::RequiredProposalNotFound(_)
+            | MlsError::RequiredCredentialNotFound(_)
+            | MlsError::CredentialTypeOfNewLeafIsUnsupported
+            | MlsError::InUseCredentialTypeUnsupportedByNewLeaf
+            | MlsError::MemberLacksCapabilities(_)
+            | MlsError::UnsupportedCompressionAlgorithm(_) => Self::Unsupported,
+
+            _ => Self::Malformed,
+        }
+    }
+}
+
+/// Report of a message rejected by
+/// [`Group::process_incoming_message`], suitable for sending back to the
+/// delivery service or to the sender of the message.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct RejectionReport {
+    /// Reason for which the message was rejected.
+    pub code: RejectionCode,
+    /// Epoch of the receiver when the message was rejected.
+    pub epoch: u64,
+    /// Human readable description of the error, for logging. Its content
+    /// is not stable.
+    pub details: String,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Report of the rejection of an incoming message with `error`, as
+    /// returned by [`Group::process_incoming_message`].
+    pub fn rejection_report(&self, error: &MlsError) -> RejectionReport {
+        #[cfg(feature = "std")]
+        let details = alloc::string::ToString::to_string(error);
+
+        #[cfg(not(feature = "std"))]
+        let details = alloc::format!("{error:?}");
+
+        RejectionReport {
+            code: RejectionCode::for_error(error),
+            epoch: self.current_epoch(),
+            details,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_n_member_group,
+    };
+
+    use super::{RejectionCode, RejectionReport};
+
+    #[test]
+    fn rejection_codes_are_stable() {
+        let codes = [
+            RejectionCode::LocalFailure,
+            RejectionCode::ResyncNeeded,
+            RejectionCode::Malformed,
+            RejectionCode::Unauthorized,
+            RejectionCode::Unsupported,
+        ];
+
+        for (code, value) in codes.into_iter().zip(1u16..) {
+            assert_eq!(code.mls_encode_to_vec().unwrap(), value.to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn errors_are_classified() {
+        let cases = [
+            (MlsError::StorageCorrupted, RejectionCode::LocalFailure),
+            (MlsError::InvalidEpoch, RejectionCode::ResyncNeeded),
+            (MlsError::InvalidSignature, RejectionCode::Unauthorized),
+            (
+                MlsError::ProtocolVersionMismatch,
+                RejectionCode::Unsupported,
+            ),
+            (MlsError::UnexpectedTrailingData, RejectionCode::Malformed),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(RejectionCode::for_error(&error), code);
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn replayed_commit_requires_resync() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let commit = groups[0].commit(vec![]).await.unwrap();
+        groups[0].apply_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_incoming_message(commit.commit_message.clone())
+            .await
+            .unwrap();
+
+        let error = groups[1]
+            .process_incoming_message(commit.commit_message)
+            .await
+            .err()
+            .unwrap();
+
+        let report = groups[1].rejection_report(&error);
+
+        assert_eq!(report.code, RejectionCode::ResyncNeeded);
+        assert_eq!(report.epoch, groups[1].current_epoch());
+
+        let encoded = report.mls_encode_to_vec().unwrap();
+        let decoded = RejectionReport::mls_decode(&mut &*encoded).unwrap();
+        assert_eq!(decoded, report);
+    }
+}
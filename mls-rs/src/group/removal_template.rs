@@ -0,0 +1,189 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        commit::{CommitGeneration, CommitProposals},
+        proposal_filter::{ProposalBundle, ProposalSource},
+        CommitOutput, ConfirmedTranscriptHash, Group, Sender,
+    },
+};
+
+/// Commits removing single members, built in advance for the epoch
+/// identified by `epoch` and `confirmed_transcript_hash`.
+#[derive(Clone)]
+pub(crate) struct RemovalTemplates {
+    epoch: u64,
+    confirmed_transcript_hash: ConfirmedTranscriptHash,
+    templates: Vec<(u32, CommitOutput, CommitGeneration)>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Build and cache, for each member in `indexes`, a signed commit
+    /// removing only that member, so that [`Group::commit_removal`] returns
+    /// it without computing a new path.
+    ///
+    /// Templates are only valid in the epoch in which they are built and
+    /// must be computed again after each commit. Calling this function
+    /// replaces all templates computed before. Templates are kept in memory
+    /// and are not written to storage.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn precompute_removal_commits(&mut self, indexes: &[u32]) -> Result<(), MlsError> {
+        self.removal_templates = None;
+
+        let mut templates = Vec::with_capacity(indexes.len());
+
+        for &index in indexes {
+            let (output, pending_commit) = self.removal_commit_internal(index).await?;
+            templates.push((index, output, pending_commit));
+        }
+
+        self.removal_templates = Some(RemovalTemplates {
+            epoch: self.current_epoch(),
+            confirmed_transcript_hash: self.context().confirmed_transcript_hash.clone(),
+            templates,
+        });
+
+        // Encrypted commits advance the handshake ratchet of this member
+        self.state_changed().await
+    }
+
+    /// Whether a commit removing the member at `index` was computed by
+    /// [`Group::precompute_removal_commits`] in the current epoch.
+    pub fn has_removal_template(&self, index: u32) -> bool {
+        self.current_removal_templates()
+            .map_or(false, |t| t.templates.iter().any(|(i, ..)| *i == index))
+    }
+
+    /// Commit the removal of the member at `index` and nothing else.
+    ///
+    /// The template computed by [`Group::precompute_removal_commits`] is
+    /// used if it was built in the current epoch, otherwise the commit is
+    /// built as usual. Proposals cached in the current epoch are not
+    /// included. The commit is applied with [`Group::apply_pending_commit`]
+    /// as any other commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn commit_removal(&mut self, index: u32) -> Result<CommitOutput, MlsError> {
+        if self.pending_commit.is_some() {
+            return Err(MlsError::ExistingPendingCommit);
+        }
+
+        let template = self.take_removal_template(index);
+
+        let (output, pending_commit) = match template {
+            Some(template) => template,
+            None => self.removal_commit_internal(index).await?,
+        };
+
+        self.pending_commit = Some(pending_commit);
+        self.state_changed().await?;
+
+        Ok(output)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn removal_commit_internal(
+        &mut self,
+        index: u32,
+    ) -> Result<(CommitOutput, CommitGeneration), MlsError> {
+        let mut proposals = ProposalBundle::default();
+
+        proposals.add(
+            self.remove_proposal(index)?,
+            Sender::Member(*self.private_tree.self_index),
+            ProposalSource::ByValue,
+        );
+
+        self.commit_internal(
+            CommitProposals::Bundle(proposals),
+            None,
+            Default::default(),
+            Default::default(),
+            None,
+            None,
+            None,
+            #[cfg(feature = "private_message")]
+            None,
+        )
+        .await
+    }
+
+    fn current_removal_templates(&self) -> Option<&RemovalTemplates> {
+        self.removal_templates.as_ref().filter(|t| {
+            t.epoch == self.current_epoch()
+                && t.confirmed_transcript_hash == self.context().confirmed_transcript_hash
+        })
+    }
+
+    fn take_removal_template(&mut self, index: u32) -> Option<(CommitOutput, CommitGeneration)> {
+        self.current_removal_templates()?;
+
+        let templates = &mut self.removal_templates.as_mut()?.templates;
+        let position = templates.iter().position(|(i, ..)| *i == index)?;
+        let (_, output, pending_commit) = templates.remove(position);
+
+        Some((output, pending_commit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_n_member_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removal_templates_are_used_in_their_epoch() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 4).await;
+
+        groups[0].precompute_removal_commits(&[1, 2]).await.unwrap();
+
+        assert!(groups[0].has_removal_template(1));
+        assert!(groups[0].has_removal_template(2));
+        assert!(!groups[0].has_removal_template(3));
+
+        let template = groups[0].removal_templates.as_ref().unwrap().templates[1]
+            .1
+            .commit_message
+            .clone();
+
+        let commit = groups[0].commit_removal(2).await.unwrap();
+        assert_eq!(commit.commit_message, template);
+        assert!(!groups[0].has_removal_template(2));
+
+        groups[0].apply_pending_commit().await.unwrap();
+
+        for group in &mut groups[1..] {
+            let res = group
+                .process_incoming_message(commit.commit_message.clone())
+                .await;
+
+            assert_matches!(res, Ok(_));
+        }
+
+        // Templates of the previous epoch are no longer used
+        assert!(!groups[0].has_removal_template(1));
+
+        let commit = groups[0].commit_removal(1).await.unwrap();
+        groups[0].apply_pending_commit().await.unwrap();
+
+        groups[3]
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(groups[3].roster().members_iter().count(), 2);
+    }
+}
@@ -11,7 +11,7 @@ use mls_rs_core::{
     protocol_version::ProtocolVersion,
 };
 
-use crate::{client::MlsError, Client, Group, MlsMessage};
+use crate::{client::MlsError, psk::GroupLineage, Client, Group, MlsMessage};
 
 use super::{
     proposal::ReInitProposal, ClientConfig, ExportedTree, JustPreSharedKeyID, MessageProcessor,
@@ -23,12 +23,14 @@ struct ResumptionGroupParameters<'a> {
     cipher_suite: CipherSuite,
     version: ProtocolVersion,
     extensions: &'a ExtensionList,
+    lineage: GroupLineage,
 }
 
 pub struct ReinitClient<C: ClientConfig + Clone> {
     client: Client<C>,
     reinit: ReInitProposal,
     psk_input: PskSecretInput,
+    lineage: GroupLineage,
 }
 
 impl<C> Group<C>
@@ -55,6 +57,7 @@ where
             cipher_suite: self.cipher_suite(),
             version: self.protocol_version(),
             extensions: &self.group_state().context.extensions,
+            lineage: self.descendant_lineage(),
         };
 
         let current_leaf_node_extensions = &self.current_user_leaf_node()?.ungreased_extensions();
@@ -84,6 +87,7 @@ where
             cipher_suite: self.cipher_suite(),
             version: self.protocol_version(),
             extensions: &self.group_state().context.extensions,
+            lineage: self.descendant_lineage(),
         };
 
         resumption_join_group(
@@ -113,8 +117,17 @@ where
         self,
         new_signer: Option<SignatureSecretKey>,
         new_signing_identity: Option<SigningIdentity>,
+    ) -> Result<ReinitClient<C>, MlsError> {
+        self.reinit_client(new_signer, new_signing_identity)
+    }
+
+    pub(crate) fn reinit_client(
+        &self,
+        new_signer: Option<SignatureSecretKey>,
+        new_signing_identity: Option<SigningIdentity>,
     ) -> Result<ReinitClient<C>, MlsError> {
         let psk_input = self.resumption_psk_input(ResumptionPSKUsage::Reinit)?;
+        let lineage = self.descendant_lineage();
 
         let new_signing_identity = new_signing_identity
             .map(Ok)
@@ -123,15 +136,13 @@ where
         let reinit = self
             .state
             .pending_reinit
+            .clone()
             .ok_or(MlsError::PendingReInitNotFound)?;
 
-        let new_signer = match new_signer {
-            Some(signer) => signer,
-            None => self.signer,
-        };
+        let new_signer = new_signer.unwrap_or_else(|| self.signer.clone());
 
         let client = Client::new(
-            self.config,
+            self.config.clone(),
             Some(new_signer),
             Some((new_signing_identity, reinit.new_cipher_suite())),
             reinit.new_version(),
@@ -141,9 +152,15 @@ where
             client,
             reinit,
             psk_input,
+            lineage,
         })
     }
 
+    fn descendant_lineage(&self) -> GroupLineage {
+        self.lineage
+            .descendant(self.group_id(), self.current_epoch())
+    }
+
     fn resumption_psk_input(&self, usage: ResumptionPSKUsage) -> Result<PskSecretInput, MlsError> {
         let psk = self.epoch_secrets.resumption_secret.clone();
 
@@ -189,6 +206,7 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
             cipher_suite: self.reinit.new_cipher_suite(),
             version: self.reinit.new_version(),
             extensions: self.reinit.new_group_context_extensions(),
+            lineage: self.lineage,
         };
 
         resumption_create_group(
@@ -219,6 +237,7 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
             cipher_suite: reinit.new_cipher_suite(),
             version: reinit.new_version(),
             extensions: reinit.new_group_context_extensions(),
+            lineage: self.lineage,
         };
 
         resumption_join_group(
@@ -260,6 +279,7 @@ async fn resumption_create_group<C: ClientConfig + Clone>(
 
     // Install the resumption psk in the new group
     group.previous_psk = Some(psk_input);
+    group.lineage = new_group_params.lineage.clone();
 
     // Create a commit that adds new key packages and uses the resumption PSK
     let mut commit = group.commit_builder();
@@ -289,7 +309,7 @@ async fn resumption_join_group<C: ClientConfig + Clone>(
 ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
     let psk_input = Some(psk_input);
 
-    let (group, new_member_info) =
+    let (mut group, new_member_info) =
         Group::<C>::from_welcome_message(welcome, tree_data, config, signer, psk_input).await?;
 
     if group.protocol_version() != expected_new_group_params.version {
@@ -301,6 +321,7 @@ async fn resumption_join_group<C: ClientConfig + Clone>(
     } else if &group.group_state().context.extensions != expected_new_group_params.extensions {
         Err(MlsError::ReInitExtensionsMismatch)
     } else {
+        group.lineage = expected_new_group_params.lineage;
         Ok((group, new_member_info))
     }
 }
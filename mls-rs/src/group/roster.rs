@@ -4,6 +4,10 @@
 
 use super::*;
 
+use alloc::collections::BTreeMap;
+
+use crate::extension::{ApplicationIdExt, RequiredCapabilitiesExt};
+
 pub use mls_rs_core::group::Member;
 
 pub(crate) fn member_from_leaf_node(leaf_node: &LeafNode, leaf_index: LeafIndex) -> Member {
@@ -64,6 +68,114 @@ impl<'a> Roster<'a> {
             .map(|l| member_from_leaf_node(l, index))
     }
 
+    /// Retrieve the member using the given signing identity, if any.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn member_with_signing_identity(
+        &self,
+        signing_identity: &SigningIdentity,
+    ) -> Option<Member> {
+        self.public_tree
+            .non_empty_leaves()
+            .find(|(_, node)| &node.signing_identity == signing_identity)
+            .map(|(index, node)| member_from_leaf_node(node, index))
+    }
+
+    /// Members that would not support a group context with `extensions`.
+    ///
+    /// A member is reported if it lacks a capability listed in the
+    /// [`RequiredCapabilitiesExt`](crate::extension::built_in::RequiredCapabilitiesExt)
+    /// of `extensions` or does not support one of the non-default extension
+    /// types in `extensions`. A commit changing the group context extensions
+    /// to `extensions` is rejected as long as any such member remains in the
+    /// group.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn members_lacking_capabilities(
+        &self,
+        extensions: &ExtensionList,
+    ) -> Result<Vec<Member>, MlsError> {
+        let required = extensions
+            .get_as::<RequiredCapabilitiesExt>()?
+            .unwrap_or_default();
+
+        let required_extensions = extensions
+            .iter()
+            .map(|ext| ext.extension_type)
+            .filter(|ext_type| !ext_type.is_default())
+            .chain(required.extensions.iter().copied());
+
+        let incapable = self.members_iter().filter(|member| {
+            let capabilities = &member.capabilities;
+
+            required_extensions
+                .clone()
+                .any(|ext_type| !capabilities.extensions.contains(&ext_type))
+                || required
+                    .proposals
+                    .iter()
+                    .any(|p| !capabilities.proposals.contains(p))
+                || required
+                    .credentials
+                    .iter()
+                    .any(|c| !capabilities.credentials.contains(c))
+        });
+
+        Ok(incapable.collect())
+    }
+
+    /// The [`ApplicationIdExt`] in the leaf node of the member with the given
+    /// `index`, if any.
+    pub fn member_application_id(&self, index: u32) -> Result<Option<ApplicationIdExt>, MlsError> {
+        let leaf_node = self.public_tree.get_leaf_node(LeafIndex(index))?;
+
+        Ok(leaf_node.extensions.get_as::<ApplicationIdExt>()?)
+    }
+
+    /// Index and [`ApplicationIdExt`] of each member presenting an
+    /// application id, in order of index.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn application_ids(&self) -> Result<Vec<(u32, ApplicationIdExt)>, MlsError> {
+        self.public_tree
+            .non_empty_leaves()
+            .filter_map(|(index, node)| {
+                node.extensions
+                    .get_as::<ApplicationIdExt>()
+                    .map_err(Into::into)
+                    .transpose()
+                    .map(|res| res.map(|id| (*index, id)))
+            })
+            .collect()
+    }
+
+    /// Members whose leaf node presents the application id `identifier`.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn members_with_application_id(&self, identifier: &[u8]) -> Result<Vec<Member>, MlsError> {
+        self.application_ids()?
+            .into_iter()
+            .filter(|(_, id)| id.identifier == identifier)
+            .map(|(index, _)| self.member_with_index(index))
+            .collect()
+    }
+
+    /// Application ids presented by more than one member, with the indexes
+    /// of these members.
+    ///
+    /// MLS does not require application ids to be unique, but applications
+    /// using them to address a member usually do.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn duplicate_application_ids(&self) -> Result<Vec<(ApplicationIdExt, Vec<u32>)>, MlsError> {
+        let mut indexes = BTreeMap::<Vec<u8>, Vec<u32>>::new();
+
+        for (index, id) in self.application_ids()? {
+            indexes.entry(id.identifier).or_default().push(index);
+        }
+
+        Ok(indexes
+            .into_iter()
+            .filter(|(_, indexes)| indexes.len() > 1)
+            .map(|(identifier, indexes)| (ApplicationIdExt::new(identifier), indexes))
+            .collect())
+    }
+
     /// Iterator over member's signing identities.
     ///
     /// # Warning
@@ -84,3 +196,67 @@ impl TreeKemPublic {
         Roster { public_tree: self }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use crate::{
+        client::test_utils::{
+            test_client_with_key_pkg_custom, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+        },
+        extension::{ApplicationIdExt, ExtensionType},
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn roster_reports_application_ids() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut commit_builder = group.commit_builder();
+
+        for (name, id) in [("bob", b"app-1"), ("carol", b"app-2"), ("dave", b"app-1")] {
+            let leaf_node_extensions = ApplicationIdExt::new(id.to_vec())
+                .to_leaf_node_extensions()
+                .unwrap();
+
+            let (_, key_package) = test_client_with_key_pkg_custom(
+                TEST_PROTOCOL_VERSION,
+                TEST_CIPHER_SUITE,
+                name,
+                Default::default(),
+                leaf_node_extensions,
+                |c| {
+                    c.0.settings
+                        .extension_types
+                        .push(ExtensionType::APPLICATION_ID)
+                },
+            )
+            .await;
+
+            commit_builder = commit_builder.add_member(key_package).unwrap();
+        }
+
+        commit_builder.build().await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        let roster = group.roster();
+
+        assert_eq!(roster.member_application_id(0).unwrap(), None);
+
+        assert_eq!(
+            roster.member_application_id(2).unwrap(),
+            Some(ApplicationIdExt::new(b"app-2".to_vec()))
+        );
+
+        assert_eq!(roster.application_ids().unwrap().len(), 3);
+
+        let members = roster.members_with_application_id(b"app-1").unwrap();
+        let indexes = members.iter().map(|m| m.index).collect::<Vec<_>>();
+        assert_eq!(indexes, vec![1, 3]);
+
+        assert_eq!(
+            roster.duplicate_application_ids().unwrap(),
+            vec![(ApplicationIdExt::new(b"app-1".to_vec()), vec![1, 3])]
+        );
+    }
+}
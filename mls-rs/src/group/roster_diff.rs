@@ -0,0 +1,234 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::{
+    error::IntoAnyError,
+    extension::ExtensionList,
+    identity::{IdentityProvider, SigningIdentity},
+};
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+/// Member occupying a leaf in one of the epochs compared by
+/// [`Group::roster_diff`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RosterDiffMember {
+    /// Leaf index of the member.
+    pub index: u32,
+    /// Signing identity of the member.
+    pub signing_identity: SigningIdentity,
+}
+
+/// Member whose signing identity changed between the epochs compared by
+/// [`Group::roster_diff`] while its application level identity stayed the
+/// same.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RosterDiffUpdate {
+    /// Leaf index of the member.
+    pub index: u32,
+    /// Signing identity of the member in the earlier epoch.
+    pub previous: SigningIdentity,
+    /// Signing identity of the member in the later epoch.
+    pub current: SigningIdentity,
+}
+
+/// Net membership changes between two epochs of a group.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct RosterDiff {
+    /// Members present in the later epoch but not in the earlier one.
+    pub added: Vec<RosterDiffMember>,
+    /// Members present in the earlier epoch but not in the later one.
+    pub removed: Vec<RosterDiffMember>,
+    /// Members present in both epochs with a different signing identity.
+    pub updated: Vec<RosterDiffUpdate>,
+}
+
+impl RosterDiff {
+    /// Returns `true` if membership did not change between the two epochs.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Compute the net membership changes between `from_epoch` and `to_epoch`.
+    ///
+    /// Both epochs must be either the current epoch or a prior epoch that is
+    /// still retained by the group state storage. Intermediate epochs are not
+    /// consulted, so a member that was added and removed in between is not
+    /// reported. A leaf whose occupant has a different application level
+    /// identity, as returned by the [`IdentityProvider`](crate::IdentityProvider),
+    /// in the two epochs is reported as a removal followed by an addition.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::InvalidEpoch`] if `from_epoch` is after `to_epoch`
+    /// and [`MlsError::EpochNotFound`] if an epoch is not retained, or was
+    /// stored before the credentials of its members were recorded.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn roster_diff(
+        &self,
+        from_epoch: u64,
+        to_epoch: u64,
+    ) -> Result<RosterDiff, MlsError> {
+        if from_epoch > to_epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        let (from, from_extensions) = self.epoch_roster(from_epoch).await?;
+        let (to, to_extensions) = self.epoch_roster(to_epoch).await?;
+
+        let mut diff = RosterDiff::default();
+
+        for index in 0..from.len().max(to.len()) {
+            let previous = from.get(index).cloned().flatten();
+            let current = to.get(index).cloned().flatten();
+            let index = index as u32;
+
+            match (previous, current) {
+                (None, None) => {}
+                (None, Some(signing_identity)) => diff.added.push(RosterDiffMember {
+                    index,
+                    signing_identity,
+                }),
+                (Some(signing_identity), None) => diff.removed.push(RosterDiffMember {
+                    index,
+                    signing_identity,
+                }),
+                (Some(previous), Some(current)) if previous != current => {
+                    let same_member = self.identity(&previous, &from_extensions).await?
+                        == self.identity(&current, &to_extensions).await?;
+
+                    if same_member {
+                        diff.updated.push(RosterDiffUpdate {
+                            index,
+                            previous,
+                            current,
+                        });
+                    } else {
+                        diff.removed.push(RosterDiffMember {
+                            index,
+                            signing_identity: previous,
+                        });
+
+                        diff.added.push(RosterDiffMember {
+                            index,
+                            signing_identity: current,
+                        });
+                    }
+                }
+                (Some(_), Some(_)) => {}
+            }
+        }
+
+        Ok(diff)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn epoch_roster(
+        &self,
+        epoch_id: u64,
+    ) -> Result<(Vec<Option<SigningIdentity>>, ExtensionList), MlsError> {
+        if epoch_id == self.current_epoch() {
+            let leaves = self
+                .state
+                .public_tree
+                .leaves()
+                .map(|l| l.map(|n| n.signing_identity.clone()))
+                .collect();
+
+            return Ok((leaves, self.context().extensions.clone()));
+        }
+
+        let epoch = self
+            .state_repo
+            .get_epoch(epoch_id)
+            .await?
+            .ok_or(MlsError::EpochNotFound)?;
+
+        let identities = epoch.signing_identities().ok_or(MlsError::EpochNotFound)?;
+
+        Ok((identities, epoch.context.extensions))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<Vec<u8>, MlsError> {
+        self.config
+            .identity_provider()
+            .identity(signing_identity, extensions)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::client::MlsError;
+    use crate::group::test_utils::test_group;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn roster_diff_reports_net_changes_across_epochs() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let start = alice.current_epoch();
+
+        let (bob, _) = alice.join("bob").await;
+        let (carol, _) = alice.join("carol").await;
+
+        alice
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let diff = alice
+            .roster_diff(start, alice.current_epoch())
+            .await
+            .unwrap();
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].index, 2);
+        assert_eq!(
+            diff.added[0].signing_identity,
+            carol.current_member_signing_identity().unwrap().clone()
+        );
+        assert!(diff.removed.is_empty() && diff.updated.is_empty());
+
+        let diff = alice.roster_diff(start + 1, start + 3).await.unwrap();
+
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].index, 1);
+        assert_eq!(
+            diff.removed[0].signing_identity,
+            bob.current_member_signing_identity().unwrap().clone()
+        );
+        assert_eq!(diff.added.len(), 1);
+        let diff = alice.roster_diff(start, start).await.unwrap();
+        assert!(diff.is_empty());
+
+        let res = alice.roster_diff(start + 1, start).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+
+        let res = alice.roster_diff(start, start + 10).await;
+        assert_matches!(res, Err(MlsError::EpochNotFound));
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::CipherSuiteProvider, error::IntoAnyError, group::Member, identity::IdentityProvider,
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::Group,
+    tree_kem::{node::LeafIndex, TreeKemPublic},
+};
+
+/// Output format of [`Group::export_roster`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RosterExportFormat {
+    /// One header line followed by one line per member. List values are
+    /// separated by `;`.
+    Csv,
+    /// An array containing one object per member.
+    Json,
+}
+
+struct RosterEntry {
+    leaf_index: u32,
+    identity: String,
+    credential_type: u16,
+    credential_fingerprint: String,
+    cipher_suites: Vec<u16>,
+    extensions: Vec<u16>,
+    proposals: Vec<u16>,
+    credentials: Vec<u16>,
+    last_update_epoch: Option<u64>,
+}
+
+/// Epoch in which each leaf was last added or updated, as observed by the
+/// local member. This is local bookkeeping and not part of the group state
+/// agreed upon by members.
+#[derive(Clone, Debug, Default, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct LeafUpdateEpochs(Vec<Option<u64>>);
+
+impl LeafUpdateEpochs {
+    pub(crate) fn new(self_index: LeafIndex, epoch: u64) -> Self {
+        let mut epochs = Self::default();
+        epochs.set(self_index, epoch);
+        epochs
+    }
+
+    pub(crate) fn get(&self, index: LeafIndex) -> Option<u64> {
+        self.0.get(*index as usize).copied().flatten()
+    }
+
+    /// Record `epoch` for every leaf of `new_tree` that differs from the leaf
+    /// at the same index in `old_tree`.
+    pub(crate) fn record(
+        &mut self,
+        old_tree: &TreeKemPublic,
+        new_tree: &TreeKemPublic,
+        epoch: u64,
+    ) {
+        let old_leaves = old_tree.leaves().chain(core::iter::repeat(None));
+
+        let changes = new_tree
+            .leaves()
+            .zip(old_leaves)
+            .enumerate()
+            .map(|(i, (new, old))| {
+                let changed = new.map(|n| &n.signature) != old.map(|o| &o.signature);
+                (LeafIndex(i as u32), new.is_some(), changed)
+            })
+            .collect::<Vec<_>>();
+
+        self.0.truncate(changes.len());
+
+        for (index, occupied, changed) in changes {
+            if !occupied {
+                self.clear(index);
+            } else if changed {
+                self.set(index, epoch);
+            }
+        }
+    }
+
+    fn set(&mut self, index: LeafIndex, epoch: u64) {
+        let i = *index as usize;
+
+        if self.0.len() <= i {
+            self.0.resize(i + 1, None);
+        }
+
+        self.0[i] = Some(epoch);
+    }
+
+    fn clear(&mut self, index: LeafIndex) {
+        if let Some(epoch) = self.0.get_mut(*index as usize) {
+            *epoch = None;
+        }
+    }
+}
+
+const CSV_HEADER: &str = "leaf_index,identity,credential_type,credential_fingerprint,\
+cipher_suites,extensions,proposals,credentials,last_update_epoch";
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Export a machine-readable membership report for access reviews.
+    ///
+    /// Each member is reported with its leaf index, its application level
+    /// identity as returned by the
+    /// [`IdentityProvider`](crate::IdentityProvider), the hash of its
+    /// credential using the group cipher suite, its capabilities and the
+    /// epoch in which its leaf was last added or updated. The last update
+    /// epoch is omitted for changes that happened before the local member
+    /// joined the group. Binary values are hex encoded.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_roster(&self, format: RosterExportFormat) -> Result<String, MlsError> {
+        let mut entries = Vec::new();
+
+        for member in self.roster().members_iter() {
+            entries.push(self.roster_entry(member).await?);
+        }
+
+        Ok(match format {
+            RosterExportFormat::Csv => csv(&entries),
+            RosterExportFormat::Json => json(&entries),
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn roster_entry(&self, member: Member) -> Result<RosterEntry, MlsError> {
+        let identity = self
+            .config
+            .identity_provider()
+            .identity(&member.signing_identity, &self.context().extensions)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        let credential = &member.signing_identity.credential;
+
+        let credential_fingerprint = self
+            .cipher_suite_provider
+            .hash(&credential.mls_encode_to_vec()?)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let capabilities = &member.capabilities;
+
+        Ok(RosterEntry {
+            leaf_index: member.index,
+            identity: hex(&identity),
+            credential_type: *credential.credential_type(),
+            credential_fingerprint: hex(&credential_fingerprint),
+            cipher_suites: capabilities.cipher_suites.iter().map(|c| **c).collect(),
+            extensions: capabilities.extensions.iter().map(|e| **e).collect(),
+            proposals: capabilities.proposals.iter().map(|p| **p).collect(),
+            credentials: capabilities.credentials.iter().map(|c| **c).collect(),
+            last_update_epoch: self.leaf_update_epochs.get(LeafIndex(member.index)),
+        })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!(
+        "{:?}",
+        mls_rs_core::debug::pretty_bytes(bytes)
+            .show_len(false)
+            .show_raw(true)
+    )
+}
+
+fn join(values: &[u16], separator: &str) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn csv(entries: &[RosterEntry]) -> String {
+    let mut out = String::from(CSV_HEADER);
+
+    for e in entries {
+        let last_update_epoch = e
+            .last_update_epoch
+            .map(|epoch| epoch.to_string())
+            .unwrap_or_default();
+
+        // Writing to a String can not fail
+        let _ = write!(
+            out,
+            "\n{},{},{},{},{},{},{},{},{}",
+            e.leaf_index,
+            e.identity,
+            e.credential_type,
+            e.credential_fingerprint,
+            join(&e.cipher_suites, ";"),
+            join(&e.extensions, ";"),
+            join(&e.proposals, ";"),
+            join(&e.credentials, ";"),
+            last_update_epoch,
+        );
+    }
+
+    out
+}
+
+fn json(entries: &[RosterEntry]) -> String {
+    let members = entries
+        .iter()
+        .map(|e| {
+            let last_update_epoch = e
+                .last_update_epoch
+                .map(|epoch| epoch.to_string())
+                .unwrap_or_else(|| "null".to_string());
+
+            format!(
+                "{{\"leaf_index\":{},\"identity\":\"{}\",\"credential_type\":{},\
+                \"credential_fingerprint\":\"{}\",\"capabilities\":{{\"cipher_suites\":[{}],\
+                \"extensions\":[{}],\"proposals\":[{}],\"credentials\":[{}]}},\
+                \"last_update_epoch\":{}}}",
+                e.leaf_index,
+                e.identity,
+                e.credential_type,
+                e.credential_fingerprint,
+                join(&e.cipher_suites, ","),
+                join(&e.extensions, ","),
+                join(&e.proposals, ","),
+                join(&e.credentials, ","),
+                last_update_epoch,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("[{}]", members.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::group::test_utils::test_group;
+
+    use super::RosterExportFormat;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn roster_export_reports_members_and_update_epochs() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = bob.commit(vec![]).await.unwrap().commit_message;
+        bob.apply_pending_commit().await.unwrap();
+        alice.process_incoming_message(commit).await.unwrap();
+
+        let csv = alice.export_roster(RosterExportFormat::Csv).await.unwrap();
+        let lines = csv.lines().collect::<vec::Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("leaf_index,identity,"));
+        assert!(lines[1].starts_with("0,") && lines[1].ends_with(",0"));
+        assert!(lines[2].starts_with("1,") && lines[2].ends_with(",2"));
+
+        // Bob joined after alice's leaf was created, so its epoch is unknown to him
+        let json = bob.export_roster(RosterExportFormat::Json).await.unwrap();
+
+        assert!(json.starts_with("[{\"leaf_index\":0,"));
+        assert!(json.contains("\"last_update_epoch\":null}"));
+        assert!(json.ends_with("\"last_update_epoch\":2}]"));
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display};
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+use super::key_schedule::kdf_expand_with_label;
+
+const SAFETY_CODE_LABEL: &[u8] = b"safety code";
+
+/// Largest number of groups of a [`SafetyCode`].
+pub const MAX_SAFETY_CODE_LENGTH: usize = 16;
+
+/// Bytes of the epoch authenticator encoded in one group of digits.
+const BYTES_PER_DIGIT_GROUP: usize = 5;
+
+/// How a [`SafetyCode`] is shown to users.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SafetyCodeEncoding {
+    /// Groups of 5 decimal digits. Each group carries about 16 bits.
+    #[default]
+    Digits,
+    /// English words from a list of 256 words. Each word carries 8 bits.
+    Words,
+}
+
+/// Length and encoding of a [`SafetyCode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SafetyCodeConfig {
+    length: usize,
+    encoding: SafetyCodeEncoding,
+}
+
+impl Default for SafetyCodeConfig {
+    fn default() -> Self {
+        Self {
+            length: 6,
+            encoding: SafetyCodeEncoding::Digits,
+        }
+    }
+}
+
+impl SafetyCodeConfig {
+    /// Six groups of 5 digits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of groups of digits or words, between 1 and
+    /// [`MAX_SAFETY_CODE_LENGTH`]. Other values are clamped to that range.
+    ///
+    /// A shorter code is a prefix of a longer code with the same encoding,
+    /// so members may compare codes of different lengths.
+    pub fn with_length(self, length: usize) -> Self {
+        Self {
+            length: length.clamp(1, MAX_SAFETY_CODE_LENGTH),
+            ..self
+        }
+    }
+
+    pub fn with_encoding(self, encoding: SafetyCodeEncoding) -> Self {
+        Self { encoding, ..self }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn encoding(&self) -> SafetyCodeEncoding {
+        self.encoding
+    }
+}
+
+/// Short human comparable encoding of the epoch authenticator, created with
+/// [`Group::safety_code`].
+///
+/// All members of a group compute the same code within an epoch, and the code
+/// changes with every epoch. Members reading each other their code, in person
+/// or over a trusted channel, confirm that they share the same group state
+/// and that no one was able to split the group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SafetyCode {
+    epoch: u64,
+    groups: Vec<String>,
+}
+
+impl SafetyCode {
+    /// Epoch the code was computed for. Codes of different epochs never match.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Groups of digits or words making up the code.
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+}
+
+impl Display for SafetyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.groups.join(" "))
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Compute a short encoding of the
+    /// [epoch authenticator](Group::epoch_authenticator) of the current epoch
+    /// for users to compare, as in "verify safety number" flows.
+    ///
+    /// The code is derived from the epoch authenticator rather than
+    /// truncating it, so revealing it does not reveal the authenticator.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn safety_code(&self, config: &SafetyCodeConfig) -> Result<SafetyCode, MlsError> {
+        // Always derive the bytes of the longest code so that codes of any
+        // length are prefixes of each other.
+        let bytes = kdf_expand_with_label(
+            &self.cipher_suite_provider,
+            &self.key_schedule.authentication_secret,
+            SAFETY_CODE_LABEL,
+            &[],
+            Some(MAX_SAFETY_CODE_LENGTH * BYTES_PER_DIGIT_GROUP),
+        )
+        .await?;
+
+        let groups = match config.encoding {
+            SafetyCodeEncoding::Digits => bytes
+                .chunks(BYTES_PER_DIGIT_GROUP)
+                .map(|chunk| {
+                    let value = chunk.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+                    format!("{:05}", value % 100_000)
+                })
+                .take(config.length)
+                .collect(),
+            SafetyCodeEncoding::Words => bytes
+                .iter()
+                .map(|b| WORDS[*b as usize].to_string())
+                .take(config.length)
+                .collect(),
+        };
+
+        Ok(SafetyCode {
+            epoch: self.current_epoch(),
+            groups,
+        })
+    }
+}
+
+const WORDS: [&str; 256] = [
+    "acid", "acorn", "actor", "adult", "agent", "alarm", "album", "alley", "amber", "angle",
+    "ankle", "apple", "apron", "arena", "armor", "arrow", "atlas", "attic", "audio", "award",
+    "bacon", "badge", "bagel", "baker", "bamboo", "banjo", "barn", "basil", "basket", "beach",
+    "beard", "beaver", "bell", "bench", "berry", "bison", "blade", "blanket", "blaze", "bloom",
+    "board", "boat", "bonus", "boot", "bottle", "bowl", "brain", "brass", "bread", "brick",
+    "bridge", "broom", "brush", "bubble", "bucket", "cabin", "cable", "cactus", "camel", "candle",
+    "canoe", "canyon", "carbon", "carpet", "castle", "cedar", "cello", "chalk", "chart", "cheese",
+    "cherry", "chess", "chimney", "cinema", "circus", "clay", "cliff", "clock", "cloud", "clover",
+    "coach", "cobra", "cocoa", "comet", "copper", "coral", "cotton", "cradle", "crane", "crayon",
+    "cricket", "crown", "crystal", "cube", "cycle", "daisy", "dance", "delta", "desert", "diamond",
+    "dinner", "disk", "dock", "dolphin", "donkey", "dragon", "drawer", "dream", "drum", "eagle",
+    "earth", "easel", "echo", "elbow", "ember", "engine", "falcon", "feather", "fence", "fern",
+    "ferry", "flag", "flame", "flute", "forest", "fossil", "fountain", "fox", "frog", "galaxy",
+    "garden", "garlic", "geyser", "ginger", "glacier", "globe", "glove", "goat", "grape", "gravel",
+    "guitar", "hammer", "harbor", "harp", "hazel", "helmet", "heron", "hill", "honey", "horizon",
+    "horse", "igloo", "iris", "island", "ivory", "jacket", "jaguar", "jelly", "jewel", "jungle",
+    "kayak", "kettle", "kiwi", "koala", "ladder", "lagoon", "lamp", "lantern", "laser", "lemon",
+    "lens", "lily", "lion", "lizard", "lobster", "locket", "lotus", "magnet", "mango", "maple",
+    "marble", "meadow", "melon", "meteor", "mint", "mirror", "moose", "mosaic", "motor",
+    "mountain", "muffin", "mural", "nectar", "needle", "nest", "noodle", "oasis", "ocean", "olive",
+    "onion", "opal", "orbit", "orchid", "otter", "owl", "paddle", "palm", "panda", "paper",
+    "parrot", "peach", "pearl", "pebble", "pencil", "pepper", "piano", "pillow", "pine", "planet",
+    "plum", "pocket", "pony", "poppy", "prism", "pumpkin", "puzzle", "quartz", "rabbit", "radar",
+    "radio", "rain", "raven", "reef", "ribbon", "river", "robin", "rocket", "rose", "ruby",
+    "saddle", "salmon", "sand", "scarf", "shell", "silver", "sketch", "snail", "spruce", "squid",
+    "statue", "stone", "sugar", "summit", "swan", "table", "tiger",
+];
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    use super::{SafetyCodeConfig, SafetyCodeEncoding, MAX_SAFETY_CODE_LENGTH, WORDS};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_share_safety_code_within_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let config = SafetyCodeConfig::new();
+        let code = alice.safety_code(&config).await.unwrap();
+
+        let bob_code = bob.safety_code(&config).await.unwrap();
+
+        assert_eq!(code, bob_code);
+        assert_eq!(code.epoch(), alice.current_epoch());
+        assert_eq!(code.groups().len(), 6);
+        assert_eq!(code.to_string().len(), 6 * 5 + 5);
+
+        assert!(code
+            .groups()
+            .iter()
+            .all(|g| g.len() == 5 && g.chars().all(|c| c.is_ascii_digit())));
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        let next_code = alice.safety_code(&config).await.unwrap();
+
+        let bob_code = bob.safety_code(&config).await.unwrap();
+
+        assert_ne!(next_code, code);
+        assert_eq!(next_code, bob_code);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn shorter_safety_codes_are_prefixes() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        for encoding in [SafetyCodeEncoding::Digits, SafetyCodeEncoding::Words] {
+            let config = SafetyCodeConfig::new().with_encoding(encoding);
+
+            let long = alice
+                .safety_code(&config.with_length(usize::MAX))
+                .await
+                .unwrap();
+
+            let short = alice.safety_code(&config.with_length(3)).await.unwrap();
+
+            assert_eq!(long.groups().len(), MAX_SAFETY_CODE_LENGTH);
+            assert_eq!(short.groups(), &long.groups()[..3]);
+        }
+
+        let config = SafetyCodeConfig::new().with_encoding(SafetyCodeEncoding::Words);
+        let code = alice.safety_code(&config).await.unwrap();
+
+        assert!(code.groups().iter().all(|w| WORDS.contains(&w.as_str())));
+    }
+}
@@ -13,15 +13,147 @@ use zeroize::Zeroizing;
 use crate::{client::MlsError, map::LargeMap, tree_kem::math::TreeIndex, CipherSuiteProvider};
 
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
-use mls_rs_core::error::IntoAnyError;
+use mls_rs_core::{
+    error::IntoAnyError,
+    extension::{ExtensionType, MlsCodecExtension},
+};
 
-use super::key_schedule::kdf_expand_with_label;
+use super::{key_schedule::kdf_expand_with_label, GroupContext};
 
 pub(crate) const MAX_RATCHET_BACK_HISTORY: u32 = 1024;
 
+/// Retention of message keys derived by the secret tree.
+///
+/// The retention windows of each setting are the following:
+///
+/// | Key | [`Standard`](KeyRetention::Standard) | [`Strict`](KeyRetention::Strict) |
+/// |-----|----------|--------|
+/// | Key used to decrypt a message | Deleted | Deleted |
+/// | Key of a skipped message (requires the `out_of_order` feature) | Kept until used or until the epoch is deleted, at most 1024 per sender | Deleted |
+/// | Secret tree of a prior epoch (requires the `prior_epoch` feature) | Kept as long as the epoch is retained by the group state storage | Deleted on epoch change |
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum KeyRetention {
+    /// Keep keys of skipped and prior epoch messages so that messages
+    /// received out of order can be decrypted.
+    #[default]
+    Standard,
+    /// Delete every key as soon as it can no longer be used for an in-order
+    /// message of the current epoch, providing maximum forward secrecy.
+    /// Messages received out of order or from a prior epoch are rejected.
+    ///
+    /// In builds with debug assertions enabled, deletion is verified after each
+    /// decryption by attempting to derive the consumed key again.
+    Strict,
+}
+
+/// Storage of the message keys skipped when a message is received ahead of
+/// the next expected generation of its sender.
+///
+/// Skipped keys are only stored with [`KeyRetention::Standard`] and the
+/// `out_of_order` feature. Since the ratchet of each sender is a hash chain,
+/// catching up to a generation takes one derivation per skipped generation;
+/// this setting trades memory for computation and forward secrecy when
+/// keeping the skipped keys. Groups using the [`TreeRatchetExt`] group context
+/// extension catch up in a logarithmic number of derivations and ignore this
+/// setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SkippedKeyStorage {
+    /// Store the key of every skipped generation. Messages at most 1024
+    /// generations ahead of the next expected one are accepted.
+    #[default]
+    Keys,
+    /// Store the ratchet secret once every `interval` skipped generations.
+    ///
+    /// A stored secret takes the memory of about one key and is used to
+    /// derive the key of a skipped generation in at most `interval` steps, so
+    /// larger intervals allow accepting messages further ahead, up to
+    /// `max_generation_gap` generations, with bounded memory. In exchange, a
+    /// stored secret can derive the keys of all later generations of its
+    /// interval, including consumed ones, until every earlier generation of
+    /// the interval is consumed or the epoch is deleted.
+    Checkpoints {
+        interval: u32,
+        max_generation_gap: u32,
+    },
+}
+
+impl SkippedKeyStorage {
+    fn max_generation_gap(&self) -> u32 {
+        match self {
+            SkippedKeyStorage::Keys => MAX_RATCHET_BACK_HISTORY,
+            SkippedKeyStorage::Checkpoints {
+                max_generation_gap, ..
+            } => *max_generation_gap,
+        }
+    }
+}
+
+/// Group context extension replacing the hash chain ratchet of each sender
+/// with a tree-structured derivation.
+///
+/// The secrets of the generations of a ratchet are the leaves of a binary
+/// tree of depth 32 rooted at the ratchet secret, where each child secret is
+/// derived from its parent. Deriving the key of any generation takes at most
+/// 32 derivations, so that a member can catch up to a generation far ahead,
+/// for example when rejoining a busy broadcast group, without limiting the
+/// generation gap.
+///
+/// A ratchet keeps the secrets of the subtrees covering the generations that
+/// were not consumed yet, at most 32 of them for the generations following
+/// the last consumed one. With [`KeyRetention::Standard`] and the
+/// `out_of_order` feature, the subtrees covering skipped generations are kept
+/// as well, at most 32 per received message. Unlike
+/// [`SkippedKeyStorage::Checkpoints`], a kept secret never derives the key of
+/// a consumed generation, but it derives the keys of every skipped generation
+/// it covers, so a compromise of the group state reveals the keys of all
+/// messages that were skipped and not yet received, however many.
+///
+/// This derivation is not defined by RFC 9420. The extension type must be
+/// listed in the capabilities of every member, for example with
+/// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type),
+/// and applies from the epoch in which the extension is set.
+#[derive(Debug, Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct TreeRatchetExt;
+
+impl TreeRatchetExt {
+    /// Extension type of the tree ratchet extension, from the private use
+    /// range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A3);
+}
+
+impl MlsCodecExtension for TreeRatchetExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
+/// Derivation of the generations of the ratchets of an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum RatchetDerivation {
+    #[default]
+    HashChain,
+    Tree,
+}
+
+impl RatchetDerivation {
+    pub(crate) fn of(group_context: &GroupContext) -> Self {
+        if group_context
+            .extensions
+            .has_extension(TreeRatchetExt::EXTENSION_TYPE)
+        {
+            RatchetDerivation::Tree
+        } else {
+            RatchetDerivation::HashChain
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
+#[allow(clippy::large_enum_variant)]
 enum SecretTreeNode {
     Secret(TreeSecret) = 0u8,
     Ratchet(SecretRatchets) = 1u8,
@@ -115,6 +247,54 @@ impl<T: TreeIndex> SecretTree<T> {
             leaf_count: T::zero(),
         }
     }
+
+    #[cfg(feature = "invariant_checks")]
+    pub(crate) fn leaf_count(&self) -> &T {
+        &self.leaf_count
+    }
+
+    /// Checkpoints and subtrees of the ratchets, which are not part of the
+    /// encoding of the tree.
+    pub(crate) fn ratchet_states(&self) -> RatchetStates<T> {
+        let mut states = self
+            .known_secrets
+            .inner
+            .iter()
+            .filter_map(|(node, secret)| match secret {
+                SecretTreeNode::Ratchet(ratchets) => Some(NodeRatchetState {
+                    node: node.clone(),
+                    application: ratchets.application.state(),
+                    handshake: ratchets.handshake.state(),
+                }),
+                SecretTreeNode::Secret(_) => None,
+            })
+            .filter(|state| !state.application.is_empty() || !state.handshake.is_empty())
+            .collect::<Vec<_>>();
+
+        states.sort_by(|a, b| a.node.cmp(&b.node));
+
+        RatchetStates(states)
+    }
+
+    /// Restore the checkpoints and subtrees returned by
+    /// [`ratchet_states`](Self::ratchet_states) into a decoded tree.
+    pub(crate) fn set_ratchet_states(
+        &mut self,
+        states: RatchetStates<T>,
+    ) -> Result<(), mls_rs_codec::Error> {
+        for state in states.0 {
+            let Some(SecretTreeNode::Ratchet(ratchets)) =
+                self.known_secrets.inner.get_mut(&state.node)
+            else {
+                return Err(mls_rs_codec::Error::Custom(6));
+            };
+
+            ratchets.application.set_state(state.application);
+            ratchets.handshake.set_state(state.handshake);
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
@@ -131,18 +311,23 @@ impl SecretRatchets {
         cipher_suite_provider: &P,
         generation: u32,
         key_type: KeyType,
+        retention: KeyRetention,
+        skipped_keys: SkippedKeyStorage,
     ) -> Result<MessageKeyData, MlsError> {
-        match key_type {
-            KeyType::Handshake => {
-                self.handshake
-                    .get_message_key(cipher_suite_provider, generation)
-                    .await
-            }
-            KeyType::Application => {
-                self.application
-                    .get_message_key(cipher_suite_provider, generation)
-                    .await
-            }
+        let ratchet = match key_type {
+            KeyType::Handshake => &mut self.handshake,
+            KeyType::Application => &mut self.application,
+        };
+
+        ratchet
+            .get_message_key(cipher_suite_provider, generation, retention, skipped_keys)
+            .await
+    }
+
+    fn use_derivation(&mut self, derivation: RatchetDerivation) {
+        if derivation == RatchetDerivation::Tree {
+            self.application.use_tree_derivation();
+            self.handshake.use_tree_derivation();
         }
     }
 
@@ -172,6 +357,17 @@ impl<T: TreeIndex> SecretTree<T> {
         }
     }
 
+    #[cfg(any(test, feature = "test_vectors"))]
+    pub(crate) fn get_root_secret(&self) -> Vec<u8> {
+        self.known_secrets
+            .clone()
+            .take_node(&self.leaf_count.root())
+            .unwrap()
+            .into_secret()
+            .unwrap()
+            .to_vec()
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn consume_node<P: CipherSuiteProvider>(
         &mut self,
@@ -240,8 +436,11 @@ impl<T: TreeIndex> SecretTree<T> {
         cipher_suite: &P,
         leaf_index: T,
         key_type: KeyType,
+        derivation: RatchetDerivation,
     ) -> Result<MessageKeyData, MlsError> {
         let mut ratchet = self.take_leaf_ratchet(cipher_suite, &leaf_index).await?;
+        ratchet.use_derivation(derivation);
+
         let res = ratchet.next_message_key(cipher_suite, key_type).await?;
 
         self.known_secrets
@@ -250,6 +449,7 @@ impl<T: TreeIndex> SecretTree<T> {
         Ok(res)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn message_key_generation<P: CipherSuiteProvider>(
         &mut self,
@@ -257,11 +457,15 @@ impl<T: TreeIndex> SecretTree<T> {
         leaf_index: T,
         key_type: KeyType,
         generation: u32,
+        retention: KeyRetention,
+        skipped_keys: SkippedKeyStorage,
+        derivation: RatchetDerivation,
     ) -> Result<MessageKeyData, MlsError> {
         let mut ratchet = self.take_leaf_ratchet(cipher_suite, &leaf_index).await?;
+        ratchet.use_derivation(derivation);
 
         let res = ratchet
-            .message_key_generation(cipher_suite, generation, key_type)
+            .message_key_generation(cipher_suite, generation, key_type, retention, skipped_keys)
             .await?;
 
         self.known_secrets
@@ -271,7 +475,8 @@ impl<T: TreeIndex> SecretTree<T> {
     }
 }
 
-#[derive(Clone, Copy)]
+/// Type of content protected by a message key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum KeyType {
     Handshake,
     Application,
@@ -325,6 +530,99 @@ impl MessageKeyData {
     }
 }
 
+/// Ratchet secret of the first of `len` consecutive skipped generations.
+#[cfg(feature = "out_of_order")]
+#[derive(Debug, Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct RatchetCheckpoint {
+    secret: TreeSecret,
+    generation: u32,
+    len: u32,
+}
+
+/// Secret of the generations `start..start + 2^level` of a ratchet using
+/// [`RatchetDerivation::Tree`].
+#[derive(Debug, Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct RatchetSubtree {
+    secret: TreeSecret,
+    start: u32,
+    level: u8,
+}
+
+impl RatchetSubtree {
+    const ROOT_LEVEL: u8 = 32;
+
+    fn contains(&self, generation: u32) -> bool {
+        generation >= self.start && u64::from(generation - self.start) < 1u64 << self.level
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn child<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        right: bool,
+    ) -> Result<Self, MlsError> {
+        let level = self.level - 1;
+
+        let (context, start) = match right {
+            false => (b"left".as_slice(), self.start),
+            true => (b"right".as_slice(), self.start + (1 << level)),
+        };
+
+        let secret = kdf_expand_with_label(
+            cipher_suite_provider,
+            &self.secret,
+            b"ratchet",
+            context,
+            None,
+        )
+        .await
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        Ok(Self {
+            secret: secret.into(),
+            start,
+            level,
+        })
+    }
+}
+
+/// Checkpoints and subtrees of a [`SecretKeyRatchet`].
+#[derive(Debug, Clone, PartialEq, Default, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct RatchetState {
+    #[cfg(feature = "out_of_order")]
+    checkpoints: Vec<RatchetCheckpoint>,
+    subtrees: Vec<RatchetSubtree>,
+}
+
+impl RatchetState {
+    fn is_empty(&self) -> bool {
+        #[cfg(feature = "out_of_order")]
+        if !self.checkpoints.is_empty() {
+            return false;
+        }
+
+        self.subtrees.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct NodeRatchetState<T: TreeIndex> {
+    node: T,
+    application: RatchetState,
+    handshake: RatchetState,
+}
+
+/// Checkpoints and subtrees of the ratchets of a [`SecretTree`], stored after
+/// the tree as they were added to the ratchets after the layout of the tree
+/// was fixed.
+#[derive(Debug, Clone, PartialEq, Default, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct RatchetStates<T: TreeIndex>(Vec<NodeRatchetState<T>>);
+
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SecretKeyRatchet {
@@ -332,8 +630,18 @@ pub struct SecretKeyRatchet {
     generation: u32,
     #[cfg(feature = "out_of_order")]
     history: LargeMap<u32, MessageKeyData>,
+    /// Sorted by generation, with disjoint generation ranges.
+    #[cfg(feature = "out_of_order")]
+    checkpoints: Vec<RatchetCheckpoint>,
+    /// Sorted by generation, with disjoint generation ranges. Empty unless
+    /// the ratchet uses [`RatchetDerivation::Tree`], in which case `secret`
+    /// is empty.
+    subtrees: Vec<RatchetSubtree>,
 }
 
+// The checkpoints and subtrees are not part of the encoding of a ratchet, so
+// that group states and prior epochs written before they were added can
+// still be read. They are stored separately as `RatchetStates`.
 impl MlsSize for SecretKeyRatchet {
     fn mls_encoded_len(&self) -> usize {
         let len = mls_rs_codec::byte_vec::mls_encoded_len(&self.secret)
@@ -379,6 +687,9 @@ impl MlsDecode for SecretKeyRatchet {
 
                 Ok(items)
             })?,
+            #[cfg(feature = "out_of_order")]
+            checkpoints: Vec::new(),
+            subtrees: Vec::new(),
         })
     }
 }
@@ -399,12 +710,50 @@ impl SecretKeyRatchet {
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-        Ok(Self {
-            secret: TreeSecret::from(secret),
-            generation: 0,
+        Ok(Self::from_secret(TreeSecret::from(secret), 0))
+    }
+
+    fn from_secret(secret: TreeSecret, generation: u32) -> Self {
+        Self {
+            secret,
+            generation,
             #[cfg(feature = "out_of_order")]
             history: Default::default(),
-        })
+            #[cfg(feature = "out_of_order")]
+            checkpoints: Vec::new(),
+            subtrees: Vec::new(),
+        }
+    }
+
+    fn state(&self) -> RatchetState {
+        RatchetState {
+            #[cfg(feature = "out_of_order")]
+            checkpoints: self.checkpoints.clone(),
+            subtrees: self.subtrees.clone(),
+        }
+    }
+
+    fn set_state(&mut self, state: RatchetState) {
+        #[cfg(feature = "out_of_order")]
+        {
+            self.checkpoints = state.checkpoints;
+        }
+
+        self.subtrees = state.subtrees;
+    }
+
+    /// Switch a ratchet that was not used yet to
+    /// [`RatchetDerivation::Tree`], with its secret as the root of the tree.
+    fn use_tree_derivation(&mut self) {
+        if self.generation == 0 && self.subtrees.is_empty() {
+            let secret = core::mem::replace(&mut self.secret, TreeSecret::from(Vec::new()));
+
+            self.subtrees.push(RatchetSubtree {
+                secret,
+                start: 0,
+                level: RatchetSubtree::ROOT_LEVEL,
+            });
+        }
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -412,14 +761,37 @@ impl SecretKeyRatchet {
         &mut self,
         cipher_suite_provider: &P,
         generation: u32,
+        retention: KeyRetention,
+        skipped_keys: SkippedKeyStorage,
     ) -> Result<MessageKeyData, MlsError> {
+        #[cfg(not(feature = "out_of_order"))]
+        let _ = retention;
+
+        if !self.subtrees.is_empty() {
+            #[cfg(feature = "out_of_order")]
+            let keep_skipped = retention == KeyRetention::Standard;
+
+            #[cfg(not(feature = "out_of_order"))]
+            let keep_skipped = false;
+
+            if generation < self.generation && !keep_skipped {
+                return Err(MlsError::KeyMissing(generation));
+            }
+
+            return self
+                .tree_message_key(cipher_suite_provider, generation, keep_skipped)
+                .await;
+        }
+
         #[cfg(feature = "out_of_order")]
         if generation < self.generation {
-            return self
-                .history
-                .remove_entry(&generation)
-                .map(|(_, mk)| mk)
-                .ok_or(MlsError::KeyMissing(generation));
+            return match self.history.remove_entry(&generation) {
+                Some((_, mk)) => Ok(mk),
+                None => {
+                    self.checkpoint_message_key(cipher_suite_provider, generation)
+                        .await
+                }
+            };
         }
 
         #[cfg(not(feature = "out_of_order"))]
@@ -427,7 +799,9 @@ impl SecretKeyRatchet {
             return Err(MlsError::KeyMissing(generation));
         }
 
-        let max_generation_allowed = self.generation + MAX_RATCHET_BACK_HISTORY;
+        let max_generation_allowed = self
+            .generation
+            .saturating_add(skipped_keys.max_generation_gap());
 
         if generation > max_generation_allowed {
             return Err(MlsError::InvalidFutureGeneration(generation));
@@ -440,17 +814,146 @@ impl SecretKeyRatchet {
 
         #[cfg(feature = "out_of_order")]
         while self.generation < generation {
-            let key_data = self.next_message_key(cipher_suite_provider).await?;
-            self.history.insert(key_data.generation, key_data);
+            match (retention, skipped_keys) {
+                (KeyRetention::Strict, _) => self.ratchet_secret(cipher_suite_provider).await?,
+                (KeyRetention::Standard, SkippedKeyStorage::Keys) => {
+                    let key_data = self.next_message_key(cipher_suite_provider).await?;
+                    self.history.insert(key_data.generation, key_data);
+                }
+                (KeyRetention::Standard, SkippedKeyStorage::Checkpoints { interval, .. }) => {
+                    let len = interval.clamp(1, generation - self.generation);
+
+                    self.checkpoints.push(RatchetCheckpoint {
+                        secret: self.secret.clone(),
+                        generation: self.generation,
+                        len,
+                    });
+
+                    for _ in 0..len {
+                        self.ratchet_secret(cipher_suite_provider).await?;
+                    }
+                }
+            }
         }
 
         self.next_message_key(cipher_suite_provider).await
     }
 
+    /// Derive the key of a skipped generation from the checkpoint covering
+    /// it, splitting the checkpoint around the consumed generation.
+    #[cfg(feature = "out_of_order")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn checkpoint_message_key<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        generation: u32,
+    ) -> Result<MessageKeyData, MlsError> {
+        let index = self
+            .checkpoints
+            .partition_point(|c| c.generation <= generation)
+            .checked_sub(1)
+            .filter(|&i| generation - self.checkpoints[i].generation < self.checkpoints[i].len)
+            .ok_or(MlsError::KeyMissing(generation))?;
+
+        let checkpoint = &mut self.checkpoints[index];
+        let end = checkpoint.generation + checkpoint.len;
+
+        let mut ratchet =
+            SecretKeyRatchet::from_secret(checkpoint.secret.clone(), checkpoint.generation);
+
+        while ratchet.generation < generation {
+            ratchet.ratchet_secret(cipher_suite_provider).await?;
+        }
+
+        let key = ratchet.next_message_key(cipher_suite_provider).await?;
+
+        checkpoint.len = generation - checkpoint.generation;
+
+        let mut next_index = index + 1;
+
+        if checkpoint.len == 0 {
+            self.checkpoints.remove(index);
+            next_index = index;
+        }
+
+        if ratchet.generation < end {
+            let checkpoint = RatchetCheckpoint {
+                len: end - ratchet.generation,
+                secret: ratchet.secret,
+                generation: ratchet.generation,
+            };
+
+            self.checkpoints.insert(next_index, checkpoint);
+        }
+
+        Ok(key)
+    }
+
+    /// Derive the key of `generation` from the subtree covering it, keeping
+    /// the subtrees covering the other generations of that subtree. Subtrees
+    /// covering skipped generations are only kept if `keep_skipped` is true.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn tree_message_key<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        generation: u32,
+        keep_skipped: bool,
+    ) -> Result<MessageKeyData, MlsError> {
+        let index = self
+            .subtrees
+            .partition_point(|s| s.start <= generation)
+            .checked_sub(1)
+            .filter(|&i| self.subtrees[i].contains(generation))
+            .ok_or(MlsError::KeyMissing(generation))?;
+
+        let mut subtree = self.subtrees.remove(index);
+
+        while subtree.level > 0 {
+            let left = subtree.child(cipher_suite_provider, false).await?;
+            let right = subtree.child(cipher_suite_provider, true).await?;
+
+            let (next, sibling) = match left.contains(generation) {
+                true => (left, Some(right)),
+                false => (right, keep_skipped.then_some(left)),
+            };
+
+            if let Some(sibling) = sibling {
+                let index = self.subtrees.partition_point(|s| s.start < sibling.start);
+                self.subtrees.insert(index, sibling);
+            }
+
+            subtree = next;
+        }
+
+        self.generation = self.generation.max(generation.saturating_add(1));
+
+        SecretKeyRatchet::from_secret(subtree.secret, generation)
+            .message_key(cipher_suite_provider)
+            .await
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn next_message_key<P: CipherSuiteProvider>(
         &mut self,
         cipher_suite_provider: &P,
+    ) -> Result<MessageKeyData, MlsError> {
+        if !self.subtrees.is_empty() {
+            return self
+                .tree_message_key(cipher_suite_provider, self.generation, false)
+                .await;
+        }
+
+        let key = self.message_key(cipher_suite_provider).await?;
+        self.ratchet_secret(cipher_suite_provider).await?;
+
+        Ok(key)
+    }
+
+    /// Key of the current generation.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn message_key<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
     ) -> Result<MessageKeyData, MlsError> {
         let generation = self.generation;
 
@@ -472,6 +975,14 @@ impl SecretKeyRatchet {
             generation,
         };
 
+        Ok(key)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn ratchet_secret<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
         self.secret = self
             .derive_secret(
                 cipher_suite_provider,
@@ -481,9 +992,9 @@ impl SecretKeyRatchet {
             .await?
             .into();
 
-        self.generation = generation + 1;
+        self.generation += 1;
 
-        Ok(key)
+        Ok(())
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -505,6 +1016,107 @@ impl SecretKeyRatchet {
     }
 }
 
+#[cfg(any(all(test, feature = "private_message"), feature = "test_vectors"))]
+pub(crate) mod test_vectors {
+    use alloc::vec::Vec;
+    use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+    use zeroize::Zeroizing;
+
+    use crate::client::MlsError;
+
+    pub use crate::group::ciphertext_processor::SenderDataTestCase;
+
+    use super::{KeyRetention, KeyType, RatchetDerivation, SecretTree, SkippedKeyStorage};
+
+    /// Secret tree test vector in the format of `secret-tree.json`.
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    pub struct SecretTreeTestCase {
+        pub cipher_suite: u16,
+        #[serde(with = "hex::serde")]
+        pub encryption_secret: Vec<u8>,
+        pub sender_data: SenderDataTestCase,
+        pub leaves: Vec<Vec<SecretTreeLeaf>>,
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    pub struct SecretTreeLeaf {
+        pub generation: u32,
+        #[serde(with = "hex::serde")]
+        pub application_key: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub application_nonce: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub handshake_key: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub handshake_nonce: Vec<u8>,
+    }
+
+    /// Generate a secret tree test vector for a tree with `n_leaves` leaves
+    /// and a random encryption secret, containing the application and
+    /// handshake keys of every leaf for each of `generations`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate<P: CipherSuiteProvider>(
+        cs: &P,
+        n_leaves: u32,
+        generations: &[u32],
+    ) -> Result<SecretTreeTestCase, MlsError> {
+        let encryption_secret = cs
+            .random_bytes_vec(cs.kdf_extract_size())
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let mut tree = SecretTree::new(n_leaves, Zeroizing::new(encryption_secret.clone()));
+        let mut leaves = Vec::new();
+
+        for leaf in 0..n_leaves {
+            let index = leaf * 2;
+            let mut keys = Vec::new();
+
+            for &generation in generations {
+                let handshake_key = tree
+                    .message_key_generation(
+                        cs,
+                        index,
+                        KeyType::Handshake,
+                        generation,
+                        KeyRetention::Standard,
+                        SkippedKeyStorage::Keys,
+                        RatchetDerivation::HashChain,
+                    )
+                    .await?;
+
+                let app_key = tree
+                    .message_key_generation(
+                        cs,
+                        index,
+                        KeyType::Application,
+                        generation,
+                        KeyRetention::Standard,
+                        SkippedKeyStorage::Keys,
+                        RatchetDerivation::HashChain,
+                    )
+                    .await?;
+
+                keys.push(SecretTreeLeaf {
+                    generation,
+                    application_key: app_key.key.to_vec(),
+                    application_nonce: app_key.nonce.to_vec(),
+                    handshake_key: handshake_key.key.to_vec(),
+                    handshake_nonce: handshake_key.nonce.to_vec(),
+                });
+            }
+
+            leaves.push(keys);
+        }
+
+        Ok(SecretTreeTestCase {
+            cipher_suite: *cs.cipher_suite(),
+            encryption_secret,
+            sender_data: SenderDataTestCase::generate(cs).await?,
+            leaves,
+        })
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use alloc::{string::String, vec::Vec};
@@ -519,18 +1131,6 @@ pub(crate) mod test_utils {
         SecretTree::new(leaf_count, Zeroizing::new(secret))
     }
 
-    impl SecretTree<u32> {
-        pub(crate) fn get_root_secret(&self) -> Vec<u8> {
-            self.known_secrets
-                .clone()
-                .take_node(&self.leaf_count.root())
-                .unwrap()
-                .into_secret()
-                .unwrap()
-                .to_vec()
-        }
-    }
-
     #[derive(Debug, serde::Serialize, serde::Deserialize)]
     pub struct RatchetInteropTestCase {
         #[serde(with = "hex::serde")]
@@ -593,6 +1193,12 @@ mod tests {
         tree_kem::node::NodeIndex,
     };
 
+    #[cfg(feature = "private_message")]
+    use crate::{
+        client::test_utils::{test_client_with_key_pkg_custom, TEST_PROTOCOL_VERSION},
+        group::{test_utils::test_group_custom_config, ReceivedMessage},
+    };
+
     #[cfg(not(mls_build_async))]
     use crate::group::test_utils::random_bytes;
 
@@ -699,12 +1305,24 @@ mod tests {
             let clone_2 = ratchet_clone.next_message_key(&provider).await.unwrap();
 
             // Going back in time should result in an error
-            let res = ratchet_clone.get_message_key(&provider, 0).await;
+            let res = ratchet_clone
+                .get_message_key(
+                    &provider,
+                    0,
+                    KeyRetention::Standard,
+                    SkippedKeyStorage::Keys,
+                )
+                .await;
             assert!(res.is_err());
 
             // Calling get key should be the same as calling next until hitting the desired generation
             let second_key = ratchet
-                .get_message_key(&provider, ratchet_clone.generation - 1)
+                .get_message_key(
+                    &provider,
+                    ratchet_clone.generation - 1,
+                    KeyRetention::Standard,
+                    SkippedKeyStorage::Keys,
+                )
                 .await
                 .unwrap();
 
@@ -747,12 +1365,27 @@ mod tests {
         let mut ordered_keys = Vec::<MessageKeyData>::new();
 
         for i in 0..=MAX_RATCHET_BACK_HISTORY {
-            ordered_keys.push(ratchet.get_message_key(&provider, i).await.unwrap());
+            ordered_keys.push(
+                ratchet
+                    .get_message_key(
+                        &provider,
+                        i,
+                        KeyRetention::Standard,
+                        SkippedKeyStorage::Keys,
+                    )
+                    .await
+                    .unwrap(),
+            );
         }
 
         // Ask for a key at index MAX_RATCHET_BACK_HISTORY in the clone
         let last_key = ratchet_clone
-            .get_message_key(&provider, MAX_RATCHET_BACK_HISTORY)
+            .get_message_key(
+                &provider,
+                MAX_RATCHET_BACK_HISTORY,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
             .await
             .unwrap();
 
@@ -762,7 +1395,17 @@ mod tests {
         let mut back_history_keys = Vec::<MessageKeyData>::new();
 
         for i in 0..MAX_RATCHET_BACK_HISTORY - 1 {
-            back_history_keys.push(ratchet_clone.get_message_key(&provider, i).await.unwrap());
+            back_history_keys.push(
+                ratchet_clone
+                    .get_message_key(
+                        &provider,
+                        i,
+                        KeyRetention::Standard,
+                        SkippedKeyStorage::Keys,
+                    )
+                    .await
+                    .unwrap(),
+            );
         }
 
         assert_eq!(
@@ -771,6 +1414,345 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "out_of_order")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn checkpoints_derive_skipped_keys() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let skipped_keys = SkippedKeyStorage::Checkpoints {
+            interval: 4,
+            max_generation_gap: MAX_RATCHET_BACK_HISTORY,
+        };
+
+        let mut ratchet = SecretKeyRatchet::new(&provider, &[0u8; 32], KeyType::Handshake)
+            .await
+            .unwrap();
+
+        let mut ordered_ratchet = ratchet.clone();
+        let mut ordered_keys = Vec::new();
+
+        for _ in 0..=10 {
+            ordered_keys.push(ordered_ratchet.next_message_key(&provider).await.unwrap());
+        }
+
+        let key = ratchet
+            .get_message_key(&provider, 10, KeyRetention::Standard, skipped_keys)
+            .await
+            .unwrap();
+
+        assert_eq!(key, ordered_keys[10]);
+        assert!(ratchet.history.is_empty());
+        assert_eq!(ratchet.checkpoints.len(), 3);
+
+        for generation in [5, 0, 9, 6, 1, 2, 3, 4, 8, 7] {
+            let key = ratchet
+                .get_message_key(&provider, generation, KeyRetention::Standard, skipped_keys)
+                .await
+                .unwrap();
+
+            assert_eq!(key, ordered_keys[generation as usize]);
+
+            let res = ratchet
+                .get_message_key(&provider, generation, KeyRetention::Standard, skipped_keys)
+                .await;
+
+            assert_matches!(res, Err(MlsError::KeyMissing(g)) if g == generation);
+        }
+
+        assert!(ratchet.checkpoints.is_empty());
+    }
+
+    #[cfg(feature = "out_of_order")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn checkpoints_allow_larger_generation_gap() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let skipped_keys = SkippedKeyStorage::Checkpoints {
+            interval: 1000,
+            max_generation_gap: 10_000,
+        };
+
+        let mut ratchet = SecretKeyRatchet::new(&provider, &[0u8; 32], KeyType::Handshake)
+            .await
+            .unwrap();
+
+        let res = ratchet
+            .clone()
+            .get_message_key(&provider, 10_001, KeyRetention::Standard, skipped_keys)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidFutureGeneration(10_001)));
+
+        ratchet
+            .get_message_key(&provider, 10_000, KeyRetention::Standard, skipped_keys)
+            .await
+            .unwrap();
+
+        assert_eq!(ratchet.checkpoints.len(), 10);
+
+        ratchet
+            .get_message_key(&provider, 4321, KeyRetention::Standard, skipped_keys)
+            .await
+            .unwrap();
+
+        assert_eq!(ratchet.checkpoints.len(), 11);
+    }
+
+    #[cfg(feature = "out_of_order")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_derivation_catches_up_to_any_generation() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut ratchet = SecretKeyRatchet::new(&provider, &[0u8; 32], KeyType::Handshake)
+            .await
+            .unwrap();
+
+        ratchet.use_tree_derivation();
+
+        let mut ordered_ratchet = ratchet.clone();
+        let mut ordered_keys = Vec::new();
+
+        for _ in 0..=10 {
+            ordered_keys.push(ordered_ratchet.next_message_key(&provider).await.unwrap());
+        }
+
+        let key = ratchet
+            .get_message_key(
+                &provider,
+                10,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(key, ordered_keys[10]);
+
+        for generation in [5, 0, 9, 6, 1, 2, 3, 4, 8, 7] {
+            let key = ratchet
+                .get_message_key(
+                    &provider,
+                    generation,
+                    KeyRetention::Standard,
+                    SkippedKeyStorage::Keys,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(key, ordered_keys[generation as usize]);
+
+            let res = ratchet
+                .get_message_key(
+                    &provider,
+                    generation,
+                    KeyRetention::Standard,
+                    SkippedKeyStorage::Keys,
+                )
+                .await;
+
+            assert_matches!(res, Err(MlsError::KeyMissing(g)) if g == generation);
+        }
+
+        // Only the generations after the last consumed one are covered.
+        assert!(ratchet.subtrees.iter().all(|s| s.start > 10));
+        assert!(ratchet.subtrees.len() <= 32);
+
+        let mut other_ratchet = ratchet.clone();
+
+        let key = ratchet
+            .get_message_key(
+                &provider,
+                1_000_000,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
+            .await
+            .unwrap();
+
+        other_ratchet
+            .get_message_key(
+                &provider,
+                999_999,
+                KeyRetention::Strict,
+                SkippedKeyStorage::Keys,
+            )
+            .await
+            .unwrap();
+
+        let other_key = other_ratchet.next_message_key(&provider).await.unwrap();
+
+        assert_eq!(key, other_key);
+        assert_eq!(key.generation, 1_000_000);
+
+        let skipped_key = ratchet
+            .get_message_key(
+                &provider,
+                500_000,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(skipped_key.generation, 500_000);
+        assert_ne!(skipped_key, key);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_derivation_with_strict_retention_discards_skipped_generations() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut ratchet = SecretKeyRatchet::new(&provider, &[0u8; 32], KeyType::Handshake)
+            .await
+            .unwrap();
+
+        ratchet.use_tree_derivation();
+
+        ratchet
+            .get_message_key(
+                &provider,
+                1000,
+                KeyRetention::Strict,
+                SkippedKeyStorage::Keys,
+            )
+            .await
+            .unwrap();
+
+        let res = ratchet
+            .get_message_key(
+                &provider,
+                999,
+                KeyRetention::Strict,
+                SkippedKeyStorage::Keys,
+            )
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyMissing(999)));
+        assert!(ratchet.subtrees.iter().all(|s| s.start > 1000));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_derivation_only_applies_to_unused_ratchets() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut ratchet = SecretKeyRatchet::new(&provider, &[0u8; 32], KeyType::Handshake)
+            .await
+            .unwrap();
+
+        let mut tree_ratchet = ratchet.clone();
+        tree_ratchet.use_tree_derivation();
+
+        let key = ratchet.next_message_key(&provider).await.unwrap();
+        let tree_key = tree_ratchet.next_message_key(&provider).await.unwrap();
+
+        assert_ne!(key, tree_key);
+
+        ratchet.use_tree_derivation();
+        assert!(ratchet.subtrees.is_empty());
+
+        let mut decoded =
+            SecretKeyRatchet::mls_decode(&mut &*tree_ratchet.mls_encode_to_vec().unwrap()).unwrap();
+
+        assert!(decoded.subtrees.is_empty());
+
+        let state =
+            RatchetState::mls_decode(&mut &*tree_ratchet.state().mls_encode_to_vec().unwrap())
+                .unwrap();
+
+        decoded.set_state(state);
+        assert_eq!(decoded, tree_ratchet);
+
+        let key = decoded.next_message_key(&provider).await.unwrap();
+        let tree_key = tree_ratchet.next_message_key(&provider).await.unwrap();
+
+        assert_eq!(key, tree_key);
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_ratchet_ext_enables_tree_derivation() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.extension_type(TreeRatchetExt::EXTENSION_TYPE)
+        })
+        .await;
+
+        let (bob, bob_kp) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "bob",
+            Default::default(),
+            Default::default(),
+            |c| {
+                c.0.settings
+                    .extension_types
+                    .push(TreeRatchetExt::EXTENSION_TYPE)
+            },
+        )
+        .await;
+
+        let mut extensions = alice.context().extensions.clone();
+        extensions.set_from(TreeRatchetExt).unwrap();
+
+        let output = alice
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .set_group_context_ext(extensions)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (mut bob, _) = bob
+            .join_group(None, &output.welcome_messages[0])
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            let message = alice
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            let received = bob.process_incoming_message(message).await.unwrap();
+
+            assert_matches!(received, ReceivedMessage::ApplicationMessage(m) if m.data() == b"hello");
+        }
+
+        let ratchet = bob
+            .epoch_secrets
+            .secret_tree
+            .known_secrets
+            .take_node(&0)
+            .unwrap();
+
+        assert_matches!(ratchet, SecretTreeNode::Ratchet(r) if !r.application.subtrees.is_empty());
+    }
+
+    #[cfg(feature = "out_of_order")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn strict_retention_discards_skipped_keys() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut ratchet = SecretKeyRatchet::new(&provider, &[0u8; 32], KeyType::Handshake)
+            .await
+            .unwrap();
+
+        ratchet
+            .get_message_key(&provider, 10, KeyRetention::Strict, SkippedKeyStorage::Keys)
+            .await
+            .unwrap();
+
+        let res = ratchet
+            .get_message_key(&provider, 9, KeyRetention::Strict, SkippedKeyStorage::Keys)
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyMissing(9)));
+        assert!(ratchet.history.is_empty());
+    }
+
     #[cfg(not(feature = "out_of_order"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn out_of_order_keys_should_throw_error() {
@@ -781,8 +1763,23 @@ mod tests {
             .await
             .unwrap();
 
-        ratchet.get_message_key(&provider, 10).await.unwrap();
-        let res = ratchet.get_message_key(&provider, 9).await;
+        ratchet
+            .get_message_key(
+                &provider,
+                10,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
+            .await
+            .unwrap();
+        let res = ratchet
+            .get_message_key(
+                &provider,
+                9,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
+            .await;
         assert_matches!(res, Err(MlsError::KeyMissing(9)))
     }
 
@@ -796,7 +1793,12 @@ mod tests {
             .unwrap();
 
         let res = ratchet
-            .get_message_key(&provider, MAX_RATCHET_BACK_HISTORY + 1)
+            .get_message_key(
+                &provider,
+                MAX_RATCHET_BACK_HISTORY + 1,
+                KeyRetention::Standard,
+                SkippedKeyStorage::Keys,
+            )
             .await;
 
         let invalid_generation = MAX_RATCHET_BACK_HISTORY + 1;
@@ -918,14 +1920,16 @@ mod tests {
 #[cfg(all(test, feature = "rfc_compliant", feature = "std"))]
 mod interop_tests {
     #[cfg(not(mls_build_async))]
-    use mls_rs_core::crypto::{CipherSuite, CipherSuiteProvider};
+    use mls_rs_core::crypto::CipherSuite;
     use zeroize::Zeroizing;
 
     use crate::{
+        client::test_utils::TEST_CIPHER_SUITE,
         crypto::test_utils::try_test_cipher_suite_provider,
-        group::{ciphertext_processor::InteropSenderData, secret_tree::KeyType},
+        group::secret_tree::{KeyRetention, KeyType, RatchetDerivation, SkippedKeyStorage},
     };
 
+    use super::test_vectors::SecretTreeTestCase as InteropTestCase;
     use super::SecretTree;
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -934,69 +1938,68 @@ mod interop_tests {
         let test_cases = load_interop_test_cases();
 
         for case in test_cases {
-            let Some(cs) = try_test_cipher_suite_provider(case.cipher_suite) else {
-                continue;
-            };
+            verify_test_case(case).await;
+        }
+    }
 
-            case.sender_data.verify(&cs).await;
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn generated_test_vector_is_valid() {
+        let cs = crate::crypto::test_utils::test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let case = super::test_vectors::generate(&cs, 8, &[0, 15])
+            .await
+            .unwrap();
 
-            let mut tree = SecretTree::new(
-                case.leaves.len() as u32,
-                Zeroizing::new(case.encryption_secret),
-            );
+        verify_test_case(case).await;
+    }
 
-            for (index, leaves) in case.leaves.iter().enumerate() {
-                for leaf in leaves.iter() {
-                    let key = tree
-                        .message_key_generation(
-                            &cs,
-                            (index as u32) * 2,
-                            KeyType::Application,
-                            leaf.generation,
-                        )
-                        .await
-                        .unwrap();
-
-                    assert_eq!(key.key.to_vec(), leaf.application_key);
-                    assert_eq!(key.nonce.to_vec(), leaf.application_nonce);
-
-                    let key = tree
-                        .message_key_generation(
-                            &cs,
-                            (index as u32) * 2,
-                            KeyType::Handshake,
-                            leaf.generation,
-                        )
-                        .await
-                        .unwrap();
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify_test_case(case: InteropTestCase) {
+        let Some(cs) = try_test_cipher_suite_provider(case.cipher_suite) else {
+            return;
+        };
 
-                    assert_eq!(key.key.to_vec(), leaf.handshake_key);
-                    assert_eq!(key.nonce.to_vec(), leaf.handshake_nonce);
-                }
-            }
-        }
-    }
+        case.sender_data.verify(&cs).await;
 
-    #[derive(Debug, serde::Serialize, serde::Deserialize)]
-    struct InteropTestCase {
-        cipher_suite: u16,
-        #[serde(with = "hex::serde")]
-        encryption_secret: Vec<u8>,
-        sender_data: InteropSenderData,
-        leaves: Vec<Vec<InteropLeaf>>,
-    }
+        let mut tree = SecretTree::new(
+            case.leaves.len() as u32,
+            Zeroizing::new(case.encryption_secret),
+        );
 
-    #[derive(Debug, serde::Serialize, serde::Deserialize)]
-    struct InteropLeaf {
-        generation: u32,
-        #[serde(with = "hex::serde")]
-        application_key: Vec<u8>,
-        #[serde(with = "hex::serde")]
-        application_nonce: Vec<u8>,
-        #[serde(with = "hex::serde")]
-        handshake_key: Vec<u8>,
-        #[serde(with = "hex::serde")]
-        handshake_nonce: Vec<u8>,
+        for (index, leaves) in case.leaves.iter().enumerate() {
+            for leaf in leaves.iter() {
+                let key = tree
+                    .message_key_generation(
+                        &cs,
+                        (index as u32) * 2,
+                        KeyType::Application,
+                        leaf.generation,
+                        KeyRetention::Standard,
+                        SkippedKeyStorage::Keys,
+                        RatchetDerivation::HashChain,
+                    )
+                    .await
+                    .unwrap();
+
+                assert_eq!(key.key.to_vec(), leaf.application_key);
+                assert_eq!(key.nonce.to_vec(), leaf.application_nonce);
+
+                let key = tree
+                    .message_key_generation(
+                        &cs,
+                        (index as u32) * 2,
+                        KeyType::Handshake,
+                        leaf.generation,
+                        KeyRetention::Standard,
+                        SkippedKeyStorage::Keys,
+                        RatchetDerivation::HashChain,
+                    )
+                    .await
+                    .unwrap();
+
+                assert_eq!(key.key.to_vec(), leaf.handshake_key);
+                assert_eq!(key.nonce.to_vec(), leaf.handshake_nonce);
+            }
+        }
     }
 
     fn load_interop_test_cases() -> Vec<InteropTestCase> {
@@ -1013,48 +2016,8 @@ mod interop_tests {
                 continue;
             };
 
-            let gens = [0, 15];
-            let tree_sizes = [1, 8, 32];
-
-            for n_leaves in tree_sizes {
-                let encryption_secret = cs.random_bytes_vec(cs.kdf_extract_size()).unwrap();
-
-                let mut tree = SecretTree::new(n_leaves, Zeroizing::new(encryption_secret.clone()));
-
-                let leaves = (0..n_leaves)
-                    .map(|leaf| {
-                        gens.into_iter()
-                            .map(|gen| {
-                                let index = leaf * 2u32;
-
-                                let handshake_key = tree
-                                    .message_key_generation(&cs, index, KeyType::Handshake, gen)
-                                    .unwrap();
-
-                                let app_key = tree
-                                    .message_key_generation(&cs, index, KeyType::Application, gen)
-                                    .unwrap();
-
-                                InteropLeaf {
-                                    generation: gen,
-                                    application_key: app_key.key.to_vec(),
-                                    application_nonce: app_key.nonce.to_vec(),
-                                    handshake_key: handshake_key.key.to_vec(),
-                                    handshake_nonce: handshake_key.nonce.to_vec(),
-                                }
-                            })
-                            .collect()
-                    })
-                    .collect();
-
-                let case = InteropTestCase {
-                    cipher_suite: *cs.cipher_suite(),
-                    encryption_secret,
-                    sender_data: InteropSenderData::new(&cs),
-                    leaves,
-                };
-
-                test_cases.push(case);
+            for n_leaves in [1, 8, 32] {
+                test_cases.push(super::test_vectors::generate(&cs, n_leaves, &[0, 15]).unwrap());
             }
         }
 
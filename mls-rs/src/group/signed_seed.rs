@@ -0,0 +1,186 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Seeds shared by some members of a group without creating an MLS group for
+//! them, used by pairwise channels and subset keys.
+//!
+//! The sender encrypts a fresh seed with HPKE to the leaf key of every
+//! recipient and signs the ciphertexts along with a context identifying the
+//! group, the epoch, the sender and the recipients. Each member combines the
+//! seed with a secret exported from the epoch, so that the derived secret is
+//! bound to the context.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use mls_rs_codec::{MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, HpkeCiphertext},
+    error::IntoAnyError,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::Group,
+    signer::Signable,
+    tree_kem::{hpke_encryption::HpkeEncryptable, node::LeafIndex},
+};
+
+#[derive(MlsSize, MlsEncode)]
+pub(crate) struct SeedContext<'a, R: MlsEncode + ?Sized> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub group_id: &'a [u8],
+    pub epoch: u64,
+    pub sender_index: LeafIndex,
+    pub recipients: &'a R,
+}
+
+#[derive(MlsSize, MlsEncode)]
+pub(crate) struct SeedTBS<'a, R: MlsEncode + ?Sized, S: MlsEncode + ?Sized> {
+    pub context: &'a SeedContext<'a, R>,
+    pub ciphertexts: &'a S,
+}
+
+/// Signed message carrying a seed encrypted to its recipients.
+pub(crate) trait SignedSeed:
+    for<'a> Signable<'a, SigningContext = ()> + Send + Sync
+{
+    /// Label of the HPKE encryption of the seed.
+    const ENCRYPT_LABEL: &'static str;
+    /// Label of the secret exported from the epoch.
+    const EXPORTER_LABEL: &'static [u8];
+
+    fn group_id(&self) -> &[u8];
+    fn epoch(&self) -> u64;
+    fn sender_index(&self) -> LeafIndex;
+
+    /// Encoded [`SeedContext`] of the message.
+    fn encoded_context(&self) -> Result<Vec<u8>, mls_rs_codec::Error>;
+}
+
+pub(crate) struct Seed<M> {
+    seed: Zeroizing<Vec<u8>>,
+    message: PhantomData<M>,
+}
+
+impl<M: SignedSeed> HpkeEncryptable for Seed<M> {
+    const ENCRYPT_LABEL: &'static str = M::ENCRYPT_LABEL;
+
+    fn from_bytes(bytes: Zeroizing<Vec<u8>>) -> Result<Self, MlsError> {
+        Ok(Self {
+            seed: bytes,
+            message: PhantomData,
+        })
+    }
+
+    fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        Ok(self.seed.to_vec())
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Generate a fresh seed of the size of the output of the KDF.
+    pub(crate) fn generate_seed<M: SignedSeed>(&self) -> Result<Seed<M>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+
+        cs.random_bytes_vec(cs.kdf_extract_size())
+            .map(|seed| Seed {
+                seed: Zeroizing::new(seed),
+                message: PhantomData,
+            })
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
+    /// Encrypt `seed` to the leaf key of `recipient` under the encoded
+    /// [`SeedContext`] `context`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn encrypt_seed<M: SignedSeed>(
+        &self,
+        seed: &Seed<M>,
+        context: &[u8],
+        recipient: LeafIndex,
+    ) -> Result<HpkeCiphertext, MlsError> {
+        let leaf = self.current_epoch_tree().get_leaf_node(recipient)?;
+
+        seed.encrypt(&self.cipher_suite_provider, &leaf.public_key, context)
+            .await
+    }
+
+    /// Check that `message` was created in the current epoch of this group.
+    pub(crate) fn check_seed_epoch<M: SignedSeed>(&self, message: &M) -> Result<(), MlsError> {
+        if message.group_id() != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if message.epoch() != self.current_epoch() {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        Ok(())
+    }
+
+    /// Verify the signature of `message` by its sender and decrypt the seed
+    /// encrypted to the local member in `ciphertext`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn open_seed<M: SignedSeed>(
+        &self,
+        message: &M,
+        ciphertext: &HpkeCiphertext,
+    ) -> Result<Seed<M>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+
+        let sender = self
+            .current_epoch_tree()
+            .get_leaf_node(message.sender_index())?;
+
+        message
+            .verify(cs, &sender.signing_identity.signature_key, &())
+            .await?;
+
+        let secret_key = self
+            .private_tree
+            .secret_keys
+            .first()
+            .and_then(Option::as_ref)
+            .ok_or(MlsError::InvalidTreeKemPrivateKey)?;
+
+        let public_key = &self.current_user_leaf_node()?.public_key;
+
+        Seed::decrypt(
+            cs,
+            secret_key,
+            public_key,
+            &message.encoded_context()?,
+            ciphertext,
+        )
+        .await
+    }
+
+    /// Combine `seed` with a secret exported from the current epoch and bound
+    /// to the context of `message`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn seed_secret<M: SignedSeed>(
+        &self,
+        message: &M,
+        seed: &Seed<M>,
+    ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+
+        let exported = self
+            .export_secret(
+                M::EXPORTER_LABEL,
+                &message.encoded_context()?,
+                cs.kdf_extract_size(),
+            )
+            .await?;
+
+        cs.kdf_extract(exported.as_bytes(), &seed.seed)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
@@ -0,0 +1,544 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt::{self, Debug};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, EpochSecretsVault, SecretHandle},
+    group::{EpochRecord, GroupState, GroupStateStorage},
+    secret::Secret,
+};
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+#[cfg(feature = "private_message")]
+use crate::group::mls_rules::ReceivedControlWireFormat;
+#[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+use crate::group::secret_tree::{KeyRetention, SkippedKeyStorage};
+
+use crate::{
+    client_config::ClientConfig,
+    extension::{ExtensionType, UnknownExtensionPolicy},
+    group::{proposal::ProposalType, Group},
+    identity::CredentialType,
+    protocol_version::ProtocolVersion,
+    storage_provider::in_memory::InMemoryKeyPackageStorage,
+    tree_kem::{Capabilities, Lifetime},
+};
+
+#[derive(Debug, Default)]
+struct Overlay {
+    states: BTreeMap<Vec<u8>, Vec<u8>>,
+    epochs: BTreeMap<(Vec<u8>, u64), Vec<u8>>,
+    integrity_counters: BTreeMap<Vec<u8>, u64>,
+}
+
+/// Group state storage of a simulated group. See
+/// [`Group::clone_for_simulation`].
+///
+/// Reads fall back to the storage of the original group, while writes are
+/// only kept in memory, so that the original storage is never modified.
+/// All clones of an instance share the same in memory writes.
+#[derive(Clone)]
+pub struct SimulationStorage<S> {
+    inner: S,
+    overlay: Arc<Mutex<Overlay>>,
+}
+
+impl<S: Debug> Debug for SimulationStorage<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulationStorage")
+            .field("inner", &self.inner)
+            .field("overlay", &*self.lock())
+            .finish()
+    }
+}
+
+impl<S> SimulationStorage<S> {
+    fn new(inner: S) -> Self {
+        Self {
+            inner,
+            overlay: Default::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Overlay> {
+        #[cfg(feature = "std")]
+        return self.overlay.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        return self.overlay.lock();
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<S> GroupStateStorage for SimulationStorage<S>
+where
+    S: GroupStateStorage,
+{
+    type Error = S::Error;
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let state = self.lock().states.get(group_id).cloned();
+
+        match state {
+            Some(state) => Ok(Some(state)),
+            None => self.inner.state(group_id).await,
+        }
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        let epoch = self
+            .lock()
+            .epochs
+            .get(&(group_id.to_vec(), epoch_id))
+            .cloned();
+
+        match epoch {
+            Some(epoch) => Ok(Some(epoch)),
+            None => self.inner.epoch(group_id, epoch_id).await,
+        }
+    }
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        epoch_inserts: Vec<EpochRecord>,
+        epoch_updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        let mut overlay = self.lock();
+
+        for epoch in epoch_inserts.into_iter().chain(epoch_updates) {
+            overlay
+                .epochs
+                .insert((state.id.clone(), epoch.id), epoch.data);
+        }
+
+        overlay.states.insert(state.id, state.data);
+
+        Ok(())
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        let simulated = self
+            .lock()
+            .epochs
+            .keys()
+            .filter(|(id, _)| id == group_id)
+            .map(|(_, epoch_id)| *epoch_id)
+            .max();
+
+        let stored = self.inner.max_epoch_id(group_id).await?;
+
+        Ok(simulated.max(stored))
+    }
+
+    async fn integrity_counter(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        let counter = self.lock().integrity_counters.get(group_id).copied();
+
+        match counter {
+            Some(counter) => Ok(Some(counter)),
+            None => self.inner.integrity_counter(group_id).await,
+        }
+    }
+
+    async fn set_integrity_counter(
+        &mut self,
+        group_id: &[u8],
+        counter: u64,
+    ) -> Result<(), Self::Error> {
+        self.lock()
+            .integrity_counters
+            .insert(group_id.to_vec(), counter);
+
+        Ok(())
+    }
+}
+
+/// Epoch secrets vault of a simulated group. See
+/// [`Group::clone_for_simulation`].
+///
+/// Secrets are held by the vault of the original group, but only those
+/// created by the simulation are released, as the original group still
+/// refers to the secrets of its own epochs.
+#[derive(Clone)]
+pub struct SimulationVault<V> {
+    inner: V,
+    created: Arc<Mutex<Vec<SecretHandle>>>,
+}
+
+impl<V: Debug> Debug for SimulationVault<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulationVault")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<V> SimulationVault<V> {
+    fn new(inner: V) -> Self {
+        Self {
+            inner,
+            created: Default::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<SecretHandle>> {
+        #[cfg(feature = "std")]
+        return self.created.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        return self.created.lock();
+    }
+
+    fn track(&self, handle: SecretHandle) -> SecretHandle {
+        self.lock().push(handle.clone());
+        handle
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<V> EpochSecretsVault for SimulationVault<V>
+where
+    V: EpochSecretsVault,
+{
+    type Error = V::Error;
+
+    async fn import<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        secret: &[u8],
+    ) -> Result<SecretHandle, Self::Error> {
+        let handle = self.inner.import(cipher_suite_provider, secret).await?;
+        Ok(self.track(handle))
+    }
+
+    async fn generate<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        len: usize,
+    ) -> Result<SecretHandle, Self::Error> {
+        let handle = self.inner.generate(cipher_suite_provider, len).await?;
+        Ok(self.track(handle))
+    }
+
+    async fn extract<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        salt: &SecretHandle,
+        ikm: &[u8],
+    ) -> Result<SecretHandle, Self::Error> {
+        let handle = self.inner.extract(cipher_suite_provider, salt, ikm).await?;
+        Ok(self.track(handle))
+    }
+
+    async fn expand<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        prk: &SecretHandle,
+        info: &[u8],
+        len: usize,
+    ) -> Result<SecretHandle, Self::Error> {
+        let handle = self
+            .inner
+            .expand(cipher_suite_provider, prk, info, len)
+            .await?;
+
+        Ok(self.track(handle))
+    }
+
+    async fn export(&self, handle: &SecretHandle) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner.export(handle).await
+    }
+
+    async fn release(&self, handle: SecretHandle) -> Result<(), Self::Error> {
+        let created = {
+            let mut created = self.lock();
+            let position = created.iter().position(|h| h == &handle);
+            position.map(|i| created.swap_remove(i)).is_some()
+        };
+
+        match created {
+            true => self.inner.release(handle).await,
+            false => Ok(()),
+        }
+    }
+}
+
+/// Configuration of a simulated group. See [`Group::clone_for_simulation`].
+///
+/// All settings and providers are those of the original group, except for
+/// the group state storage, which is a [`SimulationStorage`], the epoch
+/// secrets vault, which is a [`SimulationVault`], and the key package
+/// repository, which is empty.
+#[derive(Clone, Debug)]
+pub struct SimulationConfig<C: ClientConfig> {
+    inner: C,
+    storage: SimulationStorage<C::GroupStateStorage>,
+    vault: SimulationVault<C::EpochSecretsVault>,
+    key_package_repo: InMemoryKeyPackageStorage,
+}
+
+impl<C: ClientConfig> ClientConfig for SimulationConfig<C> {
+    type KeyPackageRepository = InMemoryKeyPackageStorage;
+    type PskStore = C::PskStore;
+    type GroupStateStorage = SimulationStorage<C::GroupStateStorage>;
+    type IdentityProvider = C::IdentityProvider;
+    type MlsRules = C::MlsRules;
+    type CryptoProvider = C::CryptoProvider;
+    type IdentityBootstrap = C::IdentityBootstrap;
+    type KeychainStorage = C::KeychainStorage;
+    type EpochSecretsVault = SimulationVault<C::EpochSecretsVault>;
+    type MessageCompressor = C::MessageCompressor;
+
+    fn supported_extensions(&self) -> Vec<ExtensionType> {
+        self.inner.supported_extensions()
+    }
+
+    fn supported_custom_proposals(&self) -> Vec<ProposalType> {
+        self.inner.supported_custom_proposals()
+    }
+
+    fn supported_protocol_versions(&self) -> Vec<ProtocolVersion> {
+        self.inner.supported_protocol_versions()
+    }
+
+    fn key_package_repo(&self) -> Self::KeyPackageRepository {
+        self.key_package_repo.clone()
+    }
+
+    fn mls_rules(&self) -> Self::MlsRules {
+        self.inner.mls_rules()
+    }
+
+    fn secret_store(&self) -> Self::PskStore {
+        self.inner.secret_store()
+    }
+
+    fn group_state_storage(&self) -> Self::GroupStateStorage {
+        self.storage.clone()
+    }
+
+    fn identity_provider(&self) -> Self::IdentityProvider {
+        self.inner.identity_provider()
+    }
+
+    fn crypto_provider(&self) -> Self::CryptoProvider {
+        self.inner.crypto_provider()
+    }
+
+    fn identity_bootstrap(&self) -> Self::IdentityBootstrap {
+        self.inner.identity_bootstrap()
+    }
+
+    fn keychain_storage(&self) -> Self::KeychainStorage {
+        self.inner.keychain_storage()
+    }
+
+    fn epoch_secrets_vault(&self) -> Self::EpochSecretsVault {
+        self.vault.clone()
+    }
+
+    fn message_compressor(&self) -> Self::MessageCompressor {
+        self.inner.message_compressor()
+    }
+
+    fn lifetime(&self) -> Lifetime {
+        self.inner.lifetime()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn version_supported(&self, version: ProtocolVersion) -> bool {
+        self.inner.version_supported(version)
+    }
+
+    fn supported_credential_types(&self) -> Vec<CredentialType> {
+        self.inner.supported_credential_types()
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn key_retention(&self) -> KeyRetention {
+        self.inner.key_retention()
+    }
+
+    fn storage_integrity_key(&self) -> Option<Secret> {
+        self.inner.storage_integrity_key()
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn skipped_key_storage(&self) -> SkippedKeyStorage {
+        self.inner.skipped_key_storage()
+    }
+
+    fn unknown_extension_policy(&self) -> UnknownExtensionPolicy {
+        self.inner.unknown_extension_policy()
+    }
+
+    fn grease(&self) -> bool {
+        self.inner.grease()
+    }
+
+    fn require_configured_capabilities(&self) -> bool {
+        self.inner.require_configured_capabilities()
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    fn max_message_age(&self) -> Option<u64> {
+        self.inner.max_message_age()
+    }
+
+    #[cfg(feature = "private_message")]
+    fn received_control_wire_format(&self) -> ReceivedControlWireFormat {
+        self.inner.received_control_wire_format()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Copy of this group for what-if analysis, such as inspecting the
+    /// outcome of a received or planned commit before acting on it.
+    ///
+    /// The copy has the state of this group, including changes not written
+    /// to storage yet, and can be used as any other group. Its writes are
+    /// kept in memory by a [`SimulationStorage`] and it never deletes key
+    /// packages, so durable state is left untouched. An operation started
+    /// with [`Group::begin_operation`] is not copied.
+    ///
+    /// The copy holds the same secrets as this group. Messages it creates
+    /// must not be sent, as they may reuse keys or nonces of messages later
+    /// sent by this group.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn clone_for_simulation(&self) -> Group<SimulationConfig<C>> {
+        let config = SimulationConfig {
+            inner: self.config.clone(),
+            storage: SimulationStorage::new(self.config.group_state_storage()),
+            vault: SimulationVault::new(self.config.epoch_secrets_vault()),
+            key_package_repo: Default::default(),
+        };
+
+        let state_repo = self
+            .state_repo
+            .with_storage(config.group_state_storage(), config.key_package_repo());
+
+        Group {
+            config,
+            cipher_suite_provider: self.cipher_suite_provider.clone(),
+            state_repo,
+            state: self.state.clone(),
+            epoch_secrets: self.epoch_secrets.clone(),
+            private_tree: self.private_tree.clone(),
+            key_schedule: self.key_schedule.clone(),
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: self.pending_updates.clone(),
+            pending_commit: self.pending_commit.clone(),
+            #[cfg(feature = "psk")]
+            previous_psk: self.previous_psk.clone(),
+            #[cfg(test)]
+            commit_modifiers: self.commit_modifiers,
+            signer: self.signer.clone(),
+            leaf_update_epochs: self.leaf_update_epochs.clone(),
+            member_uids: self.member_uids.clone(),
+            liveness_log: self.liveness_log.clone(),
+            app_data: self.app_data.clone(),
+            config_overrides: self.config_overrides.clone(),
+            #[cfg(feature = "psk")]
+            lineage: self.lineage.clone(),
+            quarantine: self.quarantine.clone(),
+            unconfirmed_members: self.unconfirmed_members.clone(),
+            future_epoch_buffer: self.future_epoch_buffer.clone(),
+            flush_mode: self.flush_mode,
+            unflushed_changes: self.unflushed_changes,
+            retired_key_schedules: Vec::new(),
+            operation: None,
+            next_operation_id: self.next_operation_id,
+            removal_templates: self.removal_templates.clone(),
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention: self.epoch_retention.clone(),
+            #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+            key_deletion_audit: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use mls_rs_core::group::GroupStateStorage;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_config::ClientConfig,
+        group::{test_utils::test_n_member_group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn simulation_leaves_group_and_storage_untouched() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        groups[1].write_to_storage().await.unwrap();
+
+        let storage = groups[1].config.group_state_storage();
+        let group_id = groups[1].group_id().to_vec();
+        let stored = storage.state(&group_id).await.unwrap();
+        let epoch = groups[1].current_epoch();
+
+        // Simulate the removal of bob planned by alice
+        let mut planned = groups[0].clone_for_simulation();
+
+        planned
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        planned.apply_pending_commit().await.unwrap();
+        assert_eq!(planned.roster().members_iter().count(), 2);
+        assert_eq!(groups[0].roster().members_iter().count(), 3);
+
+        // Simulate the processing of a commit by bob
+        let commit = groups[0].commit(vec![]).await.unwrap().commit_message;
+
+        let mut simulated = groups[1].clone_for_simulation();
+        let received = simulated.process_incoming_message(commit.clone()).await;
+        assert!(matches!(received, Ok(ReceivedMessage::Commit(_))));
+
+        simulated.write_to_storage().await.unwrap();
+        assert_eq!(simulated.current_epoch(), epoch + 1);
+
+        let simulated_state = simulated
+            .config
+            .group_state_storage()
+            .state(&group_id)
+            .await;
+        assert_ne!(simulated_state.unwrap(), stored);
+
+        assert_eq!(groups[1].current_epoch(), epoch);
+        let durable_state = storage.state(&group_id).await.unwrap();
+        assert_eq!(durable_state, stored);
+
+        groups[1].process_incoming_message(commit).await.unwrap();
+        assert_eq!(groups[1].current_epoch(), epoch + 1);
+    }
+}
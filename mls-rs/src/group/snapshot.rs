@@ -7,8 +7,10 @@ use crate::{
     client_config::ClientConfig,
     group::{
         cipher_suite_provider, epoch::EpochSecrets, key_schedule::KeySchedule,
-        state_repo::GroupStateRepository, CommitGeneration, ConfirmationTag, Group, GroupContext,
-        GroupState, InterimTranscriptHash, ReInitProposal, TreeKemPublic,
+        snapshot_format::GROUP_STATE_VERSION, state_integrity::StateIntegrity,
+        state_repo::GroupStateRepository, AppData, CommitGeneration, ConfirmationTag, Group,
+        GroupConfigOverrides, GroupContext, GroupState, InterimTranscriptHash, LeafUpdateEpochs,
+        LivenessLog, MemberUids, Quarantine, ReInitProposal, TreeKemPublic, UnconfirmedMembers,
     },
     tree_kem::TreeKemPrivate,
 };
@@ -24,12 +26,24 @@ use crate::{
     map::SmallMap,
 };
 
+#[cfg(feature = "psk")]
+use crate::psk::GroupLineage;
+
+#[cfg(feature = "prior_epoch")]
+use crate::group::epoch_retention::EpochRetentionLog;
+
+#[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+use crate::{group::secret_tree::RatchetStates, tree_kem::node::NodeIndex};
+
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::crypto::SignatureSecretKey;
 #[cfg(feature = "tree_index")]
 use mls_rs_core::identity::IdentityProvider;
 
-#[derive(Debug, PartialEq, Clone, MlsEncode, MlsDecode, MlsSize)]
+/// Stored group state. Fields added after the layout of
+/// [format version](crate::group::snapshot_format) 1 are appended at the end
+/// and decoded with [`Snapshot::decode_version`].
+#[derive(Debug, PartialEq, Clone, MlsEncode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Snapshot {
     version: u16,
@@ -41,6 +55,20 @@ pub(crate) struct Snapshot {
     pending_updates: SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
     pending_commit: Option<CommitGeneration>,
     signer: SignatureSecretKey,
+    leaf_update_epochs: LeafUpdateEpochs,
+    app_data: AppData,
+    config_overrides: GroupConfigOverrides,
+    #[cfg(feature = "psk")]
+    lineage: GroupLineage,
+    quarantine: Quarantine,
+    unconfirmed_members: UnconfirmedMembers,
+    #[cfg(feature = "prior_epoch")]
+    epoch_retention: EpochRetentionLog,
+    member_uids: MemberUids,
+    liveness_log: LivenessLog,
+    /// Checkpoints and subtrees of the ratchets of `epoch_secrets`.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    ratchet_states: RatchetStates<NodeIndex>,
 }
 
 #[derive(Debug, MlsEncode, MlsDecode, MlsSize, PartialEq, Clone)]
@@ -57,6 +85,63 @@ pub(crate) struct RawGroupState {
     pub(crate) confirmation_tag: ConfirmationTag,
 }
 
+impl Snapshot {
+    /// Decode a group state written with the format `version`. Fields that
+    /// were appended to the group state in a later version take their default
+    /// value.
+    pub(crate) fn decode_version(
+        reader: &mut &[u8],
+        version: u16,
+    ) -> Result<Self, mls_rs_codec::Error> {
+        #[allow(unused_mut)]
+        let mut snapshot = Self {
+            version: MlsDecode::mls_decode(reader)?,
+            state: MlsDecode::mls_decode(reader)?,
+            private_tree: MlsDecode::mls_decode(reader)?,
+            epoch_secrets: MlsDecode::mls_decode(reader)?,
+            key_schedule: MlsDecode::mls_decode(reader)?,
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: MlsDecode::mls_decode(reader)?,
+            pending_commit: MlsDecode::mls_decode(reader)?,
+            signer: MlsDecode::mls_decode(reader)?,
+            leaf_update_epochs: decode_appended(reader, version, 3)?,
+            app_data: decode_appended(reader, version, 4)?,
+            config_overrides: decode_appended(reader, version, 5)?,
+            #[cfg(feature = "psk")]
+            lineage: decode_appended(reader, version, 6)?,
+            quarantine: decode_appended(reader, version, 7)?,
+            unconfirmed_members: decode_appended(reader, version, 8)?,
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention: decode_appended(reader, version, 9)?,
+            member_uids: decode_appended(reader, version, 10)?,
+            liveness_log: decode_appended(reader, version, 11)?,
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            ratchet_states: decode_appended(reader, version, 12)?,
+        };
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        snapshot
+            .epoch_secrets
+            .secret_tree
+            .set_ratchet_states(snapshot.ratchet_states.clone())?;
+
+        Ok(snapshot)
+    }
+}
+
+/// Decode a field appended to the group state in the format version `since`.
+fn decode_appended<T: MlsDecode + Default>(
+    reader: &mut &[u8],
+    version: u16,
+    since: u16,
+) -> Result<T, mls_rs_codec::Error> {
+    if version >= since {
+        T::mls_decode(reader)
+    } else {
+        Ok(T::default())
+    }
+}
+
 impl RawGroupState {
     pub(crate) fn export(state: &GroupState) -> Self {
         #[cfg(feature = "tree_index")]
@@ -149,7 +234,13 @@ where
     /// that is currently in use by the group.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self) -> Result<(), MlsError> {
-        self.state_repo.write_to_storage(self.snapshot()).await
+        #[cfg(feature = "prior_epoch")]
+        self.enforce_epoch_retention().await?;
+
+        self.state_repo.write_to_storage(self.snapshot()).await?;
+        self.unflushed_changes = false;
+
+        self.release_retired_key_schedules().await
     }
 
     /// Write the current state of the group to the
@@ -159,10 +250,45 @@ where
     /// separately by calling [`Group::export_tree`].
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage_without_ratchet_tree(&mut self) -> Result<(), MlsError> {
+        #[cfg(feature = "prior_epoch")]
+        self.enforce_epoch_retention().await?;
+
         let mut snapshot = self.snapshot();
         snapshot.state.public_tree.nodes = Default::default();
 
-        self.state_repo.write_to_storage(snapshot).await
+        self.state_repo.write_to_storage(snapshot).await?;
+        self.unflushed_changes = false;
+
+        self.release_retired_key_schedules().await
+    }
+
+    /// Release the secrets held by the
+    /// [`EpochSecretsVault`](crate::crypto::EpochSecretsVault) for this group,
+    /// when the group is deleted.
+    ///
+    /// Group states written to storage before can no longer be used once the
+    /// vault released their secrets, so this should be called after the
+    /// state of the group is removed from storage.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn discard(mut self) -> Result<(), MlsError> {
+        let key_schedule = core::mem::take(&mut self.key_schedule);
+        self.retired_key_schedules.push(key_schedule);
+
+        self.release_retired_key_schedules().await
+    }
+
+    /// Release the secrets of the key schedules of past epochs, which the
+    /// group state in storage no longer refers to.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn release_retired_key_schedules(&mut self) -> Result<(), MlsError> {
+        let vault = self.config.epoch_secrets_vault();
+
+        for key_schedule in self.retired_key_schedules.drain(..) {
+            key_schedule.release(&vault).await?;
+        }
+
+        Ok(())
     }
 
     pub(crate) fn snapshot(&self) -> Snapshot {
@@ -174,8 +300,21 @@ where
             pending_updates: self.pending_updates.clone(),
             pending_commit: self.pending_commit.clone(),
             epoch_secrets: self.epoch_secrets.clone(),
-            version: 1,
+            version: GROUP_STATE_VERSION,
             signer: self.signer.clone(),
+            leaf_update_epochs: self.leaf_update_epochs.clone(),
+            member_uids: self.member_uids.clone(),
+            liveness_log: self.liveness_log.clone(),
+            app_data: self.app_data.clone(),
+            config_overrides: self.config_overrides.clone(),
+            #[cfg(feature = "psk")]
+            lineage: self.lineage.clone(),
+            quarantine: self.quarantine.clone(),
+            unconfirmed_members: self.unconfirmed_members.clone(),
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention: self.epoch_retention.clone(),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            ratchet_states: self.epoch_secrets.secret_tree.ratchet_states(),
         }
     }
 
@@ -195,17 +334,34 @@ where
             config.group_state_storage(),
             config.key_package_repo(),
             None,
-        )?;
+        )?
+        .with_integrity(StateIntegrity::from_config(&config, &cipher_suite_provider));
 
-        Ok(Group {
-            config,
-            state: snapshot
-                .state
-                .import(
-                    #[cfg(feature = "tree_index")]
-                    &identity_provider,
+        let state = snapshot
+            .state
+            .import(
+                #[cfg(feature = "tree_index")]
+                &identity_provider,
+            )
+            .await?;
+
+        // Group states written before member UIDs were stored identify the
+        // members as of the current epoch.
+        let member_uids = match snapshot.member_uids {
+            uids if uids.is_empty() => {
+                MemberUids::new(
+                    &cipher_suite_provider,
+                    &state.public_tree,
+                    state.context.epoch,
                 )
-                .await?,
+                .await?
+            }
+            uids => uids,
+        };
+
+        let group = Group {
+            config,
+            state,
             private_tree: snapshot.private_tree,
             key_schedule: snapshot.key_schedule,
             #[cfg(feature = "by_ref_proposal")]
@@ -219,13 +375,39 @@ where
             #[cfg(feature = "psk")]
             previous_psk: None,
             signer: snapshot.signer,
-        })
+            leaf_update_epochs: snapshot.leaf_update_epochs,
+            member_uids,
+            liveness_log: snapshot.liveness_log,
+            app_data: snapshot.app_data,
+            config_overrides: snapshot.config_overrides,
+            #[cfg(feature = "psk")]
+            lineage: snapshot.lineage,
+            quarantine: snapshot.quarantine,
+            unconfirmed_members: snapshot.unconfirmed_members,
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention: snapshot.epoch_retention,
+            future_epoch_buffer: Default::default(),
+            #[cfg(all(feature = "debug_utils", feature = "private_message"))]
+            key_deletion_audit: None,
+            flush_mode: Default::default(),
+            unflushed_changes: false,
+            retired_key_schedules: Default::default(),
+            operation: None,
+            next_operation_id: 0,
+            removal_templates: None,
+        };
+
+        #[cfg(feature = "invariant_checks")]
+        group.debug_assert_invariants().await?;
+
+        Ok(group)
     }
 }
 
 #[cfg(test)]
 pub(crate) mod test_utils {
-    use alloc::vec;
+    use alloc::{vec, vec::Vec};
+    use mls_rs_codec::MlsEncode;
 
     use crate::{
         cipher_suite::CipherSuite,
@@ -263,22 +445,97 @@ pub(crate) mod test_utils {
             pending_commit: None,
             version: 1,
             signer: vec![].into(),
+            leaf_update_epochs: Default::default(),
+            member_uids: Default::default(),
+            liveness_log: Default::default(),
+            app_data: Default::default(),
+            config_overrides: Default::default(),
+            #[cfg(feature = "psk")]
+            lineage: Default::default(),
+            quarantine: Default::default(),
+            unconfirmed_members: Default::default(),
+            #[cfg(feature = "prior_epoch")]
+            epoch_retention: Default::default(),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            ratchet_states: Default::default(),
         }
     }
+
+    /// Encoding of `snapshot` in the layout of the format `version`, written
+    /// field by field independently of [`Snapshot::decode_version`].
+    pub(crate) fn encode_layout(snapshot: &Snapshot, version: u16) -> Vec<u8> {
+        let mut bytes = [
+            snapshot.version.mls_encode_to_vec(),
+            snapshot.state.mls_encode_to_vec(),
+            snapshot.private_tree.mls_encode_to_vec(),
+            snapshot.epoch_secrets.mls_encode_to_vec(),
+            snapshot.key_schedule.mls_encode_to_vec(),
+            #[cfg(feature = "by_ref_proposal")]
+            snapshot.pending_updates.mls_encode_to_vec(),
+            snapshot.pending_commit.mls_encode_to_vec(),
+            snapshot.signer.mls_encode_to_vec(),
+        ]
+        .into_iter()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>()
+        .concat();
+
+        let appended = [
+            (3, snapshot.leaf_update_epochs.mls_encode_to_vec()),
+            (4, snapshot.app_data.mls_encode_to_vec()),
+            (5, snapshot.config_overrides.mls_encode_to_vec()),
+            #[cfg(feature = "psk")]
+            (6, snapshot.lineage.mls_encode_to_vec()),
+            (7, snapshot.quarantine.mls_encode_to_vec()),
+            (8, snapshot.unconfirmed_members.mls_encode_to_vec()),
+            #[cfg(feature = "prior_epoch")]
+            (9, snapshot.epoch_retention.mls_encode_to_vec()),
+            (10, snapshot.member_uids.mls_encode_to_vec()),
+            (11, snapshot.liveness_log.mls_encode_to_vec()),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            (12, snapshot.ratchet_states.mls_encode_to_vec()),
+        ];
+
+        appended
+            .into_iter()
+            .filter(|(since, _)| *since <= version)
+            .for_each(|(_, field)| bytes.extend(field.unwrap()));
+
+        bytes
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use core::fmt::Debug;
 
     use crate::{
         client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
         group::{
+            snapshot_format::SnapshotFormat,
             test_utils::{test_group, TestGroup},
-            Group,
+            Group, GroupConfigOverrides,
         },
     };
 
+    use super::{test_utils::encode_layout, Snapshot};
+
+    #[cfg(feature = "std")]
+    use crate::{
+        client_builder::ClientBuilder,
+        crypto::test_utils::TestCryptoProvider,
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+    };
+
+    #[cfg(feature = "std")]
+    use alloc::vec::Vec;
+
+    #[cfg(feature = "std")]
+    use mls_rs_core::crypto::{
+        CipherSuiteProvider, DefaultEpochSecretsVault, EpochSecretsVault, SecretHandle,
+    };
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn snapshot_restore(group: TestGroup) {
         let snapshot = group.snapshot();
@@ -333,4 +590,198 @@ mod tests {
         let recovered = serde_json::from_str(&json).unwrap();
         assert_eq!(snapshot, recovered);
     }
+
+    fn assert_appended<T: Debug + Default + PartialEq>(
+        decoded: &T,
+        original: &T,
+        version: u16,
+        since: u16,
+    ) {
+        if version >= since {
+            assert_eq!(decoded, original);
+        } else {
+            assert_eq!(decoded, &T::default());
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_state_of_each_version_is_read() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        group.app_data_set(b"app", b"key", b"value".to_vec());
+        group.config_overrides = GroupConfigOverrides::new().with_max_epoch_retention(3);
+        group.commit(vec![]).await.unwrap();
+        group.process_pending_commit().await.unwrap();
+
+        let snapshot = group.snapshot();
+
+        for version in 1..=SnapshotFormat::CURRENT_VERSION {
+            let bytes = encode_layout(&snapshot, version);
+            let reader = &mut &*bytes;
+            let decoded = Snapshot::decode_version(reader, version).unwrap();
+
+            assert!(reader.is_empty());
+            assert_eq!(decoded.state, snapshot.state);
+            assert_eq!(decoded.signer, snapshot.signer);
+
+            let (d, s) = (&decoded, &snapshot);
+            assert_appended(&d.leaf_update_epochs, &s.leaf_update_epochs, version, 3);
+            assert_appended(&d.app_data, &s.app_data, version, 4);
+            assert_appended(&d.config_overrides, &s.config_overrides, version, 5);
+            #[cfg(feature = "psk")]
+            assert_appended(&d.lineage, &s.lineage, version, 6);
+            assert_appended(&d.quarantine, &s.quarantine, version, 7);
+            assert_appended(&d.unconfirmed_members, &s.unconfirmed_members, version, 8);
+            #[cfg(feature = "prior_epoch")]
+            assert_appended(&d.epoch_retention, &s.epoch_retention, version, 9);
+            assert_appended(&d.member_uids, &s.member_uids, version, 10);
+            assert_appended(&d.liveness_log, &s.liveness_log, version, 11);
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            assert_appended(&d.ratchet_states, &s.ratchet_states, version, 12);
+        }
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn snapshot_with_ratchet_states_round_trips() {
+        use crate::group::secret_tree::{KeyType, RatchetDerivation};
+
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let cipher_suite_provider = group.cipher_suite_provider.clone();
+
+        group
+            .epoch_secrets
+            .secret_tree
+            .next_message_key(
+                &cipher_suite_provider,
+                0,
+                KeyType::Application,
+                RatchetDerivation::Tree,
+            )
+            .await
+            .unwrap();
+
+        let snapshot = group.snapshot();
+        assert_ne!(snapshot.ratchet_states, Default::default());
+
+        let decoded = Snapshot::from_bytes(&snapshot.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, snapshot);
+        assert_eq!(decoded.epoch_secrets, group.epoch_secrets);
+    }
+
+    #[cfg(feature = "std")]
+    #[derive(Clone, Default)]
+    struct CountingVault {
+        released: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[cfg(feature = "std")]
+    impl CountingVault {
+        fn released(&self) -> usize {
+            self.released.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl EpochSecretsVault for CountingVault {
+        type Error = <DefaultEpochSecretsVault as EpochSecretsVault>::Error;
+
+        async fn import<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            secret: &[u8],
+        ) -> Result<SecretHandle, Self::Error> {
+            DefaultEpochSecretsVault.import(cs, secret).await
+        }
+
+        async fn generate<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            len: usize,
+        ) -> Result<SecretHandle, Self::Error> {
+            DefaultEpochSecretsVault.generate(cs, len).await
+        }
+
+        async fn extract<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            salt: &SecretHandle,
+            ikm: &[u8],
+        ) -> Result<SecretHandle, Self::Error> {
+            DefaultEpochSecretsVault.extract(cs, salt, ikm).await
+        }
+
+        async fn expand<P: CipherSuiteProvider>(
+            &self,
+            cs: &P,
+            prk: &SecretHandle,
+            info: &[u8],
+            len: usize,
+        ) -> Result<SecretHandle, Self::Error> {
+            DefaultEpochSecretsVault.expand(cs, prk, info, len).await
+        }
+
+        async fn export(
+            &self,
+            handle: &SecretHandle,
+        ) -> Result<zeroize::Zeroizing<Vec<u8>>, Self::Error> {
+            DefaultEpochSecretsVault.export(handle).await
+        }
+
+        async fn release(&self, _handle: SecretHandle) -> Result<(), Self::Error> {
+            self.released
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn secrets_of_past_epochs_are_released() {
+        let vault = CountingVault::default();
+
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let mut group = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(BasicIdentityProvider::new())
+            .epoch_secrets_vault(vault.clone())
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+            .create_group(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        // Intermediate secrets are released as they are derived, along with
+        // the key schedule derived to create the commit
+        let released = vault.released();
+        group.commit(vec![]).await.unwrap();
+        let commit_releases = vault.released() - released;
+
+        // The stored state still refers to the secrets of the previous epoch
+        let released = vault.released();
+        group.apply_pending_commit().await.unwrap();
+        assert_eq!(vault.released() - released, commit_releases - 4);
+
+        let released = vault.released();
+        group.write_to_storage().await.unwrap();
+        assert_eq!(vault.released() - released, 4);
+
+        // Simulations don't release the secrets of the original group
+        let mut simulation = group.clone_for_simulation();
+        simulation.commit(vec![]).await.unwrap();
+        simulation.apply_pending_commit().await.unwrap();
+
+        let released = vault.released();
+        simulation.write_to_storage().await.unwrap();
+        assert_eq!(vault.released(), released);
+
+        let released = vault.released();
+        group.discard().await.unwrap();
+        assert_eq!(vault.released() - released, 4);
+    }
 }
@@ -0,0 +1,429 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Versioned format of the group state written to the
+//! [`GroupStateStorage`](crate::GroupStateStorage).
+//!
+//! A stored group state is encoded with the MLS codec as:
+//!
+//! ```text
+//! struct {
+//!     uint32 magic;                 // "MLSG"
+//!     uint16 version;               // format version of the writer
+//!     uint16 min_reader_version;    // oldest format version able to read it
+//!     CipherSuite cipher_suite;
+//!     uint32 features;              // SnapshotFeatures of the writer
+//!     opaque group_state<V>;
+//! } GroupSnapshot;
+//! ```
+//!
+//! A reader accepts any snapshot with a `min_reader_version` that is not
+//! newer than its own [`SnapshotFormat::CURRENT_VERSION`]. Newer writers only
+//! append fields to `group_state` unless they raise `min_reader_version`, and
+//! a reader ignores the fields it does not know. Fields appended after the
+//! `version` of the writer take their default value. The `features` must
+//! match those of the reader, as they change the layout of `group_state`.
+//!
+//! Versions of the format:
+//!
+//! 1. Group state without header, as written before this header was
+//!    introduced.
+//! 2. Header.
+//! 3. Epochs of the last update of each leaf.
+//! 4. Application data.
+//! 5. Group config overrides.
+//! 6. Group lineage, with the `psk` feature.
+//! 7. Quarantined members.
+//! 8. Unconfirmed members.
+//! 9. Epoch retention log, with the `prior_epoch` feature.
+//! 10. Member UIDs.
+//! 11. Liveness log.
+//! 12. Checkpoints and subtrees of the ratchets of the secret tree, with the
+//!     `private_message` feature. Readers of version 11 expect them within
+//!     the secret tree.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::crypto::CipherSuite;
+
+use crate::{client::MlsError, group::snapshot::Snapshot, group::GroupContext};
+
+const SNAPSHOT_MAGIC: u32 = u32::from_be_bytes(*b"MLSG");
+
+/// Version of the inner group state, stored before the group context.
+pub(crate) const GROUP_STATE_VERSION: u16 = 1;
+
+/// Crate features that change the layout of a stored group state.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotFeatures(u32);
+
+impl Debug for SnapshotFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SnapshotFeatures({:#06x})", self.0)
+    }
+}
+
+impl SnapshotFeatures {
+    pub const BY_REF_PROPOSAL: Self = Self(1 << 0);
+    pub const PSK: Self = Self(1 << 1);
+    pub const PRIOR_EPOCH: Self = Self(1 << 2);
+    pub const PRIVATE_MESSAGE: Self = Self(1 << 3);
+
+    /// Features this build of the crate was compiled with.
+    pub fn current() -> Self {
+        [
+            (cfg!(feature = "by_ref_proposal"), Self::BY_REF_PROPOSAL),
+            (cfg!(feature = "psk"), Self::PSK),
+            (cfg!(feature = "prior_epoch"), Self::PRIOR_EPOCH),
+            (cfg!(feature = "private_message"), Self::PRIVATE_MESSAGE),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(Self(0), |features, (_, feature)| {
+            Self(features.0 | feature.0)
+        })
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Header of a stored group state. See the [module level](self)
+/// documentation for the layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SnapshotFormat {
+    /// Format version of the writer. Group states written before versioning
+    /// was introduced have version 1.
+    pub version: u16,
+    /// Oldest format version able to read the group state.
+    pub min_reader_version: u16,
+    pub cipher_suite: CipherSuite,
+    pub features: SnapshotFeatures,
+}
+
+impl SnapshotFormat {
+    /// Format version written by this build of the crate.
+    pub const CURRENT_VERSION: u16 = 12;
+
+    /// Oldest format version able to read the group states written by this
+    /// build, raised whenever fields are not only appended.
+    const MIN_READER_VERSION: u16 = 12;
+
+    const LEGACY_VERSION: u16 = 1;
+
+    /// Read the header of a stored group state without decoding the state.
+    ///
+    /// Group states sealed with a
+    /// [storage integrity key](crate::client_builder::ClientBuilder::storage_integrity_key)
+    /// must be read with [`Client::stored_snapshot_format`](crate::Client::stored_snapshot_format).
+    pub fn read(group_state: &[u8]) -> Result<Self, MlsError> {
+        match VersionedSnapshot::decode(group_state)? {
+            Some(snapshot) => Ok(snapshot.format()),
+            None => Ok(Self {
+                version: Self::LEGACY_VERSION,
+                min_reader_version: Self::LEGACY_VERSION,
+                cipher_suite: Snapshot::decode_group_state_context(group_state)?.cipher_suite,
+                features: SnapshotFeatures::current(),
+            }),
+        }
+    }
+
+    /// Whether this build of the crate can read the group state.
+    pub fn is_supported(&self) -> bool {
+        self.min_reader_version <= Self::CURRENT_VERSION
+            && self.features == SnapshotFeatures::current()
+    }
+
+    fn check_supported(&self) -> Result<(), MlsError> {
+        if self.min_reader_version > Self::CURRENT_VERSION {
+            return Err(MlsError::UnsupportedSnapshotVersion(self.version));
+        }
+
+        if self.features != SnapshotFeatures::current() {
+            return Err(MlsError::UnsupportedSnapshotFeatures(self.features.bits()));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct VersionedSnapshot {
+    magic: u32,
+    version: u16,
+    min_reader_version: u16,
+    cipher_suite: CipherSuite,
+    features: SnapshotFeatures,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_state: Vec<u8>,
+}
+
+impl VersionedSnapshot {
+    /// Decode the header and the group state, or `None` for a legacy group
+    /// state without header.
+    fn decode(bytes: &[u8]) -> Result<Option<Self>, MlsError> {
+        if !bytes.starts_with(&SNAPSHOT_MAGIC.to_be_bytes()) {
+            return Ok(None);
+        }
+
+        Self::mls_decode(&mut &*bytes).map(Some).map_err(Into::into)
+    }
+
+    fn format(&self) -> SnapshotFormat {
+        SnapshotFormat {
+            version: self.version,
+            min_reader_version: self.min_reader_version,
+            cipher_suite: self.cipher_suite,
+            features: self.features,
+        }
+    }
+}
+
+impl Snapshot {
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        VersionedSnapshot {
+            magic: SNAPSHOT_MAGIC,
+            version: SnapshotFormat::CURRENT_VERSION,
+            min_reader_version: SnapshotFormat::MIN_READER_VERSION,
+            cipher_suite: self.state.context.cipher_suite,
+            features: SnapshotFeatures::current(),
+            group_state: self.mls_encode_to_vec()?,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        let Some(versioned) = VersionedSnapshot::decode(bytes)? else {
+            return Snapshot::decode_version(&mut &*bytes, SnapshotFormat::LEGACY_VERSION)
+                .map_err(Into::into);
+        };
+
+        versioned.format().check_supported()?;
+
+        let snapshot = Snapshot::decode_version(&mut &*versioned.group_state, versioned.version)?;
+
+        if snapshot.state.context.cipher_suite != versioned.cipher_suite {
+            return Err(MlsError::StorageCorrupted);
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Decode the group context without decoding the ratchet tree and the
+    /// private state that follow it.
+    pub(crate) fn decode_context(bytes: &[u8]) -> Result<GroupContext, MlsError> {
+        match VersionedSnapshot::decode(bytes)? {
+            Some(versioned) => {
+                versioned.format().check_supported()?;
+                Self::decode_group_state_context(&versioned.group_state)
+            }
+            None => Self::decode_group_state_context(bytes),
+        }
+    }
+
+    fn decode_group_state_context(group_state: &[u8]) -> Result<GroupContext, MlsError> {
+        let reader = &mut &*group_state;
+
+        if u16::mls_decode(reader)? != GROUP_STATE_VERSION {
+            return Err(MlsError::StorageCorrupted);
+        }
+
+        Ok(GroupContext::mls_decode(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{snapshot::Snapshot, test_utils::test_group},
+    };
+
+    #[cfg(all(
+        feature = "by_ref_proposal",
+        feature = "psk",
+        feature = "private_message",
+        feature = "prior_epoch",
+        feature = "out_of_order"
+    ))]
+    use crate::{
+        client::test_utils::TestClientBuilder, client_config::ClientConfig, group::ReceivedMessage,
+        MlsMessage,
+    };
+
+    #[cfg(all(
+        feature = "by_ref_proposal",
+        feature = "psk",
+        feature = "private_message",
+        feature = "prior_epoch",
+        feature = "out_of_order"
+    ))]
+    use mls_rs_core::group::{EpochRecord, GroupState, GroupStateStorage};
+
+    use super::{SnapshotFeatures, SnapshotFormat, VersionedSnapshot, SNAPSHOT_MAGIC};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn snapshot_round_trips_with_header() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let snapshot = group.snapshot();
+        let bytes = snapshot.to_bytes().unwrap();
+
+        assert!(bytes.starts_with(b"MLSG"));
+
+        let format = SnapshotFormat::read(&bytes).unwrap();
+        assert_eq!(format.version, SnapshotFormat::CURRENT_VERSION);
+        assert_eq!(format.cipher_suite, TEST_CIPHER_SUITE);
+        assert_eq!(format.features, SnapshotFeatures::current());
+        assert!(format.is_supported());
+
+        assert_eq!(Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+
+        let context = Snapshot::decode_context(&bytes).unwrap();
+        assert_eq!(&context, group.context());
+    }
+
+    // Group state of a group created by `test_group`, written by the crate
+    // before the header was introduced.
+    #[cfg(all(
+        feature = "by_ref_proposal",
+        feature = "psk",
+        feature = "private_message",
+        feature = "prior_epoch"
+    ))]
+    #[test]
+    fn legacy_snapshot_is_read() {
+        let legacy = include_bytes!("../../test_data/group_state_v1.mls");
+
+        let format = SnapshotFormat::read(legacy).unwrap();
+        assert_eq!(format.version, 1);
+        assert_eq!(format.cipher_suite, TEST_CIPHER_SUITE);
+
+        let snapshot = Snapshot::from_bytes(legacy).unwrap();
+        assert_eq!(snapshot.state.context.group_id, b"group");
+        assert_eq!(snapshot.state.context.epoch, 0);
+
+        // The baseline fields make up the whole group state
+        let reader = &mut &legacy[..];
+        assert_eq!(Snapshot::decode_version(reader, 1).unwrap(), snapshot);
+        assert!(reader.is_empty());
+    }
+
+    // Group state of a member at epoch 2 of a group with two members, written
+    // by the crate before the header was introduced, along with the prior
+    // epoch 1 and messages from the other member. The member encrypted and
+    // decrypted messages in both epochs, skipping the first and second
+    // messages.
+    #[cfg(all(
+        feature = "by_ref_proposal",
+        feature = "psk",
+        feature = "private_message",
+        feature = "prior_epoch",
+        feature = "out_of_order"
+    ))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn legacy_snapshot_with_used_secret_tree_is_read() {
+        let state = include_bytes!("../../test_data/group_state_v1_epoch_2.mls");
+        let epoch_1 = include_bytes!("../../test_data/prior_epoch_v1_epoch_1.mls");
+        let messages = include_bytes!("../../test_data/messages_v1_epoch_2.mls");
+
+        let snapshot = Snapshot::from_bytes(state).unwrap();
+        assert_eq!(snapshot.state.context.epoch, 2);
+
+        let client = TestClientBuilder::new_for_test().build();
+
+        client
+            .config
+            .group_state_storage()
+            .write(
+                GroupState {
+                    id: b"group".to_vec(),
+                    data: state.to_vec(),
+                },
+                vec![EpochRecord::new(1, epoch_1.to_vec())],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let mut group = client.load_group(b"group").await.unwrap();
+        let messages = Vec::<MlsMessage>::mls_decode(&mut &messages[..]).unwrap();
+
+        // Skipped message of epoch 1, skipped message of epoch 2 and next
+        // message of epoch 2
+        let expected = [b"bob 1", b"bob 3", b"bob 5"];
+
+        for (message, expected) in messages.into_iter().zip(expected) {
+            let received = group.process_incoming_message(message).await.unwrap();
+
+            assert_matches!(
+                received,
+                ReceivedMessage::ApplicationMessage(m) if m.data() == expected
+            );
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn newer_snapshot_is_read_if_compatible() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let snapshot = group.snapshot();
+
+        let mut group_state = snapshot.mls_encode_to_vec().unwrap();
+        group_state.extend_from_slice(b"field of a newer version");
+
+        let newer = |min_reader_version| {
+            VersionedSnapshot {
+                magic: SNAPSHOT_MAGIC,
+                version: SnapshotFormat::CURRENT_VERSION + 1,
+                min_reader_version,
+                cipher_suite: TEST_CIPHER_SUITE,
+                features: SnapshotFeatures::current(),
+                group_state: group_state.clone(),
+            }
+            .mls_encode_to_vec()
+            .unwrap()
+        };
+
+        let compatible = newer(SnapshotFormat::CURRENT_VERSION);
+        assert_eq!(Snapshot::from_bytes(&compatible).unwrap(), snapshot);
+
+        let incompatible = newer(SnapshotFormat::CURRENT_VERSION + 1);
+
+        assert_matches!(
+            Snapshot::from_bytes(&incompatible),
+            Err(MlsError::UnsupportedSnapshotVersion(v)) if v == SnapshotFormat::CURRENT_VERSION + 1
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn snapshot_with_other_features_is_rejected() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let bytes = group.snapshot().to_bytes().unwrap();
+
+        let mut versioned = VersionedSnapshot::mls_decode(&mut &*bytes).unwrap();
+        versioned.features = SnapshotFeatures(versioned.features.bits() ^ (1 << 31));
+        let bytes: Vec<u8> = versioned.mls_encode_to_vec().unwrap();
+
+        let format = SnapshotFormat::read(&bytes).unwrap();
+        assert!(!format.is_supported());
+
+        let res = Snapshot::from_bytes(&bytes);
+        assert_matches!(res, Err(MlsError::UnsupportedSnapshotFeatures(_)));
+    }
+}
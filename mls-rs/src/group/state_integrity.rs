@@ -0,0 +1,375 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider},
+    error::IntoAnyError,
+    group::GroupStateStorage,
+    secret::Secret,
+};
+use subtle::ConstantTimeEq;
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::util::cipher_suite_provider;
+
+const STATE_INTEGRITY_LABEL: &[u8] = b"MLS 1.0 storage integrity";
+
+/// Format of sealed blobs. Blobs with any other version are treated as
+/// corrupted.
+const SEALED_BLOB_VERSION: u16 = 1;
+
+/// Blob of a group written to storage. It is covered by the MAC so that a
+/// blob can't be swapped with another blob of the same group.
+#[derive(Clone, Copy, Debug, MlsSize, MlsEncode)]
+#[repr(u8)]
+pub(crate) enum StoredBlob {
+    GroupState = 1u8,
+    #[cfg(feature = "prior_epoch")]
+    Epoch(u64) = 2u8,
+}
+
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct SealedBlob {
+    version: u16,
+    cipher_suite: CipherSuite,
+    counter: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    data: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    mac: Vec<u8>,
+}
+
+impl SealedBlob {
+    fn decode(sealed: &[u8]) -> Result<Self, MlsError> {
+        let reader = &mut &*sealed;
+
+        SealedBlob::mls_decode(reader)
+            .ok()
+            .filter(|blob| reader.is_empty() && blob.version == SEALED_BLOB_VERSION)
+            .ok_or(MlsError::StorageCorrupted)
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct SealedBlobContent<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    label: &'a [u8],
+    version: u16,
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    blob: StoredBlob,
+    counter: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    data: &'a [u8],
+}
+
+/// Seals the blobs of a group written to storage with the integrity key set
+/// with [`ClientBuilder::storage_integrity_key`](crate::client_builder::ClientBuilder::storage_integrity_key)
+/// and verifies them when they are read back.
+///
+/// Every write of a group is sealed with a counter that increases with each
+/// write, so that a group state replaced with an older one is detected
+/// against the high water mark kept by
+/// [`GroupStateStorage::integrity_counter`].
+///
+/// Records of prior epochs are sealed with the counter of the write that last
+/// stored them but are not checked against the high water mark. A record is
+/// only rewritten when its epoch is accessed, so its counter is normally
+/// lower than the high water mark, and telling an older copy of a record from
+/// the latest one would require the group state to track the counter of every
+/// record. An older copy can only restore message keys of its epoch that were
+/// deleted after use, as the MAC still binds it to its group and epoch.
+#[derive(Clone)]
+pub(crate) struct StateIntegrity<P> {
+    key: Secret,
+    cipher_suite_provider: P,
+}
+
+impl<P: CipherSuiteProvider> StateIntegrity<P> {
+    pub fn new(key: Secret, cipher_suite_provider: P) -> Self {
+        Self {
+            key,
+            cipher_suite_provider,
+        }
+    }
+
+    pub fn from_config<C: ClientConfig>(config: &C, cipher_suite_provider: &P) -> Option<Self>
+    where
+        P: Clone,
+    {
+        config
+            .storage_integrity_key()
+            .map(|key| Self::new(key, cipher_suite_provider.clone()))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn seal(
+        &self,
+        group_id: &[u8],
+        blob: StoredBlob,
+        counter: u64,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, MlsError> {
+        let mac = self.mac(group_id, blob, counter, &data).await?;
+
+        SealedBlob {
+            version: SEALED_BLOB_VERSION,
+            cipher_suite: self.cipher_suite_provider.cipher_suite(),
+            counter,
+            data,
+            mac,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+
+    /// Verify a blob created by [`StateIntegrity::seal`] and return its
+    /// content. A blob that can't be parsed results in
+    /// [`MlsError::StorageCorrupted`] while a blob that doesn't match its
+    /// MAC results in [`MlsError::StorageIntegrityViolation`]. The counter of
+    /// the blob is not checked, see [`StateIntegrity`].
+    #[cfg(feature = "prior_epoch")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn open(
+        &self,
+        group_id: &[u8],
+        blob: StoredBlob,
+        sealed: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        self.verify(group_id, blob, SealedBlob::decode(sealed)?)
+            .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify(
+        &self,
+        group_id: &[u8],
+        blob: StoredBlob,
+        sealed: SealedBlob,
+    ) -> Result<Vec<u8>, MlsError> {
+        if sealed.cipher_suite != self.cipher_suite_provider.cipher_suite() {
+            return Err(MlsError::StorageIntegrityViolation);
+        }
+
+        let mac = self
+            .mac(group_id, blob, sealed.counter, &sealed.data)
+            .await?;
+
+        bool::from(mac.as_slice().ct_eq(&sealed.mac))
+            .then_some(sealed.data)
+            .ok_or(MlsError::StorageIntegrityViolation)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn mac(
+        &self,
+        group_id: &[u8],
+        blob: StoredBlob,
+        counter: u64,
+        data: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let content = SealedBlobContent {
+            label: STATE_INTEGRITY_LABEL,
+            version: SEALED_BLOB_VERSION,
+            cipher_suite: self.cipher_suite_provider.cipher_suite(),
+            group_id,
+            blob,
+            counter,
+            data,
+        }
+        .mls_encode_to_vec()?;
+
+        self.cipher_suite_provider
+            .mac(&self.key, &content)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+/// Counter to seal the next write of a group with, one more than the high
+/// water mark kept by `storage`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn next_counter<S: GroupStateStorage>(
+    storage: &S,
+    group_id: &[u8],
+) -> Result<u64, MlsError> {
+    let counter = storage
+        .integrity_counter(group_id)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+    Ok(counter.map_or(0, |counter| counter + 1))
+}
+
+/// Record `counter` as the high water mark of a group once the write sealed
+/// with it completed.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn commit_counter<S: GroupStateStorage>(
+    storage: &mut S,
+    group_id: &[u8],
+    counter: u64,
+) -> Result<(), MlsError> {
+    storage
+        .set_integrity_counter(group_id, counter)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+}
+
+/// Verify the sealed state of a group that is being loaded. The cipher suite
+/// of the group is only known after the blob is parsed. A state sealed with a
+/// counter lower than the high water mark kept by the storage was rolled back
+/// and results in [`MlsError::StorageIntegrityViolation`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn open_group_state<C: ClientConfig>(
+    config: &C,
+    group_id: &[u8],
+    data: Vec<u8>,
+) -> Result<Vec<u8>, MlsError> {
+    let Some(key) = config.storage_integrity_key() else {
+        return Ok(data);
+    };
+
+    let sealed = SealedBlob::decode(&data)?;
+    let counter = sealed.counter;
+
+    let cipher_suite_provider =
+        cipher_suite_provider(config.crypto_provider(), sealed.cipher_suite)?;
+
+    let data = StateIntegrity::new(key, cipher_suite_provider)
+        .verify(group_id, StoredBlob::GroupState, sealed)
+        .await?;
+
+    let high_water_mark = config
+        .group_state_storage()
+        .integrity_counter(group_id)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+    if high_water_mark.map_or(false, |high_water_mark| counter < high_water_mark) {
+        return Err(MlsError::StorageIntegrityViolation);
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
+    use mls_rs_core::group::{GroupState, GroupStateStorage};
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+            MlsError,
+        },
+        group::test_utils::TEST_GROUP,
+        storage_provider::in_memory::InMemoryGroupStateStorage,
+    };
+
+    use super::SealedBlob;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn modified_group_state_is_detected() {
+        let mut storage = InMemoryGroupStateStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(storage.clone())
+            .storage_integrity_key(vec![7; 32])
+            .build();
+
+        let mut group = client
+            .create_group_with_id(TEST_GROUP.to_vec(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        let loaded = client.load_group(TEST_GROUP).await.unwrap();
+        assert_eq!(loaded.current_epoch(), group.current_epoch());
+
+        let stored = storage.state(TEST_GROUP).await.unwrap().unwrap();
+        let mut sealed = SealedBlob::decode(&stored).unwrap();
+        *sealed.data.last_mut().unwrap() ^= 1;
+
+        storage
+            .write(
+                GroupState {
+                    id: TEST_GROUP.to_vec(),
+                    data: sealed.mls_encode_to_vec().unwrap(),
+                },
+                vec![],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let res = client.load_group(TEST_GROUP).await.map(|_| ());
+        assert_matches!(res, Err(MlsError::StorageIntegrityViolation));
+
+        storage
+            .write(
+                GroupState {
+                    id: TEST_GROUP.to_vec(),
+                    data: stored[..stored.len() - 1].to_vec(),
+                },
+                vec![],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let res = client.load_group(TEST_GROUP).await.map(|_| ());
+        assert_matches!(res, Err(MlsError::StorageCorrupted));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rolled_back_group_state_is_detected() {
+        let mut storage = InMemoryGroupStateStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(storage.clone())
+            .storage_integrity_key(vec![7; 32])
+            .build();
+
+        let mut group = client
+            .create_group_with_id(TEST_GROUP.to_vec(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+        let stored = storage.state(TEST_GROUP).await.unwrap().unwrap();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        let counter = storage.integrity_counter(TEST_GROUP).await.unwrap();
+        assert_eq!(counter, Some(1));
+
+        storage
+            .write(
+                GroupState {
+                    id: TEST_GROUP.to_vec(),
+                    data: stored,
+                },
+                vec![],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let res = client.load_group(TEST_GROUP).await.map(|_| ());
+        assert_matches!(res, Err(MlsError::StorageIntegrityViolation));
+    }
+}
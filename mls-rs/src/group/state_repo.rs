@@ -8,11 +8,17 @@ use crate::{group::PriorEpoch, key_package::KeyPackageRef};
 use alloc::collections::VecDeque;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
-use mls_rs_codec::{MlsDecode, MlsEncode};
+use mls_rs_codec::MlsEncode;
 use mls_rs_core::group::{EpochRecord, GroupState};
-use mls_rs_core::{error::IntoAnyError, group::GroupStateStorage, key_package::KeyPackageStorage};
+use mls_rs_core::{
+    crypto::CipherSuiteProvider, error::IntoAnyError, group::GroupStateStorage,
+    key_package::KeyPackageStorage,
+};
 
-use super::snapshot::Snapshot;
+use super::{
+    snapshot::Snapshot,
+    state_integrity::{commit_counter, next_counter, StateIntegrity, StoredBlob},
+};
 
 #[cfg(feature = "psk")]
 use crate::group::ResumptionPsk;
@@ -29,22 +35,25 @@ struct EpochStorageCommit {
 }
 
 #[derive(Clone)]
-pub(crate) struct GroupStateRepository<S, K>
+pub(crate) struct GroupStateRepository<S, K, P>
 where
     S: GroupStateStorage,
     K: KeyPackageStorage,
+    P: CipherSuiteProvider,
 {
     pending_commit: EpochStorageCommit,
     pending_key_package_removal: Option<KeyPackageRef>,
     group_id: Vec<u8>,
     storage: S,
     key_package_repo: K,
+    integrity: Option<StateIntegrity<P>>,
 }
 
-impl<S, K> Debug for GroupStateRepository<S, K>
+impl<S, K, P> Debug for GroupStateRepository<S, K, P>
 where
     S: GroupStateStorage + Debug,
     K: KeyPackageStorage + Debug,
+    P: CipherSuiteProvider,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("GroupStateRepository")
@@ -59,14 +68,16 @@ where
             )
             .field("storage", &self.storage)
             .field("key_package_repo", &self.key_package_repo)
+            .field("integrity", &self.integrity.is_some())
             .finish()
     }
 }
 
-impl<S, K> GroupStateRepository<S, K>
+impl<S, K, P> GroupStateRepository<S, K, P>
 where
     S: GroupStateStorage,
     K: KeyPackageStorage,
+    P: CipherSuiteProvider,
 {
     pub fn new(
         group_id: Vec<u8>,
@@ -74,16 +85,45 @@ where
         key_package_repo: K,
         // Set to `None` if restoring from snapshot; set to `Some` when joining a group.
         key_package_to_remove: Option<KeyPackageRef>,
-    ) -> Result<GroupStateRepository<S, K>, MlsError> {
+    ) -> Result<GroupStateRepository<S, K, P>, MlsError> {
         Ok(GroupStateRepository {
             group_id,
             storage,
             pending_key_package_removal: key_package_to_remove,
             pending_commit: Default::default(),
             key_package_repo,
+            integrity: None,
         })
     }
 
+    /// Seal everything written to storage with `integrity` and verify it
+    /// when it is read back.
+    pub fn with_integrity(self, integrity: Option<StateIntegrity<P>>) -> Self {
+        Self { integrity, ..self }
+    }
+
+    /// Copy of this repository, including changes not written yet, that
+    /// writes to `storage` and never deletes the key package used to join.
+    pub fn with_storage<S2, K2>(
+        &self,
+        storage: S2,
+        key_package_repo: K2,
+    ) -> GroupStateRepository<S2, K2, P>
+    where
+        S2: GroupStateStorage,
+        K2: KeyPackageStorage,
+        P: Clone,
+    {
+        GroupStateRepository {
+            pending_commit: self.pending_commit.clone(),
+            pending_key_package_removal: None,
+            group_id: self.group_id.clone(),
+            storage,
+            key_package_repo,
+            integrity: self.integrity.clone(),
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn find_max_id(&self) -> Result<Option<u64>, MlsError> {
         if let Some(max) = self.pending_commit.inserts.back().map(|e| e.epoch_id()) {
@@ -102,36 +142,38 @@ where
         &self,
         psk_id: &ResumptionPsk,
     ) -> Result<Option<PreSharedKey>, MlsError> {
-        // Search the local inserts cache
-        if let Some(min) = self.pending_commit.inserts.front().map(|e| e.epoch_id()) {
-            if psk_id.psk_epoch >= min {
-                return Ok(self
-                    .pending_commit
-                    .inserts
-                    .get((psk_id.psk_epoch - min) as usize)
-                    .map(|e| e.secrets.resumption_secret.clone()));
+        // The local caches only hold epochs of this group, while the PSK may
+        // come from an ancestor group that is only in storage
+        if psk_id.psk_group_id.0 == self.group_id {
+            // Search the local inserts cache
+            if let Some(min) = self.pending_commit.inserts.front().map(|e| e.epoch_id()) {
+                if psk_id.psk_epoch >= min {
+                    return Ok(self
+                        .pending_commit
+                        .inserts
+                        .get((psk_id.psk_epoch - min) as usize)
+                        .map(|e| e.secrets.resumption_secret.clone()));
+                }
             }
-        }
 
-        // Search the local updates cache
-        let maybe_pending = self.find_pending(psk_id.psk_epoch);
+            // Search the local updates cache
+            let maybe_pending = self.find_pending(psk_id.psk_epoch);
 
-        if let Some(pending) = maybe_pending {
-            return Ok(Some(
-                self.pending_commit.updates[pending]
-                    .secrets
-                    .resumption_secret
-                    .clone(),
-            ));
+            if let Some(pending) = maybe_pending {
+                return Ok(Some(
+                    self.pending_commit.updates[pending]
+                        .secrets
+                        .resumption_secret
+                        .clone(),
+                ));
+            }
         }
 
         // Search the stored cache
-        self.storage
-            .epoch(&psk_id.psk_group_id.0, psk_id.psk_epoch)
-            .await
-            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
-            .map(|e| Ok(PriorEpoch::mls_decode(&mut &*e)?.secrets.resumption_secret))
-            .transpose()
+        Ok(self
+            .stored_epoch(&psk_id.psk_group_id.0, psk_id.psk_epoch)
+            .await?
+            .map(|e| e.secrets.resumption_secret))
     }
 
     #[cfg(feature = "private_message")]
@@ -153,23 +195,97 @@ where
         // Look in the cached updates map, and if not found look in disk storage
         // and insert into the updates map for future caching
         match self.find_pending(epoch_id) {
-            Some(i) => self.pending_commit.updates.get_mut(i).map(Ok),
-            None => self
-                .storage
-                .epoch(&self.group_id, epoch_id)
-                .await
-                .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
-                .and_then(|epoch| {
-                    PriorEpoch::mls_decode(&mut &*epoch)
-                        .map(|epoch| {
-                            self.pending_commit.updates.push(epoch);
-                            self.pending_commit.updates.last_mut()
-                        })
-                        .transpose()
-                }),
+            Some(i) => Ok(self.pending_commit.updates.get_mut(i)),
+            None => {
+                let Some(epoch) = self.stored_epoch(&self.group_id, epoch_id).await? else {
+                    return Ok(None);
+                };
+
+                self.pending_commit.updates.push(epoch);
+                Ok(self.pending_commit.updates.last_mut())
+            }
+        }
+    }
+
+    /// Collect every retained prior epoch, oldest first, including epochs
+    /// that have not been written to storage yet.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn prior_epochs(&self) -> Result<Vec<PriorEpoch>, MlsError> {
+        let mut epochs = Vec::new();
+        let mut next_id = self.find_max_id().await?;
+
+        while let Some(epoch_id) = next_id {
+            let Some(epoch) = self.get_epoch(epoch_id).await? else {
+                break;
+            };
+
+            epochs.push(epoch);
+            next_id = epoch_id.checked_sub(1);
+        }
+
+        epochs.reverse();
+
+        Ok(epochs)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn get_epoch(&self, epoch_id: u64) -> Result<Option<PriorEpoch>, MlsError> {
+        if let Some(min) = self.pending_commit.inserts.front().map(|e| e.epoch_id()) {
+            if epoch_id >= min {
+                return Ok(self
+                    .pending_commit
+                    .inserts
+                    .get((epoch_id - min) as usize)
+                    .cloned());
+            }
+        }
+
+        if let Some(i) = self.find_pending(epoch_id) {
+            return Ok(Some(self.pending_commit.updates[i].clone()));
+        }
+
+        self.stored_epoch(&self.group_id, epoch_id).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn stored_epoch(
+        &self,
+        group_id: &[u8],
+        epoch_id: u64,
+    ) -> Result<Option<PriorEpoch>, MlsError> {
+        let Some(data) = self
+            .storage
+            .epoch(group_id, epoch_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+        else {
+            return Ok(None);
+        };
+
+        let data = match &self.integrity {
+            Some(integrity) => {
+                integrity
+                    .open(group_id, StoredBlob::Epoch(epoch_id), &data)
+                    .await?
+            }
+            None => data,
+        };
+
+        Ok(Some(PriorEpoch::from_record(&data)?))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn seal(
+        &self,
+        group_id: &[u8],
+        blob: StoredBlob,
+        counter: u64,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, MlsError> {
+        match &self.integrity {
+            Some(integrity) => integrity.seal(group_id, blob, counter, data).await,
+            None => Ok(data),
         }
-        .transpose()
-        .map_err(Into::into)
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -193,22 +309,27 @@ where
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
+        let group_id = &group_snapshot.state.context.group_id;
+
+        let counter = match self.integrity {
+            Some(_) => next_counter(&self.storage, group_id).await?,
+            None => 0,
+        };
+
         let inserts = self
-            .pending_commit
-            .inserts
-            .iter()
-            .map(|e| Ok(EpochRecord::new(e.epoch_id(), e.mls_encode_to_vec()?)))
-            .collect::<Result<_, MlsError>>()?;
+            .epoch_records(group_id, counter, self.pending_commit.inserts.iter())
+            .await?;
 
         let updates = self
-            .pending_commit
-            .updates
-            .iter()
-            .map(|e| Ok(EpochRecord::new(e.epoch_id(), e.mls_encode_to_vec()?)))
-            .collect::<Result<_, MlsError>>()?;
+            .epoch_records(group_id, counter, self.pending_commit.updates.iter())
+            .await?;
+
+        let data = group_snapshot.to_bytes()?;
 
         let group_state = GroupState {
-            data: group_snapshot.mls_encode_to_vec()?,
+            data: self
+                .seal(group_id, StoredBlob::GroupState, counter, data)
+                .await?,
             id: group_snapshot.state.context.group_id,
         };
 
@@ -217,6 +338,10 @@ where
             .await
             .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
 
+        if self.integrity.is_some() {
+            commit_counter(&mut self.storage, &self.group_id, counter).await?;
+        }
+
         if let Some(ref key_package_ref) = self.pending_key_package_removal {
             self.key_package_repo
                 .delete(key_package_ref)
@@ -230,7 +355,55 @@ where
         Ok(())
     }
 
-    #[cfg(any(feature = "psk", feature = "private_message"))]
+    /// Delete the prior epochs with an id lower than `epoch_id` from storage
+    /// and from the pending changes. Returns `false` if the storage does not
+    /// support deletion on request, in which case nothing is deleted.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn delete_epochs_under(&mut self, epoch_id: u64) -> Result<bool, MlsError> {
+        let deleted = self
+            .storage
+            .delete_epochs_under(&self.group_id, epoch_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        if deleted {
+            while self
+                .pending_commit
+                .inserts
+                .front()
+                .map_or(false, |e| e.epoch_id() < epoch_id)
+            {
+                self.pending_commit.inserts.pop_front();
+            }
+
+            self.pending_commit
+                .updates
+                .retain(|e| e.epoch_id() >= epoch_id);
+        }
+
+        Ok(deleted)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn epoch_records<'a>(
+        &self,
+        group_id: &[u8],
+        counter: u64,
+        epochs: impl Iterator<Item = &'a PriorEpoch>,
+    ) -> Result<Vec<EpochRecord>, MlsError> {
+        let mut records = Vec::new();
+
+        for epoch in epochs {
+            let data = epoch.mls_encode_to_vec()?;
+            let blob = StoredBlob::Epoch(epoch.epoch_id());
+            let data = self.seal(group_id, blob, counter, data).await?;
+
+            records.push(EpochRecord::new(epoch.epoch_id(), data));
+        }
+
+        Ok(records)
+    }
+
     fn find_pending(&self, epoch_id: u64) -> Option<usize> {
         self.pending_commit
             .updates
@@ -242,10 +415,13 @@ where
 #[cfg(test)]
 mod tests {
     use alloc::vec;
-    use mls_rs_codec::MlsEncode;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::{MlsEncode, MlsSize};
+    use mls_rs_core::crypto::CryptoProvider;
 
     use crate::{
         client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
         group::{
             epoch::{test_utils::get_test_epoch_with_id, SenderDataSecret},
             test_utils::{random_bytes, test_member, TEST_GROUP},
@@ -256,9 +432,13 @@ mod tests {
 
     use super::*;
 
-    fn test_group_state_repo(
-        retention_limit: usize,
-    ) -> GroupStateRepository<InMemoryGroupStateStorage, InMemoryKeyPackageStorage> {
+    type TestGroupStateRepository = GroupStateRepository<
+        InMemoryGroupStateStorage,
+        InMemoryKeyPackageStorage,
+        <TestCryptoProvider as CryptoProvider>::CipherSuiteProvider,
+    >;
+
+    fn test_group_state_repo(retention_limit: usize) -> TestGroupStateRepository {
         GroupStateRepository::new(
             TEST_GROUP.to_vec(),
             InMemoryGroupStateStorage::new()
@@ -279,6 +459,120 @@ mod tests {
         crate::group::snapshot::test_utils::get_test_snapshot(TEST_CIPHER_SUITE, epoch_id).await
     }
 
+    fn ratchet_states_len(epoch: &PriorEpoch) -> usize {
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        return epoch.secrets.secret_tree.ratchet_states().mls_encoded_len();
+        #[cfg(not(any(feature = "secret_tree_access", feature = "private_message")))]
+        return 0;
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stored_epoch_with_ratchet_states_is_read() {
+        use crate::group::secret_tree::{KeyType, RatchetDerivation};
+        use mls_rs_codec::MlsDecode;
+
+        let mut epoch = test_epoch(0);
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        epoch
+            .secrets
+            .secret_tree
+            .next_message_key(&cs, 0, KeyType::Application, RatchetDerivation::Tree)
+            .await
+            .unwrap();
+
+        assert_ne!(
+            epoch.secrets.secret_tree.ratchet_states(),
+            Default::default()
+        );
+
+        let record = epoch.mls_encode_to_vec().unwrap();
+        assert_eq!(PriorEpoch::from_record(&record).unwrap(), epoch);
+        assert_eq!(PriorEpoch::mls_decode(&mut &*record).unwrap(), epoch);
+
+        // Records written before the ratchet states were appended are read
+        // with the ratchet states left empty.
+        let legacy = &record[..record.len() - ratchet_states_len(&epoch)];
+        let decoded = PriorEpoch::from_record(legacy).unwrap();
+
+        assert_eq!(
+            decoded.secrets.secret_tree.ratchet_states(),
+            Default::default()
+        );
+    }
+
+    #[test]
+    fn stored_epoch_without_retirement_time_is_read() {
+        let epoch = PriorEpoch {
+            retired_at: Some(42),
+            ..test_epoch(0)
+        };
+
+        let record = epoch.mls_encode_to_vec().unwrap();
+        assert_eq!(PriorEpoch::from_record(&record).unwrap(), epoch);
+
+        // Layout of records written before the retirement time was appended
+        let appended_len = epoch.retired_at.mls_encoded_len()
+            + epoch.credentials.mls_encoded_len()
+            + ratchet_states_len(&epoch);
+
+        let legacy = &record[..record.len() - appended_len];
+
+        assert_eq!(
+            PriorEpoch::from_record(legacy).unwrap(),
+            PriorEpoch {
+                retired_at: None,
+                ..epoch
+            }
+        );
+    }
+
+    #[cfg(all(
+        feature = "by_ref_proposal",
+        feature = "psk",
+        feature = "private_message"
+    ))]
+    #[test]
+    fn stored_epoch_of_baseline_layout_is_read() {
+        let legacy = include_bytes!("../../test_data/prior_epoch_v1.mls");
+
+        let epoch = PriorEpoch::from_record(legacy).unwrap();
+
+        assert_eq!(epoch.group_id(), b"group");
+        assert_eq!(epoch.epoch_id(), 0);
+        assert!(epoch.signature_public_keys[0].is_some());
+        assert!(epoch.epoch_authenticator.is_empty());
+        assert_eq!(epoch.retired_at, None);
+        assert!(epoch.signing_identities().is_none());
+    }
+
+    #[test]
+    fn stored_epoch_without_epoch_authenticator_is_read() {
+        let epoch = PriorEpoch {
+            epoch_authenticator: vec![1u8; 32].into(),
+            ..test_epoch(0)
+        };
+
+        let record = epoch.mls_encode_to_vec().unwrap();
+
+        // Layout of records written before the epoch authenticator was appended
+        let appended_len = mls_rs_codec::byte_vec::mls_encoded_len(&epoch.epoch_authenticator)
+            + epoch.retired_at.mls_encoded_len()
+            + epoch.credentials.mls_encoded_len()
+            + ratchet_states_len(&epoch);
+
+        let legacy = &record[..record.len() - appended_len];
+
+        assert_eq!(
+            PriorEpoch::from_record(legacy).unwrap(),
+            PriorEpoch {
+                epoch_authenticator: Default::default(),
+                ..epoch
+            }
+        );
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_epoch_inserts() {
         let mut test_repo = test_group_state_repo(1);
@@ -334,7 +628,7 @@ mod tests {
 
         let stored = storage.get(TEST_GROUP).unwrap();
 
-        assert_eq!(stored.state_data, snapshot.mls_encode_to_vec().unwrap());
+        assert_eq!(stored.state_data, snapshot.to_bytes().unwrap());
 
         assert_eq!(stored.epoch_data.len(), 1);
 
@@ -402,7 +696,7 @@ mod tests {
 
         let stored = storage.get(TEST_GROUP).unwrap();
 
-        assert_eq!(stored.state_data, snapshot.mls_encode_to_vec().unwrap());
+        assert_eq!(stored.state_data, snapshot.to_bytes().unwrap());
 
         assert_eq!(stored.epoch_data.len(), 1);
 
@@ -556,7 +850,7 @@ mod tests {
 
         key_package_repo.insert(id, data);
 
-        let mut repo = GroupStateRepository::new(
+        let mut repo = TestGroupStateRepository::new(
             TEST_GROUP.to_vec(),
             InMemoryGroupStateStorage::new(),
             key_package_repo,
@@ -570,4 +864,43 @@ mod tests {
 
         assert!(repo.key_package_repo.get(&key_package.reference).is_none());
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sealed_epochs_are_verified_on_access() {
+        let integrity = StateIntegrity::new(
+            vec![7; 32].into(),
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+        );
+
+        let mut repo = test_group_state_repo(2).with_integrity(Some(integrity));
+
+        let epoch_0 = test_epoch(0);
+
+        repo.insert(epoch_0.clone()).await.unwrap();
+        repo.insert(test_epoch(1)).await.unwrap();
+        repo.write_to_storage(test_snapshot(1).await).await.unwrap();
+
+        let stored = repo.storage.epoch(TEST_GROUP, 0).await.unwrap().unwrap();
+        assert_ne!(stored, epoch_0.mls_encode_to_vec().unwrap());
+        let epoch = repo.get_epoch(0).await.unwrap();
+        assert_eq!(epoch, Some(epoch_0));
+
+        // Put the sealed epoch 0 in place of epoch 1
+        let swapped = EpochRecord::new(1, stored);
+
+        repo.storage
+            .write(
+                GroupState {
+                    id: TEST_GROUP.to_vec(),
+                    data: vec![],
+                },
+                vec![],
+                vec![swapped],
+            )
+            .await
+            .unwrap();
+
+        let res = repo.get_epoch(1).await;
+        assert_matches!(res, Err(MlsError::StorageIntegrityViolation));
+    }
 }
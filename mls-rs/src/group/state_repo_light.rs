@@ -6,49 +6,115 @@ use crate::client::MlsError;
 use crate::key_package::KeyPackageRef;
 
 use alloc::vec::Vec;
-use mls_rs_codec::MlsEncode;
+use core::fmt::{self, Debug};
 use mls_rs_core::{
+    crypto::CipherSuiteProvider,
     error::IntoAnyError,
     group::{GroupState, GroupStateStorage},
     key_package::KeyPackageStorage,
 };
 
-use super::snapshot::Snapshot;
+use super::{
+    snapshot::Snapshot,
+    state_integrity::{commit_counter, next_counter, StateIntegrity, StoredBlob},
+};
 
-#[derive(Debug, Clone)]
-pub(crate) struct GroupStateRepository<S, K>
+#[derive(Clone)]
+pub(crate) struct GroupStateRepository<S, K, P>
 where
     S: GroupStateStorage,
     K: KeyPackageStorage,
+    P: CipherSuiteProvider,
 {
     pending_key_package_removal: Option<KeyPackageRef>,
     storage: S,
     key_package_repo: K,
+    integrity: Option<StateIntegrity<P>>,
+}
+
+impl<S, K, P> Debug for GroupStateRepository<S, K, P>
+where
+    S: GroupStateStorage + Debug,
+    K: KeyPackageStorage + Debug,
+    P: CipherSuiteProvider,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupStateRepository")
+            .field(
+                "pending_key_package_removal",
+                &self.pending_key_package_removal,
+            )
+            .field("storage", &self.storage)
+            .field("key_package_repo", &self.key_package_repo)
+            .field("integrity", &self.integrity.is_some())
+            .finish()
+    }
 }
 
-impl<S, K> GroupStateRepository<S, K>
+impl<S, K, P> GroupStateRepository<S, K, P>
 where
     S: GroupStateStorage,
     K: KeyPackageStorage,
+    P: CipherSuiteProvider,
 {
     pub fn new(
         storage: S,
         key_package_repo: K,
         // Set to `None` if restoring from snapshot; set to `Some` when joining a group.
         key_package_to_remove: Option<KeyPackageRef>,
-    ) -> Result<GroupStateRepository<S, K>, MlsError> {
+    ) -> Result<GroupStateRepository<S, K, P>, MlsError> {
         Ok(GroupStateRepository {
             storage,
             pending_key_package_removal: key_package_to_remove,
             key_package_repo,
+            integrity: None,
         })
     }
 
+    /// Seal the group state written to storage with `integrity`.
+    pub fn with_integrity(self, integrity: Option<StateIntegrity<P>>) -> Self {
+        Self { integrity, ..self }
+    }
+
+    /// Copy of this repository, including changes not written yet, that
+    /// writes to `storage` and never deletes the key package used to join.
+    pub fn with_storage<S2, K2>(
+        &self,
+        storage: S2,
+        key_package_repo: K2,
+    ) -> GroupStateRepository<S2, K2, P>
+    where
+        S2: GroupStateStorage,
+        K2: KeyPackageStorage,
+        P: Clone,
+    {
+        GroupStateRepository {
+            pending_key_package_removal: None,
+            storage,
+            key_package_repo,
+            integrity: self.integrity.clone(),
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
+        let group_id = group_snapshot.state.context.group_id.clone();
+        let mut data = group_snapshot.to_bytes()?;
+        let mut counter = None;
+
+        if let Some(integrity) = &self.integrity {
+            let next = next_counter(&self.storage, &group_id).await?;
+
+            data = integrity
+                .seal(&group_id, StoredBlob::GroupState, next, data)
+                .await?;
+
+            counter = Some(next);
+        }
+
         let group_state = GroupState {
-            data: group_snapshot.mls_encode_to_vec()?,
-            id: group_snapshot.state.context.group_id,
+            data,
+            id: group_id.clone(),
         };
 
         self.storage
@@ -56,6 +122,10 @@ where
             .await
             .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
 
+        if let Some(counter) = counter {
+            commit_counter(&mut self.storage, &group_id, counter).await?;
+        }
+
         if let Some(ref key_package_ref) = self.pending_key_package_removal {
             self.key_package_repo
                 .delete(key_package_ref)
@@ -71,6 +141,7 @@ where
 mod tests {
     use crate::{
         client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::TestCryptoProvider,
         group::{
             snapshot::{test_utils::get_test_snapshot, Snapshot},
             test_utils::{test_member, TEST_GROUP},
@@ -79,9 +150,16 @@ mod tests {
     };
 
     use alloc::vec;
+    use mls_rs_core::crypto::CryptoProvider;
 
     use super::GroupStateRepository;
 
+    type TestGroupStateRepository = GroupStateRepository<
+        InMemoryGroupStateStorage,
+        InMemoryKeyPackageStorage,
+        <TestCryptoProvider as CryptoProvider>::CipherSuiteProvider,
+    >;
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn test_snapshot(epoch_id: u64) -> Snapshot {
         get_test_snapshot(TEST_CIPHER_SUITE, epoch_id).await
@@ -89,7 +167,7 @@ mod tests {
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_stored_groups_list() {
-        let mut test_repo = GroupStateRepository::new(
+        let mut test_repo = TestGroupStateRepository::new(
             InMemoryGroupStateStorage::default(),
             InMemoryKeyPackageStorage::default(),
             None,
@@ -116,7 +194,7 @@ mod tests {
 
         key_package_repo.insert(id, data);
 
-        let mut repo = GroupStateRepository::new(
+        let mut repo = TestGroupStateRepository::new(
             InMemoryGroupStateStorage::default(),
             key_package_repo,
             Some(key_package.reference.clone()),
@@ -0,0 +1,360 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Keys shared by a subset of the members of a group.
+//!
+//! A subset key lets some members of a group exchange data that the other
+//! members can't read, for example in a thread limited to the members
+//! mentioned in it, without creating an MLS group for the subset. The creator
+//! encrypts a fresh seed with HPKE to the leaf key of every other member of
+//! the subset and signs the result. Each member combines the seed with a
+//! secret exported from the epoch, so that the key is bound to the group,
+//! the epoch and the subset. The key does not change when the group advances
+//! and must be rotated by creating a new one once a member of the subset
+//! leaves the group.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::crypto::HpkeCiphertext;
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        signed_seed::{Seed, SeedContext, SeedTBS, SignedSeed},
+        Group, MemberUid,
+    },
+    signer::Signable,
+    tree_kem::node::LeafIndex,
+};
+
+const SUBSET_KEY_EXPORTER_LABEL: &[u8] = b"subset key";
+
+/// Shares of a [`SubsetKey`] sent by its creator to the other members of the
+/// subset, which accept it with [`Group::accept_subset_key`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct SubsetKeyShares {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    creator_index: LeafIndex,
+    members: Vec<LeafIndex>,
+    /// Seed encrypted to each member other than the creator, in the order of
+    /// `members`.
+    shares: Vec<HpkeCiphertext>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for SubsetKeyShares {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubsetKeyShares")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("creator_index", &self.creator_index)
+            .field("members", &self.members)
+            .finish()
+    }
+}
+
+impl SubsetKeyShares {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf index of the member that created the key.
+    pub fn creator_index(&self) -> u32 {
+        *self.creator_index
+    }
+
+    /// Leaf indexes of the members of the subset, including the creator.
+    pub fn members(&self) -> Vec<u32> {
+        self.members.iter().map(|index| **index).collect()
+    }
+
+    fn context(&self) -> SeedContext<'_, [LeafIndex]> {
+        SeedContext {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            sender_index: self.creator_index,
+            recipients: &self.members,
+        }
+    }
+}
+
+impl<'a> Signable<'a> for SubsetKeyShares {
+    const SIGN_LABEL: &'static str = "SubsetKeySharesTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        SeedTBS {
+            context: &self.context(),
+            ciphertexts: &self.shares,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl SignedSeed for SubsetKeyShares {
+    const ENCRYPT_LABEL: &'static str = "SubsetKeySeed";
+    const EXPORTER_LABEL: &'static [u8] = SUBSET_KEY_EXPORTER_LABEL;
+
+    fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    fn sender_index(&self) -> LeafIndex {
+        self.creator_index
+    }
+
+    fn encoded_context(&self) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        self.context().mls_encode_to_vec()
+    }
+}
+
+/// Key shared by a subset of the members of a group, created with
+/// [`Group::create_subset_key`] or [`Group::accept_subset_key`].
+#[derive(Clone)]
+pub struct SubsetKey {
+    secret: Zeroizing<Vec<u8>>,
+    epoch: u64,
+    members: Vec<(u32, MemberUid)>,
+}
+
+impl Debug for SubsetKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubsetKey")
+            .field("epoch", &self.epoch)
+            .field("members", &self.members())
+            .finish()
+    }
+}
+
+impl SubsetKey {
+    /// Secret shared by the members of the subset, of the size of the output
+    /// of the KDF of the cipher suite.
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Epoch in which the key was created.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf indexes of the members of the subset when the key was created.
+    pub fn members(&self) -> Vec<u32> {
+        self.members.iter().map(|(index, _)| *index).collect()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`SubsetKey`] shared by the local member and the members at
+    /// `members`.
+    ///
+    /// The returned [`SubsetKeyShares`] must be delivered to the other
+    /// members of the subset, which accept it with
+    /// [`Group::accept_subset_key`] in the same epoch.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn create_subset_key(
+        &self,
+        members: &[u32],
+    ) -> Result<(SubsetKey, SubsetKeyShares), MlsError> {
+        let creator_index = self.private_tree.self_index;
+
+        let mut members = members
+            .iter()
+            .copied()
+            .map(LeafIndex)
+            .chain([creator_index])
+            .collect::<Vec<_>>();
+
+        members.sort_unstable();
+        members.dedup();
+
+        let context = SeedContext {
+            group_id: self.group_id(),
+            epoch: self.current_epoch(),
+            sender_index: creator_index,
+            recipients: members.as_slice(),
+        }
+        .mls_encode_to_vec()?;
+
+        let seed = self.generate_seed()?;
+        let mut shares = Vec::with_capacity(members.len() - 1);
+
+        for member in members.iter().filter(|index| **index != creator_index) {
+            shares.push(self.encrypt_seed(&seed, &context, *member).await?);
+        }
+
+        let mut key_shares = SubsetKeyShares {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            creator_index,
+            members,
+            shares,
+            signature: Vec::new(),
+        };
+
+        key_shares
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        let key = self.subset_key(&key_shares, &seed).await?;
+
+        Ok((key, key_shares))
+    }
+
+    /// Accept a [`SubsetKey`] created by another member with
+    /// [`Group::create_subset_key`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn accept_subset_key(&self, shares: &SubsetKeyShares) -> Result<SubsetKey, MlsError> {
+        self.check_seed_epoch(shares)?;
+
+        let self_index = self.private_tree.self_index;
+
+        let recipients = shares
+            .members
+            .iter()
+            .filter(|index| **index != shares.creator_index)
+            .collect::<Vec<_>>();
+
+        let position = recipients
+            .iter()
+            .position(|index| **index == self_index)
+            .filter(|_| recipients.len() == shares.shares.len())
+            .ok_or(MlsError::SubsetMembershipMismatch)?;
+
+        let seed = self.open_seed(shares, &shares.shares[position]).await?;
+
+        self.subset_key(shares, &seed).await
+    }
+
+    /// Whether a member of the subset of `key` left the group since the key
+    /// was created, in which case a new key should be created for the
+    /// remaining members.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn subset_key_requires_rotation(&self, key: &SubsetKey) -> bool {
+        key.members
+            .iter()
+            .any(|(index, uid)| self.member_uid(*index) != Some(uid))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn subset_key(
+        &self,
+        shares: &SubsetKeyShares,
+        seed: &Seed<SubsetKeyShares>,
+    ) -> Result<SubsetKey, MlsError> {
+        let secret = self.seed_secret(shares, seed).await?;
+
+        let members = shares
+            .members
+            .iter()
+            .map(|index| {
+                self.member_uid(**index)
+                    .map(|uid| (**index, uid.clone()))
+                    .ok_or(MlsError::SubsetMembershipMismatch)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(SubsetKey {
+            secret,
+            epoch: shares.epoch,
+            members,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::client::MlsError;
+    use crate::group::test_utils::test_group;
+
+    use super::SubsetKeyShares;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn subset_key_is_shared_by_subset_only() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut carol, commit) = alice.join("carol").await;
+        let (_dave, commit2) = alice.join("dave").await;
+
+        bob.process_incoming_message(commit).await.unwrap();
+        bob.process_incoming_message(commit2.clone()).await.unwrap();
+        carol.process_incoming_message(commit2).await.unwrap();
+
+        let (alice_key, shares) = alice.create_subset_key(&[1, 3]).await.unwrap();
+        let shares = SubsetKeyShares::from_bytes(&shares.to_bytes().unwrap()).unwrap();
+        assert_eq!(shares.members(), vec![0, 1, 3]);
+
+        let bob_key = bob.accept_subset_key(&shares).await.unwrap();
+        assert_eq!(bob_key.secret(), alice_key.secret());
+
+        let res = carol.accept_subset_key(&shares).await;
+        assert_matches!(res, Err(MlsError::SubsetMembershipMismatch));
+
+        assert!(!alice.subset_key_requires_rotation(&alice_key));
+
+        let commit = alice
+            .commit_builder()
+            .remove_member(3)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        assert!(alice.subset_key_requires_rotation(&alice_key));
+        assert!(bob.subset_key_requires_rotation(&bob_key));
+
+        let res = bob.accept_subset_key(&shares).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+}
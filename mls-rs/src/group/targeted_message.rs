@@ -0,0 +1,484 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Targeted messages from [The Messaging Layer Security (MLS)
+//! Extensions][1].
+//!
+//! A targeted message carries application data from one member of a group to
+//! a single other member. The data is encrypted with HPKE to the leaf key of
+//! the recipient and signed by the sender. Instead of the HPKE PSK mode of the
+//! draft, the HPKE context includes a secret exported from the current epoch,
+//! which binds the message to the epoch and to the members of the group.
+//!
+//! [1]: https://datatracker.ietf.org/doc/html/draft-ietf-mls-extensions-04
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::HpkeCiphertext, error::IntoAnyError};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::{ApplicationData, MlsMessagePayload},
+        key_schedule::kdf_expand_with_label,
+        message_processor::ApplicationMessageDescription,
+        Group,
+    },
+    signer::Signable,
+    tree_kem::{hpke_encryption::HpkeEncryptable, node::LeafIndex},
+    CipherSuiteProvider, MlsMessage,
+};
+
+const SENDER_AUTH_DATA_EXPORTER_LABEL: &[u8] = b"targeted message sender auth data";
+const PSK_EXPORTER_LABEL: &[u8] = b"targeted message psk";
+
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub(crate) struct TargetedMessage {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub group_id: Vec<u8>,
+    pub epoch: u64,
+    pub recipient_leaf_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub authenticated_data: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub encrypted_sender_auth_data: Vec<u8>,
+    pub hpke_ciphertext: HpkeCiphertext,
+}
+
+impl Debug for TargetedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetedMessage")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("recipient_leaf_index", &self.recipient_leaf_index)
+            .field(
+                "authenticated_data",
+                &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
+            )
+            .field(
+                "encrypted_sender_auth_data",
+                &mls_rs_core::debug::pretty_bytes(&self.encrypted_sender_auth_data),
+            )
+            .field("hpke_ciphertext", &self.hpke_ciphertext)
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+struct TargetedMessageSenderAuthData {
+    sender_leaf_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct TargetedMessageContext<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    recipient_leaf_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    authenticated_data: &'a [u8],
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct TargetedMessageHpkeContext<'a> {
+    context: &'a TargetedMessageContext<'a>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    psk: &'a [u8],
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct TargetedMessageTBS<'a> {
+    context: &'a TargetedMessageContext<'a>,
+    sender_leaf_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    application_data: &'a [u8],
+}
+
+struct SenderAuthDataKey {
+    key: Zeroizing<Vec<u8>>,
+    nonce: Zeroizing<Vec<u8>>,
+}
+
+struct TargetedMessageContent<'a> {
+    context: &'a TargetedMessageContext<'a>,
+    sender_leaf_index: LeafIndex,
+    application_data: &'a [u8],
+    signature: Vec<u8>,
+}
+
+impl<'a> Signable<'a> for TargetedMessageContent<'_> {
+    const SIGN_LABEL: &'static str = "TargetedMessageTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        TargetedMessageTBS {
+            context: self.context,
+            sender_leaf_index: self.sender_leaf_index,
+            application_data: self.application_data,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl HpkeEncryptable for ApplicationData {
+    const ENCRYPT_LABEL: &'static str = "TargetedMessageData";
+
+    fn from_bytes(mut bytes: Zeroizing<Vec<u8>>) -> Result<Self, MlsError> {
+        Ok(core::mem::take(&mut *bytes).into())
+    }
+
+    fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        Ok(self.as_bytes().to_vec())
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt an application message that only the member at
+    /// `recipient_index` can decrypt.
+    ///
+    /// The message is bound to the current epoch and is received by the
+    /// recipient as [`ReceivedMessage::TargetedMessage`](crate::group::ReceivedMessage::TargetedMessage).
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the message.
+    ///
+    /// The message is encrypted with HPKE to the leaf key of the recipient
+    /// and signed by this member, so that other members of the group can't
+    /// read it but the recipient can authenticate its sender.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::CommitRequired`] if proposals are pending, as
+    /// for [`Group::encrypt_application_message`], and
+    /// [`MlsError::InvalidNodeIndex`] if no member occupies the leaf at
+    /// `recipient_index`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_targeted_message(
+        &mut self,
+        recipient_index: u32,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        // A group member that has observed one or more proposals within an epoch MUST send a Commit message
+        // before sending application data
+        #[cfg(feature = "by_ref_proposal")]
+        if !self.state.proposals.is_empty() {
+            return Err(MlsError::CommitRequired);
+        }
+
+        self.check_not_receive_only()?;
+
+        let recipient_leaf_index = LeafIndex(recipient_index);
+
+        let recipient = self
+            .current_epoch_tree()
+            .get_leaf_node(recipient_leaf_index)?;
+
+        let context = TargetedMessageContext {
+            group_id: self.group_id(),
+            epoch: self.current_epoch(),
+            recipient_leaf_index,
+            authenticated_data: &authenticated_data,
+        };
+
+        let sender_leaf_index = self.private_tree.self_index;
+
+        let mut content = TargetedMessageContent {
+            context: &context,
+            sender_leaf_index,
+            application_data: message,
+            signature: Vec::new(),
+        };
+
+        content
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        let psk = self.targeted_message_psk().await?;
+
+        let hpke_context = TargetedMessageHpkeContext {
+            context: &context,
+            psk: &psk,
+        }
+        .mls_encode_to_vec()?;
+
+        let hpke_ciphertext = ApplicationData::from(message.to_vec())
+            .encrypt(
+                &self.cipher_suite_provider,
+                &recipient.public_key,
+                &hpke_context,
+            )
+            .await?;
+
+        let sender_auth_data = TargetedMessageSenderAuthData {
+            sender_leaf_index,
+            signature: content.signature,
+        };
+
+        let SenderAuthDataKey { key, nonce } = self
+            .sender_auth_data_key(&hpke_ciphertext.kem_output)
+            .await?;
+
+        let encrypted_sender_auth_data = self
+            .cipher_suite_provider
+            .aead_seal(
+                &key,
+                &sender_auth_data.mls_encode_to_vec()?,
+                Some(&context.mls_encode_to_vec()?),
+                &nonce,
+            )
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let targeted_message = TargetedMessage {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            recipient_leaf_index,
+            authenticated_data,
+            encrypted_sender_auth_data,
+            hpke_ciphertext,
+        };
+
+        Ok(MlsMessage::new(
+            self.protocol_version(),
+            MlsMessagePayload::Targeted(targeted_message),
+        ))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn decrypt_targeted_message(
+        &self,
+        message: TargetedMessage,
+    ) -> Result<ApplicationMessageDescription, MlsError> {
+        if message.group_id != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if message.epoch != self.current_epoch() {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        if message.recipient_leaf_index != self.private_tree.self_index {
+            return Err(MlsError::TargetedMessageRecipientMismatch);
+        }
+
+        let context = TargetedMessageContext {
+            group_id: &message.group_id,
+            epoch: message.epoch,
+            recipient_leaf_index: message.recipient_leaf_index,
+            authenticated_data: &message.authenticated_data,
+        };
+
+        let SenderAuthDataKey { key, nonce } = self
+            .sender_auth_data_key(&message.hpke_ciphertext.kem_output)
+            .await?;
+
+        let sender_auth_data = self
+            .cipher_suite_provider
+            .aead_open(
+                &key,
+                &message.encrypted_sender_auth_data,
+                Some(&context.mls_encode_to_vec()?),
+                &nonce,
+            )
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let sender_auth_data =
+            TargetedMessageSenderAuthData::mls_decode(&mut sender_auth_data.as_slice())?;
+
+        if sender_auth_data.sender_leaf_index == self.private_tree.self_index {
+            return Err(MlsError::CantProcessMessageFromSelf);
+        }
+
+        let secret_key = self
+            .private_tree
+            .secret_keys
+            .first()
+            .and_then(Option::as_ref)
+            .ok_or(MlsError::InvalidTreeKemPrivateKey)?;
+
+        let public_key = &self.current_user_leaf_node()?.public_key;
+
+        let psk = self.targeted_message_psk().await?;
+
+        let hpke_context = TargetedMessageHpkeContext {
+            context: &context,
+            psk: &psk,
+        }
+        .mls_encode_to_vec()?;
+
+        let data = ApplicationData::decrypt(
+            &self.cipher_suite_provider,
+            secret_key,
+            public_key,
+            &hpke_context,
+            &message.hpke_ciphertext,
+        )
+        .await?;
+
+        let sender = self
+            .current_epoch_tree()
+            .get_leaf_node(sender_auth_data.sender_leaf_index)?;
+
+        TargetedMessageContent {
+            context: &context,
+            sender_leaf_index: sender_auth_data.sender_leaf_index,
+            application_data: &data,
+            signature: sender_auth_data.signature,
+        }
+        .verify(
+            &self.cipher_suite_provider,
+            &sender.signing_identity.signature_key,
+            &(),
+        )
+        .await?;
+
+        Ok(ApplicationMessageDescription {
+            sender_index: *sender_auth_data.sender_leaf_index,
+            data,
+            authenticated_data: message.authenticated_data,
+            sender_quarantined: self.is_member_quarantined(*sender_auth_data.sender_leaf_index),
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn targeted_message_psk(&self) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let psk = self
+            .export_secret(
+                PSK_EXPORTER_LABEL,
+                &[],
+                self.cipher_suite_provider.kdf_extract_size(),
+            )
+            .await?;
+
+        Ok(Zeroizing::new(psk.as_bytes().to_vec()))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn sender_auth_data_key(&self, kem_output: &[u8]) -> Result<SenderAuthDataKey, MlsError> {
+        let cs = &self.cipher_suite_provider;
+
+        let secret = self
+            .export_secret(SENDER_AUTH_DATA_EXPORTER_LABEL, &[], cs.kdf_extract_size())
+            .await?;
+
+        let key = kdf_expand_with_label(
+            cs,
+            secret.as_bytes(),
+            b"key",
+            kem_output,
+            Some(cs.aead_key_size()),
+        )
+        .await?;
+
+        let nonce = kdf_expand_with_label(
+            cs,
+            secret.as_bytes(),
+            b"nonce",
+            kem_output,
+            Some(cs.aead_nonce_size()),
+        )
+        .await?;
+
+        Ok(SenderAuthDataKey { key, nonce })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_is_only_readable_by_recipient() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut carol, commit) = alice.join("carol").await;
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let message = alice
+            .encrypt_targeted_message(2, b"for carol", b"ad".to_vec())
+            .await
+            .unwrap();
+
+        let received = carol
+            .process_incoming_message(message.clone())
+            .await
+            .unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::TargetedMessage(m)
+                if m.sender_index == 0 && m.data() == b"for carol" && m.authenticated_data == b"ad"
+        );
+
+        let res = bob.process_incoming_message(message).await;
+        assert_matches!(res, Err(MlsError::TargetedMessageRecipientMismatch));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_is_bound_to_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut message = alice
+            .encrypt_targeted_message(1, b"for bob", Vec::new())
+            .await
+            .unwrap();
+
+        let crate::group::framing::MlsMessagePayload::Targeted(targeted) = &mut message.payload
+        else {
+            panic!("expected targeted message");
+        };
+
+        targeted.authenticated_data = b"tampered".to_vec();
+
+        let res = bob.process_incoming_message(message).await;
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+
+        let message = alice
+            .encrypt_targeted_message(1, b"for bob", Vec::new())
+            .await
+            .unwrap();
+
+        let commit = alice.commit(Vec::new()).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit).await.unwrap();
+
+        let res = bob.process_incoming_message(message).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+}
@@ -61,7 +61,10 @@ impl TestGroup {
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn update_proposal(&mut self) -> Proposal {
-        self.group.update_proposal(None, None, None).await.unwrap()
+        self.group
+            .update_proposal(None, None, None, None)
+            .await
+            .unwrap()
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -219,6 +222,7 @@ pub(crate) async fn test_member(
         cipher_suite_provider: &test_cipher_suite_provider(cipher_suite),
         signing_identity: &signing_identity,
         signing_key: &signing_key,
+        grease: true,
     };
 
     let key_package = key_package_generator
@@ -504,6 +508,15 @@ impl MessageProcessor for GroupWithoutKeySchedule {
         self.inner.process_ciphertext(cipher_text).await
     }
 
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(coverage_nightly, coverage(off))]
+    async fn process_targeted_message(
+        &mut self,
+        message: TargetedMessage,
+    ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
+        self.inner.process_targeted_message(message).await
+    }
+
     #[cfg_attr(coverage_nightly, coverage(off))]
     async fn verify_plaintext_authentication(
         &self,
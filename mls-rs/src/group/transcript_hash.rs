@@ -19,7 +19,7 @@ use crate::{
     WireFormat,
 };
 
-use super::{AuthenticatedContent, ConfirmationTag};
+use super::{AuthenticatedContent, ConfirmationTag, GroupState};
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn create<P: CipherSuiteProvider>(
@@ -105,6 +105,72 @@ impl InterimTranscriptHash {
     }
 }
 
+/// Transcript hashes and confirmation tag of an epoch.
+///
+/// These identify a point in the history of the group, which lets
+/// applications bind exported data, such as entries of an audit log, to the
+/// epoch in which it was produced. All members of the epoch compute the same
+/// values.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EpochTranscript {
+    epoch: u64,
+    confirmed_transcript_hash: Vec<u8>,
+    interim_transcript_hash: Vec<u8>,
+    confirmation_tag: Vec<u8>,
+}
+
+impl Debug for EpochTranscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EpochTranscript")
+            .field("epoch", &self.epoch)
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field(
+                "interim_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.interim_transcript_hash),
+            )
+            .field(
+                "confirmation_tag",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmation_tag),
+            )
+            .finish()
+    }
+}
+
+impl EpochTranscript {
+    pub(crate) fn new(state: &GroupState) -> Self {
+        Self {
+            epoch: state.context.epoch,
+            confirmed_transcript_hash: state.context.confirmed_transcript_hash.to_vec(),
+            interim_transcript_hash: state.interim_transcript_hash.to_vec(),
+            confirmation_tag: state.confirmation_tag.to_vec(),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Hash of the transcript of commits up to and including the commit that
+    /// started the epoch.
+    pub fn confirmed_transcript_hash(&self) -> &[u8] {
+        &self.confirmed_transcript_hash
+    }
+
+    /// Hash of the confirmed transcript and the confirmation tag, from which
+    /// the confirmed transcript hash of the next epoch is computed.
+    pub fn interim_transcript_hash(&self) -> &[u8] {
+        &self.interim_transcript_hash
+    }
+
+    /// Confirmation tag of the commit that started the epoch.
+    pub fn confirmation_tag(&self) -> &[u8] {
+        &self.confirmation_tag
+    }
+}
+
 // Test vectors come from the MLS interop repository and contain a proposal by reference.
 #[cfg(feature = "by_ref_proposal")]
 #[cfg(test)]
@@ -0,0 +1,319 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+#[cfg(all(feature = "std", feature = "private_message"))]
+use core::time::Duration;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+#[cfg(feature = "private_message")]
+use crate::client::MlsError;
+use crate::{
+    client_config::ClientConfig,
+    group::{proposal_filter::ProposalBundle, Group, Sender},
+    tree_kem::node::LeafIndex,
+};
+
+#[cfg(feature = "std")]
+use crate::time::MlsTime;
+
+/// Member added to the group that has not sent any message yet.
+///
+/// A member is unconfirmed from the commit adding it until the local member
+/// receives a message, proposal or commit from it. Until then there is no
+/// evidence that the member processed its welcome message.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct UnconfirmedMember {
+    /// Leaf index of the member.
+    pub index: u32,
+    /// Epoch created by the commit adding the member.
+    pub added_in_epoch: u64,
+    /// Time at which the local member processed the commit adding the member,
+    /// in seconds since the Unix epoch. Not available without the `std`
+    /// feature.
+    pub added_at: Option<u64>,
+}
+
+/// Members added by commits processed by the local member that have not
+/// demonstrated liveness yet. This is local state and not part of the group
+/// state agreed upon by members.
+#[derive(Clone, Debug, Default, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct UnconfirmedMembers(Vec<UnconfirmedMember>);
+
+impl UnconfirmedMembers {
+    /// Track the members added at `indexes` by the commit creating `epoch`.
+    /// Leaves emptied by `proposals` are released first, so that a new member
+    /// reusing one of them is tracked from scratch.
+    pub(crate) fn record_commit(
+        &mut self,
+        proposals: &ProposalBundle,
+        indexes: &[LeafIndex],
+        epoch: u64,
+    ) {
+        for removal in proposals.remove_proposals() {
+            self.confirm(removal.proposal.to_remove);
+        }
+
+        #[cfg(feature = "std")]
+        let added_at = Some(MlsTime::now().seconds_since_epoch());
+
+        #[cfg(not(feature = "std"))]
+        let added_at = None;
+
+        self.0.extend(indexes.iter().map(|index| UnconfirmedMember {
+            index: **index,
+            added_in_epoch: epoch,
+            added_at,
+        }));
+    }
+
+    pub(crate) fn confirm_sender(&mut self, sender: &Sender) {
+        if let Sender::Member(index) = sender {
+            self.confirm(LeafIndex(*index));
+        }
+    }
+
+    fn confirm(&mut self, index: LeafIndex) -> bool {
+        let len = self.0.len();
+        self.0.retain(|m| m.index != *index);
+        self.0.len() != len
+    }
+
+    #[cfg(feature = "private_message")]
+    fn first_blocking(&self, policy: &UnconfirmedMemberPolicy) -> Option<&UnconfirmedMember> {
+        #[cfg(feature = "std")]
+        let now = MlsTime::now().seconds_since_epoch();
+
+        self.0.iter().find(|member| {
+            #[cfg(feature = "std")]
+            let timed_out = policy
+                .timeout_seconds
+                .zip(member.added_at)
+                .map_or(false, |(timeout, added_at)| {
+                    now.saturating_sub(added_at) >= timeout
+                });
+
+            #[cfg(not(feature = "std"))]
+            let timed_out = false;
+
+            !timed_out
+        })
+    }
+}
+
+/// Policy blocking application messages while some members of the group are
+/// unconfirmed, see [`Group::unconfirmed_members`].
+///
+/// Sending application messages before added members processed their
+/// welcome message risks that they never receive them, for example if the
+/// welcome message was lost. While the policy is set in
+/// [`GroupConfigOverrides`](crate::group::GroupConfigOverrides),
+/// [`Group::encrypt_application_message`] fails with
+/// [`MlsError::UnconfirmedMembers`] until every added member is confirmed,
+/// removed or timed out.
+#[cfg(feature = "private_message")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct UnconfirmedMemberPolicy {
+    /// Number of seconds after being added after which an unconfirmed member
+    /// no longer blocks application messages. Members never time out if not
+    /// set, or without the `std` feature.
+    pub timeout_seconds: Option<u64>,
+}
+
+#[cfg(feature = "private_message")]
+impl UnconfirmedMemberPolicy {
+    /// Block application messages until every added member is confirmed or
+    /// removed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout_seconds: Some(timeout.as_secs()),
+        }
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Members added by commits processed by the local member that have not
+    /// sent a message, proposal or commit since.
+    ///
+    /// Members are tracked from the epoch in which the local member joined
+    /// the group, and stop being tracked once removed.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn unconfirmed_members(&self) -> &[UnconfirmedMember] {
+        &self.unconfirmed_members.0
+    }
+
+    /// Returns `true` if some members of the group are unconfirmed, see
+    /// [`Group::unconfirmed_members`].
+    pub fn has_unconfirmed_members(&self) -> bool {
+        !self.unconfirmed_members.0.is_empty()
+    }
+
+    /// Confirm the member at leaf `index`, for example after it acknowledged
+    /// its welcome message through another channel. Returns `false` if the
+    /// member was not unconfirmed.
+    pub fn confirm_member(&mut self, index: u32) -> bool {
+        self.unconfirmed_members.confirm(LeafIndex(index))
+    }
+
+    #[cfg(feature = "private_message")]
+    pub(crate) fn check_unconfirmed_member_policy(&self) -> Result<(), MlsError> {
+        let Some(policy) = &self.config_overrides.unconfirmed_member_policy else {
+            return Ok(());
+        };
+
+        match self.unconfirmed_members.first_blocking(policy) {
+            Some(member) => Err(MlsError::UnconfirmedMembers(member.index)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "private_message"))]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, GroupConfigOverrides},
+    };
+
+    use super::UnconfirmedMemberPolicy;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn added_members_are_confirmed_by_their_messages() {
+        let (alice_client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let (bob_client, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let overrides = GroupConfigOverrides::new()
+            .with_unconfirmed_member_policy(UnconfirmedMemberPolicy::new());
+
+        let mut alice = alice_client
+            .create_group_with_overrides(None, Default::default(), Default::default(), overrides)
+            .await
+            .unwrap();
+
+        let commit_output = alice
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (mut bob, _) = bob_client
+            .join_group(None, &commit_output.welcome_messages[0])
+            .await
+            .unwrap();
+
+        assert!(!bob.has_unconfirmed_members());
+        assert_eq!(alice.unconfirmed_members().len(), 1);
+        assert_eq!(alice.unconfirmed_members()[0].index, 1);
+        assert_eq!(alice.unconfirmed_members()[0].added_in_epoch, 1);
+
+        let res = alice.encrypt_application_message(b"hello", vec![]).await;
+        assert_matches!(res, Err(MlsError::UnconfirmedMembers(1)));
+
+        let message = bob
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        alice.process_incoming_message(message).await.unwrap();
+
+        assert!(!alice.has_unconfirmed_members());
+
+        alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removed_and_manually_confirmed_members_are_released() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        for name in ["bob", "carol"] {
+            let (_, key_package) =
+                test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+            alice
+                .commit_builder()
+                .add_member(key_package)
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+
+            alice.apply_pending_commit().await.unwrap();
+        }
+
+        assert_eq!(alice.unconfirmed_members().len(), 2);
+
+        alice
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        assert_eq!(alice.unconfirmed_members().len(), 1);
+        assert!(alice.confirm_member(2));
+        assert!(!alice.confirm_member(2));
+        assert!(!alice.has_unconfirmed_members());
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn timed_out_members_do_not_block() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let policy = UnconfirmedMemberPolicy::new().with_timeout(core::time::Duration::ZERO);
+        alice.config_overrides.unconfirmed_member_policy = Some(policy);
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        assert!(alice.has_unconfirmed_members());
+
+        alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+    }
+}
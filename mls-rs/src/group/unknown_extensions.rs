@@ -0,0 +1,251 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::extension::{ExtensionList, ExtensionType};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    extension::{is_known_extension, UnknownExtensionPolicy},
+    group::{message_processor::ProvisionalState, Group},
+    tree_kem::leaf_node::LeafNode,
+};
+
+/// Extensions retained by a group whose type is not supported by the client.
+///
+/// See [`UnknownExtensionPolicy`] for the extension types that are considered
+/// known.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnknownExtensions {
+    /// Unknown extension types in the group context.
+    pub group_context: Vec<ExtensionType>,
+    /// Unknown extension types in leaf nodes, by leaf index. Leaves without
+    /// unknown extensions are omitted.
+    pub leaf_nodes: Vec<(u32, Vec<ExtensionType>)>,
+}
+
+impl UnknownExtensions {
+    /// Whether no unknown extension is retained by the group.
+    pub fn is_empty(&self) -> bool {
+        self.group_context.is_empty() && self.leaf_nodes.is_empty()
+    }
+}
+
+fn unknown_extension_types(
+    extensions: &ExtensionList,
+    supported_extensions: &[ExtensionType],
+) -> Vec<ExtensionType> {
+    extensions
+        .iter()
+        .map(|ext| ext.extension_type)
+        .filter(|&ext_type| !is_known_extension(ext_type, supported_extensions))
+        .collect()
+}
+
+/// Fail with [`MlsError::UnknownExtension`] if `config` rejects unknown
+/// extensions and any of `extensions` contains one.
+pub(crate) fn reject_unknown_extensions<'a, C, I>(config: &C, extensions: I) -> Result<(), MlsError>
+where
+    C: ClientConfig,
+    I: IntoIterator<Item = &'a ExtensionList>,
+{
+    if config.unknown_extension_policy() != UnknownExtensionPolicy::Reject {
+        return Ok(());
+    }
+
+    let supported_extensions = config.supported_extensions();
+
+    extensions
+        .into_iter()
+        .flat_map(|list| list.iter())
+        .map(|ext| ext.extension_type)
+        .find(|&ext_type| !is_known_extension(ext_type, &supported_extensions))
+        .map_or(Ok(()), |ext_type| Err(MlsError::UnknownExtension(ext_type)))
+}
+
+/// Extension lists introduced by a commit resulting in `provisional_state`.
+pub(crate) fn introduced_extensions<'a>(
+    provisional_state: &'a ProvisionalState,
+    path_leaf: Option<&'a LeafNode>,
+) -> impl Iterator<Item = &'a ExtensionList> {
+    let proposals = &provisional_state.applied_proposals;
+
+    let additions = proposals.additions.iter().flat_map(|p| {
+        [
+            &p.proposal.key_package.extensions,
+            &p.proposal.key_package.leaf_node.extensions,
+        ]
+    });
+
+    #[cfg(feature = "by_ref_proposal")]
+    let additions = additions.chain(
+        proposals
+            .updates
+            .iter()
+            .map(|p| &p.proposal.leaf_node.extensions),
+    );
+
+    core::iter::once(&provisional_state.group_context.extensions)
+        .chain(additions)
+        .chain(path_leaf.map(|leaf| &leaf.extensions))
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Extensions in the group context and in leaf nodes of the current
+    /// epoch whose type is not supported by this client.
+    ///
+    /// Unknown extensions are retained unless the client is configured with
+    /// [`UnknownExtensionPolicy::Reject`], in which case this is always empty.
+    pub fn unknown_extensions(&self) -> UnknownExtensions {
+        let supported_extensions = self.config.supported_extensions();
+
+        let leaf_nodes = self
+            .state
+            .public_tree
+            .non_empty_leaves()
+            .map(|(index, leaf)| {
+                (
+                    *index,
+                    unknown_extension_types(&leaf.extensions, &supported_extensions),
+                )
+            })
+            .filter(|(_, unknown)| !unknown.is_empty())
+            .collect();
+
+        UnknownExtensions {
+            group_context: unknown_extension_types(
+                &self.state.context.extensions,
+                &supported_extensions,
+            ),
+            leaf_nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::extension::{ExtensionList, ExtensionType};
+
+    use crate::{
+        client::{
+            test_utils::{
+                test_client_with_key_pkg_custom, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+            },
+            MlsError,
+        },
+        extension::{
+            test_utils::{TestExtension, TEST_EXTENSION_TYPE},
+            UnknownExtensionPolicy,
+        },
+        group::test_utils::{test_group, test_group_custom_config},
+        MlsMessage,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_package_with_test_extension() -> MlsMessage {
+        let mut leaf_node_extensions = ExtensionList::new();
+        leaf_node_extensions
+            .set_from(TestExtension::from(1))
+            .unwrap();
+
+        test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "bob",
+            Default::default(),
+            leaf_node_extensions,
+            |c| {
+                c.0.settings
+                    .extension_types
+                    .push(TEST_EXTENSION_TYPE.into())
+            },
+        )
+        .await
+        .1
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_extensions_are_retained_by_default() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        assert!(alice.unknown_extensions().is_empty());
+
+        alice
+            .commit_builder()
+            .add_member(key_package_with_test_extension().await)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let unknown = alice.unknown_extensions();
+
+        assert!(unknown.group_context.is_empty());
+
+        assert_eq!(
+            unknown.leaf_nodes,
+            vec![(1, vec![ExtensionType::from(TEST_EXTENSION_TYPE)])]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn strict_committer_rejects_unknown_extensions() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.unknown_extension_policy(UnknownExtensionPolicy::Reject)
+        })
+        .await;
+
+        let res = alice
+            .commit_builder()
+            .add_member(key_package_with_test_extension().await)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::UnknownExtension(t)) if *t == TEST_EXTENSION_TYPE);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn strict_members_reject_unknown_extensions() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut strict, _) = alice
+            .join_with_custom_config("strict", true, |c| {
+                c.0.settings.unknown_extension_policy = UnknownExtensionPolicy::Reject
+            })
+            .await
+            .unwrap();
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_package_with_test_extension().await)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let res = strict.process_incoming_message(commit.commit_message).await;
+
+        assert_matches!(res, Err(MlsError::UnknownExtension(t)) if *t == TEST_EXTENSION_TYPE);
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let res = alice
+            .join_with_custom_config("carol", true, |c| {
+                c.0.settings.unknown_extension_policy = UnknownExtensionPolicy::Reject
+            })
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnknownExtension(t)) if *t == TEST_EXTENSION_TYPE);
+    }
+}
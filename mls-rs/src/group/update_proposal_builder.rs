@@ -0,0 +1,163 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::{crypto::SignatureSecretKey, identity::SigningIdentity};
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::Group, tree_kem::Capabilities,
+    ExtensionList, MlsMessage,
+};
+
+/// Build an update proposal with application chosen leaf node contents.
+///
+/// By default, the new leaf node keeps the current leaf node extensions and
+/// advertises the capabilities of the client configuration, as with
+/// [`Group::propose_update`].
+pub struct UpdateProposalBuilder<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    group: &'a mut Group<C>,
+    authenticated_data: Vec<u8>,
+    new_signer: Option<SignatureSecretKey>,
+    new_signing_identity: Option<SigningIdentity>,
+    leaf_node_extensions: Option<ExtensionList>,
+    capabilities: Option<Capabilities>,
+}
+
+impl<'a, C> UpdateProposalBuilder<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Add additional authenticated data to the proposal.
+    ///
+    /// # Warning
+    ///
+    /// The data provided here is always sent unencrypted.
+    pub fn authenticated_data(self, authenticated_data: Vec<u8>) -> Self {
+        Self {
+            authenticated_data,
+            ..self
+        }
+    }
+
+    /// Change the proposer's signing identity, as with
+    /// [`Group::propose_update_with_identity`].
+    pub fn set_new_signing_identity(
+        self,
+        signer: SignatureSecretKey,
+        signing_identity: SigningIdentity,
+    ) -> Self {
+        Self {
+            new_signer: Some(signer),
+            new_signing_identity: Some(signing_identity),
+            ..self
+        }
+    }
+
+    /// Change the proposer's leaf node extensions.
+    pub fn set_leaf_node_extensions(self, leaf_node_extensions: ExtensionList) -> Self {
+        Self {
+            leaf_node_extensions: Some(leaf_node_extensions),
+            ..self
+        }
+    }
+
+    /// Advertise `capabilities` in the new leaf node instead of the
+    /// capabilities of the client configuration.
+    ///
+    /// This allows a member to advertise support for new extensions,
+    /// proposals or credentials without being added to the group again. The
+    /// new leaf node must still support the
+    /// [required capabilities](crate::extension::built_in::RequiredCapabilitiesExt)
+    /// of the group and its own extensions.
+    pub fn set_capabilities(self, capabilities: Capabilities) -> Self {
+        Self {
+            capabilities: Some(capabilities),
+            ..self
+        }
+    }
+
+    /// Finalize the update proposal to send.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn build(self) -> Result<MlsMessage, MlsError> {
+        let proposal = self
+            .group
+            .update_proposal(
+                self.new_signer,
+                self.new_signing_identity,
+                self.leaf_node_extensions,
+                self.capabilities,
+            )
+            .await?;
+
+        self.group
+            .proposal_message(proposal, self.authenticated_data)
+            .await
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a new update proposal builder that can change the contents of
+    /// this member's leaf node.
+    pub fn update_proposal_builder(&mut self) -> UpdateProposalBuilder<'_, C> {
+        UpdateProposalBuilder {
+            group: self,
+            authenticated_data: Default::default(),
+            new_signer: Default::default(),
+            new_signing_identity: Default::default(),
+            leaf_node_extensions: Default::default(),
+            capabilities: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        extension::test_utils::{TestExtension, TEST_EXTENSION_TYPE},
+        group::test_utils::test_group,
+        ExtensionList,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn update_can_change_leaf_node_extensions_and_capabilities() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut capabilities = alice.current_user_leaf_node().unwrap().capabilities.clone();
+        capabilities.extensions.push(TEST_EXTENSION_TYPE.into());
+
+        let mut extensions = ExtensionList::new();
+        extensions.set_from(TestExtension::from(1)).unwrap();
+
+        let proposal = alice
+            .update_proposal_builder()
+            .set_capabilities(capabilities.clone())
+            .set_leaf_node_extensions(extensions.clone())
+            .authenticated_data(vec![1])
+            .build()
+            .await
+            .unwrap();
+
+        bob.process_message(proposal).await.unwrap();
+
+        let commit = bob.commit(vec![]).await.unwrap().commit_message;
+        bob.apply_pending_commit().await.unwrap();
+        alice.process_message(commit).await.unwrap();
+
+        for group in [&alice, &bob] {
+            let leaf = group.roster().member_with_index(0).unwrap();
+            assert_eq!(leaf.capabilities, capabilities);
+            assert_eq!(leaf.extensions, extensions);
+        }
+    }
+}
@@ -0,0 +1,725 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use mls_rs_core::{
+    crypto::SignatureSecretKey,
+    extension::ExtensionType,
+    group::{Capabilities, ProposalType},
+    identity::{CredentialType, IdentityProvider, MemberValidationContext},
+};
+
+use crate::{
+    cipher_suite::CipherSuite,
+    client::MlsError,
+    client_config::ClientConfig,
+    extension::{MlsExtension, RequiredCapabilitiesExt},
+    grease::is_grease_value,
+    group::{
+        framing::MlsMessagePayload, group_info_verification::verify_group_info,
+        util::cipher_suite_provider, ExportedTree, Group, GroupInfoIssue, GroupInfoTrustConfig,
+    },
+    key_package::validate_key_package_properties,
+    protocol_version::ProtocolVersion,
+    time::MlsTime,
+    tree_kem::{
+        leaf_node::{LeafNode, LeafNodeSource},
+        leaf_node_validator::{LeafNodeValidator, ValidationContext},
+        TreeKemPublic,
+    },
+    CipherSuiteProvider, ExtensionList, KeyPackage, MlsMessage,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::extension::ExternalSendersExt;
+
+/// Capability that a leaf does not list but that the group requires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingCapability {
+    Extension(ExtensionType),
+    Proposal(ProposalType),
+    Credential(CredentialType),
+}
+
+impl Display for MissingCapability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Extension(e) => write!(f, "extension {}", TypeCode(e.raw_value())),
+            Self::Proposal(p) => write!(f, "proposal {}", TypeCode(p.raw_value())),
+            Self::Credential(c) => write!(f, "credential {}", TypeCode(c.raw_value())),
+        }
+    }
+}
+
+/// Problem found by [`Group::diagnose_key_package`] or
+/// [`Client::diagnose_welcome`](crate::Client::diagnose_welcome).
+///
+/// The [`Display`] implementation explains the problem in plain words.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// The protocol version is not supported by the client.
+    UnsupportedProtocolVersion(ProtocolVersion),
+    /// The protocol version differs from the one of the group.
+    ProtocolVersionMismatch {
+        expected: ProtocolVersion,
+        found: ProtocolVersion,
+    },
+    /// The cipher suite is not supported by the crypto provider. No further
+    /// checks are performed.
+    UnsupportedCipherSuite(CipherSuite),
+    /// The cipher suite differs from the one of the group. No further checks
+    /// are performed.
+    CipherSuiteMismatch {
+        expected: CipherSuite,
+        found: CipherSuite,
+    },
+    /// The signature of the key package does not verify.
+    InvalidSignature,
+    /// The signature of the leaf node does not verify.
+    InvalidLeafNodeSignature,
+    /// The init key is not a valid key for the cipher suite.
+    InvalidInitKey,
+    /// The init key is the same as the encryption key of the leaf node.
+    InitKeyEqualsLeafKey,
+    /// The leaf node was not created for a key package.
+    InvalidLeafNodeSource,
+    /// The lifetime ended the given number of seconds ago.
+    LifetimeExpired { seconds: u64 },
+    /// The lifetime starts in the given number of seconds.
+    LifetimeNotYetValid { seconds: u64 },
+    /// The [`IdentityProvider`] rejected the credential.
+    IdentityRejected(MlsError),
+    /// The leaf does not support a capability that an extension of the group
+    /// requires.
+    MissingCapability {
+        capability: MissingCapability,
+        required_by: ExtensionType,
+    },
+    /// The leaf uses an extension that it does not list in its capabilities.
+    ExtensionNotInCapabilities(ExtensionType),
+    /// The group uses an extension that the leaf does not list in its
+    /// capabilities.
+    UnsupportedGroupExtension(ExtensionType),
+    /// The leaf does not support the credential type of a member.
+    MemberCredentialUnsupported,
+    /// A member does not support the credential type of the leaf.
+    CredentialUnsupportedByMember(CredentialType),
+    /// The leaf has the same encryption key, signature key or identity as a
+    /// member.
+    DuplicateMember(u32),
+    /// An extension of the group could not be decoded.
+    InvalidGroupExtension(MlsError),
+    /// None of the key packages of the client matches the welcome.
+    KeyPackageNotFound,
+    /// The group info of the welcome failed verification. Issues that don't
+    /// prevent joining are not reported.
+    InvalidGroupInfo(GroupInfoIssue),
+    /// A validator failed for a reason not covered by the other issues.
+    Rejected(MlsError),
+    /// Joining the group failed for a reason not covered by the other issues.
+    JoinFailed(MlsError),
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedProtocolVersion(v) => {
+                write!(f, "protocol version {} is not supported", v.raw_value())
+            }
+            Self::ProtocolVersionMismatch { expected, found } => write!(
+                f,
+                "protocol version {} differs from the version {} of the group",
+                found.raw_value(),
+                expected.raw_value()
+            ),
+            Self::UnsupportedCipherSuite(cs) => write!(
+                f,
+                "cipher suite {} is not supported by the crypto provider",
+                TypeCode(cs.raw_value())
+            ),
+            Self::CipherSuiteMismatch { expected, found } => write!(
+                f,
+                "cipher suite {} differs from the cipher suite {} of the group",
+                TypeCode(found.raw_value()),
+                TypeCode(expected.raw_value())
+            ),
+            Self::InvalidSignature => f.write_str("the signature of the key package is invalid"),
+            Self::InvalidLeafNodeSignature => {
+                f.write_str("the signature of the leaf node is invalid")
+            }
+            Self::InvalidInitKey => f.write_str("the init key is invalid for the cipher suite"),
+            Self::InitKeyEqualsLeafKey => {
+                f.write_str("the init key is the same as the encryption key of the leaf node")
+            }
+            Self::InvalidLeafNodeSource => {
+                f.write_str("the leaf node was not created for a key package")
+            }
+            Self::LifetimeExpired { seconds } => {
+                write!(f, "the lifetime expired {seconds} seconds ago")
+            }
+            Self::LifetimeNotYetValid { seconds } => {
+                write!(f, "the lifetime starts in {seconds} seconds")
+            }
+            Self::IdentityRejected(e) => {
+                write!(f, "the identity provider rejected the credential: {e:?}")
+            }
+            Self::MissingCapability {
+                capability,
+                required_by,
+            } => write!(
+                f,
+                "capability {capability} is missing but required by extension {}",
+                TypeCode(required_by.raw_value())
+            ),
+            Self::ExtensionNotInCapabilities(e) => write!(
+                f,
+                "extension {} is used but not listed in the capabilities",
+                TypeCode(e.raw_value())
+            ),
+            Self::UnsupportedGroupExtension(e) => write!(
+                f,
+                "extension {} is used by the group but not listed in the capabilities",
+                TypeCode(e.raw_value())
+            ),
+            Self::MemberCredentialUnsupported => {
+                f.write_str("the credential of a member is not listed in the capabilities")
+            }
+            Self::CredentialUnsupportedByMember(credential) => write!(
+                f,
+                "credential {} is not supported by every member",
+                TypeCode(credential.raw_value())
+            ),
+            Self::DuplicateMember(member) => write!(
+                f,
+                "the keys or the identity are the same as the ones of member {member}"
+            ),
+            Self::InvalidGroupExtension(e) => {
+                write!(f, "an extension of the group can't be decoded: {e:?}")
+            }
+            Self::KeyPackageNotFound => {
+                f.write_str("no key package of this client matches the welcome")
+            }
+            Self::InvalidGroupInfo(issue) => write!(f, "the group info is invalid: {issue:?}"),
+            Self::Rejected(e) => write!(f, "validation failed: {e:?}"),
+            Self::JoinFailed(e) => write!(f, "joining the group failed: {e:?}"),
+        }
+    }
+}
+
+/// Type code shown in hex, marked when it is a GREASE value.
+struct TypeCode(u16);
+
+impl Display for TypeCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x}", self.0)?;
+
+        if is_grease_value(self.0) {
+            f.write_str(" (GREASE)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Result of [`Group::diagnose_key_package`] and
+/// [`Client::diagnose_welcome`](crate::Client::diagnose_welcome).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ValidationReport {
+    /// Capabilities of the leaf that was checked, without GREASE values.
+    pub capabilities: Option<Capabilities>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Key package left out of a commit by
+/// [`CommitBuilder::skip_incompatible_members`](crate::group::CommitBuilder::skip_incompatible_members).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SkippedKeyPackage {
+    pub key_package: KeyPackage,
+    /// Reasons for which the key package can't be added.
+    pub report: ValidationReport,
+}
+
+impl ValidationReport {
+    fn new() -> Self {
+        Self {
+            capabilities: None,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Whether no issue was found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return f.write_str("no issue found");
+        }
+
+        self.issues
+            .iter()
+            .try_for_each(|issue| writeln!(f, "- {issue}"))
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Explain why `key_package` can't be added to this group.
+    ///
+    /// The validators applied when a proposal to add `key_package` is
+    /// committed are run, and their errors are collected in the returned
+    /// report instead of failing on the first one. Each validator stops at
+    /// its first error, so fixing the reported issues may reveal others. The
+    /// lifetime of the key package is only checked if `time` is provided. An
+    /// error is only returned if `key_package` is not a key package message.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn diagnose_key_package(
+        &self,
+        key_package: &MlsMessage,
+        time: Option<MlsTime>,
+    ) -> Result<ValidationReport, MlsError> {
+        let key_package = key_package
+            .as_key_package()
+            .ok_or(MlsError::UnexpectedMessageType)?;
+
+        let mut tree = self.state.public_tree.clone();
+
+        Ok(self
+            .diagnose_new_member(key_package, &mut tree, &self.context().extensions, time)
+            .await)
+    }
+
+    /// Validate `key_package` as a new member of `tree` in a group with
+    /// `new_extensions`, and add its leaf to `tree` if no issue is found.
+    ///
+    /// As when a commit is built, the leaf node and the key package are
+    /// validated first, and the leaf is only inserted in the tree, which
+    /// checks it against the other members, if they are valid.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn diagnose_new_member(
+        &self,
+        key_package: &KeyPackage,
+        tree: &mut TreeKemPublic,
+        new_extensions: &ExtensionList,
+        time: Option<MlsTime>,
+    ) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        let leaf = &key_package.leaf_node;
+        report.capabilities = Some(leaf.ungreased_capabilities());
+
+        let issues = &mut report.issues;
+        let context = self.context();
+
+        // Signatures and keys can't be checked with the cipher suite provider
+        // of the group.
+        if key_package.cipher_suite != context.cipher_suite {
+            issues.push(ValidationIssue::CipherSuiteMismatch {
+                expected: context.cipher_suite,
+                found: key_package.cipher_suite,
+            });
+
+            return report;
+        }
+
+        let identity_provider = self.config.identity_provider();
+
+        let member_context = MemberValidationContext::ForCommit {
+            current_context: context,
+            new_extensions,
+        };
+
+        let validator = LeafNodeValidator::new(
+            &self.cipher_suite_provider,
+            &identity_provider,
+            member_context,
+        );
+
+        check_leaf_node(&validator, leaf, time, issues).await;
+
+        let res = validate_key_package_properties(
+            key_package,
+            context.protocol_version,
+            &self.cipher_suite_provider,
+        )
+        .await;
+
+        if let Err(e) = res {
+            issues.push(match e {
+                MlsError::InvalidSignature => ValidationIssue::InvalidSignature,
+                MlsError::ProtocolVersionMismatch => ValidationIssue::ProtocolVersionMismatch {
+                    expected: context.protocol_version,
+                    found: key_package.version,
+                },
+                MlsError::InvalidInitKey => ValidationIssue::InvalidInitKey,
+                MlsError::InitLeafKeyEquality => ValidationIssue::InitKeyEqualsLeafKey,
+                e => ValidationIssue::Rejected(e),
+            });
+        }
+
+        if !issues.is_empty() {
+            return report;
+        }
+
+        let res = tree
+            .add_leaf(leaf.clone(), &identity_provider, new_extensions, None)
+            .await;
+
+        if let Err(e) = res {
+            issues.push(match e {
+                MlsError::DuplicateLeafData(member) => ValidationIssue::DuplicateMember(member),
+                MlsError::InUseCredentialTypeUnsupportedByNewLeaf => {
+                    ValidationIssue::MemberCredentialUnsupported
+                }
+                MlsError::CredentialTypeOfNewLeafIsUnsupported => {
+                    let credential = leaf.signing_identity.credential.credential_type();
+                    ValidationIssue::CredentialUnsupportedByMember(credential)
+                }
+                e => ValidationIssue::Rejected(e),
+            });
+        }
+
+        report
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn diagnose_welcome(
+        welcome: &MlsMessage,
+        tree_data: Option<ExportedTree<'_>>,
+        config: C,
+        signer: SignatureSecretKey,
+    ) -> Result<ValidationReport, MlsError> {
+        let MlsMessagePayload::Welcome(welcome_payload) = &welcome.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        let mut report = ValidationReport::new();
+
+        if !config.version_supported(welcome.version) {
+            let issue = ValidationIssue::UnsupportedProtocolVersion(welcome.version);
+            report.issues.push(issue);
+            return Ok(report);
+        }
+
+        let cipher_suite = welcome_payload.cipher_suite;
+
+        let Ok(cs) = cipher_suite_provider(config.crypto_provider(), cipher_suite) else {
+            let issue = ValidationIssue::UnsupportedCipherSuite(cipher_suite);
+            report.issues.push(issue);
+            return Ok(report);
+        };
+
+        let decrypted = Self::decrypt_group_info_internal(
+            welcome,
+            &config,
+            #[cfg(feature = "psk")]
+            None,
+        )
+        .await;
+
+        let (group_info, key_package_generation) = match decrypted {
+            Ok((group_info, key_package_generation, ..)) => (group_info, key_package_generation),
+            Err(MlsError::WelcomeKeyPackageNotFound) => {
+                report.issues.push(ValidationIssue::KeyPackageNotFound);
+                return Ok(report);
+            }
+            Err(e) => {
+                report.issues.push(ValidationIssue::JoinFailed(e));
+                return Ok(report);
+            }
+        };
+
+        let leaf = &key_package_generation.key_package.leaf_node;
+        report.capabilities = Some(leaf.ungreased_capabilities());
+
+        let group_info_report = verify_group_info(
+            &config,
+            welcome.version,
+            &group_info,
+            tree_data.clone(),
+            &GroupInfoTrustConfig::default(),
+        )
+        .await?;
+
+        report.issues.extend(
+            group_info_report
+                .issues
+                .into_iter()
+                .filter(|issue| {
+                    !matches!(
+                        issue,
+                        GroupInfoIssue::MissingExternalPub | GroupInfoIssue::ConflictingRatchetTree
+                    )
+                })
+                .map(ValidationIssue::InvalidGroupInfo),
+        );
+
+        let identity_provider = config.identity_provider();
+
+        let member_context = MemberValidationContext::ForNewGroup {
+            current_context: &group_info.group_context,
+        };
+
+        let validator = LeafNodeValidator::new(&cs, &identity_provider, member_context);
+        check_leaf_node(&validator, leaf, None, &mut report.issues).await;
+
+        if report.is_ok() {
+            if let Err(e) = Self::join(welcome, tree_data, config, signer).await {
+                report.issues.push(ValidationIssue::JoinFailed(e));
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Run `validator` on `leaf` as a new member.
+///
+/// The capabilities required by the extensions of the group are checked
+/// separately, so that they are reported along with the first other error of
+/// the validator.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn check_leaf_node<I: IdentityProvider, P: CipherSuiteProvider>(
+    validator: &LeafNodeValidator<'_, I, P>,
+    leaf: &LeafNode,
+    time: Option<MlsTime>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    if let Err(e) = validator.validate_required_capabilities(leaf) {
+        issues.push(missing_capability(
+            e,
+            RequiredCapabilitiesExt::extension_type(),
+        ));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    if let Err(e) = validator.validate_external_senders_ext_credentials(leaf) {
+        issues.push(missing_capability(e, ExternalSendersExt::extension_type()));
+    }
+
+    let Err(e) = validator
+        .check_if_valid(leaf, ValidationContext::Add(time))
+        .await
+    else {
+        return;
+    };
+
+    issues.push(match e {
+        MlsError::RequiredExtensionNotFound(_)
+        | MlsError::RequiredProposalNotFound(_)
+        | MlsError::RequiredCredentialNotFound(_)
+        | MlsError::ExtensionError(_) => return,
+        MlsError::InvalidLifetime => lifetime_issue(leaf, time),
+        MlsError::InvalidLeafNodeSource => ValidationIssue::InvalidLeafNodeSource,
+        MlsError::IdentityProviderError(_) => ValidationIssue::IdentityRejected(e),
+        MlsError::InvalidSignature => ValidationIssue::InvalidLeafNodeSignature,
+        MlsError::ExtensionNotInCapabilities(e) => ValidationIssue::ExtensionNotInCapabilities(e),
+        MlsError::UnsupportedGroupExtension(e) => ValidationIssue::UnsupportedGroupExtension(e),
+        e => ValidationIssue::Rejected(e),
+    });
+}
+
+fn missing_capability(e: MlsError, required_by: ExtensionType) -> ValidationIssue {
+    let capability = match e {
+        MlsError::RequiredExtensionNotFound(e) => MissingCapability::Extension(e),
+        MlsError::RequiredProposalNotFound(p) => MissingCapability::Proposal(p),
+        MlsError::RequiredCredentialNotFound(c) => MissingCapability::Credential(c),
+        e => return ValidationIssue::InvalidGroupExtension(e),
+    };
+
+    ValidationIssue::MissingCapability {
+        capability,
+        required_by,
+    }
+}
+
+fn lifetime_issue(leaf: &LeafNode, time: Option<MlsTime>) -> ValidationIssue {
+    let (LeafNodeSource::KeyPackage(lifetime), Some(time)) = (&leaf.leaf_node_source, time) else {
+        return ValidationIssue::Rejected(MlsError::InvalidLifetime);
+    };
+
+    let now = time.seconds_since_epoch();
+
+    match now.checked_sub(lifetime.not_after) {
+        Some(seconds) => ValidationIssue::LifetimeExpired { seconds },
+        None => ValidationIssue::LifetimeNotYetValid {
+            seconds: lifetime.not_before.saturating_sub(now),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::test_utils::{
+            test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+        },
+        extension::{MlsExtension, RequiredCapabilitiesExt},
+        group::test_utils::TEST_GROUP,
+        tree_kem::leaf_node::LeafNodeSource,
+        ExtensionList,
+    };
+
+    use super::{MissingCapability, ValidationIssue};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_issues_are_explained() {
+        let required = RequiredCapabilitiesExt {
+            extensions: vec![43.into()],
+            ..Default::default()
+        };
+
+        let mut extensions = ExtensionList::new();
+        extensions.set_from(required).unwrap();
+
+        let alice = TestClientBuilder::new_for_test()
+            .extension_type(43.into())
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let group = alice
+            .create_group_with_id(TEST_GROUP.to_vec(), extensions, Default::default())
+            .await
+            .unwrap();
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let report = group
+            .diagnose_key_package(&key_package, None)
+            .await
+            .unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [ValidationIssue::MissingCapability {
+                capability: MissingCapability::Extension(e),
+                required_by,
+            }] if *e == 43.into() && *required_by == RequiredCapabilitiesExt::extension_type()
+        );
+
+        assert_eq!(
+            report.issues[0].to_string(),
+            "capability extension 0x002b is missing but required by extension 0x0003"
+        );
+
+        let LeafNodeSource::KeyPackage(lifetime) = &key_package
+            .as_key_package()
+            .unwrap()
+            .leaf_node
+            .leaf_node_source
+        else {
+            panic!("key package leaf expected");
+        };
+
+        let time = Some((lifetime.not_after + 10).into());
+        let report = group
+            .diagnose_key_package(&key_package, time)
+            .await
+            .unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [
+                ValidationIssue::MissingCapability { .. },
+                ValidationIssue::LifetimeExpired { seconds: 10 }
+            ]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn duplicate_member_is_reported() {
+        let alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let mut group = alice
+            .create_group_with_id(TEST_GROUP.to_vec(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let report = group
+            .diagnose_key_package(&key_package, None)
+            .await
+            .unwrap();
+
+        assert!(report.is_ok());
+
+        group
+            .commit_builder()
+            .add_member(key_package.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        let report = group
+            .diagnose_key_package(&key_package, None)
+            .await
+            .unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [ValidationIssue::DuplicateMember(1)]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_issues_are_explained() {
+        let alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let mut group = alice
+            .create_group_with_id(TEST_GROUP.to_vec(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let (bob, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let (carol, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let commit = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+
+        let report = bob.diagnose_welcome(welcome, None).await.unwrap();
+        assert!(report.is_ok());
+
+        let report = carol.diagnose_welcome(welcome, None).await.unwrap();
+
+        assert_matches!(
+            report.issues.as_slice(),
+            [ValidationIssue::KeyPackageNotFound]
+        );
+    }
+}
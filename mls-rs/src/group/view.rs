@@ -0,0 +1,203 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::{error::IntoAnyError, group::Member, identity::IdentityProvider};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, GroupState},
+};
+
+/// Serializable summary of the public state of a group, returned by
+/// [`Group::view`] and
+/// [`ExternalGroup::view`](crate::external_client::ExternalGroup::view).
+///
+/// A view only contains values that every member and external observer of
+/// the group can see. Types, versions and cipher suites are given by their
+/// raw values and binary values are hex encoded by human readable
+/// serializers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct GroupView {
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub group_id: Vec<u8>,
+    pub protocol_version: u16,
+    pub cipher_suite: u16,
+    /// Leaf index of the local member, `None` for an external group.
+    pub self_index: Option<u32>,
+    pub epoch: EpochView,
+    /// Types of the group context extensions.
+    pub extensions: Vec<u16>,
+    pub members: Vec<MemberView>,
+    /// Whether a reinit of the group was committed, after which the group
+    /// can no longer be used.
+    pub pending_reinit: bool,
+}
+
+/// Serializable summary of the current epoch of a group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EpochView {
+    pub epoch: u64,
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub tree_hash: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub confirmed_transcript_hash: Vec<u8>,
+    pub member_count: u32,
+}
+
+/// Serializable summary of a group member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct MemberView {
+    pub index: u32,
+    /// Application level identity of the member, as returned by the
+    /// [`IdentityProvider`](crate::IdentityProvider).
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub identity: Vec<u8>,
+    pub credential_type: u16,
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub signature_key: Vec<u8>,
+    pub cipher_suites: Vec<u16>,
+    pub extensions: Vec<u16>,
+    pub proposals: Vec<u16>,
+    pub credentials: Vec<u16>,
+    /// Types of the leaf node extensions of the member.
+    pub leaf_extensions: Vec<u16>,
+}
+
+impl GroupView {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn new<I: IdentityProvider>(
+        state: &GroupState,
+        identity_provider: &I,
+        self_index: Option<u32>,
+    ) -> Result<Self, MlsError> {
+        let context = &state.context;
+        let mut members = Vec::new();
+
+        for member in state.public_tree.roster().members_iter() {
+            members.push(MemberView::new(member, identity_provider, state).await?);
+        }
+
+        Ok(GroupView {
+            group_id: context.group_id.clone(),
+            protocol_version: *context.protocol_version,
+            cipher_suite: *context.cipher_suite,
+            self_index,
+            epoch: EpochView {
+                epoch: context.epoch,
+                tree_hash: context.tree_hash.clone(),
+                confirmed_transcript_hash: context.confirmed_transcript_hash.to_vec(),
+                member_count: members.len() as u32,
+            },
+            extensions: context
+                .extensions
+                .iter()
+                .map(|e| *e.extension_type)
+                .collect(),
+            members,
+            pending_reinit: state.pending_reinit.is_some(),
+        })
+    }
+}
+
+impl MemberView {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn new<I: IdentityProvider>(
+        member: Member,
+        identity_provider: &I,
+        state: &GroupState,
+    ) -> Result<Self, MlsError> {
+        let identity = identity_provider
+            .identity(&member.signing_identity, &state.context.extensions)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        let capabilities = &member.capabilities;
+
+        Ok(MemberView {
+            index: member.index,
+            identity,
+            credential_type: *member.signing_identity.credential.credential_type(),
+            signature_key: member.signing_identity.signature_key.to_vec(),
+            cipher_suites: capabilities.cipher_suites.iter().map(|c| **c).collect(),
+            extensions: capabilities.extensions.iter().map(|e| **e).collect(),
+            proposals: capabilities.proposals.iter().map(|p| **p).collect(),
+            credentials: capabilities.credentials.iter().map(|c| **c).collect(),
+            leaf_extensions: member
+                .extensions
+                .iter()
+                .map(|e| *e.extension_type)
+                .collect(),
+        })
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Serializable summary of the public state of the group, for exposing
+    /// it to monitoring and operations tooling.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn view(&self) -> Result<GroupView, MlsError> {
+        GroupView::new(
+            &self.state,
+            &self.config.identity_provider(),
+            Some(self.current_member_index()),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::group::test_utils::test_group;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn view_summarizes_public_state() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let view = alice.view().await.unwrap();
+
+        assert_eq!(view.group_id, alice.group_id());
+        assert_eq!(view.cipher_suite, *TEST_CIPHER_SUITE);
+        assert_eq!(view.self_index, Some(0));
+        assert_eq!(view.epoch.epoch, alice.current_epoch());
+        assert_eq!(view.epoch.tree_hash, alice.context().tree_hash);
+        assert_eq!(view.epoch.member_count, 2);
+        assert_eq!(view.members[1].identity, b"bob".to_vec());
+        assert!(!view.pending_reinit);
+
+        let bob_view = bob.view().await.unwrap();
+
+        assert_eq!(bob_view.self_index, Some(1));
+        assert_eq!(bob_view.epoch, view.epoch);
+        assert_eq!(bob_view.members, view.members);
+    }
+
+    #[cfg(feature = "serde")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn view_serializes_binary_values_as_hex() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let view = group.view().await.unwrap();
+
+        let json = serde_json::to_value(&view).unwrap();
+
+        assert_eq!(json["group_id"], hex::encode(group.group_id()));
+        assert_eq!(
+            serde_json::from_value::<super::GroupView>(json).unwrap(),
+            view
+        );
+    }
+}
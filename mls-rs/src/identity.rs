@@ -2,6 +2,8 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+/// Anonymous credential identity provider.
+pub mod anonymous;
 /// Basic credential identity provider.
 pub mod basic;
 
@@ -15,6 +17,37 @@ pub use mls_rs_core::identity::{
     Credential, CredentialType, CustomCredential, MlsCredential, SigningIdentity,
 };
 
+use core::convert::Infallible;
+use mls_rs_core::{
+    crypto::{CipherSuite, SignatureSecretKey},
+    identity::IdentityBootstrap,
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+/// [`IdentityBootstrap`] that never provisions an identity.
+///
+/// This is the bootstrap used by a [`ClientBuilder`](crate::client_builder::ClientBuilder)
+/// unless one is set with
+/// [`ClientBuilder::identity_bootstrap`](crate::client_builder::ClientBuilder::identity_bootstrap).
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct NoIdentityBootstrap;
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl IdentityBootstrap for NoIdentityBootstrap {
+    type Error = Infallible;
+
+    async fn bootstrap(
+        &self,
+        _cipher_suite: CipherSuite,
+    ) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, Self::Error> {
+        Ok(None)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     #[cfg(feature = "std")]
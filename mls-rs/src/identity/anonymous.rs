@@ -0,0 +1,266 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{identity::CredentialType, identity::SigningIdentity, time::MlsTime};
+use alloc::vec;
+use alloc::vec::Vec;
+pub use mls_rs_core::identity::{
+    AnonymousCredential, AnonymousCredentialScheme, AnonymousCredentialVerifier,
+};
+use mls_rs_core::{
+    error::{AnyError, IntoAnyError},
+    extension::ExtensionList,
+    identity::{IdentityProvider, MemberValidationContext},
+};
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+/// Error returned by an [`AnonymousIdentityProvider`].
+pub enum AnonymousIdentityProviderError {
+    #[cfg_attr(feature = "std", error("unsupported credential type found: {0:?}"))]
+    UnsupportedCredentialType(CredentialType),
+    #[cfg_attr(feature = "std", error(transparent))]
+    InvalidCredential(mls_rs_codec::Error),
+    #[cfg_attr(
+        feature = "std",
+        error("unsupported anonymous credential scheme: {0:?}")
+    )]
+    UnsupportedScheme(AnonymousCredentialScheme),
+    #[cfg_attr(feature = "std", error(transparent))]
+    VerifierError(AnyError),
+}
+
+impl IntoAnyError for AnonymousIdentityProviderError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Identity provider for [`AnonymousCredential`] presentations, which
+/// authorizes members without stable long-term identities.
+///
+/// A member creates a new presentation for each signature key it uses, over
+/// the message returned by [`AnonymousCredential::presentation_message`].
+/// The presentation is checked by the [`AnonymousCredentialVerifier`] of the
+/// credential scheme.
+///
+/// Since anonymous credentials do not contain an identity, the identity of a
+/// member is its signature key. As a consequence, a member can not replace
+/// itself with an external commit.
+///
+/// Clients using this provider advertise the
+/// [anonymous credential type](AnonymousCredential::CREDENTIAL_TYPE) in their
+/// capabilities. Groups can require it with
+/// [`RequiredCapabilitiesExt`](crate::extension::built_in::RequiredCapabilitiesExt).
+pub struct AnonymousIdentityProvider<V> {
+    verifier: V,
+}
+
+impl<V> AnonymousIdentityProvider<V>
+where
+    V: AnonymousCredentialVerifier,
+{
+    pub fn new(verifier: V) -> Self {
+        Self { verifier }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+    ) -> Result<(), AnonymousIdentityProviderError> {
+        let credential = resolve_anonymous_credential(signing_identity)?;
+
+        if !self
+            .verifier
+            .supported_schemes()
+            .contains(&credential.scheme)
+        {
+            return Err(AnonymousIdentityProviderError::UnsupportedScheme(
+                credential.scheme,
+            ));
+        }
+
+        let message = AnonymousCredential::presentation_message(&signing_identity.signature_key);
+
+        self.verifier
+            .verify_presentation(&credential, &message, timestamp)
+            .await
+            .map_err(|e| AnonymousIdentityProviderError::VerifierError(e.into_any_error()))
+    }
+}
+
+fn resolve_anonymous_credential(
+    signing_id: &SigningIdentity,
+) -> Result<AnonymousCredential, AnonymousIdentityProviderError> {
+    AnonymousCredential::from_credential(&signing_id.credential)
+        .ok_or_else(|| {
+            AnonymousIdentityProviderError::UnsupportedCredentialType(
+                signing_id.credential.credential_type(),
+            )
+        })?
+        .map_err(AnonymousIdentityProviderError::InvalidCredential)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<V> IdentityProvider for AnonymousIdentityProvider<V>
+where
+    V: AnonymousCredentialVerifier,
+{
+    type Error = AnonymousIdentityProviderError;
+
+    async fn validate_member(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        _context: MemberValidationContext<'_>,
+    ) -> Result<(), Self::Error> {
+        self.verify(signing_identity, timestamp).await
+    }
+
+    async fn validate_external_sender(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        _extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.verify(signing_identity, timestamp).await
+    }
+
+    async fn identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        _extensions: &ExtensionList,
+    ) -> Result<Vec<u8>, Self::Error> {
+        resolve_anonymous_credential(signing_identity)?;
+        Ok(signing_identity.signature_key.to_vec())
+    }
+
+    async fn valid_successor(
+        &self,
+        _predecessor: &SigningIdentity,
+        _successor: &SigningIdentity,
+        _extensions: &ExtensionList,
+    ) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn supported_types(&self) -> Vec<CredentialType> {
+        vec![AnonymousCredential::CREDENTIAL_TYPE]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        crypto::SignaturePublicKey,
+        identity::{IdentityProvider, MemberValidationContext, MlsCredential, SigningIdentity},
+        time::MlsTime,
+    };
+
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+
+    use super::{
+        AnonymousCredential, AnonymousCredentialScheme, AnonymousCredentialVerifier,
+        AnonymousIdentityProvider, AnonymousIdentityProviderError,
+    };
+
+    const TEST_SCHEME: AnonymousCredentialScheme = AnonymousCredentialScheme::new(1);
+
+    #[derive(Debug)]
+    struct InvalidPresentation;
+
+    impl mls_rs_core::error::IntoAnyError for InvalidPresentation {}
+
+    // Accepts presentations that are the issuer secret followed by the message
+    struct TestVerifier;
+
+    impl TestVerifier {
+        fn present(signature_key: &SignaturePublicKey) -> AnonymousCredential {
+            let message = AnonymousCredential::presentation_message(signature_key);
+            AnonymousCredential::new(TEST_SCHEME, [b"issuer".as_slice(), &message].concat())
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl AnonymousCredentialVerifier for TestVerifier {
+        type Error = InvalidPresentation;
+
+        async fn verify_presentation(
+            &self,
+            credential: &AnonymousCredential,
+            message: &[u8],
+            _timestamp: Option<MlsTime>,
+        ) -> Result<(), Self::Error> {
+            (credential.presentation == [b"issuer".as_slice(), message].concat())
+                .then_some(())
+                .ok_or(InvalidPresentation)
+        }
+
+        fn supported_schemes(&self) -> Vec<AnonymousCredentialScheme> {
+            vec![TEST_SCHEME]
+        }
+    }
+
+    fn signing_identity(
+        credential: AnonymousCredential,
+        signature_key: SignaturePublicKey,
+    ) -> SigningIdentity {
+        SigningIdentity::new(credential.into_credential().unwrap(), signature_key)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn presentations_are_bound_to_signature_key() {
+        let provider = AnonymousIdentityProvider::new(TestVerifier);
+        let key = SignaturePublicKey::from(vec![1u8; 32]);
+        let other_key = SignaturePublicKey::from(vec![2u8; 32]);
+
+        let identity = signing_identity(TestVerifier::present(&key), key.clone());
+
+        provider
+            .validate_member(&identity, None, MemberValidationContext::None)
+            .await
+            .unwrap();
+
+        let identity_bytes = provider
+            .identity(&identity, &Default::default())
+            .await
+            .unwrap();
+
+        assert_eq!(identity_bytes, key.to_vec());
+
+        let replayed = signing_identity(TestVerifier::present(&key), other_key);
+
+        let res = provider
+            .validate_member(&replayed, None, MemberValidationContext::None)
+            .await;
+
+        assert_matches!(res, Err(AnonymousIdentityProviderError::VerifierError(_)));
+
+        let mut unsupported = TestVerifier::present(&key);
+        unsupported.scheme = AnonymousCredentialScheme::new(2);
+
+        let res = provider
+            .validate_member(
+                &signing_identity(unsupported, key),
+                None,
+                MemberValidationContext::None,
+            )
+            .await;
+
+        assert_matches!(
+            res,
+            Err(AnonymousIdentityProviderError::UnsupportedScheme(_))
+        );
+    }
+}
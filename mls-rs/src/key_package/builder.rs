@@ -0,0 +1,111 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, extension::ApplicationIdExt,
+    tree_kem::Capabilities, Client, ExtensionList, MlsMessage,
+};
+
+/// Build a key package with properties chosen for this key package only.
+///
+/// By default, the key package has no extensions and advertises the
+/// capabilities and key package lifetime of the client configuration, as
+/// with [`Client::generate_key_package_message`]. This allows a single client
+/// to publish key packages with different properties, for example to
+/// different directories.
+pub struct KeyPackageBuilder<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    client: &'a Client<C>,
+    key_package_extensions: ExtensionList,
+    leaf_node_extensions: ExtensionList,
+    capabilities: Option<Capabilities>,
+    lifetime_in_s: Option<u64>,
+}
+
+impl<'a, C> KeyPackageBuilder<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    pub(crate) fn new(client: &'a Client<C>) -> Self {
+        Self {
+            client,
+            key_package_extensions: Default::default(),
+            leaf_node_extensions: Default::default(),
+            capabilities: Default::default(),
+            lifetime_in_s: Default::default(),
+        }
+    }
+
+    /// Set the extensions of the key package.
+    pub fn key_package_extensions(self, key_package_extensions: ExtensionList) -> Self {
+        Self {
+            key_package_extensions,
+            ..self
+        }
+    }
+
+    /// Set the extensions of the leaf node in the key package.
+    pub fn leaf_node_extensions(self, leaf_node_extensions: ExtensionList) -> Self {
+        Self {
+            leaf_node_extensions,
+            ..self
+        }
+    }
+
+    /// Present the application id `identifier` in the leaf node, replacing
+    /// any [`ApplicationIdExt`] set with
+    /// [`leaf_node_extensions`](Self::leaf_node_extensions).
+    ///
+    /// The capabilities of the key package must include
+    /// [`ExtensionType::APPLICATION_ID`](crate::extension::ExtensionType::APPLICATION_ID).
+    pub fn application_id(mut self, identifier: Vec<u8>) -> Result<Self, MlsError> {
+        self.leaf_node_extensions
+            .set_from(ApplicationIdExt::new(identifier))?;
+
+        Ok(self)
+    }
+
+    /// Advertise `capabilities` in the leaf node instead of the capabilities
+    /// of the client configuration.
+    ///
+    /// The capabilities must include the extensions of the key package and
+    /// of the leaf node, otherwise the key package is rejected by other
+    /// members.
+    pub fn capabilities(self, capabilities: Capabilities) -> Self {
+        Self {
+            capabilities: Some(capabilities),
+            ..self
+        }
+    }
+
+    /// Set the lifetime duration in seconds of the key package instead of
+    /// the duration set with
+    /// [`ClientBuilder::key_package_lifetime`](crate::client_builder::ClientBuilder::key_package_lifetime).
+    pub fn lifetime(self, duration_in_s: u64) -> Self {
+        Self {
+            lifetime_in_s: Some(duration_in_s),
+            ..self
+        }
+    }
+
+    /// Generate the key package message and store its secret keys, as with
+    /// [`Client::generate_key_package_message`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn build(self) -> Result<MlsMessage, MlsError> {
+        Ok(self
+            .client
+            .generate_key_package(
+                self.key_package_extensions,
+                self.leaf_node_extensions,
+                self.capabilities,
+                self.lifetime_in_s,
+            )
+            .await?
+            .key_package_message())
+    }
+}
@@ -32,6 +32,7 @@ where
     pub cipher_suite_provider: &'a CP,
     pub signing_identity: &'a SigningIdentity,
     pub signing_key: &'a SignatureSecretKey,
+    pub grease: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -101,6 +102,7 @@ where
         let properties = ConfigProperties {
             capabilities,
             extensions: leaf_node_extensions,
+            grease: self.grease,
         };
 
         let (leaf_node, leaf_node_secret) = LeafNode::generate(
@@ -121,7 +123,9 @@ where
             signature: vec![],
         };
 
-        package.grease(self.cipher_suite_provider)?;
+        if self.grease {
+            package.grease(self.cipher_suite_provider)?;
+        }
 
         self.sign(&mut package).await?;
 
@@ -196,6 +200,7 @@ mod tests {
                 cipher_suite_provider: &cipher_suite_provider,
                 signing_identity: &signing_identity,
                 signing_key: &signing_key,
+                grease: true,
             };
 
             let mut capabilities = get_test_capabilities();
@@ -296,6 +301,7 @@ mod tests {
                 cipher_suite_provider: &test_cipher_suite_provider(cipher_suite),
                 signing_identity: &signing_identity,
                 signing_key: &signing_key,
+                grease: true,
             };
 
             let first_key_package = test_generator
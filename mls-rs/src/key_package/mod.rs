@@ -21,6 +21,9 @@ use mls_rs_codec::MlsEncode;
 use mls_rs_codec::MlsSize;
 use mls_rs_core::extension::ExtensionList;
 
+mod builder;
+pub use builder::KeyPackageBuilder;
+
 mod validator;
 pub(crate) use validator::*;
 
@@ -205,6 +208,7 @@ pub(crate) mod test_utils {
             cipher_suite_provider: &test_cipher_suite_provider(cipher_suite),
             signing_identity: &signing_identity,
             signing_key: &secret_key,
+            grease: true,
         };
 
         let key_package = generator
@@ -131,6 +131,9 @@ pub use protocol_version::ProtocolVersion;
 pub mod client;
 pub mod client_builder;
 mod client_config;
+/// Compatibility with artifacts serialized by OpenMLS.
+pub mod compat;
+pub mod compression;
 /// Dependencies of [`CryptoProvider`] and [`CipherSuiteProvider`]
 pub mod crypto;
 /// Extension utilities and built-in extension types.
@@ -151,30 +154,41 @@ mod key_package;
 pub(crate) mod map;
 /// Pre-shared key support.
 pub mod psk;
+/// Routing of encrypted messages by a delivery service that was trusted with
+/// the sender data secret of an epoch.
+#[cfg(feature = "private_message")]
+#[cfg_attr(docsrs, doc(cfg(feature = "private_message")))]
+pub mod routing;
 mod signer;
 /// Storage providers to use with
 /// [`ClientBuilder`](client_builder::ClientBuilder).
 pub mod storage_provider;
 
 pub use mls_rs_core::{
-    crypto::{CipherSuiteProvider, CryptoProvider},
+    crypto::{CipherSuiteProvider, CryptoProvider, EpochSecretsVault},
     group::GroupStateStorage,
-    identity::IdentityProvider,
-    key_package::KeyPackageStorage,
+    identity::{IdentityBootstrap, IdentityProvider, KeychainStorage},
+    key_package::{KeyPackageQuery, KeyPackageStorage},
     psk::PreSharedKeyStorage,
 };
 
 /// Dependencies of [`MlsRules`].
 pub mod mls_rules {
     pub use crate::group::{
+        capability_enforcement::{CapabilityEnforcementRules, CapabilityPolicy},
         mls_rules::{
             CommitDirection, CommitOptions, CommitSource, DefaultMlsRules, EncryptionOptions,
+            ProposalSenderPolicy, ProposalSenderType,
         },
+        padding::{GroupInfoPadding, GroupInfoPaddingExt},
         proposal_filter::{ProposalBundle, ProposalInfo, ProposalSource},
     };
 
     #[cfg(feature = "by_ref_proposal")]
     pub use crate::group::proposal_ref::ProposalRef;
+
+    #[cfg(feature = "private_message")]
+    pub use crate::group::mls_rules::{ControlWireFormatPolicy, ReceivedControlWireFormat};
 }
 
 pub use mls_rs_core::extension::{Extension, ExtensionList};
@@ -186,12 +200,12 @@ pub use crate::{
         mls_rules::MlsRules,
         Group,
     },
-    key_package::{KeyPackage, KeyPackageRef},
+    key_package::{KeyPackage, KeyPackageBuilder, KeyPackageRef},
 };
 
 /// Error types.
 pub mod error {
-    pub use crate::client::MlsError;
+    pub use crate::client::{ErrorRecovery, MlsError};
     pub use mls_rs_core::error::{AnyError, IntoAnyError};
     pub use mls_rs_core::extension::ExtensionError;
 }
@@ -201,6 +215,10 @@ pub mod time {
     pub use mls_rs_core::time::*;
 }
 
+/// Tree math and parent hash utilities for inspecting ratchet trees.
+#[cfg(feature = "tree_inspection")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tree_inspection")))]
+pub mod tree_inspection;
 mod tree_kem;
 
 pub use mls_rs_codec;
@@ -215,5 +233,11 @@ use private::Sealed;
 #[doc(hidden)]
 pub mod test_utils;
 
+#[cfg(any(all(test, feature = "rfc_compliant"), feature = "test_vectors"))]
+pub mod test_vectors;
+
+#[cfg(all(test, feature = "zeroize_check"))]
+mod zeroize_check;
+
 #[cfg(feature = "ffi")]
 pub use safer_ffi_gen;
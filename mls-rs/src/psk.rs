@@ -26,7 +26,10 @@ use mls_rs_core::error::IntoAnyError;
 pub(crate) mod resolver;
 pub(crate) mod secret;
 
-pub use mls_rs_core::psk::{ExternalPskId, PreSharedKey};
+mod resolving_store;
+pub use resolving_store::ResolvingPskStore;
+
+pub use mls_rs_core::psk::{ExternalPskId, ExternalPskResolver, PreSharedKey};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -123,6 +126,104 @@ pub(crate) enum ResumptionPSKUsage {
     Branch = 3u8,
 }
 
+/// Group from which the next group of a [`GroupLineage`] was created by
+/// reinitialization or branching.
+#[cfg(feature = "psk")]
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineageAncestor {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    group_id: Vec<u8>,
+    epoch: u64,
+}
+
+#[cfg(feature = "psk")]
+impl Debug for LineageAncestor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineageAncestor")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .finish()
+    }
+}
+
+#[cfg(feature = "psk")]
+impl LineageAncestor {
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch of the ancestor in which the next group of the lineage was
+    /// created. This is the last epoch of the ancestor that resumption PSKs
+    /// can be derived from.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+/// Groups that a group was created from by
+/// [reinitialization](crate::group::ReinitClient) or
+/// [branching](crate::Group::branch), ordered from the oldest.
+///
+/// The lineage is stored with the group state and used by
+/// [`CommitBuilder::add_resumption_psk_from_lineage`](crate::group::CommitBuilder::add_resumption_psk_from_lineage)
+/// to derive resumption PSKs from epochs of earlier groups.
+#[cfg(feature = "psk")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupLineage {
+    ancestors: Vec<LineageAncestor>,
+}
+
+#[cfg(feature = "psk")]
+impl GroupLineage {
+    pub fn ancestors(&self) -> &[LineageAncestor] {
+        &self.ancestors
+    }
+
+    /// Lineage of a group created from epoch `epoch` of the group with this
+    /// lineage and id `group_id`.
+    pub(crate) fn descendant(&self, group_id: &[u8], epoch: u64) -> Self {
+        let mut ancestors = self.ancestors.clone();
+
+        ancestors.push(LineageAncestor {
+            group_id: group_id.to_vec(),
+            epoch,
+        });
+
+        Self { ancestors }
+    }
+
+    /// Resumption PSK for epoch `psk_epoch` of the group `psk_group_id`,
+    /// which must be either the current group, at or before `current_epoch`,
+    /// or an ancestor, at or before the epoch the next group was created in.
+    pub(crate) fn resumption_psk(
+        &self,
+        current_group_id: &[u8],
+        current_epoch: u64,
+        psk_group_id: Vec<u8>,
+        psk_epoch: u64,
+    ) -> Result<ResumptionPsk, MlsError> {
+        let in_window = (psk_group_id == current_group_id && psk_epoch <= current_epoch)
+            || self
+                .ancestors
+                .iter()
+                .any(|a| a.group_id == psk_group_id && psk_epoch <= a.epoch);
+
+        in_window
+            .then_some(ResumptionPsk {
+                usage: ResumptionPSKUsage::Application,
+                psk_group_id: PskGroupId(psk_group_id),
+                psk_epoch,
+            })
+            .ok_or(MlsError::ResumptionPskOutsideLineage)
+    }
+}
+
 #[cfg(feature = "psk")]
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode)]
 struct PSKLabel<'a> {
@@ -19,20 +19,25 @@ use crate::{
 
 use super::{secret::PskSecretInput, JustPreSharedKeyID, PreSharedKeyID, ResumptionPsk};
 
-pub(crate) struct PskResolver<'a, GS, K, PS>
+pub(crate) struct PskResolver<'a, GS, K, PS, CP>
 where
     GS: GroupStateStorage,
     PS: PreSharedKeyStorage,
     K: KeyPackageStorage,
+    CP: CipherSuiteProvider,
 {
     pub group_context: Option<&'a GroupContext>,
     pub current_epoch: Option<&'a EpochSecrets>,
-    pub prior_epochs: Option<&'a GroupStateRepository<GS, K>>,
+    pub prior_epochs: Option<&'a GroupStateRepository<GS, K, CP>>,
     pub psk_store: &'a PS,
 }
 
-impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
-    PskResolver<'_, GS, K, PS>
+impl<GS, K, PS, CP> PskResolver<'_, GS, K, PS, CP>
+where
+    GS: GroupStateStorage,
+    K: KeyPackageStorage,
+    PS: PreSharedKeyStorage,
+    CP: CipherSuiteProvider,
 {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn resolve_resumption(&self, psk_id: &ResumptionPsk) -> Result<PreSharedKey, MlsError> {
@@ -53,22 +58,41 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
         Err(MlsError::OldGroupStateNotFound)
     }
 
+    /// Fetch all external PSKs in `ids` from the store with a single lookup.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn resolve_external(&self, psk_id: &ExternalPskId) -> Result<PreSharedKey, MlsError> {
+    async fn resolve_external(
+        &self,
+        ids: &[PreSharedKeyID],
+    ) -> Result<Vec<Option<PreSharedKey>>, MlsError> {
+        let external_ids = ids
+            .iter()
+            .filter_map(|id| match &id.key_id {
+                JustPreSharedKeyID::External(external) => Some(external.clone()),
+                JustPreSharedKeyID::Resumption(_) => None,
+            })
+            .collect::<Vec<ExternalPskId>>();
+
+        if external_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
         self.psk_store
-            .get(psk_id)
+            .get_many(&external_ids)
             .await
-            .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?
-            .ok_or(MlsError::MissingRequiredPsk)
+            .map_err(|e| MlsError::PskStoreError(e.into_any_error()))
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn resolve(&self, id: &[PreSharedKeyID]) -> Result<Vec<PskSecretInput>, MlsError> {
+        let mut external_psks = self.resolve_external(id).await?.into_iter();
         let mut secret_inputs = Vec::new();
 
         for id in id {
             let psk = match &id.key_id {
-                JustPreSharedKeyID::External(external) => self.resolve_external(external).await,
+                JustPreSharedKeyID::External(_) => external_psks
+                    .next()
+                    .flatten()
+                    .ok_or(MlsError::MissingRequiredPsk),
                 JustPreSharedKeyID::Resumption(resumption) => {
                     self.resolve_resumption(resumption).await
                 }
@@ -84,12 +108,121 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn resolve_to_secret<P: CipherSuiteProvider>(
+    pub async fn resolve_to_secret(
         &self,
         id: &[PreSharedKeyID],
-        cipher_suite_provider: &P,
+        cipher_suite_provider: &CP,
     ) -> Result<PskSecret, MlsError> {
         let psk = self.resolve(id).await?;
         PskSecret::calculate(&psk, cipher_suite_provider).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use assert_matches::assert_matches;
+    use core::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use mls_rs_core::{
+        crypto::CryptoProvider,
+        psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    };
+
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
+        psk::{test_utils::make_nonce, JustPreSharedKeyID, PreSharedKeyID},
+        storage_provider::in_memory::{
+            InMemoryGroupStateStorage, InMemoryKeyPackageStorage, InMemoryPreSharedKeyStorage,
+        },
+    };
+
+    use super::PskResolver;
+
+    type TestCipherSuiteProvider = <TestCryptoProvider as CryptoProvider>::CipherSuiteProvider;
+
+    #[derive(Default)]
+    struct CountingPskStorage {
+        inner: InMemoryPreSharedKeyStorage,
+        lookups: AtomicUsize,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl PreSharedKeyStorage for CountingPskStorage {
+        type Error = Infallible;
+
+        async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+            Ok(self.inner.get(id))
+        }
+
+        async fn get_many(
+            &self,
+            ids: &[ExternalPskId],
+        ) -> Result<Vec<Option<PreSharedKey>>, Self::Error> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+            Ok(ids.iter().map(|id| self.inner.get(id)).collect())
+        }
+    }
+
+    fn external_psk_id(id: u8) -> PreSharedKeyID {
+        PreSharedKeyID {
+            key_id: JustPreSharedKeyID::External(ExternalPskId::new(vec![id])),
+            psk_nonce: make_nonce(TEST_CIPHER_SUITE),
+        }
+    }
+
+    fn resolver(
+        psk_store: &CountingPskStorage,
+    ) -> PskResolver<
+        '_,
+        InMemoryGroupStateStorage,
+        InMemoryKeyPackageStorage,
+        CountingPskStorage,
+        TestCipherSuiteProvider,
+    > {
+        PskResolver {
+            group_context: None,
+            current_epoch: None,
+            prior_epochs: None,
+            psk_store,
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_psks_are_fetched_in_one_lookup() {
+        let mut psk_store = CountingPskStorage::default();
+
+        for id in 0..3 {
+            psk_store
+                .inner
+                .insert(ExternalPskId::new(vec![id]), vec![id; 32].into());
+        }
+
+        let ids = (0..3).map(external_psk_id).collect::<Vec<_>>();
+        let inputs = resolver(&psk_store).resolve(&ids).await.unwrap();
+
+        assert_eq!(psk_store.lookups.load(Ordering::Relaxed), 1);
+
+        for (id, input) in (0..3).zip(inputs) {
+            assert_eq!(input.id.key_id, external_psk_id(id).key_id);
+            assert_eq!(input.psk, vec![id; 32].into());
+        }
+
+        let res = resolver(&psk_store)
+            .resolve_to_secret(
+                &[external_psk_id(0), external_psk_id(7)],
+                &test_cipher_suite_provider(TEST_CIPHER_SUITE),
+            )
+            .await;
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_core::{
+    error::{AnyError, IntoAnyError},
+    psk::{ExternalPskId, ExternalPskResolver, PreSharedKey, PreSharedKeyStorage},
+};
+
+/// Pre-shared key storage that calls an [`ExternalPskResolver`] for each key
+/// not found in the underlying storage.
+///
+/// Keys returned by the resolver are not inserted into the storage. See
+/// [`ClientBuilder::psk_resolver`](crate::client_builder::ClientBuilder::psk_resolver).
+#[derive(Clone, Debug)]
+pub struct ResolvingPskStore<S, R> {
+    store: S,
+    resolver: R,
+}
+
+impl<S, R> ResolvingPskStore<S, R> {
+    pub fn new(store: S, resolver: R) -> Self {
+        Self { store, resolver }
+    }
+
+    /// The underlying storage.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<S, R> PreSharedKeyStorage for ResolvingPskStore<S, R>
+where
+    S: PreSharedKeyStorage,
+    R: ExternalPskResolver,
+{
+    type Error = AnyError;
+
+    async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+        match self.store.get(id).await.map_err(|e| e.into_any_error())? {
+            Some(psk) => Ok(Some(psk)),
+            None => self
+                .resolver
+                .resolve(id)
+                .await
+                .map_err(|e| e.into_any_error()),
+        }
+    }
+
+    async fn get_many(
+        &self,
+        ids: &[ExternalPskId],
+    ) -> Result<Vec<Option<PreSharedKey>>, Self::Error> {
+        let mut keys = self
+            .store
+            .get_many(ids)
+            .await
+            .map_err(|e| e.into_any_error())?;
+
+        for (id, key) in ids.iter().zip(keys.iter_mut()) {
+            if key.is_none() {
+                *key = self
+                    .resolver
+                    .resolve(id)
+                    .await
+                    .map_err(|e| e.into_any_error())?;
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+#[cfg(all(test, feature = "psk"))]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use core::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use mls_rs_core::psk::{ExternalPskId, ExternalPskResolver, PreSharedKey};
+
+    #[cfg(target_has_atomic = "ptr")]
+    use alloc::sync::Arc;
+
+    #[cfg(not(target_has_atomic = "ptr"))]
+    use portable_atomic_util::Arc;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_builder::test_utils::TestClientBuilder,
+        group::test_utils::test_group_custom_config,
+        identity::test_utils::get_test_signing_identity,
+    };
+
+    #[derive(Clone)]
+    struct TestResolver {
+        psk_id: ExternalPskId,
+        psk: PreSharedKey,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl ExternalPskResolver for TestResolver {
+        type Error = Infallible;
+
+        async fn resolve(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Infallible> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok((*id == self.psk_id).then(|| self.psk.clone()))
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn join_resolves_unknown_external_psk() {
+        let psk_id = ExternalPskId::new(b"psk id".to_vec());
+        let psk = PreSharedKey::from(b"psk".to_vec());
+
+        let mut alice_group =
+            test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+                c.psk(psk_id.clone(), psk.clone())
+            })
+            .await;
+
+        let resolver = TestResolver {
+            psk_id: psk_id.clone(),
+            psk,
+            calls: Default::default(),
+        };
+
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .psk_resolver(resolver.clone())
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .add_external_psk(psk_id)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        bob.join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap();
+
+        assert_eq!(resolver.calls.load(Ordering::SeqCst), 1);
+        assert!(bob.secret_store().store().get(&resolver.psk_id).is_none());
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::crypto::{CipherSuite, CipherSuiteProvider};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        ciphertext_processor::{
+            sender_data_key::{SenderDataAAD, SenderDataKey},
+            GroupStateProvider,
+        },
+        epoch::SenderDataSecret,
+        framing::{ContentType, MlsMessagePayload},
+        Group,
+    },
+    MlsMessage,
+};
+
+/// Sender data secret of an epoch, shared by a member with a delivery service.
+///
+/// The sender data secret only protects the sender index and generation of
+/// private messages. Sharing it does not give access to the content of
+/// messages, but it reveals which member sent each message of the epoch.
+/// Members therefore never share it implicitly: a member that trusts the
+/// delivery service exports it with [`Group::export_sender_data_escrow`]
+/// after each epoch change and sends it to the service over a secure channel.
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct SenderDataEscrow {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    sender_data_secret: SenderDataSecret,
+}
+
+impl Debug for SenderDataEscrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SenderDataEscrow")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .finish()
+    }
+}
+
+impl SenderDataEscrow {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+/// Sender information of a private message, decrypted by a
+/// [`SenderDataDecryptor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SenderDataInfo {
+    /// Leaf index of the member that sent the message.
+    pub sender_index: u32,
+    /// Generation of the key used by the sender to encrypt the message.
+    pub generation: u32,
+    pub content_type: ContentType,
+    pub epoch: u64,
+}
+
+/// Decryption of the sender data of private messages sent in the epoch of a
+/// [`SenderDataEscrow`].
+///
+/// This lets a delivery service route messages or collect statistics per
+/// sender without being a member of the group. Only the sender data is
+/// decrypted. Its authenticity is limited to having been created by a member
+/// of the epoch, since the signature over the message is part of the
+/// encrypted content.
+#[derive(Clone, Debug)]
+pub struct SenderDataDecryptor<CP> {
+    escrow: SenderDataEscrow,
+    cipher_suite_provider: CP,
+}
+
+impl<CP> SenderDataDecryptor<CP>
+where
+    CP: CipherSuiteProvider,
+{
+    pub fn new(escrow: SenderDataEscrow, cipher_suite_provider: CP) -> Result<Self, MlsError> {
+        if cipher_suite_provider.cipher_suite() != escrow.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        Ok(Self {
+            escrow,
+            cipher_suite_provider,
+        })
+    }
+
+    pub fn escrow(&self) -> &SenderDataEscrow {
+        &self.escrow
+    }
+
+    /// Decrypt the sender data of `message`, which must be a private message
+    /// of the group and epoch of the escrow.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn decrypt(&self, message: &MlsMessage) -> Result<SenderDataInfo, MlsError> {
+        let MlsMessagePayload::Cipher(ciphertext) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        if ciphertext.group_id != self.escrow.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if ciphertext.epoch != self.escrow.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        let sender_data_aad = SenderDataAAD {
+            group_id: ciphertext.group_id.clone(),
+            epoch: ciphertext.epoch,
+            content_type: ciphertext.content_type,
+        };
+
+        let sender_data = SenderDataKey::new(
+            &self.escrow.sender_data_secret,
+            &ciphertext.ciphertext,
+            &self.cipher_suite_provider,
+        )
+        .await?
+        .open(&ciphertext.encrypted_sender_data, &sender_data_aad)
+        .await?;
+
+        Ok(SenderDataInfo {
+            sender_index: *sender_data.sender,
+            generation: sender_data.generation,
+            content_type: ciphertext.content_type,
+            epoch: ciphertext.epoch,
+        })
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Export the sender data secret of the current epoch so that a trusted
+    /// delivery service can learn the sender of private messages with a
+    /// [`SenderDataDecryptor`]. See [`SenderDataEscrow`] for the implications.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn export_sender_data_escrow(&self) -> SenderDataEscrow {
+        SenderDataEscrow {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            sender_data_secret: self.epoch_secrets().sender_data_secret.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{framing::ContentType, test_utils::test_n_member_group},
+    };
+
+    use super::{SenderDataDecryptor, SenderDataEscrow};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sender_data_is_decrypted_with_escrow() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let escrow = groups[0].export_sender_data_escrow();
+        let escrow = SenderDataEscrow::from_bytes(&escrow.to_bytes().unwrap()).unwrap();
+
+        let decryptor =
+            SenderDataDecryptor::new(escrow, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+                .unwrap();
+
+        for _ in 0..2 {
+            groups[2]
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+        }
+
+        let message = groups[2]
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let info = decryptor.decrypt(&message).await.unwrap();
+
+        assert_eq!(info.sender_index, 2);
+        assert_eq!(info.generation, 2);
+        assert_eq!(info.content_type, ContentType::Application);
+
+        groups[2].commit(vec![]).await.unwrap();
+        groups[2].apply_pending_commit().await.unwrap();
+
+        let message = groups[2]
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let res = decryptor.decrypt(&message).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+}
@@ -2,7 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use alloc::vec::Vec;
+use alloc::{format, string::String, vec::Vec};
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsEncode, MlsSize};
 use mls_rs_core::error::IntoAnyError;
@@ -36,6 +36,53 @@ impl SignContent {
     }
 }
 
+/// Labels used by the MLS protocol to sign its own structures.
+///
+/// Signatures computed with an [`ApplicationSignatureLabel`] can never be
+/// confused with signatures over these structures.
+pub const PROTOCOL_SIGNATURE_LABELS: &[&str] = &[
+    "FramedContentTBS",
+    "LeafNodeTBS",
+    "KeyPackageTBS",
+    "GroupInfoTBS",
+    "TargetedMessageTBS",
+    "MembershipProofTBS",
+    "SignedTreeHeadTBS",
+    "PairwiseChannelInitTBS",
+    "CommitRequestTBS",
+    "SubsetKeySharesTBS",
+];
+
+const APPLICATION_SIGNATURE_NAMESPACE: &str = "application ";
+
+/// Label for application data signed with the signature key of a group
+/// member.
+///
+/// Labels are placed in a namespace reserved for applications, so that the
+/// signed content is `"MLS 1.0 application " || label`. This prevents
+/// application signatures from being valid signatures over protocol
+/// structures, see [`PROTOCOL_SIGNATURE_LABELS`]. Applications should use a
+/// distinct label for each kind of data they sign.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApplicationSignatureLabel(String);
+
+impl ApplicationSignatureLabel {
+    pub fn new(label: &str) -> Self {
+        Self(format!("{APPLICATION_SIGNATURE_NAMESPACE}{label}"))
+    }
+
+    /// Label chosen by the application.
+    pub fn label(&self) -> &str {
+        &self.0[APPLICATION_SIGNATURE_NAMESPACE.len()..]
+    }
+
+    /// Label including the application namespace, as used in the signed
+    /// content.
+    pub fn full_label(&self) -> &str {
+        &self.0
+    }
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
 #[cfg_attr(
@@ -56,13 +103,17 @@ pub(crate) trait Signable<'a> {
 
     fn write_signature(&mut self, signature: Vec<u8>);
 
+    fn sign_label(&self) -> &str {
+        Self::SIGN_LABEL
+    }
+
     async fn sign<P: CipherSuiteProvider>(
         &mut self,
         signature_provider: &P,
         signer: &SignatureSecretKey,
         context: &Self::SigningContext,
     ) -> Result<(), MlsError> {
-        let sign_content = SignContent::new(Self::SIGN_LABEL, self.signable_content(context)?);
+        let sign_content = SignContent::new(self.sign_label(), self.signable_content(context)?);
 
         let signature = signature_provider
             .sign(signer, &sign_content.mls_encode_to_vec()?)
@@ -80,7 +131,7 @@ pub(crate) trait Signable<'a> {
         public_key: &SignaturePublicKey,
         context: &Self::SigningContext,
     ) -> Result<(), MlsError> {
-        let sign_content = SignContent::new(Self::SIGN_LABEL, self.signable_content(context)?);
+        let sign_content = SignContent::new(self.sign_label(), self.signable_content(context)?);
 
         signature_provider
             .verify(
@@ -354,4 +405,67 @@ mod tests {
 
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
+
+    #[test]
+    fn protocol_signature_labels_are_outside_application_namespace() {
+        let labels = [
+            <crate::group::message_signature::AuthenticatedContent as Signable>::SIGN_LABEL,
+            <crate::tree_kem::leaf_node::LeafNode as Signable>::SIGN_LABEL,
+            <crate::KeyPackage as Signable>::SIGN_LABEL,
+            <crate::group::GroupInfo as Signable>::SIGN_LABEL,
+        ];
+
+        for label in labels {
+            assert!(PROTOCOL_SIGNATURE_LABELS.contains(&label));
+        }
+
+        for label in PROTOCOL_SIGNATURE_LABELS {
+            assert!(!label.starts_with(APPLICATION_SIGNATURE_NAMESPACE));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn collect_sign_labels(dir: &std::path::Path, labels: &mut Vec<String>) {
+        const PREFIX: &str = "const SIGN_LABEL: &'static str = \"";
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                collect_sign_labels(&path, labels);
+            } else if path.extension().map_or(false, |e| e == "rs") {
+                let source = std::fs::read_to_string(&path).unwrap();
+
+                labels.extend(source.lines().filter_map(|line| {
+                    let label = line.trim().strip_prefix(PREFIX)?;
+                    Some(label.split('"').next()?.to_string())
+                }));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn every_protocol_signature_label_is_listed() {
+        let mut labels = Vec::new();
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        collect_sign_labels(&src, &mut labels);
+
+        // Application signatures replace their label with an
+        // `ApplicationSignatureLabel`, and `SignWithLabel` is only used by
+        // the tests of this module.
+        let labels = labels
+            .iter()
+            .filter(|label| !["application", "SignWithLabel"].contains(&label.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(labels.len(), PROTOCOL_SIGNATURE_LABELS.len());
+
+        for label in labels {
+            assert!(
+                PROTOCOL_SIGNATURE_LABELS.contains(&label.as_str()),
+                "{label} is not a protocol signature label"
+            );
+        }
+    }
 }
@@ -2,6 +2,8 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+/// Storage providers persisting to page based flash memory, usable without std.
+pub mod flash;
 /// Storage providers that operate completely in memory.
 pub mod in_memory;
 pub(crate) mod key_package;
@@ -0,0 +1,174 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+mod page_log;
+mod storage_provider;
+
+pub use storage_provider::*;
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+use alloc::{vec, vec::Vec};
+use core::{
+    convert::Infallible,
+    fmt::{self, Debug},
+};
+use mls_rs_core::error::{AnyError, IntoAnyError};
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(not(feature = "std"))]
+use spin::Mutex;
+
+/// Value of a byte in an erased page.
+pub const ERASED_BYTE: u8 = 0xff;
+
+/// Page based non-volatile memory, such as the flash or EEPROM of a secure
+/// element.
+///
+/// Implementations must write pages atomically: after a power loss, a page
+/// holds either its previous or its new content. Pages are used in a round
+/// robin fashion by [`FlashStorageProvider`] to spread wear over the whole
+/// memory.
+pub trait FlashStorage: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Size of a page in bytes.
+    fn page_size(&self) -> usize;
+
+    /// Number of pages available for storage.
+    fn page_count(&self) -> usize;
+
+    /// Read page `page` into `buf`, which is [`page_size`](Self::page_size)
+    /// bytes long.
+    fn read_page(&self, page: usize, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Erase page `page` and program it with `data`. `data` is at most
+    /// [`page_size`](Self::page_size) bytes long and the rest of the page is
+    /// left erased.
+    fn write_page(&mut self, page: usize, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Erase page `page`.
+    fn erase_page(&mut self, page: usize) -> Result<(), Self::Error>;
+}
+
+/// [`FlashStorage`] simulated in RAM.
+///
+/// All clones of an instance of this type share the same memory, which makes
+/// it possible to simulate a restart by creating a new
+/// [`FlashStorageProvider`] from a clone. The number of times each page was
+/// written is tracked to evaluate wear.
+#[derive(Clone)]
+pub struct RamFlash {
+    page_size: usize,
+    pages: Arc<Mutex<Vec<RamPage>>>,
+}
+
+#[derive(Clone)]
+struct RamPage {
+    data: Vec<u8>,
+    writes: u64,
+}
+
+impl Debug for RamFlash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RamFlash")
+            .field("page_size", &self.page_size)
+            .field("page_count", &self.page_count())
+            .finish()
+    }
+}
+
+impl RamFlash {
+    /// Create an erased memory of `page_count` pages of `page_size` bytes.
+    pub fn new(page_size: usize, page_count: usize) -> Self {
+        let page = RamPage {
+            data: vec![ERASED_BYTE; page_size],
+            writes: 0,
+        };
+
+        Self {
+            page_size,
+            pages: Arc::new(Mutex::new(vec![page; page_count])),
+        }
+    }
+
+    /// Number of times each page was written or erased.
+    pub fn write_counts(&self) -> Vec<u64> {
+        self.with_pages(|pages| pages.iter().map(|p| p.writes).collect())
+    }
+
+    fn with_pages<T>(&self, f: impl FnOnce(&mut Vec<RamPage>) -> T) -> T {
+        #[cfg(feature = "std")]
+        return f(&mut self.pages.lock().unwrap());
+
+        #[cfg(not(feature = "std"))]
+        return f(&mut self.pages.lock());
+    }
+}
+
+impl FlashStorage for RamFlash {
+    type Error = Infallible;
+
+    fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    fn page_count(&self) -> usize {
+        self.with_pages(|pages| pages.len())
+    }
+
+    fn read_page(&self, page: usize, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.with_pages(|pages| buf.copy_from_slice(&pages[page].data));
+        Ok(())
+    }
+
+    fn write_page(&mut self, page: usize, data: &[u8]) -> Result<(), Self::Error> {
+        self.with_pages(|pages| {
+            let page = &mut pages[page];
+            page.data.fill(ERASED_BYTE);
+            page.data[..data.len()].copy_from_slice(data);
+            page.writes += 1;
+        });
+
+        Ok(())
+    }
+
+    fn erase_page(&mut self, page: usize) -> Result<(), Self::Error> {
+        self.write_page(page, &[])
+    }
+}
+
+/// Error returned by [`FlashStorageProvider`].
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[non_exhaustive]
+pub enum FlashStorageError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    FlashError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    SerializationError(AnyError),
+    #[cfg_attr(feature = "std", error("not enough free pages to store the value"))]
+    StorageFull,
+    #[cfg_attr(feature = "std", error("key does not fit in a page"))]
+    KeyTooLarge,
+}
+
+impl IntoAnyError for FlashStorageError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+impl From<mls_rs_codec::Error> for FlashStorageError {
+    fn from(e: mls_rs_codec::Error) -> Self {
+        Self::SerializationError(e.into_any_error())
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::error::IntoAnyError;
+
+use super::{FlashStorage, FlashStorageError};
+
+const PAGE_MAGIC: u32 = 0x4d4c_5350;
+
+/// Largest growth of the encoded payload length compared to an empty
+/// payload.
+const MAX_LENGTH_PREFIX_GROWTH: usize = 3;
+
+/// Content of a page. A value is split into chunks, each stored in its own
+/// page. All chunks of a value share the same version, which increases with
+/// every write so that the latest complete version of a key can be found
+/// after a restart.
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct Page {
+    magic: u32,
+    version: u64,
+    chunk_index: u32,
+    chunk_count: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    key: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    payload: Vec<u8>,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct PageRef<'a> {
+    magic: u32,
+    version: u64,
+    chunk_index: u32,
+    chunk_count: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    key: &'a [u8],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    payload: &'a [u8],
+}
+
+struct Record {
+    pages: Vec<usize>,
+}
+
+/// Log structured key value store on top of a [`FlashStorage`].
+///
+/// Values are never overwritten in place. A new version is written to free
+/// pages following the page written last, which spreads writes evenly over
+/// the memory, and the pages of the previous version are erased once the new
+/// version is complete, so that superseded secrets don't linger in memory. A
+/// power loss during a write leaves the previous version in place.
+pub(super) struct PageLog<F> {
+    flash: F,
+    records: BTreeMap<Vec<u8>, Record>,
+    page_owners: Vec<Option<(u64, Vec<u8>)>>,
+    used: Vec<bool>,
+    next_version: u64,
+    cursor: usize,
+}
+
+impl<F: FlashStorage> PageLog<F> {
+    pub fn open(flash: F) -> Result<Self, FlashStorageError> {
+        let page_count = flash.page_count();
+        let mut buf = vec![0; flash.page_size()];
+        let mut page_owners = vec![None; page_count];
+        let mut chunks = BTreeMap::<(Vec<u8>, u64), (u32, Vec<(u32, usize)>)>::new();
+        let mut last_written = None;
+
+        for (page, owner) in page_owners.iter_mut().enumerate() {
+            flash.read_page(page, &mut buf).map_err(flash_error)?;
+
+            let Some(content) = Page::mls_decode(&mut &*buf)
+                .ok()
+                .filter(|p| p.magic == PAGE_MAGIC && p.chunk_index < p.chunk_count)
+            else {
+                continue;
+            };
+
+            if last_written.map_or(true, |(version, _)| content.version >= version) {
+                last_written = Some((content.version, page));
+            }
+
+            *owner = Some((content.version, content.key.clone()));
+
+            chunks
+                .entry((content.key, content.version))
+                .or_insert_with(|| (content.chunk_count, Vec::new()))
+                .1
+                .push((content.chunk_index, page));
+        }
+
+        let mut records = BTreeMap::<Vec<u8>, Record>::new();
+
+        // Versions of a key are visited in increasing order, so the latest
+        // complete version wins.
+        for ((key, _), (chunk_count, mut pages)) in chunks {
+            pages.sort_unstable();
+            pages.dedup_by_key(|(index, _)| *index);
+
+            if pages.len() == chunk_count as usize {
+                let pages = pages.into_iter().map(|(_, page)| page).collect();
+                records.insert(key, Record { pages });
+            }
+        }
+
+        let mut used = vec![false; page_count];
+
+        records
+            .values()
+            .flat_map(|r| &r.pages)
+            .for_each(|page| used[*page] = true);
+
+        let (next_version, cursor) = last_written.map_or((0, 0), |(version, page)| {
+            (version + 1, (page + 1) % page_count.max(1))
+        });
+
+        Ok(Self {
+            flash,
+            records,
+            page_owners,
+            used,
+            next_version,
+            cursor,
+        })
+    }
+
+    pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, FlashStorageError> {
+        let Some(record) = self.records.get(key) else {
+            return Ok(None);
+        };
+
+        let mut buf = vec![0; self.flash.page_size()];
+        let mut value = Vec::new();
+
+        for page in &record.pages {
+            self.flash.read_page(*page, &mut buf).map_err(flash_error)?;
+            value.extend(Page::mls_decode(&mut &*buf)?.payload);
+        }
+
+        Ok(Some(value))
+    }
+
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), FlashStorageError> {
+        let capacity = self.chunk_capacity(key)?;
+
+        let chunks = match value.is_empty() {
+            true => vec![value],
+            false => value.chunks(capacity).collect(),
+        };
+
+        let pages = self.allocate(chunks.len())?;
+        let version = self.next_version;
+
+        for (chunk_index, (payload, page)) in chunks.iter().zip(&pages).enumerate() {
+            let content = PageRef {
+                magic: PAGE_MAGIC,
+                version,
+                chunk_index: chunk_index as u32,
+                chunk_count: chunks.len() as u32,
+                key,
+                payload,
+            }
+            .mls_encode_to_vec()?;
+
+            self.flash
+                .write_page(*page, &content)
+                .map_err(flash_error)?;
+
+            self.page_owners[*page] = Some((version, key.to_vec()));
+            self.cursor = (*page + 1) % self.used.len();
+        }
+
+        self.next_version += 1;
+
+        pages.iter().for_each(|page| self.used[*page] = true);
+        self.records.insert(key.to_vec(), Record { pages });
+
+        self.erase_versions(key, version)
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Result<(), FlashStorageError> {
+        if self.records.remove(key).is_none() {
+            return Ok(());
+        }
+
+        self.erase_versions(key, self.next_version)
+    }
+
+    pub fn keys_with_prefix<'a>(&'a self, prefix: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+        self.records
+            .range(prefix.to_vec()..)
+            .map(|(key, _)| key.as_slice())
+            .take_while(move |key| key.starts_with(prefix))
+    }
+
+    pub fn free_pages(&self) -> usize {
+        self.used.iter().filter(|used| !**used).count()
+    }
+
+    /// Erase the pages of `key` written before version `before`.
+    fn erase_versions(&mut self, key: &[u8], before: u64) -> Result<(), FlashStorageError> {
+        // Older versions are erased first so that a power loss can't bring
+        // them back as the latest version.
+        let mut pages = self
+            .page_owners
+            .iter()
+            .enumerate()
+            .filter_map(|(page, owner)| match owner {
+                Some((version, owner)) if owner == key && *version < before => {
+                    Some((*version, page))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        pages.sort_unstable();
+
+        for (_, page) in pages {
+            self.flash.erase_page(page).map_err(flash_error)?;
+            self.page_owners[page] = None;
+            self.used[page] = false;
+        }
+
+        Ok(())
+    }
+
+    fn chunk_capacity(&self, key: &[u8]) -> Result<usize, FlashStorageError> {
+        let overhead = PageRef {
+            magic: PAGE_MAGIC,
+            version: 0,
+            chunk_index: 0,
+            chunk_count: 0,
+            key,
+            payload: &[],
+        }
+        .mls_encoded_len()
+            + MAX_LENGTH_PREFIX_GROWTH;
+
+        self.flash
+            .page_size()
+            .checked_sub(overhead)
+            .filter(|capacity| *capacity > 0)
+            .ok_or(FlashStorageError::KeyTooLarge)
+    }
+
+    fn allocate(&self, count: usize) -> Result<Vec<usize>, FlashStorageError> {
+        let page_count = self.used.len();
+
+        let pages = (0..page_count)
+            .map(|offset| (self.cursor + offset) % page_count)
+            .filter(|page| !self.used[*page])
+            .take(count)
+            .collect::<Vec<_>>();
+
+        (pages.len() == count)
+            .then_some(pages)
+            .ok_or(FlashStorageError::StorageFull)
+    }
+}
+
+fn flash_error<E: IntoAnyError>(e: E) -> FlashStorageError {
+    FlashStorageError::FlashError(e.into_any_error())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::storage_provider::flash::{FlashStorage, FlashStorageError, RamFlash};
+
+    use super::PageLog;
+
+    #[test]
+    fn values_are_chunked_and_survive_reopening() {
+        let flash = RamFlash::new(64, 16);
+        let mut log = PageLog::open(flash.clone()).unwrap();
+
+        let value = (0..200u8).collect::<vec::Vec<_>>();
+        log.insert(b"large", &value).unwrap();
+        log.insert(b"empty", &[]).unwrap();
+        log.insert(b"removed", b"value").unwrap();
+        log.remove(b"removed").unwrap();
+
+        assert!(log.free_pages() < 16 - 4);
+
+        let log = PageLog::open(flash).unwrap();
+
+        assert_eq!(log.get(b"large").unwrap(), Some(value));
+        assert_eq!(log.get(b"empty").unwrap(), Some(vec![]));
+        assert_eq!(log.get(b"removed").unwrap(), None);
+    }
+
+    #[test]
+    fn writes_are_spread_over_pages() {
+        let flash = RamFlash::new(64, 8);
+        let mut log = PageLog::open(flash.clone()).unwrap();
+
+        log.insert(b"static", b"value").unwrap();
+
+        for i in 0..70u8 {
+            log.insert(b"counter", &[i]).unwrap();
+        }
+
+        let writes = flash.write_counts();
+        let dynamic = writes.iter().filter(|w| **w > 1);
+
+        // Each page of the counter is written, then erased once superseded
+        assert!(dynamic.clone().all(|w| (19..=21).contains(w)));
+        assert_eq!(dynamic.count(), 7);
+
+        let log = PageLog::open(flash).unwrap();
+        assert_eq!(log.get(b"counter").unwrap(), Some(vec![69]));
+    }
+
+    #[test]
+    fn superseded_values_are_erased() {
+        let flash = RamFlash::new(64, 8);
+        let mut log = PageLog::open(flash.clone()).unwrap();
+
+        log.insert(b"key", &[1; 80]).unwrap();
+        let free_pages = log.free_pages();
+
+        log.insert(b"key", &[2; 80]).unwrap();
+
+        let mut buf = vec![0; 64];
+
+        for page in 0..8 {
+            flash.read_page(page, &mut buf).unwrap();
+            assert!(!buf.windows(16).any(|w| w == [1; 16]));
+        }
+
+        assert_eq!(log.free_pages(), free_pages);
+    }
+
+    #[test]
+    fn full_storage_keeps_previous_value() {
+        let mut log = PageLog::open(RamFlash::new(64, 4)).unwrap();
+
+        log.insert(b"key", &[1; 60]).unwrap();
+
+        let res = log.insert(b"key", &[2; 200]);
+        assert_matches!(res, Err(FlashStorageError::StorageFull));
+        assert_eq!(log.get(b"key").unwrap(), Some(vec![1; 60]));
+
+        let res = log.insert(&[0; 64], &[]);
+        assert_matches!(res, Err(FlashStorageError::KeyTooLarge));
+    }
+}
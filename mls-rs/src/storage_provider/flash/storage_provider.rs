@@ -0,0 +1,357 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use mls_rs_codec::{MlsDecode, MlsEncode};
+use mls_rs_core::{
+    group::{EpochRecord, GroupState, GroupStateStorage},
+    key_package::{KeyPackageData, KeyPackageQuery, KeyPackageStorage},
+    psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+};
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+use crate::{client::MlsError, storage_provider::in_memory::DEFAULT_EPOCH_RETENTION_LIMIT};
+
+use super::{page_log::PageLog, FlashStorage, FlashStorageError};
+
+const GROUP_STATE_TAG: u8 = 0;
+const EPOCH_TAG: u8 = 1;
+const KEY_PACKAGE_TAG: u8 = 2;
+const PSK_TAG: u8 = 3;
+
+/// Group state, key package and pre-shared key storage persisting to a
+/// [`FlashStorage`].
+///
+/// This provider does not require `std` and is meant for devices such as
+/// secure elements where the only available storage is a small page based
+/// memory. Each value is split into chunks stored in separate pages and
+/// updates are written to free pages in a round robin fashion, so that the
+/// pages of a frequently updated group wear out evenly.
+///
+/// All clones of a provider share the same memory and the provider
+/// implements [`GroupStateStorage`], [`KeyPackageStorage`] and
+/// [`PreSharedKeyStorage`], so a single instance can back a whole client.
+#[derive(Clone)]
+pub struct FlashStorageProvider<F> {
+    inner: Arc<Mutex<PageLog<F>>>,
+    max_epoch_retention: usize,
+}
+
+impl<F> Debug for FlashStorageProvider<F>
+where
+    F: FlashStorage,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlashStorageProvider")
+            .field("free_pages", &self.free_pages())
+            .field("max_epoch_retention", &self.max_epoch_retention)
+            .finish()
+    }
+}
+
+impl<F> FlashStorageProvider<F>
+where
+    F: FlashStorage,
+{
+    /// Create a provider from the content of `flash`. Values written by a
+    /// previous instance are loaded.
+    pub fn new(flash: F) -> Result<Self, FlashStorageError> {
+        Ok(Self {
+            inner: Arc::new(Mutex::new(PageLog::open(flash)?)),
+            max_epoch_retention: DEFAULT_EPOCH_RETENTION_LIMIT,
+        })
+    }
+
+    pub fn with_max_epoch_retention(self, max_epoch_retention: usize) -> Result<Self, MlsError> {
+        (max_epoch_retention > 0)
+            .then_some(())
+            .ok_or(MlsError::NonZeroRetentionRequired)?;
+
+        Ok(Self {
+            inner: self.inner,
+            max_epoch_retention,
+        })
+    }
+
+    /// Insert a pre-shared key into storage.
+    pub fn insert_psk(
+        &self,
+        id: &ExternalPskId,
+        psk: &PreSharedKey,
+    ) -> Result<(), FlashStorageError> {
+        self.lock()
+            .insert(&tagged_key(PSK_TAG, id), psk.raw_value())
+    }
+
+    /// Delete all data corresponding to `group_id`.
+    pub fn delete_group(&self, group_id: &[u8]) -> Result<(), FlashStorageError> {
+        let mut log = self.lock();
+
+        let epochs = log
+            .keys_with_prefix(&epoch_prefix(group_id))
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+
+        for key in epochs {
+            log.remove(&key)?;
+        }
+
+        log.remove(&tagged_key(GROUP_STATE_TAG, group_id))
+    }
+
+    /// Number of pages not holding current data.
+    pub fn free_pages(&self) -> usize {
+        self.lock().free_pages()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PageLog<F>> {
+        #[cfg(feature = "std")]
+        return self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        return self.inner.lock();
+    }
+}
+
+fn tagged_key(tag: u8, id: &[u8]) -> Vec<u8> {
+    [&[tag], id].concat()
+}
+
+fn epoch_prefix(group_id: &[u8]) -> Vec<u8> {
+    [
+        &[EPOCH_TAG],
+        &(group_id.len() as u32).to_be_bytes()[..],
+        group_id,
+    ]
+    .concat()
+}
+
+fn epoch_key(group_id: &[u8], epoch_id: u64) -> Vec<u8> {
+    [epoch_prefix(group_id), epoch_id.to_be_bytes().to_vec()].concat()
+}
+
+fn epoch_id(key: &[u8]) -> Option<u64> {
+    let bytes = key.get(key.len().checked_sub(8)?..)?;
+    Some(u64::from_be_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<F> GroupStateStorage for FlashStorageProvider<F>
+where
+    F: FlashStorage,
+{
+    type Error = FlashStorageError;
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.lock().get(&tagged_key(GROUP_STATE_TAG, group_id))
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.lock().get(&epoch_key(group_id, epoch_id))
+    }
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        epoch_inserts: Vec<EpochRecord>,
+        epoch_updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        let mut log = self.lock();
+
+        for epoch in epoch_inserts.iter().chain(&epoch_updates) {
+            log.insert(&epoch_key(&state.id, epoch.id), &epoch.data)?;
+        }
+
+        log.insert(&tagged_key(GROUP_STATE_TAG, &state.id), &state.data)?;
+
+        let epochs = log
+            .keys_with_prefix(&epoch_prefix(&state.id))
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+
+        let expired = epochs.len().saturating_sub(self.max_epoch_retention);
+
+        for key in &epochs[..expired] {
+            log.remove(key)?;
+        }
+
+        Ok(())
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        Ok(self
+            .lock()
+            .keys_with_prefix(&epoch_prefix(group_id))
+            .last()
+            .and_then(epoch_id))
+    }
+
+    async fn delete_epochs_under(
+        &mut self,
+        group_id: &[u8],
+        epoch_id_under: u64,
+    ) -> Result<bool, Self::Error> {
+        let mut log = self.lock();
+
+        let expired = log
+            .keys_with_prefix(&epoch_prefix(group_id))
+            .filter(|key| epoch_id(key).map_or(false, |id| id < epoch_id_under))
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+
+        // Removing a record erases all pages that held any version of it
+        for key in expired {
+            log.remove(&key)?;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<F> KeyPackageStorage for FlashStorageProvider<F>
+where
+    F: FlashStorage,
+{
+    type Error = FlashStorageError;
+
+    async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
+        self.lock().remove(&tagged_key(KEY_PACKAGE_TAG, id))
+    }
+
+    async fn insert(&mut self, id: Vec<u8>, pkg: KeyPackageData) -> Result<(), Self::Error> {
+        self.lock()
+            .insert(&tagged_key(KEY_PACKAGE_TAG, &id), &pkg.mls_encode_to_vec()?)
+    }
+
+    async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
+        self.lock()
+            .get(&tagged_key(KEY_PACKAGE_TAG, id))?
+            .map(|data| KeyPackageData::mls_decode(&mut &*data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn list(
+        &self,
+        query: &KeyPackageQuery,
+    ) -> Result<Option<Vec<(Vec<u8>, KeyPackageData)>>, Self::Error> {
+        let log = self.lock();
+        let mut key_packages = Vec::new();
+
+        for key in log.keys_with_prefix(&[KEY_PACKAGE_TAG]) {
+            let Some(data) = log.get(key)? else {
+                continue;
+            };
+
+            let pkg = KeyPackageData::mls_decode(&mut &*data)?;
+
+            if query.matches(&pkg) {
+                key_packages.push((key[1..].to_vec(), pkg));
+            }
+        }
+
+        Ok(Some(key_packages))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<F> PreSharedKeyStorage for FlashStorageProvider<F>
+where
+    F: FlashStorage,
+{
+    type Error = FlashStorageError;
+
+    async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+        Ok(self
+            .lock()
+            .get(&tagged_key(PSK_TAG, id))?
+            .map(PreSharedKey::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use mls_rs_core::psk::{ExternalPskId, PreSharedKey};
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+        group::test_utils::TEST_GROUP,
+        storage_provider::flash::{FlashStorageProvider, RamFlash},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn client_runs_on_flash_storage() {
+        let psk_id = ExternalPskId::new(vec![1]);
+        let psk = PreSharedKey::new(vec![2; 32]);
+
+        let alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .psk(psk_id.clone(), psk.clone())
+            .build();
+
+        let mut alice_group = alice
+            .create_group_with_id(TEST_GROUP.to_vec(), Default::default(), Default::default())
+            .await
+            .unwrap();
+
+        let flash = RamFlash::new(256, 128);
+        let storage = FlashStorageProvider::new(flash.clone()).unwrap();
+        storage.insert_psk(&psk_id, &psk).unwrap();
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(storage.clone())
+            .key_package_repo(storage.clone())
+            .psk_store(storage)
+            .build();
+
+        let group_info = alice_group
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let (mut bob_group, commit) = bob
+            .external_commit_builder()
+            .unwrap()
+            .with_external_psk(psk_id)
+            .build(group_info)
+            .await
+            .unwrap();
+
+        alice_group.process_incoming_message(commit).await.unwrap();
+
+        bob_group.write_to_storage().await.unwrap();
+
+        let restarted = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(FlashStorageProvider::new(flash).unwrap())
+            .build();
+
+        let bob_group = restarted.load_group(TEST_GROUP).await.unwrap();
+
+        assert_eq!(bob_group.current_epoch(), alice_group.current_epoch());
+    }
+}
@@ -4,8 +4,10 @@
 
 mod group_state_storage;
 mod key_package_storage;
+mod keychain_storage;
 mod psk_storage;
 
 pub use group_state_storage::*;
 pub use key_package_storage::*;
+pub use keychain_storage::*;
 pub use psk_storage::*;
@@ -17,6 +17,7 @@ use core::{
 use mls_rs_core::group::{EpochRecord, GroupState, GroupStateStorage};
 #[cfg(not(target_has_atomic = "ptr"))]
 use portable_atomic_util::Arc;
+use zeroize::Zeroize;
 
 use crate::{
     client::MlsError,
@@ -35,6 +36,7 @@ pub(crate) const DEFAULT_EPOCH_RETENTION_LIMIT: usize = 3;
 pub(crate) struct InMemoryGroupData {
     pub(crate) state_data: Vec<u8>,
     pub(crate) epoch_data: VecDeque<EpochRecord>,
+    pub(crate) integrity_counter: Option<u64>,
 }
 
 impl Debug for InMemoryGroupData {
@@ -45,6 +47,7 @@ impl Debug for InMemoryGroupData {
                 &mls_rs_core::debug::pretty_bytes(&self.state_data),
             )
             .field("epoch_data", &self.epoch_data)
+            .field("integrity_counter", &self.integrity_counter)
             .finish()
     }
 }
@@ -54,6 +57,7 @@ impl InMemoryGroupData {
         InMemoryGroupData {
             state_data,
             epoch_data: Default::default(),
+            integrity_counter: None,
         }
     }
 
@@ -91,6 +95,14 @@ impl InMemoryGroupData {
             self.epoch_data.pop_front();
         }
     }
+
+    pub fn delete_epochs_under(&mut self, epoch_id: u64) {
+        while self.epoch_data.front().map_or(false, |e| e.id < epoch_id) {
+            if let Some(mut epoch) = self.epoch_data.pop_front() {
+                epoch.data.zeroize();
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -222,6 +234,37 @@ impl GroupStateStorage for InMemoryGroupStateStorage {
 
         Ok(())
     }
+
+    async fn delete_epochs_under(
+        &mut self,
+        group_id: &[u8],
+        epoch_id: u64,
+    ) -> Result<bool, Self::Error> {
+        if let Some(group_data) = self.lock().get_mut(group_id) {
+            group_data.delete_epochs_under(epoch_id);
+        }
+
+        Ok(true)
+    }
+
+    async fn integrity_counter(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        Ok(self
+            .lock()
+            .get(group_id)
+            .and_then(|data| data.integrity_counter))
+    }
+
+    async fn set_integrity_counter(
+        &mut self,
+        group_id: &[u8],
+        counter: u64,
+    ) -> Result<(), Self::Error> {
+        if let Some(group_data) = self.lock().get_mut(group_id) {
+            group_data.integrity_counter = Some(counter);
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "prior_epoch"))]
@@ -14,7 +14,7 @@ use core::{
 };
 
 use alloc::vec::Vec;
-use mls_rs_core::key_package::{KeyPackageData, KeyPackageStorage};
+use mls_rs_core::key_package::{KeyPackageData, KeyPackageQuery, KeyPackageStorage};
 
 #[cfg(feature = "std")]
 use std::sync::{Mutex, MutexGuard};
@@ -109,4 +109,28 @@ impl KeyPackageStorage for InMemoryKeyPackageStorage {
     async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
         Ok(self.get(id))
     }
+
+    async fn list(
+        &self,
+        query: &KeyPackageQuery,
+    ) -> Result<Option<Vec<(Vec<u8>, KeyPackageData)>>, Self::Error> {
+        let key_packages = self
+            .lock()
+            .iter()
+            .filter(|(_, pkg)| query.matches(pkg))
+            .map(|(id, pkg)| (id.clone(), pkg.clone()))
+            .collect();
+
+        Ok(Some(key_packages))
+    }
+
+    async fn count(&self, query: &KeyPackageQuery) -> Result<Option<usize>, Self::Error> {
+        let count = self
+            .lock()
+            .values()
+            .filter(|pkg| query.matches(pkg))
+            .count();
+
+        Ok(Some(count))
+    }
 }
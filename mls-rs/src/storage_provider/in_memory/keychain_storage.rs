@@ -0,0 +1,100 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use mls_rs_core::{
+    crypto::{CipherSuite, SignatureSecretKey},
+    identity::{KeychainStorage, SigningIdentity},
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(not(feature = "std"))]
+use spin::Mutex;
+
+#[derive(Clone, Debug, Default)]
+/// In memory keychain storage backed by a Vec.
+///
+/// All clones of an instance of this type share the same underlying Vec.
+pub struct InMemoryKeychainStorage {
+    inner: Arc<Mutex<Vec<(CipherSuite, SigningIdentity, SignatureSecretKey)>>>,
+}
+
+impl InMemoryKeychainStorage {
+    /// Insert a signing identity and signer for `cipher_suite` into storage,
+    /// replacing any previously stored value.
+    pub fn insert(
+        &mut self,
+        cipher_suite: CipherSuite,
+        signing_identity: SigningIdentity,
+        signer: SignatureSecretKey,
+    ) {
+        #[cfg(feature = "std")]
+        let mut lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let mut lock = self.inner.lock();
+
+        lock.retain(|(cs, ..)| *cs != cipher_suite);
+        lock.push((cipher_suite, signing_identity, signer));
+    }
+
+    /// Get the signing identity and signer stored for `cipher_suite`.
+    pub fn get(&self, cipher_suite: CipherSuite) -> Option<(SigningIdentity, SignatureSecretKey)> {
+        #[cfg(feature = "std")]
+        let lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let lock = self.inner.lock();
+
+        lock.iter()
+            .find(|(cs, ..)| *cs == cipher_suite)
+            .map(|(_, identity, signer)| (identity.clone(), signer.clone()))
+    }
+
+    /// Delete the signing identity stored for `cipher_suite`.
+    pub fn delete(&mut self, cipher_suite: CipherSuite) {
+        #[cfg(feature = "std")]
+        let mut lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let mut lock = self.inner.lock();
+
+        lock.retain(|(cs, ..)| *cs != cipher_suite);
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl KeychainStorage for InMemoryKeychainStorage {
+    type Error = Infallible;
+
+    async fn signing_identity(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, Self::Error> {
+        Ok(self.get(cipher_suite))
+    }
+
+    async fn insert(
+        &mut self,
+        cipher_suite: CipherSuite,
+        signing_identity: SigningIdentity,
+        signer: SignatureSecretKey,
+    ) -> Result<(), Self::Error> {
+        self.insert(cipher_suite, signing_identity, signer);
+        Ok(())
+    }
+}
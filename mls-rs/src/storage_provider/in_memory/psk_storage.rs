@@ -14,6 +14,7 @@ use mls_rs_core::psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage};
 
 #[cfg(mls_build_async)]
 use alloc::boxed::Box;
+use alloc::vec::Vec;
 #[cfg(feature = "std")]
 use std::sync::Mutex;
 
@@ -73,4 +74,17 @@ impl PreSharedKeyStorage for InMemoryPreSharedKeyStorage {
     async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
         Ok(self.get(id))
     }
+
+    async fn get_many(
+        &self,
+        ids: &[ExternalPskId],
+    ) -> Result<Vec<Option<PreSharedKey>>, Self::Error> {
+        #[cfg(feature = "std")]
+        let lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let lock = self.inner.lock();
+
+        Ok(ids.iter().map(|id| lock.get(id).cloned()).collect())
+    }
 }
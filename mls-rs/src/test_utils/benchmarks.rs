@@ -104,6 +104,33 @@ pub fn load_group_states(cs: CipherSuite) -> Vec<GroupStates<impl MlsConfig>> {
         .collect()
 }
 
+/// Groups of 16, 64 and 128 members created with a single commit, as seen by
+/// the member at leaf 1. All subtrees on the copath of the sender are blank,
+/// so an update path is encrypted to the leaves of all other members.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn load_wide_group_states(cs: CipherSuite) -> Vec<GroupStates<impl MlsConfig>> {
+    let mut states = Vec::new();
+
+    for size in [16, 64, 128] {
+        let mut groups = get_test_groups(
+            ProtocolVersion::MLS_10,
+            cs,
+            size,
+            None,
+            false,
+            &MlsCryptoProvider::new(),
+        )
+        .await;
+
+        let sender = groups.swap_remove(1);
+        let receiver = groups.swap_remove(0);
+
+        states.push(GroupStates { sender, receiver });
+    }
+
+    states
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub async fn join_group(cs: CipherSuite, group_info: MlsMessage) -> GroupStates<impl MlsConfig> {
     let client = generate_basic_client(
@@ -0,0 +1,209 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Message serialization test vectors in the format of `messages.json`.
+//!
+//! The generator returns [`MlsError::UnsupportedCipherSuite`] if the crypto
+//! provider does not support the requested cipher suite, and panics if any
+//! group operation fails with a supported one.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use mls_rs_codec::MlsEncode;
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
+    protocol_version::ProtocolVersion,
+    psk::ExternalPskId,
+};
+
+use crate::{
+    client::MlsError,
+    group::{
+        framing::{Content, MlsMessagePayload},
+        proposal::{
+            AddProposal, ExternalInit, PreSharedKeyProposal, ReInitProposal, RemoveProposal,
+            UpdateProposal,
+        },
+        GroupSecrets,
+    },
+    psk::{JustPreSharedKeyID, PreSharedKeyID, PskNonce},
+    test_utils::{get_test_groups, TEST_EXT_PSK_ID},
+    tree_kem::{hpke_encryption::HpkeEncryptable, node::LeafIndex},
+    MlsMessage,
+};
+
+use super::key_package_with_secrets;
+
+const VERSION: ProtocolVersion = ProtocolVersion::MLS_10;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+pub struct MessagesTestCase {
+    #[serde(with = "hex::serde")]
+    pub mls_welcome: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub mls_group_info: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub mls_key_package: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub ratchet_tree: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub group_secrets: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub add_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub update_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub remove_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub pre_shared_key_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub re_init_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub external_init_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub group_context_extensions_proposal: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub commit: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub public_message_application: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub public_message_proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub public_message_commit: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub private_message: Vec<u8>,
+}
+
+/// Generate a test case with messages and structures of a group using
+/// `cipher_suite`.
+///
+/// Messages are produced by group operations and are valid in the context
+/// of that group, except for the public message carrying application data,
+/// which the protocol does not allow and is assembled by hand.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+pub async fn generate<C: CryptoProvider + Clone>(
+    crypto_provider: &C,
+    cipher_suite: CipherSuite,
+) -> Result<MessagesTestCase, MlsError> {
+    let cs = crypto_provider
+        .cipher_suite_provider(cipher_suite)
+        .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
+
+    let mut groups = get_test_groups(VERSION, cipher_suite, 2, None, false, crypto_provider).await;
+
+    let private_message = groups[0]
+        .encrypt_application_message(b"message", vec![])
+        .await
+        .unwrap();
+
+    let (key_package, key_package_data) =
+        key_package_with_secrets(crypto_provider, cipher_suite, b"new member").await;
+
+    let public_message_proposal = groups[0]
+        .propose_add(key_package.clone(), vec![])
+        .await
+        .unwrap();
+
+    let commit_output = groups[0].commit(vec![]).await.unwrap();
+    groups[0].apply_pending_commit().await.unwrap();
+
+    let public_message_commit = commit_output.commit_message;
+    let welcome = &commit_output.welcome_messages[0];
+
+    let Some(Content::Commit(commit)) = public_message_commit
+        .clone()
+        .into_plaintext()
+        .map(|m| m.content.content)
+    else {
+        panic!("commit is not a public message");
+    };
+
+    // Application data is never sent as a public message, so the framing of
+    // the proposal is reused
+    let mut public_application = public_message_proposal.clone().into_plaintext().unwrap();
+    public_application.content.content = Content::Application(b"message".to_vec().into());
+
+    let public_message_application =
+        MlsMessage::new(VERSION, MlsMessagePayload::Plain(public_application));
+
+    let mls_key_package = key_package.to_bytes().unwrap();
+    let key_package = key_package.into_key_package().unwrap();
+    let welcome_content = welcome.clone().into_welcome().unwrap();
+
+    let group_secrets = GroupSecrets::decrypt(
+        &cs,
+        &key_package_data.init_key,
+        &key_package.hpke_init_key,
+        &welcome_content.encrypted_group_info,
+        &welcome_content.secrets[0].encrypted_group_secrets,
+    )
+    .await
+    .unwrap();
+
+    let update = UpdateProposal {
+        leaf_node: groups[0]
+            .state
+            .public_tree
+            .get_leaf_node(LeafIndex(1))
+            .unwrap()
+            .clone(),
+    };
+
+    let psk = PreSharedKeyProposal {
+        psk: PreSharedKeyID {
+            key_id: JustPreSharedKeyID::External(ExternalPskId::new(TEST_EXT_PSK_ID.to_vec())),
+            psk_nonce: PskNonce::random(&cs).unwrap(),
+        },
+    };
+
+    let re_init = ReInitProposal {
+        group_id: groups[0].group_id().to_vec(),
+        version: VERSION,
+        cipher_suite,
+        extensions: Default::default(),
+    };
+
+    let external_init = ExternalInit {
+        kem_output: cs.random_bytes_vec(cs.kdf_extract_size()).unwrap(),
+    };
+
+    Ok(MessagesTestCase {
+        mls_welcome: welcome.to_bytes().unwrap(),
+        mls_group_info: groups[0]
+            .group_info_message(true)
+            .await
+            .unwrap()
+            .to_bytes()
+            .unwrap(),
+        mls_key_package,
+        ratchet_tree: groups[0].export_tree().to_bytes().unwrap(),
+        group_secrets: group_secrets.mls_encode_to_vec().unwrap(),
+        add_proposal: AddProposal { key_package }.mls_encode_to_vec().unwrap(),
+        update_proposal: update.mls_encode_to_vec().unwrap(),
+        remove_proposal: RemoveProposal {
+            to_remove: LeafIndex(1),
+        }
+        .mls_encode_to_vec()
+        .unwrap(),
+        pre_shared_key_proposal: psk.mls_encode_to_vec().unwrap(),
+        re_init_proposal: re_init.mls_encode_to_vec().unwrap(),
+        external_init_proposal: external_init.mls_encode_to_vec().unwrap(),
+        group_context_extensions_proposal: groups[0]
+            .context()
+            .extensions
+            .mls_encode_to_vec()
+            .unwrap(),
+        commit: commit.mls_encode_to_vec().unwrap(),
+        public_message_application: public_message_application.to_bytes().unwrap(),
+        public_message_proposal: public_message_proposal.to_bytes().unwrap(),
+        public_message_commit: public_message_commit.to_bytes().unwrap(),
+        private_message: private_message.to_bytes().unwrap(),
+    })
+}
@@ -0,0 +1,71 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Generators for the test vectors shared between MLS implementations at
+//! <https://github.com/mlswg/mls-implementations>.
+//!
+//! Generators are generic over the crypto provider, so vectors can be
+//! produced for any cipher suite it supports, including custom ones. Inputs
+//! are random, so every call produces a fresh vector. Test cases serialize
+//! with `serde` to the JSON format used by the interop repository.
+
+/// Key schedule test vectors in the format of `key-schedule.json`.
+pub mod key_schedule {
+    pub use crate::group::key_schedule::test_vectors::{
+        generate, KeyScheduleEpoch, KeyScheduleExporter, KeyScheduleTestCase,
+    };
+}
+
+/// Secret tree test vectors in the format of `secret-tree.json`.
+pub mod secret_tree {
+    pub use crate::group::secret_tree::test_vectors::{
+        generate, SecretTreeLeaf, SecretTreeTestCase, SenderDataTestCase,
+    };
+}
+
+pub mod messages;
+pub mod passive_client;
+pub mod tree_operations;
+pub mod tree_validation;
+pub mod welcome;
+
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
+    identity::SigningIdentity,
+    key_package::KeyPackageData,
+};
+
+use crate::{
+    client_builder::ClientBuilder, identity::basic::BasicIdentityProvider,
+    storage_provider::in_memory::InMemoryKeyPackageStorage, test_utils::get_test_basic_credential,
+    MlsMessage,
+};
+
+/// Key package of a new client named `name`, with the secrets stored for it.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+async fn key_package_with_secrets<C: CryptoProvider + Clone>(
+    crypto_provider: &C,
+    cipher_suite: CipherSuite,
+    name: &[u8],
+) -> (MlsMessage, KeyPackageData) {
+    let cs = crypto_provider.cipher_suite_provider(cipher_suite).unwrap();
+    let (secret_key, public_key) = cs.signature_key_generate().await.unwrap();
+    let identity = SigningIdentity::new(get_test_basic_credential(name.to_vec()), public_key);
+    let key_package_repo = InMemoryKeyPackageStorage::new();
+
+    let key_package = ClientBuilder::new()
+        .crypto_provider(crypto_provider.clone())
+        .identity_provider(BasicIdentityProvider::new())
+        .key_package_repo(key_package_repo.clone())
+        .signing_identity(identity, secret_key, cipher_suite)
+        .build()
+        .generate_key_package_message(Default::default(), Default::default())
+        .await
+        .unwrap();
+
+    let (_, key_package_data) = key_package_repo.key_packages()[0].clone();
+
+    (key_package, key_package_data)
+}
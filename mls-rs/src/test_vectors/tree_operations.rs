@@ -0,0 +1,199 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Tree operations test vectors in the format of `tree-operations.json`.
+//!
+//! The generator returns [`MlsError::UnsupportedCipherSuite`] if the crypto
+//! provider does not support the requested cipher suite, and panics if any
+//! group operation fails with a supported one.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use mls_rs_codec::MlsEncode;
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
+    protocol_version::ProtocolVersion,
+};
+
+use crate::{
+    client::MlsError,
+    client_builder::MlsConfig,
+    group::{
+        framing::Content,
+        proposal::{AddProposal, Proposal, RemoveProposal},
+        proposal_filter::{ProposalBundle, ProposalSource},
+        Sender,
+    },
+    identity::basic::BasicIdentityProvider,
+    test_utils::{generate_basic_client, get_test_groups},
+    tree_kem::{node::LeafIndex, TreeKemPublic},
+    Group,
+};
+
+const VERSION: ProtocolVersion = ProtocolVersion::MLS_10;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+pub struct TreeOperationsTestCase {
+    #[serde(with = "hex::serde")]
+    pub tree_before: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub proposal: Vec<u8>,
+    pub proposal_sender: u32,
+    #[serde(with = "hex::serde")]
+    pub tree_after: Vec<u8>,
+}
+
+impl TreeOperationsTestCase {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(coverage_nightly, coverage(off))]
+    async fn new<P: CipherSuiteProvider>(
+        tree_before: &TreeKemPublic,
+        proposal: Proposal,
+        proposal_sender: u32,
+        cs: &P,
+    ) -> Self {
+        let mut tree_after = tree_before.clone();
+        let mut bundle = ProposalBundle::default();
+
+        if matches!(proposal, Proposal::Update(_)) {
+            bundle.update_senders = vec![LeafIndex(proposal_sender)];
+        }
+
+        let proposal_bytes = proposal.mls_encode_to_vec().unwrap();
+
+        bundle.add(
+            proposal,
+            Sender::Member(proposal_sender),
+            ProposalSource::ByValue,
+        );
+
+        tree_after
+            .batch_edit(
+                &mut bundle,
+                &Default::default(),
+                &BasicIdentityProvider,
+                cs,
+                true,
+            )
+            .await
+            .unwrap();
+
+        Self {
+            tree_before: tree_before.nodes.mls_encode_to_vec().unwrap(),
+            proposal: proposal_bytes,
+            proposal_sender,
+            tree_after: tree_after.nodes.mls_encode_to_vec().unwrap(),
+        }
+    }
+}
+
+/// Generate test cases applying an update, adds filling a blank leaf or
+/// extending the tree, and removes in the middle or at the end of the tree,
+/// for `cipher_suite`. The test vectors of the interop repository all use
+/// [`CipherSuite::CURVE25519_AES128`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+pub async fn generate<C: CryptoProvider + Clone>(
+    crypto_provider: &C,
+    cipher_suite: CipherSuite,
+) -> Result<Vec<TreeOperationsTestCase>, MlsError> {
+    let cs = crypto_provider
+        .cipher_suite_provider(cipher_suite)
+        .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
+
+    let mut test_cases = vec![];
+
+    // Update
+    let mut groups = get_test_groups(VERSION, cipher_suite, 8, None, false, crypto_provider).await;
+    let update = groups[6].propose_update(vec![]).await.unwrap();
+
+    let Some(Content::Proposal(update)) = update.into_plaintext().map(|m| m.content.content) else {
+        panic!("update proposal is not a public message");
+    };
+
+    let tree_before = &groups[0].state.public_tree;
+    test_cases.push(TreeOperationsTestCase::new(tree_before, *update, 6, &cs).await);
+
+    // Add in the middle
+    let mut group = creator_group(crypto_provider, cipher_suite, 6).await;
+    group
+        .commit_builder()
+        .remove_member(3)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    group.apply_pending_commit().await.unwrap();
+
+    let add = add_proposal(crypto_provider, cipher_suite).await;
+    test_cases.push(TreeOperationsTestCase::new(&group.state.public_tree, add, 2, &cs).await);
+
+    // Add at the end, and at the end with the tree growing
+    for n_leaves in [6, 8] {
+        let group = creator_group(crypto_provider, cipher_suite, n_leaves).await;
+        let add = add_proposal(crypto_provider, cipher_suite).await;
+        test_cases.push(TreeOperationsTestCase::new(&group.state.public_tree, add, 2, &cs).await);
+    }
+
+    // Remove in the middle, at the end, and at the end with the tree
+    // shrinking
+    for (n_leaves, to_remove) in [(8, 2), (8, 7), (9, 8)] {
+        let group = creator_group(crypto_provider, cipher_suite, n_leaves).await;
+
+        let remove = Proposal::Remove(RemoveProposal {
+            to_remove: LeafIndex(to_remove),
+        });
+
+        test_cases
+            .push(TreeOperationsTestCase::new(&group.state.public_tree, remove, 0, &cs).await);
+    }
+
+    Ok(test_cases)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+async fn creator_group<C: CryptoProvider + Clone>(
+    crypto_provider: &C,
+    cipher_suite: CipherSuite,
+    n_members: usize,
+) -> Group<impl MlsConfig> {
+    get_test_groups(
+        VERSION,
+        cipher_suite,
+        n_members,
+        None,
+        false,
+        crypto_provider,
+    )
+    .await
+    .remove(0)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+async fn add_proposal<C: CryptoProvider + Clone>(
+    crypto_provider: &C,
+    cipher_suite: CipherSuite,
+) -> Proposal {
+    let key_package = generate_basic_client(
+        cipher_suite,
+        VERSION,
+        100,
+        None,
+        false,
+        crypto_provider,
+        None,
+    )
+    .await
+    .generate_key_package_message(Default::default(), Default::default())
+    .await
+    .unwrap()
+    .into_key_package()
+    .unwrap();
+
+    Proposal::Add(Box::new(AddProposal { key_package }))
+}
@@ -0,0 +1,175 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Tree validation test vectors in the format of `tree-validation.json`.
+//!
+//! Cipher suites for which the crypto provider returns no cipher suite
+//! provider are skipped. The generator panics if any group operation fails with
+//! a supported cipher suite.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use mls_rs_codec::MlsEncode;
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider},
+    protocol_version::ProtocolVersion,
+};
+
+use crate::{
+    client_builder::MlsConfig,
+    mls_rules::CommitOptions,
+    test_utils::{generate_basic_client, get_test_groups},
+    tree_kem::TreeKemPublic,
+    Group,
+};
+
+const VERSION: ProtocolVersion = ProtocolVersion::MLS_10;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+pub struct TreeValidationTestCase {
+    pub cipher_suite: u16,
+
+    #[serde(with = "hex::serde")]
+    pub tree: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub group_id: Vec<u8>,
+    pub tree_hashes: Vec<TestTreeHash>,
+    pub resolutions: Vec<Vec<u32>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+pub struct TestTreeHash(#[serde(with = "hex::serde")] pub Vec<u8>);
+
+impl TreeValidationTestCase {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(coverage_nightly, coverage(off))]
+    async fn new<P: CipherSuiteProvider>(tree: &TreeKemPublic, group_id: &[u8], cs: &P) -> Self {
+        // Recompute every hash rather than relying on those cached by the group
+        let mut tree = tree.clone();
+        tree.tree_hashes.current = vec![];
+        tree.tree_hash(cs).await.unwrap();
+
+        let tree_size = tree.total_leaf_count() * 2 - 1;
+
+        let resolutions = (0..tree_size)
+            .map(
+                #[cfg_attr(coverage_nightly, coverage(off))]
+                |i| tree.nodes.get_resolution_index(i).unwrap(),
+            )
+            .collect();
+
+        Self {
+            cipher_suite: cs.cipher_suite().into(),
+            tree: tree.nodes.mls_encode_to_vec().unwrap(),
+            group_id: group_id.to_vec(),
+            tree_hashes: tree
+                .tree_hashes
+                .current
+                .iter()
+                .map(|hash| TestTreeHash(hash.to_vec()))
+                .collect(),
+            resolutions,
+        }
+    }
+}
+
+/// Generate test cases covering complete trees, trees with blank leaves
+/// inside and at the end, with and without skipped parents in update paths,
+/// and trees with unmerged leaves, for every cipher suite supported by
+/// `crypto_provider`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+pub async fn generate<C: CryptoProvider + Clone>(
+    crypto_provider: &C,
+) -> Vec<TreeValidationTestCase> {
+    let mut test_cases = vec![];
+
+    for cs in crypto_provider.supported_cipher_suites() {
+        let Some(cs) = crypto_provider.cipher_suite_provider(cs) else {
+            continue;
+        };
+
+        let mut trees = vec![];
+
+        // Complete trees and trees with blank leaves at the end
+        for n_leaves in [2, 3, 4, 5, 7, 8] {
+            trees.push(creator_group(crypto_provider, &cs, n_leaves, None).await);
+        }
+
+        // Internal blanks, without and with skipping
+        for removed in [vec![2, 3], vec![1, 2, 3]] {
+            let mut group = creator_group(crypto_provider, &cs, 8, None).await;
+            let mut builder = group.commit_builder();
+
+            for index in removed {
+                builder = builder.remove_member(index).unwrap();
+            }
+
+            builder.build().await.unwrap();
+            group.apply_pending_commit().await.unwrap();
+            trees.push(group);
+        }
+
+        // Unmerged leaves : the committer populates its path, then adds a
+        // member without updating it
+        let commit_options = CommitOptions::new().with_path_required(false);
+        let mut group = creator_group(crypto_provider, &cs, 7, Some(commit_options)).await;
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        let key_package = generate_basic_client(
+            cs.cipher_suite(),
+            VERSION,
+            7,
+            None,
+            false,
+            crypto_provider,
+            None,
+        )
+        .await
+        .generate_key_package_message(Default::default(), Default::default())
+        .await
+        .unwrap();
+
+        group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        group.apply_pending_commit().await.unwrap();
+        trees.push(group);
+
+        for group in trees {
+            test_cases.push(
+                TreeValidationTestCase::new(&group.state.public_tree, group.group_id(), &cs).await,
+            );
+        }
+    }
+
+    test_cases
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+async fn creator_group<C: CryptoProvider + Clone, P: CipherSuiteProvider>(
+    crypto_provider: &C,
+    cs: &P,
+    n_members: usize,
+    commit_options: Option<CommitOptions>,
+) -> Group<impl MlsConfig> {
+    get_test_groups(
+        VERSION,
+        cs.cipher_suite(),
+        n_members,
+        commit_options,
+        false,
+        crypto_provider,
+    )
+    .await
+    .remove(0)
+}
@@ -0,0 +1,78 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Welcome test vectors in the format of `welcome.json`.
+//!
+//! Cipher suites for which the crypto provider returns no cipher suite
+//! provider are skipped. The generator panics if any group operation fails with
+//! a supported cipher suite.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use mls_rs_core::{crypto::CryptoProvider, protocol_version::ProtocolVersion};
+
+use crate::test_utils::get_test_groups;
+
+use super::key_package_with_secrets;
+
+const VERSION: ProtocolVersion = ProtocolVersion::MLS_10;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+pub struct WelcomeTestCase {
+    pub cipher_suite: u16,
+
+    #[serde(with = "hex::serde")]
+    pub init_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub signer_pub: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub key_package: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub welcome: Vec<u8>,
+}
+
+/// Generate a test case in which a member of a two member group adds a new
+/// member, for every cipher suite supported by `crypto_provider`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+pub async fn generate<C: CryptoProvider + Clone>(crypto_provider: &C) -> Vec<WelcomeTestCase> {
+    let mut test_cases = vec![];
+
+    for cs in crypto_provider.supported_cipher_suites() {
+        if crypto_provider.cipher_suite_provider(cs).is_none() {
+            continue;
+        }
+
+        let mut groups = get_test_groups(VERSION, cs, 2, None, false, crypto_provider).await;
+
+        let (key_package, key_package_data) =
+            key_package_with_secrets(crypto_provider, cs, b"new member").await;
+
+        let commit_output = groups[0]
+            .commit_builder()
+            .add_member(key_package.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let signer_pub = groups[0]
+            .current_member_signing_identity()
+            .unwrap()
+            .signature_key
+            .to_vec();
+
+        test_cases.push(WelcomeTestCase {
+            cipher_suite: cs.into(),
+            init_priv: key_package_data.init_key.to_vec(),
+            signer_pub,
+            key_package: key_package.to_bytes().unwrap(),
+            welcome: commit_output.welcome_messages[0].to_bytes().unwrap(),
+        });
+    }
+
+    test_cases
+}
@@ -0,0 +1,236 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Nodes are identified by their index in the array representation of the
+//! tree described in [RFC 9420 Section 4.1](https://www.rfc-editor.org/rfc/rfc9420.html#section-4.1),
+//! where the leaf with index `i` is the node with index `2 * i`. The
+//! `leaf_count` of a tree is always a power of two.
+//!
+//! This module is meant for diagnostics, such as recomputing the filtered
+//! direct path of a leaf or checking the parent hashes of trees received by a
+//! server. Its API may change in minor releases.
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    group::ExportedTree,
+    tree_kem::{
+        math::TreeIndex,
+        node::{LeafIndex, NodeIndex},
+        TreeKemPublic,
+    },
+    CipherSuiteProvider,
+};
+
+fn in_tree(node: u32, leaf_count: u32) -> bool {
+    leaf_count.is_power_of_two() && node.is_in_tree(&leaf_count.root())
+}
+
+/// Index of the root node of a tree with `leaf_count` leaves.
+pub fn root(leaf_count: u32) -> Option<u32> {
+    leaf_count.is_power_of_two().then(|| leaf_count.root())
+}
+
+/// Whether `node` is a leaf node.
+pub fn is_leaf(node: u32) -> bool {
+    node.is_leaf()
+}
+
+/// Left child of `node`, or `None` if `node` is a leaf.
+pub fn left(node: u32) -> Option<u32> {
+    (!node.is_leaf()).then(|| node.left_unchecked())
+}
+
+/// Right child of `node`, or `None` if `node` is a leaf.
+pub fn right(node: u32) -> Option<u32> {
+    (!node.is_leaf()).then(|| node.right_unchecked())
+}
+
+/// Parent of `node`, or `None` if `node` is the root or is not in the tree.
+pub fn parent(node: u32, leaf_count: u32) -> Option<u32> {
+    in_tree(node, leaf_count)
+        .then(|| node.parent_sibling(&leaf_count))
+        .flatten()
+        .map(|ps| ps.parent)
+}
+
+/// Sibling of `node`, or `None` if `node` is the root or is not in the tree.
+pub fn sibling(node: u32, leaf_count: u32) -> Option<u32> {
+    in_tree(node, leaf_count)
+        .then(|| node.parent_sibling(&leaf_count))
+        .flatten()
+        .map(|ps| ps.sibling)
+}
+
+/// Ancestors of `node`, ordered from its parent to the root.
+pub fn direct_path(node: u32, leaf_count: u32) -> Vec<u32> {
+    copath_nodes(node, leaf_count).map(|n| n.path).collect()
+}
+
+/// Siblings of `node` and of its ancestors except the root, ordered from the
+/// sibling of `node` upwards.
+pub fn copath(node: u32, leaf_count: u32) -> Vec<u32> {
+    copath_nodes(node, leaf_count).map(|n| n.copath).collect()
+}
+
+fn copath_nodes(
+    node: u32,
+    leaf_count: u32,
+) -> impl Iterator<Item = crate::tree_kem::math::CopathNode<u32>> {
+    let nodes = if in_tree(node, leaf_count) {
+        node.direct_copath(&leaf_count)
+    } else {
+        Vec::new()
+    };
+
+    nodes.into_iter()
+}
+
+/// Number of leaves of `tree`, including blank leaves.
+pub fn leaf_count(tree: &ExportedTree<'_>) -> u32 {
+    tree.0.total_leaf_count()
+}
+
+/// Resolution of `node` in `tree`, as defined in
+/// [RFC 9420 Section 4.1.1](https://www.rfc-editor.org/rfc/rfc9420.html#section-4.1.1).
+pub fn resolution(tree: &ExportedTree<'_>, node: u32) -> Result<Vec<u32>, MlsError> {
+    if !in_tree(node, tree.0.total_leaf_count()) {
+        return Err(MlsError::InvalidNodeIndex(node));
+    }
+
+    tree.0.get_resolution_index(node)
+}
+
+/// Filtered direct path of the leaf at `leaf_index` in `tree`, i.e. its direct
+/// path without the nodes whose child on the copath has an empty resolution.
+pub fn filtered_direct_path(
+    tree: &ExportedTree<'_>,
+    leaf_index: u32,
+) -> Result<Vec<u32>, MlsError> {
+    let node = NodeIndex::from(LeafIndex(leaf_index));
+
+    if leaf_index >= tree.0.total_leaf_count() {
+        return Err(MlsError::InvalidNodeIndex(node));
+    }
+
+    Ok(tree
+        .0
+        .direct_copath(LeafIndex(leaf_index))
+        .into_iter()
+        .filter(|n| !tree.0.is_resolution_empty(n.copath))
+        .map(|n| n.path)
+        .collect())
+}
+
+fn import(tree: &ExportedTree<'_>) -> TreeKemPublic {
+    let mut public_tree = TreeKemPublic::new();
+    public_tree.nodes = tree.0.clone().into_owned();
+    public_tree
+}
+
+/// Tree hash of the root of `tree`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn tree_hash<P: CipherSuiteProvider>(
+    tree: &ExportedTree<'_>,
+    cipher_suite_provider: &P,
+) -> Result<Vec<u8>, MlsError> {
+    import(tree).tree_hash(cipher_suite_provider).await
+}
+
+/// Check that the parent hashes of `tree` are valid, as defined in
+/// [RFC 9420 Section 7.9.2](https://www.rfc-editor.org/rfc/rfc9420.html#section-7.9.2).
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn verify_parent_hashes<P: CipherSuiteProvider>(
+    tree: &ExportedTree<'_>,
+    cipher_suite_provider: &P,
+) -> Result<(), MlsError> {
+    let mut public_tree = import(tree);
+    public_tree.tree_hash(cipher_suite_provider).await?;
+
+    public_tree
+        .validate_parent_hashes(cipher_suite_provider)
+        .await
+}
+
+/// Check the parent hashes of each tree in `trees` with
+/// [`verify_parent_hashes`], returning one result per tree.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn verify_parent_hashes_batch<P: CipherSuiteProvider>(
+    trees: &[ExportedTree<'_>],
+    cipher_suite_provider: &P,
+) -> Vec<Result<(), MlsError>> {
+    let mut results = Vec::with_capacity(trees.len());
+
+    for tree in trees {
+        results.push(verify_parent_hashes(tree, cipher_suite_provider).await);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+        tree_kem::node::Node,
+    };
+
+    use super::*;
+
+    #[test]
+    fn tree_math_matches_rfc_example() {
+        assert_eq!(root(8), Some(7));
+        assert_eq!(root(6), None);
+        assert_eq!(parent(0, 8), Some(1));
+        assert_eq!(parent(7, 8), None);
+        assert_eq!(sibling(0, 8), Some(2));
+        assert_eq!(left(3), Some(1));
+        assert_eq!(right(3), Some(5));
+        assert_eq!(left(4), None);
+        assert_eq!(direct_path(0, 8), vec![1, 3, 7]);
+        assert_eq!(copath(0, 8), vec![2, 5, 11]);
+        assert!(direct_path(15, 8).is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inspect_group_tree() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+        alice.join("carol").await;
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let tree = alice.export_tree().into_owned();
+
+        assert_eq!(leaf_count(&tree), 4);
+        assert_eq!(filtered_direct_path(&tree, 0).unwrap(), vec![1, 3]);
+        assert_eq!(resolution(&tree, 5).unwrap(), vec![4]);
+
+        let hash = tree_hash(&tree, &cs).await.unwrap();
+        assert_eq!(hash, alice.context().tree_hash);
+
+        let mut tampered = tree.clone();
+
+        if let Some(Node::Parent(p)) = tampered.0.to_mut()[1].as_mut() {
+            p.parent_hash = vec![0u8; 32].into();
+        }
+
+        let results = verify_parent_hashes_batch(&[tree, tampered], &cs).await;
+
+        assert_matches!(
+            results.as_slice(),
+            [Ok(()), Err(MlsError::ParentHashMismatch)]
+        );
+    }
+}
@@ -79,12 +79,13 @@ pub(crate) trait HpkeEncryptable: Sized {
         let plaintext = cipher_suite_provider
             .hpke_open(ciphertext, secret_key, public_key, &context, None)
             .await
+            .map(Zeroizing::new)
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-        Self::from_bytes(plaintext.to_vec())
+        Self::from_bytes(plaintext)
     }
 
-    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError>;
+    fn from_bytes(bytes: Zeroizing<Vec<u8>>) -> Result<Self, MlsError>;
     fn get_bytes(&self) -> Result<Vec<u8>, MlsError>;
 }
 
@@ -93,6 +94,7 @@ pub(crate) mod test_utils {
     use alloc::{string::String, vec::Vec};
     use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
     use mls_rs_core::crypto::{CipherSuiteProvider, HpkeCiphertext};
+    use zeroize::Zeroizing;
 
     use crate::{client::MlsError, crypto::test_utils::try_test_cipher_suite_provider};
 
@@ -140,8 +142,8 @@ pub(crate) mod test_utils {
     impl HpkeEncryptable for TestEncryptable {
         const ENCRYPT_LABEL: &'static str = "EncryptWithLabel";
 
-        fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
-            Ok(Self(bytes))
+        fn from_bytes(bytes: Zeroizing<Vec<u8>>) -> Result<Self, MlsError> {
+            Ok(Self(bytes.to_vec()))
         }
 
         #[cfg_attr(coverage_nightly, coverage(off))]
@@ -573,6 +573,7 @@ mod tests {
         let update_leaf_properties = ConfigProperties {
             capabilities: capabilities.clone().unwrap_or_else(get_test_capabilities),
             extensions: extensions.clone().unwrap_or_default(),
+            grease: true,
         };
 
         // Perform the encap function
@@ -56,6 +56,7 @@ impl Debug for LeafNode {
 pub struct ConfigProperties {
     pub capabilities: Capabilities,
     pub extensions: ExtensionList,
+    pub grease: bool,
 }
 
 impl LeafNode {
@@ -84,7 +85,9 @@ impl LeafNode {
             signature: Default::default(),
         };
 
-        leaf_node.grease(cipher_suite_provider)?;
+        if properties.grease {
+            leaf_node.grease(cipher_suite_provider)?;
+        }
 
         leaf_node
             .sign(
@@ -114,6 +117,8 @@ impl LeafNode {
 
         self.public_key = public;
 
+        let grease = new_properties.as_ref().map_or(true, |p| p.grease);
+
         if let Some(new_properties) = new_properties {
             self.capabilities = new_properties.capabilities;
             self.extensions = new_properties.extensions;
@@ -121,7 +126,9 @@ impl LeafNode {
 
         self.leaf_node_source = LeafNodeSource::Update;
 
-        self.grease(cipher_suite_provider)?;
+        if grease {
+            self.grease(cipher_suite_provider)?;
+        }
 
         if let Some(signing_identity) = signing_identity {
             self.signing_identity = signing_identity;
@@ -313,6 +320,7 @@ pub(crate) mod test_utils {
         let properties = ConfigProperties {
             capabilities,
             extensions,
+            grease: true,
         };
 
         LeafNode::generate(
@@ -337,6 +345,7 @@ pub(crate) mod test_utils {
         ConfigProperties {
             capabilities: get_test_capabilities(),
             extensions: Default::default(),
+            grease: true,
         }
     }
 
@@ -354,6 +363,7 @@ pub(crate) mod test_utils {
             ConfigProperties {
                 capabilities,
                 extensions: Default::default(),
+                grease: true,
             },
             signing_identity,
             &signature_key,
@@ -552,6 +562,7 @@ mod tests {
         let new_properties = ConfigProperties {
             capabilities: get_test_capabilities(),
             extensions: get_test_extensions(),
+            grease: true,
         };
 
         let (mut leaf, _) =
@@ -632,6 +643,7 @@ mod tests {
         let new_properties = ConfigProperties {
             capabilities: get_test_capabilities(),
             extensions: get_test_extensions(),
+            grease: true,
         };
 
         // The new identity has a fresh public key
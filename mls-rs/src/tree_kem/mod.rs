@@ -26,7 +26,6 @@ use crate::crypto::{self, CipherSuiteProvider, HpkeSecretKey};
 #[cfg(feature = "by_ref_proposal")]
 use crate::group::proposal::{AddProposal, UpdateProposal};
 
-#[cfg(any(test, feature = "by_ref_proposal"))]
 use crate::group::proposal::RemoveProposal;
 
 use crate::group::proposal_filter::ProposalBundle;
@@ -47,6 +46,7 @@ pub mod update_path;
 pub use capabilities::*;
 pub use lifetime::*;
 pub(crate) use private::*;
+pub(crate) use tree_hash::{tree_hash_from_path, TreeHashPathStep};
 pub use update_path::*;
 
 use tree_index::*;
@@ -59,7 +59,7 @@ mod tree_index;
 #[cfg(feature = "std")]
 pub(crate) mod tree_utils;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rfc_compliant"))]
 mod interop_test_vectors;
 
 #[cfg(feature = "custom_proposal")]
@@ -72,7 +72,7 @@ pub struct TreeKemPublic {
     #[cfg_attr(feature = "serde", serde(skip))]
     index: TreeIndex,
     pub(crate) nodes: NodeVec,
-    tree_hashes: TreeHashes,
+    pub(crate) tree_hashes: TreeHashes,
 }
 
 impl PartialEq for TreeKemPublic {
@@ -241,7 +241,6 @@ impl TreeKemPublic {
         self.nodes.non_empty_leaves()
     }
 
-    #[cfg(feature = "prior_epoch")]
     pub fn leaves(&self) -> impl Iterator<Item = Option<&LeafNode>> + '_ {
         self.nodes.leaves()
     }
@@ -618,42 +617,7 @@ impl Display for TreeKemPublic {
     }
 }
 
-#[cfg(test)]
-use crate::group::{proposal::Proposal, proposal_filter::ProposalSource, Sender};
-
-#[cfg(test)]
 impl TreeKemPublic {
-    #[cfg(feature = "by_ref_proposal")]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn update_leaf<I, CP>(
-        &mut self,
-        leaf_index: u32,
-        leaf_node: LeafNode,
-        identity_provider: &I,
-        cipher_suite_provider: &CP,
-    ) -> Result<(), MlsError>
-    where
-        I: IdentityProvider,
-        CP: CipherSuiteProvider,
-    {
-        let p = Proposal::Update(UpdateProposal { leaf_node });
-
-        let mut bundle = ProposalBundle::default();
-        bundle.add(p, Sender::Member(leaf_index), ProposalSource::ByValue);
-        bundle.update_senders = vec![LeafIndex(leaf_index)];
-
-        self.batch_edit(
-            &mut bundle,
-            &Default::default(),
-            identity_provider,
-            cipher_suite_provider,
-            true,
-        )
-        .await?;
-
-        Ok(())
-    }
-
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn remove_leaves<I, CP>(
         &mut self,
@@ -707,6 +671,42 @@ impl TreeKemPublic {
             })
             .collect()
     }
+}
+
+use crate::group::{proposal::Proposal, proposal_filter::ProposalSource, Sender};
+
+#[cfg(test)]
+impl TreeKemPublic {
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn update_leaf<I, CP>(
+        &mut self,
+        leaf_index: u32,
+        leaf_node: LeafNode,
+        identity_provider: &I,
+        cipher_suite_provider: &CP,
+    ) -> Result<(), MlsError>
+    where
+        I: IdentityProvider,
+        CP: CipherSuiteProvider,
+    {
+        let p = Proposal::Update(UpdateProposal { leaf_node });
+
+        let mut bundle = ProposalBundle::default();
+        bundle.add(p, Sender::Member(leaf_index), ProposalSource::ByValue);
+        bundle.update_senders = vec![LeafIndex(leaf_index)];
+
+        self.batch_edit(
+            &mut bundle,
+            &Default::default(),
+            identity_provider,
+            cipher_suite_provider,
+            true,
+        )
+        .await?;
+
+        Ok(())
+    }
 
     pub fn get_leaf_nodes(&self) -> Vec<&LeafNode> {
         self.nodes.non_empty_leaves().map(|(_, l)| l).collect()
@@ -833,22 +833,6 @@ pub(crate) mod test_utils {
             }
         }
 
-        #[cfg(feature = "rfc_compliant")]
-        #[cfg_attr(coverage_nightly, coverage(off))]
-        pub fn remove_member(&mut self, member: u32) {
-            self.tree
-                .nodes
-                .blank_direct_path(LeafIndex(member))
-                .unwrap();
-
-            self.tree.nodes.blank_leaf_node(LeafIndex(member)).unwrap();
-
-            *self
-                .signers
-                .get_mut(member as usize)
-                .expect("signer tree size mismatch") = None;
-        }
-
         #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
         pub async fn update_committer_path<P: CipherSuiteProvider>(
             &mut self,
@@ -916,6 +900,7 @@ pub(crate) mod test_utils {
         let properties = ConfigProperties {
             capabilities,
             extensions: Default::default(),
+            grease: true,
         };
 
         let (leaf, _) = LeafNode::generate(
@@ -178,7 +178,7 @@ impl TreeKemPublic {
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub(super) async fn validate_parent_hashes<P: CipherSuiteProvider>(
+    pub(crate) async fn validate_parent_hashes<P: CipherSuiteProvider>(
         &self,
         cipher_suite_provider: &P,
     ) -> Result<(), MlsError> {
@@ -72,8 +72,8 @@ impl PathSecret {
 impl HpkeEncryptable for PathSecret {
     const ENCRYPT_LABEL: &'static str = "UpdatePathNode";
 
-    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
-        Ok(Self(Zeroizing::new(bytes)))
+    fn from_bytes(bytes: Zeroizing<Vec<u8>>) -> Result<Self, MlsError> {
+        Ok(Self(bytes))
     }
 
     fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use super::leaf_node::LeafNode;
-use super::node::{LeafIndex, NodeVec};
+use super::node::{LeafIndex, NodeIndex, NodeVec};
 use super::tree_math::BfsIterTopDown;
 use crate::client::MlsError;
 use crate::crypto::CipherSuiteProvider;
@@ -51,6 +51,14 @@ pub(crate) struct TreeHashes {
     pub current: Vec<TreeHash>,
 }
 
+/// Node on the direct path of a leaf together with the tree hash of its child
+/// on the copath of the leaf.
+#[derive(Clone, Debug, MlsSize, MlsEncode, MlsDecode, PartialEq)]
+pub(crate) struct TreeHashPathStep {
+    pub parent: Option<Parent>,
+    pub copath_hash: TreeHash,
+}
+
 #[derive(Debug, MlsSize, MlsEncode)]
 struct LeafNodeHashInput<'a> {
     leaf_index: LeafIndex,
@@ -85,6 +93,29 @@ impl TreeKemPublic {
         Ok(self.tree_hashes.current[root as usize].to_vec())
     }
 
+    /// Steps needed to recompute the tree hash of the root from the leaf at
+    /// `leaf_index` with [`tree_hash_from_path`].
+    pub(crate) fn tree_hash_path(
+        &self,
+        leaf_index: LeafIndex,
+    ) -> Result<Vec<TreeHashPathStep>, MlsError> {
+        self.nodes
+            .direct_copath(leaf_index)
+            .into_iter()
+            .map(|node| {
+                Ok(TreeHashPathStep {
+                    parent: self.nodes.borrow_as_parent(node.path).ok().cloned(),
+                    copath_hash: self
+                        .tree_hashes
+                        .current
+                        .get(node.copath as usize)
+                        .cloned()
+                        .ok_or(MlsError::InvalidNodeIndex(node.copath))?,
+                })
+            })
+            .collect()
+    }
+
     // Update hashes after `committer` makes changes to the tree. `path_blank` is the
     // list of leaves whose paths were blanked, i.e. updates and removes.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -312,6 +343,47 @@ async fn tree_hash<P: CipherSuiteProvider>(
     Ok(())
 }
 
+/// Compute the tree hash of the root of a full tree from a single leaf and
+/// its [path](TreeKemPublic::tree_hash_path). Returns `None` if the path does
+/// not fit the leaf index.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn tree_hash_from_path<P: CipherSuiteProvider>(
+    leaf_index: LeafIndex,
+    leaf_node: &LeafNode,
+    path: &[TreeHashPathStep],
+    cipher_suite_provider: &P,
+) -> Result<Option<Vec<u8>>, MlsError> {
+    let Some(leaf_count) = u32::try_from(path.len())
+        .ok()
+        .and_then(|depth| 1u32.checked_shl(depth))
+        .filter(|leaf_count| *leaf_index < *leaf_count)
+    else {
+        return Ok(None);
+    };
+
+    let mut hash = hash_for_leaf(leaf_index, Some(leaf_node), cipher_suite_provider).await?;
+    let direct_copath = NodeIndex::from(leaf_index).direct_copath(&leaf_count);
+
+    for (step, node) in path.iter().zip(direct_copath) {
+        let (left_hash, right_hash) = if node.copath < node.path {
+            (&*step.copath_hash, hash.as_slice())
+        } else {
+            (hash.as_slice(), &*step.copath_hash)
+        };
+
+        hash = hash_for_parent(
+            step.parent.as_ref(),
+            cipher_suite_provider,
+            &[],
+            left_hash,
+            right_hash,
+        )
+        .await?;
+    }
+
+    Ok(Some(hash))
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 async fn hash_for_leaf<P: CipherSuiteProvider>(
     leaf_index: LeafIndex,
@@ -1,7 +1,3 @@
-// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
-// Copyright by contributors to this project.
-// SPDX-License-Identifier: (Apache-2.0 OR MIT)
-
 use alloc::string::String;
 use alloc::{format, vec};
 use core::borrow::BorrowMut;
@@ -11,18 +7,32 @@ use debug_tree::TreeBuilder;
 use super::node::{NodeIndex, NodeVec};
 use crate::{client::MlsError, tree_kem::math::TreeIndex};
 
-pub(crate) fn build_tree(
-    tree: &mut TreeBuilder,
-    nodes: &NodeVec,
-    idx: NodeIndex,
-) -> Result<(), MlsError> {
+#[cfg(feature = "debug_utils")]
+use super::{leaf_node::LeafNodeSource, node::Node};
+
+/// Output format of [`Group::debug_tree`](crate::Group::debug_tree).
+#[cfg(feature = "debug_utils")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DebugTreeFormat {
+    /// Indented text with one line per node, starting at the root.
+    Text,
+    /// Graphviz DOT graph with one vertex per node. Blank nodes are dashed.
+    Dot,
+}
+
+fn node_label(nodes: &NodeVec, idx: NodeIndex, details: bool) -> Result<String, MlsError> {
     let blank_tag = if nodes.is_blank(idx)? { "Blank " } else { "" };
 
     // Leaf Node
     if nodes.is_leaf(idx) {
-        let leaf_tag = format!("{blank_tag}Leaf ({idx})");
-        tree.add_leaf(&leaf_tag);
-        return Ok(());
+        let mut leaf_tag = format!("{blank_tag}Leaf ({idx})");
+
+        if details {
+            push_details(&mut leaf_tag, nodes, idx)?;
+        }
+
+        return Ok(leaf_tag);
     }
 
     // Parent Leaf
@@ -51,11 +61,60 @@ pub(crate) fn build_tree(
         parent_tag.push_str(&unmerged_leaves_tag);
     }
 
-    let mut branch = tree.add_branch(&parent_tag);
+    if details {
+        push_details(&mut parent_tag, nodes, idx)?;
+    }
+
+    Ok(parent_tag)
+}
+
+#[cfg(feature = "debug_utils")]
+fn push_details(tag: &mut String, nodes: &NodeVec, idx: NodeIndex) -> Result<(), MlsError> {
+    let parent_hash = match nodes.borrow_node(idx)? {
+        Some(Node::Parent(parent)) => Some(&parent.parent_hash),
+        Some(Node::Leaf(leaf)) => match &leaf.leaf_node_source {
+            LeafNodeSource::Commit(parent_hash) => Some(parent_hash),
+            _ => None,
+        },
+        None => None,
+    };
+
+    // Truncated to the first 4 bytes, which is enough to compare trees by eye
+    if let Some(parent_hash) = parent_hash.filter(|h| !h.is_empty()) {
+        let truncated: String = parent_hash
+            .iter()
+            .take(4)
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        tag.push_str(&format!(" parent hash: {truncated}"));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "debug_utils"))]
+fn push_details(_tag: &mut String, _nodes: &NodeVec, _idx: NodeIndex) -> Result<(), MlsError> {
+    Ok(())
+}
+
+pub(crate) fn build_tree(
+    tree: &mut TreeBuilder,
+    nodes: &NodeVec,
+    idx: NodeIndex,
+    details: bool,
+) -> Result<(), MlsError> {
+    let tag = node_label(nodes, idx, details)?;
+
+    if nodes.is_leaf(idx) {
+        tree.add_leaf(&tag);
+        return Ok(());
+    }
+
+    let mut branch = tree.add_branch(&tag);
 
     //This cannot panic, as we already checked that idx is not a leaf
-    build_tree(tree, nodes, idx.left_unchecked())?;
-    build_tree(tree, nodes, idx.right_unchecked())?;
+    build_tree(tree, nodes, idx.left_unchecked(), details)?;
+    build_tree(tree, nodes, idx.right_unchecked(), details)?;
 
     branch.release();
 
@@ -65,10 +124,56 @@ pub(crate) fn build_tree(
 pub(crate) fn build_ascii_tree(nodes: &NodeVec) -> String {
     let leaves_count: u32 = nodes.total_leaf_count();
     let mut tree = TreeBuilder::new();
-    build_tree(tree.borrow_mut(), nodes, leaves_count.root()).unwrap();
+    build_tree(tree.borrow_mut(), nodes, leaves_count.root(), false).unwrap();
     tree.string()
 }
 
+#[cfg(feature = "debug_utils")]
+pub(crate) fn build_debug_tree(
+    nodes: &NodeVec,
+    format: DebugTreeFormat,
+) -> Result<String, MlsError> {
+    let root = nodes.total_leaf_count().root();
+
+    match format {
+        DebugTreeFormat::Text => {
+            let mut tree = TreeBuilder::new();
+            build_tree(tree.borrow_mut(), nodes, root, true)?;
+            Ok(tree.string())
+        }
+        DebugTreeFormat::Dot => {
+            let mut dot = String::from("digraph ratchet_tree {\n    node [shape=box];\n");
+            build_dot_tree(&mut dot, nodes, root)?;
+            dot.push('}');
+            Ok(dot)
+        }
+    }
+}
+
+#[cfg(feature = "debug_utils")]
+fn build_dot_tree(dot: &mut String, nodes: &NodeVec, idx: NodeIndex) -> Result<(), MlsError> {
+    let label = node_label(nodes, idx, true)?;
+    let style = if nodes.is_blank(idx)? {
+        ", style=dashed"
+    } else {
+        ""
+    };
+
+    dot.push_str(&format!("    n{idx} [label=\"{label}\"{style}];\n"));
+
+    if nodes.is_leaf(idx) {
+        return Ok(());
+    }
+
+    //This cannot panic, as we already checked that idx is not a leaf
+    for child in [idx.left_unchecked(), idx.right_unchecked()] {
+        dot.push_str(&format!("    n{idx} -> n{child};\n"));
+        build_dot_tree(dot, nodes, child)?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -188,4 +293,53 @@ mod tests {
 
         assert_eq!(tree_str, build_ascii_tree(&tree.nodes));
     }
+
+    #[cfg(feature = "debug_utils")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn print_debug_tree_with_parent_hashes() {
+        use super::{build_debug_tree, DebugTreeFormat};
+
+        let cipher_suite_provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut tree = get_test_tree(TEST_CIPHER_SUITE).await.public;
+        let key_packages = get_test_leaf_nodes(TEST_CIPHER_SUITE).await;
+
+        tree.add_leaves(
+            [key_packages[0].clone()].to_vec(),
+            &BasicIdentityProvider,
+            &cipher_suite_provider,
+        )
+        .await
+        .unwrap();
+
+        tree.nodes[1] = Parent {
+            public_key: vec![].into(),
+            parent_hash: ParentHash::from(vec![0xab; 32]),
+            unmerged_leaves: vec![],
+        }
+        .into();
+
+        let text = concat!(
+            "Root (1) parent hash: abababab\n",
+            "├╼ Leaf (0)\n",
+            "└╼ Leaf (2)",
+        );
+
+        let res = build_debug_tree(&tree.nodes, DebugTreeFormat::Text).unwrap();
+        assert_eq!(res, text);
+
+        let dot = concat!(
+            "digraph ratchet_tree {\n",
+            "    node [shape=box];\n",
+            "    n1 [label=\"Root (1) parent hash: abababab\"];\n",
+            "    n1 -> n0;\n",
+            "    n0 [label=\"Leaf (0)\"];\n",
+            "    n1 -> n2;\n",
+            "    n2 [label=\"Leaf (2)\"];\n",
+            "}",
+        );
+
+        let res = build_debug_tree(&tree.nodes, DebugTreeFormat::Dot).unwrap();
+        assert_eq!(res, dot);
+    }
 }
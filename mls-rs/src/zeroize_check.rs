@@ -0,0 +1,222 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Detection of transient secrets that are freed without being zeroized.
+//!
+//! The test binary is built with a global allocator that scans every freed
+//! buffer for a canary, the value of a secret that is expected to be
+//! zeroized before its memory is released. Since the secrets below are
+//! derived deterministically, each test derives a secret once to learn its
+//! value, then watches for it while deriving it again.
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{Mutex, MutexGuard},
+};
+
+const MAX_CANARY_LEN: usize = 64;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU8 = AtomicU8::new(0);
+
+static CANARY: [AtomicU8; MAX_CANARY_LEN] = [ZERO; MAX_CANARY_LEN];
+static CANARY_LEN: AtomicUsize = AtomicUsize::new(0);
+static HITS: AtomicUsize = AtomicUsize::new(0);
+static WATCH_LOCK: Mutex<()> = Mutex::new(());
+
+struct CanaryAllocator;
+
+// The default `realloc` allocates a new buffer and deallocates the old one
+// with the methods below, so that buffers left behind when a vector grows
+// are scanned as well.
+unsafe impl GlobalAlloc for CanaryAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let len = CANARY_LEN.load(Ordering::Acquire);
+
+        if len > 0 && layout.size() >= len {
+            let mut canary = [0u8; MAX_CANARY_LEN];
+
+            canary
+                .iter_mut()
+                .zip(CANARY.iter())
+                .for_each(|(c, a)| *c = a.load(Ordering::Relaxed));
+
+            let freed = core::slice::from_raw_parts(ptr, layout.size());
+
+            if freed.windows(len).any(|w| w == &canary[..len]) {
+                HITS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CanaryAllocator = CanaryAllocator;
+
+/// Watch for buffers containing a secret being freed, for as long as the
+/// canary lives. Only one canary is watched at a time.
+struct Canary {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Canary {
+    fn watch(secret: &[u8]) -> Self {
+        assert!((1..=MAX_CANARY_LEN).contains(&secret.len()));
+
+        let lock = WATCH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        CANARY
+            .iter()
+            .zip(secret)
+            .for_each(|(a, b)| a.store(*b, Ordering::Relaxed));
+
+        HITS.store(0, Ordering::Relaxed);
+        CANARY_LEN.store(secret.len(), Ordering::Release);
+
+        Self { _lock: lock }
+    }
+
+    /// Number of buffers freed with the secret since the canary was created.
+    fn hits(&self) -> usize {
+        HITS.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Canary {
+    fn drop(&mut self) {
+        CANARY_LEN.store(0, Ordering::Release);
+    }
+}
+
+mod tests {
+    use alloc::vec::Vec;
+    use mls_rs_core::crypto::{CipherSuiteProvider, DefaultEpochSecretsVault};
+    use zeroize::Zeroizing;
+
+    use crate::{
+        client::test_utils::TEST_CIPHER_SUITE,
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{
+            key_schedule::{
+                get_pre_epoch_secret, kdf_derive_secret, kdf_expand_with_label, JoinerSecret,
+                KeySchedule, WelcomeSecret,
+            },
+            test_utils::get_test_group_context,
+        },
+        psk::secret::PskSecret,
+        tree_kem::{hpke_encryption::HpkeEncryptable, path_secret::PathSecret},
+    };
+
+    use super::Canary;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decrypted_path_secret_is_zeroized() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let (secret_key, public_key) = cs.kem_generate().await.unwrap();
+
+        let path_secret = PathSecret::random(&cs).unwrap();
+        let ciphertext = path_secret
+            .encrypt(&cs, &public_key, b"context")
+            .await
+            .unwrap();
+
+        let canary = Canary::watch(&path_secret);
+
+        let decrypted = PathSecret::decrypt(&cs, &secret_key, &public_key, b"context", &ciphertext)
+            .await
+            .unwrap();
+
+        assert_eq!(decrypted, path_secret);
+        drop(decrypted);
+
+        assert_eq!(canary.hits(), 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_schedule_seeds_are_zeroized() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let context = get_test_group_context(1, TEST_CIPHER_SUITE).await;
+        let psk_secret = PskSecret::new(&cs);
+
+        let init_secret = cs.random_bytes_vec(cs.kdf_extract_size()).unwrap();
+        let commit_secret = PathSecret::random(&cs).unwrap();
+        let key_schedule = KeySchedule::new(init_secret.clone().into());
+
+        let derive = || {
+            KeySchedule::from_key_schedule(
+                &key_schedule,
+                &commit_secret,
+                &context,
+                #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+                32,
+                &psk_secret,
+                &cs,
+                &DefaultEpochSecretsVault,
+            )
+        };
+
+        let joiner_secret = derive().await.unwrap().joiner_secret;
+
+        let joiner_seed = cs.kdf_extract(&init_secret, &commit_secret).await.unwrap();
+        let epoch_seed = get_pre_epoch_secret(&cs, &psk_secret, &joiner_secret)
+            .await
+            .unwrap();
+
+        for seed in [joiner_seed, epoch_seed] {
+            let canary = Canary::watch(&seed);
+            drop(derive().await.unwrap());
+            assert_eq!(canary.hits(), 0);
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_secrets_are_zeroized() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let psk_secret = PskSecret::new(&cs);
+
+        let joiner_secret = cs.random_bytes_vec(cs.kdf_extract_size()).unwrap();
+        let joiner_secret = JoinerSecret::from(Zeroizing::new(joiner_secret));
+
+        let epoch_seed = get_pre_epoch_secret(&cs, &psk_secret, &joiner_secret)
+            .await
+            .unwrap();
+
+        let welcome_secret = kdf_derive_secret(&cs, &epoch_seed, b"welcome")
+            .await
+            .unwrap();
+
+        let key_len = Some(cs.aead_key_size());
+        let key = kdf_expand_with_label(&cs, &welcome_secret, b"key", &[], key_len)
+            .await
+            .unwrap();
+
+        let nonce_len = Some(cs.aead_nonce_size());
+        let nonce = kdf_expand_with_label(&cs, &welcome_secret, b"nonce", &[], nonce_len)
+            .await
+            .unwrap();
+
+        for secret in [epoch_seed, welcome_secret, key, nonce] {
+            let canary = Canary::watch(&secret);
+
+            let welcome = WelcomeSecret::from_joiner_secret(&cs, &joiner_secret, &psk_secret)
+                .await
+                .unwrap();
+
+            let ciphertext = welcome.encrypt(b"group info").await.unwrap();
+            let plaintext: Vec<u8> = welcome.decrypt(&ciphertext).await.unwrap().to_vec();
+
+            assert_eq!(plaintext, b"group info");
+            drop(welcome);
+
+            assert_eq!(canary.hits(), 0);
+        }
+    }
+}